@@ -0,0 +1,201 @@
+//! Detection of notes sharing a first field.
+//!
+//! Anki identifies duplicate notes by their first field, so rows repeating a first field
+//! silently pile up as separate notes once a deck is imported. Keys are compared after
+//! stripping HTML tags and collapsing whitespace, and empty keys are never duplicates.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// What to do with notes whose first field repeats an earlier note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep every note and only report the duplicates.
+    #[default]
+    Warn,
+    /// Keep the first note of each duplicate group and drop the others.
+    Skip,
+    /// Fold the other notes of the same model into the first one, filling its empty fields
+    /// and appending differing values. Duplicates from other models are dropped.
+    Merge,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(Self::Warn),
+            "skip" => Ok(Self::Skip),
+            "merge" => Ok(Self::Merge),
+            _ => Err(format!(
+                "Unknown duplicate policy `{s}`, expected one of `warn`, `skip`, `merge`"
+            )),
+        }
+    }
+}
+
+impl Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Warn => "warn",
+            Self::Skip => "skip",
+            Self::Merge => "merge",
+        })
+    }
+}
+
+/// A note of a data file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteRef {
+    /// Name of the model the note belongs to.
+    pub model: String,
+    /// 1-based index of the note among the data rows of the model, excluding the header.
+    pub row: usize,
+}
+
+/// A group of notes sharing the same first field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The normalized first field shared by the notes.
+    pub key: String,
+    /// The notes in load order; the first one is kept by the skip and merge policies.
+    pub notes: Vec<NoteRef>,
+}
+
+impl Display for Duplicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let notes = self
+            .notes
+            .iter()
+            .map(|note| format!("{}#{}", note.model, note.row))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "`{}` appears in {notes}", self.key)
+    }
+}
+
+/// Normalizes a first field into the key duplicates are compared by.
+pub fn duplicate_key(field: &str) -> String {
+    let mut text = String::with_capacity(field.len());
+    let mut in_tag = false;
+    for c in field.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Finds the duplicate groups among the rows of several models, as `(model index, row index)`.
+fn duplicate_groups(models: &[(String, Vec<Vec<String>>)]) -> Vec<(String, Vec<(usize, usize)>)> {
+    let mut order = Vec::new();
+    let mut groups: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+    for (model, (_, rows)) in models.iter().enumerate() {
+        for (row, fields) in rows.iter().enumerate() {
+            let Some(first) = fields.first() else {
+                continue;
+            };
+            let key = duplicate_key(first);
+            if key.is_empty() {
+                continue;
+            }
+            let group = groups.entry(key.clone()).or_default();
+            if group.is_empty() {
+                order.push(key);
+            }
+            group.push((model, row));
+        }
+    }
+    order
+        .into_iter()
+        .filter_map(|key| {
+            let group = groups.remove(&key)?;
+            (group.len() > 1).then_some((key, group))
+        })
+        .collect()
+}
+
+/// Folds the fields of a duplicate row into the row it duplicates.
+fn merge_row(kept: &mut Vec<String>, other: &[String]) {
+    if kept.len() < other.len() {
+        kept.resize(other.len(), String::new());
+    }
+    for (field, value) in kept.iter_mut().zip(other).skip(1) {
+        if value.trim().is_empty() || field == value {
+            continue;
+        }
+        if field.trim().is_empty() {
+            field.clone_from(value);
+        } else {
+            field.push_str("<br>");
+            field.push_str(value);
+        }
+    }
+}
+
+/// Names the notes of duplicate groups found by [`duplicate_groups`].
+fn name_groups(
+    models: &[(String, Vec<Vec<String>>)],
+    groups: &[(String, Vec<(usize, usize)>)],
+) -> Vec<Duplicate> {
+    groups
+        .iter()
+        .map(|(key, group)| Duplicate {
+            key: key.clone(),
+            notes: group
+                .iter()
+                .map(|&(model, row)| NoteRef {
+                    model: models[model].0.clone(),
+                    row: row + 1,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Finds the notes sharing a first field across the rows of the given models.
+pub fn find_duplicates(models: &[(String, Vec<Vec<String>>)]) -> Vec<Duplicate> {
+    name_groups(models, &duplicate_groups(models))
+}
+
+/// Applies a duplicate policy to the rows of the given models in place.
+///
+/// Returns the duplicates found, before the policy was applied.
+pub fn resolve_duplicates(
+    models: &mut [(String, Vec<Vec<String>>)],
+    policy: DuplicatePolicy,
+) -> Vec<Duplicate> {
+    let groups = duplicate_groups(models);
+    let duplicates = name_groups(models, &groups);
+    if policy == DuplicatePolicy::Warn {
+        return duplicates;
+    }
+
+    let mut dropped = vec![Vec::new(); models.len()];
+    for (_, group) in groups {
+        let (kept_model, kept_row) = group[0];
+        for &(model, row) in &group[1..] {
+            if policy == DuplicatePolicy::Merge && model == kept_model {
+                let other = models[model].1[row].clone();
+                merge_row(&mut models[kept_model].1[kept_row], &other);
+            }
+            dropped[model].push(row);
+        }
+    }
+    for ((_, rows), dropped) in models.iter_mut().zip(dropped) {
+        let mut index = 0;
+        rows.retain(|_| {
+            index += 1;
+            !dropped.contains(&(index - 1))
+        });
+    }
+    duplicates
+}
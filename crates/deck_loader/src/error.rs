@@ -13,6 +13,16 @@ pub enum Error {
 
     #[error("Path error: {0}")]
     Path(#[from] std::path::StripPrefixError),
+
+    #[error("Media name `{0}` must be a file name without a path separator")]
+    InvalidMediaName(String),
+
+    #[error("Template `{template}` of model `{model}` requires unknown field `{field}`")]
+    UnknownField {
+        model: String,
+        template: String,
+        field: String,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
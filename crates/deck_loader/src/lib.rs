@@ -1,2 +1,4 @@
+pub mod duplicates;
 pub mod error;
 pub mod loader;
+pub mod stats;
@@ -1,4 +1,6 @@
-use crate::error::Result;
+use crate::duplicates::{Duplicate, DuplicatePolicy, find_duplicates, resolve_duplicates};
+use crate::error::{Error, Result};
+use crate::stats::{DeckStats, ModelStats};
 /// A better design could be implemented since a deck contains multiple models, each model contains multiple templates,
 /// and each template has front/back content and CSS. This can be perfectly represented using a directory structure.
 ///
@@ -17,7 +19,8 @@ use crate::error::Result;
 /// │   │   │   ├── word_to_meaning/
 /// │   │   │   │   ├── front.html
 /// │   │   │   │   ├── back.html
-/// │   │   │   │   └── style.css
+/// │   │   │   │   ├── style.css
+/// │   │   │   │   └── when.yaml   # Optional: Fields required to generate the card
 /// │   │   │   └── meaning_to_word/
 /// │   │   │       ├── front.html
 /// │   │   │       ├── back.html
@@ -34,8 +37,10 @@ use crate::error::Result;
 /// ```
 use genanki_rs_rev::{Deck, Field, Model, Note, PackageWriter, Template};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::fs::{self, File};
+use std::io;
 use std::path::{Path, PathBuf};
+use utils::cas::ContentStore;
 use walkdir::WalkDir;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,12 +57,37 @@ pub struct ModelConfig {
     fields: Vec<String>,
 }
 
+/// Conditions of a template, read from its `when.yaml`.
+///
+/// Anki only generates a card when its front renders non-empty, so guarding the front with
+/// `{{#Field}}...{{/Field}}` sections for every required field makes the template emit a card
+/// only for the notes where all of them are populated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateCondition {
+    /// Fields that must all be non-empty for the card to be generated
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+impl TemplateCondition {
+    /// Wraps the front of a template in a section guard per required field.
+    pub fn guard(&self, front_html: &str) -> String {
+        self.requires
+            .iter()
+            .rev()
+            .fold(front_html.to_string(), |html, field| {
+                format!("{{{{#{field}}}}}{html}{{{{/{field}}}}}")
+            })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TemplateConfig {
     name: String,
     front_html: String,
     back_html: String,
     style_css: String,
+    condition: TemplateCondition,
 }
 
 #[derive(Debug)]
@@ -69,6 +99,7 @@ pub struct ModelData {
 
 pub struct AnkiDeckLoader {
     project_path: PathBuf,
+    duplicate_policy: DuplicatePolicy,
 }
 
 pub mod constants {
@@ -81,6 +112,7 @@ pub mod constants {
     pub const TEMPLATE_FRONT: &str = "front.html";
     pub const TEMPLATE_BACK: &str = "back.html";
     pub const TEMPLATE_CSS: &str = "style.css";
+    pub const TEMPLATE_CONDITION: &str = "when.yaml";
 }
 use constants::*;
 impl AnkiDeckLoader {
@@ -158,10 +190,10 @@ impl AnkiDeckLoader {
     /// * `templates_path` - Path to the templates directory
     ///
     /// # Returns
-    /// * `Vec<TemplateConfig>` - Vector of loaded template configurations
-    fn load_templates(&self, templates_path: &Path) -> Vec<TemplateConfig> {
+    /// * `Result<Vec<TemplateConfig>>` - Vector of loaded template configurations or error message
+    fn load_templates(&self, templates_path: &Path) -> Result<Vec<TemplateConfig>> {
         if !templates_path.exists() {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         self.get_directory_entries(templates_path)
@@ -175,17 +207,50 @@ impl AnkiDeckLoader {
                     fs::read_to_string(template_path.join(TEMPLATE_BACK)).unwrap_or_default();
                 let style_css =
                     fs::read_to_string(template_path.join(TEMPLATE_CSS)).unwrap_or_default();
-
-                TemplateConfig {
+                let condition_path = template_path.join(TEMPLATE_CONDITION);
+                let condition = if condition_path.exists() {
+                    self.read_yaml(condition_path)?
+                } else {
+                    TemplateCondition::default()
+                };
+
+                Ok(TemplateConfig {
                     name: template_name,
                     front_html,
                     back_html,
                     style_css,
-                }
+                    condition,
+                })
             })
             .collect()
     }
 
+    /// Checks that the templates of a model only require fields of that model.
+    ///
+    /// # Arguments
+    /// * `model_name` - Name of the model
+    /// * `model_data` - Model data containing configuration and templates
+    ///
+    /// # Returns
+    /// * `Result<()>` - Success or the first unknown field
+    fn validate_conditions(&self, model_name: &str, model_data: &ModelData) -> Result<()> {
+        model_data.templates.iter().try_for_each(|template| {
+            match template
+                .condition
+                .requires
+                .iter()
+                .find(|field| !model_data.config.fields.contains(field))
+            {
+                Some(field) => Err(Error::UnknownField {
+                    model: model_name.to_string(),
+                    template: template.name.clone(),
+                    field: field.clone(),
+                }),
+                None => Ok(()),
+            }
+        })
+    }
+
     /// Creates a genanki Model from ModelData.
     ///
     /// # Arguments
@@ -207,7 +272,7 @@ impl AnkiDeckLoader {
             .iter()
             .map(|t| {
                 Template::new(&t.name)
-                    .qfmt(&t.front_html)
+                    .qfmt(&t.condition.guard(&t.front_html))
                     .afmt(&t.back_html)
             })
             .collect();
@@ -275,11 +340,27 @@ impl AnkiDeckLoader {
         Ok(all_media_files)
     }
 
+    /// Loads the CSV data of every model, paired with the model name.
+    ///
+    /// # Arguments
+    /// * `model_names` - Slice of model names to load CSV data for
+    ///
+    /// # Returns
+    /// * `Result<Vec<(String, Vec<Vec<String>>)>>` - CSV data per model or error message
+    fn load_all_csv_data(&self, model_names: &[String]) -> Result<Vec<(String, Vec<Vec<String>>)>> {
+        model_names
+            .iter()
+            .map(|model_name| Ok((model_name.clone(), self.load_csv_data(model_name)?)))
+            .collect()
+    }
+
     /// Builds a complete deck with all models and notes.
     ///
+    /// Duplicate notes are handled according to the loader's duplicate policy.
+    ///
     /// # Returns
-    /// * `Result<(Deck, Vec<String>), String>` - Tuple of deck and media files or error message
-    fn build_complete_deck(&self) -> Result<(Deck, Vec<PathBuf>)> {
+    /// * `Result<(Deck, Vec<PathBuf>, Vec<Duplicate>)>` - Tuple of deck, media files and duplicates found, or error message
+    fn build_complete_deck(&self) -> Result<(Deck, Vec<PathBuf>, Vec<Duplicate>)> {
         let deck_config = self.load_deck_config()?;
         let mut deck = Deck::new(
             deck_config.deck_id,
@@ -288,16 +369,20 @@ impl AnkiDeckLoader {
         );
         let model_names = self.get_available_models();
 
-        model_names.iter().try_for_each(|model_name| {
-            let model_data = self.load_model_data(model_name)?;
-            let csv_data = self.load_csv_data(model_name)?;
-            let model = self.create_genanki_model(model_name, &model_data);
-            self.add_notes_to_deck(&mut deck, model, csv_data);
-            Result::<()>::Ok(())
-        })?;
+        let mut all_csv_data = self.load_all_csv_data(&model_names)?;
+        let duplicates = resolve_duplicates(&mut all_csv_data, self.duplicate_policy);
+
+        all_csv_data
+            .into_iter()
+            .try_for_each(|(model_name, csv_data)| {
+                let model_data = self.load_model_data(&model_name)?;
+                let model = self.create_genanki_model(&model_name, &model_data);
+                self.add_notes_to_deck(&mut deck, model, csv_data);
+                Result::<()>::Ok(())
+            })?;
 
         let all_media_files = self.collect_all_media_files(&model_names)?;
-        Ok((deck, all_media_files))
+        Ok((deck, all_media_files, duplicates))
     }
 
     /// Writes deck or package to file.
@@ -318,15 +403,13 @@ impl AnkiDeckLoader {
         let mut w = PackageWriter::new();
 
         media_files.into_iter().try_for_each(|media_file| {
-            w.add_media(
-                media_file
-                    .as_ref()
-                    .strip_prefix(self.project_path.join(MEDIA_DIR))
-                    .unwrap()
-                    .to_str()
-                    .unwrap(),
-                media_file.as_ref(),
-            )
+            // Media of the deck and of its models alike are packaged under their file name.
+            let name = media_file
+                .as_ref()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            w.add_media(&*name, media_file.as_ref())
         })?;
         w.build(vec![deck])?.write_to_file(output_path)?;
 
@@ -380,7 +463,22 @@ impl AnkiDeckLoader {
     /// # Returns
     /// * `Result<Self, String>` - New instance or error message
     pub fn new(project_path: PathBuf) -> Self {
-        Self { project_path }
+        Self {
+            project_path,
+            duplicate_policy: DuplicatePolicy::default(),
+        }
+    }
+
+    /// Sets how notes sharing a first field are handled when building the deck.
+    ///
+    /// # Arguments
+    /// * `policy` - Duplicate policy, [`DuplicatePolicy::Warn`] by default
+    ///
+    /// # Returns
+    /// * `Self` - The loader using the given policy
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
     }
 
     /// Loads deck configuration from deck.yaml file.
@@ -408,25 +506,29 @@ impl AnkiDeckLoader {
 
         // Load templates
         let templates_path = model_path.join(TEMPLATE_DIR);
-        let templates = self.load_templates(&templates_path);
+        let templates = self.load_templates(&templates_path)?;
 
         // Load media files
         let media_files = self.collect_files_from_dir(model_path.join(MEDIA_DIR));
 
-        Ok(ModelData {
+        let model_data = ModelData {
             config,
             templates,
             media_files,
-        })
+        };
+        self.validate_conditions(model_name, &model_data)?;
+        Ok(model_data)
     }
 
     /// Gets list of available model names from the models directory.
     ///
     /// # Returns
-    /// * `Result<Vec<String>, String>` - Vector of model names or error message
+    /// * `Vec<String>` - Model names sorted alphabetically, so notes load in a stable order
     pub fn get_available_models(&self) -> Vec<String> {
         let models_path = self.project_path.join(MODELS_DIR);
-        self.collect_dir_names(&models_path)
+        let mut model_names = self.collect_dir_names(&models_path);
+        model_names.sort();
+        model_names
     }
 
     /// Loads CSV data for a specific model.
@@ -475,13 +577,118 @@ impl AnkiDeckLoader {
         Ok(())
     }
 
+    /// Adds a media file to the project, shared through a content-addressed store.
+    ///
+    /// The file is stored in `store` and linked from there into the media directory of the
+    /// model, or of the deck if no model is given, so the same image or sound added to several
+    /// decks takes up space once. Media being packaged by file name, the name must not contain
+    /// a path separator.
+    ///
+    /// # Arguments
+    /// * `store` - Store the media is kept in
+    /// * `source` - Path of the media file to add
+    /// * `model_name` - Model whose media directory receives the file, the deck's if `None`
+    /// * `name` - File name of the media in the project, that of `source` if `None`
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - Path of the media in the project or error message, an error if a
+    ///   different file of that name is already there
+    pub fn add_media(
+        &self,
+        store: &ContentStore,
+        source: &Path,
+        model_name: Option<&str>,
+        name: Option<&str>,
+    ) -> Result<PathBuf> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => source
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        if Path::new(&name)
+            .file_name()
+            .is_none_or(|file_name| file_name != name.as_str())
+        {
+            return Err(Error::InvalidMediaName(name));
+        }
+        let media_dir = match model_name {
+            Some(model_name) => self.project_path.join(MODELS_DIR).join(model_name),
+            None => self.project_path.clone(),
+        }
+        .join(MEDIA_DIR);
+        fs::create_dir_all(&media_dir)?;
+
+        let hash = store.put_file(source)?;
+        let target_path = media_dir.join(&name);
+        if target_path.exists() {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_reader(File::open(&target_path)?)?;
+            if hasher.finalize().to_hex().as_str() == hash {
+                return Ok(target_path);
+            }
+            return Err(Error::IO(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("A different media file `{}` exists", target_path.display()),
+            )));
+        }
+        store.link_to(&hash, &target_path)?;
+        Ok(target_path)
+    }
+
+    /// Finds the notes sharing a first field, within and across models.
+    ///
+    /// # Returns
+    /// * `Result<Vec<Duplicate>>` - Duplicate groups in load order or error message
+    pub fn find_duplicates(&self) -> Result<Vec<Duplicate>> {
+        let all_csv_data = self.load_all_csv_data(&self.get_available_models())?;
+        Ok(find_duplicates(&all_csv_data))
+    }
+
+    /// Computes the statistics of the deck: notes, field fill rates and lengths, template
+    /// coverage and media sizes, per model and in total.
+    ///
+    /// Notes are counted as loaded, before duplicates are handled.
+    ///
+    /// # Returns
+    /// * `Result<DeckStats>` - Statistics of the deck or error message
+    pub fn stats(&self) -> Result<DeckStats> {
+        let models = self
+            .get_available_models()
+            .iter()
+            .map(|model_name| {
+                let model_data = self.load_model_data(model_name)?;
+                let notes: Vec<Vec<String>> = self
+                    .load_csv_data(model_name)?
+                    .into_iter()
+                    .filter(|row| !row.is_empty())
+                    .collect();
+                let fronts: Vec<(String, String)> = model_data
+                    .templates
+                    .iter()
+                    .map(|t| (t.name.clone(), t.condition.guard(&t.front_html)))
+                    .collect();
+                Ok(ModelStats::collect(
+                    model_name,
+                    &model_data.config.fields,
+                    &fronts,
+                    &notes,
+                    &model_data.media_files,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let global_media = self.collect_files_from_dir(self.project_path.join(MEDIA_DIR));
+        Ok(DeckStats::collect(models, &global_media))
+    }
+
     /// Builds the deck (validation only, does not export).
     ///
     /// # Returns
-    /// * `Result<()>` - Success or error message
-    pub fn build_deck(&self) -> Result<()> {
-        let (_deck, _media_files) = self.build_complete_deck()?;
-        Ok(())
+    /// * `Result<Vec<Duplicate>>` - Duplicates found or error message
+    pub fn build_deck(&self) -> Result<Vec<Duplicate>> {
+        let (_deck, _media_files, duplicates) = self.build_complete_deck()?;
+        Ok(duplicates)
     }
 
     /// Exports the complete deck to an .apkg file.
@@ -490,10 +697,11 @@ impl AnkiDeckLoader {
     /// * `output_path` - Path where the .apkg file will be saved
     ///
     /// # Returns
-    /// * `Result<()>` - Success or error message
-    pub fn export_deck<P: AsRef<Path>>(&self, output_path: P) -> Result<()> {
-        let (deck, media_files) = self.build_complete_deck()?;
-        self.write_deck_to_file(deck, media_files, output_path.as_ref().to_path_buf())
+    /// * `Result<Vec<Duplicate>>` - Duplicates found or error message
+    pub fn export_deck<P: AsRef<Path>>(&self, output_path: P) -> Result<Vec<Duplicate>> {
+        let (deck, media_files, duplicates) = self.build_complete_deck()?;
+        self.write_deck_to_file(deck, media_files, output_path.as_ref().to_path_buf())?;
+        Ok(duplicates)
     }
 
     /// Creates a new Anki deck project template with sample files.
@@ -516,9 +724,7 @@ impl AnkiDeckLoader {
         model_name: Option<String>,
         fields: Option<Vec<String>>,
     ) -> Result<()> {
-        let loader = Self {
-            project_path: project_path.clone(),
-        };
+        let loader = Self::new(project_path.clone());
 
         // Create directory structure
         loader.create_dir_structure(&project_path)?;
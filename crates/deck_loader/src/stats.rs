@@ -0,0 +1,205 @@
+//! Statistics of the content of a deck project.
+//!
+//! Deck authors check them before exporting generated content: how many notes each model
+//! has, how often each field is filled and how long it is, how much media the deck carries,
+//! and for how many notes each template generates a card.
+
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// How a field of a model is filled across its notes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldStats {
+    /// Name of the field.
+    pub name: String,
+    /// Number of notes where the field is not blank.
+    pub filled: usize,
+    /// Share of the notes where the field is not blank, 0.0 for a model without notes.
+    pub fill_rate: f64,
+    /// Average length in characters of the field over the notes where it is not blank.
+    pub average_length: f64,
+}
+
+/// How many notes a template of a model generates a card for.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateStats {
+    /// Name of the template.
+    pub name: String,
+    /// Number of notes the template generates a card for.
+    pub cards: usize,
+    /// Share of the notes the template generates a card for, 0.0 for a model without notes.
+    pub coverage: f64,
+}
+
+/// Statistics of a model and its notes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelStats {
+    /// Name of the model.
+    pub name: String,
+    /// Number of notes of the model.
+    pub notes: usize,
+    /// The fields, in the order of the model.
+    pub fields: Vec<FieldStats>,
+    /// The templates, in the order they are loaded.
+    pub templates: Vec<TemplateStats>,
+    /// Number of media files of the model.
+    pub media_files: usize,
+    /// Total size in bytes of the media files of the model.
+    pub media_bytes: u64,
+}
+
+/// Statistics of a deck project.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeckStats {
+    /// Number of notes of all models.
+    pub notes: usize,
+    /// Number of cards all templates generate.
+    pub cards: usize,
+    /// The models, sorted by name.
+    pub models: Vec<ModelStats>,
+    /// Number of media files, of the models and global ones.
+    pub media_files: usize,
+    /// Total size in bytes of the media files, of the models and global ones.
+    pub media_bytes: u64,
+}
+
+fn share(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Sums the sizes of media files, counting unreadable files as empty.
+pub(crate) fn media_size(files: &[PathBuf]) -> u64 {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).map(|meta| meta.len()).unwrap_or(0))
+        .sum()
+}
+
+/// Whether Anki generates a card of a template for a note.
+///
+/// Anki generates a card when the front of the template references at least one non-empty
+/// field outside of the sections hiding it. Sections `{{#Field}}` are shown when the field
+/// is not blank and inverted sections `{{^Field}}` when it is, filters such as
+/// `{{text:Field}}` refer to the field after the last colon, and unknown fields are blank.
+pub fn generates_card(front: &str, fields: &[String], note: &[String]) -> bool {
+    let filled = |name: &str| {
+        fields
+            .iter()
+            .position(|field| field == name)
+            .and_then(|i| note.get(i))
+            .is_some_and(|value| !value.trim().is_empty())
+    };
+
+    // Whether each enclosing section is shown.
+    let mut sections: Vec<bool> = Vec::new();
+    let mut rest = front;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let tag = rest[start + 2..start + 2 + end].trim();
+        rest = &rest[start + 2 + end + 2..];
+        if let Some(name) = tag.strip_prefix('#') {
+            sections.push(filled(name.trim()));
+        } else if let Some(name) = tag.strip_prefix('^') {
+            sections.push(!filled(name.trim()));
+        } else if tag.starts_with('/') {
+            sections.pop();
+        } else if !tag.starts_with('!') && sections.iter().all(|&shown| shown) {
+            let name = tag.rsplit(':').next().unwrap_or(tag).trim();
+            if filled(name) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+impl ModelStats {
+    /// Computes the statistics of a model.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the model
+    /// * `fields` - Names of the fields of the model
+    /// * `fronts` - Name and front of each template, with the conditions of the template applied
+    /// * `notes` - Field values of each note
+    /// * `media` - Paths of the media files of the model
+    pub fn collect(
+        name: &str,
+        fields: &[String],
+        fronts: &[(String, String)],
+        notes: &[Vec<String>],
+        media: &[PathBuf],
+    ) -> Self {
+        let fields_stats = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let lengths: Vec<usize> = notes
+                    .iter()
+                    .filter_map(|note| note.get(i))
+                    .filter(|value| !value.trim().is_empty())
+                    .map(|value| value.chars().count())
+                    .collect();
+                FieldStats {
+                    name: field.clone(),
+                    filled: lengths.len(),
+                    fill_rate: share(lengths.len(), notes.len()),
+                    average_length: if lengths.is_empty() {
+                        0.0
+                    } else {
+                        lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+                    },
+                }
+            })
+            .collect();
+
+        let templates = fronts
+            .iter()
+            .map(|(template, front)| {
+                let cards = notes
+                    .iter()
+                    .filter(|note| generates_card(front, fields, note))
+                    .count();
+                TemplateStats {
+                    name: template.clone(),
+                    cards,
+                    coverage: share(cards, notes.len()),
+                }
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            notes: notes.len(),
+            fields: fields_stats,
+            templates,
+            media_files: media.len(),
+            media_bytes: media_size(media),
+        }
+    }
+}
+
+impl DeckStats {
+    /// Gathers the statistics of the models of a deck and its global media files.
+    pub fn collect(models: Vec<ModelStats>, global_media: &[PathBuf]) -> Self {
+        Self {
+            notes: models.iter().map(|model| model.notes).sum(),
+            cards: models
+                .iter()
+                .flat_map(|model| &model.templates)
+                .map(|template| template.cards)
+                .sum(),
+            media_files: models.iter().map(|model| model.media_files).sum::<usize>()
+                + global_media.len(),
+            media_bytes: models.iter().map(|model| model.media_bytes).sum::<u64>()
+                + media_size(global_media),
+            models,
+        }
+    }
+}
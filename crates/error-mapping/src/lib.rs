@@ -122,6 +122,12 @@ impl_as_pyerr!(strum::ParseError, PyValueError);
 #[cfg(feature = "rho-hashline")]
 impl_as_pyerr!(rho_hashline::HashlineError, PyRuntimeError);
 
+#[cfg(feature = "rusqlite")]
+impl_as_pyerr!(rusqlite::Error, PyOSError);
+
+#[cfg(feature = "notify")]
+impl_as_pyerr!(notify::Error, PyOSError);
+
 cfg_if!(
 if #[cfg(feature = "pyo3_cast")]
 {
@@ -1,5 +1,5 @@
 use crate::configs::Config;
-use dotenvy::dotenv_override;
+use crate::dotenv::load_dotenv;
 use fabricatio_constants::{CONFIG_FILE, GLOBAL_CONFIG_FILE, NAME};
 use figment::providers::{Data, Env, Format, Toml};
 use figment::value::{Dict, Map};
@@ -14,7 +14,7 @@ impl Config {
     fn figment() -> Figment {
         Figment::new()
             .join({
-                let _ = dotenv_override();
+                load_dotenv();
                 Env::prefixed(format!("{}_", NAME.to_uppercase()).as_str()).split("__")
             })
             .join(Toml::file(CONFIG_FILE))
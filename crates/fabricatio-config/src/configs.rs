@@ -2,7 +2,7 @@ use fabricatio_constants::{TEMPLATES, TEMPLATES_DIRNAME, agent_variant};
 use macro_utils::TemplateDefault;
 use pyo3::prelude::*;
 
-use crate::secstr::SecretStr;
+use crate::secstr::{ROTATE_TIMEOUT, SecretStr};
 use pyo3_stub_gen::derive::*;
 use pythonize::pythonize;
 
@@ -14,6 +14,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::Not;
 use std::path::PathBuf;
+use std::time::Duration;
 use thryd::tracker::Quota;
 use thryd::{DeploymentIdentifier, ProviderName, ProviderType, RouteGroupName};
 use validator::Validate;
@@ -83,6 +84,38 @@ pub struct DebugConfig {
 
     pub log_dir: Option<PathBuf>,
     pub rotation: Option<String>,
+
+    /// Pattern naming a log file per record below `log_dir`, e.g. `{date}/{task}.log`.
+    ///
+    /// Placeholders are `{date}`, `{hour}`, `{pid}`, `{level}` and the fields of the record,
+    /// such as those set with `logger.context(...)`. Records lacking a field go to the merged
+    /// log file, and the console keeps the merged stream. `rotation` is ignored if set.
+    pub log_file_pattern: Option<String>,
+
+    /// Whether to collapse consecutive identical records into a repetition notice.
+    pub suppress_duplicates: bool,
+
+    /// Maximum number of records written per second, unlimited if unset.
+    #[validate(range(min = 1, message = "max_records_per_second must be at least 1 if set"))]
+    pub max_records_per_second: Option<u32>,
+
+    /// Loguru-style format of the records, the built-in one if unset.
+    ///
+    /// Fields are `{time}`, `{level}`, `{name}`, `{message}` and `{process}`, with specs such
+    /// as `{time:HH:mm:ss}` or `{level: <8}`, and colors are tags such as `<green>...</green>`
+    /// or `<level>...</level>` for the color of the level.
+    pub log_format: Option<String>,
+
+    /// Colors of the levels keyed by level name, e.g. `{ WARN = "light-yellow bold" }`.
+    ///
+    /// Levels left out keep their default color.
+    pub level_colors: HashMap<String, String>,
+
+    /// Whether to write each record on a single line, with line breaks escaped as `\n`.
+    ///
+    /// Otherwise the fields of the records wrap at the width of the terminal. Either way,
+    /// records carry no escape codes when stderr is not a terminal or `NO_COLOR` is set.
+    pub compact: bool,
 }
 impl Default for DebugConfig {
     fn default() -> Self {
@@ -90,6 +123,12 @@ impl Default for DebugConfig {
             log_level: "INFO".to_string(),
             log_dir: None,
             rotation: None,
+            log_file_pattern: None,
+            suppress_duplicates: false,
+            max_records_per_second: None,
+            log_format: None,
+            level_colors: HashMap::new(),
+            compact: false,
         }
     }
 }
@@ -106,6 +145,16 @@ pub struct TemplateManagerConfig {
 
     /// The suffix of the templates.
     pub template_suffix: String,
+
+    /// The environment variables templates may read with the `env` helper.
+    pub env_allowlist: Vec<String>,
+
+    /// The configuration keys templates may read with the `config` helper, e.g. `llm.model`.
+    pub config_allowlist: Vec<String>,
+
+    /// Whether rendering a list of data fails if any item fails, instead of skipping the
+    /// failing items and logging them.
+    pub strict: bool,
 }
 
 impl Default for TemplateManagerConfig {
@@ -114,6 +163,9 @@ impl Default for TemplateManagerConfig {
             template_stores: vec![PathBuf::from(TEMPLATES_DIRNAME), TEMPLATES.clone()],
             active_loading: false,
             template_suffix: "hbs".to_string(),
+            env_allowlist: Vec::new(),
+            config_allowlist: Vec::new(),
+            strict: true,
         }
     }
 }
@@ -276,16 +328,47 @@ impl Default for GeneralConfig {
 pub struct EmitterConfig {
     /// The delimiter used to separate the event name into segments
     pub delimiter: String,
+
+    /// Number of events a subscription of the event bus holds before its overflow policy applies
+    pub queue_capacity: usize,
+
+    /// What a full subscription of the event bus does with a new event: `drop_oldest` to make
+    /// room by dropping the oldest one, or `block` to make the publisher wait for room
+    pub overflow: String,
+
+    /// The JSON Lines file every emitted event is appended to, no journal is kept if None
+    pub journal: Option<PathBuf>,
 }
 
 impl Default for EmitterConfig {
     fn default() -> Self {
         EmitterConfig {
             delimiter: "::".to_string(),
+            queue_capacity: 256,
+            overflow: "drop_oldest".to_string(),
+            journal: None,
         }
     }
 }
 
+/// A secret of the configuration about to expire, as reported by `Config.check_secrets`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(skip_from_py_object, get_all)]
+pub struct SecretReport {
+    /// Where the secret sits in the configuration, e.g. `routing.providers[0].key`.
+    pub path: String,
+
+    /// The unix timestamp the secret expires at, after any rotation.
+    pub expires_at: Option<i64>,
+
+    /// Whether the secret was refreshed by its rotation command.
+    pub rotated: bool,
+
+    /// Why the rotation failed, if it was attempted and failed.
+    pub error: Option<String>,
+}
+
 /// Configuration structure containing all system components.
 #[derive(Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
@@ -333,6 +416,23 @@ pub struct Config {
     pub ext: HashMap<String, Value>,
 }
 
+impl Config {
+    /// Lists the secrets of the configuration along with their path.
+    pub fn secrets(&self) -> Vec<(String, &SecretStr)> {
+        self.routing
+            .providers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, provider)| {
+                provider
+                    .key
+                    .as_ref()
+                    .map(|key| (format!("routing.providers[{i}].key"), key))
+            })
+            .collect()
+    }
+}
+
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
 #[cfg_attr(not(feature = "stubgen"), remove_gen_stub)]
 #[pymethods]
@@ -374,6 +474,38 @@ impl Config {
         Ok(())
     }
 
+    /// Report the secrets expiring within the given number of seconds, or already expired.
+    ///
+    /// Secrets get an expiry and a rotation command when configured as a table, e.g.
+    /// `key = { value = "sk-...", expires_at = 1767225600, rotate_cmd = "vault read ..." }`.
+    /// With `rotate`, the secrets having a rotation command are refreshed in place; providers
+    /// already registered in a router keep their key until re-registered, see
+    /// `Router.reload_providers`. A failed rotation is reported instead of raised, so one
+    /// broken command does not keep the other secrets from being refreshed, and a command
+    /// running for longer than 30 seconds is killed. The GIL is released while the commands
+    /// run.
+    #[pyo3(signature = (within = 300, rotate = false))]
+    fn check_secrets(&self, python: Python, within: i64, rotate: bool) -> Vec<SecretReport> {
+        let timeout = Duration::from_secs_f64(ROTATE_TIMEOUT);
+        let secrets = self.secrets();
+        python.detach(|| {
+            secrets
+                .into_iter()
+                .filter(|(_, secret)| secret.expires_within(within))
+                .map(|(path, secret)| {
+                    let result = (rotate && secret.rotate_cmd().is_some())
+                        .then(|| secret.rotate_blocking(timeout));
+                    SecretReport {
+                        path,
+                        expires_at: secret.expires_at(),
+                        rotated: matches!(result, Some(Ok(()))),
+                        error: result.and_then(Result::err).map(|e| e.to_string()),
+                    }
+                })
+                .collect()
+        })
+    }
+
     /// Load configuration data for a given section name and instantiate a Python class
     ///
     /// This method performs configuration loading with the following behavior:
@@ -0,0 +1,92 @@
+//! Discovery of the `.env` files loaded into the environment before the configuration.
+//!
+//! If the variable [`ENV_FILE_VARNAME`] names a file, only that file is loaded. Otherwise the
+//! nearest directory holding a `.env` or a `.env.local` file is looked for, from the working
+//! directory upward, and both files are loaded from it, `.env.local` last so that its values
+//! win. Values of the files override those already in the environment. Missing files are not
+//! an error, as running without any `.env` file is common.
+
+use fabricatio_constants::ENV_FILE_VARNAME;
+use std::env;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// The name of the file holding the variables of a project.
+const ENV_FILE: &str = ".env";
+/// The name of the file holding the local overrides of the variables of a project, usually
+/// left out of version control.
+const ENV_LOCAL_FILE: &str = ".env.local";
+
+/// Returns the `.env` files of the nearest directory holding any, from `start` upward, in the
+/// order to load them.
+fn discover(start: &Path) -> Vec<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| {
+            [ENV_FILE, ENV_LOCAL_FILE]
+                .into_iter()
+                .map(|name| dir.join(name))
+                .filter(|path| path.is_file())
+                .collect::<Vec<_>>()
+        })
+        .find(|files| !files.is_empty())
+        .unwrap_or_default()
+}
+
+/// Loads the `.env` files into the environment, overriding the variables already set.
+///
+/// Returns:
+///     The files loaded, in the order they were loaded.
+pub(crate) fn load_dotenv() -> Vec<PathBuf> {
+    let files = match env::var_os(ENV_FILE_VARNAME) {
+        Some(pointer) => vec![PathBuf::from(pointer)],
+        None => match env::current_dir() {
+            Ok(cwd) => discover(&cwd),
+            Err(e) => {
+                debug!("Cannot read the working directory, skipping .env discovery: {e}");
+                Vec::new()
+            }
+        },
+    };
+    if files.is_empty() {
+        debug!("No .env file found, using the environment as is");
+    }
+    files
+        .into_iter()
+        .filter(|file| match dotenvy::from_path_override(file) {
+            Ok(()) => {
+                debug!("Loaded environment variables from {}", file.display());
+                true
+            }
+            Err(e) => {
+                debug!("Skipping env file {}: {e}", file.display());
+                false
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_discover_nearest() {
+        let root = env::temp_dir().join(format!("dotenv-discover-{}", std::process::id()));
+        let nested = root.join("project").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(ENV_FILE), "A=1").unwrap();
+        assert_eq!(discover(&nested), vec![root.join(ENV_FILE)]);
+
+        let project = root.join("project");
+        fs::write(project.join(ENV_LOCAL_FILE), "A=2").unwrap();
+        assert_eq!(discover(&nested), vec![project.join(ENV_LOCAL_FILE)]);
+        fs::write(project.join(ENV_FILE), "A=3").unwrap();
+        assert_eq!(
+            discover(&nested),
+            vec![project.join(ENV_FILE), project.join(ENV_LOCAL_FILE)]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -31,6 +31,7 @@
 
 mod config_loader;
 mod configs;
+mod dotenv;
 mod secstr;
 
 pub use crate::configs::*;
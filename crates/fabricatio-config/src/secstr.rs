@@ -1,16 +1,48 @@
-use pyo3::{pyclass, pymethods};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::{PyResult, Python, pyclass, pymethods};
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
-use serde::de::Visitor;
+use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The keys of a secret given as a table.
+const SECRET_FIELDS: &[&str] = &["value", "expires_at", "rotate_cmd"];
+
+/// How long a rotation command may run before it is killed, in seconds.
+pub(crate) const ROTATE_TIMEOUT: f64 = 30.0;
+
+/// How often a running rotation command is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The current value of a secret and its expiry, shared by every clone of a `SecretStr`
+/// so that a rotation reaches all of them.
+struct SecretState {
+    value: String,
+    expires_at: Option<i64>,
+}
+
+/// The output of a rotation command printing JSON instead of the bare secret.
+#[derive(Deserialize)]
+struct RotatedSecret {
+    value: String,
+    expires_at: Option<i64>,
+}
 
 #[derive(Clone)]
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
 #[pyclass(from_py_object)]
 pub struct SecretStr {
-    source: String,
+    state: Arc<RwLock<SecretState>>,
+    /// Shell command printing a fresh value of the secret, if it can be rotated.
+    rotate_cmd: Option<String>,
 }
 
 struct SecStrVisitor;
@@ -19,28 +51,47 @@ impl<'de> Visitor<'de> for SecStrVisitor {
     type Value = SecretStr;
 
     fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-        formatter.write_str("A string containing a secret value.")
+        formatter.write_str(
+            "A string containing a secret value, or a table with `value`, `expires_at` and `rotate_cmd`.",
+        )
     }
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
-        E: serde::de::Error,
+        E: de::Error,
     {
         Ok(v.into())
     }
 
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
     where
-        E: serde::de::Error,
+        E: de::Error,
     {
         Ok(v.into())
     }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut value = None;
+        let mut expires_at = None;
+        let mut rotate_cmd = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "value" => value = Some(map.next_value::<String>()?),
+                "expires_at" => expires_at = Some(map.next_value::<i64>()?),
+                "rotate_cmd" => rotate_cmd = Some(map.next_value::<String>()?),
+                other => return Err(de::Error::unknown_field(other, SECRET_FIELDS)),
+            }
+        }
+        let value = value.ok_or_else(|| de::Error::missing_field("value"))?;
+        Ok(SecretStr::new(&value, expires_at, rotate_cmd))
+    }
 }
 
 impl<S: AsRef<str>> From<S> for SecretStr {
     fn from(source: S) -> Self {
-        Self {
-            source: source.as_ref().to_string(),
-        }
+        Self::new(source.as_ref(), None, None)
     }
 }
 
@@ -49,7 +100,7 @@ impl<'de> Deserialize<'de> for SecretStr {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_string(SecStrVisitor)
+        deserializer.deserialize_any(SecStrVisitor)
     }
 }
 
@@ -62,15 +113,201 @@ impl Serialize for SecretStr {
     }
 }
 
+/// The current unix timestamp.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Output of a child read on a thread of its own, so that a command printing more than a
+/// pipe holds never blocks on it.
+struct PipeReader {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    done: Receiver<()>,
+}
+
+impl PipeReader {
+    fn spawn<R: Read + Send + 'static>(pipe: Option<R>) -> Self {
+        let bytes = Arc::new(Mutex::new(Vec::new()));
+        let (sender, done) = mpsc::channel();
+        let sink = bytes.clone();
+        thread::spawn(move || {
+            if let Some(mut pipe) = pipe {
+                let mut chunk = [0; 8192];
+                while let Ok(read @ 1..) = pipe.read(&mut chunk) {
+                    sink.lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .extend_from_slice(&chunk[..read]);
+                }
+            }
+            let _ = sender.send(());
+        });
+        Self { bytes, done }
+    }
+
+    /// Waits until the end of the output or the deadline, returning what was read by then.
+    ///
+    /// Processes a command leaves running in the background may hold its pipes open past its
+    /// exit.
+    fn collect(self, deadline: Instant) -> Vec<u8> {
+        let _ = self
+            .done
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        std::mem::take(&mut self.bytes.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Runs a command through the shell of the platform, returning its standard output.
+///
+/// The command is killed if it runs for longer than `timeout`.
+fn run_shell(cmd: &str, timeout: Duration) -> PyResult<String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", cmd]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd]);
+        command
+    };
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to run the rotation command: {e}")))?;
+    let stdout = PipeReader::spawn(child.stdout.take());
+    let stderr = PipeReader::spawn(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PyRuntimeError::new_err(format!(
+                    "The rotation command timed out after {}s",
+                    timeout.as_secs_f64()
+                )));
+            }
+            Err(e) => {
+                return Err(PyRuntimeError::new_err(format!(
+                    "Failed to wait for the rotation command: {e}"
+                )));
+            }
+        }
+    };
+    let (stdout, stderr) = (stdout.collect(deadline), stderr.collect(deadline));
+    if !status.success() {
+        return Err(PyRuntimeError::new_err(format!(
+            "The rotation command exited with {}: {}",
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        )));
+    }
+    String::from_utf8(stdout).map_err(|_| {
+        PyRuntimeError::new_err("The rotation command printed a secret that is not valid UTF-8")
+    })
+}
+
+impl SecretStr {
+    /// Seconds left before the secret expires, negative once it has, if it has an expiry.
+    pub fn expires_in(&self) -> Option<i64> {
+        self.expires_at().map(|expires_at| expires_at - now())
+    }
+
+    /// Replaces the secret with the output of its rotation command, killing the command if it
+    /// runs for longer than `timeout`. Blocks until the command is done, see `rotate`.
+    pub fn rotate_blocking(&self, timeout: Duration) -> PyResult<()> {
+        let cmd = self
+            .rotate_cmd
+            .as_deref()
+            .ok_or_else(|| PyValueError::new_err("The secret has no rotation command"))?;
+        let output = run_shell(cmd, timeout)?;
+        let output = output.trim();
+        let rotated = match serde_json::from_str::<RotatedSecret>(output) {
+            Ok(rotated) => rotated,
+            Err(_) => RotatedSecret {
+                value: output.to_string(),
+                expires_at: None,
+            },
+        };
+        if rotated.value.is_empty() {
+            return Err(PyRuntimeError::new_err(
+                "The rotation command printed an empty secret",
+            ));
+        }
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.value = rotated.value;
+        state.expires_at = rotated.expires_at;
+        Ok(())
+    }
+}
+
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
 #[pymethods]
 impl SecretStr {
+    /// Creates a secret, optionally expiring at a unix timestamp and rotated by a shell command.
     #[new]
-    pub fn new(source: &str) -> Self {
-        source.into()
+    #[pyo3(signature = (source, expires_at = None, rotate_cmd = None))]
+    pub fn new(source: &str, expires_at: Option<i64>, rotate_cmd: Option<String>) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(SecretState {
+                value: source.to_string(),
+                expires_at,
+            })),
+            rotate_cmd,
+        }
+    }
+
+    pub fn get_secret_value(&self) -> String {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .value
+            .clone()
+    }
+
+    /// The unix timestamp after which the secret is no longer valid, if known.
+    #[getter]
+    pub fn expires_at(&self) -> Option<i64> {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .expires_at
     }
-    pub fn get_secret_value(&self) -> &str {
-        self.source.as_str()
+
+    /// The shell command printing a fresh value of the secret, if any.
+    #[getter]
+    pub fn rotate_cmd(&self) -> Option<&str> {
+        self.rotate_cmd.as_deref()
+    }
+
+    /// Whether the secret expires within the given number of seconds, or already has.
+    pub fn expires_within(&self, seconds: i64) -> bool {
+        self.expires_in().is_some_and(|left| left <= seconds)
+    }
+
+    /// Replaces the secret with the output of its rotation command.
+    ///
+    /// The command may print the bare secret, which then has no expiry, or a JSON object with
+    /// `value` and optionally `expires_at`. Every clone of the secret sees the new value. The
+    /// GIL is released while the command runs.
+    ///
+    /// Args:
+    ///     timeout: Seconds the command may run for before it is killed. Defaults to 30.
+    ///
+    /// Raises:
+    ///     ValueError: If the secret has no rotation command or the timeout is invalid.
+    ///     RuntimeError: If the command fails, times out or prints an empty secret.
+    #[pyo3(signature = (timeout = ROTATE_TIMEOUT))]
+    pub fn rotate(&self, python: Python, timeout: f64) -> PyResult<()> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .map_err(|_| PyValueError::new_err(format!("Invalid timeout {timeout}")))?;
+        python.detach(|| self.rotate_blocking(timeout))
     }
 
     fn __str__(&self) -> &str {
@@ -78,6 +78,10 @@ pub static ROAMING: Lazy<PathBuf> =
 pub const TEMPLATES_DIRNAME: &str = "templates";
 /// A global static instance of the templates directory located within the roaming configuration directory.
 pub static TEMPLATES: Lazy<PathBuf> = Lazy::new(|| ROAMING.join(TEMPLATES_DIRNAME));
+/// The name of the content-addressed store's directory.
+pub const CAS_DIRNAME: &str = "cas";
+/// A global static instance of the content-addressed store directory, shared by all packages, located within the roaming configuration directory.
+pub static CAS: Lazy<PathBuf> = Lazy::new(|| ROAMING.join(CAS_DIRNAME));
 /// A global static instance of the global configuration file path, constructed by joining
 /// the roaming directory with the application-specific configuration file name.
 pub static GLOBAL_CONFIG_FILE: Lazy<PathBuf> = Lazy::new(|| ROAMING.join(CONFIG_FILE));
@@ -86,6 +90,8 @@ pub static GLOBAL_CONFIG_FILE: Lazy<PathBuf> = Lazy::new(|| ROAMING.join(CONFIG_
 pub const LOGGER_VARNAME: &str = "logger";
 /// The name of the configuration variable used by the application.
 pub const CONFIG_VARNAME: &str = "CONFIG";
+/// The environment variable naming the `.env` file to load, instead of discovering one from the working directory.
+pub const ENV_FILE_VARNAME: &str = "FABRIK_ENV_FILE";
 /// The key used to store the Python source code path.
 pub const PY_SOURCE_KEY: &str = "py_source";
 
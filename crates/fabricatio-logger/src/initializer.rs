@@ -12,15 +12,22 @@
 //! - Custom log levels (SUCCESS/CRITICAL) through metadata filtering
 //! - Thread-safe initialization and global logger management
 //! - Precise timestamps using chrono's local timezone
+//! - Duplicate suppression and per-second rate limiting of records
+//! - Loguru-style format strings and per-level colors, see [`Style`]
+//! - Plain records for pipes and files, structured fields wrapped at the width of terminals
+//! - Log files partitioned by runtime fields of the records, e.g. one file per task
 //!
 //! # Usage
 //!
 //! ## Basic Rust Initialization
 //! ```rust
-//! use fabricatio_logger::{init_logger, init_logger_auto};
+//! use fabricatio_logger::{LoggerOptions, Throttle, init_logger, init_logger_auto};
 //!
 //! // Manual initialization with specified level
-//! init_logger("debug",None,None)?;
+//! init_logger(LoggerOptions::new("debug").with_throttle(Throttle {
+//!     suppress_duplicates: true,
+//!     max_records_per_second: Some(200),
+//! }));
 //!
 //! // Or automatic configuration from Python settings
 //! init_logger_auto().expect("Failed to initialize logger from Python config");
@@ -43,10 +50,17 @@ use fabricatio_constants::CORE_PACKAGE_NAME;
 use pyo3::prelude::*;
 use std::io;
 use std::path::PathBuf;
+use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{EnvFilter, fmt};
 
+use crate::partition::{FilePattern, Partitioned, PartitionedWriter};
 use crate::renderer::MyFormatter;
+use crate::style::Style;
+use crate::throttle::{SharedWriter, Throttle, Throttled};
+use pyo3::exceptions::PyValueError;
+use std::collections::HashMap;
+use std::sync::Arc;
 use strum::EnumString;
 use tracing_appender::rolling::{daily, hourly, minutely, never};
 
@@ -60,40 +74,165 @@ pub enum RotationType {
     Daily,
 }
 
-pub fn init_logger(level: &str, log_dir: Option<PathBuf>, rotation: Option<RotationType>) -> () {
-    if let Some(sink) = log_dir {
-        let name = format!("{}.log", env!("CARGO_CRATE_NAME"));
-        let writer = match rotation.unwrap_or_default() {
-            RotationType::Never => never(sink, name),
-            RotationType::Minutely => minutely(sink, name),
-            RotationType::Hourly => hourly(sink, name),
-            RotationType::Daily => daily(sink, name),
-        };
-        let fmt_layer = fmt::layer()
-            .with_target(true)
-            .event_format(MyFormatter)
-            .with_writer(writer)
-            .with_filter(EnvFilter::new(level));
-
-        tracing_subscriber::registry().with(fmt_layer).init();
-    } else {
-        let fmt_layer = fmt::layer()
-            .with_target(true)
-            .event_format(MyFormatter)
-            .with_writer(io::stderr)
-            .with_filter(EnvFilter::new(level));
-        tracing_subscriber::registry().with(fmt_layer).init();
+/// Formats the records with `style`, the throttle notices pending on drop going to `writer`
+fn styled<W>(style: &Arc<Style>, throttle: Throttle, writer: W) -> Throttled<MyFormatter>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    Throttled::new(
+        MyFormatter {
+            style: style.clone(),
+        },
+        throttle,
+        style.clone(),
+    )
+    .with_writer(writer)
+}
+
+/// Settings of the global logger installed by [`init_logger`]
+///
+/// Built from a level with [`LoggerOptions::new`], everything else defaulting to plain
+/// records on stderr in the [`Style::default`] style, neither suppressed nor rate limited.
+pub struct LoggerOptions {
+    level: String,
+    log_dir: Option<PathBuf>,
+    rotation: Option<RotationType>,
+    file_pattern: Option<String>,
+    throttle: Throttle,
+    style: Style,
+}
+
+impl LoggerOptions {
+    /// Starts the settings from a level filter, such as `info` or `fabricatio=debug`
+    pub fn new(level: impl Into<String>) -> Self {
+        Self {
+            level: level.into(),
+            log_dir: None,
+            rotation: None,
+            file_pattern: None,
+            throttle: Throttle::default(),
+            style: Style::default(),
+        }
+    }
+
+    /// Writes the records to files in `log_dir` instead of stderr, if set
+    pub fn with_log_dir(mut self, log_dir: Option<PathBuf>) -> Self {
+        self.log_dir = log_dir;
+        self
+    }
+
+    /// Rotates the log file of `log_dir`, never if unset
+    pub fn with_rotation(mut self, rotation: Option<RotationType>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Names a log file per record from its fields, such as `{date}/{task}.log`
+    pub fn with_file_pattern(mut self, file_pattern: Option<String>) -> Self {
+        self.file_pattern = file_pattern;
+        self
+    }
+
+    /// Suppresses duplicate records and limits their rate
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Renders the records with `style`
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// Installs the global logger.
+///
+/// Records go to stderr unless a log directory is set. With a file pattern such as
+/// `{date}/{task}.log`, each record is appended to the file named by its fields below the
+/// log directory while stderr keeps the merged stream; the rotation is ignored then, as the
+/// pattern decides when files change. Records are rendered with the style of the options on
+/// every sink, without escape codes in files or when stderr is not a terminal, and with
+/// their fields wrapped at the width of the terminal otherwise.
+pub fn init_logger(options: LoggerOptions) {
+    let LoggerOptions {
+        level,
+        log_dir,
+        rotation,
+        file_pattern,
+        throttle,
+        style,
+    } = options;
+    let level = level.as_str();
+    let console = Arc::new(style.for_stderr());
+    let file = Arc::new(style.for_file());
+    let name = format!("{}.log", env!("CARGO_CRATE_NAME"));
+    match (log_dir, file_pattern) {
+        (Some(sink), Some(pattern)) => {
+            let console_layer = fmt::layer()
+                .with_target(true)
+                .event_format(styled(&console, throttle, io::stderr))
+                .with_writer(io::stderr)
+                .with_filter(EnvFilter::new(level));
+            let writer = SharedWriter::new(PartitionedWriter::new(sink, name));
+            let file_layer = fmt::layer()
+                .with_target(true)
+                .event_format(Partitioned::new(
+                    styled(&file, throttle, writer.clone()),
+                    FilePattern::new(&pattern),
+                ))
+                .with_writer(writer)
+                .with_filter(EnvFilter::new(level));
+
+            tracing_subscriber::registry()
+                .with(console_layer)
+                .with(file_layer)
+                .init();
+        }
+        (Some(sink), None) => {
+            let writer = SharedWriter::new(match rotation.unwrap_or_default() {
+                RotationType::Never => never(sink, name),
+                RotationType::Minutely => minutely(sink, name),
+                RotationType::Hourly => hourly(sink, name),
+                RotationType::Daily => daily(sink, name),
+            });
+            let fmt_layer = fmt::layer()
+                .with_target(true)
+                .event_format(styled(&file, throttle, writer.clone()))
+                .with_writer(writer)
+                .with_filter(EnvFilter::new(level));
+
+            tracing_subscriber::registry().with(fmt_layer).init();
+        }
+        (None, _) => {
+            let fmt_layer = fmt::layer()
+                .with_target(true)
+                .event_format(styled(&console, throttle, io::stderr))
+                .with_writer(io::stderr)
+                .with_filter(EnvFilter::new(level));
+            tracing_subscriber::registry().with(fmt_layer).init();
+        }
     };
 }
 
 pub fn init_logger_auto() -> PyResult<()> {
-    let (level, sink, rotation) = Python::attach(|py| {
+    let (level, sink, rotation, file_pattern, throttle, style) = Python::attach(|py| {
         let debug_config = py
             .import(CORE_PACKAGE_NAME)?
             .getattr(CONFIG_VARNAME)?
             .getattr("debug")?;
 
-        Ok::<(String, Option<PathBuf>, Option<String>), PyErr>((
+        Ok::<
+            (
+                String,
+                Option<PathBuf>,
+                Option<String>,
+                Option<String>,
+                Throttle,
+                Style,
+            ),
+            PyErr,
+        >((
             debug_config.getattr("log_level")?.extract::<String>()?,
             debug_config
                 .getattr("log_dir")?
@@ -101,13 +240,38 @@ pub fn init_logger_auto() -> PyResult<()> {
             debug_config
                 .getattr("rotation")?
                 .extract::<Option<String>>()?,
+            debug_config
+                .getattr("log_file_pattern")?
+                .extract::<Option<String>>()?,
+            Throttle {
+                suppress_duplicates: debug_config
+                    .getattr("suppress_duplicates")?
+                    .extract::<bool>()?,
+                max_records_per_second: debug_config
+                    .getattr("max_records_per_second")?
+                    .extract::<Option<u32>>()?,
+            },
+            Style::new(
+                debug_config
+                    .getattr("log_format")?
+                    .extract::<Option<String>>()?
+                    .as_deref(),
+                &debug_config
+                    .getattr("level_colors")?
+                    .extract::<HashMap<String, String>>()?,
+            )
+            .map_err(PyValueError::new_err)?
+            .compact(debug_config.getattr("compact")?.extract::<bool>()?),
         ))
     })?;
 
     init_logger(
-        level.as_str(),
-        sink,
-        rotation.map(|s| s.parse::<RotationType>().unwrap_or_default()),
+        LoggerOptions::new(level)
+            .with_log_dir(sink)
+            .with_rotation(rotation.map(|s| s.parse::<RotationType>().unwrap_or_default()))
+            .with_file_pattern(file_pattern)
+            .with_throttle(throttle)
+            .with_style(style),
     );
     Ok(())
 }
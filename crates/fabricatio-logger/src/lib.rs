@@ -8,14 +8,17 @@
 //! - **Python/Rust Integration**: Automatic configuration from Python settings with PyO3 bindings
 //! - **Advanced Configuration**: Log rotation, thread-safe initialization, and customizable output destinations
 //! - **Structured Logging**: Key-value logging via tracing subsystem with custom formatting
+//! - **Throttling**: Duplicate suppression and per-second record caps against runaway loops
+//! - **Partitioned Files**: Per-task log files named from record fields, e.g. `{date}/{task}.log`
+//! - **Custom Styling**: Loguru-style format strings and per-level colors
 //!
 //! ## Usage
 //!
 //! ```rust
-//! use fabricatio_logger::{init_logger, init_logger_auto, info, debug, warn, error};
+//! use fabricatio_logger::{LoggerOptions, init_logger, init_logger_auto, info, debug, warn, error};
 //!
 //! // Manual initialization
-//! init_logger("debug", None, None);
+//! init_logger(LoggerOptions::new("debug"));
 //!
 //! // Or automatic configuration from Python
 //! init_logger_auto().expect("Failed to initialize logger from Python config");
@@ -30,11 +33,42 @@
 //! For more information, see the [README](https://github.com/Whth/fabricatio/blob/main/crates/fabricatio-logger/README.md).
 
 mod initializer;
+mod partition;
 mod renderer;
+mod style;
+mod throttle;
 
 pub use initializer::*;
+pub use partition::FilePattern;
+use partition::{CONTEXT_SEPARATOR, LOG_CONTEXT_KEY};
 use pyo3::prelude::*;
-use pyo3::types::PyList;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyDict, PyList};
+pub use style::{DEFAULT_FORMAT, Style};
+pub use throttle::Throttle;
+
+/// The `contextvars.ContextVar` holding the log context of the running Python task.
+static LOG_CONTEXT: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+fn log_context<'py>(py: Python<'py>) -> PyResult<&'py Bound<'py, PyAny>> {
+    LOG_CONTEXT
+        .get_or_try_init(py, || {
+            Ok::<_, PyErr>(
+                py.import("contextvars")?
+                    .getattr("ContextVar")?
+                    .call1((LOG_CONTEXT_KEY,))?
+                    .unbind(),
+            )
+        })
+        .map(|var| var.bind(py))
+}
+
+/// Reads the log context of the running Python task, as stored in the [`LOG_CONTEXT`] variable.
+fn current_context(py: Python) -> PyResult<Option<String>> {
+    log_context(py)?
+        .call_method1("get", (py.None(),))?
+        .extract()
+}
 
 pub use tracing::{debug, error, info, trace, warn};
 
@@ -64,13 +98,94 @@ impl Logger {
         Ok("<Unknown>".to_string())
     }
 }
+
+/// Context manager attaching fields to the records logged within it.
+#[cfg_attr(feature = "stubgen", pyo3_stub_gen::derive::gen_stub_pyclass)]
+#[pyclass]
+pub struct LogContext {
+    fields: Vec<(String, String)>,
+    token: Option<Py<PyAny>>,
+}
+
+#[cfg_attr(feature = "stubgen", pyo3_stub_gen::derive::gen_stub_pymethods)]
+#[pymethods]
+impl LogContext {
+    fn __enter__(mut slf: PyRefMut<Self>) -> PyResult<PyRefMut<Self>> {
+        let py = slf.py();
+        let mut pairs = current_context(py)?
+            .map(|context| {
+                context
+                    .split(CONTEXT_SEPARATOR)
+                    .filter_map(|pair| pair.split_once('='))
+                    .filter(|(key, _)| slf.fields.iter().all(|(k, _)| k != key))
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        pairs.extend(
+            slf.fields
+                .iter()
+                .map(|(key, value)| format!("{key}={value}")),
+        );
+        let context = pairs.join(&CONTEXT_SEPARATOR.to_string());
+        slf.token = Some(log_context(py)?.call_method1("set", (context,))?.unbind());
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __exit__<'a>(
+        &mut self,
+        python: Python<'a>,
+        _exc_type: Option<Bound<'a, PyAny>>,
+        _exc_value: Option<Bound<'a, PyAny>>,
+        _traceback: Option<Bound<'a, PyAny>>,
+    ) -> PyResult<()> {
+        if let Some(token) = self.token.take() {
+            log_context(python)?.call_method1("reset", (token,))?;
+        }
+        Ok(())
+    }
+}
 #[cfg_attr(feature = "stubgen", pyo3_stub_gen::derive::gen_stub_pymethods)]
 #[pymethods]
 impl Logger {
+    /// Attaches fields to the records logged within a `with` block.
+    ///
+    /// The fields are stored in a context variable, so they follow the current asyncio task
+    /// and the tasks it creates, and can name the log file of the records through the
+    /// `log_file_pattern` setting, e.g. `{date}/{task}.log`. Nested contexts add to the
+    /// fields of the outer ones.
+    ///
+    /// Args:
+    ///     **fields: The fields to attach, converted to strings.
+    ///
+    /// Returns:
+    ///     A context manager setting the fields on enter and restoring the previous ones on exit.
+    #[pyo3(signature = (**fields))]
+    fn context(&self, fields: Option<&Bound<PyDict>>) -> PyResult<LogContext> {
+        let fields = fields
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = value.str()?.to_string().replace(CONTEXT_SEPARATOR, " ");
+                        Ok((key.extract::<String>()?, value))
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(LogContext {
+            fields,
+            token: None,
+        })
+    }
+
     fn info(&self, msg: &str) -> PyResult<()> {
         Python::attach(|py| {
             let source = Self::extract_py_source(&py.import("inspect")?)?;
-            info!(py_source = source, "{}", msg);
+            let context = current_context(py)?;
+            info!(py_source = source, log_context = context, "{}", msg);
             Ok(())
         })
     }
@@ -78,7 +193,8 @@ impl Logger {
     fn debug(&self, msg: &str) -> PyResult<()> {
         Python::attach(|py| {
             let source = Self::extract_py_source(&py.import("inspect")?)?;
-            debug!(py_source = source, "{}", msg);
+            let context = current_context(py)?;
+            debug!(py_source = source, log_context = context, "{}", msg);
             Ok(())
         })
     }
@@ -86,7 +202,8 @@ impl Logger {
     fn error(&self, msg: &str) -> PyResult<()> {
         Python::attach(|py| {
             let source = Self::extract_py_source(&py.import("inspect")?)?;
-            error!(py_source = source, "{}", msg);
+            let context = current_context(py)?;
+            error!(py_source = source, log_context = context, "{}", msg);
             Ok(())
         })
     }
@@ -94,7 +211,8 @@ impl Logger {
     fn warn(&self, msg: &str) -> PyResult<()> {
         Python::attach(|py| {
             let source = Self::extract_py_source(&py.import("inspect")?)?;
-            warn!(py_source = source, "{}", msg);
+            let context = current_context(py)?;
+            warn!(py_source = source, log_context = context, "{}", msg);
             Ok(())
         })
     }
@@ -102,7 +220,8 @@ impl Logger {
     fn trace(&self, msg: &str) -> PyResult<()> {
         Python::attach(|py| {
             let source = Self::extract_py_source(&py.import("inspect")?)?;
-            trace!(py_source = source, "{}", msg);
+            let context = current_context(py)?;
+            trace!(py_source = source, log_context = context, "{}", msg);
             Ok(())
         })
     }
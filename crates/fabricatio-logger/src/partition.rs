@@ -0,0 +1,249 @@
+//! Log files partitioned by runtime fields of the records
+//!
+//! Agents running several tasks at once interleave their records in a single file, which
+//! makes a run hard to follow afterwards. A [`FilePattern`] such as `{date}/{task}.log`
+//! names the file of each record from its fields, so every task writes to a file of its own:
+//! - `{date}` (`YYYY-MM-DD`), `{hour}` (`HH`), `{pid}` and `{level}` are always available
+//! - any other placeholder is looked up in the fields of the record, including the
+//!   context set through `Logger.context` on the Python side
+//!
+//! Records lacking a field of the pattern go to the merged fallback file instead.
+//!
+//! [`Partitioned`] resolves the file while formatting a record and hands it over to
+//! [`PartitionedWriter`], which the `fmt` layer asks for a writer right after formatting.
+
+use chrono::Local;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter, format::Writer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Name of the event field carrying the context set from Python, as `key=value` pairs
+/// separated by [`CONTEXT_SEPARATOR`].
+pub const LOG_CONTEXT_KEY: &str = "log_context";
+
+/// Separator of the pairs of the [`LOG_CONTEXT_KEY`] field.
+pub const CONTEXT_SEPARATOR: char = '\x1f';
+
+/// Number of open files above which the cached handles are closed.
+const MAX_OPEN_FILES: usize = 64;
+
+thread_local! {
+    /// File of the record being formatted on this thread, `None` for the fallback file.
+    static CURRENT_FILE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(String),
+}
+
+/// A file naming pattern with `{field}` placeholders, relative to the log directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePattern {
+    segments: Vec<Segment>,
+}
+
+impl FilePattern {
+    /// Parses a pattern, treating unbalanced braces as literal text.
+    pub fn new(pattern: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start + 1..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 1..start + 1 + len];
+            if !rest[..start].is_empty() {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            segments.push(Segment::Field(name.trim().to_string()));
+            rest = &rest[start + len + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+        Self { segments }
+    }
+
+    /// Renders the relative path of a record, or `None` if one of its fields is missing.
+    fn render(&self, level: &tracing::Level, fields: &HashMap<String, String>) -> Option<PathBuf> {
+        let now = Local::now();
+        let mut path = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => path.push_str(text),
+                Segment::Field(name) => {
+                    let value = match name.as_str() {
+                        "date" => now.format("%Y-%m-%d").to_string(),
+                        "hour" => now.format("%H").to_string(),
+                        "pid" => std::process::id().to_string(),
+                        "level" => level.as_str().to_lowercase(),
+                        _ => fields.get(name)?.clone(),
+                    };
+                    path.push_str(&sanitize(&value)?);
+                }
+            }
+        }
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Makes a field value safe to use as a single path component.
+fn sanitize(value: &str) -> Option<String> {
+    let value: String = value
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match value.as_str() {
+        "" => None,
+        "." | ".." => Some("_".to_string()),
+        _ => Some(value),
+    }
+}
+
+/// Collects the fields of a record, unpacking the context set from Python.
+#[derive(Default)]
+struct FieldVisitor {
+    fields: HashMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == LOG_CONTEXT_KEY {
+            for pair in value.split(CONTEXT_SEPARATOR) {
+                if let Some((key, value)) = pair.split_once('=') {
+                    self.fields
+                        .entry(key.to_string())
+                        .or_insert_with(|| value.to_string());
+                }
+            }
+        } else {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() != "message" {
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// Event formatter wrapper resolving the file of each record from a [`FilePattern`]
+pub struct Partitioned<F> {
+    inner: F,
+    pattern: FilePattern,
+}
+
+impl<F> Partitioned<F> {
+    pub fn new(inner: F, pattern: FilePattern) -> Self {
+        Self { inner, pattern }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for Partitioned<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let file = self
+            .pattern
+            .render(event.metadata().level(), &visitor.fields);
+        CURRENT_FILE.with(|current| *current.borrow_mut() = file);
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
+/// Writer appending each record to the file resolved by [`Partitioned`]
+pub struct PartitionedWriter {
+    dir: PathBuf,
+    fallback: PathBuf,
+    files: Mutex<HashMap<PathBuf, File>>,
+}
+
+impl PartitionedWriter {
+    /// Creates a writer below `dir`, sending records without a file to `fallback`.
+    pub fn new(dir: PathBuf, fallback: impl AsRef<Path>) -> Self {
+        Self {
+            fallback: dir.join(fallback),
+            dir,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn append(&self, path: &Path, buf: &[u8]) -> io::Result<()> {
+        let mut files = self
+            .files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !files.contains_key(path) {
+            if files.len() >= MAX_OPEN_FILES {
+                files.clear();
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            files.insert(path.to_path_buf(), file);
+        }
+        files
+            .get_mut(path)
+            .expect("file was just opened")
+            .write_all(buf)
+    }
+}
+
+impl<'a> MakeWriter<'a> for PartitionedWriter {
+    type Writer = PartitionedFile<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        let path = CURRENT_FILE
+            .with(|current| current.borrow_mut().take())
+            .map(|file| self.dir.join(file))
+            .unwrap_or_else(|| self.fallback.clone());
+        PartitionedFile { sink: self, path }
+    }
+}
+
+/// A single record on its way to its file, opened lazily so empty records create no file
+pub struct PartitionedFile<'a> {
+    sink: &'a PartitionedWriter,
+    path: PathBuf,
+}
+
+impl Write for PartitionedFile<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sink.append(&self.path, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,16 +1,41 @@
-use chrono::{DateTime, Local};
+use crate::partition::{CONTEXT_SEPARATOR, LOG_CONTEXT_KEY};
+use crate::style::Style;
 use fabricatio_constants::PY_SOURCE_KEY;
+use std::sync::Arc;
 use tracing::field::{Field, Visit};
 use tracing::{Event, Subscriber};
 use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, format::Writer};
 use tracing_subscriber::registry::LookupSpan;
 
-struct PySourceVisitor {
-    py_source_value: Option<String>,
-    message: Option<String>,
+#[derive(Default)]
+pub(crate) struct PySourceVisitor {
+    pub(crate) py_source_value: Option<String>,
+    pub(crate) message: Option<String>,
+    /// The other fields of the record in order, the context set from Python unpacked.
+    pub(crate) fields: Vec<(String, String)>,
 }
 
 impl Visit for PySourceVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            PY_SOURCE_KEY => {
+                self.py_source_value = Some(value.replace("\"", ""));
+            }
+            "message" => {
+                self.message = Some(value.to_string());
+            }
+            LOG_CONTEXT_KEY => {
+                self.fields.extend(
+                    value
+                        .split(CONTEXT_SEPARATOR)
+                        .filter_map(|pair| pair.split_once('='))
+                        .map(|(key, value)| (key.to_string(), value.to_string())),
+                );
+            }
+            name => self.fields.push((name.to_string(), value.to_string())),
+        }
+    }
+
     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
         match field.name() {
             PY_SOURCE_KEY => {
@@ -19,14 +44,19 @@ impl Visit for PySourceVisitor {
             "message" => {
                 self.message = Some(format!("{:?}", value));
             }
-            _ => {}
+            // Metadata of records bridged from the `log` crate.
+            name if name.starts_with("log.") => {}
+            name => self.fields.push((name.to_string(), format!("{:?}", value))),
         }
     }
 }
 
 /// Custom event formatter that mimics loguru-style output.
-/// Format: "HH:MM:SS | LEVEL   | target:span - message"
-pub struct MyFormatter;
+/// Format: "HH:MM:SS | LEVEL   | target:span - message key=value" unless the [`Style`] says
+/// otherwise.
+pub struct MyFormatter {
+    pub(crate) style: Arc<Style>,
+}
 
 impl<S, N> FormatEvent<S, N> for MyFormatter
 where
@@ -39,52 +69,24 @@ where
         mut writer: Writer<'_>,
         event: &Event<'_>,
     ) -> std::fmt::Result {
-        let mut visitor = PySourceVisitor {
-            py_source_value: None,
-            message: None,
-        };
+        let mut visitor = PySourceVisitor::default();
         event.record(&mut visitor);
 
         let meta = event.metadata();
 
-        let level = event.metadata().level();
-
-        let level_color: &str = match *level {
-            tracing::Level::ERROR => "\x1b[31m\x1b[1m",
-            tracing::Level::WARN => "\x1b[33m\x1b[1m",
-            tracing::Level::INFO => "\x1b[0m\x1b[1m",
-            tracing::Level::DEBUG => "\x1b[34m\x1b[1m",
-            tracing::Level::TRACE => "\x1b[2m\x1b[1m",
-        };
-
-        // 1. Time (dimmed green)
-        let local: DateTime<Local> = Local::now();
-        let time = local.format("%H:%M:%S").to_string();
-        let time = format!("{}{}{}", "\x1b[32m", time, "\x1b[0m");
-
         // 3. Target (cyan)
         let formatted_target = if let Some(py_source) = visitor.py_source_value {
             py_source
         } else {
             format!("<rust>::{}", meta.target())
         };
-        let formatted_target = format!("{}{}{}", "\x1b[36m", formatted_target, "\x1b[0m");
-        // 4. Write formatted parts
-        write!(
-            writer,
-            "{} \x1b[31m| {}{:<5}\x1b[0m \x1b[31m| {} \x1b[31m- ",
-            time,
-            level_color,
-            level.as_str(),
-            formatted_target
-        )?;
 
-        write!(
-            writer,
-            "{}{}\x1b[0m",
-            level_color,
-            visitor.message.unwrap_or_default()
-        )?;
-        writeln!(writer)
+        self.style.write_record(
+            &mut writer,
+            meta.level(),
+            &formatted_target,
+            &visitor.message.unwrap_or_default(),
+            &visitor.fields,
+        )
     }
 }
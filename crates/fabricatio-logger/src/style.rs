@@ -0,0 +1,481 @@
+//! Loguru-style format strings and level colors for log records
+//!
+//! A format mixes fields in braces with color markup in angle brackets, as loguru does:
+//!
+//! ```text
+//! <green>{time:HH:mm:ss}</green> | <level>{level: <5}</level> | <cyan>{name}</cyan> - {message}
+//! ```
+//!
+//! - fields are `time`, `level`, `name` (the Python function or the Rust target), `message`
+//!   and `process`; they take an alignment spec as in Python, such as `{level: <8}`, except
+//!   `time` whose spec is a date made of `YYYY`, `YY`, `MM`, `DD`, `HH`, `hh`, `mm`, `ss`,
+//!   `SSS`, `A` and `Z`
+//! - tags are colors (`red`, `light-blue`, ...), styles (`bold`, `dim`, `italic`,
+//!   `underline`, `reverse`) and `level`, the color of the level of the record
+//! - `{{`, `}}` and `\<` stand for literal braces and angle brackets
+//!
+//! The structured fields of a record follow its message as `key=value` pairs. On a terminal
+//! they wrap at its width, indented below the record; elsewhere, such as in files or pipes,
+//! records carry no escape codes and are not wrapped. A compact style writes every record on
+//! a single line.
+
+use chrono::Local;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::{self, IsTerminal};
+use terminal_size::{Width, terminal_size_of};
+use tracing::Level;
+use tracing_subscriber::fmt::format::Writer;
+
+/// The format of the records unless configured otherwise
+pub const DEFAULT_FORMAT: &str = "<green>{time:HH:mm:ss}</green> <red>|</red> <level>{level: <5}</level> <red>|</red> <cyan>{name}</cyan> <red>-</red> <level>{message}</level>";
+
+/// The default colors of the levels, from `ERROR` to `TRACE`
+const DEFAULT_LEVEL_COLORS: [&str; 5] =
+    ["red bold", "yellow bold", "bold", "blue bold", "dim bold"];
+
+/// The date of `{time}` without a spec
+const DEFAULT_TIME_FORMAT: &str = "YYYY-MM-DD HH:mm:ss.SSS";
+
+const RESET: &str = "\x1b[0m";
+
+/// Color of the keys of the structured fields
+const FIELD_KEY_COLOR: &str = "\x1b[2m";
+
+/// Indentation of the structured fields wrapped below their record
+const FIELD_INDENT: &str = "    ";
+
+/// Loguru date tokens and their chrono equivalents, longest first
+const TIME_TOKENS: [(&str, &str); 11] = [
+    ("YYYY", "%Y"),
+    ("SSS", "%3f"),
+    ("YY", "%y"),
+    ("MM", "%m"),
+    ("DD", "%d"),
+    ("HH", "%H"),
+    ("hh", "%I"),
+    ("mm", "%M"),
+    ("ss", "%S"),
+    ("A", "%p"),
+    ("Z", "%:z"),
+];
+
+fn ansi_of(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "reset" | "normal" => "\x1b[0m",
+        "bold" | "b" => "\x1b[1m",
+        "dim" | "d" => "\x1b[2m",
+        "italic" | "i" => "\x1b[3m",
+        "underline" | "u" => "\x1b[4m",
+        "reverse" | "r" => "\x1b[7m",
+        "black" => "\x1b[30m",
+        "red" => "\x1b[31m",
+        "green" => "\x1b[32m",
+        "yellow" => "\x1b[33m",
+        "blue" => "\x1b[34m",
+        "magenta" => "\x1b[35m",
+        "cyan" => "\x1b[36m",
+        "white" => "\x1b[37m",
+        "light-black" => "\x1b[90m",
+        "light-red" => "\x1b[91m",
+        "light-green" => "\x1b[92m",
+        "light-yellow" => "\x1b[93m",
+        "light-blue" => "\x1b[94m",
+        "light-magenta" => "\x1b[95m",
+        "light-cyan" => "\x1b[96m",
+        "light-white" => "\x1b[97m",
+        _ => return None,
+    })
+}
+
+/// Escape codes of a color given as names separated by spaces, or as tags like `<red><bold>`
+fn markup_codes(spec: &str) -> Result<String, String> {
+    spec.replace(['<', '>'], " ")
+        .split_whitespace()
+        .map(|name| ansi_of(name).ok_or_else(|| format!("Unknown color `{name}`")))
+        .collect()
+}
+
+fn level_index(level: &Level) -> usize {
+    match *level {
+        Level::ERROR => 0,
+        Level::WARN => 1,
+        Level::INFO => 2,
+        Level::DEBUG => 3,
+        Level::TRACE => 4,
+    }
+}
+
+/// Padding of a field to a minimal width
+#[derive(Debug, Clone, PartialEq)]
+struct Align {
+    fill: char,
+    /// `<`, `>` or `^`, as in Python
+    side: char,
+    width: usize,
+}
+
+impl Align {
+    /// Parses a spec such as ` <8`, `>5` or `10`
+    fn parse(spec: &str) -> Result<Self, String> {
+        let chars: Vec<char> = spec.chars().collect();
+        let is_side = |c: &char| matches!(c, '<' | '>' | '^');
+        let (fill, side, width) = match chars.as_slice() {
+            [fill, side, width @ ..] if is_side(side) => (*fill, *side, width),
+            [side, width @ ..] if is_side(side) => (' ', *side, width),
+            width => (' ', '<', width),
+        };
+        let width = width
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map_err(|_| format!("Invalid alignment `{spec}`"))?;
+        Ok(Self { fill, side, width })
+    }
+
+    fn write(&self, writer: &mut impl Write, value: &str) -> std::fmt::Result {
+        let pad = self.width.saturating_sub(value.chars().count());
+        let (before, after) = match self.side {
+            '>' => (pad, 0),
+            '^' => (pad / 2, pad - pad / 2),
+            _ => (0, pad),
+        };
+        (0..before).try_for_each(|_| writer.write_char(self.fill))?;
+        writer.write_str(value)?;
+        (0..after).try_for_each(|_| writer.write_char(self.fill))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    /// The local time, with a chrono format
+    Time(String),
+    Level,
+    Name,
+    Message,
+    Process,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Ansi(&'static str),
+    LevelColor,
+    Field(Field, Option<Align>),
+}
+
+/// Converts a loguru date such as `YYYY-MM-DD HH:mm:ss` to a chrono format
+fn chrono_format(spec: &str) -> String {
+    let mut format = String::new();
+    let mut rest = spec;
+    while let Some(c) = rest.chars().next() {
+        if let Some((token, chrono)) = TIME_TOKENS
+            .iter()
+            .find(|(token, _)| rest.starts_with(token))
+        {
+            format.push_str(chrono);
+            rest = &rest[token.len()..];
+            continue;
+        }
+        if c == '%' {
+            format.push('%');
+        }
+        format.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    format
+}
+
+fn parse_field(token: &str) -> Result<Segment, String> {
+    let (name, spec) = match token.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (token, None),
+    };
+    let field = match name {
+        "time" => {
+            return Ok(Segment::Field(
+                Field::Time(chrono_format(spec.unwrap_or(DEFAULT_TIME_FORMAT))),
+                None,
+            ));
+        }
+        "level" => Field::Level,
+        "name" => Field::Name,
+        "message" => Field::Message,
+        "process" => Field::Process,
+        _ => return Err(format!("Unknown field `{{{name}}}`")),
+    };
+    Ok(Segment::Field(field, spec.map(Align::parse).transpose()?))
+}
+
+/// Takes the characters up to a closing delimiter, which is consumed
+fn take_until(chars: &mut impl Iterator<Item = char>, end: char) -> Result<String, String> {
+    let mut token = String::new();
+    for c in chars {
+        if c == end {
+            return Ok(token);
+        }
+        token.push(c);
+    }
+    Err(format!("Missing `{end}` after `{token}`"))
+}
+
+fn parse_format(format: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    // The open tags, to restore the outer colors once an inner tag closes
+    let mut open: Vec<(String, Segment)> = Vec::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('<')) | ('{', Some('{')) | ('}', Some('}')) => {
+                text.extend(chars.next());
+                continue;
+            }
+            ('}', _) => return Err("Single `}` in format, use `}}` for a literal one".into()),
+            ('{', _) | ('<', _) => {}
+            _ => {
+                text.push(c);
+                continue;
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(std::mem::take(&mut text)));
+        }
+        if c == '{' {
+            segments.push(parse_field(&take_until(&mut chars, '}')?)?);
+            continue;
+        }
+        let tag = take_until(&mut chars, '>')?;
+        if let Some(name) = tag.strip_prefix('/') {
+            match open.pop() {
+                Some((opened, _)) if opened == name => {}
+                _ => {
+                    return Err(format!(
+                        "Closing tag `</{name}>` does not match an open tag"
+                    ));
+                }
+            }
+            segments.push(Segment::Ansi(RESET));
+            segments.extend(open.iter().map(|(_, segment)| segment.clone()));
+        } else {
+            let segment = match tag.as_str() {
+                "level" => Segment::LevelColor,
+                name => {
+                    Segment::Ansi(ansi_of(name).ok_or_else(|| format!("Unknown tag `<{name}>`"))?)
+                }
+            };
+            segments.push(segment.clone());
+            open.push((tag, segment));
+        }
+    }
+    if let Some((name, _)) = open.last() {
+        return Err(format!("Tag `<{name}>` is never closed"));
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Escapes line breaks, so that a value stays on a single line
+fn single_line(value: &str) -> String {
+    value.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// Formats a value of a structured field, quoted if it has spaces, quotes, `=` or line breaks
+fn field_value(value: &str) -> String {
+    if value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '='))
+    {
+        format!("{value:?}")
+    } else {
+        value.to_string()
+    }
+}
+
+/// The number of columns of the last line of a text, escape codes left out
+fn last_line_width(text: &str) -> usize {
+    let line = text.rsplit('\n').next().unwrap_or(text);
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// The width of the terminal stderr is, None if it is not a terminal
+fn stderr_width() -> Option<usize> {
+    terminal_size_of(io::stderr()).map(|(Width(width), _)| usize::from(width))
+}
+
+/// How records are rendered: a parsed format, the colors of the levels and how records fit
+/// the output they are written to
+#[derive(Debug, Clone)]
+pub struct Style {
+    segments: Vec<Segment>,
+    /// Escape codes of the colors of the levels, from `ERROR` to `TRACE`
+    level_colors: [String; 5],
+    /// Whether to write escape codes
+    ansi: bool,
+    /// Whether to wrap the structured fields at the width of the terminal of stderr
+    wrap: bool,
+    /// Whether to write each record on a single line
+    compact: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new(None, &HashMap::new()).expect("the default style is valid")
+    }
+}
+
+impl Style {
+    /// Builds a style from a loguru-style format, [`DEFAULT_FORMAT`] if `None`, and colors
+    /// keyed by level name, such as `{"WARN": "light-yellow bold"}`
+    ///
+    /// Levels left out of `level_colors` keep their default color. Returns a description of
+    /// the problem if the format, a level or a color is invalid.
+    pub fn new(
+        format: Option<&str>,
+        level_colors: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let mut colors = DEFAULT_LEVEL_COLORS.map(|spec| markup_codes(spec).unwrap_or_default());
+        for (level, spec) in level_colors {
+            let index = match level.to_uppercase().as_str() {
+                "ERROR" => 0,
+                "WARN" | "WARNING" => 1,
+                "INFO" => 2,
+                "DEBUG" => 3,
+                "TRACE" => 4,
+                _ => return Err(format!("Unknown log level `{level}`")),
+            };
+            colors[index] = markup_codes(spec)?;
+        }
+        Ok(Self {
+            segments: parse_format(format.unwrap_or(DEFAULT_FORMAT))?,
+            level_colors: colors,
+            ansi: true,
+            wrap: false,
+            compact: false,
+        })
+    }
+
+    /// Writes each record on a single line, line breaks of messages and fields escaped as
+    /// `\n`, and never wraps fields
+    pub fn compact(self, compact: bool) -> Self {
+        Self { compact, ..self }
+    }
+
+    /// Adapts the style to stderr: escape codes only if it is a terminal and `NO_COLOR` is
+    /// unset, and fields wrapped at the width of the terminal
+    pub(crate) fn for_stderr(&self) -> Self {
+        let terminal = io::stderr().is_terminal();
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self {
+            ansi: terminal && !no_color,
+            wrap: terminal,
+            ..self.clone()
+        }
+    }
+
+    /// Adapts the style to a log file: neither escape codes nor wrapping
+    pub(crate) fn for_file(&self) -> Self {
+        Self {
+            ansi: false,
+            wrap: false,
+            ..self.clone()
+        }
+    }
+
+    /// Appends the structured fields to a rendered record, wrapping them below it if they
+    /// overflow the terminal
+    fn write_fields(&self, line: &mut String, fields: &[(String, String)]) {
+        let width = (self.wrap && !self.compact)
+            .then(stderr_width)
+            .flatten()
+            .filter(|&width| width > FIELD_INDENT.len());
+        let mut column = last_line_width(line);
+        for (key, value) in fields {
+            let value = field_value(value);
+            let pair_width = key.chars().count() + 1 + value.chars().count();
+            let (key_color, reset) = if self.ansi {
+                (FIELD_KEY_COLOR, RESET)
+            } else {
+                ("", "")
+            };
+            match width {
+                Some(width) if column + 1 + pair_width > width => {
+                    line.push('\n');
+                    line.push_str(FIELD_INDENT);
+                    column = FIELD_INDENT.len();
+                }
+                _ => {
+                    line.push(' ');
+                    column += 1;
+                }
+            }
+            let _ = write!(line, "{key_color}{key}={reset}");
+            column += key.chars().count() + 1;
+            // A value longer than the terminal continues on indented lines of their own.
+            for c in value.chars() {
+                if let Some(width) = width
+                    && column >= width
+                {
+                    line.push('\n');
+                    line.push_str(FIELD_INDENT);
+                    column = FIELD_INDENT.len();
+                }
+                line.push(c);
+                column += 1;
+            }
+        }
+    }
+
+    /// Writes a single record, followed by its structured fields
+    pub(crate) fn write_record(
+        &self,
+        writer: &mut Writer<'_>,
+        level: &Level,
+        target: &str,
+        message: &str,
+        fields: &[(String, String)],
+    ) -> std::fmt::Result {
+        let mut line = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => line.push_str(text),
+                Segment::Ansi(code) if self.ansi => line.push_str(code),
+                Segment::LevelColor if self.ansi => {
+                    line.push_str(&self.level_colors[level_index(level)])
+                }
+                Segment::Ansi(_) | Segment::LevelColor => {}
+                Segment::Field(field, align) => {
+                    let value = match field {
+                        Field::Time(format) => Local::now().format(format).to_string(),
+                        Field::Level => level.as_str().to_string(),
+                        Field::Name => target.to_string(),
+                        Field::Message if self.compact => single_line(message),
+                        Field::Message => message.to_string(),
+                        Field::Process => std::process::id().to_string(),
+                    };
+                    match align {
+                        Some(align) => align.write(&mut line, &value)?,
+                        None => line.push_str(&value),
+                    }
+                }
+            }
+        }
+        self.write_fields(&mut line, fields);
+        if self.ansi {
+            line.push_str(RESET);
+        }
+        writeln!(writer, "{line}")
+    }
+}
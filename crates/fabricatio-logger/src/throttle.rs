@@ -0,0 +1,435 @@
+//! Duplicate suppression and rate limiting for log records
+//!
+//! Runaway agent loops tend to emit the same record over and over, which can
+//! grow log files to several gigabytes. [`Throttled`] wraps any event formatter and:
+//! - collapses consecutive identical records into a single
+//!   "last message repeated N times" notice, written once another record comes or, for a
+//!   record repeated for longer than a second, every second
+//! - caps the number of records written per second, reporting how many were dropped
+//!   once the next window opens
+//!
+//! The notices still pending when the formatter is flushed or dropped go to the writer
+//! given with [`Throttled::with_writer`], so the last repetitions are not lost.
+
+use crate::renderer::PySourceVisitor;
+use crate::style::Style;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter, format::Writer};
+use tracing_subscriber::registry::LookupSpan;
+
+const THROTTLE_TARGET: &str = "<logger>::throttle";
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Writes notices outside of the formatting of a record
+type Sink = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Settings controlling duplicate suppression and rate limiting of log records
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Throttle {
+    /// Collapse consecutive identical records into a repetition notice
+    pub suppress_duplicates: bool,
+    /// Maximum number of records written per second, unlimited if `None`
+    pub max_records_per_second: Option<u32>,
+}
+
+impl Throttle {
+    /// Returns true if the throttle has no effect at all
+    pub fn is_noop(&self) -> bool {
+        !self.suppress_duplicates && self.max_records_per_second.is_none()
+    }
+}
+
+/// Identity of a record used to detect duplicates
+#[derive(PartialEq)]
+struct RecordKey {
+    level: Level,
+    target: String,
+    message: Option<String>,
+}
+
+struct ThrottleState {
+    last: Option<RecordKey>,
+    repeated: u64,
+    /// When the first of the repetitions not yet reported was suppressed
+    repeat_start: Instant,
+    window_start: Instant,
+    window_count: u32,
+    dropped: u64,
+}
+
+/// Event formatter wrapper applying a [`Throttle`] before delegating to the inner formatter
+pub struct Throttled<F> {
+    inner: F,
+    throttle: Throttle,
+    /// Style of the notices about suppressed and dropped records
+    style: Arc<Style>,
+    state: Mutex<ThrottleState>,
+    /// Where [`Throttled::flush`] writes the pending notices
+    sink: Option<Sink>,
+}
+
+impl<F> Throttled<F> {
+    pub fn new(inner: F, throttle: Throttle, style: Arc<Style>) -> Self {
+        Self {
+            inner,
+            throttle,
+            style,
+            state: Mutex::new(ThrottleState {
+                last: None,
+                repeated: 0,
+                repeat_start: Instant::now(),
+                window_start: Instant::now(),
+                window_count: 0,
+                dropped: 0,
+            }),
+            sink: None,
+        }
+    }
+
+    /// Writes the notices pending on flush or drop with `make_writer`, that of the layer
+    pub fn with_writer<W>(mut self, make_writer: W) -> Self
+    where
+        W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+    {
+        self.sink = Some(Box::new(move |notices| {
+            // A notice that cannot be written has nowhere else to go.
+            let _ = make_writer.make_writer().write_all(notices);
+        }));
+        self
+    }
+
+    /// Writes the notices of the records suppressed or dropped so far, if any
+    ///
+    /// Does nothing without a writer set with [`Throttled::with_writer`].
+    pub fn flush(&self) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut notices = String::new();
+        let mut writer = Writer::new(&mut notices);
+        if self.write_repeated(&mut writer, &mut state).is_ok()
+            && self.write_dropped(&mut writer, &mut state).is_ok()
+            && !notices.is_empty()
+        {
+            sink(notices.as_bytes());
+        }
+    }
+
+    /// Writes the "repeated N times" notice of the last record, if it was repeated
+    fn write_repeated(
+        &self,
+        writer: &mut Writer<'_>,
+        state: &mut ThrottleState,
+    ) -> std::fmt::Result {
+        if state.repeated > 0 {
+            self.style.write_record(
+                writer,
+                &key_level(&state.last),
+                THROTTLE_TARGET,
+                &format!("last message repeated {} times", state.repeated),
+                &[],
+            )?;
+            state.repeated = 0;
+        }
+        Ok(())
+    }
+
+    /// Writes how many records the rate limit dropped, if any
+    fn write_dropped(
+        &self,
+        writer: &mut Writer<'_>,
+        state: &mut ThrottleState,
+    ) -> std::fmt::Result {
+        if state.dropped > 0 {
+            self.style.write_record(
+                writer,
+                &Level::WARN,
+                THROTTLE_TARGET,
+                &format!(
+                    "{} records dropped, rate limit of {} records per second exceeded",
+                    state.dropped,
+                    self.throttle.max_records_per_second.unwrap_or_default()
+                ),
+                &[],
+            )?;
+            state.dropped = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<F> Drop for Throttled<F> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for Throttled<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        if self.throttle.is_noop() {
+            return self.inner.format_event(ctx, writer, event);
+        }
+
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if self.throttle.suppress_duplicates {
+            let mut visitor = PySourceVisitor::default();
+            event.record(&mut visitor);
+            let meta = event.metadata();
+            let key = RecordKey {
+                level: *meta.level(),
+                target: visitor
+                    .py_source_value
+                    .unwrap_or_else(|| meta.target().to_string()),
+                message: visitor.message,
+            };
+
+            if state.last.as_ref() == Some(&key) {
+                if state.repeated == 0 {
+                    state.repeat_start = Instant::now();
+                }
+                state.repeated += 1;
+                if state.repeat_start.elapsed() >= WINDOW {
+                    self.write_repeated(&mut writer, &mut state)?;
+                }
+                return Ok(());
+            }
+
+            self.write_repeated(&mut writer, &mut state)?;
+            state.last = Some(key);
+        }
+
+        if let Some(max) = self.throttle.max_records_per_second {
+            if state.window_start.elapsed() >= WINDOW {
+                self.write_dropped(&mut writer, &mut state)?;
+                state.window_start = Instant::now();
+                state.window_count = 0;
+            }
+
+            if state.window_count >= max {
+                state.dropped += 1;
+                return Ok(());
+            }
+            state.window_count += 1;
+        }
+
+        drop(state);
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
+/// A writer shared by a layer and the [`Throttled`] formatter of the layer
+pub(crate) struct SharedWriter<W>(Arc<W>);
+
+impl<W> SharedWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self(Arc::new(writer))
+    }
+}
+
+impl<W> Clone for SharedWriter<W> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, W: MakeWriter<'a>> MakeWriter<'a> for SharedWriter<W> {
+    type Writer = W::Writer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.0.make_writer()
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.0.make_writer_for(meta)
+    }
+}
+
+#[inline]
+fn key_level(key: &Option<RecordKey>) -> Level {
+    key.as_ref().map(|k| k.level).unwrap_or(Level::INFO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::MyFormatter;
+    use std::collections::HashMap;
+    use std::io;
+    use std::thread;
+    use tracing::{info, warn};
+    use tracing_subscriber::fmt;
+    use tracing_subscriber::prelude::*;
+
+    /// Collects what the subscriber writes.
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs `emit` with the records throttled, returning the lines written.
+    fn throttled_lines(throttle: Throttle, emit: impl FnOnce()) -> Vec<String> {
+        let style = Arc::new(
+            Style::new(Some("{level} | {message}"), &HashMap::new())
+                .unwrap()
+                .for_file(),
+        );
+        let output = Output::default();
+        let writer = output.clone();
+        let sink = output.clone();
+        let layer = fmt::layer()
+            .event_format(
+                Throttled::new(
+                    MyFormatter {
+                        style: style.clone(),
+                    },
+                    throttle,
+                    style,
+                )
+                .with_writer(move || sink.clone()),
+            )
+            .with_writer(move || writer.clone());
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), emit);
+        let bytes = output.0.lock().unwrap().clone();
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicates_collapse() {
+        let throttle = Throttle {
+            suppress_duplicates: true,
+            max_records_per_second: None,
+        };
+        let lines = throttled_lines(throttle, || {
+            for _ in 0..4 {
+                info!("looping");
+            }
+            warn!("looping");
+            warn!("done");
+        });
+        assert_eq!(
+            lines,
+            [
+                "INFO | looping",
+                "INFO | last message repeated 3 times",
+                "WARN | looping",
+                "WARN | done",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeats_reported_every_window() {
+        let throttle = Throttle {
+            suppress_duplicates: true,
+            max_records_per_second: None,
+        };
+        let lines = throttled_lines(throttle, || {
+            info!("looping");
+            info!("looping");
+            thread::sleep(WINDOW);
+            info!("looping");
+            info!("looping");
+            info!("looping");
+        });
+        assert_eq!(
+            lines,
+            [
+                "INFO | looping",
+                "INFO | last message repeated 2 times",
+                "INFO | last message repeated 2 times",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_notices_flushed_on_drop() {
+        let throttle = Throttle {
+            suppress_duplicates: true,
+            max_records_per_second: Some(2),
+        };
+        let lines = throttled_lines(throttle, || {
+            info!("looping");
+            info!("looping");
+            info!("other");
+            info!("last");
+            info!("last");
+        });
+        assert_eq!(
+            lines,
+            [
+                "INFO | looping",
+                "INFO | last message repeated 1 times",
+                "INFO | other",
+                "INFO | last message repeated 1 times",
+                "WARN | 1 records dropped, rate limit of 2 records per second exceeded",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_kept_by_default() {
+        let lines = throttled_lines(Throttle::default(), || {
+            info!("looping");
+            info!("looping");
+        });
+        assert_eq!(lines, ["INFO | looping", "INFO | looping"]);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let throttle = Throttle {
+            suppress_duplicates: false,
+            max_records_per_second: Some(2),
+        };
+        let lines = throttled_lines(throttle, || {
+            for i in 0..5 {
+                info!("record {i}");
+            }
+            thread::sleep(WINDOW);
+            info!("record 5");
+        });
+        assert_eq!(
+            lines,
+            [
+                "INFO | record 0",
+                "INFO | record 1",
+                "WARN | 3 records dropped, rate limit of 2 records per second exceeded",
+                "INFO | record 5",
+            ]
+        );
+    }
+}
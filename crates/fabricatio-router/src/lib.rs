@@ -369,6 +369,20 @@ impl Router {
         Ok(())
     }
 
+    /// Re-registers the providers of the configuration, picking up their current keys.
+    ///
+    /// Providers keep the key they were registered with, so call this after refreshing
+    /// secrets with `CONFIG.check_secrets(rotate=True)`.
+    ///
+    /// Returns:
+    ///     None
+    pub fn reload_providers(&self) -> PyResult<()> {
+        for config in fabricatio_config::CONFIG.routing.providers.clone() {
+            self.add_provider(config.ptype, config.name, config.key, config.base_url)?;
+        }
+        Ok(())
+    }
+
     #[pyo3(signature = (group, model_identifier, rpm = None, tpm = None))]
     /// Adds a completion model to the specified group.
     ///
@@ -75,6 +75,8 @@ fn main() -> Result<()> {
     fabricatio_tei::stub_info()?.generate()?;
     #[cfg(feature = "skill")]
     fabricatio_skill::stub_info()?.generate()?;
+    #[cfg(feature = "rag")]
+    fabricatio_rag::stub_info()?.generate()?;
 
     println!("Stubgen Done!");
     Ok(())
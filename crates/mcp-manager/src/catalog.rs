@@ -0,0 +1,96 @@
+//! Snapshots of the tools of the servers, to plan and generate stubs without live servers.
+//!
+//! ```json
+//! {
+//!   "servers": {
+//!     "filesystem": [{ "name": "read_file", "description": "...", "inputSchema": { ... } }]
+//!   }
+//! }
+//! ```
+
+use crate::error::{McpError, Result};
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The tool metadata of a set of servers, keyed by server name
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Catalog {
+    /// Map of server names to the tools they expose
+    pub servers: BTreeMap<String, Vec<Tool>>,
+}
+
+impl Catalog {
+    /// Loads a catalog written by [`Catalog::to_file`]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a catalog document
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(McpError::InvalidCatalog)
+    }
+
+    /// Writes the catalog as pretty-printed JSON, creating missing parent directories
+    ///
+    /// Servers are sorted by name so that snapshots of the same tools are identical.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(McpError::InvalidCatalog)?;
+        Ok(fs::write(path, json)?)
+    }
+
+    /// Returns the number of tools over all servers
+    pub fn tool_count(&self) -> usize {
+        self.servers.values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_round_trip() {
+        let catalog = Catalog::from_json(
+            r#"{
+                "servers": {
+                    "fs": [
+                        {"name": "read_file", "description": "Reads a file", "inputSchema": {"type": "object"}},
+                        {"name": "list_dir", "inputSchema": {"type": "object"}}
+                    ],
+                    "empty": []
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(catalog.tool_count(), 2);
+        assert_eq!(catalog.servers["fs"][0].name, "read_file");
+
+        let path = std::env::temp_dir().join(format!("mcp-catalog-{}.json", std::process::id()));
+        catalog.to_file(&path).unwrap();
+        let loaded = Catalog::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.servers.keys().collect::<Vec<_>>(),
+            vec!["empty", "fs"]
+        );
+        assert_eq!(
+            loaded.servers["fs"][0].description.as_deref(),
+            Some("Reads a file")
+        );
+    }
+
+    #[test]
+    fn test_catalog_invalid() {
+        assert!(matches!(
+            Catalog::from_json(r#"{"servers": {"fs": [{"description": "no name"}]}}"#),
+            Err(McpError::InvalidCatalog(_))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::{McpError, Result};
+use crate::limit::ResultLimit;
+use crate::policy::ToolPolicy;
+use rmcp::RoleClient;
+use rmcp::model::Tool;
+use rmcp::service::{DynService, RunningService};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+pub(crate) type MCPService = RunningService<RoleClient, Box<dyn DynService<RoleClient>>>;
+
+/// A running MCP service together with the rules governing its use
+pub(crate) struct ManagedClient {
+    /// The running service
+    pub(crate) service: MCPService,
+    /// Allow/deny rules for the tools of this service
+    pub(crate) policy: ToolPolicy,
+    /// Size cap on the results of tool calls, unlimited if `None`
+    pub(crate) limit: Option<ResultLimit>,
+    /// Limits the number of in-flight tool calls, unlimited if `None`
+    limiter: Option<Semaphore>,
+    /// The tools last listed, whose input schemas call arguments are checked against
+    tools: RwLock<Option<Arc<Vec<Tool>>>>,
+}
+
+impl ManagedClient {
+    pub(crate) fn new(
+        service: MCPService,
+        policy: ToolPolicy,
+        limit: Option<ResultLimit>,
+        max_concurrency: Option<usize>,
+    ) -> Self {
+        Self {
+            service,
+            policy,
+            limit,
+            limiter: max_concurrency.map(|n| Semaphore::new(n.max(1))),
+            tools: RwLock::new(None),
+        }
+    }
+
+    /// Returns the tools last listed, `None` if they were never listed
+    pub(crate) fn cached_tools(&self) -> Option<Arc<Vec<Tool>>> {
+        self.tools
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the cached tools with a fresh listing
+    pub(crate) fn cache_tools(&self, tools: Vec<Tool>) {
+        *self
+            .tools
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(tools));
+    }
+
+    /// Stops accepting calls and cancels the running service
+    ///
+    /// Dropping the service afterwards also terminates the child process of stdio services.
+    pub(crate) fn close(&self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.close();
+        }
+        self.service.cancellation_token().cancel();
+    }
+
+    /// Waits until a call slot is available, the returned permit frees the slot on drop
+    pub(crate) async fn acquire(&self, client_id: &str) -> Result<Option<SemaphorePermit<'_>>> {
+        match &self.limiter {
+            Some(limiter) => limiter
+                .acquire()
+                .await
+                .map(Some)
+                .map_err(|_| McpError::ClientClosed(client_id.to_string())),
+            None => Ok(None),
+        }
+    }
+}
@@ -26,9 +26,52 @@ pub enum McpError {
     #[error("Command not found: {0}")]
     CommandNotFound(#[from] which::Error),
 
+    /// Configuration file could not be parsed
+    #[error("Invalid MCP configuration: {0}")]
+    InvalidConfig(#[from] serde_json::Error),
+
+    /// Tool catalog could not be parsed or written
+    #[error("Invalid tool catalog: {0}")]
+    InvalidCatalog(serde_json::Error),
+
+    /// HTTP header name or value is invalid
+    #[error("Invalid HTTP header: {0}")]
+    InvalidHeader(String),
+
     /// Tool not found
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
+
+    /// Client no longer accepts calls
+    #[error("Client {0} is closed")]
+    ClientClosed(String),
+
+    /// Invalid glob pattern in a tool policy
+    #[error("Invalid tool pattern: {0}")]
+    InvalidPattern(#[from] glob::PatternError),
+
+    /// Tool is blocked by the server's tool policy
+    #[error("Tool {0} is blocked by policy")]
+    ToolBlocked(String),
+
+    /// Destructive tool call was rejected by the confirmation callback
+    #[error("Call to destructive tool {0} was rejected")]
+    ToolCallRejected(String),
+
+    /// Tool call arguments do not match the input schema of the tool
+    #[error("Invalid arguments for tool {tool}:\n{}", .violations.join("\n"))]
+    InvalidArguments {
+        tool: String,
+        violations: Vec<String>,
+    },
+
+    /// Tool result is larger than the server's result size limit
+    #[error("Result of tool {tool} is {size} bytes, over the limit of {limit} bytes")]
+    ResultTooLarge {
+        tool: String,
+        size: usize,
+        limit: usize,
+    },
 }
 /// Result type alias for MCP operations
 pub type Result<T> = std::result::Result<T, McpError>;
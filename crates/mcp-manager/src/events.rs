@@ -0,0 +1,177 @@
+use rmcp::model::{
+    LoggingLevel, LoggingMessageNotificationParam, NumberOrString, ProgressNotificationParam,
+    ProgressToken,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Something a server reported while a tool call was running
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CallEvent {
+    /// A progress notification of the call
+    Progress {
+        /// The progress so far, increasing with each notification
+        progress: f64,
+        /// The progress at completion, if known
+        total: Option<f64>,
+        /// A description of the current step
+        message: Option<String>,
+    },
+    /// A log message of the server, which servers use to stream partial output
+    Log {
+        level: LoggingLevel,
+        /// The name of the logger, if any
+        logger: Option<String>,
+        data: Value,
+    },
+}
+
+/// Receives the events of a call as they arrive
+pub type CallEventSender = UnboundedSender<CallEvent>;
+
+/// The senders of the running calls that asked for their events, by progress token
+#[derive(Default)]
+pub(crate) struct CallListeners {
+    next_token: AtomicI64,
+    /// Client ID and sender of each call
+    listeners: Mutex<HashMap<i64, (String, CallEventSender)>>,
+}
+
+impl CallListeners {
+    fn listeners(&self) -> MutexGuard<'_, HashMap<i64, (String, CallEventSender)>> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Registers the sender of a call to a client until the returned guard is dropped
+    pub(crate) fn listen(self: &Arc<Self>, client_id: &str, sender: CallEventSender) -> Listening {
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        self.listeners()
+            .insert(token, (client_id.to_string(), sender));
+        Listening {
+            listeners: self.clone(),
+            token,
+        }
+    }
+
+    /// Forwards a progress notification to the call holding its token
+    pub(crate) fn progress(&self, params: ProgressNotificationParam) {
+        let ProgressToken(NumberOrString::Number(token)) = params.progress_token else {
+            return;
+        };
+        if let Some((_, sender)) = self.listeners().get(&token) {
+            let _ = sender.send(CallEvent::Progress {
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            });
+        }
+    }
+
+    /// Forwards a log message to every running call to the client that sent it
+    ///
+    /// Log messages are not tied to a request by the protocol, so concurrent calls to the
+    /// same server all receive them.
+    pub(crate) fn log(&self, client_id: &str, params: LoggingMessageNotificationParam) {
+        let event = CallEvent::Log {
+            level: params.level,
+            logger: params.logger,
+            data: params.data,
+        };
+        self.listeners()
+            .values()
+            .filter(|(client, _)| client == client_id)
+            .for_each(|(_, sender)| {
+                let _ = sender.send(event.clone());
+            });
+    }
+}
+
+/// Keeps the sender of a call registered while it is alive
+pub(crate) struct Listening {
+    listeners: Arc<CallListeners>,
+    token: i64,
+}
+
+impl Listening {
+    /// The progress token to send with the call
+    pub(crate) fn token(&self) -> ProgressToken {
+        ProgressToken(NumberOrString::Number(self.token))
+    }
+}
+
+impl Drop for Listening {
+    fn drop(&mut self) {
+        self.listeners.listeners().remove(&self.token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn progress(token: i64, progress: f64) -> ProgressNotificationParam {
+        serde_json::from_value(json!({ "progressToken": token, "progress": progress })).unwrap()
+    }
+
+    fn log(data: &str) -> LoggingMessageNotificationParam {
+        serde_json::from_value(json!({ "level": "info", "data": data })).unwrap()
+    }
+
+    #[test]
+    fn test_progress_reaches_the_call_of_its_token() {
+        let listeners = Arc::new(CallListeners::default());
+        let (first_tx, mut first_rx) = unbounded_channel();
+        let (second_tx, mut second_rx) = unbounded_channel();
+        let first = listeners.listen("fs", first_tx);
+        let _second = listeners.listen("fs", second_tx);
+
+        listeners.progress(progress(1, 0.5));
+        assert!(first_rx.try_recv().is_err());
+        assert_eq!(
+            second_rx.try_recv().unwrap(),
+            CallEvent::Progress {
+                progress: 0.5,
+                total: None,
+                message: None
+            }
+        );
+
+        drop(first);
+        listeners.progress(progress(0, 1.0));
+        assert!(first_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_log_reaches_the_calls_to_its_client() {
+        let listeners = Arc::new(CallListeners::default());
+        let (fs_tx, mut fs_rx) = unbounded_channel();
+        let (web_tx, mut web_rx) = unbounded_channel();
+        let _fs = listeners.listen("fs", fs_tx);
+        let _web = listeners.listen("web", web_tx);
+
+        listeners.log("fs", log("line 1"));
+        assert!(matches!(
+            fs_rx.try_recv().unwrap(),
+            CallEvent::Log { data, .. } if data == json!("line 1")
+        ));
+        assert!(web_rx.try_recv().is_err());
+        assert_eq!(
+            serde_json::to_value(CallEvent::Log {
+                level: LoggingLevel::Info,
+                logger: None,
+                data: json!("line 1"),
+            })
+            .unwrap(),
+            json!({ "type": "log", "level": "info", "logger": null, "data": "line 1" })
+        );
+    }
+}
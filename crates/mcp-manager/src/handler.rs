@@ -0,0 +1,131 @@
+use crate::events::CallListeners;
+use futures::future::BoxFuture;
+use rmcp::model::{
+    ClientCapabilities, ClientInfo, CreateElicitationRequestParams, CreateElicitationResult,
+    CreateMessageRequestMethod, CreateMessageRequestParams, CreateMessageResult,
+    LoggingMessageNotificationParam, ProgressNotificationParam,
+};
+use rmcp::service::{NotificationContext, RequestContext};
+use rmcp::{ClientHandler, ErrorData, RoleClient};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
+
+/// Callback answering a request sent by a server to the client.
+///
+/// Receives the client ID and the request parameters as JSON, and resolves to the
+/// JSON result or an error message.
+pub type RequestCallback =
+    Arc<dyn Fn(String, Value) -> BoxFuture<'static, Result<Value, String>> + Send + Sync>;
+
+/// Callbacks shared by the handlers of all clients of a manager
+#[derive(Default)]
+pub(crate) struct RequestCallbacks {
+    /// Answers `sampling/createMessage` requests
+    pub(crate) sampling: RwLock<Option<RequestCallback>>,
+    /// Answers `elicitation/create` requests
+    pub(crate) elicitation: RwLock<Option<RequestCallback>>,
+    /// Receive the progress and log notifications of running calls
+    pub(crate) calls: Arc<CallListeners>,
+}
+
+impl RequestCallbacks {
+    pub(crate) fn set(slot: &RwLock<Option<RequestCallback>>, callback: Option<RequestCallback>) {
+        *slot
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = callback;
+    }
+
+    fn get(slot: &RwLock<Option<RequestCallback>>) -> Option<RequestCallback> {
+        slot.read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+/// Client handler forwarding server-initiated requests to the registered callbacks
+///
+/// Sampling requests without a callback are answered with "method not found", and
+/// elicitation requests without a callback are declined. Progress and log notifications
+/// are forwarded to the running calls listening for them.
+pub(crate) struct ForwardingHandler {
+    /// ID of the client this handler serves
+    client_id: String,
+    /// Callbacks shared with the manager
+    callbacks: Arc<RequestCallbacks>,
+}
+
+impl ForwardingHandler {
+    pub(crate) fn new(client_id: String, callbacks: Arc<RequestCallbacks>) -> Self {
+        Self {
+            client_id,
+            callbacks,
+        }
+    }
+
+    /// Serializes the parameters, runs the callback and deserializes its result
+    async fn forward<P: Serialize, R: DeserializeOwned>(
+        &self,
+        callback: RequestCallback,
+        params: P,
+    ) -> Result<R, ErrorData> {
+        let params = serde_json::to_value(params)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let result = callback(self.client_id.clone(), params)
+            .await
+            .map_err(|e| ErrorData::internal_error(e, None))?;
+        serde_json::from_value(result)
+            .map_err(|e| ErrorData::internal_error(format!("Invalid callback result: {e}"), None))
+    }
+}
+
+impl ClientHandler for ForwardingHandler {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        match RequestCallbacks::get(&self.callbacks.sampling) {
+            Some(callback) => self.forward(callback, params).await,
+            None => Err(ErrorData::method_not_found::<CreateMessageRequestMethod>()),
+        }
+    }
+
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, ErrorData> {
+        match RequestCallbacks::get(&self.callbacks.elicitation) {
+            Some(callback) => self.forward(callback, request).await,
+            None => serde_json::from_value(json!({ "action": "decline" }))
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None)),
+        }
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.callbacks.calls.progress(params);
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.callbacks.calls.log(&self.client_id, params);
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = ClientInfo::default();
+        info.capabilities = ClientCapabilities::builder()
+            .enable_sampling()
+            .enable_elicitation()
+            .build();
+        info
+    }
+}
@@ -1,24 +1,48 @@
+mod catalog;
+mod client;
 mod error;
+mod events;
+mod handler;
+mod limit;
+mod mcp_json;
+mod policy;
 
+pub use catalog::Catalog;
 pub use error::McpError;
+pub use events::{CallEvent, CallEventSender};
+pub use handler::RequestCallback;
+pub use limit::OversizePolicy;
+pub use policy::{ConfirmationCallback, ToolPolicy};
+use client::{MCPService, ManagedClient};
 use error::McpError::RmcpError;
+use handler::{ForwardingHandler, RequestCallbacks};
+use limit::ResultLimit;
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt, TryFutureExt, stream};
-use rmcp::model::{CallToolRequestParams, Tool};
-use rmcp::service::{DynService, RunningService};
+use http::{HeaderName, HeaderValue};
+use rmcp::model::{CallToolRequestParams, RequestParamsMeta, Tool};
 use rmcp::transport::ConfigureCommandExt;
 use rmcp::transport::child_process::TokioChildProcess;
-use rmcp::transport::streamable_http_client::StreamableHttpClientTransport;
+use rmcp::transport::streamable_http_client::{
+    StreamableHttpClientTransport, StreamableHttpClientTransportConfig,
+};
 use rmcp::transport::worker::WorkerTransport;
-use rmcp::{RoleClient, ServiceExt};
+use rmcp::ServiceExt;
 use serde::{Deserialize, Serialize};
 use serde_json::value::Value;
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 use tokio::process::Command;
 use which::which;
 
+/// Resolves a command to the path of the executable it runs, searching `PATH` like a shell
+pub fn resolve_command(command: &str) -> error::Result<PathBuf> {
+    Ok(which(command)?)
+}
+
 /// Transport protocol types for service communication
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -28,13 +52,14 @@ enum Transport {
     #[default]
     Stdio,
     /// HTTP streaming transport protocol
+    #[serde(alias = "http", alias = "streamable-http", alias = "streamableHttp", alias = "sse")]
     Stream,
     /// Web worker transport protocol
     Worker,
 }
 
 /// Configuration for a single service instance
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ServiceConfig {
     /// Type of transport to use for this service
     #[serde(default, rename = "type")]
@@ -48,6 +73,10 @@ pub struct ServiceConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     url: Option<String>,
 
+    /// HTTP headers sent with every request to stream services
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    headers: HashMap<String, String>,
+
     /// Command-line arguments for stdio services
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     args: Vec<String>,
@@ -55,6 +84,31 @@ pub struct ServiceConfig {
     /// Environment variables for the service process
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     env: HashMap<String, Value>,
+
+    /// Glob patterns of tool names that may be invoked, empty allows all tools
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    allowed_tools: Vec<String>,
+
+    /// Glob patterns of tool names that may never be invoked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocked_tools: Vec<String>,
+
+    /// Maximum number of concurrent tool calls, excess calls are queued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrency: Option<usize>,
+
+    /// Maximum size in bytes of a tool result, unlimited if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_result_size: Option<usize>,
+
+    /// What to do with tool results larger than `max_result_size`
+    #[serde(default)]
+    oversize_policy: OversizePolicy,
+
+    /// Directory oversized results are spilled to, a temporary directory private to the user
+    /// by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spill_dir: Option<PathBuf>,
 }
 
 /// Top-level MCP configuration structure
@@ -64,59 +118,198 @@ pub struct MCPConfig {
     pub servers: HashMap<String, ServiceConfig>,
 }
 
-type MCPService = RunningService<RoleClient, Box<dyn DynService<RoleClient>>>;
-
 /// Inner manager structure handling client connections
 pub struct MCPManager {
     /// Map of client IDs to their running services
-    clients: HashMap<String, MCPService>,
+    clients: RwLock<HashMap<String, Arc<ManagedClient>>>,
+    /// Map of server names that failed to start to the reason of the failure
+    failed: HashMap<String, String>,
+    /// Callback consulted before invoking destructive tools
+    confirmation: RwLock<Option<ConfirmationCallback>>,
+    /// Callbacks answering sampling and elicitation requests sent by servers
+    callbacks: Arc<RequestCallbacks>,
+    /// Tools of servers loaded from a catalog, listed when the server is not connected
+    catalog: RwLock<Catalog>,
 }
 
 type ClientFuture<'a> = BoxFuture<'a, error::Result<MCPService>>;
 
+/// A single tool invocation: client ID, tool name and optional arguments
+pub type ToolCallRequest = (String, String, Option<serde_json::Map<String, Value>>);
+
 impl MCPManager {
     /// Creates a new MCP manager from configuration
     pub async fn create(config: MCPConfig) -> Self {
+        let callbacks = Arc::new(RequestCallbacks::default());
         let clients = stream::iter(config.servers)
-            .map(|(name, config)| async move {
-                let serv_res = match config.service_type {
-                    Transport::Stdio if config.command.is_some() => {
-                        Self::make_stdio_client_future(&config).await
-                    }
-                    Transport::Stream if config.url.is_some() => {
-                        ().into_dyn()
-                            .serve(StreamableHttpClientTransport::from_uri(config.url.unwrap()))
-                            .map_err(|e| McpError::ServiceInitError(Box::new(e)))
-                            .await
-                    }
-                    Transport::Worker if config.url.is_some() => {
-                        ().into_dyn()
-                            .serve(WorkerTransport::from_uri(config.url.unwrap()))
-                            .map_err(|e| McpError::ServiceInitError(Box::new(e)))
-                            .await
-                    }
-                    _ => async { Err(McpError::ServiceNotSupportedError) }.await,
-                };
-                (name, serv_res)
+            .map(|(name, config)| {
+                let handler = ForwardingHandler::new(name.clone(), callbacks.clone());
+                async move {
+                    let policy =
+                        match ToolPolicy::new(&config.allowed_tools, &config.blocked_tools) {
+                            Ok(policy) => policy,
+                            Err(e) => return (name, Err(e)),
+                        };
+                    let serv_res = match config.service_type {
+                        Transport::Stdio if config.command.is_some() => {
+                            Self::make_stdio_client_future(&config, handler).await
+                        }
+                        Transport::Stream if config.url.is_some() => {
+                            match Self::stream_transport_config(&config) {
+                                Ok(transport_config) => {
+                                    handler
+                                        .into_dyn()
+                                        .serve(StreamableHttpClientTransport::from_config(
+                                            transport_config,
+                                        ))
+                                        .map_err(|e| McpError::ServiceInitError(Box::new(e)))
+                                        .await
+                                }
+                                Err(e) => Err(e),
+                            }
+                        }
+                        Transport::Worker if config.url.is_some() => {
+                            handler
+                                .into_dyn()
+                                .serve(WorkerTransport::from_uri(config.url.unwrap()))
+                                .map_err(|e| McpError::ServiceInitError(Box::new(e)))
+                                .await
+                        }
+                        _ => async { Err(McpError::ServiceNotSupportedError) }.await,
+                    };
+                    let limit = config.max_result_size.map(|max_size| {
+                        ResultLimit::new(max_size, config.oversize_policy, config.spill_dir)
+                    });
+                    (
+                        name,
+                        serv_res.map(|serv| {
+                            ManagedClient::new(serv, policy, limit, config.max_concurrency)
+                        }),
+                    )
+                }
             })
             .buffer_unordered(3)
-            .filter_map(|(name, serv_res)| async {
-                match serv_res {
-                    Ok(serv) => Some((name, serv)),
-                    _ => None,
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut failed = HashMap::new();
+        let clients = clients
+            .into_iter()
+            .filter_map(|(name, serv_res)| match serv_res {
+                Ok(client) => Some((name, Arc::new(client))),
+                Err(e) => {
+                    failed.insert(name, e.to_string());
+                    None
+                }
+            })
+            .collect::<HashMap<_, _>>();
+
+        Self {
+            clients: RwLock::new(clients),
+            failed,
+            confirmation: RwLock::new(None),
+            callbacks,
+            catalog: RwLock::new(Catalog::default()),
+        }
+    }
+
+    /// Sets the callback consulted before invoking destructive tools, `None` disables confirmation
+    pub fn set_confirmation_callback(&self, callback: Option<ConfirmationCallback>) {
+        *self
+            .confirmation
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = callback;
+    }
+
+    /// Sets the callback answering `sampling/createMessage` requests from servers
+    ///
+    /// `None` makes the client reply that sampling is not supported.
+    pub fn set_sampling_callback(&self, callback: Option<RequestCallback>) {
+        RequestCallbacks::set(&self.callbacks.sampling, callback);
+    }
+
+    /// Sets the callback answering `elicitation/create` requests from servers
+    ///
+    /// `None` makes the client decline all elicitation requests.
+    pub fn set_elicitation_callback(&self, callback: Option<RequestCallback>) {
+        RequestCallbacks::set(&self.callbacks.elicitation, callback);
+    }
+
+    fn confirmation_callback(&self) -> Option<ConfirmationCallback> {
+        self.confirmation
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn clients(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<ManagedClient>>> {
+        self.clients
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn get_client(&self, client_id: &str) -> error::Result<Arc<ManagedClient>> {
+        self.clients()
+            .get(client_id)
+            .cloned()
+            .ok_or(McpError::ClientNotFound(client_id.to_owned()))
+    }
+
+    /// Shuts down all running services and terminates their child processes
+    ///
+    /// Calls waiting for a concurrency slot fail with [`McpError::ClientClosed`], and the
+    /// manager holds no clients afterwards. Services still serving an in-flight call are
+    /// cancelled without waiting for them to wind down.
+    pub async fn shutdown(&self) {
+        let clients = std::mem::take(
+            &mut *self
+                .clients
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+
+        stream::iter(clients)
+            .for_each_concurrent(None, |(_, client)| async move {
+                client.close();
+                if let Ok(client) = Arc::try_unwrap(client) {
+                    let _ = client.service.cancel().await;
                 }
             })
-            .collect::<HashMap<_, _>>()
             .await;
+    }
 
-        Self { clients }
+    /// Builds the streamable HTTP transport configuration, including custom headers
+    fn stream_transport_config(
+        config: &ServiceConfig,
+    ) -> error::Result<StreamableHttpClientTransportConfig> {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                Ok((
+                    HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| McpError::InvalidHeader(name.clone()))?,
+                    HeaderValue::from_str(value)
+                        .map_err(|_| McpError::InvalidHeader(name.clone()))?,
+                ))
+            })
+            .collect::<error::Result<HashMap<_, _>>>()?;
+
+        Ok(
+            StreamableHttpClientTransportConfig::with_uri(config.url.clone().unwrap_or_default())
+                .custom_headers(headers),
+        )
     }
 
-    fn make_stdio_client_future(config: &'_ ServiceConfig) -> ClientFuture<'_> {
+    fn make_stdio_client_future(
+        config: &'_ ServiceConfig,
+        handler: ForwardingHandler,
+    ) -> ClientFuture<'_> {
         let cmd_str = config.command.as_ref().unwrap();
 
-        let cmd = match which(cmd_str) {
+        let cmd = match resolve_command(cmd_str) {
             Ok(cmd_path) => Command::new(cmd_path.as_os_str()).configure(|cmd| {
+                cmd.kill_on_drop(true);
                 cmd.args(config.args.iter().map(OsStr::new));
                 cmd.envs(config.env.iter().map(|(k, v)| {
                     let v = match v {
@@ -126,12 +319,13 @@ impl MCPManager {
                     (k.clone(), v)
                 }));
             }),
-            Err(e) => return async move { Err(McpError::CommandNotFound(e)) }.boxed(),
+            Err(e) => return async move { Err(e) }.boxed(),
         };
 
         match TokioChildProcess::new(cmd) {
             Ok(proc) => {
-                ().into_dyn()
+                handler
+                    .into_dyn()
                     .serve(proc)
                     .map_err(|e| McpError::ServiceInitError(Box::new(e)))
                     .boxed()
@@ -141,9 +335,8 @@ impl MCPManager {
     }
 
     pub async fn ping(&self, client_id: &str) -> error::Result<bool> {
-        self.clients
-            .get(client_id)
-            .ok_or(McpError::ClientNotFound(client_id.to_string()))?
+        self.get_client(client_id)?
+            .service
             .list_tools(None)
             .await
             .map(|_| true)
@@ -152,21 +345,109 @@ impl MCPManager {
 
     /// Returns a list of all server names currently managed by the MCP manager
     pub fn server_list(&self) -> Vec<String> {
-        self.clients.keys().cloned().collect()
+        self.clients().keys().cloned().collect()
     }
 
     /// Returns the number of servers currently managed by the MCP manager
     pub fn server_count(&self) -> usize {
-        self.clients.len()
+        self.clients().len()
     }
 
-    /// Lists available tools from a client
-    pub async fn list_tools(&self, client_id: &str) -> error::Result<Vec<Tool>> {
-        self.clients
+    /// Returns the servers that failed to start, mapped to the error that occurred
+    pub fn failed_servers(&self) -> &HashMap<String, String> {
+        &self.failed
+    }
+
+    /// Writes the tools of every connected server to a catalog file
+    ///
+    /// Tools blocked by a server's policy are left out. Returns the number of tools written.
+    pub async fn export_catalog<P: AsRef<Path>>(&self, path: P) -> error::Result<usize> {
+        let mut catalog = Catalog::default();
+        for client_id in self.server_list() {
+            let tools = self.list_tools(&client_id).await?;
+            catalog.servers.insert(client_id, tools);
+        }
+        catalog.to_file(path)?;
+        Ok(catalog.tool_count())
+    }
+
+    /// Loads a catalog written by [`MCPManager::export_catalog`], replacing any loaded before
+    ///
+    /// The tools of servers that are not connected are then listed from the catalog, so stub
+    /// generation and planning work offline. Calling them still fails with
+    /// [`McpError::ClientNotFound`]. Returns the number of servers in the catalog.
+    pub fn load_catalog<P: AsRef<Path>>(&self, path: P) -> error::Result<usize> {
+        let catalog = Catalog::from_file(path)?;
+        let count = catalog.servers.len();
+        *self
+            .catalog
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = catalog;
+        Ok(count)
+    }
+
+    /// Returns the servers of the loaded catalog, connected or not
+    pub fn catalog_servers(&self) -> Vec<String> {
+        self.catalog
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .servers
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn catalog_tools(&self, client_id: &str) -> Option<Vec<Tool>> {
+        self.catalog
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .servers
             .get(client_id)
-            .ok_or(McpError::ClientNotFound(client_id.to_owned()))
-            .map(|client| async move { client.list_all_tools().await.map_err(RmcpError) })?
-            .await
+            .cloned()
+    }
+
+    /// Lists available tools from a client, omitting tools blocked by its policy
+    ///
+    /// If the client is not connected, the tools of the loaded catalog are listed instead.
+    pub async fn list_tools(&self, client_id: &str) -> error::Result<Vec<Tool>> {
+        let client = match self.get_client(client_id) {
+            Ok(client) => client,
+            Err(e) => return self.catalog_tools(client_id).ok_or(e),
+        };
+        let tools = client.service.list_all_tools().await.map_err(RmcpError)?;
+        client.cache_tools(tools.clone());
+        Ok(tools
+            .into_iter()
+            .filter(|tool| client.policy.permits(&tool.name))
+            .collect())
+    }
+
+    /// Checks the arguments of a call against the input schema of the tool
+    ///
+    /// The schema is taken from the tools the client last listed, listing them first if they
+    /// never were. Arguments the cached schema rejects are checked again against a fresh
+    /// listing, in case the server changed the tool since. Calls to tools the server does not
+    /// list, or whose tools cannot be listed, are left for the server to judge.
+    async fn check_arguments(
+        &self,
+        client_id: &str,
+        client: &ManagedClient,
+        tool_name: &str,
+        arguments: Option<&serde_json::Map<String, Value>>,
+    ) -> error::Result<()> {
+        let check = |tools: &[Tool]| match tools.iter().find(|tool| tool.name == tool_name) {
+            Some(tool) => policy::check_arguments(tool, arguments),
+            None => Ok(()),
+        };
+        if let Some(tools) = client.cached_tools()
+            && check(&tools).is_ok()
+        {
+            return Ok(());
+        }
+        match self.list_tools(client_id).await {
+            Ok(tools) => check(&tools),
+            Err(_) => Ok(()),
+        }
     }
     /// Retrieves a specific tool from a client by name
     ///
@@ -180,44 +461,108 @@ impl MCPManager {
     /// * `Result<Tool>` - The requested tool if found, or an error if the client
     ///   doesn't exist, there's a communication issue, or the tool is not found
     pub async fn get_tool(&self, client_id: &str, tool_name: &str) -> error::Result<Tool> {
-        self.clients
-            .get(client_id)
-            .ok_or(McpError::ClientNotFound(client_id.to_owned()))
-            .map(|client| async move {
-                client
-                    .list_all_tools()
-                    .await
-                    .map_err(RmcpError)?
-                    .into_iter()
-                    .filter(|tool| tool.name == tool_name)
-                    .last()
-                    .ok_or(McpError::ToolNotFound(tool_name.to_string()))
-            })?
-            .await
+        self.list_tools(client_id)
+            .await?
+            .into_iter()
+            .filter(|tool| tool.name == tool_name)
+            .last()
+            .ok_or(McpError::ToolNotFound(tool_name.to_string()))
     }
     /// Executes a tool on a client
+    ///
+    /// The call is refused if the tool is blocked by the client's policy, if the arguments
+    /// do not match the input schema of the tool, or if the tool is destructive and the
+    /// confirmation callback rejects it. While a confirmation callback is set, a tool that
+    /// cannot be found in the tool list of the client is refused as well. If the client has a
+    /// concurrency limit, the call waits until a slot is free. Results larger than the
+    /// client's result size limit are handled according to its oversize policy.
     pub async fn call_tool(
         &self,
         client_id: &str,
         tool_name: &str,
         arguments: Option<serde_json::Map<String, Value>>,
     ) -> error::Result<rmcp::model::CallToolResult> {
-        self.clients
-            .get(client_id)
-            .ok_or(McpError::ClientNotFound(client_id.to_owned()))
-            .map(|client| {
-                client.call_tool(
-                    CallToolRequestParams::new(tool_name.to_string())
-                        .with_arguments(arguments.unwrap_or_default()),
-                )
-            })?
+        self.call(client_id, tool_name, arguments, None).await
+    }
+
+    /// Executes a tool on a client, sending what the server reports while it runs to `events`
+    ///
+    /// The call carries a progress token, so the progress notifications of the server reach
+    /// `events` as they arrive, as do the log messages it sends meanwhile, through which
+    /// servers stream partial output. The call is otherwise the same as [`MCPManager::call_tool`].
+    pub async fn call_tool_streaming(
+        &self,
+        client_id: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Map<String, Value>>,
+        events: CallEventSender,
+    ) -> error::Result<rmcp::model::CallToolResult> {
+        self.call(client_id, tool_name, arguments, Some(events)).await
+    }
+
+    async fn call(
+        &self,
+        client_id: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Map<String, Value>>,
+        events: Option<CallEventSender>,
+    ) -> error::Result<rmcp::model::CallToolResult> {
+        let client = self.get_client(client_id)?;
+        client.policy.check(tool_name)?;
+        self.check_arguments(client_id, &client, tool_name, arguments.as_ref())
+            .await?;
+
+        if let Some(confirm) = self.confirmation_callback() {
+            // A tool that cannot be resolved may be destructive, so the call is refused.
+            let tool = self.get_tool(client_id, tool_name).await?;
+            if policy::is_destructive(&tool) && !confirm(client_id, tool_name, arguments.as_ref()) {
+                return Err(McpError::ToolCallRejected(tool_name.to_string()));
+            }
+        }
+
+        let _permit = client.acquire(client_id).await?;
+        let mut params = CallToolRequestParams::new(tool_name.to_string())
+            .with_arguments(arguments.unwrap_or_default());
+        let _listening = events.map(|events| {
+            let listening = self.callbacks.calls.listen(client_id, events);
+            params.set_progress_token(listening.token());
+            listening
+        });
+        let result = client
+            .service
+            .call_tool(params)
             .await
-            .map_err(RmcpError)
+            .map_err(RmcpError)?;
+        match &client.limit {
+            Some(limit) => limit.apply(tool_name, result),
+            None => Ok(result),
+        }
+    }
+
+    /// Executes multiple tool calls concurrently
+    ///
+    /// At most `concurrency` calls are in flight at once, per-client concurrency limits
+    /// still apply. Results are returned in the order of `requests`.
+    pub async fn call_tools_batch(
+        &self,
+        requests: Vec<ToolCallRequest>,
+        concurrency: usize,
+    ) -> Vec<error::Result<rmcp::model::CallToolResult>> {
+        let mut results = stream::iter(requests.into_iter().enumerate())
+            .map(|(idx, (client_id, tool_name, arguments))| async move {
+                (idx, self.call_tool(&client_id, &tool_name, arguments).await)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        results.sort_by_key(|(idx, _)| *idx);
+        results.into_iter().map(|(_, res)| res).collect()
     }
 
     /// Checks if a client with the given ID exists in the manager
     pub fn has_client(&self, client_id: &str) -> bool {
-        self.clients.contains_key(client_id)
+        self.clients().contains_key(client_id)
     }
 
     /// Checks if a specific tool exists for a given client
@@ -232,17 +577,19 @@ impl MCPManager {
     /// * `Result<bool>` - Ok(true) if the tool exists, Ok(false) if it doesn't,
     ///   or an error if the client doesn't exist or there's a communication issue
     pub async fn has_tool(&self, client_id: &str, tool_name: &str) -> error::Result<bool> {
-        self.clients
-            .get(client_id)
-            .map(|client| async move {
-                client
-                    .list_all_tools()
-                    .await
-                    .map(|tools| tools.iter().any(|t| t.name == tool_name))
-                    .map_err(RmcpError)
-            })
-            .ok_or(McpError::ClientNotFound(client_id.to_owned()))?
+        self.list_tools(client_id)
             .await
+            .map(|tools| tools.iter().any(|t| t.name == tool_name))
+    }
+}
+
+impl Drop for MCPManager {
+    fn drop(&mut self) {
+        self.clients
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .drain()
+            .for_each(|(_, client)| client.close());
     }
 }
 
@@ -263,6 +610,7 @@ mod tests {
                 args: vec!["test".to_string()],
                 url: None,
                 env: HashMap::new(),
+                ..Default::default()
             },
         );
 
@@ -270,6 +618,12 @@ mod tests {
         let manager = MCPManager::create(config).await;
 
         assert_eq!(manager.server_count(), 0);
+        assert!(
+            manager
+                .failed_servers()
+                .get("test_stdio")
+                .is_some_and(|e| e.starts_with("Command not found"))
+        );
     }
 
     #[tokio::test]
@@ -283,6 +637,7 @@ mod tests {
                 args: vec![],
                 url: None,
                 env: HashMap::new(),
+                ..Default::default()
             },
         );
 
@@ -295,6 +650,33 @@ mod tests {
                 .server_list()
                 .contains(&"invalid_service".to_string())
         );
+        assert_eq!(
+            manager.failed_servers().get("invalid_service"),
+            Some(&"Service not supported".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mcp_manager_create_invalid_policy() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "bad_policy".to_string(),
+            ServiceConfig {
+                service_type: Transport::Stdio,
+                command: Some("nonexist".to_string()),
+                blocked_tools: vec!["[".to_string()],
+                ..Default::default()
+            },
+        );
+
+        let manager = MCPManager::create(MCPConfig { servers }).await;
+
+        assert!(
+            manager
+                .failed_servers()
+                .get("bad_policy")
+                .is_some_and(|e| e.starts_with("Invalid tool pattern"))
+        );
     }
 
     #[tokio::test]
@@ -341,6 +723,77 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_mcp_manager_call_tools_batch_keeps_order() {
+        let manager = MCPManager::create(MCPConfig {
+            servers: HashMap::new(),
+        })
+        .await;
+
+        let results = manager
+            .call_tools_batch(
+                vec![
+                    ("first".to_string(), "tool".to_string(), None),
+                    ("second".to_string(), "tool".to_string(), None),
+                    ("third".to_string(), "tool".to_string(), None),
+                ],
+                2,
+            )
+            .await;
+
+        let clients = results
+            .into_iter()
+            .map(|res| match res {
+                Err(McpError::ClientNotFound(client)) => client,
+                _ => panic!("Expected ClientNotFound error"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(clients, vec!["first", "second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_mcp_manager_catalog_offline() {
+        let manager = MCPManager::create(MCPConfig {
+            servers: HashMap::new(),
+        })
+        .await;
+        let dir = std::env::temp_dir().join(format!("mcp-offline-{}", std::process::id()));
+        let path = dir.join("catalog.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &path,
+            r#"{"servers": {"fs": [{"name": "read_file", "inputSchema": {"type": "object"}}]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(manager.load_catalog(&path).unwrap(), 1);
+        assert_eq!(manager.catalog_servers(), vec!["fs"]);
+        assert!(manager.has_tool("fs", "read_file").await.unwrap());
+        assert!(matches!(
+            manager.call_tool("fs", "read_file", None).await,
+            Err(McpError::ClientNotFound(_))
+        ));
+        assert!(matches!(
+            manager.list_tools("other").await,
+            Err(McpError::ClientNotFound(_))
+        ));
+
+        assert_eq!(manager.export_catalog(&path).await.unwrap(), 0);
+        assert_eq!(manager.load_catalog(&path).unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mcp_manager_shutdown_empty() {
+        let manager = MCPManager::create(MCPConfig {
+            servers: HashMap::new(),
+        })
+        .await;
+
+        manager.shutdown().await;
+        assert_eq!(manager.server_count(), 0);
+    }
+
     #[test]
     fn test_mcp_error_display() {
         let client_not_found = McpError::ClientNotFound("test_client".to_string());
@@ -368,6 +821,7 @@ mod tests {
                 map.insert("TEST_ENV".to_string(), json!("test_value"));
                 map
             },
+            ..Default::default()
         };
 
         let serialized = serde_json::to_string(&config).unwrap();
@@ -382,4 +836,32 @@ mod tests {
         assert_eq!(deserialized.url, None);
         assert_eq!(deserialized.env.get("TEST_ENV"), Some(&json!("test_value")));
     }
+
+    #[test]
+    fn test_service_config_max_concurrency() {
+        let config: ServiceConfig =
+            serde_json::from_value(json!({"command": "cmd", "max_concurrency": 1})).unwrap();
+        assert_eq!(config.max_concurrency, Some(1));
+
+        let config: ServiceConfig = serde_json::from_value(json!({"command": "cmd"})).unwrap();
+        assert_eq!(config.max_concurrency, None);
+    }
+
+    #[test]
+    fn test_service_config_result_limit() {
+        let config: ServiceConfig = serde_json::from_value(json!({
+            "command": "cmd",
+            "max_result_size": 65536,
+            "oversize_policy": "spill",
+            "spill_dir": "/tmp/spill"
+        }))
+        .unwrap();
+        assert_eq!(config.max_result_size, Some(65536));
+        assert_eq!(config.oversize_policy, OversizePolicy::Spill);
+        assert_eq!(config.spill_dir, Some(PathBuf::from("/tmp/spill")));
+
+        let config: ServiceConfig = serde_json::from_value(json!({"command": "cmd"})).unwrap();
+        assert_eq!(config.max_result_size, None);
+        assert_eq!(config.oversize_policy, OversizePolicy::Truncate);
+    }
 }
@@ -0,0 +1,346 @@
+//! Size limits on the results of tool calls.
+//!
+//! Scraping and search tools can return megabytes of text, which would end up whole in a
+//! prompt. A limit is enforced on the result as returned by the server, before it is handed
+//! to the caller, by cutting it, by moving it to a file, or by failing the call. The size of
+//! a result is the number of bytes of its text contents, plus the JSON size of its other
+//! contents and of its structured content.
+
+use crate::error::{McpError, Result};
+use rmcp::model::{CallToolResult, Content, RawContent};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Distinguishes the files spilled within the same instant
+static SPILL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Name of the default spill directory in the system temporary directory
+const SPILL_DIRNAME: &str = "fabricatio-mcp";
+
+/// What to do with a tool result larger than the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizePolicy {
+    /// Cut the result at the limit and append a marker telling how much was omitted
+    #[default]
+    Truncate,
+    /// Write the whole result to a file and return the path of the file instead
+    Spill,
+    /// Fail the call with [`McpError::ResultTooLarge`]
+    Error,
+}
+
+/// Size cap applied to the results of the tools of a server
+#[derive(Debug, Clone)]
+pub(crate) struct ResultLimit {
+    /// Maximum size of a result in bytes
+    max_size: usize,
+    /// What to do with larger results
+    policy: OversizePolicy,
+    /// Directory spilled results are written to, the private default one if `None`
+    spill_dir: Option<PathBuf>,
+}
+
+fn content_size(content: &Content) -> usize {
+    match &content.raw {
+        RawContent::Text(text) => text.text.len(),
+        other => serde_json::to_string(other).map_or(0, |json| json.len()),
+    }
+}
+
+fn result_size(result: &CallToolResult) -> usize {
+    result.content.iter().map(content_size).sum::<usize>()
+        + result
+            .structured_content
+            .as_ref()
+            .and_then(|value| serde_json::to_string(value).ok())
+            .map_or(0, |json| json.len())
+}
+
+/// Creates the default spill directory, private to the current user
+///
+/// The system temporary directory being shared between users, the directory is suffixed
+/// with the user id and created with mode 0700. It is refused if it is not a directory of
+/// its own, is owned by another user or is open to others, since another user could then
+/// read the spilled results or swap them.
+#[cfg(unix)]
+fn private_spill_dir() -> io::Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    // SAFETY: `geteuid` has no preconditions and cannot fail.
+    let uid = unsafe { libc::geteuid() };
+    let dir = env::temp_dir().join(format!("{SPILL_DIRNAME}-{uid}"));
+    if let Err(e) = fs::DirBuilder::new().mode(0o700).create(&dir)
+        && e.kind() != io::ErrorKind::AlreadyExists
+    {
+        return Err(e);
+    }
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "The spill directory {} is not private to the current user",
+                dir.display()
+            ),
+        ));
+    }
+    Ok(dir)
+}
+
+/// Creates the default spill directory, the temporary directory being per user already
+#[cfg(not(unix))]
+fn private_spill_dir() -> io::Result<PathBuf> {
+    let dir = env::temp_dir().join(SPILL_DIRNAME);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Largest index not above `index` that falls on a char boundary of `s`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len()))
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+impl ResultLimit {
+    /// Builds the limit of a server, the default spill directory is `fabricatio-mcp-<uid>` in
+    /// the system temporary directory, private to the current user
+    pub(crate) fn new(max_size: usize, policy: OversizePolicy, spill_dir: Option<PathBuf>) -> Self {
+        Self {
+            max_size,
+            policy,
+            spill_dir,
+        }
+    }
+
+    /// Applies the limit to the result of a call, results within the limit are left untouched
+    ///
+    /// Oversized results lose their structured content, which by convention duplicates a
+    /// text content.
+    pub(crate) fn apply(
+        &self,
+        tool_name: &str,
+        mut result: CallToolResult,
+    ) -> Result<CallToolResult> {
+        let size = result_size(&result);
+        if size <= self.max_size {
+            return Ok(result);
+        }
+        match self.policy {
+            OversizePolicy::Error => Err(McpError::ResultTooLarge {
+                tool: tool_name.to_string(),
+                size,
+                limit: self.max_size,
+            }),
+            OversizePolicy::Truncate => {
+                result.structured_content = None;
+                result.content = self.truncate(std::mem::take(&mut result.content), size);
+                Ok(result)
+            }
+            OversizePolicy::Spill => {
+                let path = self.spill(tool_name, &result)?;
+                result.structured_content = None;
+                result.content = vec![Content::text(format!(
+                    "[The result of `{tool_name}` is {size} bytes, over the limit of {} bytes, it was saved to {}]",
+                    self.max_size,
+                    path.display()
+                ))];
+                Ok(result)
+            }
+        }
+    }
+
+    /// Keeps the contents in order until the limit is reached, cutting the text content that
+    /// crosses it, and appends a marker content
+    fn truncate(&self, contents: Vec<Content>, size: usize) -> Vec<Content> {
+        let mut budget = self.max_size;
+        let mut kept = Vec::new();
+        for mut content in contents {
+            let len = content_size(&content);
+            if len <= budget {
+                budget -= len;
+                kept.push(content);
+                continue;
+            }
+            if let RawContent::Text(text) = &mut content.raw {
+                let end = floor_char_boundary(&text.text, budget);
+                if end > 0 {
+                    text.text.truncate(end);
+                    budget -= end;
+                    kept.push(content);
+                }
+            }
+            break;
+        }
+        kept.push(Content::text(format!(
+            "[truncated: {} of {size} bytes omitted, over the limit of {} bytes]",
+            size - (self.max_size - budget),
+            self.max_size
+        )));
+        kept
+    }
+
+    /// Writes the whole result to a new file of the spill directory
+    ///
+    /// Text contents are written as is, other contents and the structured content as JSON,
+    /// each on its own lines.
+    fn spill(&self, tool_name: &str, result: &CallToolResult) -> Result<PathBuf> {
+        let mut parts = result
+            .content
+            .iter()
+            .map(|content| match &content.raw {
+                RawContent::Text(text) => text.text.clone(),
+                other => serde_json::to_string(other).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        if let Some(structured) = &result.structured_content {
+            parts.push(structured.to_string());
+        }
+
+        let stem: String = tool_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let dir = match &self.spill_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                dir.clone()
+            }
+            None => private_spill_dir()?,
+        };
+        let path = dir.join(format!(
+            "{stem}-{}-{nanos}-{}.txt",
+            process::id(),
+            SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, parts.join("\n"))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(result: &CallToolResult) -> Vec<String> {
+        result
+            .content
+            .iter()
+            .map(|c| c.as_text().map(|t| t.text.clone()).unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_within_limit_is_untouched() {
+        let limit = ResultLimit::new(5, OversizePolicy::Error, None);
+        let result = limit
+            .apply(
+                "tool",
+                CallToolResult::success(vec![Content::text("hello")]),
+            )
+            .unwrap();
+        assert_eq!(texts(&result), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let limit = ResultLimit::new(9, OversizePolicy::Truncate, None);
+        let result = limit
+            .apply(
+                "tool",
+                CallToolResult::success(vec![
+                    Content::text("abcde"),
+                    Content::text("fghéij"),
+                    Content::text("klm"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            texts(&result),
+            vec![
+                "abcde",
+                "fgh",
+                "[truncated: 7 of 15 bytes omitted, over the limit of 9 bytes]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let limit = ResultLimit::new(2, OversizePolicy::Error, None);
+        assert!(matches!(
+            limit.apply(
+                "scrape",
+                CallToolResult::success(vec![Content::text("abc")])
+            ),
+            Err(McpError::ResultTooLarge {
+                size: 3,
+                limit: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_spill() {
+        let dir = env::temp_dir().join(format!("fabricatio-mcp-test-{}", process::id()));
+        let limit = ResultLimit::new(4, OversizePolicy::Spill, Some(dir.clone()));
+        let result = limit
+            .apply(
+                "web/scrape",
+                CallToolResult::success(vec![Content::text("first"), Content::text("second")]),
+            )
+            .unwrap();
+
+        let marker = texts(&result).pop().unwrap();
+        assert_eq!(result.content.len(), 1);
+        let path = marker
+            .rsplit_once("saved to ")
+            .map(|(_, path)| PathBuf::from(path.trim_end_matches(']')))
+            .unwrap();
+        assert!(path.starts_with(&dir));
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("web_scrape-")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_spill_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = private_spill_dir().unwrap();
+        assert_eq!(private_spill_dir().unwrap(), dir);
+        assert_eq!(
+            fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let refused = private_spill_dir();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(refused.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+}
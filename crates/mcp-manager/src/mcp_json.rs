@@ -0,0 +1,93 @@
+//! Loading of the `mcp.json` layout shared by common MCP clients.
+//!
+//! ```json
+//! {
+//!   "mcpServers": {
+//!     "filesystem": { "command": "npx", "args": ["-y", "@modelcontextprotocol/server-filesystem", "."] },
+//!     "remote": { "url": "https://example.com/mcp", "headers": { "Authorization": "Bearer ..." } }
+//!   }
+//! }
+//! ```
+
+use crate::error::Result;
+use crate::{MCPConfig, ServiceConfig, Transport};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Top-level layout of an `mcp.json` file
+#[derive(Deserialize)]
+struct McpJson {
+    #[serde(rename = "mcpServers", default)]
+    mcp_servers: HashMap<String, Map<String, Value>>,
+}
+
+impl MCPConfig {
+    /// Loads the servers of an `mcp.json` file
+    ///
+    /// See [`MCPConfig::from_json`] for how entries are interpreted.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the servers of an `mcp.json` document
+    ///
+    /// Entries marked `"disabled": true` are skipped. Entries without a `type` are stdio
+    /// services if they have a `command`, and stream services if they only have a `url`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let servers = serde_json::from_str::<McpJson>(json)?
+            .mcp_servers
+            .into_iter()
+            .filter(|(_, entry)| entry.get("disabled") != Some(&Value::Bool(true)))
+            .map(|(name, entry)| {
+                let has_type = entry.contains_key("type");
+                let mut config: ServiceConfig = serde_json::from_value(Value::Object(entry))?;
+                if !has_type && config.command.is_none() && config.url.is_some() {
+                    config.service_type = Transport::Stream;
+                }
+                Ok((name, config))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(Self { servers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::McpError;
+
+    #[test]
+    fn test_from_json() {
+        let config = MCPConfig::from_json(
+            r#"{
+                "mcpServers": {
+                    "fs": {"command": "npx", "args": ["-y", "server-fs"], "env": {"DEBUG": "1"}},
+                    "remote": {"url": "https://example.com/mcp", "headers": {"Authorization": "Bearer t"}},
+                    "legacy": {"type": "sse", "url": "https://example.com/sse"},
+                    "off": {"command": "npx", "disabled": true}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.servers.len(), 3);
+        let fs = &config.servers["fs"];
+        assert_eq!(fs.service_type, Transport::Stdio);
+        assert_eq!(fs.args, vec!["-y", "server-fs"]);
+        let remote = &config.servers["remote"];
+        assert_eq!(remote.service_type, Transport::Stream);
+        assert_eq!(remote.headers["Authorization"], "Bearer t");
+        assert_eq!(config.servers["legacy"].service_type, Transport::Stream);
+    }
+
+    #[test]
+    fn test_from_json_invalid() {
+        assert!(matches!(
+            MCPConfig::from_json(r#"{"mcpServers": {"bad": {"args": "not a list"}}}"#),
+            Err(McpError::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::{McpError, Result};
+use glob::Pattern;
+use rmcp::model::Tool;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Callback consulted before a destructive tool is invoked.
+///
+/// Receives the client ID, the tool name and the call arguments, and returns
+/// whether the call may proceed.
+pub type ConfirmationCallback =
+    Arc<dyn Fn(&str, &str, Option<&Map<String, Value>>) -> bool + Send + Sync>;
+
+/// Allow/deny rules restricting which tools of a server may be invoked
+#[derive(Debug, Clone, Default)]
+pub struct ToolPolicy {
+    /// Patterns a tool name must match to be callable, empty means all tools are allowed
+    allowed: Vec<Pattern>,
+    /// Patterns that make a tool uncallable, takes precedence over `allowed`
+    blocked: Vec<Pattern>,
+}
+
+impl ToolPolicy {
+    /// Builds a policy from allow and block glob patterns
+    pub fn new(allowed: &[String], blocked: &[String]) -> Result<Self> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Pattern::new(p).map_err(McpError::InvalidPattern))
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            allowed: compile(allowed)?,
+            blocked: compile(blocked)?,
+        })
+    }
+
+    /// Checks whether the tool with the given name may be invoked
+    pub fn permits(&self, tool_name: &str) -> bool {
+        !self.blocked.iter().any(|p| p.matches(tool_name))
+            && (self.allowed.is_empty() || self.allowed.iter().any(|p| p.matches(tool_name)))
+    }
+
+    /// Returns an error if the tool with the given name may not be invoked
+    pub fn check(&self, tool_name: &str) -> Result<()> {
+        if self.permits(tool_name) {
+            Ok(())
+        } else {
+            Err(McpError::ToolBlocked(tool_name.to_string()))
+        }
+    }
+}
+
+/// Checks whether a tool declares itself as destructive through its annotations
+///
+/// Follows the MCP defaults: a tool that is not read-only is destructive unless
+/// it explicitly states otherwise, so tools without annotations are destructive.
+pub fn is_destructive(tool: &Tool) -> bool {
+    tool.annotations.as_ref().is_none_or(|annotations| {
+        !annotations.read_only_hint.unwrap_or(false)
+            && annotations.destructive_hint.unwrap_or(true)
+    })
+}
+
+/// Returns an error listing every violation if the arguments do not match the input schema
+/// of the tool
+///
+/// Missing arguments are validated as an empty object, as they are sent.
+pub fn check_arguments(tool: &Tool, arguments: Option<&Map<String, Value>>) -> Result<()> {
+    let arguments = Value::Object(arguments.cloned().unwrap_or_default());
+    let violations = signify::validate(&tool.schema_as_json_value(), &arguments);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(McpError::InvalidArguments {
+            tool: tool.name.to_string(),
+            violations: violations.iter().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty_policy_permits_everything() {
+        let policy = ToolPolicy::default();
+        assert!(policy.permits("read_file"));
+        assert!(policy.permits("delete_file"));
+    }
+
+    #[test]
+    fn test_blocked_takes_precedence() {
+        let policy = ToolPolicy::new(
+            &["*_file".to_string()],
+            &["delete_*".to_string()],
+        )
+        .unwrap();
+        assert!(policy.permits("read_file"));
+        assert!(!policy.permits("delete_file"));
+        assert!(!policy.permits("list_dir"));
+        assert!(matches!(
+            policy.check("delete_file"),
+            Err(McpError::ToolBlocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(matches!(
+            ToolPolicy::new(&["[".to_string()], &[]),
+            Err(McpError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_destructive() {
+        let tool = |annotations: Value| -> Tool {
+            let mut tool = json!({"name": "write_file", "inputSchema": {"type": "object"}});
+            if !annotations.is_null() {
+                tool["annotations"] = annotations;
+            }
+            serde_json::from_value(tool).unwrap()
+        };
+
+        assert!(is_destructive(&tool(Value::Null)));
+        assert!(is_destructive(&tool(json!({}))));
+        assert!(is_destructive(&tool(json!({"destructiveHint": true}))));
+        assert!(!is_destructive(&tool(json!({"readOnlyHint": true}))));
+        assert!(!is_destructive(&tool(json!({"destructiveHint": false}))));
+    }
+
+    #[test]
+    fn test_check_arguments() {
+        let tool: Tool = serde_json::from_value(json!({
+            "name": "read_file",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"path": {"type": "string"}, "limit": {"type": "integer"}},
+                "required": ["path"]
+            }
+        }))
+        .unwrap();
+        let arguments = |value: Value| value.as_object().cloned();
+
+        assert!(check_arguments(&tool, arguments(json!({"path": "a.txt"})).as_ref()).is_ok());
+        match check_arguments(&tool, arguments(json!({"limit": "ten"})).as_ref()) {
+            Err(McpError::InvalidArguments { tool, violations }) => {
+                assert_eq!(tool, "read_file");
+                assert_eq!(
+                    violations,
+                    vec![
+                        "$: missing required property `path`",
+                        "$.limit: expected integer, got string \"ten\"",
+                    ]
+                );
+            }
+            other => panic!("Expected InvalidArguments error, got {other:?}"),
+        }
+        assert!(check_arguments(&tool, None).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Entry points of installed packages.
+//!
+//! Entry points are declared in the `entry_points.txt` file of a `.dist-info` or `.egg-info`
+//! directory, an INI file whose sections are groups such as `console_scripts` and whose
+//! entries map a name to an object reference of the form `module:attr [extras]`. Files are
+//! parsed lazily per package, like METADATA files.
+
+use crate::PythonPackageScanner;
+use std::fs;
+use std::sync::Arc;
+
+/// The group of the command line scripts installed with a package.
+pub const CONSOLE_SCRIPTS: &str = "console_scripts";
+
+/// An entry point declared by an installed package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    /// Name of the entry point, e.g. the command of a console script.
+    pub name: String,
+    /// Group of the entry point, e.g. `console_scripts`.
+    pub group: String,
+    /// The object reference, e.g. `package.module:function [extra]`.
+    pub value: String,
+    /// The module part of the object reference.
+    pub module: String,
+    /// The attribute part of the object reference, `None` if the module itself is referenced.
+    pub attr: Option<String>,
+    /// Extras of the package the entry point requires.
+    pub extras: Vec<String>,
+    /// Name of the package declaring the entry point.
+    pub package: String,
+}
+
+impl PythonPackageScanner {
+    /// Returns the entry points declared by an installed package.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the package.
+    ///
+    /// # Returns
+    ///
+    /// The entries of the package's `entry_points.txt`, empty if it has none, or `None` if the
+    /// package is not installed.
+    pub fn entry_points_of(&self, name: &str) -> Option<Arc<Vec<EntryPoint>>> {
+        let key = self.package_key(name)?;
+        let pkg_root = self.known_packages.get(key.as_str())?;
+        Some(self.entry_points.get_with_by_ref(key.as_str(), || {
+            Arc::new(Self::parse_entry_points(
+                &key,
+                &fs::read_to_string(pkg_root.join("entry_points.txt")).unwrap_or_default(),
+            ))
+        }))
+    }
+
+    /// Lists the entry points declared by all installed packages.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Only list the entry points of this group, if given.
+    ///
+    /// # Returns
+    ///
+    /// The entry points sorted by group, name and package.
+    pub fn list_entry_points(&self, group: Option<&str>) -> Vec<EntryPoint> {
+        let mut entry_points: Vec<EntryPoint> = self
+            .known_packages
+            .iter()
+            .filter_map(|(key, _)| self.entry_points_of(&key))
+            .flat_map(|entries| {
+                entries
+                    .iter()
+                    .filter(|entry| group.is_none_or(|group| entry.group == group))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        entry_points
+            .sort_by(|a, b| (&a.group, &a.name, &a.package).cmp(&(&b.group, &b.name, &b.package)));
+        entry_points
+    }
+
+    /// Lists the console scripts installed with all packages.
+    ///
+    /// # Returns
+    ///
+    /// Pairs of the command and its object reference, sorted by command.
+    pub fn get_console_scripts(&self) -> Vec<(String, String)> {
+        self.list_entry_points(Some(CONSOLE_SCRIPTS))
+            .into_iter()
+            .map(|entry| (entry.name, entry.value))
+            .collect()
+    }
+
+    /// Parses an `entry_points.txt` file, skipping malformed entries.
+    fn parse_entry_points(package: &str, content: &str) -> Vec<EntryPoint> {
+        let mut group: Option<&str> = None;
+        let mut entry_points = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                group = Some(section.trim());
+                continue;
+            }
+            let (Some(group), Some((name, value))) = (group, line.split_once('=')) else {
+                continue;
+            };
+            let value = value.trim();
+            let (reference, extras) = match value.split_once('[') {
+                Some((reference, extras)) => (
+                    reference.trim(),
+                    extras
+                        .trim_end_matches(']')
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|extra| !extra.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                ),
+                None => (value, Vec::new()),
+            };
+            let (module, attr) = match reference.split_once(':') {
+                Some((module, attr)) => (module.trim(), Some(attr.trim().to_string())),
+                None => (reference, None),
+            };
+            entry_points.push(EntryPoint {
+                name: name.trim().to_string(),
+                group: group.to_string(),
+                value: value.to_string(),
+                module: module.to_string(),
+                attr,
+                extras,
+                package: package.to_string(),
+            });
+        }
+        entry_points
+    }
+}
@@ -0,0 +1,197 @@
+//! Dependency graph of installed packages.
+//!
+//! Every `Requires-Dist` entry of a package's METADATA is an edge of the graph. Entries are
+//! parsed lazily per package, so walking a subtree only reads the METADATA files it visits.
+//! Entries whose environment markers exclude the running interpreter are left out.
+
+use crate::PythonPackageScanner;
+use pep508_rs::{MarkerExpression, Requirement, VerbatimUrl, VersionOrUrl};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A `Requires-Dist` entry of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    /// PEP 503 normalized name of the required package.
+    pub name: String,
+    /// Version specifiers of the requirement, e.g. `>=2.1, <3`, or `@ <url>` for direct
+    /// references. Empty if any version is accepted.
+    pub specifier: String,
+    /// Extras of the required package that are requested as well.
+    pub extras: Vec<String>,
+    /// The extra of the requiring package that pulls the requirement, `None` if it is
+    /// always required.
+    pub extra: Option<String>,
+}
+
+/// A node of a dependency tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyNode {
+    /// Name of the package.
+    pub name: String,
+    /// Installed version of the package, `None` if it is missing.
+    pub version: Option<String>,
+    /// Version specifiers the parent requires, empty for the root.
+    pub specifier: String,
+    /// The extra of the parent that pulls the package, `None` if it is always required.
+    pub extra: Option<String>,
+    /// Whether the package is installed with a version matching the specifiers.
+    pub satisfied: bool,
+    /// Dependencies of the package; empty at the depth limit and for cycles.
+    pub children: Vec<DependencyNode>,
+}
+
+impl PythonPackageScanner {
+    /// Returns the `Requires-Dist` entries of an installed package.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the package.
+    ///
+    /// # Returns
+    ///
+    /// The entries of the package's METADATA, or `None` if the package is not installed.
+    pub fn requirements(&self, name: &str) -> Option<Arc<Vec<Dependency>>> {
+        let key = self.package_key(name)?;
+        let pkg_root = self.known_packages.get(key.as_str())?;
+        Some(self.dependencies.get_with_by_ref(key.as_str(), || {
+            Arc::new(self.parse_requirements(&Self::read_metadata(&pkg_root).unwrap_or_default()))
+        }))
+    }
+
+    /// Builds the dependency tree of an installed package.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the root package.
+    /// * `depth` - Maximum depth of the tree below the root, unlimited if `None`.
+    /// * `extras` - Extras of the root package whose dependencies are included as well.
+    ///
+    /// # Returns
+    ///
+    /// The root node, or `None` if the package is not installed.
+    pub fn dependency_tree(
+        &self,
+        name: &str,
+        depth: Option<usize>,
+        extras: &[String],
+    ) -> Option<DependencyNode> {
+        let version = self.get_version(name)?;
+        let mut path = vec![Self::normalize(name)];
+        Some(DependencyNode {
+            name: name.to_string(),
+            version: Some(version),
+            specifier: String::new(),
+            extra: None,
+            satisfied: true,
+            children: self.expand(name, depth, extras, &mut path),
+        })
+    }
+
+    /// Lists the installed packages requiring a package.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the required package.
+    ///
+    /// # Returns
+    ///
+    /// Pairs of the requiring package and its entry for the package, sorted by package name.
+    pub fn reverse_dependencies(&self, name: &str) -> Vec<(String, Dependency)> {
+        let target = Self::normalize(name);
+        let mut dependents: Vec<(String, Dependency)> = self
+            .known_packages
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter_map(|key| self.requirements(&key).map(|deps| (key, deps)))
+            .flat_map(|(key, deps)| {
+                deps.iter()
+                    .filter(|dep| dep.name == target)
+                    .map(|dep| (key.clone(), dep.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        dependents.sort_by(|(a, _), (b, _)| a.cmp(b));
+        dependents
+    }
+
+    /// Expands the dependencies of a package, tracking the packages on the current path.
+    fn expand(
+        &self,
+        name: &str,
+        depth: Option<usize>,
+        extras: &[String],
+        path: &mut Vec<String>,
+    ) -> Vec<DependencyNode> {
+        if depth == Some(0) {
+            return Vec::new();
+        }
+        let Some(requirements) = self.requirements(name) else {
+            return Vec::new();
+        };
+        requirements
+            .iter()
+            .filter(|dep| {
+                dep.extra
+                    .as_ref()
+                    .is_none_or(|extra| extras.contains(extra))
+            })
+            .map(|dep| {
+                let version = self.get_version(&dep.name);
+                let children = if version.is_none() || path.contains(&dep.name) {
+                    Vec::new()
+                } else {
+                    path.push(dep.name.clone());
+                    let children = self.expand(&dep.name, depth.map(|d| d - 1), &dep.extras, path);
+                    path.pop();
+                    children
+                };
+                DependencyNode {
+                    name: dep.name.clone(),
+                    satisfied: self.satisfies(&dep.name, &dep.specifier),
+                    version,
+                    specifier: dep.specifier.clone(),
+                    extra: dep.extra.clone(),
+                    children,
+                }
+            })
+            .collect()
+    }
+
+    /// Checks whether a package is installed with a version matching a requirement's specifiers.
+    fn satisfies(&self, name: &str, specifier: &str) -> bool {
+        if specifier.is_empty() || specifier.starts_with('@') {
+            self.get_version(name).is_some()
+        } else {
+            self.version_satisfies(name, specifier).unwrap_or(false)
+        }
+    }
+
+    /// Parses the `Requires-Dist` entries of a METADATA file, skipping malformed ones and
+    /// those not applying to the running interpreter.
+    fn parse_requirements(&self, metadata: &str) -> Vec<Dependency> {
+        metadata
+            .lines()
+            .filter_map(|line| line.strip_prefix("Requires-Dist: "))
+            .filter_map(|line| Requirement::<VerbatimUrl>::from_str(line).ok())
+            .filter_map(|req| {
+                let extra = match req.marker.top_level_extra() {
+                    Some(MarkerExpression::Extra { name, .. }) => Some(name.to_string()),
+                    _ => None,
+                };
+                self.marker_applies(&req, extra.as_deref())
+                    .then_some((req, extra))
+            })
+            .map(|(req, extra)| Dependency {
+                name: req.name.to_string(),
+                specifier: match &req.version_or_url {
+                    Some(VersionOrUrl::VersionSpecifier(specifiers)) => specifiers.to_string(),
+                    Some(VersionOrUrl::Url(url)) => format!("@ {url}"),
+                    None => String::new(),
+                },
+                extras: req.extras.iter().map(ToString::to_string).collect(),
+                extra,
+            })
+            .collect()
+    }
+}
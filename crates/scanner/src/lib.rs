@@ -8,7 +8,7 @@
 //!
 //! The scanner uses a two-phase strategy:
 //!
-//! 1. **Discovery Phase (`refresh`)**: Scans the `site-packages` directory to index package names and `.dist-info` paths into an in-memory LRU cache (`moka::sync::Cache`).
+//! 1. **Discovery Phase (`refresh`)**: Scans the `site-packages` directory to index package names and `.dist-info` paths into an in-memory LRU cache (`moka::sync::Cache`). Legacy `.egg-info` records, `.egg-link` files of `setup.py develop` installs and `__editable__*.pth` files of editable installs are indexed too. Inside a conda environment, the `conda-meta/*.json` records are indexed as well, so conda-managed packages without `.dist-info` are still detected.
 //!
 //! 2. **Lazy Resolution Phase (`get_extra_all`)**: Parses the `METADATA` file of a package only when its extras are first queried. The resulting `extra -> [dependencies]` mapping is cached atomically to ensure subsequent lookups are O(1) memory operations.
 //!
@@ -17,8 +17,14 @@
 //! ## Key Features
 //!
 //! - **Fast Queries**: `is_installed()` and `extra_satisfied()` operate on cached data, achieving sub-microsecond latency.
+//! - **Version Checks**: `get_version()` and `version_satisfies()` match installed versions against PEP 440 specifiers.
+//! - **Dependency Graph**: `dependency_tree()` and `reverse_dependencies()` walk the `Requires-Dist` entries of installed packages.
+//! - **Entry Points**: `list_entry_points()` and `get_console_scripts()` read the `entry_points.txt` files of installed packages, e.g. to discover plugins.
 //! - **Lazy Parsing**: METADATA files are read and parsed only upon first access, optimizing cold-start time.
+//! - **Filesystem Watch**: `watch()` follows package records being added to or removed from `site-packages`, so long-running processes see `pip install` results without calling `refresh()`.
+//! - **Development Installs**: packages installed with `pip install -e`, including fabricatio's own packages in a checkout, are detected through their `.egg-link` or `__editable__*.pth` records.
 //! - **Thread Safety**: Built on `moka` and `rayon`, safe for concurrent use in async environments.
+//! - **Environment Markers**: PEP 508 markers such as `sys_platform == "win32"` are evaluated against the running interpreter, so dependencies of other platforms are not required.
 //! - **Accurate Matching**: Normalizes package names (replacing `-` with `_`) to align with Python's import system.
 //!
 //! ## Performance
@@ -30,16 +36,27 @@
 //! This module provides functionality to scan and analyze installed Python packages,
 //! including their dependencies and extras requirements.
 
+mod entry_points;
+mod graph;
+mod records;
+
+pub use entry_points::{CONSOLE_SCRIPTS, EntryPoint};
+pub use graph::{Dependency, DependencyNode};
 use moka::sync::Cache;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use once_cell::sync::Lazy;
-use pep508_rs::{MarkerExpression, Requirement, VerbatimUrl};
+use pep440_rs::{Version, VersionSpecifiers};
+use pep508_rs::{
+    ExtraName, MarkerEnvironment, MarkerEnvironmentBuilder, MarkerExpression, Requirement,
+    VerbatimUrl,
+};
 use pyo3::prelude::*;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use records::RecordKind;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use walkdir::WalkDir;
 
 /// Get the lib path using `sysconfig.get_paths()["purelib"]`
@@ -56,6 +73,68 @@ static SITE_PACKAGES: Lazy<PathBuf> = Lazy::new(|| {
     })
 });
 
+/// Get the `conda-meta` directory of the running interpreter's prefix, if it is a conda environment
+static CONDA_META: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    Python::attach(|py| {
+        py.import("sys")
+            .and_then(|sys| sys.getattr("prefix"))
+            .and_then(|prefix| prefix.extract::<PathBuf>())
+            .ok()
+    })
+    .map(|prefix| prefix.join("conda-meta"))
+    .filter(|conda_meta| conda_meta.is_dir())
+});
+
+/// Get the PEP 508 marker environment of the running interpreter, as `packaging.markers.default_environment()` does
+static MARKER_ENVIRONMENT: Lazy<Option<MarkerEnvironment>> = Lazy::new(|| {
+    Python::attach(|py| {
+        let sys = py.import("sys")?;
+        let os = py.import("os")?;
+        let platform = py.import("platform")?;
+        let call = |name: &str| -> PyResult<String> { platform.call_method0(name)?.extract() };
+
+        let implementation = sys.getattr("implementation")?;
+        let version = implementation.getattr("version")?;
+        let release_level: String = version.getattr("releaselevel")?.extract()?;
+        let mut implementation_version = format!(
+            "{}.{}.{}",
+            version.getattr("major")?.extract::<u32>()?,
+            version.getattr("minor")?.extract::<u32>()?,
+            version.getattr("micro")?.extract::<u32>()?
+        );
+        if release_level != "final" {
+            implementation_version.push_str(&release_level[..1]);
+            implementation_version
+                .push_str(&version.getattr("serial")?.extract::<u32>()?.to_string());
+        }
+        let python_full_version = call("python_version")?;
+        let python_version = python_full_version
+            .splitn(3, '.')
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        Ok::<_, PyErr>(
+            MarkerEnvironment::try_from(MarkerEnvironmentBuilder {
+                implementation_name: &implementation.getattr("name")?.extract::<String>()?,
+                implementation_version: &implementation_version,
+                os_name: &os.getattr("name")?.extract::<String>()?,
+                platform_machine: &call("machine")?,
+                platform_python_implementation: &call("python_implementation")?,
+                platform_release: &call("release")?,
+                platform_system: &call("system")?,
+                platform_version: &call("version")?,
+                python_full_version: &python_full_version,
+                python_version: &python_version,
+                sys_platform: &sys.getattr("platform")?.extract::<String>()?,
+            })
+            .ok(),
+        )
+    })
+    .ok()
+    .flatten()
+});
+
 type PackageExtras = HashMap<String, Vec<String>>;
 
 type PackageRoot = PathBuf;
@@ -69,8 +148,24 @@ pub struct PythonPackageScanner {
 
     /// Cache storing package names mapped to their extra mappings.
     extras_mappings: Cache<String, Arc<PackageExtras>>,
+    /// Cache storing package names mapped to their versions, resolved on first query.
+    versions: Cache<String, Option<String>>,
+    /// Cache storing package names mapped to their `Requires-Dist` entries, parsed on first query.
+    dependencies: Cache<String, Arc<Vec<Dependency>>>,
+    /// Cache storing package names mapped to their entry points, parsed on first query.
+    entry_points: Cache<String, Arc<Vec<EntryPoint>>>,
+    /// Cache storing PEP 503 normalized package names mapped to their keys in `known_packages`.
+    aliases: Cache<String, String>,
+    /// Cache storing normalized names of conda-managed packages mapped to their versions.
+    conda_packages: Cache<String, String>,
     /// Path to the site-packages directory.
     site_packages: PathBuf,
+    /// Path to the `conda-meta` directory, if running inside a conda environment.
+    conda_meta: Option<PathBuf>,
+    /// Marker environment of the running interpreter, `None` if it could not be determined.
+    marker_environment: Option<MarkerEnvironment>,
+    /// Watcher of the site-packages directory, `None` unless `watch` was called.
+    watcher: Mutex<Option<RecommendedWatcher>>,
 }
 
 impl Default for PythonPackageScanner {
@@ -89,49 +184,279 @@ impl PythonPackageScanner {
     ///
     /// A new instance of `PythonPackageScanner`.
     pub fn new() -> Self {
-        Self {
+        let scanner = Self {
             known_packages: Cache::builder().build(),
             extras_mappings: Cache::builder().build(),
+            versions: Cache::builder().build(),
+            dependencies: Cache::builder().build(),
+            entry_points: Cache::builder().build(),
+            aliases: Cache::builder().build(),
+            conda_packages: Cache::builder().build(),
             site_packages: SITE_PACKAGES.clone(),
-        }
-        .refresh()
+            conda_meta: CONDA_META.clone(),
+            marker_environment: MARKER_ENVIRONMENT.clone(),
+            watcher: Mutex::new(None),
+        };
+        scanner.refresh();
+        scanner
     }
 
+    /// Lists the names of all installed packages, including conda-managed packages
+    /// that have no `.dist-info` directory.
     pub fn list_installed(&self) -> Vec<String> {
-        self.known_packages
+        let mut installed: Vec<String> = self
+            .known_packages
             .iter()
             .map(|(k, _)| k.to_string())
-            .collect()
+            .collect();
+        let normalized: HashSet<String> = installed.iter().map(|k| Self::conda_key(k)).collect();
+        installed.extend(
+            self.conda_packages
+                .iter()
+                .map(|(k, _)| k.to_string())
+                .filter(|k| !normalized.contains(k)),
+        );
+        installed
     }
-    /// Refreshes the package cache by scanning the site-packages directory.
+
+    /// Returns true if the scanner runs inside a conda environment.
+    pub fn is_conda_env(&self) -> bool {
+        self.conda_meta.is_some()
+    }
+
+    /// Returns the version of a conda-managed package as recorded in `conda-meta`.
     ///
-    /// Clears the current cache and rescans the site-packages directory to
-    /// discover installed packages. Each package's .dist-info directory is
-    /// identified and added to the cache.
+    /// # Arguments
+    ///
+    /// * `name` - The name of the package, matched case-insensitively with `-` and `_` treated alike.
     ///
     /// # Returns
     ///
-    /// The same instance with refreshed cache.
-    pub fn refresh(self) -> Self {
+    /// The version string if the package is managed by conda, `None` otherwise.
+    pub fn conda_version(&self, name: &str) -> Option<String> {
+        self.conda_packages.get(Self::conda_key(name).as_str())
+    }
+
+    /// Normalizes a package name for conda lookups.
+    #[inline]
+    fn conda_key(name: &str) -> String {
+        name.to_lowercase().replace("-", "_")
+    }
+
+    /// Normalizes a package name as specified by PEP 503, e.g. `Foo.Bar_baz` to `foo-bar-baz`.
+    fn normalize(name: &str) -> String {
+        let mut normalized = String::with_capacity(name.len());
+        for c in name.trim().chars() {
+            if matches!(c, '-' | '_' | '.') {
+                if !normalized.ends_with('-') {
+                    normalized.push('-');
+                }
+            } else {
+                normalized.push(c.to_ascii_lowercase());
+            }
+        }
+        normalized
+    }
+
+    /// Resolves a package name to its key in `known_packages`, ignoring case and separators.
+    fn package_key(&self, name: &str) -> Option<String> {
+        let key = name.replace("-", "_");
+        if self.known_packages.contains_key(key.as_str()) {
+            Some(key)
+        } else {
+            self.aliases.get(Self::normalize(name).as_str())
+        }
+    }
+
+    /// Parses a `conda-meta` record file name of the form `<name>-<version>-<build>.json`.
+    ///
+    /// # Returns
+    ///
+    /// The package name and version, or `None` if the file name is not a conda record.
+    fn parse_conda_record(file_name: &str) -> Option<(&str, &str)> {
+        let mut parts = file_name.strip_suffix(".json")?.rsplitn(3, '-');
+        let _build = parts.next()?;
+        let version = parts.next()?;
+        let name = parts.next()?;
+        Some((name, version))
+    }
+    /// Indexes a package record of the site-packages directory.
+    ///
+    /// Editable `.pth` files are only indexed if the package is not recorded otherwise, since
+    /// they usually come with a `.dist-info` directory.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the path is a package record.
+    fn index_record(
+        known_packages: &Cache<String, PackageRoot>,
+        aliases: &Cache<String, String>,
+        site_packages: &Path,
+        path: &Path,
+    ) -> bool {
+        let Some((kind, pkg_name, _)) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(Self::parse_record)
+        else {
+            return false;
+        };
+        let alias = Self::normalize(pkg_name);
+        if kind == RecordKind::EditablePth && aliases.contains_key(alias.as_str()) {
+            return true;
+        }
+        aliases.insert(alias, pkg_name.to_string());
+        known_packages.insert(
+            pkg_name.to_string(),
+            Self::resolve_record(site_packages, kind, path),
+        );
+        true
+    }
+
+    /// Refreshes the package cache by scanning the site-packages directory.
+    ///
+    /// Clears the current cache and rescans the site-packages directory to
+    /// discover installed packages. Each package's record, a `.dist-info` directory or one of
+    /// the legacy and editable records, is identified and added to the cache.
+    ///
+    /// Caches derived from the METADATA and `entry_points.txt` files are cleared as well.
+    pub fn refresh(&self) {
         self.known_packages.invalidate_all();
-        WalkDir::new(&self.site_packages)
+        self.extras_mappings.invalidate_all();
+        self.versions.invalidate_all();
+        self.dependencies.invalidate_all();
+        self.entry_points.invalidate_all();
+        self.aliases.invalidate_all();
+        let records: Vec<(RecordKind, PathBuf)> = WalkDir::new(&self.site_packages)
             .max_depth(1)
             .min_depth(1)
             .into_iter()
-            .par_bridge()
             .filter_map(Result::ok)
-            .filter(|entry| entry.file_type().is_dir())
-            .map(|entry| {
-                let path = entry.path().to_path_buf();
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                (path, dir_name)
+            .filter_map(|entry| {
+                let (kind, _, _) = Self::parse_record(&entry.file_name().to_string_lossy())?;
+                Some((kind, entry.into_path()))
             })
-            .filter(|(_, dir_name)| dir_name.ends_with(".dist-info"))
-            .for_each(|(entry_path, dir_name)| {
-                let (pkg_name, _) = dir_name.split_once("-").unwrap();
-                self.known_packages.insert(pkg_name.to_string(), entry_path);
+            .collect();
+        // Editable `.pth` files go last, so they only fill in for packages without a record.
+        records
+            .par_iter()
+            .filter(|(kind, _)| *kind != RecordKind::EditablePth)
+            .for_each(|(_, path)| {
+                Self::index_record(
+                    &self.known_packages,
+                    &self.aliases,
+                    &self.site_packages,
+                    path,
+                );
+            });
+        records
+            .iter()
+            .filter(|(kind, _)| *kind == RecordKind::EditablePth)
+            .for_each(|(_, path)| {
+                Self::index_record(
+                    &self.known_packages,
+                    &self.aliases,
+                    &self.site_packages,
+                    path,
+                );
             });
-        self
+
+        self.conda_packages.invalidate_all();
+        if let Some(conda_meta) = &self.conda_meta {
+            WalkDir::new(conda_meta)
+                .max_depth(1)
+                .min_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file())
+                .for_each(|entry| {
+                    if let Some((name, version)) =
+                        Self::parse_conda_record(&entry.file_name().to_string_lossy())
+                    {
+                        self.conda_packages
+                            .insert(Self::conda_key(name), version.to_string());
+                    }
+                });
+        }
+    }
+
+    /// Starts watching the site-packages directory for installed and removed packages.
+    ///
+    /// Once a package record such as a `.dist-info` directory appears or disappears, the
+    /// package is added to or dropped from the cache and the caches derived from package metadata are cleared.
+    /// Calling this method while already watching does nothing.
+    ///
+    /// # Returns
+    ///
+    /// An error if the watcher cannot be set up.
+    pub fn watch(&self) -> notify::Result<()> {
+        let mut watcher = self
+            .watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if watcher.is_some() {
+            return Ok(());
+        }
+
+        let known_packages = self.known_packages.clone();
+        let aliases = self.aliases.clone();
+        let extras_mappings = self.extras_mappings.clone();
+        let versions = self.versions.clone();
+        let dependencies = self.dependencies.clone();
+        let entry_points = self.entry_points.clone();
+        let site_packages = self.site_packages.clone();
+        let mut new_watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let mut changed = false;
+            for path in &event.paths {
+                let Some((kind, pkg_name, _)) = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(Self::parse_record)
+                else {
+                    continue;
+                };
+                // Events may arrive out of order during upgrades, so the record is checked
+                // rather than trusting the event kind, and only the indexed one is dropped.
+                if path.exists() {
+                    Self::index_record(&known_packages, &aliases, &site_packages, path);
+                } else if known_packages.get(pkg_name).is_some_and(|root| {
+                    root.file_name() == path.file_name()
+                        || (kind == RecordKind::EggLink && !root.starts_with(&site_packages))
+                }) {
+                    known_packages.invalidate(pkg_name);
+                    aliases.invalidate(Self::normalize(pkg_name).as_str());
+                }
+                changed = true;
+            }
+            if changed {
+                extras_mappings.invalidate_all();
+                versions.invalidate_all();
+                dependencies.invalidate_all();
+                entry_points.invalidate_all();
+            }
+        })?;
+        new_watcher.watch(&self.site_packages, RecursiveMode::NonRecursive)?;
+        *watcher = Some(new_watcher);
+        Ok(())
+    }
+
+    /// Stops watching the site-packages directory, if `watch` was called.
+    pub fn unwatch(&self) {
+        self.watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+    }
+
+    /// Returns true if the site-packages directory is being watched.
+    pub fn is_watching(&self) -> bool {
+        self.watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_some()
     }
 
     /// Checks whether **all** given extras of a package have their dependencies satisfied.
@@ -149,6 +474,7 @@ impl PythonPackageScanner {
     /// # Note
     /// - If any extra is not defined in the package metadata, this returns `false`.
     /// - Only top-level `extra == "..."` markers are supported (no complex marker expressions).
+    /// - Dependencies whose environment markers exclude the running interpreter are not required.
     pub fn extras_satisfied<I, S>(&self, pkg_name: &str, extras: I) -> bool
     where
         I: IntoIterator<Item = S>,
@@ -158,8 +484,7 @@ impl PythonPackageScanner {
         if let Some(all_extra) = self.get_extra_all(&pkg_name) {
             extras.into_iter().all(|extra| {
                 if let Some(deps) = all_extra.get(extra.as_ref()) {
-                    deps.iter()
-                        .all(|dep| self.known_packages.get(dep.as_str()).is_some())
+                    deps.iter().all(|dep| self.is_installed(dep))
                 } else {
                     false // extra not defined → not satisfied
                 }
@@ -188,10 +513,9 @@ impl PythonPackageScanner {
     fn get_extra_all(&self, pkg_name: &str) -> Option<Arc<PackageExtras>> {
         if let Some(pkg_root) = self.known_packages.get(pkg_name) {
             Some(self.extras_mappings.get_with_by_ref(pkg_name, || {
-                Arc::new(Self::acquire_extra_mapping(
-                    fs::read_to_string(pkg_root.join("METADATA"))
-                        .expect("Failed to read METADATA file"),
-                ))
+                Arc::new(
+                    self.acquire_extra_mapping(Self::read_metadata(&pkg_root).unwrap_or_default()),
+                )
             }))
         } else {
             None
@@ -201,7 +525,8 @@ impl PythonPackageScanner {
     /// Extracts the extra dependency mapping from package metadata.
     ///
     /// Parses the METADATA file of a package to extract the mapping between
-    /// extras and their required dependencies.
+    /// extras and their required dependencies. Dependencies whose environment markers
+    /// exclude the running interpreter are left out.
     ///
     /// # Arguments
     ///
@@ -211,7 +536,7 @@ impl PythonPackageScanner {
     ///
     /// A map of extras to their dependency lists.
     #[inline]
-    fn acquire_extra_mapping(metadata: String) -> PackageExtras {
+    fn acquire_extra_mapping(&self, metadata: String) -> PackageExtras {
         let mut reg: PackageExtras = HashMap::new();
 
         metadata
@@ -219,25 +544,45 @@ impl PythonPackageScanner {
             .filter_map(|line: &str| line.strip_prefix("Requires-Dist: "))
             .filter(|line: &&str| line.contains("extra =="))
             .filter_map(|line: &str| Requirement::<VerbatimUrl>::from_str(line).ok())
-            .filter_map(|req| {
-                req.marker
-                    .top_level_extra()
-                    .map(|expr| (req.name.to_string(), expr))
-            })
-            .for_each(|(req_name, expr)| {
-                if let MarkerExpression::Extra { name, .. } = expr {
-                    reg.entry(name.to_string())
-                        .or_default()
-                        .push(req_name.replace("-", "_"))
+            .for_each(|req| {
+                if let Some(MarkerExpression::Extra { name, .. }) = req.marker.top_level_extra() {
+                    let extra = name.to_string();
+                    if self.marker_applies(&req, Some(&extra)) {
+                        reg.entry(extra)
+                            .or_default()
+                            .push(req.name.to_string().replace("-", "_"))
+                    }
                 }
                 //TODO: add complex extra analytics
             });
         reg
     }
 
+    /// Evaluates the environment markers of a requirement against the running interpreter.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The requirement to evaluate.
+    /// * `extra` - The extra the requirement is requested for, if any.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the requirement applies, or if the marker environment is unknown.
+    fn marker_applies(&self, req: &Requirement<VerbatimUrl>, extra: Option<&str>) -> bool {
+        let Some(environment) = &self.marker_environment else {
+            return true;
+        };
+        let extras: Vec<ExtraName> = extra
+            .and_then(|extra| ExtraName::from_str(extra).ok())
+            .into_iter()
+            .collect();
+        req.marker.evaluate(environment, &extras)
+    }
+
     /// Checks if a package is installed.
     ///
-    /// Determines whether a package with the given name is present in the cache.
+    /// Determines whether a package with the given name is present in the cache,
+    /// either as a package record of site-packages or as a conda-managed package.
     ///
     /// # Arguments
     ///
@@ -247,8 +592,69 @@ impl PythonPackageScanner {
     ///
     /// `true` if the package is installed, `false` otherwise.
     pub fn is_installed(&self, name: &str) -> bool {
-        self.known_packages
-            .get(name.replace("-", "_").as_str())
-            .is_some()
+        self.package_key(name).is_some()
+            || self
+                .conda_packages
+                .contains_key(Self::conda_key(name).as_str())
+    }
+
+    /// Returns the installed version of a package.
+    ///
+    /// The `Version` field of the package's `METADATA` takes precedence over the version in
+    /// the name of its record, e.g. the `.dist-info` directory. Conda-managed packages without
+    /// such a record fall back to the version recorded in `conda-meta`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the package.
+    ///
+    /// # Returns
+    ///
+    /// The version string if the package is installed, `None` otherwise.
+    pub fn get_version(&self, name: &str) -> Option<String> {
+        if let Some(pkg_name) = self.package_key(name)
+            && let Some(pkg_root) = self.known_packages.get(pkg_name.as_str())
+        {
+            let version = self.versions.get_with_by_ref(pkg_name.as_str(), || {
+                Self::read_metadata(&pkg_root)
+                    .and_then(|metadata| Self::metadata_version(&metadata))
+                    .or_else(|| Self::record_version(&pkg_root))
+            });
+            if version.is_some() {
+                return version;
+            }
+        }
+        self.conda_version(name)
+    }
+
+    /// Checks whether the installed version of a package matches PEP 440 version specifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the package.
+    /// * `specifiers` - Comma-separated PEP 440 specifiers, e.g. `>=2.1,<3`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the package is installed with a matching version, `Ok(false)` if it is
+    /// missing or its version is not PEP 440 compliant, and an error message if the
+    /// specifiers are invalid.
+    pub fn version_satisfies(&self, name: &str, specifiers: &str) -> Result<bool, String> {
+        let specifiers = VersionSpecifiers::from_str(specifiers)
+            .map_err(|e| format!("Invalid version specifiers `{specifiers}`: {e}"))?;
+        Ok(self
+            .get_version(name)
+            .and_then(|version| Version::from_str(&version).ok())
+            .is_some_and(|version| specifiers.contains(&version)))
+    }
+
+    /// Extracts the `Version` field from the header of a METADATA file.
+    fn metadata_version(metadata: &str) -> Option<String> {
+        metadata
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| line.strip_prefix("Version:"))
+            .map(|version| version.trim().to_string())
+            .filter(|version| !version.is_empty())
     }
 }
@@ -0,0 +1,192 @@
+//! Records of installed packages in the site-packages directory.
+//!
+//! Besides the `.dist-info` directories of wheel installs, packages may be recorded by:
+//!
+//! - `.egg-info` directories or files of legacy setuptools installs, holding a `PKG-INFO`
+//!   file in the METADATA format and the requirements in a separate `requires.txt`;
+//! - `.egg-link` files of `setup.py develop` installs, pointing at the project directory
+//!   that holds the `.egg-info` directory;
+//! - `__editable__.<name>-<version>.pth` files of PEP 660 editable installs, which usually
+//!   come with a `.dist-info` directory but are recorded on their own if it is missing.
+//!
+//! Every record resolves to a package root, whose metadata is read by `read_metadata`.
+
+use crate::PythonPackageScanner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DIST_INFO: &str = ".dist-info";
+const EGG_INFO: &str = ".egg-info";
+const EGG_LINK: &str = ".egg-link";
+const EDITABLE_PREFIX: &str = "__editable__.";
+const PTH: &str = ".pth";
+
+/// The kind of a site-packages entry recording an installed package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordKind {
+    DistInfo,
+    EggInfo,
+    EggLink,
+    EditablePth,
+}
+
+impl PythonPackageScanner {
+    /// Parses the file name of a package record, e.g. `PyYAML-6.0.2.dist-info`,
+    /// `foo-1.0-py3.12.egg-info`, `foo.egg-link` or `__editable__.foo-1.0.pth`.
+    ///
+    /// # Returns
+    ///
+    /// The kind of the record, the package name and the version if the name carries one, or
+    /// `None` if the file name is not a package record.
+    pub(crate) fn parse_record(file_name: &str) -> Option<(RecordKind, &str, Option<&str>)> {
+        if let Some(stem) = file_name.strip_suffix(DIST_INFO) {
+            let (name, version) = stem.split_once('-')?;
+            return Some((RecordKind::DistInfo, name, Some(version)));
+        }
+        if let Some(name) = file_name.strip_suffix(EGG_LINK) {
+            // Links are named after the project alone, which may contain `-`.
+            return (!name.is_empty()).then_some((RecordKind::EggLink, name, None));
+        }
+        let (kind, stem) = if let Some(stem) = file_name.strip_suffix(EGG_INFO) {
+            (RecordKind::EggInfo, stem)
+        } else {
+            let stem = file_name.strip_prefix(EDITABLE_PREFIX)?.strip_suffix(PTH)?;
+            (RecordKind::EditablePth, stem)
+        };
+        let mut parts = stem.split('-');
+        let name = parts.next().filter(|name| !name.is_empty())?;
+        Some((
+            kind,
+            name,
+            parts.next().filter(|version| !version.is_empty()),
+        ))
+    }
+
+    /// Extracts the package name from the file name of a package record.
+    pub(crate) fn record_name(file_name: &str) -> Option<&str> {
+        Self::parse_record(file_name).map(|(_, name, _)| name)
+    }
+
+    /// Extracts the version from the file name of a package root, if it carries one.
+    pub(crate) fn record_version(pkg_root: &Path) -> Option<String> {
+        Self::parse_record(pkg_root.file_name()?.to_str()?)
+            .and_then(|(_, _, version)| version.map(str::to_string))
+    }
+
+    /// Resolves a package record to the root the package is indexed under.
+    ///
+    /// An `.egg-link` resolves to the `.egg-info` directory in the project it points at, or to
+    /// itself if none is found; any other record is its own root.
+    pub(crate) fn resolve_record(site_packages: &Path, kind: RecordKind, path: &Path) -> PathBuf {
+        if kind != RecordKind::EggLink {
+            return path.to_path_buf();
+        }
+        let Some(target) = fs::read_to_string(path).ok().and_then(|content| {
+            content
+                .lines()
+                .next()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| site_packages.join(line))
+        }) else {
+            return path.to_path_buf();
+        };
+        let wanted = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(Self::record_name)
+            .map(Self::normalize);
+        let egg_infos: Vec<PathBuf> = fs::read_dir(&target)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with(EGG_INFO))
+            })
+            .collect();
+        egg_infos
+            .iter()
+            .find(|egg_info| {
+                egg_info
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(Self::record_name)
+                    .map(Self::normalize)
+                    == wanted
+            })
+            .or(egg_infos.first())
+            .cloned()
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Reads the metadata of a package root in the METADATA format.
+    ///
+    /// The `PKG-INFO` of an `.egg-info` lacking `Requires-Dist` entries is completed with the
+    /// requirements of its `requires.txt`.
+    ///
+    /// # Returns
+    ///
+    /// The metadata, or `None` if the root has none, e.g. a bare editable `.pth` file.
+    pub(crate) fn read_metadata(pkg_root: &Path) -> Option<String> {
+        let file_name = pkg_root.file_name()?.to_str()?;
+        if file_name.ends_with(DIST_INFO) {
+            return fs::read_to_string(pkg_root.join("METADATA")).ok();
+        }
+        if !file_name.ends_with(EGG_INFO) {
+            return None;
+        }
+        if pkg_root.is_file() {
+            return fs::read_to_string(pkg_root).ok();
+        }
+        let mut metadata = fs::read_to_string(pkg_root.join("PKG-INFO")).ok()?;
+        if !metadata
+            .lines()
+            .any(|line| line.starts_with("Requires-Dist:"))
+            && let Ok(requires) = fs::read_to_string(pkg_root.join("requires.txt"))
+        {
+            if !metadata.ends_with('\n') {
+                metadata.push('\n');
+            }
+            metadata.push_str(&Self::requires_to_metadata(&requires));
+        }
+        Some(metadata)
+    }
+
+    /// Converts a `requires.txt` file into `Requires-Dist` entries.
+    ///
+    /// Sections of the form `[extra]`, `[extra:marker]` or `[:marker]` apply the extra and the
+    /// environment marker to the requirements below them.
+    fn requires_to_metadata(requires: &str) -> String {
+        let mut marker: Option<String> = None;
+        let mut metadata = String::new();
+        for line in requires.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let (extra, condition) = section.split_once(':').unwrap_or((section, ""));
+                let mut clauses = Vec::new();
+                if !condition.trim().is_empty() {
+                    clauses.push(format!("({})", condition.trim()));
+                }
+                if !extra.trim().is_empty() {
+                    clauses.push(format!("extra == \"{}\"", extra.trim()));
+                }
+                marker = (!clauses.is_empty()).then(|| clauses.join(" and "));
+                continue;
+            }
+            metadata.push_str("Requires-Dist: ");
+            metadata.push_str(line);
+            if let Some(marker) = &marker {
+                metadata.push_str("; ");
+                metadata.push_str(marker);
+            }
+            metadata.push('\n');
+        }
+        metadata
+    }
+}
@@ -4,12 +4,21 @@
 //! Python type hints for the function signature and the `Args:` section of a Google-style docstring.
 //! It strictly adheres to the convention that all non-required parameters are typed as `Optional[T] = None`
 //! in both the signature and the docstring.
+//!
+//! It also compares two versions of a schema with `schema_diff`, so callers can detect when the
+//! signature of a tool drifted away from the stubs generated for it, and validates values against
+//! a schema with `validate`, so that the arguments of a tool call can be checked before it is sent.
+
+mod validate;
+
+pub use validate::{Violation, validate};
 
 use heck::ToSnakeCase;
 // For sorted_by_key and other iterator utilities
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 // --- Data Structures for JSON Schema Parsing ---
 
@@ -31,6 +40,8 @@ struct JsonSchema {
 struct ParameterInfo {
     /// The parameter name converted to Python's snake_case convention.
     name: String,
+    /// The parameter name as written in the schema.
+    schema_name: String,
     /// The base Python type (e.g., "str", "list[str]", "bool").
     base_py_type: String,
     /// The description of the parameter from the schema.
@@ -90,7 +101,10 @@ fn extract_parameter_infos(schema: &JsonSchema) -> Vec<ParameterInfo> {
             && let Some(param_info) =
                 process_property(&original_name.to_snake_case(), prop_value, true)
         {
-            ordered.push(param_info);
+            ordered.push(ParameterInfo {
+                schema_name: original_name.clone(),
+                ..param_info
+            });
         }
     }
     // 2. Optional
@@ -99,7 +113,10 @@ fn extract_parameter_infos(schema: &JsonSchema) -> Vec<ParameterInfo> {
             && let Some(param_info) =
                 process_property(&original_name.to_snake_case(), prop_value, false)
         {
-            ordered.push(param_info);
+            ordered.push(ParameterInfo {
+                schema_name: original_name.clone(),
+                ..param_info
+            });
         }
     }
     ordered
@@ -175,6 +192,142 @@ pub fn schema_to_docstring_args(schema_value: &Value) -> Option<String> {
     }
 }
 
+/// Lists the parameters of a JSON Schema in signature order.
+///
+/// # Arguments
+/// * `schema_value`: A `serde_json::Value` representing the JSON Schema.
+///
+/// # Returns
+/// * `Some(Vec<(String, String)>)`: Pairs of the Python parameter name and the property name in the schema,
+///   needed to map the arguments of a generated function back to the schema.
+/// * `None`: If the input schema is invalid or not an object schema.
+pub fn schema_to_parameter_names(schema_value: &Value) -> Option<Vec<(String, String)>> {
+    let schema: JsonSchema = serde_json::from_value(schema_value.clone()).ok()?;
+    Some(
+        extract_parameter_infos(&schema)
+            .into_iter()
+            .map(|info| (info.name, info.schema_name))
+            .collect(),
+    )
+}
+
+/// A parameter whose Python type changed between two versions of a schema.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RetypedParameter {
+    /// The property name in the schema.
+    pub name: String,
+    /// The Python type in the old schema.
+    pub old_type: String,
+    /// The Python type in the new schema.
+    pub new_type: String,
+}
+
+/// The differences between the parameters of two versions of a JSON Schema.
+///
+/// Parameters are identified by their property name in the schema. Added, retyped and
+/// re-required parameters are listed in the signature order of the new schema, removed ones in
+/// the signature order of the old schema.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Parameters only present in the new schema.
+    pub added: Vec<String>,
+    /// Parameters only present in the old schema.
+    pub removed: Vec<String>,
+    /// Parameters present in both schemas with different Python types.
+    pub retyped: Vec<RetypedParameter>,
+    /// Parameters that were optional and became required.
+    pub now_required: Vec<String>,
+    /// Parameters that were required and became optional.
+    pub now_optional: Vec<String>,
+}
+
+impl SchemaDiff {
+    /// Returns `true` if both schemas describe the same signature.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.retyped.is_empty()
+            && self.now_required.is_empty()
+            && self.now_optional.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    /// Formats the differences as one line per change, e.g. `retyped `limit`: int -> str`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        lines.extend(self.added.iter().map(|name| format!("added `{name}`")));
+        lines.extend(self.removed.iter().map(|name| format!("removed `{name}`")));
+        lines.extend(
+            self.retyped
+                .iter()
+                .map(|p| format!("retyped `{}`: {} -> {}", p.name, p.old_type, p.new_type)),
+        );
+        lines.extend(
+            self.now_required
+                .iter()
+                .map(|name| format!("`{name}` is now required")),
+        );
+        lines.extend(
+            self.now_optional
+                .iter()
+                .map(|name| format!("`{name}` is now optional")),
+        );
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Compares the parameters of two versions of a JSON Schema.
+///
+/// # Arguments
+/// * `old`: The schema the existing signature was generated from.
+/// * `new`: The current schema.
+///
+/// # Returns
+/// * `Some(SchemaDiff)`: The added, removed and retyped parameters and the required-ness changes,
+///   empty if the generated signatures would be the same.
+/// * `None`: If either schema is invalid or not an object schema.
+pub fn schema_diff(old: &Value, new: &Value) -> Option<SchemaDiff> {
+    let old_infos = extract_parameter_infos(&serde_json::from_value(old.clone()).ok()?);
+    let new_infos = extract_parameter_infos(&serde_json::from_value(new.clone()).ok()?);
+    let old_by_name: HashMap<&str, &ParameterInfo> = old_infos
+        .iter()
+        .map(|info| (info.schema_name.as_str(), info))
+        .collect();
+    let new_names: HashSet<&str> = new_infos
+        .iter()
+        .map(|info| info.schema_name.as_str())
+        .collect();
+
+    let mut diff = SchemaDiff {
+        removed: old_infos
+            .iter()
+            .filter(|info| !new_names.contains(info.schema_name.as_str()))
+            .map(|info| info.schema_name.clone())
+            .collect(),
+        ..SchemaDiff::default()
+    };
+    for info in &new_infos {
+        let Some(old_info) = old_by_name.get(info.schema_name.as_str()) else {
+            diff.added.push(info.schema_name.clone());
+            continue;
+        };
+        if old_info.base_py_type != info.base_py_type {
+            diff.retyped.push(RetypedParameter {
+                name: info.schema_name.clone(),
+                old_type: old_info.base_py_type.clone(),
+                new_type: info.base_py_type.clone(),
+            });
+        }
+        match (old_info.is_required, info.is_required) {
+            (false, true) => diff.now_required.push(info.schema_name.clone()),
+            (true, false) => diff.now_optional.push(info.schema_name.clone()),
+            _ => {}
+        }
+    }
+    Some(diff)
+}
+
 /// Processes a single property definition from the JSON Schema.
 ///
 /// This function extracts the base type, description, and enum values.
@@ -219,6 +372,7 @@ fn process_property(
 
     Some(ParameterInfo {
         name: snake_name.to_string(),
+        schema_name: snake_name.to_string(),
         base_py_type,
         description,
         is_required,
@@ -463,6 +617,98 @@ mod tests {
         assert_eq!(docstring, Some(expected_docstring.to_string()));
     }
 
+    #[test]
+    fn test_parameter_names() {
+        let mut properties = serde_json::Map::new();
+        properties.insert("maxDepth".to_string(), json!({"type": "integer"}));
+        properties.insert("path".to_string(), json!({"type": "string"}));
+        let schema_value = schema_from_props_and_required(properties, vec!["path"]);
+        assert_eq!(
+            schema_to_parameter_names(&schema_value),
+            Some(vec![
+                ("path".to_string(), "path".to_string()),
+                ("max_depth".to_string(), "maxDepth".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let mut old_properties = serde_json::Map::new();
+        old_properties.insert("path".to_string(), json!({"type": "string"}));
+        old_properties.insert("maxDepth".to_string(), json!({"type": "integer"}));
+        old_properties.insert("recursive".to_string(), json!({"type": "boolean"}));
+        old_properties.insert("exclude".to_string(), json!({"type": "string"}));
+        let old = schema_from_props_and_required(old_properties, vec!["path", "recursive"]);
+
+        let mut new_properties = serde_json::Map::new();
+        new_properties.insert("path".to_string(), json!({"type": "string"}));
+        new_properties.insert("maxDepth".to_string(), json!({"type": "string"}));
+        new_properties.insert("recursive".to_string(), json!({"type": "boolean"}));
+        new_properties.insert(
+            "exclude".to_string(),
+            json!({"type": "array", "items": {"type": "string"}}),
+        );
+        new_properties.insert("followLinks".to_string(), json!({"type": "boolean"}));
+        let new = schema_from_props_and_required(new_properties, vec!["path", "maxDepth"]);
+
+        let diff = schema_diff(&old, &new).unwrap();
+        assert_eq!(diff.added, vec!["followLinks".to_string()]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(
+            diff.retyped,
+            vec![
+                RetypedParameter {
+                    name: "maxDepth".to_string(),
+                    old_type: "int".to_string(),
+                    new_type: "str".to_string(),
+                },
+                RetypedParameter {
+                    name: "exclude".to_string(),
+                    old_type: "str".to_string(),
+                    new_type: "list[str]".to_string(),
+                },
+            ]
+        );
+        assert_eq!(diff.now_required, vec!["maxDepth".to_string()]);
+        assert_eq!(diff.now_optional, vec!["recursive".to_string()]);
+        assert_eq!(
+            diff.to_string(),
+            indoc! {"
+                added `followLinks`
+                retyped `maxDepth`: int -> str
+                retyped `exclude`: str -> list[str]
+                `maxDepth` is now required
+                `recursive` is now optional
+            "}
+            .trim_end()
+        );
+
+        let reversed = schema_diff(&new, &old).unwrap();
+        assert_eq!(reversed.removed, vec!["followLinks".to_string()]);
+        assert!(reversed.added.is_empty());
+    }
+
+    #[test]
+    fn test_schema_diff_unchanged() {
+        let mut properties = serde_json::Map::new();
+        properties.insert(
+            "query".to_string(),
+            json!({"type": "string", "description": "old"}),
+        );
+        let old = schema_from_props_and_required(properties.clone(), vec!["query"]);
+        properties.insert(
+            "query".to_string(),
+            json!({"type": "string", "description": "reworded"}),
+        );
+        let new = schema_from_props_and_required(properties, vec!["query"]);
+
+        let diff = schema_diff(&old, &new).unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+        assert_eq!(schema_diff(&old, &json!("not a schema")), None);
+    }
+
     #[test]
     fn test_extract_parameter_info_handles_missing_type() {
         let prop_value: Value = json!({"description": "A param without type"});
@@ -0,0 +1,491 @@
+//! Validation of JSON values, such as tool arguments, against a JSON Schema.
+//!
+//! Covers the keywords tool input schemas use: `type`, `enum`, `const`, `properties`,
+//! `required`, `additionalProperties`, `items`, the length and range bounds, `allOf`, `anyOf`,
+//! `oneOf`, `not`, and `$ref` to the `$defs` or `definitions` of the root schema. Other
+//! keywords, such as `pattern` and `format`, are left to the server, and so are the values
+//! under a `$ref` that does not resolve within the root schema, such as an external URI.
+
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Maximum number of `$ref` followed on a path, so recursive definitions terminate.
+const MAX_REF_DEPTH: usize = 32;
+
+/// A value breaking a rule of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Where the value is in the validated value, e.g. `$.items[0].name`.
+    pub path: String,
+    /// What is wrong with it, e.g. `expected integer, got string "ten"`.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Names the JSON type of a value, telling integers from other numbers.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Whether a value is of a JSON Schema type, integral floats such as `1.0` being integers.
+fn has_type(value: &Value, expected: &str) -> bool {
+    match (expected, value) {
+        ("integer", Value::Number(n)) => n.as_f64().is_some_and(|f| f.fract() == 0.0),
+        ("number", Value::Number(_)) => true,
+        _ => type_name(value) == expected,
+    }
+}
+
+/// Shows a value in a message, shortened if long.
+fn show(value: &Value) -> String {
+    let shown = value.to_string();
+    match shown.char_indices().nth(40) {
+        Some((cut, _)) => format!("{}...", &shown[..cut]),
+        None => shown,
+    }
+}
+
+fn describe(value: &Value) -> String {
+    format!("{} {}", type_name(value), show(value))
+}
+
+/// Walks a schema and a value together, gathering the violations.
+struct Validator<'a> {
+    root: &'a Value,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn violation(&mut self, path: &str, message: String) {
+        self.violations.push(Violation {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// Resolves a `$ref` to a definition of the root schema.
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+
+    /// Whether a value satisfies a schema, without recording its violations.
+    fn satisfies(&self, schema: &Value, value: &Value, depth: usize) -> bool {
+        let mut inner = Validator {
+            root: self.root,
+            violations: Vec::new(),
+        };
+        inner.check(schema, value, "$", depth);
+        inner.violations.is_empty()
+    }
+
+    fn check(&mut self, schema: &Value, value: &Value, path: &str, depth: usize) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                return self.violation(path, "no value is allowed here".to_string());
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            // An unresolvable reference is the server's to resolve, not a fault of the value.
+            if let Some(target) = self.resolve(reference)
+                && depth < MAX_REF_DEPTH
+            {
+                self.check(target, value, path, depth + 1);
+            }
+        }
+
+        if !self.check_type(schema, value, path) {
+            // The other keywords would only repeat the type mismatch.
+            return;
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(value)
+        {
+            let allowed = allowed.iter().map(show).collect::<Vec<_>>().join(", ");
+            self.violation(path, format!("{} is not one of {allowed}", show(value)));
+        }
+        if let Some(constant) = schema.get("const")
+            && constant != value
+        {
+            self.violation(
+                path,
+                format!("expected {}, got {}", show(constant), show(value)),
+            );
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, depth),
+            Value::Array(items) => self.check_array(schema, items, path, depth),
+            Value::String(text) => self.check_length(schema, text.chars().count(), path),
+            Value::Number(_) => self.check_range(schema, value, path),
+            _ => {}
+        }
+        self.check_combinators(schema, value, path, depth);
+    }
+
+    /// Checks the `type` keyword, returning whether the value has one of the types.
+    fn check_type(&mut self, schema: &Map<String, Value>, value: &Value, path: &str) -> bool {
+        let expected: Vec<&str> = match schema.get("type") {
+            Some(Value::String(expected)) => vec![expected],
+            Some(Value::Array(expected)) => expected.iter().filter_map(Value::as_str).collect(),
+            _ => return true,
+        };
+        if expected.is_empty() || expected.iter().any(|t| has_type(value, t)) {
+            return true;
+        }
+        self.violation(
+            path,
+            format!(
+                "expected {}, got {}",
+                expected.join(" or "),
+                describe(value)
+            ),
+        );
+        false
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) {
+        let empty = Map::new();
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(required) {
+                self.violation(path, format!("missing required property `{required}`"));
+            }
+        }
+        for (key, item) in object {
+            let item_path = format!("{path}.{key}");
+            match (properties.get(key), schema.get("additionalProperties")) {
+                (Some(property), _) => self.check(property, item, &item_path, depth),
+                (None, Some(Value::Bool(false))) => {
+                    let mut known: Vec<&str> = properties.keys().map(String::as_str).collect();
+                    known.sort_unstable();
+                    let message = if known.is_empty() {
+                        format!("unexpected property `{key}`")
+                    } else {
+                        format!(
+                            "unexpected property `{key}`, expected one of {}",
+                            known.join(", ")
+                        )
+                    };
+                    self.violation(path, message);
+                }
+                (None, Some(additional)) => self.check(additional, item, &item_path, depth),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        depth: usize,
+    ) {
+        let bound = |key: &str| schema.get(key).and_then(Value::as_u64);
+        if let Some(min) = bound("minItems")
+            && (items.len() as u64) < min
+        {
+            self.violation(
+                path,
+                format!("expected at least {min} items, got {}", items.len()),
+            );
+        }
+        if let Some(max) = bound("maxItems")
+            && (items.len() as u64) > max
+        {
+            self.violation(
+                path,
+                format!("expected at most {max} items, got {}", items.len()),
+            );
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                self.check(item_schema, item, &format!("{path}[{i}]"), depth);
+            }
+        }
+    }
+
+    fn check_length(&mut self, schema: &Map<String, Value>, length: usize, path: &str) {
+        let bound = |key: &str| schema.get(key).and_then(Value::as_u64);
+        if let Some(min) = bound("minLength")
+            && (length as u64) < min
+        {
+            self.violation(
+                path,
+                format!("expected at least {min} characters, got {length}"),
+            );
+        }
+        if let Some(max) = bound("maxLength")
+            && (length as u64) > max
+        {
+            self.violation(
+                path,
+                format!("expected at most {max} characters, got {length}"),
+            );
+        }
+    }
+
+    fn check_range(&mut self, schema: &Map<String, Value>, value: &Value, path: &str) {
+        let Some(number) = value.as_f64() else {
+            return;
+        };
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        let shown = show(value);
+        if let Some(min) = bound("minimum")
+            && number < min
+        {
+            self.violation(path, format!("{shown} is less than the minimum of {min}"));
+        }
+        if let Some(max) = bound("maximum")
+            && number > max
+        {
+            self.violation(
+                path,
+                format!("{shown} is greater than the maximum of {max}"),
+            );
+        }
+        if let Some(min) = bound("exclusiveMinimum")
+            && number <= min
+        {
+            self.violation(path, format!("{shown} is not greater than {min}"));
+        }
+        if let Some(max) = bound("exclusiveMaximum")
+            && number >= max
+        {
+            self.violation(path, format!("{shown} is not less than {max}"));
+        }
+    }
+
+    fn check_combinators(
+        &mut self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) {
+        let branches = |key: &str| schema.get(key).and_then(Value::as_array).map(Vec::as_slice);
+        if let Some(all) = branches("allOf") {
+            for branch in all {
+                self.check(branch, value, path, depth);
+            }
+        }
+        if let Some(any) = branches("anyOf")
+            && !any
+                .iter()
+                .any(|branch| self.satisfies(branch, value, depth))
+        {
+            self.violation(
+                path,
+                format!("{} matches none of the allowed schemas", describe(value)),
+            );
+        }
+        if let Some(one) = branches("oneOf") {
+            let matched = one
+                .iter()
+                .filter(|branch| self.satisfies(branch, value, depth))
+                .count();
+            if matched != 1 {
+                self.violation(
+                    path,
+                    format!(
+                        "{} matches {matched} of the schemas, expected exactly one",
+                        describe(value)
+                    ),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not")
+            && self.satisfies(not, value, depth)
+        {
+            self.violation(
+                path,
+                format!("{} matches a forbidden schema", describe(value)),
+            );
+        }
+    }
+}
+
+/// Validates a value, such as the arguments of a tool call, against a JSON Schema.
+///
+/// # Arguments
+/// * `schema`: The schema, whose `$ref`s may point to its own `$defs` or `definitions`, other
+///   references leaving their values unconstrained.
+/// * `value`: The value to validate.
+///
+/// # Returns
+/// The violations in the order they are found, empty if the value is valid. Each names the
+/// path of the offending value from `$`, the validated value, so that a caller such as an LLM
+/// can fix its arguments without a round trip to the server.
+pub fn validate(schema: &Value, value: &Value) -> Vec<Violation> {
+    let mut validator = Validator {
+        root: schema,
+        violations: Vec::new(),
+    };
+    validator.check(schema, value, "$", 0);
+    validator.violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(schema: &Value, value: &Value) -> Vec<String> {
+        validate(schema, value)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string", "minLength": 1},
+                "limit": {"type": "integer", "minimum": 1},
+                "mode": {"type": "string", "enum": ["read", "write"]}
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        });
+        let args = json!({"path": "a.txt", "limit": 10, "mode": "read"});
+        assert!(validate(&schema, &args).is_empty());
+        assert!(validate(&schema, &json!({"path": "a.txt", "limit": 2.0})).is_empty());
+    }
+
+    #[test]
+    fn test_object_violations() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "limit": {"type": "integer", "minimum": 1}
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        });
+        assert_eq!(
+            messages(&schema, &json!({"limit": "ten", "recursive": true})),
+            vec![
+                "$: missing required property `path`",
+                "$.limit: expected integer, got string \"ten\"",
+                "$: unexpected property `recursive`, expected one of limit, path",
+            ]
+        );
+        assert_eq!(
+            messages(&schema, &json!({"path": "a", "limit": 0})),
+            vec!["$.limit: 0 is less than the minimum of 1"]
+        );
+    }
+
+    #[test]
+    fn test_arrays_and_enums() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": ["a", "b"]},
+                    "maxItems": 2
+                }
+            }
+        });
+        assert_eq!(
+            messages(&schema, &json!({"tags": ["a", "c", 1]})),
+            vec![
+                "$.tags: expected at most 2 items, got 3",
+                "$.tags[1]: \"c\" is not one of \"a\", \"b\"",
+                "$.tags[2]: expected string, got integer 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refs_and_combinators() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "target": {"$ref": "#/$defs/Target"},
+                "timeout": {"anyOf": [{"type": "number"}, {"type": "null"}]}
+            },
+            "$defs": {
+                "Target": {
+                    "type": "object",
+                    "properties": {"host": {"type": "string"}},
+                    "required": ["host"]
+                }
+            }
+        });
+        assert!(validate(&schema, &json!({"target": {"host": "h"}, "timeout": null})).is_empty());
+        assert_eq!(
+            messages(&schema, &json!({"target": {}, "timeout": "5"})),
+            vec![
+                "$.target: missing required property `host`",
+                "$.timeout: string \"5\" matches none of the allowed schemas",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_ref_terminates() {
+        let schema = json!({
+            "$ref": "#/$defs/Node",
+            "$defs": {"Node": {"type": "object", "properties": {"next": {"$ref": "#/$defs/Node"}}}}
+        });
+        assert!(validate(&schema, &json!({"next": {"next": {}}})).is_empty());
+        assert_eq!(
+            messages(&schema, &json!({"next": 1})),
+            vec!["$.next: expected object, got integer 1"]
+        );
+    }
+
+    #[test]
+    fn test_unresolvable_ref_is_unconstrained() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "point": {"$ref": "https://example.com/schemas/point.json"},
+                "color": {"$ref": "#/$defs/Missing", "type": "string"}
+            }
+        });
+        assert!(validate(&schema, &json!({"point": [1, 2], "color": "red"})).is_empty());
+        assert_eq!(
+            messages(&schema, &json!({"color": 1})),
+            vec!["$.color: expected string, got integer 1"]
+        );
+    }
+}
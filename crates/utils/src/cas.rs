@@ -0,0 +1,447 @@
+//! A content-addressed store of blobs, keyed by the BLAKE3 hash of their content.
+//!
+//! Packages keeping copies of files store them here instead of each laying out its own blob
+//! directory: the anki package links the media of decks from it, the rag package caches the
+//! documents it extracts in it, and the checkpoint package keeps the large files it leaves out
+//! of snapshots in it.
+//!
+//! A blob lives at `<root>/<first two hex digits>/<remaining hex digits>` and is written to a
+//! temporary file under `<root>/tmp` first, then made read-only and renamed into place, so
+//! readers never see a partial blob and storing the same content twice writes it once.
+//!
+//! Each package records the blobs it uses as named references under its own owner name, in
+//! `<root>/refs/<owner>`, one `hash`, `name` pair per line separated by a tab. A collection
+//! keeps the blobs referenced by any owner, along with those stored or stored again within a
+//! grace period, so a blob a package has just stored survives until it gets referenced.
+//!
+//! Writes hold a shared lock on `<root>/lock` and collections an exclusive one, so a
+//! collection never removes a blob a write has just found already stored, nor the directory
+//! a write is about to move a blob into.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Length of a hex encoded BLAKE3 hash.
+const HASH_LEN: usize = 64;
+
+/// Name of the directory blobs are written to before being moved into place.
+const TMP_DIRNAME: &str = "tmp";
+
+/// Name of the directory holding the references of each owner.
+const REFS_DIRNAME: &str = "refs";
+
+/// Name of the file locked by writes and collections.
+const LOCK_FILENAME: &str = "lock";
+
+/// Age past which a temporary file is considered abandoned.
+const STALE_TMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Distinguishes the temporary files of the threads of a process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// What a garbage collection of the store removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// Number of blobs kept because they are referenced or recently stored.
+    pub kept: usize,
+    /// Number of blobs removed.
+    pub removed: usize,
+    /// Bytes freed by removing the blobs.
+    pub reclaimed_bytes: u64,
+}
+
+/// A content-addressed store rooted at a directory.
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+/// Whether a string is a hex encoded BLAKE3 hash, in lowercase.
+fn is_hash(hash: &str) -> bool {
+    hash.len() == HASH_LEN && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Refuses owner names that are not a plain file name.
+fn check_owner(owner: &str) -> io::Result<()> {
+    let valid = !owner.is_empty()
+        && !owner.starts_with('.')
+        && owner
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if !valid {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("`{owner}` is not a valid owner name, use letters, digits, `-`, `_` or `.`"),
+        ));
+    }
+    Ok(())
+}
+
+/// Refuses reference names that cannot be written on a line of their own.
+fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("{name:?} is not a valid reference name, it must be a non-empty single line"),
+        ));
+    }
+    Ok(())
+}
+
+/// Marks a blob as used now, so a collection within the grace period keeps it.
+///
+/// Blobs being read-only, the file is opened only to change its times.
+fn touch(path: &Path) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        // FILE_WRITE_ATTRIBUTES, which a read-only file still grants.
+        options.access_mode(0x100);
+    }
+    #[cfg(not(windows))]
+    options.read(true);
+    options.open(path)?.set_modified(SystemTime::now())
+}
+
+/// Removes a blob, clearing its read-only flag first where it prevents the removal.
+fn remove_blob(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        let mut permissions = fs::metadata(path)?.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(path, permissions)?;
+    }
+    fs::remove_file(path)
+}
+
+impl ContentStore {
+    /// Opens the store rooted at a directory, creating the directory if needed.
+    pub fn open<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(TMP_DIRNAME))?;
+        fs::create_dir_all(root.join(REFS_DIRNAME))?;
+        Ok(Self { root })
+    }
+
+    /// The directory the store is rooted at.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of the blob with the given hash, whether it is stored or not.
+    ///
+    /// Errors:
+    ///     `InvalidInput` if the hash is not a lowercase hex encoded BLAKE3 hash, so that no
+    ///     hash can name a path outside the store.
+    pub fn path_of(&self, hash: &str) -> io::Result<PathBuf> {
+        if !is_hash(hash) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("`{hash}` is not a BLAKE3 hash"),
+            ));
+        }
+        Ok(self.root.join(&hash[..2]).join(&hash[2..]))
+    }
+
+    /// Whether the blob with the given hash is stored.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path_of(hash).is_ok_and(|path| path.is_file())
+    }
+
+    /// Locks the store, shared by writes or exclusively by collections and reference
+    /// updates. The lock is released when the returned file is dropped.
+    fn lock(&self, exclusive: bool) -> io::Result<File> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.root.join(LOCK_FILENAME))?;
+        if exclusive {
+            file.lock()?;
+        } else {
+            file.lock_shared()?;
+        }
+        Ok(file)
+    }
+
+    /// Creates a temporary file to write a blob to.
+    fn temp_file(&self) -> io::Result<(PathBuf, File)> {
+        let path = self.root.join(TMP_DIRNAME).join(format!(
+            "{}-{}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create(&path)?;
+        Ok((path, file))
+    }
+
+    /// Marks a stored blob as used now, returning whether it is stored.
+    ///
+    /// Must be called with the store locked.
+    fn refresh(&self, hash: &str) -> io::Result<bool> {
+        match touch(&self.path_of(hash)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Marks a stored blob as used now under the shared lock, returning whether it is stored.
+    fn refresh_locked(&self, hash: &str) -> io::Result<bool> {
+        let _lock = self.lock(false)?;
+        self.refresh(hash)
+    }
+
+    /// Moves a fully written temporary file to the place of its blob, or drops it if the
+    /// blob is already stored.
+    fn commit(&self, temp: &Path, hash: &str) -> io::Result<()> {
+        let path = self.path_of(hash)?;
+        let _lock = self.lock(false)?;
+        if self.refresh(hash)? {
+            return fs::remove_file(temp);
+        }
+        fs::create_dir_all(self.root.join(&hash[..2]))?;
+        let mut permissions = fs::metadata(temp)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(temp, permissions)
+            .and_then(|()| fs::rename(temp, &path))
+            .inspect_err(|_| {
+                let _ = remove_blob(temp);
+            })
+    }
+
+    /// Stores a blob, returning its hash.
+    pub fn put_bytes(&self, content: &[u8]) -> io::Result<String> {
+        let hash = blake3::hash(content).to_hex().to_string();
+        if self.refresh_locked(&hash)? {
+            return Ok(hash);
+        }
+        let (temp, mut file) = self.temp_file()?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        drop(file);
+        self.commit(&temp, &hash)?;
+        Ok(hash)
+    }
+
+    /// Stores the content of a file, returning its hash.
+    ///
+    /// The file is read once, hashed while being copied, so large files are never held in
+    /// memory.
+    pub fn put_file<P: AsRef<Path>>(&self, source: P) -> io::Result<String> {
+        let mut source = File::open(source)?;
+        let (temp, mut file) = self.temp_file()?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buffer = vec![0; 64 * 1024];
+        let copied = (|| {
+            loop {
+                let read = source.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                file.write_all(&buffer[..read])?;
+            }
+            file.sync_all()
+        })();
+        drop(file);
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        let hash = hasher.finalize().to_hex().to_string();
+        self.commit(&temp, &hash)?;
+        Ok(hash)
+    }
+
+    /// Reads a blob.
+    ///
+    /// Errors:
+    ///     `NotFound` if the blob is not stored, `InvalidInput` if the hash is malformed.
+    pub fn get(&self, hash: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path_of(hash)?).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => io::Error::new(
+                ErrorKind::NotFound,
+                format!("No blob `{hash}` in the store"),
+            ),
+            _ => e,
+        })
+    }
+
+    /// Makes a blob appear at `dest`, as a hard link to it where the filesystem allows,
+    /// otherwise as a copy.
+    ///
+    /// A link shares the read-only blob, so the file at `dest` can be replaced but not
+    /// modified in place. The parent directory of `dest` must exist and `dest` must not.
+    ///
+    /// Returns:
+    ///     Whether `dest` is a link rather than a copy.
+    ///
+    /// Errors:
+    ///     `NotFound` if the blob is not stored, `AlreadyExists` if `dest` exists.
+    pub fn link_to<P: AsRef<Path>>(&self, hash: &str, dest: P) -> io::Result<bool> {
+        let path = self.path_of(hash)?;
+        let _lock = self.lock(false)?;
+        if !self.refresh(hash)? {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!("No blob `{hash}` in the store"),
+            ));
+        }
+        match fs::hard_link(&path, &dest) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(e),
+            // Across filesystems, or on one without links. Copied by content, so the copy is
+            // writable rather than read-only like the blob.
+            Err(_) => {
+                let mut target = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&dest)?;
+                io::copy(&mut File::open(&path)?, &mut target)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Path of the references file of an owner.
+    fn refs_path(&self, owner: &str) -> io::Result<PathBuf> {
+        check_owner(owner)?;
+        Ok(self.root.join(REFS_DIRNAME).join(owner))
+    }
+
+    /// Reads the references of an owner, skipping malformed lines.
+    fn read_refs(path: &Path) -> io::Result<BTreeMap<String, String>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter(|(hash, _)| is_hash(hash))
+            .map(|(hash, name)| (name.to_string(), hash.to_string()))
+            .collect())
+    }
+
+    /// Returns the references of an owner, mapping their names to the hashes of the blobs.
+    ///
+    /// Errors:
+    ///     `InvalidInput` if the owner name is not made of letters, digits, `-`, `_` or `.`.
+    pub fn references(&self, owner: &str) -> io::Result<BTreeMap<String, String>> {
+        let path = self.refs_path(owner)?;
+        let _lock = self.lock(false)?;
+        Self::read_refs(&path)
+    }
+
+    /// Returns the hash a reference of an owner names, if any.
+    pub fn reference(&self, owner: &str, name: &str) -> io::Result<Option<String>> {
+        Ok(self.references(owner)?.remove(name))
+    }
+
+    /// Updates the references of an owner, replacing them with what `update` leaves.
+    ///
+    /// The references are rewritten with the store locked, so concurrent updates of the same
+    /// owner are not lost. Only the references of the given owner are touched, those of the
+    /// other packages sharing the store stay as they are.
+    ///
+    /// Errors:
+    ///     `InvalidInput` if the owner name is invalid, or if `update` leaves a malformed hash
+    ///     or a name that is empty or spans several lines.
+    pub fn update_references<F>(&self, owner: &str, update: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut BTreeMap<String, String>),
+    {
+        let path = self.refs_path(owner)?;
+        let _lock = self.lock(true)?;
+        let mut refs = Self::read_refs(&path)?;
+        update(&mut refs);
+        let mut content = String::new();
+        for (name, hash) in &refs {
+            check_name(name)?;
+            self.path_of(hash)?;
+            content.push_str(&format!("{hash}\t{name}\n"));
+        }
+        if refs.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let (temp, mut file) = self.temp_file()?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+    }
+
+    /// Sets a reference of an owner to a blob, replacing the one of the same name.
+    pub fn set_reference(&self, owner: &str, name: &str, hash: &str) -> io::Result<()> {
+        self.update_references(owner, |refs| {
+            refs.insert(name.to_string(), hash.to_string());
+        })
+    }
+
+    /// Removes the blobs no owner references and that were not stored or stored again within
+    /// `grace`, along with temporary files left over by interrupted writes.
+    ///
+    /// The grace period protects the blobs a package has stored but not referenced yet, so it
+    /// should exceed the time packages take between storing a blob and referencing it.
+    pub fn gc(&self, grace: Duration) -> io::Result<GcStats> {
+        let _lock = self.lock(true)?;
+        let mut referenced = HashSet::new();
+        for refs in fs::read_dir(self.root.join(REFS_DIRNAME))? {
+            referenced.extend(Self::read_refs(&refs?.path())?.into_values());
+        }
+
+        let mut stats = GcStats::default();
+        for fan_out in fs::read_dir(&self.root)? {
+            let fan_out = fan_out?;
+            let prefix = fan_out.file_name().to_string_lossy().into_owned();
+            if prefix.len() != 2 || !fan_out.file_type()?.is_dir() {
+                continue;
+            }
+            for blob in fs::read_dir(fan_out.path())? {
+                let blob = blob?;
+                let hash = format!("{prefix}{}", blob.file_name().to_string_lossy());
+                if !is_hash(&hash) {
+                    continue;
+                }
+                let meta = blob.metadata()?;
+                let recent = meta
+                    .modified()
+                    .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age < grace));
+                if recent || referenced.contains(&hash) {
+                    stats.kept += 1;
+                    continue;
+                }
+                remove_blob(&blob.path())?;
+                stats.removed += 1;
+                stats.reclaimed_bytes += meta.len();
+            }
+            // Only succeeds once the directory is empty.
+            let _ = fs::remove_dir(fan_out.path());
+        }
+
+        // Temporary files this old were left by writers that died before finishing.
+        for temp in fs::read_dir(self.root.join(TMP_DIRNAME))?.flatten() {
+            let stale = temp
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_TMP_AGE));
+            if stale {
+                let _ = remove_blob(&temp.path());
+            }
+        }
+        Ok(stats)
+    }
+}
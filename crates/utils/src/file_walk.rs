@@ -0,0 +1,76 @@
+//! Walking of the files below a directory under a set of rules, shared by the directory tree
+//! inspection of the tools and the file browser of the web UI, so both list the same paths.
+//!
+//! By default hidden files are skipped and `.gitignore`, `.ignore` and git exclude files are
+//! respected. Entries come in file name order, each directory before its contents.
+
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{DirEntry, WalkBuilder};
+use std::path::Path;
+
+/// Which entries a walk lists.
+#[derive(Debug, Clone)]
+pub struct WalkRules {
+    /// Maximum depth below the root, 1 listing only its direct entries.
+    pub max_depth: usize,
+    /// Glob patterns of the files to list, all files if empty. Directories are always listed.
+    pub include: Vec<String>,
+    /// Glob patterns of the files and directories to skip.
+    pub exclude: Vec<String>,
+    /// Whether to respect `.gitignore`, `.ignore` and git exclude files.
+    pub gitignore: bool,
+    /// Whether to list hidden files and directories.
+    pub hidden: bool,
+}
+
+impl Default for WalkRules {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            include: vec![],
+            exclude: vec![],
+            gitignore: true,
+            hidden: false,
+        }
+    }
+}
+
+/// Builds glob overrides, negating the patterns if `exclude` is set.
+fn globs(root: &Path, patterns: &[String], exclude: bool) -> Result<Override, String> {
+    let mut builder = OverrideBuilder::new(root);
+    for pattern in patterns {
+        let glob = if exclude {
+            format!("!{pattern}")
+        } else {
+            pattern.clone()
+        };
+        builder
+            .add(&glob)
+            .map_err(|e| format!("Invalid glob `{pattern}`: {e}"))?;
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Walks the entries below `root` that the rules list, leaving out `root` itself and the
+/// entries that cannot be read.
+///
+/// Returns a description of the problem if a glob pattern is invalid.
+pub fn walk(root: &Path, rules: &WalkRules) -> Result<impl Iterator<Item = DirEntry>, String> {
+    let include = globs(root, &rules.include, false)?;
+    let walker = WalkBuilder::new(root)
+        .max_depth(Some(rules.max_depth))
+        .hidden(!rules.hidden)
+        .git_ignore(rules.gitignore)
+        .git_global(rules.gitignore)
+        .git_exclude(rules.gitignore)
+        .ignore(rules.gitignore)
+        .overrides(globs(root, &rules.exclude, true)?)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    Ok(walker.skip(1).filter_map(Result::ok).filter(move |entry| {
+        include.is_empty()
+            || entry.file_type().is_some_and(|t| t.is_dir())
+            || include.matched(entry.path(), false).is_whitelist()
+    }))
+}
@@ -1,3 +1,8 @@
+pub mod cas;
+pub mod file_walk;
+pub mod project;
+pub mod workspace_path;
+
 use std::sync::{Arc, Mutex};
 
 /// A utility function that wraps any type T into an Arc<Mutex<T>>
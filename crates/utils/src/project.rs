@@ -0,0 +1,186 @@
+//! Detection of the project a path belongs to, from the manifest at the root of the project.
+//!
+//! A project is the nearest directory, starting from the path and going up, holding one of
+//! the manifests of `ProjectKind`. A directory may hold several of them, such as a Python
+//! package built by maturin with both a `pyproject.toml` and a `Cargo.toml`, in which case the
+//! project has every kind found and its metadata comes from the first manifest providing it,
+//! in the order of `ProjectKind::ALL`. Manifests that cannot be parsed still identify the
+//! kind of the project, so a manifest being edited does not hide it.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The kinds of project recognized, by their manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    /// `pyproject.toml`
+    Python,
+    /// `Cargo.toml`
+    Rust,
+    /// `package.json`
+    Node,
+    /// `typst.toml`
+    Typst,
+}
+
+impl ProjectKind {
+    /// Every kind, in the order their manifests are read.
+    pub const ALL: [ProjectKind; 4] = [Self::Python, Self::Rust, Self::Node, Self::Typst];
+
+    /// The lowercase name of the kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Python => "python",
+            Self::Rust => "rust",
+            Self::Node => "node",
+            Self::Typst => "typst",
+        }
+    }
+
+    /// The name of the manifest file identifying the kind.
+    pub fn manifest(self) -> &'static str {
+        match self {
+            Self::Python => "pyproject.toml",
+            Self::Rust => "Cargo.toml",
+            Self::Node => "package.json",
+            Self::Typst => "typst.toml",
+        }
+    }
+
+    /// Patterns in the `.gitignore` syntax of the files the tooling of the kind generates,
+    /// such as build outputs, caches and installed dependencies.
+    pub fn ignore(self) -> &'static [&'static str] {
+        match self {
+            Self::Python => &[
+                "__pycache__/",
+                "*.py[cod]",
+                "*.egg-info/",
+                ".venv/",
+                ".pytest_cache/",
+                ".mypy_cache/",
+                ".ruff_cache/",
+                "/build/",
+                "/dist/",
+            ],
+            Self::Rust => &["/target/"],
+            Self::Node => &["node_modules/", "/dist/", ".cache/"],
+            Self::Typst => &[],
+        }
+    }
+
+    /// The JSON pointers of the tables holding the metadata in the manifest, by precedence.
+    fn sections(self) -> &'static [&'static str] {
+        match self {
+            Self::Python => &["/project", "/tool/poetry"],
+            Self::Rust => &["/package", "/workspace/package"],
+            Self::Node => &[""],
+            Self::Typst => &["/package"],
+        }
+    }
+
+    /// Parses the manifest, None if it is not valid.
+    fn parse(self, content: &str) -> Option<Value> {
+        match self {
+            Self::Node => serde_json::from_str(content).ok(),
+            _ => toml::from_str(content).ok(),
+        }
+    }
+}
+
+/// A detected project and the metadata of its manifests.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Project {
+    /// The directory holding the manifests.
+    pub root: PathBuf,
+    /// The kinds of the manifests found in the root, in the order of `ProjectKind::ALL`.
+    pub kinds: Vec<ProjectKind>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Project {
+    /// The path of the manifest of a kind of the project.
+    pub fn manifest(&self, kind: ProjectKind) -> PathBuf {
+        self.root.join(kind.manifest())
+    }
+
+    /// The ignore patterns of every kind of the project, without duplicates.
+    pub fn ignore(&self) -> Vec<&'static str> {
+        let mut patterns = Vec::new();
+        for pattern in self.kinds.iter().flat_map(|kind| kind.ignore()) {
+            if !patterns.contains(pattern) {
+                patterns.push(*pattern);
+            }
+        }
+        patterns
+    }
+
+    /// Reads the manifests of a directory, None if it holds none.
+    fn read(root: &Path) -> io::Result<Option<Self>> {
+        let mut project = Self {
+            root: root.to_path_buf(),
+            kinds: Vec::new(),
+            name: None,
+            version: None,
+            description: None,
+        };
+        for kind in ProjectKind::ALL {
+            let manifest = root.join(kind.manifest());
+            if !manifest.is_file() {
+                continue;
+            }
+            project.kinds.push(kind);
+            let Some(parsed) = kind.parse(&fs::read_to_string(&manifest)?) else {
+                continue;
+            };
+            let field = |key: &str| {
+                kind.sections().iter().find_map(|section| {
+                    parsed
+                        .pointer(&format!("{section}/{key}"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                })
+            };
+            project.name = project.name.or_else(|| field("name"));
+            project.version = project.version.or_else(|| field("version"));
+            project.description = project.description.or_else(|| field("description"));
+        }
+        Ok((!project.kinds.is_empty()).then_some(project))
+    }
+}
+
+/// Detects the project a path belongs to.
+///
+/// Args:
+///     path: A file or directory of the project, which must exist.
+///     boundary: The directory the search stops at, included. The search goes up to the root
+///         of the filesystem if None, or if the path is not below it.
+///
+/// Returns:
+///     The nearest project containing the path, None if there is none.
+///
+/// Errors:
+///     Any error canonicalizing the path or reading a manifest.
+pub fn detect(path: &Path, boundary: Option<&Path>) -> io::Result<Option<Project>> {
+    let path = path.canonicalize()?;
+    let boundary = boundary.map(Path::canonicalize).transpose()?;
+    let start = if path.is_dir() {
+        path.as_path()
+    } else {
+        path.parent().unwrap_or(&path)
+    };
+    for dir in start.ancestors() {
+        if let Some(project) = Project::read(dir)? {
+            return Ok(Some(project));
+        }
+        if boundary.as_deref() == Some(dir) {
+            break;
+        }
+    }
+    Ok(None)
+}
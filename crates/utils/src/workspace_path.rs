@@ -0,0 +1,168 @@
+//! Path handling for files confined to a workspace root.
+//!
+//! Every package reading or writing files on behalf of an agent resolves the paths it is
+//! given through `confine`, which follows symlinks before checking containment, so a link
+//! inside the root cannot be used to reach a file outside of it. Canonical paths are verbatim
+//! (`\\?\`) paths on Windows, which are not subject to the 260 characters limit; `long_path`
+//! turns other absolute paths into verbatim ones and `simplified` reverses it for display.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+/// Builds the error reported for a path leading out of its root.
+fn escape_error(root: &Path, path: &Path) -> io::Error {
+    io::Error::new(
+        ErrorKind::PermissionDenied,
+        format!(
+            "`{}` escapes the workspace root `{}`",
+            simplified(path).display(),
+            simplified(root).display()
+        ),
+    )
+}
+
+/// Resolves a candidate path against a root, failing if it leads out of the root.
+///
+/// A relative candidate is taken relative to the root, an absolute one must lie below it.
+/// The components of the candidate are resolved one by one, following symlinks wherever the
+/// path so far exists, and the components that do not exist yet are applied lexically, so the
+/// candidate may name a file about to be created.
+///
+/// Args:
+///     root: The workspace root, which must exist.
+///     candidate: The path to resolve.
+///
+/// Returns:
+///     The canonical path the candidate designates, inside the canonical root.
+///
+/// Errors:
+///     `PermissionDenied` if the candidate leads out of the root, `NotFound` if the root
+///     does not exist or the candidate goes through a dangling symlink.
+pub fn confine(root: &Path, candidate: &Path) -> io::Result<PathBuf> {
+    let root = root.canonicalize()?;
+    let relative = if candidate.is_absolute() {
+        relativize(&root, candidate)?
+    } else {
+        candidate.to_path_buf()
+    };
+
+    let mut resolved = root.clone();
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => continue,
+        }
+        // Checked after every component, as `..` can lead from a missing directory back to an
+        // existing one, possibly a symlink. A dangling symlink counts as existing,
+        // canonicalizing it fails rather than writing through it to wherever it points.
+        if fs::symlink_metadata(&resolved).is_ok() {
+            resolved = resolved.canonicalize()?;
+        }
+    }
+    if !resolved.starts_with(&root) {
+        return Err(escape_error(&root, candidate));
+    }
+    Ok(resolved)
+}
+
+/// Makes a path relative to a root, without touching the filesystem unless needed.
+///
+/// A relative path is only normalized. An absolute path is stripped of the root, and if it
+/// does not start with the root as given, of the canonical root after canonicalizing the
+/// path, to handle roots reached through symlinks.
+///
+/// Args:
+///     root: The workspace root.
+///     path: The path to relativize.
+///
+/// Returns:
+///     The normalized path relative to the root, empty for the root itself.
+///
+/// Errors:
+///     `PermissionDenied` if the path is not below the root or climbs out of it with `..`.
+pub fn relativize(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let relative = if path.is_relative() {
+        path.to_path_buf()
+    } else if let Ok(relative) = simplified(path).strip_prefix(simplified(root)) {
+        relative.to_path_buf()
+    } else {
+        let canonical = |p: &Path| p.canonicalize().map(|p| simplified(&p));
+        match (canonical(root), canonical(path)) {
+            (Ok(root), Ok(path)) if path.starts_with(&root) => {
+                path.strip_prefix(&root).unwrap_or(&path).to_path_buf()
+            }
+            _ => return Err(escape_error(root, path)),
+        }
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir => {}
+            Component::ParentDir if normalized.pop() => {}
+            _ => return Err(escape_error(root, path)),
+        }
+    }
+    Ok(normalized)
+}
+
+/// Turns an absolute path into a verbatim path on Windows, lifting the 260 characters limit.
+///
+/// The path is normalized first, as verbatim paths are not. Relative and already verbatim
+/// paths are returned unchanged, and so is any path on other platforms.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::path::Prefix;
+        if let Ok(absolute) = std::path::absolute(path)
+            && let Some(Component::Prefix(prefix)) = absolute.components().next()
+        {
+            let rest = absolute
+                .strip_prefix(prefix.as_os_str())
+                .unwrap_or(&absolute);
+            let mut long = match prefix.kind() {
+                Prefix::Disk(drive) => PathBuf::from(format!(r"\\?\{}:\", drive as char)),
+                Prefix::UNC(server, share) => {
+                    let mut long = PathBuf::from(r"\\?\UNC\");
+                    long.push(server);
+                    long.push(share);
+                    long
+                }
+                _ => return path.to_path_buf(),
+            };
+            long.push(rest.strip_prefix(r"\").unwrap_or(rest));
+            return long;
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Strips the verbatim prefix of a path on Windows where it is not needed to designate it,
+/// for display or for comparison with paths given by users. Other paths are unchanged.
+pub fn simplified(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::path::Prefix;
+        if let Some(Component::Prefix(prefix)) = path.components().next() {
+            let rest = path.strip_prefix(prefix.as_os_str()).unwrap_or(path);
+            let mut short = match prefix.kind() {
+                Prefix::VerbatimDisk(drive) => PathBuf::from(format!(r"{}:\", drive as char)),
+                Prefix::VerbatimUNC(server, share) => {
+                    let mut short = PathBuf::from(r"\\");
+                    short.push(server);
+                    short.push(share);
+                    short
+                }
+                _ => return path.to_path_buf(),
+            };
+            short.push(rest.strip_prefix(r"\").unwrap_or(rest));
+            return short;
+        }
+    }
+    path.to_path_buf()
+}
@@ -7,9 +7,12 @@ import typing
 
 __all__ = [
     "add_csv_data",
+    "add_media",
     "compile_deck",
     "create_deck_project",
+    "deck_stats",
     "extract_html_component",
+    "find_duplicate_notes",
     "save_metadata",
     "save_template",
 ]
@@ -57,8 +60,47 @@ def add_csv_data(
         - The data directory will be created if it doesn't exist within the project structure.
     """
 
+def add_media(
+    project_path: builtins.str | os.PathLike | pathlib.Path,
+    source: builtins.str | os.PathLike | pathlib.Path,
+    model_name: typing.Optional[builtins.str] = None,
+    name: typing.Optional[builtins.str] = None,
+    store_root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+) -> pathlib.Path:
+    r"""Add a media file to an Anki deck project, sharing it with other decks through the
+    content-addressed store of the roaming directory.
+
+    The file is stored once in the shared `ContentStore` and hard linked from there into the
+    media directory of the model, or of the deck, so the same image or sound used by several
+    decks takes up space once. Where links are not possible, such as across filesystems, the
+    file is copied instead.
+
+    Args:
+        project_path: The path to the root directory of the Anki deck project.
+        source: The path of the media file to add.
+        model_name: The model whose `media` directory receives the file. Defaults to None,
+                    adding it to the `media` directory of the deck.
+        name: The file name of the media in the project, referenced by the templates.
+              Defaults to None, keeping the file name of `source`.
+        store_root: The directory of the content-addressed store. Defaults to None, using the
+                    `cas` directory of the roaming directory shared by all packages.
+
+    Returns:
+        The path of the media in the project.
+
+    Raises:
+        Exception: If the name contains a path separator, the source cannot be read, or a
+                   different media file of that name is already in the project.
+
+    Note:
+        A linked media file shares the stored blob, which is read-only: replace the file to
+        change the media rather than editing it in place.
+    """
+
 def compile_deck(
-    path: builtins.str | os.PathLike | pathlib.Path, output: builtins.str | os.PathLike | pathlib.Path
+    path: builtins.str | os.PathLike | pathlib.Path,
+    output: builtins.str | os.PathLike | pathlib.Path,
+    on_duplicate: builtins.str = "warn",
 ) -> None:
     r"""Compile an Anki deck from a project directory and export it to the specified output path.
 
@@ -74,6 +116,9 @@ def compile_deck(
     4. Collecting and packaging media files referenced in the templates
     5. Generating the final .apkg file with proper Anki database structure
 
+    Notes sharing a first field, which Anki treats as duplicates, are handled according to
+    `on_duplicate`; see `find_duplicate_notes` for how they are detected.
+
     Args:
         path: The absolute or relative path to the Anki deck project directory.
               This directory should contain:
@@ -86,10 +131,16 @@ def compile_deck(
                 The file will be created if it doesn't exist, or overwritten if it does.
                 The path should include the desired filename with .apkg extension.
 
+        on_duplicate: What to do with notes repeating the first field of an earlier note.
+                      "warn" keeps them and logs a warning per duplicate group, "skip" keeps
+                      only the first note, and "merge" folds notes of the same model into the
+                      first one, filling its empty fields and appending differing values.
+
     Returns:
         None on success.
 
     Raises:
+        ValueError: If `on_duplicate` is not one of "warn", "skip" or "merge".
         Exception: If the project directory structure is invalid or missing required files.
         Exception: If the deck.toml configuration file contains invalid settings.
         Exception: If any model definition files are malformed or contain syntax errors.
@@ -224,6 +275,34 @@ def create_deck_project(
           configuration files and clear separation of content and presentation.
     """
 
+def deck_stats(path: builtins.str | os.PathLike | pathlib.Path) -> typing.Dict[builtins.str, typing.Any]:
+    r"""Compute statistics of the content of an Anki deck project, to sanity-check generated
+    content before exporting it.
+
+    Notes are counted as loaded from the data files, before duplicates are handled. A field
+    counts as filled when it is not blank, and its average length is taken over the notes
+    filling it. A template generates a card for a note when its front, with the conditions
+    of its `when.yaml` applied, references at least one filled field, as Anki does.
+
+    Args:
+        path: The absolute or relative path to the Anki deck project directory.
+
+    Returns:
+        A dictionary with the `notes` and `cards` of the deck, its `media_files` and their
+        total size in `media_bytes`, and `models`, a list with per model its `name`, `notes`,
+        `media_files` and `media_bytes`, `fields` (each with `name`, `filled`, `fill_rate`
+        and `average_length`) and `templates` (each with `name`, `cards` and `coverage`,
+        the share of notes generating a card).
+
+    Raises:
+        Exception: If a model definition, template condition or data file cannot be read.
+
+    Example:
+        >>> stats = deck_stats("/path/to/my-deck-project")
+        >>> [(field["name"], field["fill_rate"]) for field in stats["models"][0]["fields"]]
+        [('Front', 1.0), ('Back', 0.5)]
+    """
+
 def extract_html_component(html: builtins.str) -> tuple[builtins.str, builtins.str, builtins.str]:
     r"""Extract HTML components by separating layout, JavaScript, and CSS content.
 
@@ -249,6 +328,31 @@ def extract_html_component(html: builtins.str) -> tuple[builtins.str, builtins.s
         >>> print(css)     # 'div{color:red}'
     """
 
+def find_duplicate_notes(
+    path: builtins.str | os.PathLike | pathlib.Path,
+) -> builtins.list[tuple[builtins.str, builtins.list[tuple[builtins.str, builtins.int]]]]:
+    r"""Find the notes of an Anki deck project that Anki would treat as duplicates.
+
+    Anki identifies duplicate notes by their first field. This function compares the first
+    field of every row of every data file, within and across models, after stripping HTML
+    tags and collapsing whitespace. Rows with an empty first field are ignored.
+
+    Args:
+        path: The absolute or relative path to the Anki deck project directory.
+
+    Returns:
+        A list of `(key, notes)` tuples, one per group of duplicates in load order, where
+        `key` is the normalized first field and `notes` lists the `(model_name, row)` pairs
+        sharing it. Rows are 1-based and exclude the CSV header.
+
+    Raises:
+        Exception: If a data file cannot be read.
+
+    Example:
+        >>> find_duplicate_notes("/path/to/my-deck-project")
+        [('What is 2+2?', [('basic_card', 2), ('basic_card', 5)])]
+    """
+
 def save_metadata(dir_path: builtins.str | os.PathLike | pathlib.Path, name: builtins.str, data: typing.Any) -> None:
     r"""Save metadata as a YAML file in the specified directory.
 
@@ -285,6 +389,7 @@ def save_template(
     front: builtins.str,
     back: builtins.str,
     css: typing.Optional[builtins.str] = None,
+    requires: typing.Optional[typing.Sequence[builtins.str]] = None,
 ) -> None:
     r"""Save card type template files (front.html, back.html, and optional style.css) to a directory.
 
@@ -297,6 +402,7 @@ def save_template(
     - front.html: Contains the HTML template for the front side of the card
     - back.html: Contains the HTML template for the back side of the card
     - style.css: Contains CSS styling rules (only if css parameter is provided)
+    - when.yaml: Lists the fields required to generate the card (only if requires is provided)
 
     Args:
         dir_path: The directory path where the template files should be saved.
@@ -319,6 +425,10 @@ def save_template(
              valid stylesheet content that styles the HTML elements
              in the front and back templates.
 
+        requires: Names of the fields that must all be non-empty for a note to generate
+                  a card of this template. If provided, they are written to a when.yaml file,
+                  and compiling the deck fails if any of them is not a field of the model.
+
     Returns:
         None on success.
 
@@ -345,6 +455,14 @@ def save_template(
         ...     css=".question { font-size: 24px; color: blue; } .answer { background: #f0f0f0; }"
         ... )
 
+        Card template only generated for notes with an example sentence:
+        >>> save_template(
+        ...     Path("/path/to/models/vocab/templates/usage_card"),
+        ...     front="<p>{{Example}}</p>",
+        ...     back="{{FrontSide}}<hr>{{Word}}",
+        ...     requires=["Example"]
+        ... )
+
     Note:
         - The directory must exist before calling this function - it will not be created automatically.
         - Existing template files in the directory will be overwritten without warning.
@@ -0,0 +1,35 @@
+"""Tests for conditional card generation configured per template."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_anki.rust import compile_deck, create_deck_project, save_template
+
+
+@pytest.fixture
+def project(tmp_path: Path) -> Path:
+    """Create a deck project whose model has an optional field."""
+    path = tmp_path / "deck"
+    create_deck_project(path, "Deck", "Conditions", "Tester", "vocab", ["Word", "Meaning", "Example"])
+    (path / "data" / "vocab.csv").write_text("Word,Meaning,Example\nhund,dog,Der Hund bellt\nkatze,cat,\n")
+    return path
+
+
+def test_compile_with_condition(project: Path, tmp_path: Path) -> None:
+    """Templates requiring fields of the model compile."""
+    template = project / "models" / "vocab" / "templates" / "usage"
+    save_template(template, "<p>{{Example}}</p>", "{{FrontSide}}<hr>{{Word}}", requires=["Example"])
+    assert "Example" in (template / "when.yaml").read_text()
+
+    output = tmp_path / "deck.apkg"
+    compile_deck(project, output)
+    assert output.exists()
+
+
+def test_compile_with_unknown_field(project: Path, tmp_path: Path) -> None:
+    """Templates requiring fields missing from the model are rejected."""
+    template = project / "models" / "vocab" / "templates" / "usage"
+    save_template(template, "<p>{{Usage}}</p>", "{{FrontSide}}", requires=["Usage"])
+
+    with pytest.raises(Exception, match="unknown field `Usage`"):
+        compile_deck(project, tmp_path / "deck.apkg")
@@ -0,0 +1,40 @@
+"""Tests for duplicate note detection in deck projects."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_anki.rust import compile_deck, create_deck_project, find_duplicate_notes
+
+
+@pytest.fixture
+def project(tmp_path: Path) -> Path:
+    """Create a deck project whose data repeats first fields within and across models."""
+    path = tmp_path / "deck"
+    create_deck_project(path, "Deck", "Duplicates", "Tester", "basic_card", ["Front", "Back"])
+    create_deck_project(path, "Deck", "Duplicates", "Tester", "other_card", ["Front", "Back"])
+    (path / "models" / "other_card" / "fields.yaml").write_text("model_id: 1\nfields:\n- Front\n- Back\n")
+    (path / "data" / "basic_card.csv").write_text('Front,Back\n"<b>Paris</b>",France\nParis ,\nRome,Italy\n')
+    (path / "data" / "other_card.csv").write_text("Front,Back\nRome,Capital\nBerlin,Germany\n")
+    return path
+
+
+def test_find_duplicate_notes(project: Path) -> None:
+    """Duplicates are grouped by their normalized first field in load order."""
+    assert find_duplicate_notes(project) == [
+        ("Paris", [("basic_card", 1), ("basic_card", 2)]),
+        ("Rome", [("basic_card", 3), ("other_card", 1)]),
+    ]
+
+
+@pytest.mark.parametrize("policy", ["warn", "skip", "merge"])
+def test_compile_deck_on_duplicate(project: Path, tmp_path: Path, policy: str) -> None:
+    """Every duplicate policy produces a deck."""
+    output = tmp_path / "deck.apkg"
+    compile_deck(project, output, on_duplicate=policy)
+    assert output.exists()
+
+
+def test_compile_deck_unknown_policy(project: Path, tmp_path: Path) -> None:
+    """An unknown duplicate policy is rejected."""
+    with pytest.raises(ValueError, match="Unknown duplicate policy"):
+        compile_deck(project, tmp_path / "deck.apkg", on_duplicate="ignore")
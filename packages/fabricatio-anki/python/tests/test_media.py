@@ -0,0 +1,58 @@
+"""Tests for adding media to deck projects through the content-addressed store."""
+
+import json
+import zipfile
+from pathlib import Path
+
+import pytest
+from fabricatio_anki.rust import add_media, compile_deck, create_deck_project
+from fabricatio_core.rust import ContentStore, blake3_hash
+
+
+@pytest.fixture
+def project(tmp_path: Path) -> Path:
+    """Create a deck project with a single model."""
+    path = tmp_path / "deck"
+    create_deck_project(path, "Deck", "Media", "Tester", "vocab", ["Word", "Meaning"])
+    (path / "data" / "vocab.csv").write_text("Word,Meaning\nchat,cat\n")
+    return path
+
+
+def test_add_media_shares_blobs(project: Path, tmp_path: Path) -> None:
+    """Media added to the deck and to a model are linked from the same stored blob."""
+    store_root = tmp_path / "cas"
+    source = tmp_path / "cat.png"
+    source.write_bytes(b"\x89PNG cat")
+
+    deck_media = add_media(project, source, store_root=store_root)
+    model_media = add_media(project, source, model_name="vocab", name="kitten.png", store_root=store_root)
+    assert deck_media == project / "media" / "cat.png"
+    assert model_media == project / "models" / "vocab" / "media" / "kitten.png"
+    assert deck_media.read_bytes() == model_media.read_bytes() == b"\x89PNG cat"
+
+    blob = ContentStore(store_root).path_of(blake3_hash(b"\x89PNG cat"))
+    assert deck_media.stat().st_ino == model_media.stat().st_ino == blob.stat().st_ino
+
+    # Adding the same content again is a no-op, a different one under the same name is refused.
+    assert add_media(project, source, store_root=store_root) == deck_media
+    other = tmp_path / "other" / "cat.png"
+    other.parent.mkdir()
+    other.write_bytes(b"\x89PNG another cat")
+    with pytest.raises(RuntimeError, match="different media file"):
+        add_media(project, other, store_root=store_root)
+    with pytest.raises(RuntimeError, match="path separator"):
+        add_media(project, source, name="../cat.png", store_root=store_root)
+
+
+def test_compile_packages_model_media(project: Path, tmp_path: Path) -> None:
+    """Media of the deck and of its models are packaged under their file names."""
+    store_root = tmp_path / "cas"
+    for name, model in (("cat.png", None), ("dog.png", "vocab")):
+        source = tmp_path / name
+        source.write_bytes(name.encode())
+        add_media(project, source, model_name=model, store_root=store_root)
+
+    output = tmp_path / "deck.apkg"
+    compile_deck(project, output)
+    with zipfile.ZipFile(output) as package:
+        assert sorted(json.loads(package.read("media")).values()) == ["cat.png", "dog.png"]
@@ -0,0 +1,54 @@
+"""Tests for the statistics of deck projects."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_anki.rust import create_deck_project, deck_stats, save_template
+
+
+@pytest.fixture
+def project(tmp_path: Path) -> Path:
+    """Create a deck project with a conditional template, a sparse field and media files."""
+    path = tmp_path / "deck"
+    create_deck_project(path, "Deck", "Stats", "Tester", "vocab", ["Word", "Meaning", "Example"])
+    templates = path / "models" / "vocab" / "templates"
+    save_template(templates / "card", "{{Word}}", "{{FrontSide}}<hr>{{Meaning}}")
+    save_template(templates / "example", "{{Example}}", "{{Word}}", requires=["Meaning"])
+    rows = ["Word,Meaning,Example", "chat,cat,Le chat dort.", "chien,dog,", "loup,,Un loup."]
+    (path / "data" / "vocab.csv").write_text("\n".join(rows) + "\n")
+    (path / "media" / "cat.png").write_bytes(b"\x00" * 100)
+    (path / "models" / "vocab" / "media").mkdir()
+    (path / "models" / "vocab" / "media" / "dog.png").write_bytes(b"\x00" * 28)
+    return path
+
+
+def test_deck_stats(project: Path) -> None:
+    """Notes, fill rates, lengths, template coverage and media are reported per model and in total."""
+    stats = deck_stats(project)
+    assert (stats["notes"], stats["cards"]) == (3, 4)
+    assert (stats["media_files"], stats["media_bytes"]) == (2, 128)
+
+    (model,) = stats["models"]
+    assert (model["name"], model["notes"], model["media_files"], model["media_bytes"]) == ("vocab", 3, 1, 28)
+    fields = {field["name"]: field for field in model["fields"]}
+    assert fields["Word"]["filled"] == 3
+    assert fields["Word"]["fill_rate"] == 1.0
+    assert fields["Meaning"]["fill_rate"] == pytest.approx(2 / 3)
+    assert fields["Meaning"]["average_length"] == 3.0
+    assert fields["Example"]["filled"] == 2
+
+    templates = {template["name"]: template for template in model["templates"]}
+    assert templates["card"]["cards"] == 3
+    assert templates["example"]["cards"] == 1
+    assert templates["example"]["coverage"] == pytest.approx(1 / 3)
+
+
+def test_deck_stats_empty_model(tmp_path: Path) -> None:
+    """A model without data reports zero rates instead of failing."""
+    path = tmp_path / "deck"
+    create_deck_project(path, "Deck", "Stats", "Tester", "basic_card", ["Front", "Back"])
+    (path / "data" / "basic_card.csv").write_text("Front,Back\n")
+    (model,) = deck_stats(path)["models"]
+    assert model["notes"] == 0
+    assert all(field["fill_rate"] == 0.0 for field in model["fields"])
+    assert all(template["coverage"] == 0.0 for template in model["templates"])
@@ -1,7 +1,10 @@
-use deck_loader::loader::{AnkiDeckLoader, constants};
-use pyo3::exceptions::PyRuntimeError;
+use deck_loader::duplicates::DuplicatePolicy;
+use deck_loader::loader::{AnkiDeckLoader, TemplateCondition, constants};
+use fabricatio_constants::CAS;
+use fabricatio_logger::warn;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pythonize::depythonize;
+use pythonize::{depythonize, pythonize};
 
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
@@ -9,6 +12,7 @@ use pyo3_stub_gen::derive::*;
 use serde_yaml2::wrapper::YamlNodeWrapper;
 use std::fs;
 use std::path::PathBuf;
+use utils::cas::ContentStore;
 
 #[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
 #[pyfunction]
@@ -26,6 +30,9 @@ use std::path::PathBuf;
 /// 4. Collecting and packaging media files referenced in the templates
 /// 5. Generating the final .apkg file with proper Anki database structure
 ///
+/// Notes sharing a first field, which Anki treats as duplicates, are handled according to
+/// `on_duplicate`; see `find_duplicate_notes` for how they are detected.
+///
 /// Args:
 ///     path: The absolute or relative path to the Anki deck project directory.
 ///           This directory should contain:
@@ -38,10 +45,16 @@ use std::path::PathBuf;
 ///             The file will be created if it doesn't exist, or overwritten if it does.
 ///             The path should include the desired filename with .apkg extension.
 ///
+///     on_duplicate: What to do with notes repeating the first field of an earlier note.
+///                   "warn" keeps them and logs a warning per duplicate group, "skip" keeps
+///                   only the first note, and "merge" folds notes of the same model into the
+///                   first one, filling its empty fields and appending differing values.
+///
 /// Returns:
 ///     None on success.
 ///
 /// Raises:
+///     ValueError: If `on_duplicate` is not one of "warn", "skip" or "merge".
 ///     Exception: If the project directory structure is invalid or missing required files.
 ///     Exception: If the deck.toml configuration file contains invalid settings.
 ///     Exception: If any model definition files are malformed or contain syntax errors.
@@ -57,13 +70,96 @@ use std::path::PathBuf;
 ///     The function will validate the entire project structure before beginning compilation.
 ///     All errors are reported with descriptive messages to help identify and fix issues.
 ///     The generated .apkg file is compatible with Anki 2.1 and later versions.
-fn compile_deck(path: PathBuf, output: PathBuf) -> PyResult<()> {
-    AnkiDeckLoader::new(path)
+#[pyo3(signature = (path, output, on_duplicate = "warn"))]
+fn compile_deck(path: PathBuf, output: PathBuf, on_duplicate: &str) -> PyResult<()> {
+    let policy = on_duplicate
+        .parse::<DuplicatePolicy>()
+        .map_err(PyValueError::new_err)?;
+    let duplicates = AnkiDeckLoader::new(path)
+        .with_duplicate_policy(policy)
         .export_deck(output)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(format!("{}", e)))?;
+    if policy == DuplicatePolicy::Warn {
+        for duplicate in duplicates {
+            warn!("Duplicate note: {duplicate}");
+        }
+    }
     Ok(())
 }
 
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+/// Find the notes of an Anki deck project that Anki would treat as duplicates.
+///
+/// Anki identifies duplicate notes by their first field. This function compares the first
+/// field of every row of every data file, within and across models, after stripping HTML
+/// tags and collapsing whitespace. Rows with an empty first field are ignored.
+///
+/// Args:
+///     path: The absolute or relative path to the Anki deck project directory.
+///
+/// Returns:
+///     A list of `(key, notes)` tuples, one per group of duplicates in load order, where
+///     `key` is the normalized first field and `notes` lists the `(model_name, row)` pairs
+///     sharing it. Rows are 1-based and exclude the CSV header.
+///
+/// Raises:
+///     Exception: If a data file cannot be read.
+///
+/// Example:
+///     >>> find_duplicate_notes("/path/to/my-deck-project")
+///     [('What is 2+2?', [('basic_card', 2), ('basic_card', 5)])]
+fn find_duplicate_notes(path: PathBuf) -> PyResult<Vec<(String, Vec<(String, usize)>)>> {
+    let duplicates = AnkiDeckLoader::new(path)
+        .find_duplicates()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(format!("{}", e)))?;
+    Ok(duplicates
+        .into_iter()
+        .map(|duplicate| {
+            let notes = duplicate
+                .notes
+                .into_iter()
+                .map(|note| (note.model, note.row))
+                .collect();
+            (duplicate.key, notes)
+        })
+        .collect())
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+/// Compute statistics of the content of an Anki deck project, to sanity-check generated
+/// content before exporting it.
+///
+/// Notes are counted as loaded from the data files, before duplicates are handled. A field
+/// counts as filled when it is not blank, and its average length is taken over the notes
+/// filling it. A template generates a card for a note when its front, with the conditions
+/// of its `when.yaml` applied, references at least one filled field, as Anki does.
+///
+/// Args:
+///     path: The absolute or relative path to the Anki deck project directory.
+///
+/// Returns:
+///     A dictionary with the `notes` and `cards` of the deck, its `media_files` and their
+///     total size in `media_bytes`, and `models`, a list with per model its `name`, `notes`,
+///     `media_files` and `media_bytes`, `fields` (each with `name`, `filled`, `fill_rate`
+///     and `average_length`) and `templates` (each with `name`, `cards` and `coverage`,
+///     the share of notes generating a card).
+///
+/// Raises:
+///     Exception: If a model definition, template condition or data file cannot be read.
+///
+/// Example:
+///     >>> stats = deck_stats("/path/to/my-deck-project")
+///     >>> [(field["name"], field["fill_rate"]) for field in stats["models"][0]["fields"]]
+///     [('Front', 1.0), ('Back', 0.5)]
+fn deck_stats<'py>(python: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyAny>> {
+    let stats = AnkiDeckLoader::new(path)
+        .stats()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(format!("{}", e)))?;
+    pythonize(python, &stats).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
 #[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
 #[pyfunction]
 /// Create a new Anki deck project template with the specified configuration.
@@ -281,6 +377,53 @@ fn add_csv_data(project_path: PathBuf, model_name: &str, data: PathBuf) -> PyRes
         .map_err(|e| PyRuntimeError::new_err(e.to_string()))
 }
 
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+/// Add a media file to an Anki deck project, sharing it with other decks through the
+/// content-addressed store of the roaming directory.
+///
+/// The file is stored once in the shared `ContentStore` and hard linked from there into the
+/// media directory of the model, or of the deck, so the same image or sound used by several
+/// decks takes up space once. Where links are not possible, such as across filesystems, the
+/// file is copied instead.
+///
+/// Args:
+///     project_path: The path to the root directory of the Anki deck project.
+///     source: The path of the media file to add.
+///     model_name: The model whose `media` directory receives the file. Defaults to None,
+///                 adding it to the `media` directory of the deck.
+///     name: The file name of the media in the project, referenced by the templates.
+///           Defaults to None, keeping the file name of `source`.
+///     store_root: The directory of the content-addressed store. Defaults to None, using the
+///                 `cas` directory of the roaming directory shared by all packages.
+///
+/// Returns:
+///     The path of the media in the project.
+///
+/// Raises:
+///     Exception: If the name contains a path separator, the source cannot be read, or a
+///                different media file of that name is already in the project.
+///
+/// Note:
+///     A linked media file shares the stored blob, which is read-only: replace the file to
+///     change the media rather than editing it in place.
+#[pyo3(signature = (project_path, source, model_name = None, name = None, store_root = None))]
+fn add_media(
+    python: Python,
+    project_path: PathBuf,
+    source: PathBuf,
+    model_name: Option<&str>,
+    name: Option<&str>,
+    store_root: Option<PathBuf>,
+) -> PyResult<PathBuf> {
+    python
+        .detach(|| {
+            let store = ContentStore::open(store_root.unwrap_or_else(|| CAS.clone()))?;
+            AnkiDeckLoader::new(project_path).add_media(&store, &source, model_name, name)
+        })
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
 #[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
 #[pyfunction]
 /// Save card type template files (front.html, back.html, and optional style.css) to a directory.
@@ -294,6 +437,7 @@ fn add_csv_data(project_path: PathBuf, model_name: &str, data: PathBuf) -> PyRes
 /// - front.html: Contains the HTML template for the front side of the card
 /// - back.html: Contains the HTML template for the back side of the card
 /// - style.css: Contains CSS styling rules (only if css parameter is provided)
+/// - when.yaml: Lists the fields required to generate the card (only if requires is provided)
 ///
 /// Args:
 ///     dir_path: The directory path where the template files should be saved.
@@ -316,6 +460,10 @@ fn add_csv_data(project_path: PathBuf, model_name: &str, data: PathBuf) -> PyRes
 ///          valid stylesheet content that styles the HTML elements
 ///          in the front and back templates.
 ///
+///     requires: Names of the fields that must all be non-empty for a note to generate
+///               a card of this template. If provided, they are written to a when.yaml file,
+///               and compiling the deck fails if any of them is not a field of the model.
+///
 /// Returns:
 ///     None on success.
 ///
@@ -342,6 +490,14 @@ fn add_csv_data(project_path: PathBuf, model_name: &str, data: PathBuf) -> PyRes
 ///     ...     css=".question { font-size: 24px; color: blue; } .answer { background: #f0f0f0; }"
 ///     ... )
 ///
+///     Card template only generated for notes with an example sentence:
+///     >>> save_template(
+///     ...     Path("/path/to/models/vocab/templates/usage_card"),
+///     ...     front="<p>{{Example}}</p>",
+///     ...     back="{{FrontSide}}<hr>{{Word}}",
+///     ...     requires=["Example"]
+///     ... )
+///
 /// Note:
 ///     - The directory must exist before calling this function - it will not be created automatically.
 ///     - Existing template files in the directory will be overwritten without warning.
@@ -349,12 +505,13 @@ fn add_csv_data(project_path: PathBuf, model_name: &str, data: PathBuf) -> PyRes
 ///     - Field placeholders in the format {{FieldName}} will be replaced by Anki with actual card data.
 ///     - The CSS file is optional but recommended for consistent card styling across different devices.
 ///     - Template files use standard HTML/CSS which allows for rich formatting and multimedia content.
-#[pyo3(signature=(dir_path, front, back, css=None))]
+#[pyo3(signature=(dir_path, front, back, css=None, requires=None))]
 fn save_template(
     dir_path: PathBuf,
     front: String,
     back: String,
     css: Option<String>,
+    requires: Option<Vec<String>>,
 ) -> PyResult<()> {
     fs::create_dir_all(&dir_path)?;
     fs::write(dir_path.join(constants::TEMPLATE_FRONT), front)?;
@@ -362,6 +519,11 @@ fn save_template(
     if let Some(css) = css {
         fs::write(dir_path.join(constants::TEMPLATE_CSS), css)?;
     }
+    if let Some(requires) = requires {
+        let content = serde_yaml2::to_string(TemplateCondition { requires })
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        fs::write(dir_path.join(constants::TEMPLATE_CONDITION), content)?;
+    }
 
     Ok(())
 }
@@ -439,9 +601,12 @@ fn extract_html_component(html: &str) -> PyResult<(String, String, String)> {
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(compile_deck, m)?)?;
     m.add_function(wrap_pyfunction!(create_deck_project, m)?)?;
+    m.add_function(wrap_pyfunction!(find_duplicate_notes, m)?)?;
+    m.add_function(wrap_pyfunction!(deck_stats, m)?)?;
     m.add_function(wrap_pyfunction!(save_metadata, m)?)?;
     m.add_function(wrap_pyfunction!(save_template, m)?)?;
     m.add_function(wrap_pyfunction!(add_csv_data, m)?)?;
+    m.add_function(wrap_pyfunction!(add_media, m)?)?;
     m.add_function(wrap_pyfunction!(extract_html_component, m)?)?;
     Ok(())
 }
@@ -1,5 +1,7 @@
 use clap::{Parser, ValueEnum};
+use deck_loader::duplicates::DuplicatePolicy;
 use deck_loader::loader::AnkiDeckLoader as CoreAnkiDeckLoader;
+use deck_loader::stats::DeckStats;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -89,6 +91,15 @@ enum Cli {
         /// Dry run - validate and show what would be built without creating output
         #[arg(short, long, help = "Perform a dry run without creating output files")]
         dry_run: bool,
+
+        /// How to handle notes sharing a first field
+        #[arg(
+            long,
+            value_name = "POLICY",
+            default_value = "warn",
+            help = "How to handle notes sharing a first field (warn, skip or merge)"
+        )]
+        on_duplicate: DuplicatePolicy,
     },
 
     /// Create a new project template
@@ -182,6 +193,13 @@ enum Cli {
         fix: bool,
     },
 
+    /// Show statistics of the content of a project
+    Stats {
+        /// Path to the project directory
+        #[arg(value_name = "PROJECT_PATH", help = "Path to the project directory")]
+        project_path: PathBuf,
+    },
+
     /// Clean build artifacts and temporary files
     Clean {
         /// Path to the project directory
@@ -217,6 +235,7 @@ fn handle_cli_command(cli: Cli) -> Result<(), String> {
             log_level,
             compression,
             dry_run,
+            on_duplicate,
         } => handle_build(
             project_path,
             output,
@@ -226,6 +245,7 @@ fn handle_cli_command(cli: Cli) -> Result<(), String> {
             log_level,
             compression,
             dry_run,
+            on_duplicate,
         ),
         Cli::New {
             project_path,
@@ -254,6 +274,7 @@ fn handle_cli_command(cli: Cli) -> Result<(), String> {
             verbose,
             fix,
         } => handle_validate(project_path, strict, verbose, fix),
+        Cli::Stats { project_path } => handle_stats(project_path),
         Cli::Clean {
             project_path,
             all,
@@ -271,14 +292,16 @@ fn handle_build(
     log_level: LogLevel,
     compression: u8,
     dry_run: bool,
+    on_duplicate: DuplicatePolicy,
 ) -> Result<(), String> {
     if verbose {
         println!("Building deck from project: {}", project_path.display());
         println!("Log level: {:?}", log_level);
         println!("Compression: {}", compression);
+        println!("Duplicate policy: {}", on_duplicate);
     }
 
-    let loader = CoreAnkiDeckLoader::new(project_path.clone());
+    let loader = CoreAnkiDeckLoader::new(project_path.clone()).with_duplicate_policy(on_duplicate);
 
     let output_path = output.unwrap_or_else(|| {
         let mut path = project_path.clone();
@@ -317,10 +340,17 @@ fn handle_build(
         );
     }
 
-    loader
+    let duplicates = loader
         .export_deck(output_path.clone())
         .map_err(|e| format!("Failed to export deck: {}", e))?;
 
+    for duplicate in &duplicates {
+        match on_duplicate {
+            DuplicatePolicy::Warn => println!("Warning: duplicate note {}", duplicate),
+            DuplicatePolicy::Skip => println!("Skipped duplicate note {}", duplicate),
+            DuplicatePolicy::Merge => println!("Merged duplicate note {}", duplicate),
+        }
+    }
     println!("Deck exported successfully to {}", output_path.display());
     Ok(())
 }
@@ -388,10 +418,20 @@ fn handle_validate(
 
     let loader = CoreAnkiDeckLoader::new(project_path.clone());
 
-    loader
+    let duplicates = loader
         .build_deck()
         .map_err(|e| format!("Validation failed: {}", e))?;
 
+    for duplicate in &duplicates {
+        println!("Duplicate note {}", duplicate);
+    }
+    if strict && !duplicates.is_empty() {
+        return Err(format!(
+            "Validation failed: {} group(s) of notes share a first field",
+            duplicates.len()
+        ));
+    }
+
     if verbose {
         println!("All validation checks passed!");
     } else {
@@ -404,6 +444,75 @@ fn handle_validate(
     Ok(())
 }
 
+/// Formats a size in bytes with a binary unit.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn print_stats(stats: &DeckStats) {
+    println!(
+        "Deck: {} notes, {} cards, {} media files ({})",
+        stats.notes,
+        stats.cards,
+        stats.media_files,
+        format_size(stats.media_bytes)
+    );
+    for model in &stats.models {
+        println!();
+        println!(
+            "Model {}: {} notes, {} media files ({})",
+            model.name,
+            model.notes,
+            model.media_files,
+            format_size(model.media_bytes)
+        );
+        let width = model
+            .fields
+            .iter()
+            .map(|f| f.name.len())
+            .chain(model.templates.iter().map(|t| t.name.len()))
+            .max()
+            .unwrap_or(0);
+        println!("  Fields:");
+        for field in &model.fields {
+            println!(
+                "    {:<width$}  {:>5.1}% filled, {:.1} characters on average",
+                field.name,
+                field.fill_rate * 100.0,
+                field.average_length
+            );
+        }
+        println!("  Templates:");
+        for template in &model.templates {
+            println!(
+                "    {:<width$}  {} cards, {:.1}% of notes",
+                template.name,
+                template.cards,
+                template.coverage * 100.0
+            );
+        }
+    }
+}
+
+fn handle_stats(project_path: PathBuf) -> Result<(), String> {
+    let stats = CoreAnkiDeckLoader::new(project_path)
+        .stats()
+        .map_err(|e| format!("Failed to compute statistics: {}", e))?;
+    print_stats(&stats);
+    Ok(())
+}
+
 fn handle_clean(project_path: PathBuf, all: bool, dry_run: bool) -> Result<(), String> {
     println!("Cleaning project directory: {}", project_path.display());
 
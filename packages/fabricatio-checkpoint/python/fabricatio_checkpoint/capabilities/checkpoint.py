@@ -2,14 +2,14 @@
 
 from abc import ABC
 from pathlib import Path
-from typing import Optional, Self
+from typing import Any, Dict, List, Optional, Self
 
 from fabricatio_core.capabilities.usages import UseLLM
 from fabricatio_core.utils import ok
 from pydantic import Field, PrivateAttr
 
 from fabricatio_checkpoint.inited_service import get_checkpoint_service
-from fabricatio_checkpoint.rust import CheckPointStore
+from fabricatio_checkpoint.rust import CheckPointStore, CheckpointTag, FileChange, SearchHit
 
 
 class Checkpoint(UseLLM, ABC):
@@ -40,10 +40,30 @@ class Checkpoint(UseLLM, ABC):
         """Save a checkpoint."""
         return self.access_checkpoint_store().save(msg)
 
+    async def asave_checkpoint(self, msg: str = "Changes") -> str:
+        """Save a checkpoint without blocking the event loop."""
+        return await self.access_checkpoint_store().save_async(msg)
+
+    def save_tagged_checkpoint(self, name: str, msg: str = "Changes", metadata: Optional[Dict[str, Any]] = None) -> str:
+        """Save a checkpoint and tag it with a name, such as `before-refactor`."""
+        return self.access_checkpoint_store().save_tagged(name, msg, metadata)
+
+    def list_checkpoint_tags(self) -> List[CheckpointTag]:
+        """List the tagged checkpoints, the oldest first."""
+        return self.access_checkpoint_store().list_tags()
+
+    def reset_to_tag(self, name: str) -> str:
+        """Reset the worktree to a tagged checkpoint."""
+        return self.access_checkpoint_store().restore_tag(name)
+
     def rollback(self, commit_id: str, file_path: Path | str) -> None:
         """Rollback to a checkpoint."""
         self.access_checkpoint_store().rollback(commit_id, file_path)
 
+    def rollback_tree(self, commit_id: str, dir_path: Path | str) -> None:
+        """Rollback a directory and everything below it to a checkpoint."""
+        self.access_checkpoint_store().rollback_tree(commit_id, dir_path)
+
     def reset_to_checkpoint(self, commit_id: str) -> None:
         """Reset the checkpoint."""
         self.access_checkpoint_store().reset(commit_id)
@@ -51,3 +71,15 @@ class Checkpoint(UseLLM, ABC):
     def get_file_diff(self, commit_id: str, file_path: Path | str) -> str:
         """Get the diff for a specific file at a given commit."""
         return self.access_checkpoint_store().get_file_diff(commit_id, file_path)
+
+    def get_changed_files(self, commit_id: str) -> List[FileChange]:
+        """Get the files changed by a checkpoint compared to the previous one."""
+        return self.access_checkpoint_store().get_changed_files(commit_id)
+
+    def search_checkpoints(self, pattern: str, path_glob: Optional[str] = None) -> List[SearchHit]:
+        """Search the lines the checkpoints added or removed, the latest checkpoint first."""
+        return self.access_checkpoint_store().search_history(pattern, path_glob)
+
+    def export_checkpoint(self, commit_id: str, output: Path | str) -> int:
+        """Write the files of a checkpoint to a tar archive, gzipped for `.tar.gz` outputs."""
+        return self.access_checkpoint_store().export_checkpoint(commit_id, output)
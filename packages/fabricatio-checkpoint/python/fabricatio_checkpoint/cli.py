@@ -40,10 +40,26 @@ def save(ctx: Context, message: Annotated[str, Argument(help="The message of the
 
 
 @app.command()
-def diff(ctx: Context) -> None:
-    """Show the difference between the workspace and the last commit."""
-    diff_result = get_checkpoint_service().get_store(ctx.obj["workspace"]).get_changed_files()
-    echo("\n".join(diff_result))
+def diff(
+    ctx: Context,
+    commit_id: Annotated[str | None, Argument(help="The commit id to compare to, the last one by default.")] = None,
+) -> None:
+    """Show the uncommitted changes of the workspace since a commit."""
+    echo(get_checkpoint_service().get_store(ctx.obj["workspace"]).diff_workdir(commit_id), nl=False)
+
+
+@app.command()
+def prune(
+    ctx: Context,
+    keep_last: Annotated[int | None, Option("--keep-last", "-n", help="Number of latest commits to keep.")] = None,
+    older_than: Annotated[float | None, Option("--older-than", "-d", help="Drop commits older than these days.")] = None,
+) -> None:
+    """Drop old commits of the workspace and reclaim their space."""
+    store = get_checkpoint_service().get_store(ctx.obj["workspace"])
+    if keep_last is not None:
+        echo(store.prune(keep_last))
+    if older_than is not None:
+        echo(store.prune_older_than(older_than))
 
 
 @app.command()
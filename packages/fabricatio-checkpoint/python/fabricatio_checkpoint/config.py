@@ -1,7 +1,8 @@
 """Module containing configuration classes for fabricatio-checkpoint."""
 
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 from pathlib import Path
+from typing import Dict, List, Optional
 
 from fabricatio_core import CONFIG
 
@@ -14,6 +15,34 @@ class CheckpointConfig:
     """Directory to store checkpoints. Aka the shadow repositories."""
     cache_size: int = 100
     """Maximum number of checkpoints to keep in memory."""
+    max_file_size: Optional[int] = 50 * 1024 * 1024
+    """Files larger than this many bytes are recorded as placeholders instead of being stored. None stores every file."""
+    skipped_store: Optional[Path] = None
+    """Content-addressed store keeping the content of files over `max_file_size`. None keeps only their placeholders."""
+    ignore: List[str] = field(
+        default_factory=lambda: [
+            ".venv/",
+            "venv/",
+            "node_modules/",
+            "__pycache__/",
+            "*.py[cod]",
+            ".mypy_cache/",
+            ".pytest_cache/",
+            ".ruff_cache/",
+            "target/",
+        ]
+    )
+    """Patterns in the `.gitignore` syntax of files never to checkpoint, on top of the `.checkpointignore` file."""
+    project_ignore: bool = True
+    """Whether the build outputs and caches of the project at the root of a workspace are never checkpointed either."""
+    keep_last: Optional[int] = None
+    """Number of latest checkpoints each store keeps, older ones are pruned automatically. None keeps them all."""
+    max_age_days: Optional[float] = None
+    """Age in days over which checkpoints are pruned automatically. None keeps them whatever their age."""
+    hooks: Dict[str, List[str]] = field(default_factory=dict)
+    """Shell commands to run around store operations, keyed by hook point (`pre_save`, `post_save`, `pre_reset`, `post_reset`)."""
+    hook_failure_fatal: bool = False
+    """Whether a failing hook command aborts the operation instead of only being logged."""
 
 
 checkpoint_config = CONFIG.load("checkpoint", CheckpointConfig)
@@ -9,7 +9,20 @@ from fabricatio_checkpoint.rust import CheckpointService
 @once
 def get_checkpoint_service() -> CheckpointService:
     """Get the singleton instance of the ShadowRepoManager."""
-    return CheckpointService(stores_root=checkpoint_config.checkpoint_dir, cache_size=checkpoint_config.cache_size)
+    service = CheckpointService(
+        stores_root=checkpoint_config.checkpoint_dir,
+        cache_size=checkpoint_config.cache_size,
+        max_file_size=checkpoint_config.max_file_size,
+        ignore=checkpoint_config.ignore,
+        keep_last=checkpoint_config.keep_last,
+        max_age_days=checkpoint_config.max_age_days,
+        project_ignore=checkpoint_config.project_ignore,
+        skipped_store=checkpoint_config.skipped_store,
+    )
+    for point, commands in checkpoint_config.hooks.items():
+        for command in commands:
+            service.add_hook_command(point, command, fatal=checkpoint_config.hook_failure_fatal)
+    return service
 
 
 __all__ = ["get_checkpoint_service"]
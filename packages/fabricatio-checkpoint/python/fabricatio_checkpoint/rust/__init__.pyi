@@ -8,6 +8,11 @@ import typing
 __all__ = [
     "CheckPointStore",
     "CheckpointService",
+    "CheckpointTag",
+    "FileChange",
+    "PruneReport",
+    "SearchHit",
+    "SkippedFile",
     "prune_stores",
 ]
 
@@ -33,8 +38,27 @@ class CheckPointStore:
         Returns:
             The commit ID (OID) as a string.
 
+        Raises:
+            Exception: If a fatal `pre_save` or `post_save` hook fails. A failing `pre_save`
+                hook aborts the save.
+
         Note:
             If there are no changes to commit, this method returns the ID of the last commit (the HEAD).
+            With a retention policy on the service, old checkpoints may be pruned afterwards.
+            The GIL is released while the worktree is staged and committed.
+        """
+    def save_async(self, commit_msg: typing.Optional[builtins.str] = None) -> typing.Awaitable[builtins.str]:
+        r"""Saves the current state of the worktree as a new commit, without blocking the event loop.
+
+        Same as `save`, but the work runs on a background thread and the returned awaitable
+        resolves once the checkpoint is committed, so an agent loop keeps running while a
+        large workspace is staged.
+
+        Args:
+            commit_msg: Optional commit message; defaults to empty string if not provided.
+
+        Returns:
+            An awaitable resolving to the commit ID (OID) as a string.
         """
     def head(self) -> builtins.str:
         r"""Retrieves the ID of the current HEAD commit.
@@ -62,6 +86,80 @@ class CheckPointStore:
 
         Args:
             commit_id: The commit ID (OID as string) to reset to.
+
+        Raises:
+            Exception: If a fatal `pre_reset` or `post_reset` hook fails. A failing `pre_reset`
+                hook aborts the reset.
+        """
+    def save_tagged(
+        self,
+        name: builtins.str,
+        message: typing.Optional[builtins.str] = None,
+        metadata: typing.Optional[builtins.dict[builtins.str, typing.Any]] = None,
+        force: builtins.bool = False,
+    ) -> builtins.str:
+        r"""Saves a checkpoint and tags it with a name, to mark a milestone such as
+        `before-refactor` instead of keeping track of its commit ID.
+
+        If nothing changed since the last checkpoint, the HEAD commit is tagged.
+
+        Args:
+            name: The name of the tag, a valid git reference name.
+            message: Optional message of the checkpoint and of the tag.
+            metadata: Optional JSON-serializable data kept along with the tag.
+            force: Moves the tag if it already exists instead of failing.
+
+        Returns:
+            The commit ID (OID) of the tagged checkpoint as a string.
+
+        Raises:
+            ValueError: If the name is invalid, or already taken and `force` is not set.
+        """
+    def list_tags(self) -> builtins.list[CheckpointTag]:
+        r"""Lists the tagged checkpoints.
+
+        Returns:
+            The tags with their message and metadata, the oldest checkpoint first.
+        """
+    def restore_tag(self, name: builtins.str) -> builtins.str:
+        r"""Resets the worktree to a tagged checkpoint, as `reset` does with its commit ID.
+
+        Args:
+            name: The name of the tag.
+
+        Returns:
+            The commit ID (OID) of the tagged checkpoint as a string.
+
+        Raises:
+            KeyError: If no checkpoint has this tag.
+        """
+    def prune(self, keep_last: builtins.int) -> PruneReport:
+        r"""Drops all checkpoints but the latest ones and reclaims the space they used.
+
+        The history is rebuilt with the kept checkpoints, which therefore get new IDs, while
+        their content, messages, notes and tags are preserved. Tagged checkpoints are always
+        kept. Objects no checkpoint refers to anymore, including those of checkpoints a reset
+        left behind, are deleted.
+
+        Args:
+            keep_last: The number of latest checkpoints to keep; the latest one is always kept.
+
+        Returns:
+            The numbers of kept and dropped checkpoints and of reclaimed bytes.
+        """
+    def prune_older_than(self, days: builtins.float) -> PruneReport:
+        r"""Drops the checkpoints older than a number of days and reclaims the space they used.
+
+        Works as `prune`, the latest and the tagged checkpoints are kept whatever their age.
+
+        Args:
+            days: The age in days over which checkpoints are dropped, fractions allowed.
+
+        Returns:
+            The numbers of kept and dropped checkpoints and of reclaimed bytes.
+
+        Raises:
+            ValueError: If `days` is negative.
         """
     def rollback(self, commit_id: builtins.str, file_path: builtins.str | os.PathLike | pathlib.Path) -> None:
         r"""Restores a specific file from a commit.
@@ -72,6 +170,26 @@ class CheckPointStore:
         Args:
             commit_id: The commit ID (OID as string) to restore from.
             file_path: The relative path to the file within the worktree.
+
+        Raises:
+            PermissionError: If the file resolves outside the worktree, such as through a symlink.
+        """
+    def rollback_tree(self, commit_id: builtins.str, dir_path: builtins.str | os.PathLike | pathlib.Path) -> None:
+        r"""Restores a directory and everything below it from a commit, leaving the rest of the
+        worktree untouched.
+
+        Files of the directory are brought back to their content at the commit, and files the
+        latest checkpoint stored there that the commit lacks are deleted. Files never saved in
+        a checkpoint, such as ignored or oversized ones, are left alone.
+
+        Args:
+            commit_id: The commit ID (OID as string) to restore from.
+            dir_path: The directory, relative to the worktree or absolute.
+
+        Raises:
+            ValueError: If the directory is the worktree itself, use `reset` instead.
+            PermissionError: If a file to restore or delete resolves outside the worktree, such as
+                through a symlink.
         """
     def get_file_diff(
         self, commit_id: builtins.str, file_path: builtins.str | os.PathLike | pathlib.Path
@@ -89,6 +207,174 @@ class CheckPointStore:
         Returns:
             A string containing the unified diff in patch format.
         """
+    def diff(
+        self,
+        commit_a: builtins.str,
+        commit_b: builtins.str,
+        path: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+    ) -> builtins.str:
+        r"""Retrieves the patch going from one commit to another.
+
+        Unlike `get_file_diff`, the commits need not be consecutive, the changes of every commit
+        in between are aggregated.
+
+        Args:
+            commit_a: The commit ID (OID as string) of the older state.
+            commit_b: The commit ID (OID as string) of the newer state.
+            path: Optional file or directory, relative to the worktree or absolute, to limit
+                the patch to.
+
+        Returns:
+            A string containing the unified diff of all changed files in patch format.
+        """
+    def diff_by_file(
+        self,
+        commit_a: builtins.str,
+        commit_b: builtins.str,
+        path: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+    ) -> builtins.dict[builtins.str, builtins.str]:
+        r"""Retrieves the patch of each file changed from one commit to another.
+
+        Args:
+            commit_a: The commit ID (OID as string) of the older state.
+            commit_b: The commit ID (OID as string) of the newer state.
+            path: Optional file or directory, relative to the worktree or absolute, to limit
+                the patches to.
+
+        Returns:
+            A dictionary mapping the path of each changed file, relative to the worktree, to
+            its unified diff in patch format.
+        """
+    def diff_workdir(
+        self,
+        commit_id: typing.Optional[builtins.str] = None,
+        path: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+    ) -> builtins.str:
+        r"""Retrieves the patch of the changes made in the worktree since a commit, those not
+        saved yet when the commit is HEAD.
+
+        New files are included in full. Files the ignore rules or the size limit keep out of
+        checkpoints are left out.
+
+        Args:
+            commit_id: The commit ID (OID as string) to compare to; defaults to HEAD.
+            path: Optional file or directory, relative to the worktree or absolute, to limit
+                the patch to.
+
+        Returns:
+            A string containing the unified diff in patch format, empty if the worktree
+            matches the commit.
+        """
+    def get_changed_files(self, commit_id: builtins.str) -> builtins.list[FileChange]:
+        r"""Lists the files a commit changed compared to its parent.
+
+        The first checkpoint is compared to an empty tree, so all of its files are added.
+
+        Args:
+            commit_id: The commit ID (OID as string) to inspect.
+
+        Returns:
+            The changed files with the kind of their change, sorted by path.
+        """
+    def changes_between(self, commit_a: builtins.str, commit_b: builtins.str) -> builtins.list[FileChange]:
+        r"""Lists the files changed from one commit to another, the changes of every commit in
+        between aggregated.
+
+        A file added then deleted in between does not appear, and one deleted then restored
+        only does if its content differs.
+
+        Args:
+            commit_a: The commit ID (OID as string) of the older state.
+            commit_b: The commit ID (OID as string) of the newer state.
+
+        Returns:
+            The changed files with the kind of their change going from `commit_a` to
+            `commit_b`, sorted by path.
+        """
+    def search_history(
+        self, pattern: builtins.str, path_glob: typing.Optional[builtins.str] = None
+    ) -> builtins.list[SearchHit]:
+        r"""Searches the lines the checkpoints added or removed, like `git log -G`.
+
+        Useful to find when a function appeared or disappeared without exporting the
+        repository. The GIL is released during the search.
+
+        Args:
+            pattern: The regular expression the lines are matched against.
+            path_glob: Optional glob the searched files must match, relative to the
+                workspace, such as `src/**/*.py`.
+
+        Returns:
+            The matching lines, the latest checkpoint first.
+
+        Raises:
+            ValueError: If the pattern is not a valid regular expression.
+        """
+    def export_checkpoint(
+        self, commit_id: builtins.str, output: builtins.str | os.PathLike | pathlib.Path
+    ) -> builtins.int:
+        r"""Writes the files of a checkpoint to a tar archive, to hand off or keep a state of the
+        workspace without the shadow repository.
+
+        The archive is gzipped if the output path ends with `.gz` or `.tgz`. Files left out of
+        the checkpoint for exceeding the size limit are not in it. The GIL is released while
+        the archive is written.
+
+        Args:
+            commit_id: The commit ID (OID as string) of the checkpoint to export.
+            output: The path of the archive to write, such as `state.tar.gz`.
+
+        Returns:
+            The number of files written to the archive.
+        """
+    def skipped_files(self, commit_id: typing.Optional[builtins.str] = None) -> builtins.list[SkippedFile]:
+        r"""Lists the files left out of a checkpoint for exceeding the size limit.
+
+        Such files are not restored by `reset` or `rollback`; their size and hash tell whether
+        the copy in the workspace still matches the one at the time of the checkpoint, and
+        `restore_skipped` brings them back if the service keeps their content.
+
+        Args:
+            commit_id: The commit ID (OID as string) to inspect; defaults to HEAD.
+
+        Returns:
+            The placeholders of the skipped files, sorted by path.
+        """
+    def restore_skipped(self, commit_id: typing.Optional[builtins.str] = None) -> builtins.list[builtins.str]:
+        r"""Restores the files left out of a checkpoint from the content store of the service.
+
+        Files whose copy in the workspace already matches the placeholder are left alone, as
+        are those the store lacks the content of, such as files skipped before the store was
+        set up or listed by a bundle imported from another machine. The GIL is released while
+        the files are compared and written.
+
+        Args:
+            commit_id: The commit ID (OID as string) whose skipped files to restore; defaults
+                to HEAD.
+
+        Returns:
+            The paths of the restored files, relative to the worktree.
+
+        Raises:
+            ValueError: If the service was created without a `skipped_store`.
+            PermissionError: If a path resolves outside the worktree, such as through a symlink.
+        """
+    def is_ignored(self, file_path: builtins.str | os.PathLike | pathlib.Path) -> builtins.bool:
+        r"""Checks whether a file is kept out of checkpoints by the ignore rules.
+
+        The rules are the patterns given to the service, those of the `.checkpointignore` file
+        at the root of the worktree and those of the project it holds, in the `.gitignore`
+        syntax.
+
+        Args:
+            file_path: The path to the file, relative to the worktree or absolute.
+
+        Returns:
+            True if the file or one of its parent directories matches an ignore pattern.
+
+        Raises:
+            PermissionError: If the path is not inside the worktree.
+        """
     def get_status(self) -> builtins.list[builtins.str]:
         r"""Retrieves the status of the worktree.
 
@@ -119,7 +405,15 @@ class CheckpointService:
             A CheckPointStore instance for the specified worktree.
         """
     def __new__(
-        cls, stores_root: builtins.str | os.PathLike | pathlib.Path, cache_size: builtins.int = 10
+        cls,
+        stores_root: builtins.str | os.PathLike | pathlib.Path,
+        cache_size: builtins.int = 10,
+        max_file_size: typing.Optional[builtins.int] = None,
+        ignore: typing.Sequence[builtins.str] = [],
+        keep_last: typing.Optional[builtins.int] = None,
+        max_age_days: typing.Optional[builtins.float] = None,
+        project_ignore: builtins.bool = True,
+        skipped_store: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
     ) -> CheckpointService:
         r"""Creates a new CheckpointService instance.
 
@@ -129,9 +423,31 @@ class CheckpointService:
         Args:
             stores_root: The root directory where shadow repositories will be stored.
             cache_size: Maximum number of repositories to keep in the in-memory cache.
+            max_file_size: Files larger than this many bytes are left out of checkpoints and
+                only recorded by path, size and hash, see `CheckPointStore.skipped_files`.
+                None to store every file.
+            ignore: Patterns in the `.gitignore` syntax of files never to store, such as build
+                artifacts or virtualenvs. They apply to every workspace, on top of the patterns
+                of the `.checkpointignore` file at its root.
+            keep_last: If set, stores prune their history down to this many latest checkpoints
+                once it grows some checkpoints past it, see `CheckPointStore.prune`.
+            max_age_days: If set, stores prune the checkpoints older than this many days once
+                the oldest is a day past it, see `CheckPointStore.prune_older_than`.
+            project_ignore: Whether the build outputs, caches and installed dependencies of the
+                project at the root of a workspace are left out as well, such as `target/` for
+                a `Cargo.toml` or `node_modules/` for a `package.json`, see `detect_project`.
+            skipped_store: The directory of a content-addressed store to keep the content of the
+                files left out for their size in, so `CheckPointStore.restore_skipped` can bring
+                them back, such as the `cas` directory of the roaming directory shared with the
+                other packages. The content stays referenced for as long as a checkpoint lists
+                it. None to keep only their placeholders.
 
         Returns:
             A new CheckpointService instance.
+
+        Raises:
+            ValueError: If an ignore pattern is invalid or `max_age_days` is negative.
+            OSError: If the `skipped_store` directory cannot be created.
         """
     def workspaces(self) -> builtins.list[pathlib.Path]:
         r"""Returns a list of all managed workspaces.
@@ -139,6 +455,105 @@ class CheckpointService:
         Returns:
             A list of paths to managed workspace directories.
         """
+    def disk_usage(self) -> builtins.dict[pathlib.Path, builtins.int]:
+        r"""Returns the disk space taken by the store of each managed workspace.
+
+        Returns:
+            A dict mapping workspace paths to the size of their store in bytes.
+        """
+    def add_hook(
+        self,
+        point: typing.Literal["pre_save", "post_save", "pre_reset", "post_reset"],
+        callback: typing.Callable[[pathlib.Path, typing.Optional[builtins.str]], typing.Any],
+        fatal: builtins.bool = False,
+    ) -> None:
+        r"""Registers a Python callable to run before or after stores save or reset.
+
+        The callable receives the workspace path and the commit ID involved: None for
+        `pre_save`, the new commit for `post_save`, and the target commit for `pre_reset`
+        and `post_reset`. Hooks apply to every store handed out by this service.
+
+        Args:
+            point: One of `pre_save`, `post_save`, `pre_reset` or `post_reset`.
+            callback: The callable to run.
+            fatal: If True, an exception raised by the callable aborts the operation (for
+                `pre_*` hooks) or is raised to the caller (for `post_*` hooks). Otherwise the
+                failure is only logged.
+
+        Raises:
+            ValueError: If the hook point is unknown.
+        """
+    def add_hook_command(
+        self,
+        point: typing.Literal["pre_save", "post_save", "pre_reset", "post_reset"],
+        command: builtins.str,
+        fatal: builtins.bool = False,
+    ) -> None:
+        r"""Registers a shell command to run before or after stores save or reset.
+
+        The command runs in the workspace directory with the `CHECKPOINT_HOOK`,
+        `CHECKPOINT_WORKSPACE` and `CHECKPOINT_COMMIT` environment variables set. A non-zero
+        exit status counts as a failure.
+
+        Args:
+            point: One of `pre_save`, `post_save`, `pre_reset` or `post_reset`.
+            command: The shell command to run.
+            fatal: If True, a failure aborts the operation (for `pre_*` hooks) or is raised to
+                the caller (for `post_*` hooks). Otherwise the failure is only logged.
+
+        Raises:
+            ValueError: If the hook point is unknown.
+        """
+    def clear_hooks(
+        self, point: typing.Optional[typing.Literal["pre_save", "post_save", "pre_reset", "post_reset"]] = None
+    ) -> None:
+        r"""Removes registered hooks.
+
+        Args:
+            point: The hook point to clear, or None to remove all hooks.
+
+        Raises:
+            ValueError: If the hook point is unknown.
+        """
+    def export_bundle(
+        self, workspace: builtins.str | os.PathLike | pathlib.Path, path: builtins.str | os.PathLike | pathlib.Path
+    ) -> builtins.str:
+        r"""Writes the whole checkpoint history of a workspace to a bundle file.
+
+        The file uses the format of `git bundle` and holds every checkpoint along with the
+        records of the skipped files, so the history can be moved to another machine with
+        `import_bundle`, attached to a bug report, or inspected with `git clone`.
+
+        Args:
+            workspace: The workspace whose checkpoints are exported.
+            path: The path of the bundle file to write, replaced if it exists.
+
+        Returns:
+            The ID of the latest checkpoint in the bundle.
+
+        Raises:
+            FileNotFoundError: If the workspace has no checkpoint store.
+        """
+    def import_bundle(
+        self, path: builtins.str | os.PathLike | pathlib.Path, new_workspace: builtins.str | os.PathLike | pathlib.Path
+    ) -> builtins.str:
+        r"""Loads the checkpoint history of a bundle written by `export_bundle` into the store of
+        a workspace.
+
+        The history replaces the one of the store, if any, while the files of the workspace are
+        left untouched: reset the store to the returned checkpoint to restore them.
+
+        Args:
+            path: The path of the bundle file.
+            new_workspace: The workspace the history is attached to, usually where the
+                exported workspace was copied or cloned.
+
+        Returns:
+            The ID of the latest checkpoint in the bundle.
+
+        Raises:
+            ValueError: If the file is not a checkpoint bundle.
+        """
     def prune_invalid(self) -> None:
         r"""Prunes stores which manage an invalid workspace.
 
@@ -146,6 +561,88 @@ class CheckpointService:
             PyResult<()> indicating success.
         """
 
+@typing.final
+class CheckpointTag:
+    r"""A checkpoint marked with a name."""
+    @property
+    def name(self) -> builtins.str:
+        r"""The name of the tag."""
+    @property
+    def commit_id(self) -> builtins.str:
+        r"""The commit ID (OID as string) of the tagged checkpoint."""
+    @property
+    def message(self) -> builtins.str:
+        r"""The message given when tagging."""
+    @property
+    def timestamp(self) -> builtins.int:
+        r"""The time of the checkpoint, in seconds since the Unix epoch."""
+    @property
+    def metadata(self) -> typing.Any:
+        r"""The metadata given when tagging."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class FileChange:
+    r"""A file added, modified or deleted between two checkpoints."""
+    @property
+    def path(self) -> builtins.str:
+        r"""The path of the file, relative to the workspace."""
+    @property
+    def kind(self) -> builtins.str:
+        r"""How the file changed: `added`, `modified` or `deleted`."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class PruneReport:
+    r"""The outcome of pruning the checkpoints of a store."""
+    @property
+    def kept(self) -> builtins.int:
+        r"""Number of checkpoints kept."""
+    @property
+    def dropped(self) -> builtins.int:
+        r"""Number of checkpoints dropped."""
+    @property
+    def reclaimed_bytes(self) -> builtins.int:
+        r"""Number of bytes the repository shrank by."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class SearchHit:
+    r"""A line matching a search, added or removed by a checkpoint."""
+    @property
+    def commit_id(self) -> builtins.str:
+        r"""The commit ID (OID as string) of the checkpoint."""
+    @property
+    def timestamp(self) -> builtins.int:
+        r"""The time of the checkpoint, in seconds since the Unix epoch."""
+    @property
+    def path(self) -> builtins.str:
+        r"""The path of the file, relative to the workspace."""
+    @property
+    def line_number(self) -> builtins.int:
+        r"""The number of the line, in the file after the checkpoint if added, before if removed."""
+    @property
+    def line(self) -> builtins.str:
+        r"""The content of the line, without its line ending."""
+    @property
+    def change(self) -> builtins.str:
+        r"""Whether the checkpoint `added` or `removed` the line."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class SkippedFile:
+    r"""A file left out of a checkpoint because it exceeded the size limit."""
+    @property
+    def path(self) -> builtins.str:
+        r"""The path of the file, relative to the workspace."""
+    @property
+    def size(self) -> builtins.int:
+        r"""The size of the file in bytes."""
+    @property
+    def hash(self) -> builtins.str:
+        r"""The BLAKE3 hash of the file content, hex encoded."""
+    def __repr__(self) -> builtins.str: ...
+
 def prune_stores(stores_root: builtins.str | os.PathLike | pathlib.Path) -> None:
     r"""Removes all store repositories under the given root path whose working directories no longer exist.
 
@@ -1,10 +1,14 @@
 """Tests for the checkpoint."""
 
+import tarfile
+import time
 from pathlib import Path
 
 import pytest
 from fabricatio_checkpoint.capabilities.checkpoint import Checkpoint
 from fabricatio_checkpoint.config import checkpoint_config
+from fabricatio_checkpoint.rust import CheckpointService
+from fabricatio_core.rust import ContentStore
 from fabricatio_mock.models.mock_role import LLMTestRole
 
 
@@ -97,7 +101,7 @@ def test_rollback_with_external_absolute_path(role: CheckpointRole, tmp_worktree
     external_file = tmp_path / "test1.txt"
     external_file.write_text(texts * 3)
 
-    with pytest.raises(RuntimeError, match="prefix not found"):
+    with pytest.raises(PermissionError, match="escapes the workspace root"):
         role.rollback(id_1, external_file.absolute())
     role.rollback(id_1, file1.absolute())
 
@@ -134,3 +138,427 @@ def test_reset(role: CheckpointRole, tmp_worktree_dir: Path) -> None:
     # Verify all changes are undone
     assert file1.read_text() == content_v1_file1
     assert file2.read_text() == content_v1_file2
+
+
+@pytest.fixture
+def service(tmp_path: Path) -> CheckpointService:
+    """Create a standalone checkpoint service."""
+    return CheckpointService(stores_root=tmp_path / "hook_stores")
+
+
+async def test_save_async(role: CheckpointRole, tmp_worktree_dir: Path) -> None:
+    """Test that saving in the background commits like a blocking save."""
+    tmp_worktree_dir.joinpath("test.txt").write_text("hello world")
+    cid = await role.asave_checkpoint("async")
+    assert cid == role.access_checkpoint_store().head()
+    assert cid == role.save_checkpoint("unchanged")
+
+    tmp_worktree_dir.joinpath("test.txt").write_text("changed")
+    assert await role.asave_checkpoint("changed") != cid
+
+
+def test_hooks_called(service: CheckpointService, tmp_worktree_dir: Path) -> None:
+    """Test that hooks receive the workspace and the commit ids involved."""
+    calls = []
+    for point in ("pre_save", "post_save", "pre_reset", "post_reset"):
+        service.add_hook(point, lambda workspace, commit_id, p=point: calls.append((p, workspace, commit_id)))
+
+    store = service.get_store(tmp_worktree_dir)
+    tmp_worktree_dir.joinpath("test.txt").write_text("hello world")
+    cid = store.save("test")
+    store.reset(cid)
+
+    assert calls == [
+        ("pre_save", store.workspace, None),
+        ("post_save", store.workspace, cid),
+        ("pre_reset", store.workspace, cid),
+        ("post_reset", store.workspace, cid),
+    ]
+
+
+def test_hook_failures(service: CheckpointService, tmp_worktree_dir: Path) -> None:
+    """Test that only fatal hook failures abort the operation."""
+
+    def failing(_workspace: Path, _commit_id: str | None) -> None:
+        raise ValueError("hook failed")
+
+    store = service.get_store(tmp_worktree_dir)
+    tmp_worktree_dir.joinpath("test.txt").write_text("hello world")
+
+    service.add_hook("pre_save", failing)
+    head = store.save("non fatal")
+    assert store.commits() == [head]
+
+    service.clear_hooks()
+    service.add_hook("pre_save", failing, fatal=True)
+    tmp_worktree_dir.joinpath("test.txt").write_text("changed")
+    with pytest.raises(ValueError, match="hook failed"):
+        store.save("fatal")
+    assert store.head() == head
+
+    with pytest.raises(ValueError, match="Unknown hook point"):
+        service.add_hook("before_save", failing)
+
+
+def test_skip_large_files(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that files over the size limit are recorded as placeholders instead of blobs."""
+    service = CheckpointService(stores_root=tmp_path / "limited_stores", max_file_size=16)
+    store = service.get_store(tmp_worktree_dir)
+    small = tmp_worktree_dir / "small.txt"
+    large = tmp_worktree_dir / "data" / "weights.bin"
+    large.parent.mkdir()
+    small.write_text("hello")
+    large.write_bytes(b"x" * 64)
+
+    cid = store.save("with large file")
+    assert [(f.path, f.size) for f in store.skipped_files(cid)] == [("data/weights.bin", 64)]
+    assert store.skipped_files() == store.skipped_files(cid)
+    assert len(store.skipped_files(cid)[0].hash) == 64
+
+    small.write_text("hello world, now over the limit")
+    large.write_bytes(b"y" * 8)
+    cid2 = store.save("swapped sizes")
+    assert [f.path for f in store.skipped_files(cid2)] == ["small.txt"]
+
+    small.unlink()
+    store.reset(cid)
+    assert small.read_text() == "hello"
+    assert large.read_bytes() == b"y" * 8
+
+
+def test_restore_skipped_files(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that skipped files kept in a content store are restored, and released once pruned."""
+    cas = tmp_path / "cas"
+    service = CheckpointService(stores_root=tmp_path / "kept_stores", max_file_size=16, skipped_store=cas)
+    store = service.get_store(tmp_worktree_dir)
+    large = tmp_worktree_dir / "weights.bin"
+    large.write_bytes(b"x" * 64)
+    cid = store.save("first weights")
+    large.write_bytes(b"y" * 64)
+    store.save("second weights")
+
+    large.unlink()
+    assert store.restore_skipped(cid) == ["weights.bin"]
+    assert large.read_bytes() == b"x" * 64
+    assert store.restore_skipped(cid) == []
+    assert store.restore_skipped() == ["weights.bin"]
+    assert large.read_bytes() == b"y" * 64
+
+    # Both versions are referenced until pruning drops the first checkpoint.
+    assert ContentStore(cas).gc(grace=0).removed == 0
+    store.prune(keep_last=1)
+    assert ContentStore(cas).gc(grace=0).removed == 1
+    assert store.restore_skipped() == []
+
+    plain = CheckpointService(stores_root=tmp_path / "plain_stores", max_file_size=16).get_store(tmp_worktree_dir)
+    with pytest.raises(ValueError, match="skipped_store"):
+        plain.restore_skipped()
+
+
+def test_changed_files(service: CheckpointService, tmp_worktree_dir: Path) -> None:
+    """Test that changed files are listed per commit and aggregated between commits."""
+    store = service.get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "kept.txt").write_text("v1")
+    (tmp_worktree_dir / "removed.txt").write_text("bye")
+    first = store.save("first")
+    assert [(c.path, c.kind) for c in store.get_changed_files(first)] == [
+        ("kept.txt", "added"),
+        ("removed.txt", "added"),
+    ]
+
+    (tmp_worktree_dir / "kept.txt").write_text("v2")
+    (tmp_worktree_dir / "removed.txt").unlink()
+    (tmp_worktree_dir / "sub").mkdir()
+    (tmp_worktree_dir / "sub" / "new.txt").write_text("hi")
+    second = store.save("second")
+    assert [(c.path, c.kind) for c in store.get_changed_files(second)] == [
+        ("kept.txt", "modified"),
+        ("removed.txt", "deleted"),
+        ("sub/new.txt", "added"),
+    ]
+
+    (tmp_worktree_dir / "sub" / "new.txt").unlink()
+    third = store.save("third")
+    assert [(c.path, c.kind) for c in store.changes_between(first, third)] == [
+        ("kept.txt", "modified"),
+        ("removed.txt", "deleted"),
+    ]
+    assert store.changes_between(third, third) == []
+
+
+def test_ignore_rules(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that ignored files never enter checkpoints and are dropped once ignored."""
+    service = CheckpointService(stores_root=tmp_path / "ignoring_stores", ignore=[".venv/"])
+    store = service.get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / ".venv" / "lib").mkdir(parents=True)
+    (tmp_worktree_dir / ".venv" / "lib" / "site.py").write_text("import os")
+    (tmp_worktree_dir / "main.py").write_text("print('hi')")
+    (tmp_worktree_dir / "run.log").write_text("started")
+
+    first = store.save("first")
+    assert [c.path for c in store.get_changed_files(first)] == ["main.py", "run.log"]
+    assert store.is_ignored(".venv/lib/site.py")
+    assert not store.is_ignored("run.log")
+
+    (tmp_worktree_dir / ".checkpointignore").write_text("*.log\n")
+    second = store.save("second")
+    assert [(c.path, c.kind) for c in store.get_changed_files(second)] == [
+        (".checkpointignore", "added"),
+        ("run.log", "deleted"),
+    ]
+    assert store.is_ignored(tmp_worktree_dir / "run.log")
+
+    with pytest.raises(ValueError, match="Invalid ignore pattern"):
+        CheckpointService(stores_root=tmp_path / "ignoring_stores", ignore=["a/**b["])
+
+
+def test_project_ignore_rules(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that the build outputs of the project are ignored unless re-included or turned off."""
+    (tmp_worktree_dir / "Cargo.toml").write_text('[package]\nname = "demo"\n')
+    (tmp_worktree_dir / "target" / "debug").mkdir(parents=True)
+    (tmp_worktree_dir / "target" / "debug" / "demo").write_text("binary")
+    (tmp_worktree_dir / "target" / "notes.txt").write_text("kept")
+
+    store = CheckpointService(stores_root=tmp_path / "project_stores").get_store(tmp_worktree_dir)
+    assert store.is_ignored("target/debug/demo")
+    (tmp_worktree_dir / ".checkpointignore").write_text("!/target/\n/target/debug/\n")
+    assert not store.is_ignored("target/notes.txt")
+    assert store.is_ignored("target/debug/demo")
+
+    plain = CheckpointService(stores_root=tmp_path / "plain_stores", project_ignore=False).get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / ".checkpointignore").unlink()
+    assert not plain.is_ignored("target/debug/demo")
+
+
+def test_bundle_roundtrip(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that a bundle carries the whole history, skipped files included, to another workspace."""
+    service = CheckpointService(stores_root=tmp_path / "source_stores", max_file_size=16)
+    store = service.get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "notes.txt").write_text("v1")
+    store.save("first")
+    (tmp_worktree_dir / "notes.txt").write_text("v2")
+    (tmp_worktree_dir / "weights.bin").write_bytes(b"x" * 64)
+    head = store.save("second")
+
+    bundle = tmp_path / "history.bundle"
+    assert service.export_bundle(tmp_worktree_dir, bundle) == head
+    assert bundle.read_bytes().startswith(b"# v2 git bundle\n")
+
+    target = tmp_path / "moved"
+    target.mkdir()
+    other = CheckpointService(stores_root=tmp_path / "target_stores")
+    assert other.import_bundle(bundle, target) == head
+    moved = other.get_store(target)
+    assert moved.commits() == store.commits()
+    assert [f.path for f in moved.skipped_files()] == ["weights.bin"]
+    moved.reset(head)
+    assert (target / "notes.txt").read_text() == "v2"
+
+    with pytest.raises(FileNotFoundError):
+        service.export_bundle(tmp_path / "untracked", bundle)
+    (tmp_path / "fake.bundle").write_text("not a bundle\n\n")
+    with pytest.raises(ValueError, match="not a checkpoint bundle"):
+        other.import_bundle(tmp_path / "fake.bundle", target)
+
+
+def test_tags(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test tagging checkpoints with metadata and restoring them by name."""
+    service = CheckpointService(stores_root=tmp_path / "tagging_stores")
+    store = service.get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "main.py").write_text("v1")
+    first = store.save_tagged("before-refactor", "v1", {"task": "split", "step": 1})
+    (tmp_worktree_dir / "main.py").write_text("v2")
+    second = store.save_tagged("after-refactor")
+
+    tags = {t.name: t for t in store.list_tags()}
+    assert {name: t.commit_id for name, t in tags.items()} == {"before-refactor": first, "after-refactor": second}
+    assert tags["before-refactor"].message == "v1"
+    assert tags["before-refactor"].metadata == {"task": "split", "step": 1}
+    assert tags["after-refactor"].metadata == {}
+
+    assert store.restore_tag("before-refactor") == first
+    assert (tmp_worktree_dir / "main.py").read_text() == "v1"
+
+    with pytest.raises(ValueError, match="already exists"):
+        store.save_tagged("before-refactor")
+    assert store.save_tagged("before-refactor", force=True) == first
+    with pytest.raises(ValueError, match="Invalid tag name"):
+        store.save_tagged("bad..name")
+    with pytest.raises(KeyError):
+        store.restore_tag("missing")
+
+    bundle = tmp_path / "tagged.bundle"
+    service.export_bundle(tmp_worktree_dir, bundle)
+    target = tmp_path / "moved_tagged"
+    target.mkdir()
+    other = CheckpointService(stores_root=tmp_path / "tagged_target_stores")
+    other.import_bundle(bundle, target)
+    assert {t.name for t in other.get_store(target).list_tags()} == {"before-refactor", "after-refactor"}
+
+
+def test_diff_between_commits_and_workdir(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test diffing arbitrary checkpoints and the uncommitted changes of the worktree."""
+    store = CheckpointService(stores_root=tmp_path / "diff_stores", ignore=["*.log"]).get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "src").mkdir()
+    (tmp_worktree_dir / "src" / "a.py").write_text("one\n")
+    (tmp_worktree_dir / "b.txt").write_text("alpha\n")
+    first = store.save("first")
+    (tmp_worktree_dir / "src" / "a.py").write_text("two\n")
+    store.save("second")
+    (tmp_worktree_dir / "b.txt").write_text("beta\n")
+    third = store.save("third")
+
+    patch = store.diff(first, third)
+    assert "-one\n+two\n" in patch
+    assert "-alpha\n+beta\n" in patch
+    assert store.diff(first, third, "src") == store.diff_by_file(first, third)["src/a.py"]
+    assert set(store.diff_by_file(first, third)) == {"b.txt", "src/a.py"}
+
+    assert store.diff_workdir() == ""
+    (tmp_worktree_dir / "b.txt").write_text("gamma\n")
+    (tmp_worktree_dir / "new.py").write_text("print()\n")
+    (tmp_worktree_dir / "debug.log").write_text("noise\n")
+    drift = store.diff_workdir()
+    assert "-beta\n+gamma\n" in drift
+    assert "+print()\n" in drift
+    assert "debug.log" not in drift
+    assert "+gamma\n" in store.diff_workdir(first, "b.txt")
+    assert "new.py" not in store.diff_workdir(path="b.txt")
+
+
+def test_search_history(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test finding the checkpoints that added or removed matching lines."""
+    store = CheckpointService(stores_root=tmp_path / "search_stores").get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "src").mkdir()
+    (tmp_worktree_dir / "src" / "a.py").write_text("def helper():\n    pass\n")
+    (tmp_worktree_dir / "notes.txt").write_text("def helper is documented here\n")
+    added = store.save("add helper")
+    (tmp_worktree_dir / "src" / "a.py").write_text("def other():\n    pass\n")
+    removed = store.save("drop helper")
+
+    hits = store.search_history(r"def helper\(")
+    assert [(hit.commit_id, hit.change, hit.path, hit.line_number) for hit in hits] == [
+        (removed, "removed", "src/a.py", 1),
+        (added, "added", "src/a.py", 1),
+    ]
+    assert hits[0].line == "def helper():"
+    assert {hit.path for hit in store.search_history("helper", "*.txt")} == {"notes.txt"}
+    with pytest.raises(ValueError):
+        store.search_history("(")
+
+
+def test_export_checkpoint(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test exporting the files of a checkpoint to a tarball."""
+    store = CheckpointService(stores_root=tmp_path / "export_stores").get_store(tmp_worktree_dir)
+    (tmp_worktree_dir / "src").mkdir()
+    (tmp_worktree_dir / "src" / "a.py").write_text("print('a')\n")
+    (tmp_worktree_dir / "b.txt").write_text("beta\n")
+    commit_id = store.save("state")
+    (tmp_worktree_dir / "b.txt").write_text("changed\n")
+    store.save("later")
+
+    output = tmp_path / "out" / "state.tar.gz"
+    assert store.export_checkpoint(commit_id, output) == 2
+    with tarfile.open(output, "r:gz") as archive:
+        assert sorted(archive.getnames()) == ["b.txt", "src/a.py"]
+        assert archive.extractfile("b.txt").read() == b"beta\n"
+        assert not any(".git" in name for name in archive.getnames())
+
+    plain = tmp_path / "state.tar"
+    assert store.export_checkpoint(commit_id, plain) == 2
+    with tarfile.open(plain, "r:") as archive:
+        assert archive.extractfile("src/a.py").read() == b"print('a')\n"
+
+
+def test_rollback_tree(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test restoring one directory while the rest of the worktree keeps its changes."""
+    store = CheckpointService(stores_root=tmp_path / "tree_stores").get_store(tmp_worktree_dir)
+    feature = tmp_worktree_dir / "feature"
+    (feature / "nested").mkdir(parents=True)
+    (feature / "a.py").write_text("a1")
+    (feature / "nested" / "b.py").write_text("b1")
+    (tmp_worktree_dir / "other.py").write_text("o1")
+    first = store.save("first")
+
+    (feature / "a.py").write_text("a2")
+    (feature / "nested" / "b.py").unlink()
+    (feature / "added.py").write_text("new")
+    (tmp_worktree_dir / "other.py").write_text("o2")
+    store.save("second")
+    (feature / "scratch.txt").write_text("unsaved")
+
+    store.rollback_tree(first, "feature")
+    assert (feature / "a.py").read_text() == "a1"
+    assert (feature / "nested" / "b.py").read_text() == "b1"
+    assert not (feature / "added.py").exists()
+    assert (feature / "scratch.txt").read_text() == "unsaved"
+    assert (tmp_worktree_dir / "other.py").read_text() == "o2"
+
+    with pytest.raises(ValueError, match="use `reset`"):
+        store.rollback_tree(first, tmp_worktree_dir)
+
+
+def test_rollback_through_symlink(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that rolling back refuses to write through a symlinked directory leading out of the worktree."""
+    store = CheckpointService(stores_root=tmp_path / "symlink_stores").get_store(tmp_worktree_dir)
+    nested = tmp_worktree_dir / "feature" / "nested"
+    nested.mkdir(parents=True)
+    (nested / "b.py").write_text("b1")
+    first = store.save("first")
+
+    outside = tmp_path / "outside"
+    outside.mkdir()
+    (outside / "b.py").write_text("secret")
+    (nested / "b.py").unlink()
+    nested.rmdir()
+    nested.symlink_to(outside, target_is_directory=True)
+
+    with pytest.raises(PermissionError, match="escapes the workspace root"):
+        store.rollback(first, nested / "b.py")
+    with pytest.raises(PermissionError, match="escapes the workspace root"):
+        store.rollback_tree(first, "feature")
+    assert (outside / "b.py").read_text() == "secret"
+
+
+def test_prune(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that pruning keeps the latest and tagged checkpoints with their content."""
+    store = CheckpointService(stores_root=tmp_path / "prune_stores").get_store(tmp_worktree_dir)
+    target = tmp_worktree_dir / "data.txt"
+    for i in range(6):
+        target.write_text(f"version {i}\n" * 200)
+        if i == 1:
+            store.save_tagged("milestone", "v1")
+        else:
+            store.save(f"v{i}")
+    assert len(store.commits()) == 6
+
+    report = store.prune(keep_last=2)
+    assert (report.kept, report.dropped) == (3, 3)
+    commits = store.commits()
+    assert len(commits) == 3
+    assert store.list_tags()[0].commit_id in commits
+    assert store.restore_tag("milestone")
+    assert target.read_text() == "version 1\n" * 200
+    store.reset(commits[0])
+    assert target.read_text() == "version 5\n" * 200
+
+    # Commit times have a resolution of one second, so after two seconds every checkpoint is
+    # more than a second old, and only the latest and the tagged ones outlive a one-second age.
+    time.sleep(2.1)
+    report = store.prune_older_than(days=1 / 86400)
+    assert (report.kept, report.dropped) == (2, 1)
+    assert len(store.commits()) == 2
+    assert store.list_tags()[0].commit_id == store.commits()[1]
+    assert [t.name for t in store.list_tags()] == ["milestone"]
+    with pytest.raises(ValueError, match="negative"):
+        store.prune_older_than(days=-1)
+
+
+def test_auto_prune(tmp_path: Path, tmp_worktree_dir: Path) -> None:
+    """Test that stores prune themselves once the history is well past the retention limit."""
+    store = CheckpointService(stores_root=tmp_path / "auto_prune_stores", keep_last=1).get_store(tmp_worktree_dir)
+    for i in range(22):
+        (tmp_worktree_dir / "counter.txt").write_text(str(i))
+        head = store.save(str(i))
+    assert store.commits() == [head]
+    assert (tmp_worktree_dir / "counter.txt").read_text() == "21"
@@ -0,0 +1,108 @@
+//! Checkpoints exported as tar archives, like `git archive`.
+//!
+//! The archive holds the files of the checkpoint as they were in the workspace, without
+//! anything of the shadow repository. Files left out of the checkpoint for their size are
+//! not in it either.
+
+use error_mapping::AsPyErr;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use git2::{Commit, ObjectType, TreeWalkMode, TreeWalkResult};
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tar::{Builder, EntryType, Header};
+
+/// Git file mode of executable files.
+const EXECUTABLE_MODE: i32 = 0o100755;
+
+/// Git file mode of symbolic links.
+const SYMLINK_MODE: i32 = 0o120000;
+
+/// Whether an archive at this path is compressed, as told by its extension.
+fn is_gzipped(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.ends_with(".gz") || name.ends_with(".tgz")
+}
+
+/// Appends the files of a commit to a tar archive.
+///
+/// Returns:
+///     The number of files written.
+fn append_tree<W: Write>(builder: &mut Builder<W>, commit: &Commit) -> PyResult<usize> {
+    let repo = commit.owner();
+    let tree = commit.tree().into_pyresult()?;
+    let mtime = commit.time().seconds().max(0) as u64;
+
+    let mut blobs = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(ObjectType::Blob)
+            && let Some(name) = entry.name()
+        {
+            blobs.push((format!("{dir}{name}"), entry.id(), entry.filemode()));
+        }
+        TreeWalkResult::Ok
+    })
+    .into_pyresult()?;
+
+    for (path, oid, filemode) in &blobs {
+        let blob = repo.find_blob(*oid).into_pyresult()?;
+        let mut header = Header::new_gnu();
+        header.set_mtime(mtime);
+        if *filemode == SYMLINK_MODE {
+            header.set_entry_type(EntryType::Symlink);
+            header.set_size(0);
+            header.set_mode(0o777);
+            let target = String::from_utf8_lossy(blob.content()).into_owned();
+            builder
+                .append_link(&mut header, path, target)
+                .into_pyresult()?;
+        } else {
+            header.set_entry_type(EntryType::Regular);
+            header.set_size(blob.size() as u64);
+            header.set_mode(if *filemode == EXECUTABLE_MODE {
+                0o755
+            } else {
+                0o644
+            });
+            builder
+                .append_data(&mut header, path, blob.content())
+                .into_pyresult()?;
+        }
+    }
+    Ok(blobs.len())
+}
+
+/// Writes the files of a commit to a tar archive, gzipped if the path ends with `.gz` or
+/// `.tgz`.
+///
+/// Returns:
+///     The number of files written.
+pub(crate) fn write_archive(commit: &Commit, path: &Path) -> PyResult<usize> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).into_pyresult()?;
+    }
+    let file = BufWriter::new(File::create(path).into_pyresult()?);
+    if is_gzipped(path) {
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+        let count = append_tree(&mut builder, commit)?;
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .and_then(|mut file| file.flush())
+            .into_pyresult()?;
+        Ok(count)
+    } else {
+        let mut builder = Builder::new(file);
+        let count = append_tree(&mut builder, commit)?;
+        builder
+            .into_inner()
+            .and_then(|mut file| file.flush())
+            .into_pyresult()?;
+        Ok(count)
+    }
+}
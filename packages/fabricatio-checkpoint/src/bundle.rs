@@ -0,0 +1,140 @@
+//! Checkpoint histories moved between machines as git bundles.
+//!
+//! A bundle is a file in the v2 format of `git bundle`: a `# v2 git bundle` line, one
+//! `<oid> <refname>` line per reference, an empty line, then a pack of every object the
+//! references reach. It holds the whole history of a shadow repository, including its tags and
+//! the notes listing skipped files and describing tags, so it can be imported on another
+//! machine or read with `git clone <bundle>`.
+
+use crate::constants::{
+    HEAD_NAME, HEAD_REF_NAME, SKIPPED_NOTES_REF, TAG_NOTES_REF, TAG_REF_PREFIX,
+};
+use error_mapping::AsPyErr;
+use git2::{Buf, Oid, Repository};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// First line of a bundle in the v2 format.
+const BUNDLE_SIGNATURE: &str = "# v2 git bundle";
+
+/// The references a bundle carries besides the tags, those of the history and of the notes.
+const BUNDLED_REFS: [&str; 3] = [HEAD_REF_NAME, SKIPPED_NOTES_REF, TAG_NOTES_REF];
+
+/// Names of the tag references of a repository.
+fn tag_refs(repo: &Repository) -> PyResult<Vec<String>> {
+    repo.references_glob(&format!("{TAG_REF_PREFIX}*"))
+        .into_pyresult()?
+        .names()
+        .map(|name| name.map(str::to_string).into_pyresult())
+        .collect()
+}
+
+/// Writes the history of a repository to a bundle file.
+///
+/// Returns:
+///     The ID of the HEAD commit of the bundle.
+pub(crate) fn write_bundle(repo: &Repository, path: &Path) -> PyResult<String> {
+    let head = repo.refname_to_id(HEAD_REF_NAME).into_pyresult()?;
+    let refs = BUNDLED_REFS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(tag_refs(repo)?)
+        .filter_map(|name| repo.refname_to_id(&name).ok().map(|oid| (oid, name)))
+        .collect::<Vec<_>>();
+
+    let mut walk = repo.revwalk().into_pyresult()?;
+    refs.iter()
+        .try_for_each(|(oid, _)| walk.push(*oid))
+        .into_pyresult()?;
+    let mut builder = repo.packbuilder().into_pyresult()?;
+    builder.insert_walk(&mut walk).into_pyresult()?;
+    let mut pack = Buf::new();
+    builder.write_buf(&mut pack).into_pyresult()?;
+
+    let mut header = format!("{BUNDLE_SIGNATURE}\n{head} {HEAD_NAME}\n");
+    refs.iter()
+        .for_each(|(oid, name)| header.push_str(&format!("{oid} {name}\n")));
+    header.push('\n');
+
+    let mut file = fs::File::create(path).into_pyresult()?;
+    file.write_all(header.as_bytes()).into_pyresult()?;
+    file.write_all(&pack).into_pyresult()?;
+    Ok(head.to_string())
+}
+
+/// Reads a bundle file into a repository, replacing its references with those of the bundle.
+///
+/// Returns:
+///     The ID of the HEAD commit of the bundle.
+///
+/// Raises:
+///     ValueError: If the file is not a bundle of a checkpoint history.
+pub(crate) fn read_bundle(repo: &Repository, path: &Path) -> PyResult<String> {
+    let content = fs::read(path).into_pyresult()?;
+    let invalid = |reason: &str| {
+        PyValueError::new_err(format!(
+            "{} is not a checkpoint bundle: {reason}",
+            path.display()
+        ))
+    };
+
+    let header_len = content
+        .windows(2)
+        .position(|window| window == b"\n\n")
+        .ok_or_else(|| invalid("truncated header"))?;
+    let header =
+        std::str::from_utf8(&content[..header_len]).map_err(|_| invalid("malformed header"))?;
+    let mut lines = header.lines();
+    if lines.next() != Some(BUNDLE_SIGNATURE) {
+        return Err(invalid("unsupported format"));
+    }
+
+    let mut refs = Vec::new();
+    for line in lines {
+        if line.starts_with('-') {
+            return Err(invalid("it depends on commits it does not contain"));
+        }
+        let (oid, name) = line
+            .split_once(' ')
+            .ok_or_else(|| invalid("malformed reference"))?;
+        refs.push((
+            Oid::from_str(oid).map_err(|_| invalid("malformed reference"))?,
+            name.to_string(),
+        ));
+    }
+    let head = refs
+        .iter()
+        .find(|(_, name)| name == HEAD_REF_NAME)
+        .map(|(oid, _)| *oid)
+        .ok_or_else(|| invalid("no checkpoint history"))?;
+
+    let odb = repo.odb().into_pyresult()?;
+    let mut writer = odb.packwriter().into_pyresult()?;
+    writer
+        .write_all(&content[header_len + 2..])
+        .into_pyresult()?;
+    writer.commit().into_pyresult()?;
+
+    // Notes and tags of the replaced history would refer to commits the bundle does not have.
+    for name in BUNDLED_REFS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(tag_refs(repo)?)
+    {
+        if !refs.iter().any(|(_, bundled)| *bundled == name)
+            && let Ok(mut reference) = repo.find_reference(&name)
+        {
+            reference.delete().into_pyresult()?;
+        }
+    }
+    for (oid, name) in &refs {
+        if BUNDLED_REFS.contains(&name.as_str()) || name.starts_with(TAG_REF_PREFIX) {
+            repo.reference(name, *oid, true, "import checkpoint bundle")
+                .into_pyresult()?;
+        }
+    }
+    Ok(head.to_string())
+}
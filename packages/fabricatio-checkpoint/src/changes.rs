@@ -0,0 +1,92 @@
+//! Files changed between the trees of two checkpoints, and their patches.
+
+use error_mapping::AsPyErr;
+use git2::{Delta, Diff, DiffFormat, DiffLineType};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+/// A file added, modified or deleted between two checkpoints.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// The path of the file, relative to the workspace.
+    pub(crate) path: String,
+    /// How the file changed: `added`, `modified` or `deleted`.
+    pub(crate) kind: String,
+}
+
+impl FileChange {
+    /// Lists the files changed by a diff of two trees, sorted by path.
+    pub(crate) fn of_diff(diff: &Diff) -> Vec<Self> {
+        let mut changes: Vec<Self> = diff
+            .deltas()
+            .filter_map(|delta| {
+                let (kind, file) = match delta.status() {
+                    Delta::Added => ("added", delta.new_file()),
+                    Delta::Deleted => ("deleted", delta.old_file()),
+                    Delta::Modified | Delta::Typechange => ("modified", delta.new_file()),
+                    _ => return None,
+                };
+                Some(Self {
+                    path: file.path()?.to_string_lossy().replace('\\', "/"),
+                    kind: kind.to_string(),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+/// Renders the patch of each file of a diff, in the order of the diff.
+///
+/// Files for which `keep` returns false, given their path relative to the workspace, are left
+/// out. Binary files only get a header telling they differ.
+///
+/// Returns:
+///     The path of each file, relative to the workspace, with its patch.
+pub(crate) fn patches_of(
+    diff: &Diff,
+    mut keep: impl FnMut(&str) -> bool,
+) -> PyResult<Vec<(String, String)>> {
+    let mut patches: Vec<(String, String)> = Vec::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        let Some(path) = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+        else {
+            return true;
+        };
+        if line.origin_value() == DiffLineType::FileHeader {
+            if !keep(&path) {
+                return true;
+            }
+            patches.push((path, String::new()));
+        }
+        let Some((_, patch)) = patches.last_mut().filter(|(last, _)| *last == path) else {
+            return true;
+        };
+        if matches!(
+            line.origin_value(),
+            DiffLineType::Context | DiffLineType::Addition | DiffLineType::Deletion
+        ) {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .into_pyresult()?;
+    Ok(patches)
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl FileChange {
+    fn __repr__(&self) -> String {
+        format!("FileChange(path={:?}, kind={:?})", self.path, self.kind)
+    }
+}
@@ -4,8 +4,13 @@
 //! that track file changes and enable checkpoint/restore functionality. Each worktree directory
 //! gets its own bare Git repository for tracking changes independently.
 
+use crate::changes::FileChange;
+use crate::prune::PruneReport;
+use crate::search::SearchHit;
 use crate::service::CheckpointService;
+use crate::skipped::SkippedFile;
 use crate::store::CheckPointStore;
+use crate::tags::CheckpointTag;
 use error_mapping::*;
 use pyo3::prelude::*;
 
@@ -20,5 +25,10 @@ use pyo3::prelude::*;
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<CheckPointStore>()?;
     m.add_class::<CheckpointService>()?;
+    m.add_class::<SkippedFile>()?;
+    m.add_class::<FileChange>()?;
+    m.add_class::<CheckpointTag>()?;
+    m.add_class::<SearchHit>()?;
+    m.add_class::<PruneReport>()?;
     Ok(())
 }
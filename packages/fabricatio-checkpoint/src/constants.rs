@@ -1,3 +1,11 @@
 pub const HEAD_REF_NAME: &str = "refs/heads/master";
 
 pub const HEAD_NAME: &str = "HEAD";
+
+pub const SKIPPED_NOTES_REF: &str = "refs/notes/skipped";
+
+pub const IGNORE_FILE_NAME: &str = ".checkpointignore";
+
+pub const TAG_REF_PREFIX: &str = "refs/tags/";
+
+pub const TAG_NOTES_REF: &str = "refs/notes/tags";
@@ -0,0 +1,174 @@
+//! Hooks run before and after saving or resetting a checkpoint store.
+//!
+//! A hook is either a Python callable or a shell command. Hooks are registered on the
+//! `CheckpointService` and shared by every store it hands out.
+
+use error_mapping::AsPyErr;
+use fabricatio_logger::{debug, warn};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// The points of a store operation a hook can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum HookPoint {
+    PreSave,
+    PostSave,
+    PreReset,
+    PostReset,
+}
+
+impl FromStr for HookPoint {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pre_save" => Ok(Self::PreSave),
+            "post_save" => Ok(Self::PostSave),
+            "pre_reset" => Ok(Self::PreReset),
+            "post_reset" => Ok(Self::PostReset),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown hook point `{s}`, expected one of `pre_save`, `post_save`, `pre_reset`, `post_reset`"
+            ))),
+        }
+    }
+}
+
+impl Display for HookPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::PreSave => "pre_save",
+            Self::PostSave => "post_save",
+            Self::PreReset => "pre_reset",
+            Self::PostReset => "post_reset",
+        })
+    }
+}
+
+/// What a hook does when it runs.
+pub(crate) enum HookAction {
+    /// A Python callable invoked with the workspace path and the commit ID (or None).
+    Callback(Py<PyAny>),
+    /// A shell command run inside the workspace.
+    Command(String),
+}
+
+/// A registered hook.
+pub(crate) struct Hook {
+    action: HookAction,
+    /// Whether a failure of this hook aborts the operation.
+    fatal: bool,
+}
+
+impl Hook {
+    pub(crate) fn new(action: HookAction, fatal: bool) -> Self {
+        Self { action, fatal }
+    }
+
+    fn run(&self, point: HookPoint, workspace: &Path, commit_id: Option<&str>) -> PyResult<()> {
+        match &self.action {
+            HookAction::Callback(callback) => Python::attach(|python| {
+                callback.call1(python, (workspace.to_path_buf(), commit_id))?;
+                Ok(())
+            }),
+            HookAction::Command(command) => {
+                let mut cmd = if cfg!(windows) {
+                    let mut cmd = Command::new("cmd");
+                    cmd.args(["/C", command]);
+                    cmd
+                } else {
+                    let mut cmd = Command::new("sh");
+                    cmd.args(["-c", command]);
+                    cmd
+                };
+                let output = cmd
+                    .current_dir(workspace)
+                    .env("CHECKPOINT_HOOK", point.to_string())
+                    .env("CHECKPOINT_WORKSPACE", workspace)
+                    .env("CHECKPOINT_COMMIT", commit_id.unwrap_or_default())
+                    .output()
+                    .into_pyresult()?;
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(PyRuntimeError::new_err(format!(
+                        "Hook command `{command}` exited with {}: {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// The hooks registered for each hook point.
+#[derive(Default)]
+pub(crate) struct Hooks {
+    registry: RwLock<HashMap<HookPoint, Vec<Arc<Hook>>>>,
+}
+
+pub(crate) type HooksEntry = Arc<Hooks>;
+
+impl Hooks {
+    /// Registers a hook to run at the given point, after the hooks already registered there.
+    pub(crate) fn add(&self, point: HookPoint, hook: Hook) {
+        self.registry
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(point)
+            .or_default()
+            .push(Arc::new(hook));
+    }
+
+    /// Removes the hooks of the given point, or all hooks if no point is given.
+    pub(crate) fn clear(&self, point: Option<HookPoint>) {
+        let mut registry = self
+            .registry
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match point {
+            Some(point) => {
+                registry.remove(&point);
+            }
+            None => registry.clear(),
+        }
+    }
+
+    /// Runs the hooks of the given point in registration order.
+    ///
+    /// Failures of non-fatal hooks are logged, the first failure of a fatal hook is returned.
+    pub(crate) fn run(
+        &self,
+        point: HookPoint,
+        workspace: &Path,
+        commit_id: Option<&str>,
+    ) -> PyResult<()> {
+        // Snapshot the hooks so callbacks may register or clear hooks themselves.
+        let hooks = self
+            .registry
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&point)
+            .cloned()
+            .unwrap_or_default();
+
+        if !hooks.is_empty() {
+            debug!("Running {} {point} hook(s)...", hooks.len());
+        }
+        for hook in hooks {
+            if let Err(e) = hook.run(point, workspace, commit_id) {
+                if hook.fatal {
+                    return Err(e);
+                }
+                warn!("A {point} hook of {} failed: {e}", workspace.display());
+            }
+        }
+        Ok(())
+    }
+}
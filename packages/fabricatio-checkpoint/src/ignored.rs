@@ -0,0 +1,73 @@
+//! Rules keeping files out of checkpoints.
+//!
+//! Patterns follow the `.gitignore` syntax and come from three places, later ones overriding
+//! earlier ones: the build outputs and caches of the project the workspace holds, as
+//! `detect_project` reports them, unless the service disables them, the `.checkpointignore`
+//! file at the root of a workspace, and the service, applying to every workspace. The file and
+//! the manifests are read anew at every save, so editing them takes effect on the next
+//! checkpoint. The `.gitignore` files of the workspace are honored as well, as by git itself.
+
+use crate::constants::IGNORE_FILE_NAME;
+use fabricatio_logger::warn;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::path::Path;
+use utils::project;
+
+/// Builds the ignore rules of a workspace from the given patterns and its `.checkpointignore`.
+///
+/// Args:
+///     workspace: The workspace directory, which patterns are anchored to.
+///     patterns: The patterns applying on top of the `.checkpointignore` file.
+///     project_ignore: Whether the patterns of the project at the root of the workspace come
+///         first.
+///
+/// Raises:
+///     ValueError: If one of the given patterns is invalid. Invalid lines of the
+///         `.checkpointignore` file and unreadable manifests are only logged.
+pub(crate) fn ignore_rules(
+    workspace: &Path,
+    patterns: &[String],
+    project_ignore: bool,
+) -> PyResult<Gitignore> {
+    let mut builder = GitignoreBuilder::new(workspace);
+    if project_ignore {
+        match project::detect(workspace, Some(workspace)) {
+            Ok(Some(project)) => {
+                for pattern in project.ignore() {
+                    // The patterns of the project are known to be valid
+                    let _ = builder.add_line(None, pattern);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!(
+                "Failed to detect the project of {}: {e}",
+                workspace.display()
+            ),
+        }
+    }
+    let file = workspace.join(IGNORE_FILE_NAME);
+    if file.is_file()
+        && let Some(e) = builder.add(&file)
+    {
+        warn!("Invalid rules in {}: {e}", file.display());
+    }
+    for pattern in patterns {
+        builder.add_line(None, pattern).map_err(|e| {
+            PyValueError::new_err(format!("Invalid ignore pattern `{pattern}`: {e}"))
+        })?;
+    }
+    builder
+        .build()
+        .map_err(|e| PyValueError::new_err(format!("Invalid ignore rules: {e}")))
+}
+
+/// Checks whether a path relative to the workspace is ignored, by itself or through one of
+/// its parent directories.
+pub(crate) fn is_ignored(rules: &Gitignore, rel_path: &Path, is_dir: bool) -> bool {
+    rel_path.components().next().is_some()
+        && rules
+            .matched_path_or_any_parents(rel_path, is_dir)
+            .is_ignore()
+}
@@ -3,10 +3,19 @@
 use pyo3::prelude::*;
 use pyo3_stub_gen::define_stub_info_gatherer;
 
+mod archive;
+mod bundle;
+mod changes;
 mod checkpoint;
 mod constants;
+mod hooks;
+mod ignored;
+mod prune;
+mod search;
 mod service;
+mod skipped;
 mod store;
+mod tags;
 mod utils;
 
 define_stub_info_gatherer!(stub_info);
@@ -0,0 +1,270 @@
+//! Retention of checkpoints: dropping old ones and reclaiming the space they use.
+//!
+//! The history of a store is linear, so pruning rebuilds it on top of the initial commit with
+//! only the kept checkpoints, each with its original tree, message and signatures. The latest
+//! checkpoint and the tagged ones are always kept, and the notes and tags follow the rebuilt
+//! commits. The objects only dropped checkpoints used are then removed by repacking the
+//! repository, as `git gc --prune=now` would.
+
+use crate::constants::{
+    HEAD_NAME, HEAD_REF_NAME, SKIPPED_NOTES_REF, TAG_NOTES_REF, TAG_REF_PREFIX,
+};
+use error_mapping::AsPyErr;
+use git2::{Commit, Oid, Repository};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the directory the new pack is written to before it replaces the old objects.
+const REPACK_DIR: &str = "pack-prune";
+
+/// Number of checkpoints over `keep_last` tolerated before an automatic prune.
+const AUTO_PRUNE_SLACK: usize = 20;
+
+/// Days over `max_age_days` tolerated before an automatic prune.
+const AUTO_PRUNE_SLACK_DAYS: f64 = 1.0;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// The outcome of pruning the checkpoints of a store.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, Default)]
+pub struct PruneReport {
+    /// Number of checkpoints kept.
+    pub(crate) kept: usize,
+    /// Number of checkpoints dropped.
+    pub(crate) dropped: usize,
+    /// Number of bytes the repository shrank by.
+    pub(crate) reclaimed_bytes: u64,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl PruneReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "PruneReport(kept={}, dropped={}, reclaimed_bytes={})",
+            self.kept, self.dropped, self.reclaimed_bytes
+        )
+    }
+}
+
+/// The checkpoints a store keeps when pruned automatically after saving.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Retention {
+    /// Number of latest checkpoints to keep.
+    pub(crate) keep_last: Option<usize>,
+    /// Age in days over which checkpoints are dropped.
+    pub(crate) max_age_days: Option<f64>,
+}
+
+impl Retention {
+    pub(crate) fn is_none(&self) -> bool {
+        self.keep_last.is_none() && self.max_age_days.is_none()
+    }
+
+    /// Checks whether the history is far enough past the limits to be worth pruning.
+    ///
+    /// Args:
+    ///     count: The number of checkpoints.
+    ///     oldest: The time of the oldest checkpoint, in seconds since the Unix epoch.
+    pub(crate) fn is_due(&self, count: usize, oldest: Option<i64>) -> bool {
+        self.keep_last
+            .is_some_and(|keep_last| count > keep_last + AUTO_PRUNE_SLACK)
+            || self
+                .max_age_days
+                .zip(oldest)
+                .is_some_and(|(days, oldest)| oldest < cutoff_of(days + AUTO_PRUNE_SLACK_DAYS))
+    }
+
+    /// Whether the checkpoint at `index` from the latest one, made at `time`, is kept.
+    pub(crate) fn keeps(&self, index: usize, time: i64) -> bool {
+        self.keep_last.is_none_or(|keep_last| index < keep_last)
+            && self.max_age_days.is_none_or(|days| time >= cutoff_of(days))
+    }
+}
+
+/// The time, in seconds since the Unix epoch, `days` ago.
+fn cutoff_of(days: f64) -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    now - (days * SECONDS_PER_DAY) as i64
+}
+
+/// Lists the checkpoints from the latest one, leaving out the initial commit.
+pub(crate) fn checkpoints_of(repo: &Repository) -> PyResult<Vec<Commit<'_>>> {
+    let mut walk = repo.revwalk().into_pyresult()?;
+    walk.push_ref(HEAD_REF_NAME).into_pyresult()?;
+    let mut commits = walk
+        .map(|oid| oid.and_then(|oid| repo.find_commit(oid)))
+        .collect::<Result<Vec<_>, _>>()
+        .into_pyresult()?;
+    commits.pop();
+    Ok(commits)
+}
+
+fn tag_targets(repo: &Repository) -> PyResult<Vec<(String, Oid)>> {
+    repo.references_glob(&format!("{TAG_REF_PREFIX}*"))
+        .into_pyresult()?
+        .filter_map(|reference| {
+            reference
+                .map(|reference| reference.name().map(str::to_string).zip(reference.target()))
+                .transpose()
+        })
+        .collect::<Result<_, _>>()
+        .into_pyresult()
+}
+
+/// Rebuilds the notes of a ref on the rebuilt commits, dropping those of dropped commits.
+fn rewrite_notes(
+    repo: &Repository,
+    notes_ref: &str,
+    rewritten: &HashMap<Oid, Oid>,
+) -> PyResult<()> {
+    let Ok(notes) = repo.notes(Some(notes_ref)) else {
+        return Ok(());
+    };
+    let mut moved = Vec::new();
+    for note in notes {
+        let (_, annotated) = note.into_pyresult()?;
+        if let Some(new) = rewritten.get(&annotated) {
+            let note = repo.find_note(Some(notes_ref), annotated).into_pyresult()?;
+            moved.push((*new, note.message().unwrap_or_default().to_string()));
+        }
+    }
+    repo.find_reference(notes_ref)
+        .and_then(|mut reference| reference.delete())
+        .into_pyresult()?;
+    let sig = repo.signature().into_pyresult()?;
+    for (oid, message) in moved {
+        repo.note(&sig, &sig, Some(notes_ref), oid, &message, true)
+            .into_pyresult()?;
+    }
+    Ok(())
+}
+
+/// Rebuilds the history keeping the checkpoints `keep` accepts, given their index from the
+/// latest one and their time.
+///
+/// Returns:
+///     The numbers of kept and dropped checkpoints.
+pub(crate) fn rewrite_history(
+    repo: &Repository,
+    keep: impl Fn(usize, i64) -> bool,
+) -> PyResult<(usize, usize)> {
+    let checkpoints = checkpoints_of(repo)?;
+    let tags = tag_targets(repo)?;
+    let tagged: HashSet<Oid> = tags.iter().map(|(_, oid)| *oid).collect();
+    let kept: Vec<&Commit> = checkpoints
+        .iter()
+        .enumerate()
+        .filter(|(index, commit)| {
+            *index == 0 || tagged.contains(&commit.id()) || keep(*index, commit.time().seconds())
+        })
+        .map(|(_, commit)| commit)
+        .collect();
+    let dropped = checkpoints.len() - kept.len();
+    if dropped == 0 {
+        return Ok((kept.len(), 0));
+    }
+
+    // The initial commit, below the oldest checkpoint.
+    let mut parent = checkpoints[checkpoints.len() - 1]
+        .parent(0)
+        .into_pyresult()?;
+    let mut rewritten = HashMap::new();
+    for commit in kept.iter().rev() {
+        let oid = repo
+            .commit(
+                None,
+                &commit.author(),
+                &commit.committer(),
+                commit.message_raw().unwrap_or_default(),
+                &commit.tree().into_pyresult()?,
+                &[&parent],
+            )
+            .into_pyresult()?;
+        rewritten.insert(commit.id(), oid);
+        parent = repo.find_commit(oid).into_pyresult()?;
+    }
+
+    repo.reference(HEAD_REF_NAME, parent.id(), true, "prune checkpoints")
+        .into_pyresult()?;
+    for notes_ref in [SKIPPED_NOTES_REF, TAG_NOTES_REF] {
+        rewrite_notes(repo, notes_ref, &rewritten)?;
+    }
+    for (name, target) in tags {
+        if let Some(oid) = rewritten.get(&target) {
+            repo.reference(&name, *oid, true, "prune checkpoints")
+                .into_pyresult()?;
+        }
+    }
+    Ok((kept.len(), dropped))
+}
+
+/// Total size in bytes of the files below a directory.
+pub(crate) fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map_or(0, |meta| meta.len()),
+        })
+        .sum()
+}
+
+/// Packs the objects reachable from the references of a repository, and deletes all others,
+/// such as those of dropped checkpoints or of checkpoints a reset left behind.
+///
+/// The repository is reopened before the old objects are deleted, so that no handle to them
+/// stays open.
+pub(crate) fn repack(repo: &mut Repository) -> PyResult<()> {
+    // The reflogs would still refer to the deleted objects.
+    for name in [HEAD_REF_NAME, HEAD_NAME] {
+        repo.reflog_delete(name).into_pyresult()?;
+    }
+    let objects = repo.path().join("objects");
+    let staging = objects.join(REPACK_DIR);
+    if staging.exists() {
+        fs::remove_dir_all(&staging).into_pyresult()?;
+    }
+    fs::create_dir_all(&staging).into_pyresult()?;
+
+    {
+        let mut walk = repo.revwalk().into_pyresult()?;
+        for reference in repo.references().into_pyresult()? {
+            if let Ok(commit) = reference.into_pyresult()?.peel_to_commit() {
+                walk.push(commit.id()).into_pyresult()?;
+            }
+        }
+        let mut builder = repo.packbuilder().into_pyresult()?;
+        builder.insert_walk(&mut walk).into_pyresult()?;
+        builder.write(&staging, 0).into_pyresult()?;
+    }
+
+    let repo_path = repo.path().to_path_buf();
+    *repo = Repository::open(&repo_path).into_pyresult()?;
+
+    let pack_dir = objects.join("pack");
+    for entry in fs::read_dir(&objects).into_pyresult()? {
+        let path = entry.into_pyresult()?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        // Loose objects live in directories named by the first two hex digits of their ID.
+        if name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+            fs::remove_dir_all(&path).into_pyresult()?;
+        }
+    }
+    if pack_dir.exists() {
+        fs::remove_dir_all(&pack_dir).into_pyresult()?;
+    }
+    fs::rename(&staging, &pack_dir).into_pyresult()?;
+    repo.odb().and_then(|odb| odb.refresh()).into_pyresult()
+}
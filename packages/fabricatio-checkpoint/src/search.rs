@@ -0,0 +1,97 @@
+//! Search of the lines the checkpoints added or removed, like `git log -G`.
+
+use crate::prune::checkpoints_of;
+use error_mapping::AsPyErr;
+use git2::{DiffFormat, DiffLineType, DiffOptions, Repository};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use regex::Regex;
+
+/// A line matching a search, added or removed by a checkpoint.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// The commit ID (OID as string) of the checkpoint.
+    pub(crate) commit_id: String,
+    /// The time of the checkpoint, in seconds since the Unix epoch.
+    pub(crate) timestamp: i64,
+    /// The path of the file, relative to the workspace.
+    pub(crate) path: String,
+    /// The number of the line, in the file after the checkpoint if added, before if removed.
+    pub(crate) line_number: u32,
+    /// The content of the line, without its line ending.
+    pub(crate) line: String,
+    /// Whether the checkpoint `added` or `removed` the line.
+    pub(crate) change: String,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl SearchHit {
+    fn __repr__(&self) -> String {
+        format!(
+            "SearchHit(commit_id={:?}, path={:?}, line_number={}, change={:?})",
+            self.commit_id, self.path, self.line_number, self.change
+        )
+    }
+}
+
+/// Lists the lines matching a pattern that the checkpoints added or removed, the latest
+/// checkpoint first.
+///
+/// Args:
+///     pattern: The regular expression the lines are matched against.
+///     path_glob: Optional glob the searched files must match.
+pub(crate) fn search_history(
+    repo: &Repository,
+    pattern: &Regex,
+    path_glob: Option<&str>,
+) -> PyResult<Vec<SearchHit>> {
+    let mut opts = DiffOptions::new();
+    if let Some(glob) = path_glob {
+        opts.pathspec(glob);
+    }
+    let mut hits = Vec::new();
+    for commit in checkpoints_of(repo)? {
+        let parent_tree = commit
+            .parent(0)
+            .and_then(|parent| parent.tree())
+            .into_pyresult()?;
+        let diff = repo
+            .diff_tree_to_tree(
+                Some(&parent_tree),
+                Some(&commit.tree().into_pyresult()?),
+                Some(&mut opts),
+            )
+            .into_pyresult()?;
+        let commit_id = commit.id().to_string();
+        diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+            let (change, line_number) = match line.origin_value() {
+                DiffLineType::Addition => ("added", line.new_lineno()),
+                DiffLineType::Deletion => ("removed", line.old_lineno()),
+                _ => return true,
+            };
+            let content = String::from_utf8_lossy(line.content());
+            let content = content.trim_end_matches(['\r', '\n']);
+            if !pattern.is_match(content) {
+                return true;
+            }
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                return true;
+            };
+            hits.push(SearchHit {
+                commit_id: commit_id.clone(),
+                timestamp: commit.time().seconds(),
+                path: path.to_string_lossy().replace('\\', "/"),
+                line_number: line_number.unwrap_or_default(),
+                line: content.to_string(),
+                change: change.to_string(),
+            });
+            true
+        })
+        .into_pyresult()?;
+    }
+    Ok(hits)
+}
@@ -1,17 +1,25 @@
+use crate::hooks::{Hook, HookAction, HookPoint, HooksEntry};
+use crate::ignored::ignore_rules;
+use crate::prune::Retention;
+use crate::skipped::SkippedContent;
 use crate::store::{CheckPointStore, RepoEntry};
 use crate::utils::{
-    AsKey, create_shadow_repo, managed_workspaces, normalized_path_of, prune_stores,
+    AsKey, create_shadow_repo, managed_workspaces, normalized_path_of, prune_stores, store_sizes,
 };
 use error_mapping::AsPyErr;
 use fabricatio_logger::debug;
 use git2::Repository;
 use moka::sync::Cache;
-use pyo3::{PyResult, pyclass, pymethods};
+use pyo3::exceptions::{PyFileNotFoundError, PyValueError};
+use pyo3::{Py, PyAny, PyResult, pyclass, pymethods};
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use utils::cas::ContentStore;
 use utils::mwrap;
 
 /// Manages shadow Git repositories for file checkpointing.
@@ -25,6 +33,12 @@ use utils::mwrap;
 pub struct CheckpointService {
     stores_root: PathBuf,
     repo_cache: Cache<PathBuf, RepoEntry>,
+    hooks: HooksEntry,
+    max_file_size: Option<u64>,
+    skipped_store: Option<ContentStore>,
+    ignore: Arc<[String]>,
+    project_ignore: bool,
+    retention: Retention,
 }
 
 impl CheckpointService {
@@ -45,7 +59,7 @@ impl CheckpointService {
             })
             .into_pyresult()?;
 
-        let store = CheckPointStore::new(workspace, repo);
+        let store = self.store_of(workspace, &repo_root, repo);
         store.add_init_commit()?;
         Ok(store)
     }
@@ -59,11 +73,25 @@ impl CheckpointService {
         let repo = self
             .repo_cache
             .try_get_with(repo_root.clone(), || {
-                Ok::<RepoEntry, git2::Error>(mwrap(Repository::open(repo_root)?))
+                Ok::<RepoEntry, git2::Error>(mwrap(Repository::open(&repo_root)?))
             })
             .into_pyresult()?;
-        let store = CheckPointStore::new(workspace, repo);
-        Ok(store)
+        Ok(self.store_of(workspace, &repo_root, repo))
+    }
+
+    fn store_of(&self, workspace: PathBuf, repo_root: &Path, repo: RepoEntry) -> CheckPointStore {
+        CheckPointStore::new(
+            workspace,
+            repo,
+            self.hooks.clone(),
+            self.max_file_size,
+            self.skipped_store
+                .clone()
+                .map(|store| SkippedContent::new(store, repo_root)),
+            self.ignore.clone(),
+            self.project_ignore,
+            self.retention,
+        )
     }
 
     fn create_or_open(&self, workspace: PathBuf, repo_root: PathBuf) -> PyResult<CheckPointStore> {
@@ -106,16 +134,62 @@ impl CheckpointService {
     /// Args:
     ///     stores_root: The root directory where shadow repositories will be stored.
     ///     cache_size: Maximum number of repositories to keep in the in-memory cache.
+    ///     max_file_size: Files larger than this many bytes are left out of checkpoints and
+    ///         only recorded by path, size and hash, see `CheckPointStore.skipped_files`.
+    ///         None to store every file.
+    ///     ignore: Patterns in the `.gitignore` syntax of files never to store, such as build
+    ///         artifacts or virtualenvs. They apply to every workspace, on top of the patterns
+    ///         of the `.checkpointignore` file at its root.
+    ///     keep_last: If set, stores prune their history down to this many latest checkpoints
+    ///         once it grows some checkpoints past it, see `CheckPointStore.prune`.
+    ///     max_age_days: If set, stores prune the checkpoints older than this many days once
+    ///         the oldest is a day past it, see `CheckPointStore.prune_older_than`.
+    ///     project_ignore: Whether the build outputs, caches and installed dependencies of the
+    ///         project at the root of a workspace are left out as well, such as `target/` for
+    ///         a `Cargo.toml` or `node_modules/` for a `package.json`, see `detect_project`.
+    ///     skipped_store: The directory of a content-addressed store to keep the content of the
+    ///         files left out for their size in, so `CheckPointStore.restore_skipped` can bring
+    ///         them back, such as the `cas` directory of the roaming directory shared with the
+    ///         other packages. The content stays referenced for as long as a checkpoint lists
+    ///         it. None to keep only their placeholders.
     ///
     /// Returns:
     ///     A new CheckpointService instance.
-    #[pyo3(signature = (stores_root, cache_size=10))]
+    ///
+    /// Raises:
+    ///     ValueError: If an ignore pattern is invalid or `max_age_days` is negative.
+    ///     OSError: If the `skipped_store` directory cannot be created.
+    #[pyo3(signature = (stores_root, cache_size=10, max_file_size=None, ignore=vec![], keep_last=None, max_age_days=None, project_ignore=true, skipped_store=None))]
     #[new]
-    fn new(stores_root: PathBuf, cache_size: u64) -> PyResult<Self> {
+    fn new(
+        stores_root: PathBuf,
+        cache_size: u64,
+        max_file_size: Option<u64>,
+        ignore: Vec<String>,
+        keep_last: Option<usize>,
+        max_age_days: Option<f64>,
+        project_ignore: bool,
+        skipped_store: Option<PathBuf>,
+    ) -> PyResult<Self> {
+        if max_age_days.is_some_and(|days| days.is_nan() || days < 0.0) {
+            return Err(PyValueError::new_err("max_age_days must not be negative"));
+        }
         fs::create_dir_all(&stores_root).into_pyresult()?;
+        let stores_root = stores_root.canonicalize().into_pyresult()?;
+        // Rules are anchored to each workspace when saving, any directory checks the patterns.
+        ignore_rules(&stores_root, &ignore, false)?;
         Ok(Self {
-            stores_root: stores_root.canonicalize().into_pyresult()?,
+            stores_root,
             repo_cache: Cache::new(cache_size),
+            hooks: HooksEntry::default(),
+            max_file_size,
+            skipped_store: skipped_store.map(ContentStore::open).transpose()?,
+            ignore: ignore.into(),
+            project_ignore,
+            retention: Retention {
+                keep_last,
+                max_age_days,
+            },
         })
     }
 
@@ -127,6 +201,125 @@ impl CheckpointService {
         managed_workspaces(&self.stores_root)
     }
 
+    /// Returns the disk space taken by the store of each managed workspace.
+    ///
+    /// Returns:
+    ///     A dict mapping workspace paths to the size of their store in bytes.
+    fn disk_usage(&self) -> PyResult<HashMap<PathBuf, u64>> {
+        Ok(store_sizes(&self.stores_root)?.into_iter().collect())
+    }
+
+    /// Registers a Python callable to run before or after stores save or reset.
+    ///
+    /// The callable receives the workspace path and the commit ID involved: None for
+    /// `pre_save`, the new commit for `post_save`, and the target commit for `pre_reset`
+    /// and `post_reset`. Hooks apply to every store handed out by this service.
+    ///
+    /// Args:
+    ///     point: One of `pre_save`, `post_save`, `pre_reset` or `post_reset`.
+    ///     callback: The callable to run.
+    ///     fatal: If True, an exception raised by the callable aborts the operation (for
+    ///         `pre_*` hooks) or is raised to the caller (for `post_*` hooks). Otherwise the
+    ///         failure is only logged.
+    ///
+    /// Raises:
+    ///     ValueError: If the hook point is unknown.
+    #[pyo3(signature = (point, callback, fatal = false))]
+    fn add_hook(&self, point: &str, callback: Py<PyAny>, fatal: bool) -> PyResult<()> {
+        self.hooks.add(
+            point.parse()?,
+            Hook::new(HookAction::Callback(callback), fatal),
+        );
+        Ok(())
+    }
+
+    /// Registers a shell command to run before or after stores save or reset.
+    ///
+    /// The command runs in the workspace directory with the `CHECKPOINT_HOOK`,
+    /// `CHECKPOINT_WORKSPACE` and `CHECKPOINT_COMMIT` environment variables set. A non-zero
+    /// exit status counts as a failure.
+    ///
+    /// Args:
+    ///     point: One of `pre_save`, `post_save`, `pre_reset` or `post_reset`.
+    ///     command: The shell command to run.
+    ///     fatal: If True, a failure aborts the operation (for `pre_*` hooks) or is raised to
+    ///         the caller (for `post_*` hooks). Otherwise the failure is only logged.
+    ///
+    /// Raises:
+    ///     ValueError: If the hook point is unknown.
+    #[pyo3(signature = (point, command, fatal = false))]
+    fn add_hook_command(&self, point: &str, command: String, fatal: bool) -> PyResult<()> {
+        self.hooks.add(
+            point.parse()?,
+            Hook::new(HookAction::Command(command), fatal),
+        );
+        Ok(())
+    }
+
+    /// Removes registered hooks.
+    ///
+    /// Args:
+    ///     point: The hook point to clear, or None to remove all hooks.
+    ///
+    /// Raises:
+    ///     ValueError: If the hook point is unknown.
+    #[pyo3(signature = (point = None))]
+    fn clear_hooks(&self, point: Option<&str>) -> PyResult<()> {
+        self.hooks
+            .clear(point.map(str::parse::<HookPoint>).transpose()?);
+        Ok(())
+    }
+
+    /// Writes the whole checkpoint history of a workspace to a bundle file.
+    ///
+    /// The file uses the format of `git bundle` and holds every checkpoint along with the
+    /// records of the skipped files, so the history can be moved to another machine with
+    /// `import_bundle`, attached to a bug report, or inspected with `git clone`.
+    ///
+    /// Args:
+    ///     workspace: The workspace whose checkpoints are exported.
+    ///     path: The path of the bundle file to write, replaced if it exists.
+    ///
+    /// Returns:
+    ///     The ID of the latest checkpoint in the bundle.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the workspace has no checkpoint store.
+    fn export_bundle(&self, workspace: PathBuf, path: PathBuf) -> PyResult<String> {
+        let workspace = normalized_path_of(workspace)?;
+        let repo_root = self.repo_root_of(workspace.clone());
+        if !repo_root.exists() {
+            return Err(PyFileNotFoundError::new_err(format!(
+                "No checkpoint store for {}",
+                workspace.display()
+            )));
+        }
+        self.open_from_path(workspace, repo_root)?
+            .export_bundle(&path)
+    }
+
+    /// Loads the checkpoint history of a bundle written by `export_bundle` into the store of
+    /// a workspace.
+    ///
+    /// The history replaces the one of the store, if any, while the files of the workspace are
+    /// left untouched: reset the store to the returned checkpoint to restore them.
+    ///
+    /// Args:
+    ///     path: The path of the bundle file.
+    ///     new_workspace: The workspace the history is attached to, usually where the
+    ///         exported workspace was copied or cloned.
+    ///
+    /// Returns:
+    ///     The ID of the latest checkpoint in the bundle.
+    ///
+    /// Raises:
+    ///     ValueError: If the file is not a checkpoint bundle.
+    fn import_bundle(&self, path: PathBuf, new_workspace: PathBuf) -> PyResult<String> {
+        let workspace = normalized_path_of(new_workspace)?;
+        self.create_or_open(workspace.clone(), self.repo_root_of(workspace))?
+            .import_bundle(&path)
+    }
+
     /// Prunes stores which manage an invalid workspace.
     ///
     /// Returns:
@@ -0,0 +1,195 @@
+//! Placeholders of files left out of snapshots for exceeding the size limit.
+//!
+//! Large files such as model weights or datasets are not stored as blobs. Instead, each commit
+//! carries a git note under `SKIPPED_NOTES_REF` listing the skipped files, one `size`, `hash`,
+//! `path` triple per line separated by tabs, so they can still be told apart between commits.
+//!
+//! If the service is given a content-addressed store, the content of the skipped files is
+//! kept there as well, referenced under an owner name unique to the shadow repository for as
+//! long as a note lists it, so they can be restored.
+
+use crate::constants::SKIPPED_NOTES_REF;
+use crate::utils::confined_path;
+use error_mapping::AsPyErr;
+use git2::Repository;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use utils::cas::ContentStore;
+
+/// A file left out of a checkpoint because it exceeded the size limit.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    /// The path of the file, relative to the workspace.
+    pub(crate) path: String,
+    /// The size of the file in bytes.
+    pub(crate) size: u64,
+    /// The BLAKE3 hash of the file content, hex encoded.
+    pub(crate) hash: String,
+}
+
+impl SkippedFile {
+    /// Hashes a skipped file of the workspace.
+    ///
+    /// Args:
+    ///     workspace: The workspace directory.
+    ///     rel_path: The path of the file relative to the workspace.
+    ///     size: The size of the file in bytes.
+    pub(crate) fn of(workspace: &Path, rel_path: &Path, size: u64) -> PyResult<Self> {
+        Ok(Self::placeholder(
+            rel_path,
+            size,
+            hash_file(&workspace.join(rel_path)).into_pyresult()?,
+        ))
+    }
+
+    /// Builds the placeholder of a file from the hash of its content.
+    fn placeholder(rel_path: &Path, size: u64, hash: String) -> Self {
+        Self {
+            path: rel_path.to_string_lossy().replace('\\', "/"),
+            size,
+            hash,
+        }
+    }
+
+    /// Whether the file at a path has the content the placeholder records.
+    fn matches(&self, path: &Path) -> bool {
+        fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.len() == self.size)
+            && hash_file(path).is_ok_and(|hash| hash == self.hash)
+    }
+
+    /// Encodes the placeholders as the content of a note.
+    pub(crate) fn encode(files: &[Self]) -> String {
+        files
+            .iter()
+            .map(|file| format!("{}\t{}\t{}\n", file.size, file.hash, file.path))
+            .collect()
+    }
+
+    /// Decodes the placeholders from the content of a note, skipping malformed lines.
+    pub(crate) fn decode(note: &str) -> Vec<Self> {
+        note.lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                Some(Self {
+                    size: fields.next()?.parse().ok()?,
+                    hash: fields.next()?.to_string(),
+                    path: fields.next()?.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Hashes the content of a file with BLAKE3, hex encoded.
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// The content of the skipped files of a shadow repository, kept in a content-addressed store.
+#[derive(Debug, Clone)]
+pub(crate) struct SkippedContent {
+    store: ContentStore,
+    /// The owner of the references to the content, unique to the shadow repository.
+    owner: String,
+}
+
+impl SkippedContent {
+    pub(crate) fn new(store: ContentStore, repo_root: &Path) -> Self {
+        let key = blake3::hash(repo_root.to_string_lossy().as_bytes()).to_hex();
+        Self {
+            store,
+            owner: format!("checkpoint-{}", &key[..16]),
+        }
+    }
+
+    /// Stores a skipped file of the workspace, returning its placeholder.
+    ///
+    /// Args:
+    ///     workspace: The workspace directory.
+    ///     rel_path: The path of the file relative to the workspace.
+    ///     size: The size of the file in bytes.
+    pub(crate) fn put(
+        &self,
+        workspace: &Path,
+        rel_path: &Path,
+        size: u64,
+    ) -> PyResult<SkippedFile> {
+        let hash = self.store.put_file(workspace.join(rel_path))?;
+        Ok(SkippedFile::placeholder(rel_path, size, hash))
+    }
+
+    /// References the content of the files listed by the notes of the repository, and only
+    /// that, so the content of files listed by dropped checkpoints can be collected.
+    pub(crate) fn sync(&self, repo: &Repository) -> PyResult<()> {
+        let mut hashes = Vec::new();
+        if let Ok(notes) = repo.notes(Some(SKIPPED_NOTES_REF)) {
+            for note in notes {
+                let (_, annotated) = note.into_pyresult()?;
+                let note = repo
+                    .find_note(Some(SKIPPED_NOTES_REF), annotated)
+                    .into_pyresult()?;
+                hashes.extend(
+                    SkippedFile::decode(note.message().unwrap_or_default())
+                        .into_iter()
+                        .map(|file| file.hash)
+                        // Notes imported from a bundle are not trusted to hold valid hashes.
+                        .filter(|hash| self.store.path_of(hash).is_ok()),
+                );
+            }
+        }
+        Ok(self.store.update_references(&self.owner, |refs| {
+            *refs = hashes
+                .into_iter()
+                .map(|hash| (hash.clone(), hash))
+                .collect();
+        })?)
+    }
+
+    /// Restores a skipped file into the workspace, unless its copy there already matches.
+    ///
+    /// Returns:
+    ///     Whether the file was written, false if it already matched or the store lacks its
+    ///     content.
+    ///
+    /// Raises:
+    ///     PermissionError: If the path resolves outside the workspace.
+    pub(crate) fn restore(&self, workspace: &Path, file: &SkippedFile) -> PyResult<bool> {
+        let target = confined_path(workspace, Path::new(&file.path))?;
+        if file.matches(&target) {
+            return Ok(false);
+        }
+        let mut blob = match self.store.path_of(&file.hash).and_then(File::open) {
+            Ok(blob) => blob,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Written aside then renamed, the blob being read-only and possibly linked elsewhere.
+        let mut temp = target.clone().into_os_string();
+        temp.push(".checkpoint-restore");
+        io::copy(&mut blob, &mut File::create(&temp)?)?;
+        fs::rename(&temp, &target)?;
+        Ok(true)
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl SkippedFile {
+    fn __repr__(&self) -> String {
+        format!(
+            "SkippedFile(path={:?}, size={}, hash={:?})",
+            self.path, self.size, self.hash
+        )
+    }
+}
@@ -1,11 +1,32 @@
-use crate::constants::{HEAD_NAME, HEAD_REF_NAME};
-use crate::utils::{head_commit_of, normalized_rel_path};
+use crate::archive::write_archive;
+use crate::bundle::{read_bundle, write_bundle};
+use crate::changes::{FileChange, patches_of};
+use crate::constants::{HEAD_NAME, HEAD_REF_NAME, SKIPPED_NOTES_REF};
+use crate::hooks::{HookPoint, HooksEntry};
+use crate::ignored::{ignore_rules, is_ignored};
+use crate::prune::{PruneReport, Retention, checkpoints_of, dir_size, repack, rewrite_history};
+use crate::search::{SearchHit, search_history};
+use crate::skipped::{SkippedContent, SkippedFile};
+use crate::tags::{CheckpointTag, create_tag, list_tags, tag_ref_name, tag_target};
+use crate::utils::{confined_path, head_commit_of, normalized_rel_path};
 use error_mapping::AsPyErr;
 use fabricatio_logger::*;
-use git2::{DiffOptions, IndexAddOption, Oid, Repository};
+use git2::build::CheckoutBuilder;
+use git2::{
+    Commit, DiffOptions, ErrorCode, FileMode, IndexAddOption, IndexMatchedPath, ObjectType, Oid,
+    Repository, TreeWalkMode, TreeWalkResult,
+};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3_async_runtimes::tokio::future_into_py;
+#[cfg(not(feature = "stubgen"))]
+use pyo3_stub_gen::derive::remove_gen_stub;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf, absolute};
 use std::sync::{Arc, Mutex, MutexGuard};
@@ -17,17 +38,47 @@ pub type RepoEntry = Arc<Mutex<Repository>>;
 /// This class manages the shadow repository and provides methods for
 /// saving checkpoints, rolling back files, and retrieving commit history.
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
-#[pyclass]
+#[pyclass(skip_from_py_object)]
+#[derive(Clone)]
 pub struct CheckPointStore {
     #[pyo3(get)]
     /// The worktree directory being tracked.
     pub(crate) workspace: PathBuf,
     repo: RepoEntry,
+    hooks: HooksEntry,
+    /// Files larger than this many bytes are recorded as placeholders instead of blobs.
+    max_file_size: Option<u64>,
+    /// Where the content of the files recorded as placeholders is kept, if anywhere.
+    skipped_content: Option<SkippedContent>,
+    /// Ignore patterns applying on top of the `.checkpointignore` file of the workspace.
+    ignore: Arc<[String]>,
+    /// Whether the ignore patterns of the project at the root of the workspace apply.
+    project_ignore: bool,
+    /// The checkpoints kept when pruning automatically after saving.
+    retention: Retention,
 }
 
 impl CheckPointStore {
-    pub(crate) fn new(workspace: PathBuf, repo: RepoEntry) -> Self {
-        Self { workspace, repo }
+    pub(crate) fn new(
+        workspace: PathBuf,
+        repo: RepoEntry,
+        hooks: HooksEntry,
+        max_file_size: Option<u64>,
+        skipped_content: Option<SkippedContent>,
+        ignore: Arc<[String]>,
+        project_ignore: bool,
+        retention: Retention,
+    ) -> Self {
+        Self {
+            workspace,
+            repo,
+            hooks,
+            max_file_size,
+            skipped_content,
+            ignore,
+            project_ignore,
+            retention,
+        }
     }
 
     pub(crate) fn add_init_commit(&self) -> Result<&Self, PyErr> {
@@ -53,6 +104,24 @@ impl CheckPointStore {
         Ok(self)
     }
 
+    /// Reads the placeholders of the files skipped by a commit.
+    fn skipped_at(repo: &Repository, oid: Oid) -> PyResult<Vec<SkippedFile>> {
+        match repo.find_note(Some(SKIPPED_NOTES_REF), oid) {
+            Ok(note) => Ok(SkippedFile::decode(note.message().unwrap_or_default())),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e).into_pyresult(),
+        }
+    }
+
+    /// References the content of the files the notes of the repository list as skipped, if
+    /// it is kept.
+    fn sync_skipped_content(&self, repo: &Repository) -> PyResult<()> {
+        match &self.skipped_content {
+            Some(content) => content.sync(repo),
+            None => Ok(()),
+        }
+    }
+
     #[inline]
     fn access_repo(&self) -> PyResult<MutexGuard<'_, Repository>> {
         self.repo.lock().into_pyresult()
@@ -62,33 +131,216 @@ impl CheckPointStore {
     fn norm_repo_rel_path<P: AsRef<Path>>(&self, file_path: P) -> PyResult<PathBuf> {
         normalized_rel_path(&self.workspace, file_path.as_ref().to_path_buf())
     }
-}
 
-#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
-#[pymethods]
-impl CheckPointStore {
-    /// Saves the current state of the worktree as a new commit.
+    /// Builds diff options limited to a file or directory of the worktree, if one is given.
+    fn diff_options(&self, path: Option<PathBuf>) -> PyResult<DiffOptions> {
+        let mut opts = DiffOptions::new();
+        if let Some(path) = path {
+            let rel_path = self.norm_repo_rel_path(path)?;
+            if rel_path.components().next().is_some() {
+                opts.pathspec(rel_path);
+            }
+        }
+        Ok(opts)
+    }
+
+    /// Checks that checking out a directory from a commit stays inside the workspace.
     ///
-    /// This method stages all changes in the worktree directory and creates a new commit
-    /// in the shadow repository. It acts as a checkpoint that can later be restored.
+    /// Every file the commit or HEAD holds below the directory is resolved with symlinks
+    /// followed: the directory it lives in, which the checkout writes and deletes files in,
+    /// and the file itself unless it is a symlink, which the checkout replaces rather than
+    /// writes through.
     ///
-    /// Args:
-    ///     commit_msg: Optional commit message; defaults to empty string if not provided.
+    /// Raises:
+    ///     PermissionError: If a path resolves outside the workspace.
+    fn confine_checkout(&self, commits: &[&Commit], rel_dir: &Path) -> PyResult<()> {
+        confined_path(&self.workspace, rel_dir)?;
+        let mut files = Vec::new();
+        for commit in commits {
+            let entry = match commit.tree().into_pyresult()?.get_path(rel_dir) {
+                Ok(entry) => entry,
+                Err(e) if e.code() == ErrorCode::NotFound => continue,
+                Err(e) => return Err(e).into_pyresult(),
+            };
+            if entry.kind() != Some(ObjectType::Tree) {
+                files.push((rel_dir.to_path_buf(), entry.filemode()));
+                continue;
+            }
+            let subtree = entry
+                .to_object(commit.owner())
+                .into_pyresult()?
+                .peel_to_tree()
+                .into_pyresult()?;
+            subtree
+                .walk(TreeWalkMode::PreOrder, |dir, entry| {
+                    if entry.kind() == Some(ObjectType::Blob)
+                        && let Some(name) = entry.name()
+                    {
+                        files.push((rel_dir.join(dir).join(name), entry.filemode()));
+                    }
+                    TreeWalkResult::Ok
+                })
+                .into_pyresult()?;
+        }
+        for (path, filemode) in files {
+            let target = if filemode == i32::from(FileMode::Link) {
+                path.parent().unwrap_or(rel_dir)
+            } else {
+                &path
+            };
+            confined_path(&self.workspace, target)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the patch of each file changed between the trees of two commits.
+    fn tree_patches(
+        &self,
+        commit_a: &str,
+        commit_b: &str,
+        path: Option<PathBuf>,
+    ) -> PyResult<Vec<(String, String)>> {
+        let mut opts = self.diff_options(path)?;
+        let repo = self.access_repo()?;
+        let tree_of = |commit_id: &str| {
+            repo.find_commit(Oid::from_str(commit_id).into_pyresult()?)
+                .and_then(|commit| commit.tree())
+                .into_pyresult()
+        };
+        let diff = repo
+            .diff_tree_to_tree(
+                Some(&tree_of(commit_a)?),
+                Some(&tree_of(commit_b)?),
+                Some(&mut opts),
+            )
+            .into_pyresult()?;
+        patches_of(&diff, |_| true)
+    }
+
+    #[inline]
+    fn join_patches(patches: Vec<(String, String)>) -> String {
+        patches.into_iter().map(|(_, patch)| patch).collect()
+    }
+
+    /// Drops the checkpoints `keep` rejects, given their index from the latest one and their
+    /// time, then repacks the repository.
+    fn prune_with(&self, keep: impl Fn(usize, i64) -> bool) -> PyResult<PruneReport> {
+        let mut repo = self.access_repo()?;
+        let objects = repo.path().join("objects");
+        let before = dir_size(&objects);
+        let (kept, dropped) = rewrite_history(&repo, keep)?;
+        self.sync_skipped_content(&repo)?;
+        repack(&mut repo)?;
+        Ok(PruneReport {
+            kept,
+            dropped,
+            reclaimed_bytes: before.saturating_sub(dir_size(&objects)),
+        })
+    }
+
+    /// Prunes the history once it is far enough past the retention limits, returning whether
+    /// it did.
+    fn auto_prune(&self) -> PyResult<bool> {
+        if self.retention.is_none() {
+            return Ok(false);
+        }
+        let due = {
+            let repo = self.access_repo()?;
+            let checkpoints = checkpoints_of(&repo)?;
+            self.retention.is_due(
+                checkpoints.len(),
+                checkpoints.last().map(|commit| commit.time().seconds()),
+            )
+        };
+        if due {
+            let report = self.prune_with(|index, time| self.retention.keeps(index, time))?;
+            debug!(
+                "Pruned {} checkpoints of {}, reclaiming {} bytes",
+                report.dropped,
+                self.workspace.display(),
+                report.reclaimed_bytes
+            );
+        }
+        Ok(due)
+    }
+
+    /// Runs the hooks around committing the worktree, then prunes the history if due.
     ///
-    /// Returns:
-    ///     The commit ID (OID) as a string.
+    /// Meant to run without the GIL, hooks calling into Python acquire it themselves.
+    fn save_inner(&self, commit_msg: Option<String>) -> PyResult<String> {
+        self.hooks.run(HookPoint::PreSave, &self.workspace, None)?;
+        let commit_id = self.commit_all(commit_msg)?;
+        self.hooks
+            .run(HookPoint::PostSave, &self.workspace, Some(&commit_id))?;
+        if self.auto_prune()? {
+            // Pruning rebuilds the history, so the new checkpoint has a new ID.
+            return self.head();
+        }
+        Ok(commit_id)
+    }
+
+    /// Writes the checkpoint history to a bundle file, returning the ID of its HEAD commit.
+    pub(crate) fn export_bundle(&self, path: &Path) -> PyResult<String> {
+        write_bundle(&self.access_repo()?, path)
+    }
+
+    /// Replaces the checkpoint history with that of a bundle file, returning the ID of its
+    /// HEAD commit.
+    pub(crate) fn import_bundle(&self, path: &Path) -> PyResult<String> {
+        let repo = self.access_repo()?;
+        let head = read_bundle(&repo, path)?;
+        self.sync_skipped_content(&repo)?;
+        Ok(head)
+    }
+
+    /// Stages all changes and commits them, returning the new commit ID or HEAD if nothing changed.
     ///
-    /// Note:
-    ///     If there are no changes to commit, this method returns the ID of the last commit (the HEAD).
-    #[pyo3(signature=( commit_msg=None))]
-    pub fn save(&self, commit_msg: Option<String>) -> PyResult<String> {
+    /// Ignored files are left out of the tree. Files over the size limit are left out too and
+    /// listed in a note of the commit, their content going to the content store if one is set.
+    fn commit_all(&self, commit_msg: Option<String>) -> PyResult<String> {
+        let rules = ignore_rules(&self.workspace, &self.ignore, self.project_ignore)?;
         let repo = self.access_repo()?;
         let mut index = repo.index().into_pyresult()?;
         let sig = repo.signature().into_pyresult()?;
-        index.update_all(["*"].iter(), None).into_pyresult()?;
+
+        let mut oversized = BTreeMap::new();
+        let mut skip_excluded = |path: &Path, _: &[u8]| -> i32 {
+            if is_ignored(&rules, path, false) {
+                return 1;
+            }
+            match (self.max_file_size, fs::metadata(self.workspace.join(path))) {
+                (Some(max), Ok(meta)) if meta.is_file() && meta.len() > max => {
+                    oversized.insert(path.to_path_buf(), meta.len());
+                    1
+                }
+                _ => 0,
+            }
+        };
+        index
+            .update_all(
+                ["*"].iter(),
+                Some(&mut skip_excluded as &mut IndexMatchedPath),
+            )
+            .into_pyresult()?;
         index
-            .add_all(["*"].iter(), IndexAddOption::default(), None)
+            .add_all(
+                ["*"].iter(),
+                IndexAddOption::default(),
+                Some(&mut skip_excluded as &mut IndexMatchedPath),
+            )
             .into_pyresult()?;
+        // Files tracked before they were ignored or went over the limit keep a stale entry
+        // otherwise, unchanged files are not even passed to the callback.
+        let stale = index
+            .iter()
+            .filter_map(|entry| {
+                let path = PathBuf::from(String::from_utf8(entry.path).ok()?);
+                (oversized.contains_key(&path) || is_ignored(&rules, &path, false)).then_some(path)
+            })
+            .collect::<Vec<_>>();
+        for path in stale {
+            index.remove_path(&path).into_pyresult()?;
+        }
 
         let head_commit = head_commit_of(&repo)?;
         let tree = {
@@ -101,18 +353,96 @@ impl CheckPointStore {
             Ok(head_commit.id().to_string())
         } else {
             debug!("Committing changes to {}...", self.workspace.display());
-            repo.commit(
-                Some(HEAD_NAME),
-                &sig,
-                &sig,
-                commit_msg.unwrap_or_default().as_str(),
-                &tree,
-                &[&head_commit],
-            )
-            .into_pyresult()
-            .map(|oid| oid.to_string())
+            let oid = repo
+                .commit(
+                    Some(HEAD_NAME),
+                    &sig,
+                    &sig,
+                    commit_msg.unwrap_or_default().as_str(),
+                    &tree,
+                    &[&head_commit],
+                )
+                .into_pyresult()?;
+            if !oversized.is_empty() {
+                debug!("Skipped {} oversized files", oversized.len());
+                let skipped = oversized
+                    .iter()
+                    .map(|(path, size)| match &self.skipped_content {
+                        Some(content) => content.put(&self.workspace, path, *size),
+                        None => SkippedFile::of(&self.workspace, path, *size),
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                repo.note(
+                    &sig,
+                    &sig,
+                    Some(SKIPPED_NOTES_REF),
+                    oid,
+                    &SkippedFile::encode(&skipped),
+                    true,
+                )
+                .into_pyresult()?;
+                self.sync_skipped_content(&repo)?;
+            }
+            Ok(oid.to_string())
         }
     }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[cfg_attr(not(feature = "stubgen"), remove_gen_stub)]
+#[pymethods]
+impl CheckPointStore {
+    /// Saves the current state of the worktree as a new commit.
+    ///
+    /// This method stages all changes in the worktree directory and creates a new commit
+    /// in the shadow repository. It acts as a checkpoint that can later be restored.
+    ///
+    /// Args:
+    ///     commit_msg: Optional commit message; defaults to empty string if not provided.
+    ///
+    /// Returns:
+    ///     The commit ID (OID) as a string.
+    ///
+    /// Raises:
+    ///     Exception: If a fatal `pre_save` or `post_save` hook fails. A failing `pre_save`
+    ///         hook aborts the save.
+    ///
+    /// Note:
+    ///     If there are no changes to commit, this method returns the ID of the last commit (the HEAD).
+    ///     With a retention policy on the service, old checkpoints may be pruned afterwards.
+    ///     The GIL is released while the worktree is staged and committed.
+    #[pyo3(signature=( commit_msg=None))]
+    pub fn save(&self, python: Python<'_>, commit_msg: Option<String>) -> PyResult<String> {
+        python.detach(|| self.save_inner(commit_msg))
+    }
+
+    #[gen_stub(
+        override_return_type(type_repr = "typing.Awaitable[builtins.str]", imports = ("typing", "builtins"))
+    )]
+    /// Saves the current state of the worktree as a new commit, without blocking the event loop.
+    ///
+    /// Same as `save`, but the work runs on a background thread and the returned awaitable
+    /// resolves once the checkpoint is committed, so an agent loop keeps running while a
+    /// large workspace is staged.
+    ///
+    /// Args:
+    ///     commit_msg: Optional commit message; defaults to empty string if not provided.
+    ///
+    /// Returns:
+    ///     An awaitable resolving to the commit ID (OID) as a string.
+    #[pyo3(signature=( commit_msg=None))]
+    pub fn save_async<'a>(
+        &self,
+        python: Python<'a>,
+        commit_msg: Option<String>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let store = self.clone();
+        future_into_py(python, async move {
+            tokio::task::spawn_blocking(move || store.save_inner(commit_msg))
+                .await
+                .map_err(|e| PyRuntimeError::new_err(format!("Saving failed: {e}")))?
+        })
+    }
 
     /// Retrieves the ID of the current HEAD commit.
     ///
@@ -151,18 +481,158 @@ impl CheckPointStore {
     ///
     /// Args:
     ///     commit_id: The commit ID (OID as string) to reset to.
+    ///
+    /// Raises:
+    ///     Exception: If a fatal `pre_reset` or `post_reset` hook fails. A failing `pre_reset`
+    ///         hook aborts the reset.
     pub fn reset(&self, commit_id: String) -> PyResult<()> {
+        self.hooks
+            .run(HookPoint::PreReset, &self.workspace, Some(&commit_id))?;
         debug!(
             "Resetting workspace {} to commit {}...",
             self.workspace.display(),
             commit_id
         );
+        {
+            let repo = self.access_repo()?;
+            let oid = Oid::from_str(&commit_id).into_pyresult()?;
+            let commit = repo.find_commit(oid).into_pyresult()?;
+            // Skipped files are absent from the target tree, so the reset would delete those a
+            // later checkpoint stored. They are moved aside until it is done.
+            let mut kept = Vec::new();
+            for skipped in Self::skipped_at(&repo, oid)? {
+                let path = self.workspace.join(&skipped.path);
+                if path.is_file() {
+                    let mut aside = path.clone().into_os_string();
+                    aside.push(".checkpoint-keep");
+                    fs::rename(&path, &aside).into_pyresult()?;
+                    kept.push((path, aside));
+                }
+            }
+            let reset = repo.reset(&commit.into_object(), git2::ResetType::Hard, None);
+            for (path, aside) in kept {
+                fs::rename(aside, path).into_pyresult()?;
+            }
+            reset.into_pyresult()?;
+        }
+        self.hooks
+            .run(HookPoint::PostReset, &self.workspace, Some(&commit_id))
+    }
+
+    /// Saves a checkpoint and tags it with a name, to mark a milestone such as
+    /// `before-refactor` instead of keeping track of its commit ID.
+    ///
+    /// If nothing changed since the last checkpoint, the HEAD commit is tagged.
+    ///
+    /// Args:
+    ///     name: The name of the tag, a valid git reference name.
+    ///     message: Optional message of the checkpoint and of the tag.
+    ///     metadata: Optional JSON-serializable data kept along with the tag.
+    ///     force: Moves the tag if it already exists instead of failing.
+    ///
+    /// Returns:
+    ///     The commit ID (OID) of the tagged checkpoint as a string.
+    ///
+    /// Raises:
+    ///     ValueError: If the name is invalid, or already taken and `force` is not set.
+    #[pyo3(signature = (name, message = None, metadata = None, force = false))]
+    pub fn save_tagged(
+        &self,
+        python: Python<'_>,
+        name: String,
+        message: Option<String>,
+        metadata: Option<Bound<'_, PyDict>>,
+        force: bool,
+    ) -> PyResult<String> {
+        let metadata: Value = match metadata {
+            Some(metadata) => pythonize::depythonize(&metadata).into_pyresult()?,
+            None => Value::Object(Default::default()),
+        };
+        // Checked before saving, so that a taken name does not leave an untagged checkpoint.
+        if !force
+            && self
+                .access_repo()?
+                .find_reference(&tag_ref_name(&name)?)
+                .is_ok()
+        {
+            return Err(PyValueError::new_err(format!(
+                "Tag `{name}` already exists"
+            )));
+        }
+        let commit_id = python.detach(|| self.save_inner(message.clone()))?;
         let repo = self.access_repo()?;
-        let commit = repo
-            .find_commit(Oid::from_str(&commit_id).into_pyresult()?)
-            .into_pyresult()?;
-        repo.reset(&commit.into_object(), git2::ResetType::Hard, None)
-            .into_pyresult()
+        create_tag(
+            &repo,
+            &name,
+            Oid::from_str(&commit_id).into_pyresult()?,
+            &message.unwrap_or_default(),
+            metadata,
+            force,
+        )?;
+        Ok(commit_id)
+    }
+
+    /// Lists the tagged checkpoints.
+    ///
+    /// Returns:
+    ///     The tags with their message and metadata, the oldest checkpoint first.
+    pub fn list_tags(&self) -> PyResult<Vec<CheckpointTag>> {
+        list_tags(&self.access_repo()?)
+    }
+
+    /// Resets the worktree to a tagged checkpoint, as `reset` does with its commit ID.
+    ///
+    /// Args:
+    ///     name: The name of the tag.
+    ///
+    /// Returns:
+    ///     The commit ID (OID) of the tagged checkpoint as a string.
+    ///
+    /// Raises:
+    ///     KeyError: If no checkpoint has this tag.
+    pub fn restore_tag(&self, name: String) -> PyResult<String> {
+        let commit_id = tag_target(&self.access_repo()?, &name)?.to_string();
+        self.reset(commit_id.clone())?;
+        Ok(commit_id)
+    }
+
+    /// Drops all checkpoints but the latest ones and reclaims the space they used.
+    ///
+    /// The history is rebuilt with the kept checkpoints, which therefore get new IDs, while
+    /// their content, messages, notes and tags are preserved. Tagged checkpoints are always
+    /// kept. Objects no checkpoint refers to anymore, including those of checkpoints a reset
+    /// left behind, are deleted.
+    ///
+    /// Args:
+    ///     keep_last: The number of latest checkpoints to keep; the latest one is always kept.
+    ///
+    /// Returns:
+    ///     The numbers of kept and dropped checkpoints and of reclaimed bytes.
+    pub fn prune(&self, keep_last: usize) -> PyResult<PruneReport> {
+        self.prune_with(|index, _| index < keep_last)
+    }
+
+    /// Drops the checkpoints older than a number of days and reclaims the space they used.
+    ///
+    /// Works as `prune`, the latest and the tagged checkpoints are kept whatever their age.
+    ///
+    /// Args:
+    ///     days: The age in days over which checkpoints are dropped, fractions allowed.
+    ///
+    /// Returns:
+    ///     The numbers of kept and dropped checkpoints and of reclaimed bytes.
+    ///
+    /// Raises:
+    ///     ValueError: If `days` is negative.
+    pub fn prune_older_than(&self, days: f64) -> PyResult<PruneReport> {
+        if days.is_nan() || days < 0.0 {
+            return Err(PyValueError::new_err("days must not be negative"));
+        }
+        let retention = Retention {
+            keep_last: None,
+            max_age_days: Some(days),
+        };
+        self.prune_with(|index, time| retention.keeps(index, time))
     }
 
     /// Restores a specific file from a commit.
@@ -173,9 +643,14 @@ impl CheckPointStore {
     /// Args:
     ///     commit_id: The commit ID (OID as string) to restore from.
     ///     file_path: The relative path to the file within the worktree.
+    ///
+    /// Raises:
+    ///     PermissionError: If the file resolves outside the worktree, such as through a symlink.
     pub fn rollback(&self, commit_id: String, file_path: PathBuf) -> PyResult<()> {
         let file_path = absolute(&file_path).into_pyresult()?;
         let norm_file_path = self.norm_repo_rel_path(&file_path)?;
+        // Following symlinks, as writing through one could land outside the workspace.
+        let target = confined_path(&self.workspace, &norm_file_path)?;
         let repo = self.access_repo()?;
         let commit = repo
             .find_commit(Oid::from_str(&commit_id).into_pyresult()?)
@@ -193,7 +668,48 @@ impl CheckPointStore {
             .into_pyresult()?;
 
         let blob = repo.find_blob(file_obj.id()).into_pyresult()?;
-        fs::write(file_path, blob.content()).into_pyresult()
+        fs::write(target, blob.content()).into_pyresult()
+    }
+
+    /// Restores a directory and everything below it from a commit, leaving the rest of the
+    /// worktree untouched.
+    ///
+    /// Files of the directory are brought back to their content at the commit, and files the
+    /// latest checkpoint stored there that the commit lacks are deleted. Files never saved in
+    /// a checkpoint, such as ignored or oversized ones, are left alone.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) to restore from.
+    ///     dir_path: The directory, relative to the worktree or absolute.
+    ///
+    /// Raises:
+    ///     ValueError: If the directory is the worktree itself, use `reset` instead.
+    ///     PermissionError: If a file to restore or delete resolves outside the worktree, such as
+    ///         through a symlink.
+    pub fn rollback_tree(&self, commit_id: String, dir_path: PathBuf) -> PyResult<()> {
+        let rel_dir = self.norm_repo_rel_path(&dir_path)?;
+        if rel_dir.components().next().is_none() {
+            return Err(PyValueError::new_err(
+                "Cannot roll back the whole worktree, use `reset` instead",
+            ));
+        }
+        let repo = self.access_repo()?;
+        let commit = repo
+            .find_commit(Oid::from_str(&commit_id).into_pyresult()?)
+            .into_pyresult()?;
+
+        debug!(
+            "Rolling back directory {} to commit {}",
+            rel_dir.display(),
+            commit_id
+        );
+        self.confine_checkout(&[&commit, &head_commit_of(&repo)?], &rel_dir)?;
+        // The baseline of a checkout is HEAD, so files HEAD holds below the directory and the
+        // commit lacks are removed, while untracked ones stay.
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().path(&rel_dir);
+        repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+            .into_pyresult()
     }
 
     /// Retrieves the diff for a specific file at a given commit.
@@ -235,15 +751,294 @@ impl CheckPointStore {
         let diff = repo
             .diff_tree_to_tree(Some(&parent_tree_obj), Some(&file_obj), Some(&mut opts))
             .into_pyresult()?;
-        let mut ret = String::new();
+        Ok(Self::join_patches(patches_of(&diff, |_| true)?))
+    }
+
+    /// Retrieves the patch going from one commit to another.
+    ///
+    /// Unlike `get_file_diff`, the commits need not be consecutive, the changes of every commit
+    /// in between are aggregated.
+    ///
+    /// Args:
+    ///     commit_a: The commit ID (OID as string) of the older state.
+    ///     commit_b: The commit ID (OID as string) of the newer state.
+    ///     path: Optional file or directory, relative to the worktree or absolute, to limit
+    ///         the patch to.
+    ///
+    /// Returns:
+    ///     A string containing the unified diff of all changed files in patch format.
+    #[pyo3(signature = (commit_a, commit_b, path = None))]
+    pub fn diff(
+        &self,
+        commit_a: String,
+        commit_b: String,
+        path: Option<PathBuf>,
+    ) -> PyResult<String> {
+        Ok(Self::join_patches(
+            self.tree_patches(&commit_a, &commit_b, path)?,
+        ))
+    }
+
+    /// Retrieves the patch of each file changed from one commit to another.
+    ///
+    /// Args:
+    ///     commit_a: The commit ID (OID as string) of the older state.
+    ///     commit_b: The commit ID (OID as string) of the newer state.
+    ///     path: Optional file or directory, relative to the worktree or absolute, to limit
+    ///         the patches to.
+    ///
+    /// Returns:
+    ///     A dictionary mapping the path of each changed file, relative to the worktree, to
+    ///     its unified diff in patch format.
+    #[pyo3(signature = (commit_a, commit_b, path = None))]
+    pub fn diff_by_file(
+        &self,
+        commit_a: String,
+        commit_b: String,
+        path: Option<PathBuf>,
+    ) -> PyResult<BTreeMap<String, String>> {
+        Ok(self
+            .tree_patches(&commit_a, &commit_b, path)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Retrieves the patch of the changes made in the worktree since a commit, those not
+    /// saved yet when the commit is HEAD.
+    ///
+    /// New files are included in full. Files the ignore rules or the size limit keep out of
+    /// checkpoints are left out.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) to compare to; defaults to HEAD.
+    ///     path: Optional file or directory, relative to the worktree or absolute, to limit
+    ///         the patch to.
+    ///
+    /// Returns:
+    ///     A string containing the unified diff in patch format, empty if the worktree
+    ///     matches the commit.
+    #[pyo3(signature = (commit_id = None, path = None))]
+    pub fn diff_workdir(
+        &self,
+        commit_id: Option<String>,
+        path: Option<PathBuf>,
+    ) -> PyResult<String> {
+        let rules = ignore_rules(&self.workspace, &self.ignore, self.project_ignore)?;
+        let mut opts = self.diff_options(path)?;
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let repo = self.access_repo()?;
+        let commit = match commit_id {
+            Some(commit_id) => repo
+                .find_commit(Oid::from_str(&commit_id).into_pyresult()?)
+                .into_pyresult()?,
+            None => head_commit_of(&repo)?,
+        };
+        let diff = repo
+            .diff_tree_to_workdir(Some(&commit.tree().into_pyresult()?), Some(&mut opts))
+            .into_pyresult()?;
+        let patches = patches_of(&diff, |path| {
+            !is_ignored(&rules, Path::new(path), false)
+                && self.max_file_size.is_none_or(|max_size| {
+                    // Deleted files have no size and are always shown.
+                    fs::metadata(self.workspace.join(path)).map_or(0, |meta| meta.len()) <= max_size
+                })
+        })?;
+        Ok(Self::join_patches(patches))
+    }
+
+    /// Lists the files a commit changed compared to its parent.
+    ///
+    /// The first checkpoint is compared to an empty tree, so all of its files are added.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) to inspect.
+    ///
+    /// Returns:
+    ///     The changed files with the kind of their change, sorted by path.
+    pub fn get_changed_files(&self, commit_id: String) -> PyResult<Vec<FileChange>> {
+        let repo = self.access_repo()?;
+        let commit = repo
+            .find_commit(Oid::from_str(&commit_id).into_pyresult()?)
+            .into_pyresult()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().into_pyresult()?),
+            Err(_) => None,
+        };
+        let diff = repo
+            .diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&commit.tree().into_pyresult()?),
+                None,
+            )
+            .into_pyresult()?;
+        Ok(FileChange::of_diff(&diff))
+    }
+
+    /// Lists the files changed from one commit to another, the changes of every commit in
+    /// between aggregated.
+    ///
+    /// A file added then deleted in between does not appear, and one deleted then restored
+    /// only does if its content differs.
+    ///
+    /// Args:
+    ///     commit_a: The commit ID (OID as string) of the older state.
+    ///     commit_b: The commit ID (OID as string) of the newer state.
+    ///
+    /// Returns:
+    ///     The changed files with the kind of their change going from `commit_a` to
+    ///     `commit_b`, sorted by path.
+    pub fn changes_between(&self, commit_a: String, commit_b: String) -> PyResult<Vec<FileChange>> {
+        let repo = self.access_repo()?;
+        let tree_of = |commit_id: &str| {
+            repo.find_commit(Oid::from_str(commit_id).into_pyresult()?)
+                .and_then(|commit| commit.tree())
+                .into_pyresult()
+        };
+        let diff = repo
+            .diff_tree_to_tree(Some(&tree_of(&commit_a)?), Some(&tree_of(&commit_b)?), None)
+            .into_pyresult()?;
+        Ok(FileChange::of_diff(&diff))
+    }
+
+    /// Searches the lines the checkpoints added or removed, like `git log -G`.
+    ///
+    /// Useful to find when a function appeared or disappeared without exporting the
+    /// repository. The GIL is released during the search.
+    ///
+    /// Args:
+    ///     pattern: The regular expression the lines are matched against.
+    ///     path_glob: Optional glob the searched files must match, relative to the
+    ///         workspace, such as `src/**/*.py`.
+    ///
+    /// Returns:
+    ///     The matching lines, the latest checkpoint first.
+    ///
+    /// Raises:
+    ///     ValueError: If the pattern is not a valid regular expression.
+    #[pyo3(signature = (pattern, path_glob = None))]
+    pub fn search_history(
+        &self,
+        python: Python<'_>,
+        pattern: &str,
+        path_glob: Option<String>,
+    ) -> PyResult<Vec<SearchHit>> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| PyValueError::new_err(format!("Invalid pattern: {e}")))?;
+        python.detach(|| search_history(&self.access_repo()?, &pattern, path_glob.as_deref()))
+    }
 
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            ret.push_str(std::str::from_utf8(line.content()).unwrap_or("<invalid utf8>"));
-            true
+    /// Writes the files of a checkpoint to a tar archive, to hand off or keep a state of the
+    /// workspace without the shadow repository.
+    ///
+    /// The archive is gzipped if the output path ends with `.gz` or `.tgz`. Files left out of
+    /// the checkpoint for exceeding the size limit are not in it. The GIL is released while
+    /// the archive is written.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) of the checkpoint to export.
+    ///     output: The path of the archive to write, such as `state.tar.gz`.
+    ///
+    /// Returns:
+    ///     The number of files written to the archive.
+    pub fn export_checkpoint(
+        &self,
+        python: Python<'_>,
+        commit_id: String,
+        output: PathBuf,
+    ) -> PyResult<usize> {
+        let oid = Oid::from_str(&commit_id).into_pyresult()?;
+        python.detach(|| {
+            let repo = self.access_repo()?;
+            let commit = repo.find_commit(oid).into_pyresult()?;
+            write_archive(&commit, &output)
         })
-        .into_pyresult()?;
+    }
 
-        Ok(ret)
+    /// Lists the files left out of a checkpoint for exceeding the size limit.
+    ///
+    /// Such files are not restored by `reset` or `rollback`; their size and hash tell whether
+    /// the copy in the workspace still matches the one at the time of the checkpoint, and
+    /// `restore_skipped` brings them back if the service keeps their content.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) to inspect; defaults to HEAD.
+    ///
+    /// Returns:
+    ///     The placeholders of the skipped files, sorted by path.
+    #[pyo3(signature = (commit_id = None))]
+    pub fn skipped_files(&self, commit_id: Option<String>) -> PyResult<Vec<SkippedFile>> {
+        let repo = self.access_repo()?;
+        let oid = match commit_id {
+            Some(commit_id) => Oid::from_str(&commit_id).into_pyresult()?,
+            None => head_commit_of(&repo)?.id(),
+        };
+        Self::skipped_at(&repo, oid)
+    }
+
+    /// Restores the files left out of a checkpoint from the content store of the service.
+    ///
+    /// Files whose copy in the workspace already matches the placeholder are left alone, as
+    /// are those the store lacks the content of, such as files skipped before the store was
+    /// set up or listed by a bundle imported from another machine. The GIL is released while
+    /// the files are compared and written.
+    ///
+    /// Args:
+    ///     commit_id: The commit ID (OID as string) whose skipped files to restore; defaults
+    ///         to HEAD.
+    ///
+    /// Returns:
+    ///     The paths of the restored files, relative to the worktree.
+    ///
+    /// Raises:
+    ///     ValueError: If the service was created without a `skipped_store`.
+    ///     PermissionError: If a path resolves outside the worktree, such as through a symlink.
+    #[pyo3(signature = (commit_id = None))]
+    pub fn restore_skipped(
+        &self,
+        python: Python<'_>,
+        commit_id: Option<String>,
+    ) -> PyResult<Vec<String>> {
+        let Some(content) = &self.skipped_content else {
+            return Err(PyValueError::new_err(
+                "The content of skipped files is not kept, set `skipped_store` on the service",
+            ));
+        };
+        let skipped = self.skipped_files(commit_id)?;
+        python.detach(|| {
+            let mut restored = Vec::new();
+            for file in skipped {
+                if content.restore(&self.workspace, &file)? {
+                    restored.push(file.path);
+                }
+            }
+            Ok(restored)
+        })
+    }
+
+    /// Checks whether a file is kept out of checkpoints by the ignore rules.
+    ///
+    /// The rules are the patterns given to the service, those of the `.checkpointignore` file
+    /// at the root of the worktree and those of the project it holds, in the `.gitignore`
+    /// syntax.
+    ///
+    /// Args:
+    ///     file_path: The path to the file, relative to the worktree or absolute.
+    ///
+    /// Returns:
+    ///     True if the file or one of its parent directories matches an ignore pattern.
+    ///
+    /// Raises:
+    ///     PermissionError: If the path is not inside the worktree.
+    pub fn is_ignored(&self, file_path: PathBuf) -> PyResult<bool> {
+        let rel_path = self.norm_repo_rel_path(&file_path)?;
+        let rules = ignore_rules(&self.workspace, &self.ignore, self.project_ignore)?;
+        Ok(is_ignored(
+            &rules,
+            &rel_path,
+            self.workspace.join(&rel_path).is_dir(),
+        ))
     }
 
     /// Retrieves the status of the worktree.
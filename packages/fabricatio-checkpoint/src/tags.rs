@@ -0,0 +1,178 @@
+//! Checkpoints tagged with a name, marking milestones such as `before-refactor`.
+//!
+//! A tag is a lightweight git tag under `TAG_REF_PREFIX` pointing at the commit of the
+//! checkpoint. Its message and metadata live in a git note of the commit under
+//! `TAG_NOTES_REF`, a JSON object mapping the name of each tag of the commit to an object
+//! with its `message` and `metadata`, so several tags can share a commit.
+
+use crate::constants::{TAG_NOTES_REF, TAG_REF_PREFIX};
+use error_mapping::AsPyErr;
+use fabricatio_logger::warn;
+use git2::{ErrorCode, Oid, Repository};
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use serde_json::{Map, Value, json};
+
+/// A checkpoint marked with a name.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct CheckpointTag {
+    /// The name of the tag.
+    #[pyo3(get)]
+    pub(crate) name: String,
+    /// The commit ID (OID as string) of the tagged checkpoint.
+    #[pyo3(get)]
+    pub(crate) commit_id: String,
+    /// The message given when tagging.
+    #[pyo3(get)]
+    pub(crate) message: String,
+    /// The time of the checkpoint, in seconds since the Unix epoch.
+    #[pyo3(get)]
+    pub(crate) timestamp: i64,
+    pub(crate) metadata: Value,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl CheckpointTag {
+    /// The metadata given when tagging.
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        pythonize::pythonize(py, &self.metadata).into_pyresult()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CheckpointTag(name={:?}, commit_id={:?}, message={:?})",
+            self.name, self.commit_id, self.message
+        )
+    }
+}
+
+/// The full reference name of a tag.
+///
+/// Raises:
+///     ValueError: If the name is not a valid git reference name.
+pub(crate) fn tag_ref_name(name: &str) -> PyResult<String> {
+    let ref_name = format!("{TAG_REF_PREFIX}{name}");
+    if name.is_empty() || !git2::Reference::is_valid_name(&ref_name) {
+        return Err(PyValueError::new_err(format!("Invalid tag name `{name}`")));
+    }
+    Ok(ref_name)
+}
+
+/// Reads the entries of the tags of a commit, an empty map if it has none.
+fn read_entries(repo: &Repository, oid: Oid) -> PyResult<Map<String, Value>> {
+    let note = match repo.find_note(Some(TAG_NOTES_REF), oid) {
+        Ok(note) => note,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(Map::new()),
+        Err(e) => return Err(e).into_pyresult(),
+    };
+    match serde_json::from_str(note.message().unwrap_or_default()) {
+        Ok(Value::Object(entries)) => Ok(entries),
+        _ => {
+            warn!("Malformed tag note on commit {oid}, ignoring it");
+            Ok(Map::new())
+        }
+    }
+}
+
+/// Writes the entries of the tags of a commit, removing the note once none is left.
+fn write_entries(repo: &Repository, oid: Oid, entries: Map<String, Value>) -> PyResult<()> {
+    let sig = repo.signature().into_pyresult()?;
+    if entries.is_empty() {
+        return match repo.note_delete(oid, Some(TAG_NOTES_REF), &sig, &sig) {
+            Err(e) if e.code() != ErrorCode::NotFound => Err(e).into_pyresult(),
+            _ => Ok(()),
+        };
+    }
+    repo.note(
+        &sig,
+        &sig,
+        Some(TAG_NOTES_REF),
+        oid,
+        &Value::Object(entries).to_string(),
+        true,
+    )
+    .into_pyresult()?;
+    Ok(())
+}
+
+/// The commit a tag points at.
+///
+/// Raises:
+///     KeyError: If no checkpoint has this tag.
+pub(crate) fn tag_target(repo: &Repository, name: &str) -> PyResult<Oid> {
+    repo.refname_to_id(&tag_ref_name(name)?)
+        .map_err(|_| PyKeyError::new_err(format!("No checkpoint is tagged `{name}`")))
+}
+
+/// Tags a commit, recording the message and metadata of the tag in the note of the commit.
+///
+/// Raises:
+///     ValueError: If the name is invalid, or already taken and `force` is not set.
+pub(crate) fn create_tag(
+    repo: &Repository,
+    name: &str,
+    oid: Oid,
+    message: &str,
+    metadata: Value,
+    force: bool,
+) -> PyResult<()> {
+    let ref_name = tag_ref_name(name)?;
+    if let Ok(previous) = repo.refname_to_id(&ref_name) {
+        if !force {
+            return Err(PyValueError::new_err(format!(
+                "Tag `{name}` already exists on commit {previous}"
+            )));
+        }
+        let mut entries = read_entries(repo, previous)?;
+        if entries.remove(name).is_some() {
+            write_entries(repo, previous, entries)?;
+        }
+    }
+    repo.reference(&ref_name, oid, true, "tag checkpoint")
+        .into_pyresult()?;
+    let mut entries = read_entries(repo, oid)?;
+    entries.insert(
+        name.to_string(),
+        json!({ "message": message, "metadata": metadata }),
+    );
+    write_entries(repo, oid, entries)
+}
+
+/// Lists the tags of a repository, the oldest checkpoint first and by name on ties.
+pub(crate) fn list_tags(repo: &Repository) -> PyResult<Vec<CheckpointTag>> {
+    let mut tags = Vec::new();
+    for reference in repo
+        .references_glob(&format!("{TAG_REF_PREFIX}*"))
+        .into_pyresult()?
+    {
+        let reference = reference.into_pyresult()?;
+        let (Some(name), Some(oid)) = (
+            reference
+                .name()
+                .and_then(|name| name.strip_prefix(TAG_REF_PREFIX)),
+            reference.target(),
+        ) else {
+            continue;
+        };
+        let commit = repo.find_commit(oid).into_pyresult()?;
+        let entry = read_entries(repo, oid)?.remove(name).unwrap_or(Value::Null);
+        tags.push(CheckpointTag {
+            name: name.to_string(),
+            commit_id: oid.to_string(),
+            message: entry["message"].as_str().unwrap_or_default().to_string(),
+            timestamp: commit.time().seconds(),
+            metadata: entry
+                .get("metadata")
+                .cloned()
+                .unwrap_or_else(|| Value::Object(Map::new())),
+        });
+    }
+    tags.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.name.cmp(&b.name)));
+    Ok(tags)
+}
@@ -1,3 +1,4 @@
+use crate::prune::dir_size;
 use blake3::hash;
 use error_mapping::AsPyErr;
 use fabricatio_logger::trace;
@@ -10,6 +11,7 @@ use std::fs;
 use std::fs::read_dir;
 use std::path::{Path, PathBuf, absolute};
 use std::sync::MutexGuard;
+use utils::workspace_path;
 
 #[inline]
 /// Gets the head commit from a repository.
@@ -45,14 +47,26 @@ pub(crate) fn normalized_path_of<P: AsRef<Path>>(path: P) -> PyResult<PathBuf> {
 ///
 /// Returns:
 ///     The relative path.
-pub(crate) fn normalized_rel_path(root: &PathBuf, path: PathBuf) -> PyResult<PathBuf> {
-    if path.is_relative() {
-        Ok(path)
-    } else {
-        path.strip_prefix(root)
-            .map(|p| p.to_path_buf())
-            .into_pyresult()
-    }
+///
+/// Raises:
+///     PermissionError: If the path is not below the root.
+pub(crate) fn normalized_rel_path(root: &Path, path: PathBuf) -> PyResult<PathBuf> {
+    Ok(workspace_path::relativize(root, &path)?)
+}
+
+/// Resolves a path relative to a root directory, following symlinks.
+///
+/// Args:
+///     root: The root directory.
+///     rel_path: The path relative to the root.
+///
+/// Returns:
+///     The absolute path, with its existing components resolved.
+///
+/// Raises:
+///     PermissionError: If the path resolves outside the root, such as through a symlink.
+pub(crate) fn confined_path(root: &Path, rel_path: &Path) -> PyResult<PathBuf> {
+    Ok(workspace_path::confine(root, rel_path)?)
 }
 
 /// Creates a shadow repository by initializing a bare repository and then configuring it
@@ -130,6 +144,16 @@ pub(crate) fn managed_workspaces(stores_root: &PathBuf) -> PyResult<Vec<PathBuf>
         .collect::<Vec<_>>())
 }
 
+/// Returns the managed workspace directories with the disk space taken by their store, in bytes.
+pub(crate) fn store_sizes(stores_root: &PathBuf) -> PyResult<Vec<(PathBuf, u64)>> {
+    Ok(dir_entries!(stores_root)
+        .filter_map(|entry| {
+            let workdir = Repository::open(&entry).ok()?.workdir()?.to_path_buf();
+            Some((workdir, dir_size(&entry)))
+        })
+        .collect::<Vec<_>>())
+}
+
 /// Removes all store repositories under the given root path whose working directories no longer exist.
 ///
 /// This function:
@@ -3,15 +3,18 @@
 import asyncio
 from asyncio import as_completed
 from asyncio.tasks import Task
-from collections import defaultdict
-from typing import TYPE_CHECKING, Callable, Coroutine, Dict, List, Self, Tuple
+from typing import TYPE_CHECKING, Callable, Coroutine, Dict, List, Optional, Self
 
-from fabricatio_core.rust import CONFIG
+from fabricatio_core.rust import CONFIG, EventBus, EventJournal, TopicTrie
 
 if TYPE_CHECKING:
     from fabricatio_core.models.task import Task as _Task
 
 WILDCARD = "*"
+"""The pattern segment matching exactly one segment of an event."""
+
+MULTI_WILDCARD = "**"
+"""The pattern segment matching any number of segments of an event, none included."""
 
 
 type Callback[T] = Callable[[T], Coroutine[None, None, None]]
@@ -21,30 +24,38 @@ type Callback[T] = Callable[[T], Coroutine[None, None, None]]
 class EventEmitter[T]:
     """An event emitter that supports both exact and wildcard event matching.
 
-    The emitter allows registering event handlers for specific events or patterns
-    containing wildcards (`*`). It can then emit events and invoke all matching handlers
-    concurrently.
+    The emitter allows registering event handlers for specific events or hierarchical
+    patterns, where a `*` segment matches one segment and a `**` segment any number of
+    them, e.g. `task::*::Finished` or `task::**`. It can then emit events and invoke all
+    matching handlers concurrently. Patterns are matched by a trie in Rust, so emitting
+    costs the same whatever the number of patterns registered.
     """
 
-    def __init__(self, sep: str = "::") -> None:
+    def __init__(self, sep: str = "::", bus: Optional[EventBus] = None, journal: Optional[EventJournal] = None) -> None:
         """Creates a new EventEmitter with the specified separator.
 
         Args:
             sep: The separator string used to split event names into segments.
                  Defaults to "::".
+            bus: The event bus every emitted event is published to as well, so that
+                 consumers can await events instead of registering handlers.
+            journal: The journal every emitted event is recorded to, so that a run can be
+                 replayed afterwards.
         """
         self.sep = sep
-        # Stores handlers for exact event matches (key: event name, value: list of callbacks)
-        self._handlers: Dict[str, List[Callback[T]]] = defaultdict(list)
-        # Stores handlers for wildcard event patterns (key: pattern tuple, value: list of callbacks)
-        self._wildcard_handlers: Dict[Tuple[str, ...], List[Callback[T]]] = defaultdict(list)
+        self.bus = bus
+        self.journal = journal
+        # Matches event names against the registered patterns, yielding subscription ids
+        self._topics = TopicTrie(sep)
+        # Stores the handler of each subscription (key: subscription id, value: callback)
+        self._handlers: Dict[int, Callback[T]] = {}
 
     def on(self, pattern: str, callback: Callback[T]) -> Self:
         """Registers an event handler for a specific pattern.
 
-        The pattern can be an exact event name or contain wildcards (`*`) to match
-        multiple events. The callback will be invoked whenever an event matching
-        the pattern is emitted.
+        The pattern can be an exact event name or contain wildcards (`*` for one
+        segment, `**` for any number of them) to match multiple events. The callback
+        will be invoked whenever an event matching the pattern is emitted.
 
         Args:
             pattern: The event pattern to register the handler for.
@@ -57,18 +68,13 @@ class EventEmitter[T]:
         if not pattern:
             raise ValueError("Pattern cannot be empty")
 
-        parts = pattern.split(self.sep)
-        if any(part == WILDCARD for part in parts):
-            # Use tuple as key for hashability
-            self._wildcard_handlers[tuple(parts)].append(callback)
-        else:
-            self._handlers[pattern].append(callback)
+        self._handlers[self._topics.subscribe(pattern)] = callback
         return self
 
     def off(self, pattern: str) -> Self:
-        """Removes an event handler for a specific pattern.
+        """Removes the event handlers for a specific pattern.
 
-        The pattern must match the pattern used when registering the handler.
+        The pattern must match the pattern used when registering the handlers.
 
         Args:
             pattern: The event pattern to remove the handler for.
@@ -79,32 +85,10 @@ class EventEmitter[T]:
         if not pattern:
             raise ValueError("Pattern cannot be empty")
 
-        parts = pattern.split(self.sep)
-        if any(part == WILDCARD for part in parts):
-            self._wildcard_handlers.pop(tuple(parts))
-        else:
-            self._handlers.pop(pattern)
+        for subscription in self._topics.unsubscribe(pattern):
+            del self._handlers[subscription]
         return self
 
-    def _gather_exact_handlers(self, event_parts: List[str]) -> List[Callback[T]]:
-        """Gathers all exact handlers that match the given event parts."""
-        event_name = self.sep.join(event_parts)
-        return self._handlers.get(event_name, [])
-
-    def _gather_wildcard_handlers(self, event_parts: List[str]) -> List[Callback[T]]:
-        """Gathers all wildcard handlers that match the given event parts."""
-        matching_handlers = []
-        event_tuple = tuple(event_parts)
-
-        for pattern_tuple, handlers in self._wildcard_handlers.items():
-            # Length must match
-            if len(pattern_tuple) == len(event_tuple) and all(
-                p_segment in (WILDCARD, e_segment)
-                for p_segment, e_segment in zip(pattern_tuple, event_tuple, strict=False)
-            ):
-                matching_handlers.extend(handlers)
-        return matching_handlers
-
     async def emit(self, event: str, data: T) -> None:
         """Emits an event with the given data to all matching handlers.
 
@@ -119,15 +103,15 @@ class EventEmitter[T]:
             The execution of the event handlers is concurrent, and this method
             will wait for all handlers to complete before returning.
         """
-        parts = event.split(self.sep)
-        callbacks: List[Callback[T]] = []
+        if self.journal is not None:
+            self.journal.record(event, data)
 
-        # Gather exact match handlers
-        callbacks.extend(self._gather_exact_handlers(parts))
+        # Queue the event to the subscriptions of the bus, waiting for room in blocking ones
+        if self.bus is not None and len(self.bus):
+            await self.bus.publish(event, data)
 
-        # Gather wildcard match handlers (only if there are parts to match against)
-        if len(parts) > 0:
-            callbacks.extend(self._gather_wildcard_handlers(parts))
+        # Gather the handlers of all matching patterns, in the order they were registered
+        callbacks: List[Callback[T]] = [self._handlers[subscription] for subscription in self._topics.matches(event)]
 
         # Run all gathered callbacks concurrently
         if callbacks:
@@ -151,5 +135,11 @@ class EventEmitter[T]:
         return asyncio.ensure_future(self.emit(event, data))
 
 
-EMITTER: EventEmitter["_Task"] = EventEmitter(sep=CONFIG.emitter.delimiter)
+EVENT_BUS = EventBus(CONFIG.emitter.delimiter)
+"""The global event bus, receiving every event of the global emitter."""
+
+EVENT_JOURNAL = EventJournal(CONFIG.emitter.journal, CONFIG.emitter.delimiter) if CONFIG.emitter.journal else None
+"""The global event journal, recording every event of the global emitter if `emitter.journal` is set."""
+
+EMITTER: EventEmitter["_Task"] = EventEmitter(sep=CONFIG.emitter.delimiter, bus=EVENT_BUS, journal=EVENT_JOURNAL)
 """The global event emitter instance."""
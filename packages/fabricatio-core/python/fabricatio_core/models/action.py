@@ -168,54 +168,58 @@ class WorkFlow(WithBriefing):
             2. Execute each action sequentially
             3. Handle task cancellation and exceptions
             4. Extract final result from context
+
+        Records logged meanwhile carry the `workflow` and `task` fields, which
+        `log_file_pattern` can name per-task log files by.
         """
-        logger.info(f"Start execute workflow: {self.name}")
-
-        await task.start()
-        await self._init_context(task)
-
-        current_action = None
-        try:
-            # Process each action in sequence
-            for i, step in enumerate(self._instances):
-                logger.info(f"Executing step [{i}] >> {(current_action := step.name)}")
-
-                # Get current context and execute action
-                context = await self._context.get()
-
-                self.override_action_variable(step, context)
-                act_task = create_task(step.act(context))
-                # Handle task cancellation
-                if task.is_cancelled():
-                    logger.warn(f"Workflow cancelled by task: {task.name}")
-                    act_task.cancel(f"Cancelled by task: {task.name}")
-                    break
-
-                # Update context with modified values
-                modified_ctx = await act_task
-                logger.info(f"Step [{i}] `{current_action}` execution finished.")
-                if step.output_key:
-                    logger.info(f"Setting action `{current_action}` output to `{step.output_key}`")
-                await self._context.put(modified_ctx)
-
-            logger.info(f"Workflow `{self.name}` execution finished.")
-
-            # Get final context and extract result
-            final_ctx = await self._context.get()
-            result = final_ctx.get(self.task_output_key)
-
-            if self.task_output_key not in final_ctx:
-                logger.warn(
-                    f"Task output key: `{self.task_output_key}` not found in the context, None will be returned. "
-                    f"You can check if `Action.output_key` is set the same as `WorkFlow.task_output_key`."
-                )
-
-            await task.finish(result)
-
-        except Exception as e:  # noqa: BLE001
-            logger.error(f"Error during task: {current_action} execution: {e}")
-            logger.error(traceback.format_exc())
-            await task.fail()
+        with logger.context(workflow=self.name, task=task.name):
+            logger.info(f"Start execute workflow: {self.name}")
+
+            await task.start()
+            await self._init_context(task)
+
+            current_action = None
+            try:
+                # Process each action in sequence
+                for i, step in enumerate(self._instances):
+                    logger.info(f"Executing step [{i}] >> {(current_action := step.name)}")
+
+                    # Get current context and execute action
+                    context = await self._context.get()
+
+                    self.override_action_variable(step, context)
+                    act_task = create_task(step.act(context))
+                    # Handle task cancellation
+                    if task.is_cancelled():
+                        logger.warn(f"Workflow cancelled by task: {task.name}")
+                        act_task.cancel(f"Cancelled by task: {task.name}")
+                        break
+
+                    # Update context with modified values
+                    modified_ctx = await act_task
+                    logger.info(f"Step [{i}] `{current_action}` execution finished.")
+                    if step.output_key:
+                        logger.info(f"Setting action `{current_action}` output to `{step.output_key}`")
+                    await self._context.put(modified_ctx)
+
+                logger.info(f"Workflow `{self.name}` execution finished.")
+
+                # Get final context and extract result
+                final_ctx = await self._context.get()
+                result = final_ctx.get(self.task_output_key)
+
+                if self.task_output_key not in final_ctx:
+                    logger.warn(
+                        f"Task output key: `{self.task_output_key}` not found in the context, None will be returned. "
+                        f"You can check if `Action.output_key` is set the same as `WorkFlow.task_output_key`."
+                    )
+
+                await task.finish(result)
+
+            except Exception as e:  # noqa: BLE001
+                logger.error(f"Error during task: {current_action} execution: {e}")
+                logger.error(traceback.format_exc())
+                await task.fail()
 
     async def _init_context[T](self, task: Task[T]) -> None:
         """Initialize workflow execution context.
@@ -26,34 +26,53 @@ __all__ = [
     "CodeSnippetParser",
     "Config",
     "ContentBlockParser",
+    "ContentStore",
     "DebugConfig",
+    "DependencyNode",
     "DeploymentConfig",
     "EmbeddingConfig",
     "EmitterConfig",
+    "EntryPoint",
     "Event",
+    "EventBus",
+    "EventJournal",
+    "EventSubscription",
+    "GcReport",
     "GeneralConfig",
     "GenericBlockParser",
+    "JournalEntry",
     "JsonParser",
     "LLMConfig",
+    "LogContext",
     "Logger",
+    "ProjectManifest",
     "ProviderConfig",
     "ProviderType",
     "RerankerConfig",
     "Router",
     "RouterUsage",
     "RoutingConfig",
+    "SecretReport",
     "SecretStr",
     "TaskStatus",
     "TemplateConfig",
     "TemplateManager",
     "TemplateManagerConfig",
+    "TemplateVariables",
     "TextCapturer",
+    "TopicTrie",
     "ValueType",
     "blake3_hash",
+    "conda_version",
+    "confine",
+    "dependency_tree",
     "detect_language",
+    "detect_project",
     "extra_satisfied",
     "extras_satisfied",
     "generic_parser",
+    "get_console_scripts",
+    "get_version",
     "is_arabic",
     "is_chinese",
     "is_dutch",
@@ -72,15 +91,25 @@ __all__ = [
     "is_turkish",
     "is_vietnamese",
     "json_parser",
+    "list_entry_points",
     "list_installed",
     "logger",
+    "long_path",
     "python_parser",
+    "refresh_installed",
+    "relativize",
+    "reverse_dependencies",
     "router_usage",
     "snippet_parser",
     "split_into_chunks",
+    "split_paragraphs",
     "split_sentence_bounds",
+    "split_sentences",
     "split_word_bounds",
     "tokens_of",
+    "unwatch_installed",
+    "version_satisfies",
+    "watch_installed",
     "word_count",
 ]
 
@@ -238,6 +267,18 @@ class Config:
         """
     def configure_llm_variant(self, kind: builtins.str, target: typing.Optional[builtins.str] = None) -> None:
         r"""Configure the LLM variant to use."""
+    def check_secrets(self, within: builtins.int = 300, rotate: builtins.bool = False) -> builtins.list[SecretReport]:
+        r"""Report the secrets expiring within the given number of seconds, or already expired.
+
+        Secrets get an expiry and a rotation command when configured as a table, e.g.
+        `key = { value = "sk-...", expires_at = 1767225600, rotate_cmd = "vault read ..." }`.
+        With `rotate`, the secrets having a rotation command are refreshed in place; providers
+        already registered in a router keep their key until re-registered, see
+        `Router.reload_providers`. A failed rotation is reported instead of raised, so one
+        broken command does not keep the other secrets from being refreshed, and a command
+        running for longer than 30 seconds is killed. The GIL is released while the commands
+        run.
+        """
     def load(self, name: str, config_cls: typing.Type[_T]) -> _T:
         r"""Load configuration data for a given section name and instantiate a Python class."""
 
@@ -272,6 +313,159 @@ class ContentBlockParser:
         Returns a vector of captured contents.
         """
 
+@typing.final
+class ContentStore:
+    r"""A content-addressed store of blobs, keyed by the BLAKE3 hash of their content.
+
+    Packages keeping copies of files, such as the media of anki decks, cached rag documents or
+    large files left out of checkpoints, store them here instead of each managing its own blob
+    directory. Storing the same content twice stores it once, and blobs are written to a
+    temporary file first, so a blob is never seen partially written.
+
+    Each package records the blobs it uses as references under its own owner name, which
+    `gc` keeps.
+    """
+    def __new__(cls, root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None) -> ContentStore:
+        r"""Opens the store rooted at a directory, creating it if needed.
+
+        Args:
+            root: The directory of the store. Defaults to the `cas` directory of the roaming
+                directory, shared by all packages.
+        """
+    @property
+    def root(self) -> pathlib.Path:
+        r"""The directory the store is rooted at."""
+    def put_bytes(self, content: bytes) -> builtins.str:
+        r"""Stores a blob.
+
+        Args:
+            content: The content of the blob.
+
+        Returns:
+            The hex encoded BLAKE3 hash of the content, which retrieves it.
+        """
+    def put_file(self, path: builtins.str | os.PathLike | pathlib.Path) -> builtins.str:
+        r"""Stores the content of a file, reading it in chunks.
+
+        Args:
+            path: The file to store.
+
+        Returns:
+            The hex encoded BLAKE3 hash of the content, the same `blake3_hash` gives.
+
+        Raises:
+            FileNotFoundError: If the file does not exist.
+        """
+    def get(self, hash: builtins.str) -> bytes:
+        r"""Reads a blob.
+
+        Args:
+            hash: The hash `put_bytes` or `put_file` returned.
+
+        Returns:
+            The content of the blob.
+
+        Raises:
+            FileNotFoundError: If the blob is not stored.
+            ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+        """
+    def path_of(self, hash: builtins.str) -> pathlib.Path:
+        r"""Returns the path of a blob, to hand it to tools reading files, whether it is stored
+        or not.
+
+        The file must not be modified, blobs being shared by every package using the store.
+
+        Raises:
+            ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+        """
+    def contains(self, hash: builtins.str) -> builtins.bool:
+        r"""Whether a blob is stored."""
+    def __contains__(self, hash: builtins.str) -> builtins.bool: ...
+    def link_to(self, hash: builtins.str, dest: builtins.str | os.PathLike | pathlib.Path) -> builtins.bool:
+        r"""Makes a blob appear at a path, as a hard link to it where the filesystem allows,
+        otherwise as a copy, to hand it out without duplicating it.
+
+        A link shares the read-only blob, so the file can be replaced but not modified in
+        place.
+
+        Args:
+            hash: The hash of the blob.
+            dest: The path to create, whose parent directory must exist.
+
+        Returns:
+            True if `dest` is a link to the blob, False if it is a copy.
+
+        Raises:
+            FileNotFoundError: If the blob is not stored.
+            FileExistsError: If `dest` already exists.
+            ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+        """
+    def references(self, owner: builtins.str) -> builtins.dict[builtins.str, builtins.str]:
+        r"""Returns the references an owner recorded, mapping their names to the hashes of blobs.
+
+        Args:
+            owner: The name the references are recorded under, usually that of a package.
+
+        Raises:
+            ValueError: If the owner is not made of letters, digits, `-`, `_` or `.`.
+        """
+    def reference(self, owner: builtins.str, name: builtins.str) -> typing.Optional[builtins.str]:
+        r"""Returns the hash of the blob a reference names, if the owner recorded it.
+
+        Raises:
+            ValueError: If the owner name is invalid.
+        """
+    def set_reference(self, owner: builtins.str, name: builtins.str, hash: builtins.str) -> None:
+        r"""Records a reference to a blob, replacing the one of the same name, so collections keep
+        the blob.
+
+        Args:
+            owner: The name the reference is recorded under, usually that of a package.
+            name: The name of the reference, on a single line.
+            hash: The hash of the blob.
+
+        Raises:
+            ValueError: If the owner, the name or the hash is invalid.
+        """
+    def set_references(self, owner: builtins.str, references: typing.Mapping[builtins.str, builtins.str]) -> None:
+        r"""Replaces all the references an owner recorded.
+
+        Args:
+            owner: The name the references are recorded under.
+            references: The hashes of the blobs the owner uses, by reference name.
+
+        Raises:
+            ValueError: If the owner, a name or a hash is invalid.
+        """
+    def remove_reference(self, owner: builtins.str, name: builtins.str) -> builtins.bool:
+        r"""Removes a reference an owner recorded.
+
+        Returns:
+            True if the reference existed.
+
+        Raises:
+            ValueError: If the owner name is invalid.
+        """
+    def gc(self, grace: builtins.float = 3600.0) -> GcReport:
+        r"""Removes the blobs no owner references and that were not stored or stored again within
+        the grace period, along with temporary files left over by interrupted writes.
+
+        Each package only manages the references of its own owner name, so a collection never
+        removes the blobs other packages still use. Writes and collections lock the store, so
+        they can run at the same time from any process.
+
+        Args:
+            grace: The age in seconds under which unreferenced blobs are kept, so that blobs
+                just stored survive until they get referenced.
+
+        Returns:
+            A `GcReport` of what was removed.
+
+        Raises:
+            ValueError: If `grace` is negative or not finite.
+        """
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class DebugConfig:
     @property
@@ -280,6 +474,64 @@ class DebugConfig:
     def log_dir(self) -> typing.Optional[pathlib.Path]: ...
     @property
     def rotation(self) -> typing.Optional[builtins.str]: ...
+    @property
+    def log_file_pattern(self) -> typing.Optional[builtins.str]:
+        r"""Pattern naming a log file per record below `log_dir`, e.g. `{date}/{task}.log`.
+
+        Placeholders are `{date}`, `{hour}`, `{pid}`, `{level}` and the fields of the record,
+        such as those set with `logger.context(...)`. Records lacking a field go to the merged
+        log file, and the console keeps the merged stream. `rotation` is ignored if set.
+        """
+    @property
+    def suppress_duplicates(self) -> builtins.bool:
+        r"""Whether to collapse consecutive identical records into a repetition notice."""
+    @property
+    def max_records_per_second(self) -> typing.Optional[builtins.int]:
+        r"""Maximum number of records written per second, unlimited if unset."""
+    @property
+    def log_format(self) -> typing.Optional[builtins.str]:
+        r"""Loguru-style format of the records, the built-in one if unset.
+
+        Fields are `{time}`, `{level}`, `{name}`, `{message}` and `{process}`, with specs such
+        as `{time:HH:mm:ss}` or `{level: <8}`, and colors are tags such as `<green>...</green>`
+        or `<level>...</level>` for the color of the level.
+        """
+    @property
+    def level_colors(self) -> builtins.dict[builtins.str, builtins.str]:
+        r"""Colors of the levels keyed by level name, e.g. `{ WARN = "light-yellow bold" }`.
+
+        Levels left out keep their default color.
+        """
+    @property
+    def compact(self) -> builtins.bool:
+        r"""Whether to write each record on a single line, with line breaks escaped as `\n`.
+
+        Otherwise the fields of the records wrap at the width of the terminal. Either way,
+        records carry no escape codes when stderr is not a terminal or `NO_COLOR` is set.
+        """
+
+@typing.final
+class DependencyNode:
+    r"""A node of the dependency tree of an installed package."""
+    @property
+    def name(self) -> builtins.str:
+        r"""Name of the package."""
+    @property
+    def version(self) -> typing.Optional[builtins.str]:
+        r"""Installed version of the package, None if it is missing."""
+    @property
+    def specifier(self) -> builtins.str:
+        r"""Version specifiers the parent requires, empty for the root."""
+    @property
+    def extra(self) -> typing.Optional[builtins.str]:
+        r"""The extra of the parent that pulls the package, None if it is always required."""
+    @property
+    def satisfied(self) -> builtins.bool:
+        r"""Whether the package is installed with a version matching the specifiers."""
+    @property
+    def children(self) -> builtins.list[DependencyNode]:
+        r"""Dependencies of the package; empty at the depth limit and for cycles."""
+    def __repr__(self) -> builtins.str: ...
 
 @typing.final
 class DeploymentConfig:
@@ -319,6 +571,43 @@ class EmitterConfig:
     @property
     def delimiter(self) -> builtins.str:
         r"""The delimiter used to separate the event name into segments."""
+    @property
+    def queue_capacity(self) -> builtins.int:
+        r"""Number of events a subscription of the event bus holds before its overflow policy applies."""
+    @property
+    def overflow(self) -> builtins.str:
+        r"""What a full subscription of the event bus does with a new event: `drop_oldest` to make
+        room by dropping the oldest one, or `block` to make the publisher wait for room.
+        """
+    @property
+    def journal(self) -> typing.Optional[pathlib.Path]:
+        r"""The JSON Lines file every emitted event is appended to, no journal is kept if None."""
+
+@typing.final
+class EntryPoint:
+    r"""An entry point declared by an installed Python package."""
+    @property
+    def name(self) -> builtins.str:
+        r"""Name of the entry point, e.g. the command of a console script."""
+    @property
+    def group(self) -> builtins.str:
+        r"""Group of the entry point, e.g. `console_scripts`."""
+    @property
+    def value(self) -> builtins.str:
+        r"""The object reference, e.g. `package.module:function [extra]`."""
+    @property
+    def module(self) -> builtins.str:
+        r"""The module part of the object reference."""
+    @property
+    def attr(self) -> typing.Optional[builtins.str]:
+        r"""The attribute part of the object reference, None if the module itself is referenced."""
+    @property
+    def extras(self) -> builtins.list[builtins.str]:
+        r"""Extras of the package the entry point requires."""
+    @property
+    def package(self) -> builtins.str:
+        r"""Name of the package declaring the entry point."""
+    def __repr__(self) -> builtins.str: ...
 
 @typing.final
 class Event:
@@ -454,6 +743,168 @@ class Event:
             True if the comparison holds, False otherwise.
         """
 
+@typing.final
+class EventBus:
+    r"""Delivers published events to the bounded queues of the subscriptions matching them."""
+    @property
+    def delimiter(self) -> builtins.str:
+        r"""The delimiter of the segments of event names."""
+    def __new__(cls, delimiter: typing.Optional[builtins.str] = None) -> EventBus:
+        r"""Creates a bus without subscriptions.
+
+        Args:
+            delimiter: The delimiter of the segments of event names, the `emitter.delimiter`
+                config value if None.
+
+        Raises:
+            ValueError: If the delimiter is empty.
+        """
+    def subscribe(
+        self,
+        pattern: builtins.str = "**",
+        capacity: typing.Optional[builtins.int] = None,
+        overflow: typing.Optional[builtins.str] = None,
+    ) -> EventSubscription:
+        r"""Subscribes to the events matching a pattern.
+
+        Args:
+            pattern: The pattern, such as `task::*::Finished`, every event by default.
+            capacity: Number of events the subscription holds before its overflow policy
+                applies, the `emitter.queue_capacity` config value if None.
+            overflow: `drop_oldest` to drop the oldest event of a full subscription, or `block`
+                to make publishers wait for room, the `emitter.overflow` config value if None.
+
+        Returns:
+            The subscription, receiving the events published from now on until it is closed.
+
+        Raises:
+            ValueError: If the pattern is empty, the capacity is zero or the overflow policy
+                is unknown.
+        """
+    def publish(self, event: builtins.str, data: typing.Optional[typing.Any] = None) -> typing.Any:
+        r"""Publishes an event to the subscriptions matching it.
+
+        Args:
+            event: The name of the event, such as `task::write::Finished`.
+            data: The data delivered with the event, shared by every subscription.
+
+        Returns:
+            An awaitable resolving to the number of subscriptions the event was queued to,
+            once every blocking subscription had room for it.
+        """
+    def __len__(self) -> builtins.int: ...
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class EventJournal:
+    r"""Records emitted events to a JSON Lines file and replays them."""
+    @property
+    def path(self) -> pathlib.Path:
+        r"""The JSON Lines file of the journal."""
+    def __new__(
+        cls, path: builtins.str | os.PathLike | pathlib.Path, delimiter: typing.Optional[builtins.str] = None
+    ) -> EventJournal:
+        r"""Opens a journal, creating the file and its parent directories if needed.
+
+        A last line left truncated by a crash is terminated, so the next event is recorded on
+        a line of its own.
+
+        Args:
+            path: The JSON Lines file the events are appended to.
+            delimiter: The delimiter of the segments of event names, used by the patterns of
+                `replay`, the `emitter.delimiter` config value if None.
+
+        Raises:
+            OSError: If the file cannot be opened.
+        """
+    def record(self, event: builtins.str, payload: typing.Optional[typing.Any] = None) -> None:
+        r"""Appends an event to the journal, timestamped with the current time.
+
+        Args:
+            event: The name of the event.
+            payload: The data emitted with the event.
+
+        Raises:
+            OSError: If the file cannot be written.
+        """
+    def replay(
+        self,
+        pattern: typing.Optional[builtins.str] = None,
+        since: typing.Optional[builtins.float] = None,
+        limit: typing.Optional[builtins.int] = None,
+    ) -> builtins.list[JournalEntry]:
+        r"""Reads back the recorded events, oldest first.
+
+        Args:
+            pattern: Only replays the events matching this pattern, with the `*` and `**`
+                wildcards of the emitter, such as `task::**::Failed`. Every event if None.
+            since: Only replays the events recorded at or after this POSIX timestamp, such as
+                `datetime.timestamp()` returns. Every event if None.
+            limit: Only replays the latest `limit` events matching the other filters.
+
+        Returns:
+            The matching events. Lines that cannot be parsed, such as one left truncated by a
+            crash, are skipped with a warning.
+
+        Raises:
+            ValueError: If the pattern is empty or `since` is out of range.
+            OSError: If the file cannot be read.
+        """
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class EventSubscription:
+    r"""The events of a bus matching a pattern, consumed with `next_event` or `async for`.
+
+    The subscription is closed by `close`, or when it is garbage collected.
+    """
+    @property
+    def pattern(self) -> builtins.str:
+        r"""The pattern of the subscription."""
+    @property
+    def closed(self) -> builtins.bool:
+        r"""Whether the subscription is closed."""
+    @property
+    def pending(self) -> builtins.int:
+        r"""Number of events queued and not consumed yet."""
+    @property
+    def dropped(self) -> builtins.int:
+        r"""Number of events dropped because the subscription was full."""
+    @property
+    def capacity(self) -> builtins.int:
+        r"""Number of events the subscription holds before its overflow policy applies."""
+    @property
+    def overflow(self) -> builtins.str:
+        r"""The overflow policy, `drop_oldest` or `block`."""
+    def next_event(self) -> typing.Any:
+        r"""Waits for the next event of the subscription.
+
+        Returns:
+            An awaitable resolving to the name and the data of the oldest queued event, or to
+            None once the subscription is closed and its queued events consumed.
+        """
+    def close(self) -> None:
+        r"""Stops receiving events and releases the publishers waiting for room. The events
+        already queued can still be consumed.
+        """
+    def __aiter__(self) -> EventSubscription: ...
+    def __anext__(self) -> typing.Any: ...
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class GcReport:
+    r"""What a garbage collection of a `ContentStore` removed."""
+    @property
+    def kept(self) -> builtins.int:
+        r"""Number of blobs kept because they are referenced or recently stored."""
+    @property
+    def removed(self) -> builtins.int:
+        r"""Number of blobs removed."""
+    @property
+    def reclaimed_bytes(self) -> builtins.int:
+        r"""Bytes freed by removing the blobs."""
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class GeneralConfig:
     r"""General configuration structure for application-wide settings."""
@@ -488,6 +939,20 @@ class GenericBlockParser:
         Returns a vector of captured block contents.
         """
 
+@typing.final
+class JournalEntry:
+    r"""An event read back from the journal."""
+    @property
+    def timestamp(self) -> builtins.str:
+        r"""When the event was recorded, in RFC 3339 format."""
+    @property
+    def event(self) -> builtins.str:
+        r"""The name of the event."""
+    @property
+    def payload(self) -> typing.Any:
+        r"""The payload, as converted to JSON when the event was recorded."""
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class JsonParser:
     @staticmethod
@@ -636,14 +1101,67 @@ class LLMConfig:
     def effort(self) -> typing.Optional[builtins.str]:
         r"""Reasoning effort for models that support it."""
 
+@typing.final
+class LogContext:
+    r"""Context manager attaching fields to the records logged within it."""
+    def __enter__(self) -> LogContext: ...
+    def __exit__(
+        self,
+        _exc_type: typing.Optional[typing.Any] = None,
+        _exc_value: typing.Optional[typing.Any] = None,
+        _traceback: typing.Optional[typing.Any] = None,
+    ) -> None: ...
+
 @typing.final
 class Logger:
+    def context(self, **fields: typing.Any) -> LogContext:
+        r"""Attaches fields to the records logged within a `with` block.
+
+        The fields are stored in a context variable, so they follow the current asyncio task
+        and the tasks it creates, and can name the log file of the records through the
+        `log_file_pattern` setting, e.g. `{date}/{task}.log`. Nested contexts add to the
+        fields of the outer ones.
+
+        Args:
+            **fields: The fields to attach, converted to strings.
+
+        Returns:
+            A context manager setting the fields on enter and restoring the previous ones on exit.
+        """
     def info(self, msg: builtins.str) -> None: ...
     def debug(self, msg: builtins.str) -> None: ...
     def error(self, msg: builtins.str) -> None: ...
     def warn(self, msg: builtins.str) -> None: ...
     def trace(self, msg: builtins.str) -> None: ...
 
+@typing.final
+class ProjectManifest:
+    r"""The project a path belongs to, as described by its manifests."""
+    @property
+    def root(self) -> pathlib.Path:
+        r"""The directory holding the manifests."""
+    @property
+    def kinds(self) -> builtins.list[builtins.str]:
+        r"""The kinds of the project, among `python`, `rust`, `node` and `typst`, in that order."""
+    @property
+    def manifests(self) -> builtins.list[pathlib.Path]:
+        r"""The manifest of each kind, in the same order."""
+    @property
+    def name(self) -> typing.Optional[builtins.str]:
+        r"""Name of the project, from the first manifest declaring one."""
+    @property
+    def version(self) -> typing.Optional[builtins.str]:
+        r"""Version of the project, from the first manifest declaring one."""
+    @property
+    def description(self) -> typing.Optional[builtins.str]:
+        r"""Description of the project, from the first manifest declaring one."""
+    @property
+    def ignore(self) -> builtins.list[builtins.str]:
+        r"""Patterns in the `.gitignore` syntax of the build outputs, caches and installed
+        dependencies the tooling of the project generates.
+        """
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class ProviderConfig:
     r"""Configuration for a specific provider.
@@ -798,6 +1316,15 @@ class Router:
         Returns:
             None: This is an asynchronous operation that modifies the router state.
         """
+    def reload_providers(self) -> None:
+        r"""Re-registers the providers of the configuration, picking up their current keys.
+
+        Providers keep the key they were registered with, so call this after refreshing
+        secrets with `CONFIG.check_secrets(rotate=True)`.
+
+        Returns:
+            None
+        """
     def add_completion_model(
         self,
         group: builtins.str,
@@ -1373,10 +1900,54 @@ class RoutingConfig:
     def retry_backoff_multiplier(self) -> typing.Optional[builtins.float]:
         r"""Exponential backoff multiplier. Default: 2.0."""
 
+@typing.final
+class SecretReport:
+    r"""A secret of the configuration about to expire, as reported by `Config.check_secrets`."""
+    @property
+    def path(self) -> builtins.str:
+        r"""Where the secret sits in the configuration, e.g. `routing.providers[0].key`."""
+    @property
+    def expires_at(self) -> typing.Optional[builtins.int]:
+        r"""The unix timestamp the secret expires at, after any rotation."""
+    @property
+    def rotated(self) -> builtins.bool:
+        r"""Whether the secret was refreshed by its rotation command."""
+    @property
+    def error(self) -> typing.Optional[builtins.str]:
+        r"""Why the rotation failed, if it was attempted and failed."""
+
 @typing.final
 class SecretStr:
-    def __new__(cls, source: builtins.str) -> SecretStr: ...
+    def __new__(
+        cls,
+        source: builtins.str,
+        expires_at: typing.Optional[builtins.int] = None,
+        rotate_cmd: typing.Optional[builtins.str] = None,
+    ) -> SecretStr:
+        r"""Creates a secret, optionally expiring at a unix timestamp and rotated by a shell command."""
     def get_secret_value(self) -> builtins.str: ...
+    @property
+    def expires_at(self) -> typing.Optional[builtins.int]:
+        r"""The unix timestamp after which the secret is no longer valid, if known."""
+    @property
+    def rotate_cmd(self) -> typing.Optional[builtins.str]:
+        r"""The shell command printing a fresh value of the secret, if any."""
+    def expires_within(self, seconds: builtins.int) -> builtins.bool:
+        r"""Whether the secret expires within the given number of seconds, or already has."""
+    def rotate(self, timeout: builtins.float = 30.0) -> None:
+        r"""Replaces the secret with the output of its rotation command.
+
+        The command may print the bare secret, which then has no expiry, or a JSON object with
+        `value` and optionally `expires_at`. Every clone of the secret sees the new value. The
+        GIL is released while the command runs.
+
+        Args:
+            timeout: Seconds the command may run for before it is killed. Defaults to 30.
+
+        Raises:
+            ValueError: If the secret has no rotation command or the timeout is invalid.
+            RuntimeError: If the command fails, times out or prints an empty secret.
+        """
 
 @typing.final
 class TemplateConfig:
@@ -1426,6 +1997,16 @@ class TemplateManager:
     @property
     def templates_stores(self) -> builtins.list[pathlib.Path]: ...
     @property
+    def strict(self) -> builtins.bool:
+        r"""Whether rendering a list of data fails if any item fails, used when `strict` is not
+        given to `render_template` or `render_template_raw`.
+        """
+    @strict.setter
+    def strict(self, value: builtins.bool) -> None:
+        r"""Whether rendering a list of data fails if any item fails, used when `strict` is not
+        given to `render_template` or `render_template_raw`.
+        """
+    @property
     def template_count(self) -> builtins.int:
         r"""The count of templates currently registered."""
     def add_store(
@@ -1452,26 +2033,222 @@ class TemplateManager:
         Returns:
             A mutable reference to self for method chaining.
         """
+    @property
+    def env_allowlist(self) -> builtins.list[builtins.str]:
+        r"""The environment variables templates may read with the `env` helper, sorted."""
+    @property
+    def config_allowlist(self) -> builtins.list[builtins.str]:
+        r"""The configuration keys templates may read with the `config` helper, sorted."""
+    def allow_env(self, names: typing.Sequence[builtins.str]) -> TemplateManager:
+        r"""Allows templates to read environment variables with `{{env "VAR"}}`.
+
+        The helper renders the variable, or its optional second parameter if the variable is
+        unset. Reading a variable that was not allowed fails the rendering.
+
+        Args:
+            names: The names of the environment variables.
+
+        Returns:
+            A mutable reference to self for method chaining.
+        """
+    def allow_config(self, keys: typing.Sequence[builtins.str]) -> TemplateManager:
+        r"""Allows templates to read configuration values with `{{config "llm.model"}}`.
+
+        A key such as `llm` also allows every key below it, such as `llm.model`. Secrets are
+        rendered redacted, and reading a key that was not allowed fails the rendering.
+
+        Args:
+            keys: The dotted configuration keys.
+
+        Returns:
+            A mutable reference to self for method chaining.
+        """
+    def register_template(self, name: builtins.str, source: builtins.str) -> TemplateManager:
+        r"""Registers a template from its source, to be rendered by name like a discovered one.
+
+        Templates registered this way, such as generated ones, are kept when templates are
+        discovered again and take precedence over templates of the stores with the same name.
+        They are registered as given, so they cannot extend a layout, but may include partials.
+
+        Args:
+            name: The name to render the template by.
+            source: The source of the template.
+
+        Returns:
+            A mutable reference to self for method chaining.
+
+        Raises:
+            ValueError: If the source is not a valid template.
+        """
+    def unregister_template(self, name: builtins.str) -> builtins.bool:
+        r"""Unregisters a template or partial, whether registered from Python or discovered.
+
+        A discovered template comes back at the next discovery, as long as its file exists.
+
+        Args:
+            name: The name of the template.
+
+        Returns:
+            Whether a template was registered under that name.
+        """
+    def register_partial(self, name: builtins.str, source: builtins.str) -> TemplateManager:
+        r"""Registers a partial, a template other templates include with `{{> name}}`.
+
+        Partials registered this way are kept when templates are discovered again, and take
+        precedence over templates and partials of the stores with the same name. Partials
+        can also be put in the `partials/` subdirectory of a store, where `partials/base.hbs`
+        registers `base`.
+
+        Args:
+            name: The name to include the partial by.
+            source: The source of the partial, which may itself include partials.
+
+        Returns:
+            A mutable reference to self for method chaining.
+
+        Raises:
+            ValueError: If the source is not a valid template.
+        """
+    def register_helper(self, name: builtins.str, function: typing.Any) -> TemplateManager:
+        r"""Registers a Python function as a helper, called as `{{name arg ... key=value}}`.
+
+        The parameters of the helper are converted to Python objects and passed as positional
+        arguments, its hash as keyword arguments. What the function returns is converted back
+        and rendered, or passed on when the helper is used in a subexpression, so it must be
+        serializable like template data; `None` renders as nothing. Helpers are kept when
+        templates are discovered again, and replace a helper of the same name, built-in or not.
+
+        Args:
+            name: The name to call the helper by.
+            function: The function to call, which may be called from any thread.
+
+        Returns:
+            A mutable reference to self for method chaining.
+
+        Raises:
+            TypeError: If the function is not callable.
+            ValueError: If the name is empty or contains whitespace.
+        """
+    def template_variables(self, name: builtins.str) -> TemplateVariables:
+        r"""Lists the variables, helpers and partials a registered template references.
+
+        Partials are followed, so the variables they read are listed too. Variables are given
+        as dotted paths from the root of the data, such as `user.name`; those relative to the
+        items of an `each` or to a `with` block depend on the data and are left out.
+
+        Args:
+            name: The name of the template.
+
+        Returns:
+            A `TemplateVariables` with the required and optional variables, the helpers and
+            the partials, each sorted.
+
+        Raises:
+            RuntimeError: If the template is missing.
+        """
+    def validate_data(self, name: builtins.str, data: typing.Any) -> builtins.list[builtins.str]:
+        r"""Checks that data holds every variable a template always renders, before rendering it.
+
+        Variables only tested by an `if` or `unless`, or only rendered inside a conditional
+        block, are not required. A variable holding `None` counts as present.
+
+        Args:
+            name: The name of the template.
+            data: A dictionary containing template variables.
+
+        Returns:
+            The sorted dotted paths of the missing variables, empty if the data is complete.
+
+        Raises:
+            RuntimeError: If the template is missing.
+        """
     def discover_templates(self) -> TemplateManager:
         r"""Discovers and registers all templates from the configured directories.
 
         Returns:
             A mutable reference to self for method chaining.
         """
+    def template_variants(self, name: builtins.str) -> builtins.list[builtins.str]:
+        r"""Lists the variants registered for a template.
+
+        A template file named `summarize@v2.hbs` registers the variant `v2` of `summarize`,
+        while the bare `summarize.hbs` is reported as the `default` variant.
+
+        Args:
+            name: The base name of the template.
+
+        Returns:
+            The sorted list of variant labels.
+        """
+    def render_variant(
+        self,
+        name: builtins.str,
+        data: typing.Any,
+        variant: typing.Optional[builtins.str] = None,
+        weights: typing.Optional[typing.Mapping[builtins.str, builtins.float]] = None,
+    ) -> tuple[builtins.str, builtins.str]:
+        r"""Renders one variant of a template with the given data.
+
+        If `variant` is given, that variant is rendered. Otherwise a variant is picked at
+        random among the registered ones, weighted by `weights` (variants absent from
+        `weights` get a weight of 1.0). The chosen variant is logged as the structured
+        `template_variant` field and returned alongside the rendered text.
+
+        Args:
+            name: The base name of the template.
+            data: A dictionary containing template variables.
+            variant: The variant label to render, `default` refers to the bare template.
+            weights: Optional mapping of variant labels to selection weights.
+
+        Returns:
+            A tuple of (variant label, rendered string).
+
+        Raises:
+            RuntimeError: If the template has no variants or the requested variant does not exist.
+        """
+    @typing.overload
+    def render_template(
+        self, name: str, data: typing.Dict[str, typing.Any], strict: typing.Optional[bool] = None
+    ) -> str: ...
     @typing.overload
-    def render_template(self, name: str, data: typing.Dict[str, typing.Any]) -> str: ...
+    def render_template(
+        self, name: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Literal[True]
+    ) -> typing.List[str]: ...
     @typing.overload
-    def render_template(self, name: str, data: typing.List[typing.Dict[str, typing.Any]]) -> typing.List[str]: ...
+    def render_template(
+        self, name: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Optional[bool] = None
+    ) -> typing.List[typing.Optional[str]]: ...
     @typing.overload
     def render_template(
-        self, name: str, data: typing.List[typing.Dict[str, typing.Any]] | typing.Dict[str, typing.Any]
-    ) -> typing.List[str] | str: ...
+        self,
+        name: str,
+        data: typing.List[typing.Dict[str, typing.Any]] | typing.Dict[str, typing.Any],
+        strict: typing.Optional[bool] = None,
+    ) -> typing.List[typing.Optional[str]] | str: ...
     @typing.overload
-    def render_template_raw(self, template: str, data: typing.Dict[str, typing.Any]) -> str: ...
+    def render_template_async(
+        self, name: str, data: typing.Dict[str, typing.Any], strict: typing.Optional[bool] = None
+    ) -> typing.Awaitable[str]: ...
+    @typing.overload
+    def render_template_async(
+        self, name: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Literal[True]
+    ) -> typing.Awaitable[typing.List[str]]: ...
+    @typing.overload
+    def render_template_async(
+        self, name: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Optional[bool] = None
+    ) -> typing.Awaitable[typing.List[typing.Optional[str]]]: ...
     @typing.overload
     def render_template_raw(
-        self, template: str, data: typing.List[typing.Dict[str, typing.Any]]
+        self, template: str, data: typing.Dict[str, typing.Any], strict: typing.Optional[bool] = None
+    ) -> str: ...
+    @typing.overload
+    def render_template_raw(
+        self, template: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Literal[True]
     ) -> typing.List[str]: ...
+    @typing.overload
+    def render_template_raw(
+        self, template: str, data: typing.List[typing.Dict[str, typing.Any]], strict: typing.Optional[bool] = None
+    ) -> typing.List[typing.Optional[str]]: ...
 
 @typing.final
 class TemplateManagerConfig:
@@ -1484,6 +2261,36 @@ class TemplateManagerConfig:
     @property
     def template_suffix(self) -> builtins.str:
         r"""The suffix of the templates."""
+    @property
+    def env_allowlist(self) -> builtins.list[builtins.str]:
+        r"""The environment variables templates may read with the `env` helper."""
+    @property
+    def config_allowlist(self) -> builtins.list[builtins.str]:
+        r"""The configuration keys templates may read with the `config` helper, e.g. `llm.model`."""
+    @property
+    def strict(self) -> builtins.bool:
+        r"""Whether rendering a list of data fails if any item fails, instead of skipping the
+        failing items and logging them.
+        """
+
+@typing.final
+class TemplateVariables:
+    r"""The variables, helpers and partials a template references."""
+    @property
+    def variables(self) -> builtins.list[builtins.str]:
+        r"""The variables the template always renders, as sorted dotted paths."""
+    @property
+    def optional(self) -> builtins.list[builtins.str]:
+        r"""The variables only tested by an `if` or `unless`, or only rendered in a conditional
+        block, which the data may leave out.
+        """
+    @property
+    def helpers(self) -> builtins.list[builtins.str]:
+        r"""The helpers called, sorted."""
+    @property
+    def partials(self) -> builtins.list[builtins.str]:
+        r"""The partials included, sorted, those they include in turn too."""
+    def __repr__(self) -> builtins.str: ...
 
 @typing.final
 class TextCapturer:
@@ -1600,6 +2407,62 @@ class TextCapturer:
             - If `right_delimiter` is not provided, it defaults to `left_delimiter`.
         """
 
+@typing.final
+class TopicTrie:
+    r"""Subscriptions to event patterns, matched against emitted events in a single walk.
+
+    Subscriptions are identified by the integer `subscribe` returns, which the emitter maps to
+    its handlers.
+    """
+    def __new__(cls, delimiter: typing.Optional[builtins.str] = None) -> TopicTrie:
+        r"""Creates an empty trie.
+
+        Args:
+            delimiter: The delimiter of the segments of event names, the `emitter.delimiter`
+                config value if None.
+
+        Raises:
+            ValueError: If the delimiter is empty.
+        """
+    @property
+    def delimiter(self) -> builtins.str:
+        r"""The delimiter of the segments of event names."""
+    def subscribe(self, pattern: builtins.str) -> builtins.int:
+        r"""Subscribes to the events matching a pattern.
+
+        Args:
+            pattern: The pattern, such as `task::*::Finished` or `task::**`.
+
+        Returns:
+            The identifier of the subscription, increasing with each subscription.
+
+        Raises:
+            ValueError: If the pattern is empty.
+        """
+    def unsubscribe(self, pattern: builtins.str) -> builtins.list[builtins.int]:
+        r"""Removes every subscription to a pattern.
+
+        Args:
+            pattern: The pattern as subscribed to.
+
+        Returns:
+            The identifiers of the removed subscriptions, empty if there were none.
+
+        Raises:
+            ValueError: If the pattern is empty.
+        """
+    def matches(self, event: builtins.str) -> builtins.list[builtins.int]:
+        r"""Finds the subscriptions whose pattern matches an event.
+
+        Args:
+            event: The name of the emitted event, such as `task::write::Finished`.
+
+        Returns:
+            The identifiers of the matching subscriptions, in the order they were made.
+        """
+    def __len__(self) -> builtins.int: ...
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class ProviderType(enum.Enum):
     r"""Enum representing supported LLM provider types.
@@ -1664,6 +2527,58 @@ def blake3_hash(content: bytes) -> builtins.str:
         A hexadecimal string representation of the BLAKE3 hash.
     """
 
+def conda_version(pkg_name: builtins.str) -> typing.Optional[builtins.str]:
+    r"""Returns the version of a conda-managed package.
+
+    Args:
+        pkg_name: The name of the package.
+
+    Returns:
+        The version recorded in the environment's `conda-meta` directory, or None if the
+        package is not managed by conda or no conda environment is active.
+    """
+
+def confine(
+    root: builtins.str | os.PathLike | pathlib.Path, candidate: builtins.str | os.PathLike | pathlib.Path
+) -> pathlib.Path:
+    r"""Resolves a path against a workspace root, refusing paths that lead out of it.
+
+    A relative path is taken relative to the root, an absolute one must lie below it.
+    Symlinks are followed before checking, so a link inside the root cannot reach a file
+    outside of it, and the path may name a file that does not exist yet.
+
+    Args:
+        root: The workspace root, which must exist.
+        candidate: The path to resolve.
+
+    Returns:
+        The canonical path the candidate designates, a verbatim path on Windows.
+
+    Raises:
+        PermissionError: If the path leads out of the root.
+        FileNotFoundError: If the root does not exist or the path goes through a dangling
+            symlink.
+    """
+
+def dependency_tree(
+    pkg_name: builtins.str, depth: typing.Optional[builtins.int] = None, extras: typing.Sequence[builtins.str] = []
+) -> typing.Optional[DependencyNode]:
+    r"""Builds the dependency tree of an installed Python package from its `Requires-Dist` entries.
+
+    Nodes whose installed version does not match the specifiers of their parent are marked
+    as unsatisfied, which reveals conflicts. Packages already on the path to a node are not
+    expanded again, and requirements whose environment markers exclude the running
+    interpreter are left out.
+
+    Args:
+        pkg_name: The name of the root package.
+        depth: Maximum depth of the tree below the root, unlimited if None.
+        extras: Extras of the root package whose dependencies are included as well.
+
+    Returns:
+        The root node, or None if the package is not installed.
+    """
+
 def detect_language(string: builtins.str) -> builtins.str:
     r"""Detects the language of a given string and returns its full native name.
 
@@ -1677,9 +2592,35 @@ def detect_language(string: builtins.str) -> builtins.str:
         A string containing the detected language name in its native script.
     """
 
+def detect_project(
+    path: builtins.str | os.PathLike | pathlib.Path,
+    boundary: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+) -> typing.Optional[ProjectManifest]:
+    r"""Detects the project a path belongs to from the manifest at its root.
+
+    The root is the nearest directory, starting from the path and going up, holding a
+    `pyproject.toml`, `Cargo.toml`, `package.json` or `typst.toml`. A directory holding several
+    of them makes a project of several kinds. Manifests that cannot be parsed still identify
+    the kind, without metadata.
+
+    Args:
+        path: A file or directory of the project.
+        boundary: The directory the search stops at, included, such as a workspace root. The
+            search goes up to the root of the filesystem if None.
+
+    Returns:
+        The manifest of the nearest project, or None if the path belongs to none.
+
+    Raises:
+        FileNotFoundError: If the path or the boundary does not exist.
+    """
+
 def extra_satisfied(pkg_name: builtins.str, extra_name: builtins.str) -> builtins.bool:
     r"""Checks if a specific extra (optional dependency) of a Python package is satisfied.
 
+    Dependencies whose environment markers, e.g. `sys_platform == "win32"`, exclude the
+    running interpreter are not required.
+
     Args:
         pkg_name: The name of the package.
         extra_name: The name of the extra/optional dependency.
@@ -1691,6 +2632,8 @@ def extra_satisfied(pkg_name: builtins.str, extra_name: builtins.str) -> builtin
 def extras_satisfied(pkg_name: builtins.str, extras: typing.Sequence[builtins.str]) -> builtins.bool:
     r"""Checks if all specified extras (optional dependencies) of a Python package are satisfied.
 
+    Dependencies whose environment markers exclude the running interpreter are not required.
+
     Args:
         pkg_name: The name of the package.
         extras: A list of extra/optional dependency names to check.
@@ -1699,6 +2642,26 @@ def extras_satisfied(pkg_name: builtins.str, extras: typing.Sequence[builtins.st
         True if all extras are satisfied, False otherwise.
     """
 
+def get_console_scripts() -> builtins.dict[builtins.str, builtins.str]:
+    r"""Lists the console scripts installed with Python packages.
+
+    Returns:
+        A mapping of each command to its object reference, e.g. `{"pytest": "pytest:console_main"}`.
+    """
+
+def get_version(pkg_name: builtins.str) -> typing.Optional[builtins.str]:
+    r"""Returns the installed version of a Python package.
+
+    The version is read from the `Version` field of the package's METADATA, falling back to
+    the name of its `.dist-info` or `.egg-info` record and then to the `conda-meta` records.
+
+    Args:
+        pkg_name: The name of the package.
+
+    Returns:
+        The installed version, or None if the package is not installed.
+    """
+
 def is_arabic(string: builtins.str) -> builtins.bool:
     r"""Checks if the given string is written in Arabic.
 
@@ -1772,6 +2735,9 @@ def is_hindi(string: builtins.str) -> builtins.bool:
 def is_installed(pkg_name: builtins.str) -> builtins.bool:
     r"""Checks if a Python package is installed.
 
+    Packages managed by conda are detected even if they have no `.dist-info` directory, and
+    so are legacy `.egg-info` installs and editable installs made with `pip install -e`.
+
     Args:
         pkg_name: The name of the package to check.
 
@@ -1872,6 +2838,20 @@ def is_vietnamese(string: builtins.str) -> builtins.bool:
         True if the detected language is Vietnamese, False otherwise.
     """
 
+def list_entry_points(group: typing.Optional[builtins.str] = None) -> builtins.list[EntryPoint]:
+    r"""Lists the entry points declared by installed Python packages in their `entry_points.txt`.
+
+    Unlike `importlib.metadata.entry_points`, the files are parsed once and cached, so plugin
+    groups can be queried repeatedly at runtime.
+
+    Args:
+        group: Only list the entry points of this group, e.g. `console_scripts`. Defaults to
+            None, listing all groups.
+
+    Returns:
+        The entry points sorted by group, name and package.
+    """
+
 def list_installed() -> builtins.list[builtins.str]:
     r"""Lists all installed Python packages.
 
@@ -1879,6 +2859,52 @@ def list_installed() -> builtins.list[builtins.str]:
         A list of names of all installed packages.
     """
 
+def long_path(path: builtins.str | os.PathLike | pathlib.Path) -> pathlib.Path:
+    r"""Turns an absolute path into a verbatim `\\?\` path on Windows, which is not subject to the
+    260 characters limit. Other paths, and any path on other platforms, are returned as is.
+
+    Args:
+        path: The path to convert.
+
+    Returns:
+        The converted path.
+    """
+
+def refresh_installed() -> None:
+    r"""Rescans the site-packages directory, picking up packages installed or removed since the
+    last scan.
+    """
+
+def relativize(
+    root: builtins.str | os.PathLike | pathlib.Path, path: builtins.str | os.PathLike | pathlib.Path
+) -> pathlib.Path:
+    r"""Makes a path relative to a workspace root, normalizing `.` and `..` components.
+
+    Args:
+        root: The workspace root.
+        path: An absolute path below the root, or a path relative to it.
+
+    Returns:
+        The path relative to the root, `.` for the root itself.
+
+    Raises:
+        PermissionError: If the path is not below the root.
+    """
+
+def reverse_dependencies(
+    pkg_name: builtins.str,
+) -> builtins.list[tuple[builtins.str, builtins.str, typing.Optional[builtins.str]]]:
+    r"""Lists the installed Python packages requiring a package.
+
+    Args:
+        pkg_name: The name of the required package.
+
+    Returns:
+        A list of `(package, specifier, extra)` tuples sorted by package, where `extra` is the
+        extra of the requiring package that pulls the dependency, or None if it is always
+        required.
+    """
+
 def split_into_chunks(
     string: builtins.str, max_chunk_size: builtins.int, max_overlapping_rate: builtins.float = 0.3
 ) -> builtins.list[builtins.str]:
@@ -1896,6 +2922,20 @@ def split_into_chunks(
         A list of chunk strings.
     """
 
+def split_paragraphs(text: builtins.str) -> builtins.list[builtins.str]:
+    r"""Splits a text into paragraphs, trimmed and non-empty.
+
+    Paragraphs are separated by blank lines. A line indented with an ideographic space
+    (`U+3000`), as in CJK typesetting, also starts a paragraph. The lines of a paragraph are
+    kept as they are.
+
+    Args:
+        text: The text to split.
+
+    Returns:
+        A list of paragraph strings.
+    """
+
 def split_sentence_bounds(string: builtins.str) -> builtins.list[builtins.str]:
     r"""Splits a string into sentences using Unicode sentence boundaries.
 
@@ -1909,6 +2949,22 @@ def split_sentence_bounds(string: builtins.str) -> builtins.list[builtins.str]:
         A list of sentence strings.
     """
 
+def split_sentences(text: builtins.str, lang: typing.Optional[builtins.str] = None) -> builtins.list[builtins.str]:
+    r"""Splits a text into sentences, trimmed and non-empty.
+
+    Unlike `split_sentence_bounds`, a period after an abbreviation such as `Dr.` or `e.g.`
+    or after an initial does not end a sentence. CJK terminators such as `。`, `！` and `？`
+    end sentences even without a following space, along with the closing quotes after them.
+
+    Args:
+        text: The text to split.
+        lang: The code of the language of the text, such as `en` or `de`, which selects the
+            abbreviations. Detected from the text if None.
+
+    Returns:
+        A list of sentence strings.
+    """
+
 def split_word_bounds(string: builtins.str) -> builtins.list[builtins.str]:
     r"""Splits a string into words using Unicode word boundaries.
 
@@ -1934,6 +2990,34 @@ def tokens_of(text: builtins.str) -> builtins.int:
         The number of tokens in the text.
     """
 
+def unwatch_installed() -> None:
+    r"""Stops watching the site-packages directory."""
+
+def version_satisfies(pkg_name: builtins.str, specifiers: builtins.str) -> builtins.bool:
+    r"""Checks if the installed version of a Python package matches PEP 440 version specifiers.
+
+    Args:
+        pkg_name: The name of the package.
+        specifiers: Comma-separated PEP 440 version specifiers, e.g. `">=2.1,<3"`.
+
+    Returns:
+        True if the package is installed with a matching version, False if it is missing or
+        its version is not PEP 440 compliant.
+
+    Raises:
+        ValueError: If the specifiers are invalid.
+    """
+
+def watch_installed() -> None:
+    r"""Watches the site-packages directory so that packages installed or removed later, e.g.
+    by `pip install`, are picked up without calling `refresh_installed`.
+
+    Calling it again while already watching does nothing.
+
+    Raises:
+        OSError: If the directory cannot be watched.
+    """
+
 def word_count(string: builtins.str) -> builtins.int:
     r"""Counts the number of words in a string.
 
@@ -0,0 +1,95 @@
+"""Tests for the content-addressed store."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_core.rust import ContentStore, blake3_hash
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> ContentStore:
+    """Fixture to open a store in a temporary directory."""
+    return ContentStore(tmp_path / "cas")
+
+
+def test_put_and_get(store: ContentStore, tmp_path: Path) -> None:
+    """Blobs are keyed by their BLAKE3 hash and stored once."""
+    digest = store.put_bytes(b"hello")
+    assert digest == blake3_hash(b"hello")
+    assert store.put_bytes(b"hello") == digest
+    assert store.get(digest) == b"hello"
+    assert digest in store
+    assert store.path_of(digest) == store.root / digest[:2] / digest[2:]
+    assert store.path_of(digest).read_bytes() == b"hello"
+
+    source = tmp_path / "source.bin"
+    source.write_bytes(b"hello")
+    assert store.put_file(source) == digest
+
+
+def test_missing_and_invalid(store: ContentStore, tmp_path: Path) -> None:
+    """Unknown blobs are missing files, malformed hashes are refused."""
+    with pytest.raises(FileNotFoundError):
+        store.get("0" * 64)
+    with pytest.raises(ValueError, match="BLAKE3"):
+        store.get("../../etc/passwd")
+    with pytest.raises(ValueError, match="BLAKE3"):
+        store.path_of("ABC")
+    with pytest.raises(FileNotFoundError):
+        store.put_file(tmp_path / "missing.bin")
+    assert not store.contains("0" * 64)
+
+
+def test_gc(store: ContentStore) -> None:
+    """Only the blobs referenced by some owner or stored within the grace period survive a collection."""
+    kept = store.put_bytes(b"kept")
+    dropped = store.put_bytes(b"dropped")
+    assert store.gc().removed == 0
+    store.set_reference("tests", "kept", kept)
+    report = store.gc(grace=0)
+    assert (report.kept, report.removed, report.reclaimed_bytes) == (1, 1, len(b"dropped"))
+    assert kept in store
+    assert dropped not in store
+    assert store.remove_reference("tests", "kept")
+    assert not store.remove_reference("tests", "kept")
+    assert store.gc(grace=0).removed == 1
+    with pytest.raises(ValueError, match="grace"):
+        store.gc(grace=-1)
+
+
+def test_references_are_scoped_per_owner(store: ContentStore) -> None:
+    """An owner replacing its references leaves the blobs of the other owners alone."""
+    media = store.put_bytes(b"media")
+    cached = store.put_bytes(b"cached")
+    store.set_reference("anki", "deck/cat.png", media)
+    store.set_references("rag", {"doc": cached})
+    assert store.references("anki") == {"deck/cat.png": media}
+    assert store.reference("rag", "doc") == cached
+    assert store.reference("rag", "missing") is None
+
+    store.set_references("rag", {})
+    assert store.references("rag") == {}
+    assert store.gc(grace=0).removed == 1
+    assert media in store
+    assert cached not in store
+
+    with pytest.raises(ValueError, match="owner"):
+        store.set_reference("../escape", "x", media)
+    with pytest.raises(ValueError, match="reference name"):
+        store.set_reference("anki", "two\nlines", media)
+    with pytest.raises(ValueError, match="BLAKE3"):
+        store.set_reference("anki", "x", "not-a-hash")
+
+
+def test_link_to(store: ContentStore, tmp_path: Path) -> None:
+    """Blobs are handed out as files that outlive their collection."""
+    digest = store.put_bytes(b"image")
+    dest = tmp_path / "image.png"
+    store.link_to(digest, dest)
+    assert dest.read_bytes() == b"image"
+    with pytest.raises(FileExistsError):
+        store.link_to(digest, dest)
+    assert store.gc(grace=0).removed == 1
+    assert dest.read_bytes() == b"image"
+    with pytest.raises(FileNotFoundError):
+        store.link_to(digest, tmp_path / "other.png")
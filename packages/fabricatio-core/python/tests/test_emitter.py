@@ -0,0 +1,168 @@
+"""Tests for the wildcard and hierarchical event subscriptions, the event bus and the event journal."""
+
+import asyncio
+import time
+from pathlib import Path
+from typing import List
+
+import pytest
+from fabricatio_core.emitter import Callback, EventEmitter
+from fabricatio_core.rust import EventBus, EventJournal, TopicTrie
+
+
+def test_topic_trie_matches() -> None:
+    """`*` matches one segment, `**` any number of them, and matches come in subscription order."""
+    trie = TopicTrie("::")
+    single = trie.subscribe("task::*::Finished")
+    multi = trie.subscribe("task::**")
+    exact = trie.subscribe("task::write::Finished")
+    nested = trie.subscribe("task::**::Failed")
+    assert trie.matches("task::write::Finished") == [single, multi, exact]
+    assert trie.matches("task::a::b::Failed") == [multi, nested]
+    assert trie.matches("task::Failed") == [multi, nested]
+    assert trie.matches("task") == [multi]
+    assert trie.matches("other::write::Finished") == []
+    assert len(trie) == 4
+
+
+def test_topic_trie_unsubscribe() -> None:
+    """Unsubscribing removes every subscription to the pattern and nothing else."""
+    trie = TopicTrie(".")
+    first = trie.subscribe("task.*")
+    second = trie.subscribe("task.*")
+    kept = trie.subscribe("task.run")
+    assert trie.unsubscribe("task.*") == [first, second]
+    assert trie.unsubscribe("task.*") == []
+    assert trie.matches("task.run") == [kept]
+    assert len(trie) == 1
+    with pytest.raises(ValueError):
+        trie.subscribe("")
+
+
+@pytest.mark.asyncio
+async def test_emitter_hierarchical_patterns() -> None:
+    """The emitter invokes the handlers of every matching pattern until they are removed."""
+    emitter: EventEmitter[str] = EventEmitter(sep="::")
+    received: List[str] = []
+
+    def recorder(tag: str) -> Callback[str]:
+        async def handler(data: str) -> None:
+            received.append(f"{tag}:{data}")
+
+        return handler
+
+    emitter.on("task::**", recorder("all")).on("task::*::Finished", recorder("finished"))
+    await emitter.emit("task::write::Finished", "a")
+    assert sorted(received) == ["all:a", "finished:a"]
+
+    received.clear()
+    emitter.off("task::**")
+    await emitter.emit("task::write::Running", "b")
+    await emitter.emit("task::write::Finished", "c")
+    assert received == ["finished:c"]
+
+
+@pytest.mark.asyncio
+async def test_bus_drop_oldest() -> None:
+    """A full subscription drops its oldest events, and only matching subscriptions receive them."""
+    bus = EventBus("::")
+    finished = bus.subscribe("task::*::Finished", capacity=2, overflow="drop_oldest")
+    failed = bus.subscribe("task::**::Failed")
+    for name in ("a", "b", "c"):
+        assert await bus.publish(f"task::{name}::Finished", name) == 1
+
+    assert (finished.pending, finished.dropped) == (2, 1)
+    assert await finished.next_event() == ("task::b::Finished", "b")
+    assert await finished.next_event() == ("task::c::Finished", "c")
+    assert failed.pending == 0
+
+    finished.close()
+    assert await bus.publish("task::d::Finished") == 0
+    assert await finished.next_event() is None
+    assert len(bus) == 1
+    with pytest.raises(ValueError, match="overflow"):
+        bus.subscribe(overflow="drop_newest")
+
+
+@pytest.mark.asyncio
+async def test_bus_block() -> None:
+    """A full blocking subscription holds the publisher back until the consumer catches up."""
+    bus = EventBus("::")
+    subscription = bus.subscribe(capacity=1, overflow="block")
+    await bus.publish("tick", 1)
+    pending = asyncio.ensure_future(bus.publish("tick", 2))
+    await asyncio.sleep(0.05)
+    assert not pending.done()
+
+    assert await subscription.next_event() == ("tick", 1)
+    assert await asyncio.wait_for(pending, 1) == 1
+
+    received = []
+    subscription.close()
+    async for event, data in subscription:
+        received.append((event, data))
+    assert received == [("tick", 2)]
+
+
+@pytest.mark.asyncio
+async def test_emitter_publishes_to_bus() -> None:
+    """Events emitted by an emitter with a bus are queued to its subscriptions."""
+    bus = EventBus("::")
+    emitter: EventEmitter[str] = EventEmitter(sep="::", bus=bus)
+    subscription = bus.subscribe("task::**")
+    await emitter.emit("task::write::Finished", "done")
+    assert await asyncio.wait_for(subscription.next_event(), 1) == ("task::write::Finished", "done")
+
+
+def test_journal_replay(tmp_path: Path) -> None:
+    """Replay filters the recorded events by pattern, time and count, and skips truncated lines."""
+    journal = EventJournal(tmp_path / "runs" / "events.jsonl", "::")
+    journal.record("task::write::Finished", {"output": "draft"})
+    since = time.time()
+    journal.record("task::review::Failed", ["too short"])
+    journal.record("task::write::Failed", object())
+    with (tmp_path / "runs" / "events.jsonl").open("a") as file:
+        file.write('{"timestamp": "2026-')
+
+    entries = journal.replay()
+    assert [entry.event for entry in entries] == [
+        "task::write::Finished",
+        "task::review::Failed",
+        "task::write::Failed",
+    ]
+    assert entries[0].payload == {"output": "draft"}
+    assert entries[2].payload.startswith("<object object")
+
+    assert [entry.event for entry in journal.replay("task::*::Failed")] == [
+        "task::review::Failed",
+        "task::write::Failed",
+    ]
+    assert [entry.event for entry in journal.replay(since=since, limit=1)] == ["task::write::Failed"]
+    assert [entry.event for entry in journal.replay("task::write::*", since=since)] == ["task::write::Failed"]
+    assert journal.replay(since=time.time() + 60) == []
+
+
+def test_journal_recovers_from_truncated_line(tmp_path: Path) -> None:
+    """A line cut in the middle of a character is skipped, and reopening starts a new line."""
+    path = tmp_path / "events.jsonl"
+    EventJournal(path, "::").record("task::write::Finished", "caf\u00e9")
+    with path.open("ab") as file:
+        file.write('{"timestamp": "2026-", "event": "caf\u00e9'.encode()[:-1])
+
+    journal = EventJournal(path, "::")
+    journal.record("task::write::Failed")
+    assert [(entry.event, entry.payload) for entry in journal.replay()] == [
+        ("task::write::Finished", "caf\u00e9"),
+        ("task::write::Failed", None),
+    ]
+    assert path.read_bytes().count(b"\n") == 3
+
+
+@pytest.mark.asyncio
+async def test_emitter_records_to_journal(tmp_path: Path) -> None:
+    """Events emitted by an emitter with a journal are recorded with their payload."""
+    journal = EventJournal(tmp_path / "events.jsonl", "::")
+    emitter: EventEmitter[str] = EventEmitter(sep="::", journal=journal)
+    await emitter.emit("task::write::Finished", "done")
+    [entry] = journal.replay()
+    assert (entry.event, entry.payload) == ("task::write::Finished", "done")
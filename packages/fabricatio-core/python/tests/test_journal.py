@@ -0,0 +1,33 @@
+"""Tests for the log context attached to records through `logger.context`."""
+
+import asyncio
+
+import pytest
+from fabricatio_core.journal import logger
+from fabricatio_core.rust import LogContext
+
+
+def test_context_nests() -> None:
+    """Contexts nest and restore the outer fields on exit, even when raising."""
+    with logger.context(task="outer", attempt=1) as outer:
+        assert isinstance(outer, LogContext)
+        logger.info("in outer context")
+        with pytest.raises(RuntimeError), logger.context(task="inner"):
+            logger.info("in inner context")
+            raise RuntimeError
+        logger.info("back in outer context")
+    logger.info("without context")
+
+
+def test_context_follows_asyncio_tasks() -> None:
+    """Concurrent tasks keep their own context."""
+
+    async def run(name: str) -> None:
+        with logger.context(task=name):
+            await asyncio.sleep(0)
+            logger.info(f"running {name}")
+
+    async def main() -> None:
+        await asyncio.gather(*(run(f"task-{i}") for i in range(4)))
+
+    asyncio.run(main())
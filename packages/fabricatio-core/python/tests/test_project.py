@@ -0,0 +1,43 @@
+"""Tests for the detection of the project a path belongs to."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_core.rust import detect_project
+
+
+def test_detect_project_kinds(tmp_path: Path) -> None:
+    """Every manifest of the nearest root counts, and metadata comes from the first one declaring it."""
+    (tmp_path / "pyproject.toml").write_text('[project]\nname = "demo"\nversion = "0.3.0"\n')
+    (tmp_path / "Cargo.toml").write_text('[package]\nname = "demo-rs"\ndescription = "Native part"\n')
+    (tmp_path / "src" / "demo").mkdir(parents=True)
+    (tmp_path / "src" / "demo" / "main.py").write_text("print()")
+
+    project = detect_project(tmp_path / "src" / "demo" / "main.py")
+    assert project is not None
+    assert project.root == tmp_path.resolve()
+    assert project.kinds == ["python", "rust"]
+    assert project.manifests == [tmp_path.resolve() / "pyproject.toml", tmp_path.resolve() / "Cargo.toml"]
+    assert (project.name, project.version, project.description) == ("demo", "0.3.0", "Native part")
+    assert "__pycache__/" in project.ignore
+    assert "/target/" in project.ignore
+
+
+def test_detect_project_nearest(tmp_path: Path) -> None:
+    """The nearest manifest wins, invalid ones still count, and the search stops at the boundary."""
+    (tmp_path / "pyproject.toml").write_text('[project]\nname = "outer"\n')
+    frontend = tmp_path / "frontend"
+    frontend.mkdir()
+    (frontend / "package.json").write_text('{"name": "web", ')
+
+    project = detect_project(frontend)
+    assert project is not None
+    assert project.kinds == ["node"]
+    assert project.name is None
+    assert "node_modules/" in project.ignore
+
+    (frontend / "package.json").unlink()
+    assert detect_project(frontend, boundary=frontend) is None
+    assert detect_project(frontend).name == "outer"
+    with pytest.raises(FileNotFoundError):
+        detect_project(tmp_path / "missing")
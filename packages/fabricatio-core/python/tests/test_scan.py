@@ -0,0 +1,153 @@
+"""Tests for version and dependency queries of installed packages."""
+
+import os
+import shutil
+import sysconfig
+import time
+from collections.abc import Callable
+from importlib.metadata import entry_points, requires, version
+from pathlib import Path
+
+import pytest
+from packaging.requirements import Requirement
+from packaging.utils import canonicalize_name
+from fabricatio_core.rust import (
+    dependency_tree,
+    get_console_scripts,
+    get_version,
+    is_installed,
+    list_entry_points,
+    refresh_installed,
+    reverse_dependencies,
+    unwatch_installed,
+    version_satisfies,
+    watch_installed,
+)
+
+
+def test_get_version() -> None:
+    """The version matches the one reported by importlib."""
+    assert get_version("pytest") == version("pytest")
+    assert get_version("not-an-installed-package") is None
+
+
+@pytest.mark.parametrize(
+    ("specifiers", "expected"),
+    [(">=1", True), ("<1", False), (f"=={version('pytest')}", True), (">=1,!=" + version("pytest"), False)],
+)
+def test_version_satisfies(specifiers: str, expected: bool) -> None:
+    """Specifiers are matched with PEP 440 semantics."""
+    assert version_satisfies("pytest", specifiers) is expected
+
+
+def test_version_satisfies_missing_package() -> None:
+    """A missing package satisfies no specifiers."""
+    assert version_satisfies("not-an-installed-package", ">=0") is False
+
+
+def test_version_satisfies_invalid_specifiers() -> None:
+    """Invalid specifiers are rejected."""
+    with pytest.raises(ValueError, match="Invalid version specifiers"):
+        version_satisfies("pytest", "~=banana")
+
+
+def test_dependency_tree() -> None:
+    """The tree lists the requirements applying to the running interpreter."""
+    root = dependency_tree("pytest", depth=1)
+    assert root is not None
+    assert root.version == version("pytest")
+    requirements = [Requirement(req) for req in requires("pytest") or []]
+    expected = {canonicalize_name(req.name) for req in requirements if req.marker is None or req.marker.evaluate()}
+    assert {child.name for child in root.children} == expected
+    assert all(not child.children for child in root.children)
+    assert dependency_tree("not-an-installed-package") is None
+
+
+def test_reverse_dependencies() -> None:
+    """Packages requiring a package are found."""
+    assert any(package == "pytest" and extra is None for package, _, extra in reverse_dependencies("pluggy"))
+
+
+def test_get_console_scripts() -> None:
+    """Console scripts match those reported by importlib."""
+    scripts = get_console_scripts()
+    assert scripts["pytest"] == entry_points(group="console_scripts")["pytest"].value
+
+
+def test_list_entry_points() -> None:
+    """Entry points of a group are parsed into their parts."""
+    plugins = list_entry_points("pytest11")
+    assert all(entry.group == "pytest11" for entry in plugins)
+    expected = {(ep.name, ep.module, ep.attr) for ep in entry_points(group="pytest11")}
+    assert {(entry.name, entry.module, entry.attr) for entry in plugins} == expected
+    pytest_entry = next(entry for entry in list_entry_points() if entry.name == "pytest")
+    assert pytest_entry.group == "console_scripts"
+    assert pytest_entry.module == "pytest"
+
+
+def wait_for(condition: Callable[[], bool], timeout: float = 5.0) -> bool:
+    """Polls a condition until it holds or the timeout expires."""
+    deadline = time.monotonic() + timeout
+    while not condition():
+        if time.monotonic() > deadline:
+            return False
+        time.sleep(0.05)
+    return True
+
+
+@pytest.fixture
+def site_packages() -> Path:
+    """The site-packages directory, skipping the test if it is read-only."""
+    path = Path(sysconfig.get_paths()["purelib"])
+    if not os.access(path, os.W_OK):
+        pytest.skip("site-packages is not writable")
+    return path
+
+
+def test_watch_installed(site_packages: Path) -> None:
+    """Packages installed and removed while watching are picked up without a refresh."""
+    dist_info = site_packages / "fabricatio_watch_probe-0.1.0.dist-info"
+    watch_installed()
+    watch_installed()
+    try:
+        dist_info.mkdir()
+        (dist_info / "METADATA").write_text("Name: fabricatio-watch-probe\nVersion: 0.1.0\n")
+        assert wait_for(lambda: get_version("fabricatio-watch-probe") == "0.1.0")
+        shutil.rmtree(dist_info)
+        assert wait_for(lambda: not is_installed("fabricatio-watch-probe"))
+    finally:
+        unwatch_installed()
+        shutil.rmtree(dist_info, ignore_errors=True)
+        refresh_installed()
+
+
+def test_development_installs(site_packages: Path, tmp_path: Path) -> None:
+    """Egg-info, egg-link and editable `.pth` records are detected."""
+    egg_info = site_packages / "fabricatio_egg_probe-1.2.0-py3.12.egg-info"
+    egg_link = site_packages / "fabricatio_link_probe.egg-link"
+    pth = site_packages / "__editable__.fabricatio_pth_probe-0.3.0.pth"
+    project = tmp_path / "link-probe"
+    project_egg_info = project / "fabricatio_link_probe.egg-info"
+    project_egg_info.mkdir(parents=True)
+    (project_egg_info / "PKG-INFO").write_text("Name: fabricatio-link-probe\nVersion: 0.9.0\n")
+    (project_egg_info / "requires.txt").write_text("pytest\n\n[extra]\nfabricatio-missing-probe\n")
+    try:
+        egg_info.mkdir()
+        (egg_info / "PKG-INFO").write_text("Name: fabricatio-egg-probe\n")
+        egg_link.write_text(f"{project}\n.\n")
+        pth.write_text(str(project))
+        refresh_installed()
+
+        assert get_version("fabricatio-egg-probe") == "1.2.0"
+        assert get_version("fabricatio-link-probe") == "0.9.0"
+        tree = dependency_tree("fabricatio-link-probe", depth=1)
+        assert tree is not None
+        assert {child.name for child in tree.children} == {"pytest"}
+        assert is_installed("fabricatio-pth-probe")
+        assert get_version("fabricatio-pth-probe") == "0.3.0"
+    finally:
+        shutil.rmtree(egg_info, ignore_errors=True)
+        egg_link.unlink(missing_ok=True)
+        pth.unlink(missing_ok=True)
+        refresh_installed()
+    assert not is_installed("fabricatio-link-probe")
@@ -0,0 +1,70 @@
+"""Tests for the expiry and rotation of secrets."""
+
+import sys
+import time
+
+import pytest
+from fabricatio_core.rust import CONFIG, SecretStr
+
+
+def test_plain_secret() -> None:
+    """Secrets without metadata never expire and cannot be rotated."""
+    secret = SecretStr("sk-plain")
+    assert secret.get_secret_value() == "sk-plain"
+    assert secret.expires_at is None
+    assert not secret.expires_within(10**9)
+    with pytest.raises(ValueError, match="rotation command"):
+        secret.rotate()
+    assert "sk-plain" not in repr(secret)
+
+
+def test_expires_within() -> None:
+    """Secrets report whether they expire within a window."""
+    secret = SecretStr("sk-short", expires_at=int(time.time()) + 60)
+    assert secret.expires_within(120)
+    assert not secret.expires_within(10)
+    assert SecretStr("sk-gone", expires_at=0).expires_within(0)
+
+
+def test_rotate_bare_output() -> None:
+    """A command printing the bare secret replaces the value and clears the expiry."""
+    secret = SecretStr("sk-old", expires_at=0, rotate_cmd="echo sk-new")
+    secret.rotate()
+    assert secret.get_secret_value() == "sk-new"
+    assert secret.expires_at is None
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="quoting differs in cmd")
+def test_rotate_json_output() -> None:
+    """A command printing JSON sets the value and the new expiry."""
+    secret = SecretStr("sk-old", expires_at=0, rotate_cmd="""echo '{"value": "sk-new", "expires_at": 42}'""")
+    secret.rotate()
+    assert secret.get_secret_value() == "sk-new"
+    assert secret.expires_at == 42
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="relies on a POSIX shell")
+def test_rotate_failure() -> None:
+    """Failing commands raise and leave the secret unchanged."""
+    secret = SecretStr("sk-old", expires_at=0, rotate_cmd="echo denied >&2; exit 3")
+    with pytest.raises(RuntimeError, match="denied"):
+        secret.rotate()
+    assert secret.get_secret_value() == "sk-old"
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="relies on a POSIX shell")
+def test_rotate_timeout() -> None:
+    """Commands running past the timeout are killed and leave the secret unchanged."""
+    secret = SecretStr("sk-old", expires_at=0, rotate_cmd="sleep 5; echo sk-new")
+    started = time.monotonic()
+    with pytest.raises(RuntimeError, match="timed out"):
+        secret.rotate(timeout=0.2)
+    assert time.monotonic() - started < 3
+    assert secret.get_secret_value() == "sk-old"
+    with pytest.raises(ValueError, match="Invalid timeout"):
+        secret.rotate(timeout=-1)
+
+
+def test_check_secrets() -> None:
+    """Reports only cover secrets close to their expiry."""
+    assert all(report.path.startswith("routing.providers[") for report in CONFIG.check_secrets(within=10**9))
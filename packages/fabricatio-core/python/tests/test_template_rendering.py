@@ -158,3 +158,243 @@ def test_render_template_error_handling(template_manager: TemplateManager) -> No
 
     with pytest.raises(RuntimeError):
         template_manager.render_template(template_name, data)
+
+
+def test_render_batch_strict(template_manager: TemplateManager, monkeypatch: pytest.MonkeyPatch) -> None:
+    """In strict mode, a failing item fails the batch and the error lists every failing item."""
+    monkeypatch.setenv("FABRICATIO_TEST_REGION", "eu-west")
+    template_manager.allow_env(["FABRICATIO_TEST_REGION"])
+    data = [{"var": "FABRICATIO_TEST_REGION"}, {"var": "FABRICATIO_TEST_SECRET"}, {"var": "FABRICATIO_TEST_OTHER"}]
+
+    with pytest.raises(RuntimeError, match="2 of 3 items failed to render") as info:
+        template_manager.render_template_raw("{{env var}}", data, strict=True)
+    assert [index for index, _ in info.value.failures] == [1, 2]
+    assert all("allowlist" in message for _, message in info.value.failures)
+
+
+def test_render_batch_lenient(template_manager: TemplateManager, monkeypatch: pytest.MonkeyPatch) -> None:
+    """Without strict mode, failing items are rendered as None and the others kept in place."""
+    monkeypatch.setenv("FABRICATIO_TEST_REGION", "eu-west")
+    template_manager.allow_env(["FABRICATIO_TEST_REGION"])
+    data = [{"var": "FABRICATIO_TEST_SECRET"}, {"var": "FABRICATIO_TEST_REGION"}]
+
+    assert template_manager.render_template_raw("{{env var}}", data, strict=False) == [None, "eu-west"]
+    assert template_manager.strict
+    template_manager.strict = False
+    try:
+        assert template_manager.render_template_raw("{{env var}}", data) == [None, "eu-west"]
+    finally:
+        template_manager.strict = True
+
+
+def test_env_helper(template_manager: TemplateManager, monkeypatch: pytest.MonkeyPatch) -> None:
+    """Only allowlisted environment variables are rendered, with an optional default."""
+    monkeypatch.setenv("FABRICATIO_TEST_REGION", "eu-west")
+    monkeypatch.setenv("FABRICATIO_TEST_TOKEN", "hidden")
+    monkeypatch.delenv("FABRICATIO_TEST_UNSET", raising=False)
+    template_manager.allow_env(["FABRICATIO_TEST_REGION", "FABRICATIO_TEST_UNSET"])
+    assert "FABRICATIO_TEST_REGION" in template_manager.env_allowlist
+
+    assert template_manager.render_template_raw('{{env "FABRICATIO_TEST_REGION"}}', {}) == "eu-west"
+    assert template_manager.render_template_raw('{{env "FABRICATIO_TEST_UNSET" "none"}}', {}) == "none"
+    with pytest.raises(RuntimeError, match="allowlist"):
+        template_manager.render_template_raw('{{env "FABRICATIO_TEST_TOKEN"}}', {})
+
+
+def test_config_helper(template_manager: TemplateManager) -> None:
+    """Only allowlisted configuration keys and the keys below them are rendered."""
+    template_manager.allow_config(["template_manager"])
+    assert "template_manager" in template_manager.config_allowlist
+
+    assert template_manager.render_template_raw('{{config "template_manager.template_suffix"}}', {}) == "hbs"
+    assert template_manager.render_template_raw('{{config "template_manager.missing"}}', {}) == ""
+    with pytest.raises(RuntimeError, match="allowlist"):
+        template_manager.render_template_raw('{{config "template_manager_extra"}}', {})
+    with pytest.raises(RuntimeError, match="allowlist"):
+        template_manager.render_template_raw('{{config "llm.api_key"}}', {})
+
+
+def test_layout_inheritance(template_manager: TemplateManager, tmp_path: Path) -> None:
+    """Templates extending a layout across stores override its blocks and keep the others."""
+    base_store = tmp_path / "layouts"
+    prompt_store = tmp_path / "prompts"
+    base_store.mkdir()
+    prompt_store.mkdir()
+    (base_store / "layout_base.hbs").write_text(
+        'You are {{role}}.\n{{#block "task"}}No task.{{/block}}\n{{#block "footer"}}Be concise.{{/block}}'
+    )
+    (prompt_store / "layout_child.hbs").write_text(
+        '{{#extend "layout_base"}}{{#block "task"}}Summarize {{text}}.{{/block}}{{/extend}}'
+    )
+    (prompt_store / "layout_grandchild.hbs").write_text(
+        '{{#extend "layout_child"}}{{#block "footer"}}Answer in {{lang}}.{{/block}}{{/extend}}'
+    )
+    (prompt_store / "layout_cycle.hbs").write_text('{{#extend "layout_cycle"}}{{/extend}}')
+    template_manager.add_stores([base_store, prompt_store], rediscovery=True)
+
+    data = {"role": "an editor", "text": "the notes", "lang": "French"}
+    assert template_manager.render_template("layout_base", data) == "You are an editor.\nNo task.\nBe concise."
+    child = template_manager.render_template("layout_child", data)
+    assert child == "You are an editor.\nSummarize the notes.\nBe concise."
+    assert (
+        template_manager.render_template("layout_grandchild", data)
+        == "You are an editor.\nSummarize the notes.\nAnswer in French."
+    )
+    with pytest.raises(RuntimeError):
+        template_manager.render_template("layout_cycle", data)
+
+
+def test_partials(template_manager: TemplateManager, tmp_path: Path) -> None:
+    """Partials of the `partials/` subdirectory of a store and from Python are included by name."""
+    store = tmp_path / "partial_store"
+    (store / "partials").mkdir(parents=True)
+    (store / "partials" / "partial_header.hbs").write_text("# {{title}}")
+    (store / "partial_page.hbs").write_text("{{> partial_header}} / {{> partial_footer}}")
+    template_manager.register_partial("partial_footer", "by {{author}}")
+    template_manager.add_store(store, rediscovery=True)
+
+    data = {"title": "Notes", "author": "Ada"}
+    assert template_manager.render_template("partial_page", data) == "# Notes / by Ada"
+    template_manager.register_partial("partial_footer", "-- {{author}}").discover_templates()
+    assert template_manager.render_template("partial_page", data) == "# Notes / -- Ada"
+    with pytest.raises(ValueError, match="partial_broken"):
+        template_manager.register_partial("partial_broken", "{{#if}}")
+
+
+def test_register_template(template_manager: TemplateManager) -> None:
+    """Templates registered from Python render by name, survive rediscovery and can be unregistered."""
+    template_manager.register_template("generated_greeting", "Hi {{name}}")
+    assert template_manager.render_template("generated_greeting", {"name": "Ada"}) == "Hi Ada"
+    assert template_manager.render_template("generated_greeting", [{"name": "Ada"}, {"name": "Alan"}]) == [
+        "Hi Ada",
+        "Hi Alan",
+    ]
+    template_manager.discover_templates()
+    assert template_manager.render_template("generated_greeting", {"name": "Ada"}) == "Hi Ada"
+
+    assert template_manager.unregister_template("generated_greeting")
+    assert not template_manager.unregister_template("generated_greeting")
+    template_manager.discover_templates()
+    with pytest.raises(RuntimeError):
+        template_manager.render_template("generated_greeting", {"name": "Ada"})
+    with pytest.raises(ValueError, match="generated_broken"):
+        template_manager.register_template("generated_broken", "{{#each}}")
+
+
+def test_template_variables(template_manager: TemplateManager) -> None:
+    """Template variables list root paths, helpers and partials, leaving out those relative to blocks."""
+    template_manager.register_partial("lint_footer", "-- {{author.name}}")
+    template_manager.register_template(
+        "lint_page",
+        "{{title}} {{#if draft}}{{status}}{{/if}} {{#each items as |item|}}{{item.label}}{{@root.unit}}{{/each}}"
+        " {{join tags ', '}} {{> lint_footer}}",
+    )
+    variables = template_manager.template_variables("lint_page")
+    assert variables.variables == ["author.name", "items", "tags", "title"]
+    assert variables.optional == ["draft", "status", "unit"]
+    assert variables.helpers == ["each", "if", "join"]
+    assert variables.partials == ["lint_footer"]
+    with pytest.raises(RuntimeError):
+        template_manager.template_variables("lint_missing")
+
+
+def test_validate_data(template_manager: TemplateManager) -> None:
+    """Validating data reports the required variables it lacks, sorted."""
+    template_manager.register_template("lint_report", "{{title}} by {{author.name}}{{#if draft}} ({{status}}){{/if}}")
+    assert template_manager.validate_data("lint_report", {"title": "Q3"}) == ["author.name"]
+    assert template_manager.validate_data("lint_report", {"author": {"name": None}}) == ["title"]
+    assert template_manager.validate_data("lint_report", {"title": "Q3", "author": {"name": "Ada"}}) == []
+
+
+@pytest.mark.asyncio
+async def test_render_template_async(template_manager: TemplateManager) -> None:
+    """Async rendering gives the same results as the blocking one and raises when awaited."""
+    template_manager.register_template("async_greeting", "Hi {{name}}")
+    assert await template_manager.render_template_async("async_greeting", {"name": "Ada"}) == "Hi Ada"
+    data = [{"name": "Ada"}, {"name": "Alan"}]
+    assert await template_manager.render_template_async("async_greeting", data) == ["Hi Ada", "Hi Alan"]
+    with pytest.raises(RuntimeError):
+        await template_manager.render_template_async("async_missing", {"name": "Ada"})
+
+
+def test_register_helper(template_manager: TemplateManager) -> None:
+    """Python helpers receive the params and hash, render their result and are known to the linter."""
+    template_manager.register_helper("py_shout", lambda text, mark="!": text.upper() + mark)
+    template_manager.register_helper("py_items", lambda n: list(range(n)))
+    assert template_manager.render_template_raw("{{py_shout name mark='?'}}", {"name": "ada"}) == "ADA?"
+    assert template_manager.render_template_raw("{{py_shout name}}", [{"name": "ada"}, {"name": "al"}]) == [
+        "ADA!",
+        "AL!",
+    ]
+    assert template_manager.render_template_raw("{{len (py_items 3)}}", {}) == "3"
+
+    template_manager.discover_templates()
+    template_manager.register_template("py_helper_page", "{{py_shout title}}")
+    assert template_manager.render_template("py_helper_page", {"title": "q3"}) == "Q3!"
+    assert template_manager.template_variables("py_helper_page").helpers == ["py_shout"]
+
+    template_manager.register_helper("py_fail", lambda: 1 / 0)
+    with pytest.raises(RuntimeError, match="py_fail"):
+        template_manager.render_template_raw("{{py_fail}}", {})
+    with pytest.raises(TypeError):
+        template_manager.register_helper("py_not_callable", 42)
+
+
+def test_text_helpers(template_manager: TemplateManager) -> None:
+    """The text helpers truncate, replace, pretty-print, tabulate and convert case."""
+    text = "The quick brown fox jumps over the lazy dog"
+    assert template_manager.render_template_raw("{{truncate text 5}}", {"text": text}) == "The quick brown..."
+    assert template_manager.render_template_raw("{{truncate text 100}}", {"text": text}) == text
+    assert template_manager.render_template_raw('{{replace text "o(\\\\w)" "0$1"}}', {"text": text}) == (
+        "The quick br0wn f0x jumps 0ver the lazy d0g"
+    )
+    assert template_manager.render_template_raw("{{json v}}", {"v": {"a": [1]}}) == '{\n  "a": [\n    1\n  ]\n}'
+    rows = [{"name": "Ada", "age": 36}, {"name": "Alan|T"}]
+    assert template_manager.render_template_raw("{{table rows}}", {"rows": rows}) == (
+        "| age | name |\n| --- | --- |\n| 36 | Ada |\n|  | Alan\\|T |"
+    )
+    assert template_manager.render_template_raw("{{table rows columns='name'}}", {"rows": rows}) == (
+        "| name |\n| --- |\n| Ada |\n| Alan\\|T |"
+    )
+    cases = template_manager.render_template_raw("{{snake_case v}} {{camel_case v}}", {"v": "HTTPRequest parserName"})
+    assert cases == "http_request_parser_name httpRequestParserName"
+    with pytest.raises(RuntimeError):
+        template_manager.render_template_raw('{{replace text "(" "x"}}', {"text": text})
+
+
+def test_template_variants(template_manager: TemplateManager, tmp_path: Path) -> None:
+    """Files named `name@variant` register variants of `name`, the bare file being the `default` one."""
+    store = tmp_path / "variant_store"
+    store.mkdir()
+    (store / "variant_greet.hbs").write_text("Hello {{name}}")
+    (store / "variant_greet@formal.hbs").write_text("Good day, {{name}}")
+    (store / "variant_greet@casual.hbs").write_text("Hey {{name}}")
+    (store / "variant_greeting@other.hbs").write_text("Not a variant of variant_greet")
+    template_manager.add_store(store, rediscovery=True)
+
+    assert template_manager.template_variants("variant_greet") == ["casual", "default", "formal"]
+    assert template_manager.template_variants("variant_missing") == []
+    assert template_manager.render_template("variant_greet@formal", {"name": "Ada"}) == "Good day, Ada"
+    assert template_manager.render_variant("variant_greet", {"name": "Ada"}, "formal") == ("formal", "Good day, Ada")
+    assert template_manager.render_variant("variant_greet", {"name": "Ada"}, "default") == ("default", "Hello Ada")
+    with pytest.raises(RuntimeError, match="variant_greet@polite"):
+        template_manager.render_variant("variant_greet", {"name": "Ada"}, "polite")
+    with pytest.raises(RuntimeError, match="no variants"):
+        template_manager.render_variant("variant_missing", {"name": "Ada"})
+
+
+def test_weighted_variant_picking(template_manager: TemplateManager) -> None:
+    """Variants absent from the weights count once, and variants without a positive weight are never picked."""
+    template_manager.register_template("picked", "default {{name}}")
+    template_manager.register_template("picked@a", "a {{name}}")
+    template_manager.register_template("picked@b", "b {{name}}")
+
+    picked = {template_manager.render_variant("picked", {"name": "Ada"})[0] for _ in range(200)}
+    assert picked == {"default", "a", "b"}
+    picked = {template_manager.render_variant("picked", {}, weights={"a": 0, "b": -1})[0] for _ in range(50)}
+    assert picked == {"default"}
+    picked = {template_manager.render_variant("picked", {}, weights={"default": 0.0, "a": 5.0})[0] for _ in range(200)}
+    assert picked <= {"a", "b"}
+    assert "a" in picked
+    with pytest.raises(RuntimeError, match="no variants"):
+        template_manager.render_variant("picked", {}, weights={"default": 0, "a": 0, "b": 0})
@@ -0,0 +1,41 @@
+"""Tests for the sentence and paragraph segmentation."""
+
+from fabricatio_core.rust import split_into_chunks, split_paragraphs, split_sentences
+
+
+def test_split_sentences_keeps_abbreviations() -> None:
+    """Periods after abbreviations and initials do not end sentences."""
+    text = "Dr. Smith met J. R. Tolkien at 5 p.m. today. They talked, e.g. about maps. Then they left."
+    assert split_sentences(text, "en") == [
+        "Dr. Smith met J. R. Tolkien at 5 p.m. today.",
+        "They talked, e.g. about maps.",
+        "Then they left.",
+    ]
+    assert split_sentences("Wir sahen z.B. Berge. Dann gingen wir.", "de-DE") == [
+        "Wir sahen z.B. Berge.",
+        "Dann gingen wir.",
+    ]
+
+
+def test_split_sentences_cjk() -> None:
+    """CJK terminators end sentences without spaces, closing quotes included."""
+    assert split_sentences("他说：“你好。”然后走了！真的吗？是的。") == [
+        "他说：“你好。”",
+        "然后走了！",
+        "真的吗？",
+        "是的。",
+    ]
+
+
+def test_split_paragraphs() -> None:
+    """Blank lines and ideographic indents separate paragraphs."""
+    text = "First line\nstill first.\n\n \nSecond.\n　第三段。\n　第四段。\n"
+    assert split_paragraphs(text) == ["First line\nstill first.", "Second.", "第三段。", "第四段。"]
+
+
+def test_chunks_do_not_split_abbreviations() -> None:
+    """Chunks are made of the same sentences and join back into the text."""
+    text = "Mr. Brown arrived. Mrs. Brown stayed home. The end."
+    chunks = split_into_chunks(text, 5, max_overlapping_rate=0.0)
+    assert "".join(chunks) == text
+    assert all(not chunk.rstrip().endswith("Mr.") for chunk in chunks)
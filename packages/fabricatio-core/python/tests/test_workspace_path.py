@@ -0,0 +1,92 @@
+"""Tests for the confinement of paths to a workspace root."""
+
+import os
+import sys
+from pathlib import Path
+
+import pytest
+from fabricatio_core.rust import confine, long_path, relativize
+
+
+@pytest.fixture
+def root(tmp_path: Path) -> Path:
+    """Fixture to create a workspace root holding a file and a directory."""
+    root = tmp_path / "workspace"
+    (root / "src").mkdir(parents=True)
+    (root / "src" / "main.py").write_text("print()")
+    return root
+
+
+def test_confine_inside(root: Path) -> None:
+    """Paths below the root resolve to canonical paths, whether they exist or not."""
+    canonical = root.resolve()
+    assert confine(root, "src/main.py") == canonical / "src" / "main.py"
+    assert confine(root, "src/../src/new/file.txt") == canonical / "src" / "new" / "file.txt"
+    assert confine(root, canonical / "src") == canonical / "src"
+    assert confine(root, ".") == canonical
+
+
+def test_confine_outside(root: Path) -> None:
+    """Paths climbing out of the root or lying elsewhere are refused."""
+    with pytest.raises(PermissionError, match="escapes"):
+        confine(root, "../outside.txt")
+    with pytest.raises(PermissionError, match="escapes"):
+        confine(root, "missing/../../outside.txt")
+    with pytest.raises(PermissionError):
+        confine(root, root.parent / "outside.txt")
+    with pytest.raises(FileNotFoundError):
+        confine(root / "nowhere", "file.txt")
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="creating symlinks needs privileges")
+def test_confine_symlinks(root: Path, tmp_path: Path) -> None:
+    """Symlinks are followed before checking, and dangling ones are refused."""
+    outside = tmp_path / "outside"
+    outside.mkdir()
+    os.symlink(outside, root / "escape")
+    os.symlink(root / "src", root / "alias")
+    os.symlink(tmp_path / "nowhere", root / "dangling")
+
+    with pytest.raises(PermissionError):
+        confine(root, "escape/secret.txt")
+    assert confine(root, "alias/main.py") == root.resolve() / "src" / "main.py"
+    with pytest.raises(FileNotFoundError):
+        confine(root, "dangling")
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="creating symlinks needs privileges")
+def test_confine_symlinks_after_missing(root: Path, tmp_path: Path) -> None:
+    """Symlinks reached back through `..` after a missing component are still followed."""
+    outside = tmp_path / "outside"
+    outside.mkdir()
+    (outside / "secret.txt").write_text("secret")
+    os.symlink(outside, root / "escape")
+    os.symlink(outside, root / "src" / "parent")
+
+    with pytest.raises(PermissionError):
+        confine(root, "missing/../escape/secret.txt")
+    with pytest.raises(PermissionError):
+        confine(root, "missing/../escape/new.txt")
+    with pytest.raises(PermissionError):
+        confine(root, "src/missing/../parent/secret.txt")
+    assert confine(root, "missing/../src/main.py") == root.resolve() / "src" / "main.py"
+
+
+def test_relativize(root: Path) -> None:
+    """Paths are made relative to the root and normalized."""
+    assert relativize(root, root / "src" / "main.py") == Path("src/main.py")
+    assert relativize(root, "src/./new/../main.py") == Path("src/main.py")
+    assert relativize(root, root) == Path()
+    with pytest.raises(PermissionError):
+        relativize(root, "../outside.txt")
+    with pytest.raises(PermissionError):
+        relativize(root, root.parent / "outside.txt")
+
+
+def test_long_path(root: Path) -> None:
+    """Long paths are verbatim on Windows only, and relative paths are left alone."""
+    assert long_path("relative/file.txt") == Path("relative/file.txt")
+    if sys.platform == "win32":
+        assert str(long_path(root)).startswith("\\\\?\\")
+    else:
+        assert long_path(root) == root
@@ -0,0 +1,411 @@
+//! An event bus delivering published events to the queues of subscriptions.
+//!
+//! Unlike the handlers of the emitter, which run as the event is emitted, subscriptions are
+//! consumed at their own pace by awaiting `next_event`, so a consumer such as a websocket
+//! waits for events instead of polling for them. Each subscription has a bounded queue, and
+//! its overflow policy decides what happens once it is full: `drop_oldest` makes room by
+//! dropping the oldest event and counts it, while `block` makes publishing wait until the
+//! consumer catches up, slowing the publisher down to the pace of the slowest consumer.
+//! Patterns are matched by the topic trie, with the same `*` and `**` wildcards.
+
+use crate::topics::TopicTrie;
+use fabricatio_config::CONFIG;
+use futures::future::join_all;
+use pyo3::exceptions::{PyStopAsyncIteration, PyValueError};
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use strum::{Display, EnumString};
+use tokio::sync::Notify;
+
+/// What a full queue does with a new event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+enum Overflow {
+    /// Drops the oldest event to make room.
+    DropOldest,
+    /// Waits for the consumer to make room.
+    Block,
+}
+
+/// An event as delivered to a subscription.
+type Delivery = (String, Py<PyAny>);
+
+/// The events and the state of a queue, guarded together.
+struct Slots<T> {
+    items: VecDeque<T>,
+    closed: bool,
+}
+
+/// A bounded queue of events, awaited by consumers and, when blocking, by publishers.
+struct Queue<T> {
+    slots: Mutex<Slots<T>>,
+    capacity: usize,
+    overflow: Overflow,
+    /// Notified when an event is pushed or the queue is closed
+    readable: Notify,
+    /// Notified when an event is popped or the queue is closed
+    writable: Notify,
+    dropped: AtomicU64,
+}
+
+impl<T> Queue<T> {
+    fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            slots: Mutex::new(Slots {
+                items: VecDeque::with_capacity(capacity.min(1024)),
+                closed: false,
+            }),
+            capacity,
+            overflow,
+            readable: Notify::new(),
+            writable: Notify::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn slots(&self) -> MutexGuard<'_, Slots<T>> {
+        self.slots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Pushes an event, waiting for room if the queue blocks.
+    ///
+    /// Returns:
+    ///     Whether the event was queued, false if the queue is closed.
+    async fn push(&self, item: T) -> bool {
+        loop {
+            {
+                let mut slots = self.slots();
+                if slots.closed {
+                    return false;
+                }
+                if slots.items.len() >= self.capacity && self.overflow == Overflow::DropOldest {
+                    slots.items.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                if slots.items.len() < self.capacity {
+                    slots.items.push_back(item);
+                    // Wake another blocked publisher if there is still room
+                    if slots.items.len() < self.capacity {
+                        self.writable.notify_one();
+                    }
+                    self.readable.notify_one();
+                    return true;
+                }
+            }
+            // A permit is stored if room was made in between, so the wakeup is not lost
+            self.writable.notified().await;
+        }
+    }
+
+    /// Pops the oldest event, waiting for one if the queue is empty.
+    ///
+    /// Returns:
+    ///     The event, or None once the queue is closed and drained.
+    async fn pop(&self) -> Option<T> {
+        loop {
+            {
+                let mut slots = self.slots();
+                if let Some(item) = slots.items.pop_front() {
+                    if !slots.items.is_empty() {
+                        self.readable.notify_one();
+                    }
+                    self.writable.notify_one();
+                    return Some(item);
+                }
+                if slots.closed {
+                    return None;
+                }
+            }
+            self.readable.notified().await;
+        }
+    }
+
+    /// Closes the queue, waking everyone waiting on it. Queued events can still be popped.
+    fn close(&self) {
+        self.slots().closed = true;
+        for notify in [&self.readable, &self.writable] {
+            notify.notify_waiters();
+            notify.notify_one();
+        }
+    }
+}
+
+/// The subscriptions of a bus and the patterns matching them.
+struct Subscribers {
+    topics: TopicTrie,
+    queues: HashMap<u64, Arc<Queue<Delivery>>>,
+}
+
+/// The state of a bus, shared with its subscriptions.
+struct Shared {
+    subscribers: Mutex<Subscribers>,
+}
+
+impl Shared {
+    fn subscribers(&self) -> MutexGuard<'_, Subscribers> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Delivers published events to the bounded queues of the subscriptions matching them.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(frozen)]
+pub struct EventBus {
+    shared: Arc<Shared>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl EventBus {
+    /// Creates a bus without subscriptions.
+    ///
+    /// Args:
+    ///     delimiter: The delimiter of the segments of event names, the `emitter.delimiter`
+    ///         config value if None.
+    ///
+    /// Raises:
+    ///     ValueError: If the delimiter is empty.
+    #[new]
+    #[pyo3(signature = (delimiter = None))]
+    fn new(delimiter: Option<String>) -> PyResult<Self> {
+        Ok(Self {
+            shared: Arc::new(Shared {
+                subscribers: Mutex::new(Subscribers {
+                    topics: TopicTrie::new(delimiter)?,
+                    queues: HashMap::new(),
+                }),
+            }),
+        })
+    }
+
+    /// The delimiter of the segments of event names.
+    #[getter]
+    fn delimiter(&self) -> String {
+        self.shared.subscribers().topics.delimiter().to_string()
+    }
+
+    /// Subscribes to the events matching a pattern.
+    ///
+    /// Args:
+    ///     pattern: The pattern, such as `task::*::Finished`, every event by default.
+    ///     capacity: Number of events the subscription holds before its overflow policy
+    ///         applies, the `emitter.queue_capacity` config value if None.
+    ///     overflow: `drop_oldest` to drop the oldest event of a full subscription, or `block`
+    ///         to make publishers wait for room, the `emitter.overflow` config value if None.
+    ///
+    /// Returns:
+    ///     The subscription, receiving the events published from now on until it is closed.
+    ///
+    /// Raises:
+    ///     ValueError: If the pattern is empty, the capacity is zero or the overflow policy
+    ///         is unknown.
+    #[pyo3(signature = (pattern = "**", capacity = None, overflow = None))]
+    fn subscribe(
+        &self,
+        pattern: &str,
+        capacity: Option<usize>,
+        overflow: Option<&str>,
+    ) -> PyResult<EventSubscription> {
+        let capacity = capacity.unwrap_or(CONFIG.emitter.queue_capacity);
+        if capacity == 0 {
+            return Err(PyValueError::new_err("The capacity must be at least 1"));
+        }
+        let overflow = overflow.unwrap_or(CONFIG.emitter.overflow.as_str());
+        let overflow = Overflow::from_str(overflow).map_err(|_| {
+            PyValueError::new_err(format!(
+                "Unknown overflow policy `{overflow}`, expected `drop_oldest` or `block`"
+            ))
+        })?;
+
+        let queue = Arc::new(Queue::new(capacity, overflow));
+        let mut subscribers = self.shared.subscribers();
+        let id = subscribers.topics.subscribe(pattern)?;
+        subscribers.queues.insert(id, queue.clone());
+        Ok(EventSubscription {
+            shared: self.shared.clone(),
+            id,
+            pattern: pattern.to_string(),
+            queue,
+        })
+    }
+
+    /// Publishes an event to the subscriptions matching it.
+    ///
+    /// Args:
+    ///     event: The name of the event, such as `task::write::Finished`.
+    ///     data: The data delivered with the event, shared by every subscription.
+    ///
+    /// Returns:
+    ///     An awaitable resolving to the number of subscriptions the event was queued to,
+    ///     once every blocking subscription had room for it.
+    #[pyo3(signature = (event, data = None))]
+    fn publish<'py>(
+        &self,
+        python: Python<'py>,
+        event: String,
+        data: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let data = data.unwrap_or_else(|| python.None());
+        let deliveries: Vec<_> = {
+            let subscribers = self.shared.subscribers();
+            subscribers
+                .topics
+                .matches(&event)
+                .into_iter()
+                .filter_map(|id| subscribers.queues.get(&id).cloned())
+                .map(|queue| (queue, (event.clone(), data.clone_ref(python))))
+                .collect()
+        };
+        future_into_py(python, async move {
+            let queued = join_all(
+                deliveries
+                    .into_iter()
+                    .map(|(queue, delivery)| async move { queue.push(delivery).await }),
+            )
+            .await;
+            Ok(queued.into_iter().filter(|queued| *queued).count())
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.shared.subscribers().queues.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let subscribers = self.shared.subscribers();
+        format!(
+            "EventBus(delimiter={:?}, subscriptions={})",
+            subscribers.topics.delimiter(),
+            subscribers.queues.len()
+        )
+    }
+}
+
+/// The events of a bus matching a pattern, consumed with `next_event` or `async for`.
+///
+/// The subscription is closed by `close`, or when it is garbage collected.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(frozen)]
+pub struct EventSubscription {
+    shared: Arc<Shared>,
+    id: u64,
+    /// The pattern of the subscription.
+    #[pyo3(get)]
+    pattern: String,
+    queue: Arc<Queue<Delivery>>,
+}
+
+impl EventSubscription {
+    fn next<'py>(&self, python: Python<'py>, stop: bool) -> PyResult<Bound<'py, PyAny>> {
+        let queue = self.queue.clone();
+        future_into_py(python, async move {
+            match queue.pop().await {
+                Some(delivery) => Ok(Some(delivery)),
+                None if stop => Err(PyStopAsyncIteration::new_err(())),
+                None => Ok(None),
+            }
+        })
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl EventSubscription {
+    /// Waits for the next event of the subscription.
+    ///
+    /// Returns:
+    ///     An awaitable resolving to the name and the data of the oldest queued event, or to
+    ///     None once the subscription is closed and its queued events consumed.
+    fn next_event<'py>(&self, python: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.next(python, false)
+    }
+
+    /// Stops receiving events and releases the publishers waiting for room. The events
+    /// already queued can still be consumed.
+    fn close(&self) {
+        {
+            let mut subscribers = self.shared.subscribers();
+            // The pattern was valid when subscribing
+            let _ = subscribers.topics.remove(&self.pattern, self.id);
+            subscribers.queues.remove(&self.id);
+        }
+        self.queue.close();
+    }
+
+    /// Whether the subscription is closed.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.queue.slots().closed
+    }
+
+    /// Number of events queued and not consumed yet.
+    #[getter]
+    fn pending(&self) -> usize {
+        self.queue.slots().items.len()
+    }
+
+    /// Number of events dropped because the subscription was full.
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of events the subscription holds before its overflow policy applies.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.queue.capacity
+    }
+
+    /// The overflow policy, `drop_oldest` or `block`.
+    #[getter]
+    fn overflow(&self) -> String {
+        self.queue.overflow.to_string()
+    }
+
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, python: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.next(python, true)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EventSubscription(pattern={:?}, pending={}, dropped={})",
+            self.pattern,
+            self.pending(),
+            self.dropped()
+        )
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Registers the event bus with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<EventBus>()?;
+    m.add_class::<EventSubscription>()?;
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use fabricatio_constants::CAS;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use utils::cas;
+
+/// Raises malformed hashes as `ValueError`, other errors as the matching `OSError`.
+fn to_pyerr(e: io::Error) -> PyErr {
+    match e.kind() {
+        io::ErrorKind::InvalidInput => PyValueError::new_err(e.to_string()),
+        _ => e.into(),
+    }
+}
+
+/// What a garbage collection of a `ContentStore` removed.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct GcReport {
+    /// Number of blobs kept because they are referenced or recently stored.
+    kept: usize,
+    /// Number of blobs removed.
+    removed: usize,
+    /// Bytes freed by removing the blobs.
+    reclaimed_bytes: u64,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl GcReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "GcReport(kept={}, removed={}, reclaimed_bytes={})",
+            self.kept, self.removed, self.reclaimed_bytes
+        )
+    }
+}
+
+/// A content-addressed store of blobs, keyed by the BLAKE3 hash of their content.
+///
+/// Packages keeping copies of files, such as the media of anki decks, cached rag documents or
+/// large files left out of checkpoints, store them here instead of each managing its own blob
+/// directory. Storing the same content twice stores it once, and blobs are written to a
+/// temporary file first, so a blob is never seen partially written.
+///
+/// Each package records the blobs it uses as references under its own owner name, which
+/// `gc` keeps.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(frozen)]
+pub struct ContentStore {
+    inner: cas::ContentStore,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl ContentStore {
+    /// Opens the store rooted at a directory, creating it if needed.
+    ///
+    /// Args:
+    ///     root: The directory of the store. Defaults to the `cas` directory of the roaming
+    ///         directory, shared by all packages.
+    #[new]
+    #[pyo3(signature = (root = None))]
+    fn new(root: Option<PathBuf>) -> PyResult<Self> {
+        Ok(Self {
+            inner: cas::ContentStore::open(root.unwrap_or_else(|| CAS.clone()))?,
+        })
+    }
+
+    /// The directory the store is rooted at.
+    #[getter]
+    fn root(&self) -> PathBuf {
+        self.inner.root().to_path_buf()
+    }
+
+    /// Stores a blob.
+    ///
+    /// Args:
+    ///     content: The content of the blob.
+    ///
+    /// Returns:
+    ///     The hex encoded BLAKE3 hash of the content, which retrieves it.
+    fn put_bytes(&self, python: Python, content: Vec<u8>) -> PyResult<String> {
+        Ok(python.detach(|| self.inner.put_bytes(&content))?)
+    }
+
+    /// Stores the content of a file, reading it in chunks.
+    ///
+    /// Args:
+    ///     path: The file to store.
+    ///
+    /// Returns:
+    ///     The hex encoded BLAKE3 hash of the content, the same `blake3_hash` gives.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    fn put_file(&self, python: Python, path: PathBuf) -> PyResult<String> {
+        Ok(python.detach(|| self.inner.put_file(&path))?)
+    }
+
+    /// Reads a blob.
+    ///
+    /// Args:
+    ///     hash: The hash `put_bytes` or `put_file` returned.
+    ///
+    /// Returns:
+    ///     The content of the blob.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the blob is not stored.
+    ///     ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+    fn get<'py>(&self, python: Python<'py>, hash: &str) -> PyResult<Bound<'py, PyBytes>> {
+        let content = python.detach(|| self.inner.get(hash)).map_err(to_pyerr)?;
+        Ok(PyBytes::new(python, &content))
+    }
+
+    /// Returns the path of a blob, to hand it to tools reading files, whether it is stored
+    /// or not.
+    ///
+    /// The file must not be modified, blobs being shared by every package using the store.
+    ///
+    /// Raises:
+    ///     ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+    fn path_of(&self, hash: &str) -> PyResult<PathBuf> {
+        self.inner.path_of(hash).map_err(to_pyerr)
+    }
+
+    /// Whether a blob is stored.
+    fn contains(&self, hash: &str) -> bool {
+        self.inner.contains(hash)
+    }
+
+    fn __contains__(&self, hash: &str) -> bool {
+        self.inner.contains(hash)
+    }
+
+    /// Makes a blob appear at a path, as a hard link to it where the filesystem allows,
+    /// otherwise as a copy, to hand it out without duplicating it.
+    ///
+    /// A link shares the read-only blob, so the file can be replaced but not modified in
+    /// place.
+    ///
+    /// Args:
+    ///     hash: The hash of the blob.
+    ///     dest: The path to create, whose parent directory must exist.
+    ///
+    /// Returns:
+    ///     True if `dest` is a link to the blob, False if it is a copy.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the blob is not stored.
+    ///     FileExistsError: If `dest` already exists.
+    ///     ValueError: If the hash is not a lowercase hex encoded BLAKE3 hash.
+    fn link_to(&self, python: Python, hash: &str, dest: PathBuf) -> PyResult<bool> {
+        python
+            .detach(|| self.inner.link_to(hash, &dest))
+            .map_err(to_pyerr)
+    }
+
+    /// Returns the references an owner recorded, mapping their names to the hashes of blobs.
+    ///
+    /// Args:
+    ///     owner: The name the references are recorded under, usually that of a package.
+    ///
+    /// Raises:
+    ///     ValueError: If the owner is not made of letters, digits, `-`, `_` or `.`.
+    fn references(&self, owner: &str) -> PyResult<BTreeMap<String, String>> {
+        self.inner.references(owner).map_err(to_pyerr)
+    }
+
+    /// Returns the hash of the blob a reference names, if the owner recorded it.
+    ///
+    /// Raises:
+    ///     ValueError: If the owner name is invalid.
+    fn reference(&self, owner: &str, name: &str) -> PyResult<Option<String>> {
+        self.inner.reference(owner, name).map_err(to_pyerr)
+    }
+
+    /// Records a reference to a blob, replacing the one of the same name, so collections keep
+    /// the blob.
+    ///
+    /// Args:
+    ///     owner: The name the reference is recorded under, usually that of a package.
+    ///     name: The name of the reference, on a single line.
+    ///     hash: The hash of the blob.
+    ///
+    /// Raises:
+    ///     ValueError: If the owner, the name or the hash is invalid.
+    fn set_reference(&self, owner: &str, name: &str, hash: &str) -> PyResult<()> {
+        self.inner
+            .set_reference(owner, name, hash)
+            .map_err(to_pyerr)
+    }
+
+    /// Replaces all the references an owner recorded.
+    ///
+    /// Args:
+    ///     owner: The name the references are recorded under.
+    ///     references: The hashes of the blobs the owner uses, by reference name.
+    ///
+    /// Raises:
+    ///     ValueError: If the owner, a name or a hash is invalid.
+    fn set_references(&self, owner: &str, references: BTreeMap<String, String>) -> PyResult<()> {
+        self.inner
+            .update_references(owner, |refs| *refs = references)
+            .map_err(to_pyerr)
+    }
+
+    /// Removes a reference an owner recorded.
+    ///
+    /// Returns:
+    ///     True if the reference existed.
+    ///
+    /// Raises:
+    ///     ValueError: If the owner name is invalid.
+    fn remove_reference(&self, owner: &str, name: &str) -> PyResult<bool> {
+        let mut removed = false;
+        self.inner
+            .update_references(owner, |refs| removed = refs.remove(name).is_some())
+            .map_err(to_pyerr)?;
+        Ok(removed)
+    }
+
+    /// Removes the blobs no owner references and that were not stored or stored again within
+    /// the grace period, along with temporary files left over by interrupted writes.
+    ///
+    /// Each package only manages the references of its own owner name, so a collection never
+    /// removes the blobs other packages still use. Writes and collections lock the store, so
+    /// they can run at the same time from any process.
+    ///
+    /// Args:
+    ///     grace: The age in seconds under which unreferenced blobs are kept, so that blobs
+    ///         just stored survive until they get referenced.
+    ///
+    /// Returns:
+    ///     A `GcReport` of what was removed.
+    ///
+    /// Raises:
+    ///     ValueError: If `grace` is negative or not finite.
+    #[pyo3(signature = (grace = 3600.0))]
+    fn gc(&self, python: Python, grace: f64) -> PyResult<GcReport> {
+        let grace = Duration::try_from_secs_f64(grace)
+            .map_err(|_| PyValueError::new_err(format!("Invalid grace: {grace}")))?;
+        let stats = python.detach(|| self.inner.gc(grace))?;
+        Ok(GcReport {
+            kept: stats.kept,
+            removed: stats.removed,
+            reclaimed_bytes: stats.reclaimed_bytes,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ContentStore(root={:?})", self.inner.root())
+    }
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ContentStore>()?;
+    m.add_class::<GcReport>()?;
+    Ok(())
+}
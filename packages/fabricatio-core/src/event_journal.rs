@@ -0,0 +1,273 @@
+//! An append-only journal of emitted events, for replaying what happened during a run.
+//!
+//! Each event is a line of JSON holding the time it was recorded at, its name and its payload:
+//! `{"timestamp": "2026-10-16T08:00:00.123456Z", "event": "task::write::Finished", "payload": {...}}`.
+//! Payloads are converted to JSON as they are recorded: plain Python values as is, pydantic
+//! models through `model_dump(mode="json")`, and anything else as its `repr`. Lines are written
+//! whole with a single call, so a crash leaves at most one truncated line, which replaying
+//! skips, and which is terminated when the journal is opened again so the next event starts a
+//! line of its own. Other tools, such as timeline views, can read the file directly.
+
+use crate::topics::TopicTrie;
+use chrono::{DateTime, SecondsFormat, Utc};
+use fabricatio_logger::warn;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use pythonize::{depythonize, pythonize};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A line of the journal.
+#[derive(Debug, Serialize, Deserialize)]
+struct Line {
+    /// RFC 3339, in UTC with microseconds
+    timestamp: String,
+    event: String,
+    payload: Value,
+}
+
+/// Converts a payload to JSON, falling back to `model_dump` and then to `repr`.
+fn to_json(payload: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if let Ok(value) = depythonize::<Value>(payload) {
+        return Ok(value);
+    }
+    if payload.hasattr("model_dump")? {
+        let kwargs = PyDict::new(payload.py());
+        kwargs.set_item("mode", "json")?;
+        if let Ok(dumped) = payload.call_method("model_dump", (), Some(&kwargs))
+            && let Ok(value) = depythonize::<Value>(&dumped)
+        {
+            return Ok(value);
+        }
+    }
+    Ok(Value::String(payload.repr()?.to_string()))
+}
+
+/// An event read back from the journal.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+pub struct JournalEntry {
+    /// When the event was recorded, in RFC 3339 format.
+    timestamp: String,
+    /// The name of the event.
+    event: String,
+    /// The payload, as converted to JSON when the event was recorded.
+    payload: Py<PyAny>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl JournalEntry {
+    fn __repr__(&self) -> String {
+        format!(
+            "JournalEntry(timestamp={:?}, event={:?})",
+            self.timestamp, self.event
+        )
+    }
+}
+
+/// Records emitted events to a JSON Lines file and replays them.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(frozen)]
+pub struct EventJournal {
+    path: PathBuf,
+    file: Mutex<File>,
+    delimiter: Option<String>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl EventJournal {
+    /// Opens a journal, creating the file and its parent directories if needed.
+    ///
+    /// A last line left truncated by a crash is terminated, so the next event is recorded on
+    /// a line of its own.
+    ///
+    /// Args:
+    ///     path: The JSON Lines file the events are appended to.
+    ///     delimiter: The delimiter of the segments of event names, used by the patterns of
+    ///         `replay`, the `emitter.delimiter` config value if None.
+    ///
+    /// Raises:
+    ///     OSError: If the file cannot be opened.
+    #[new]
+    #[pyo3(signature = (path, delimiter = None))]
+    fn new(path: PathBuf, delimiter: Option<String>) -> PyResult<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        if file.metadata()?.len() > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            delimiter,
+        })
+    }
+
+    /// The JSON Lines file of the journal.
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    /// Appends an event to the journal, timestamped with the current time.
+    ///
+    /// Args:
+    ///     event: The name of the event.
+    ///     payload: The data emitted with the event.
+    ///
+    /// Raises:
+    ///     OSError: If the file cannot be written.
+    #[pyo3(signature = (event, payload = None))]
+    fn record(&self, event: String, payload: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let line = Line {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            event,
+            payload: payload.map(to_json).transpose()?.unwrap_or(Value::Null),
+        };
+        let mut json = serde_json::to_string(&line)
+            .map_err(|e| PyValueError::new_err(format!("Unserializable payload: {e}")))?;
+        json.push('\n');
+        self.file
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Reads back the recorded events, oldest first.
+    ///
+    /// Args:
+    ///     pattern: Only replays the events matching this pattern, with the `*` and `**`
+    ///         wildcards of the emitter, such as `task::**::Failed`. Every event if None.
+    ///     since: Only replays the events recorded at or after this POSIX timestamp, such as
+    ///         `datetime.timestamp()` returns. Every event if None.
+    ///     limit: Only replays the latest `limit` events matching the other filters.
+    ///
+    /// Returns:
+    ///     The matching events. Lines that cannot be parsed, such as one left truncated by a
+    ///     crash, are skipped with a warning.
+    ///
+    /// Raises:
+    ///     ValueError: If the pattern is empty or `since` is out of range.
+    ///     OSError: If the file cannot be read.
+    #[pyo3(signature = (pattern = None, since = None, limit = None))]
+    fn replay(
+        &self,
+        python: Python,
+        pattern: Option<&str>,
+        since: Option<f64>,
+        limit: Option<usize>,
+    ) -> PyResult<Vec<JournalEntry>> {
+        let filter = pattern
+            .map(|pattern| -> PyResult<TopicTrie> {
+                let mut trie = TopicTrie::new(self.delimiter.clone())?;
+                trie.subscribe(pattern)?;
+                Ok(trie)
+            })
+            .transpose()?;
+        let since = since
+            .map(|since| {
+                DateTime::from_timestamp_micros((since * 1e6) as i64)
+                    .ok_or_else(|| PyValueError::new_err(format!("`since` out of range: {since}")))
+            })
+            .transpose()?;
+
+        let lines = python.detach(|| -> std::io::Result<Vec<Line>> {
+            let file = match File::open(&self.path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e),
+            };
+            let mut lines = Vec::new();
+            for (number, bytes) in BufReader::new(file).split(b'\n').enumerate() {
+                let text = match String::from_utf8(bytes?) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        warn!(
+                            "Skipping line {} of {}: {e}",
+                            number + 1,
+                            self.path.display()
+                        );
+                        continue;
+                    }
+                };
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let line = match serde_json::from_str::<Line>(&text) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!(
+                            "Skipping line {} of {}: {e}",
+                            number + 1,
+                            self.path.display()
+                        );
+                        continue;
+                    }
+                };
+                if since.is_some_and(|since| {
+                    DateTime::parse_from_rfc3339(&line.timestamp).is_ok_and(|at| at < since)
+                }) || filter
+                    .as_ref()
+                    .is_some_and(|filter| filter.matches(&line.event).is_empty())
+                {
+                    continue;
+                }
+                lines.push(line);
+            }
+            if let Some(limit) = limit {
+                lines.drain(..lines.len().saturating_sub(limit));
+            }
+            Ok(lines)
+        })?;
+
+        lines
+            .into_iter()
+            .map(|line| {
+                Ok(JournalEntry {
+                    timestamp: line.timestamp,
+                    event: line.event,
+                    payload: pythonize(python, &line.payload)?.unbind(),
+                })
+            })
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EventJournal(path={:?})", self.path)
+    }
+}
+
+/// Registers the event journal with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<EventJournal>()?;
+    m.add_class::<JournalEntry>()?;
+    Ok(())
+}
@@ -59,3 +59,84 @@ pub fn generic_block(title: &str, value: &str) -> String {
 pub fn code_block(lang: &str, v: &str) -> String {
     format!("```{lang}\n{v}\n```")
 }
+
+/// Formats a value as the cell of a Markdown table, escaping pipes and line breaks.
+fn table_cell(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Creates a Markdown table from rows of objects or arrays.
+///
+/// Objects are laid out by key, under the given columns or else under every key of the rows in
+/// alphabetical order, missing keys leaving their cells empty. Without columns, a table of arrays
+/// takes its header from the first row. Strings are written as is, other values as JSON.
+///
+/// Args:
+///     rows: The rows of the table.
+///     columns: The names of the columns, empty to derive them from the rows.
+///
+/// Returns:
+///     The table, one line per row after the header and its separator, or an empty string if
+///     there are no rows.
+pub fn markdown_table(rows: &[serde_json::Value], columns: &[String]) -> String {
+    use serde_json::Value;
+
+    let mut rows = rows.iter();
+    let mut header: Vec<String> = columns.to_vec();
+    let mut first_row: Option<&Value> = None;
+    if header.is_empty() {
+        match rows.next() {
+            Some(Value::Array(cells)) => header = cells.iter().map(table_cell).collect(),
+            Some(row) => first_row = Some(row),
+            None => return String::new(),
+        }
+    }
+    let rows: Vec<&Value> = first_row.into_iter().chain(rows).collect();
+    if header.is_empty() {
+        let mut keys: Vec<&String> = rows
+            .iter()
+            .filter_map(|row| row.as_object())
+            .flat_map(|object| object.keys())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        header = keys.into_iter().cloned().collect();
+    }
+    if header.is_empty() {
+        header.push("value".to_string());
+    }
+
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut table = line(
+        header
+            .iter()
+            .map(|name| table_cell(&Value::from(name.as_str())))
+            .collect(),
+    );
+    table.push_str(&line(vec!["---".to_string(); header.len()]));
+    for row in rows {
+        let cells = match row {
+            Value::Object(object) => header
+                .iter()
+                .map(|name| object.get(name).map(table_cell).unwrap_or_default())
+                .collect(),
+            Value::Array(items) => (0..header.len())
+                .map(|i| items.get(i).map(table_cell).unwrap_or_default())
+                .collect(),
+            scalar => {
+                let mut cells = vec![String::new(); header.len()];
+                cells[0] = table_cell(scalar);
+                cells
+            }
+        };
+        table.push_str(&line(cells));
+    }
+    table.trim_end().to_string()
+}
@@ -1,9 +1,20 @@
-use crate::formatter::{code_block, generic_block};
+use crate::formatter::{code_block, generic_block, markdown_table};
 use crate::language::convert_to_string_respectively;
-use crate::word_split::word_count as wc;
+use crate::word_split::{truncate_tokens, word_count as wc};
 use blake3::hash as blake3_hash;
-use handlebars::handlebars_helper;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson, handlebars_helper,
+};
+use heck::{ToLowerCamelCase, ToSnakeCase};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use pythonize::{depythonize, pythonize};
+use regex::Regex;
 use serde_json::{Value, to_string};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
 use whichlang::detect_language;
 
 handlebars_helper!(len: |v: Value| match v {
@@ -75,3 +86,183 @@ handlebars_helper!(head: |v:String , n: i64| {
 });
 
 handlebars_helper!(join: |v: Vec<String>, sep: String| v.join(&sep));
+
+handlebars_helper!(truncate_by_tokens: |v: String, n: u64, {suffix: str = "..."}| {
+    match truncate_tokens(&v, n as usize) {
+        Some(cut) => format!("{cut}{suffix}"),
+        None => v,
+    }
+});
+
+handlebars_helper!(json_pretty: |v: Json| serde_json::to_string_pretty(v).unwrap_or_default());
+
+handlebars_helper!(table: |rows: array, {columns: str = ""}| {
+    let columns: Vec<String> = columns
+        .split(',')
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .map(String::from)
+        .collect();
+    markdown_table(rows, &columns)
+});
+
+handlebars_helper!(snake_case: |v: String| v.to_snake_case());
+
+handlebars_helper!(camel_case: |v: String| v.to_lower_camel_case());
+
+/// Names a helper is allowed to look up, shared between the manager and its helpers.
+pub type Allowlist = Arc<RwLock<HashSet<String>>>;
+
+/// The global configuration as JSON, secrets being redacted by their serializer.
+static CONFIG_VALUE: Lazy<Value> =
+    Lazy::new(|| serde_json::to_value(&*fabricatio_config::CONFIG).unwrap_or_default());
+
+/// Reads the string parameter at `index` of a helper.
+fn str_param<'a>(h: &'a Helper, name: &'static str, index: usize) -> Result<&'a str, RenderError> {
+    h.param(index)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(name, index))?
+        .value()
+        .as_str()
+        .ok_or_else(|| RenderErrorReason::InvalidParamType("string").into())
+}
+
+/// `{{env "VAR"}}` renders an allowlisted environment variable, or the optional second
+/// parameter if it is unset.
+pub struct EnvHelper {
+    pub allowlist: Allowlist,
+}
+
+impl HelperDef for EnvHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let name = str_param(h, "env", 0)?;
+        if !self
+            .allowlist
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(name)
+        {
+            return Err(RenderErrorReason::Other(format!(
+                "Environment variable `{name}` is not in the template allowlist"
+            ))
+            .into());
+        }
+        let value = std::env::var(name)
+            .ok()
+            .map(Value::String)
+            .or_else(|| h.param(1).map(|default| default.value().clone()))
+            .unwrap_or_default();
+        Ok(ScopedJson::Derived(value))
+    }
+}
+
+/// `{{replace text "pattern" "replacement"}}` replaces every match of a regular expression,
+/// the replacement referring to capture groups as `$1` or `${name}`.
+pub struct RegexReplaceHelper;
+
+impl HelperDef for RegexReplaceHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let text = str_param(h, "replace", 0)?;
+        let pattern = str_param(h, "replace", 1)?;
+        let replacement = str_param(h, "replace", 2)?;
+        let regex = Regex::new(pattern).map_err(|e| {
+            RenderErrorReason::Other(format!("Invalid regular expression `{pattern}`: {e}"))
+        })?;
+        Ok(ScopedJson::Derived(Value::String(
+            regex.replace_all(text, replacement).into_owned(),
+        )))
+    }
+}
+
+/// `{{config "llm.model"}}` renders the configuration value at an allowlisted dotted path.
+///
+/// An allowlist entry covers its own path and every path below it, and missing values render
+/// as nothing.
+pub struct ConfigHelper {
+    pub allowlist: Allowlist,
+}
+
+impl HelperDef for ConfigHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let key = str_param(h, "config", 0)?;
+        let allowed = self
+            .allowlist
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|entry| {
+                key == entry
+                    || key
+                        .strip_prefix(entry.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            });
+        if !allowed {
+            return Err(RenderErrorReason::Other(format!(
+                "Config key `{key}` is not in the template allowlist"
+            ))
+            .into());
+        }
+        let value = key
+            .split('.')
+            .try_fold(&*CONFIG_VALUE, |value, segment| value.get(segment))
+            .cloned()
+            .unwrap_or_default();
+        Ok(ScopedJson::Derived(value))
+    }
+}
+
+/// A helper calling a Python function, with the parameters of the helper as positional
+/// arguments and its hash as keyword arguments, and rendering what the function returns.
+///
+/// The GIL is taken for the call only, so the templates using it render in parallel with
+/// the GIL released like the others, their Python calls running one at a time.
+pub struct PyHelper {
+    pub name: String,
+    pub function: Py<PyAny>,
+}
+
+impl HelperDef for PyHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        Python::attach(|py| -> PyResult<Value> {
+            let args = h
+                .params()
+                .iter()
+                .map(|param| pythonize(py, param.value()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let kwargs = PyDict::new(py);
+            for (key, param) in h.hash() {
+                kwargs.set_item(*key, pythonize(py, param.value())?)?;
+            }
+            let returned = self
+                .function
+                .bind(py)
+                .call(PyTuple::new(py, args)?, Some(&kwargs))?;
+            Ok(depythonize::<Value>(&returned)?)
+        })
+        .map(ScopedJson::Derived)
+        .map_err(|e| RenderErrorReason::Other(format!("Helper `{}` failed: {e}", self.name)).into())
+    }
+}
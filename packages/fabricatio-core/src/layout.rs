@@ -0,0 +1,134 @@
+//! Template inheritance: layouts with overridable blocks.
+//!
+//! A layout marks the regions other templates may override with
+//! `{{#block "name"}}default{{/block}}`. A template extends it by wrapping its overrides in
+//! `{{#extend "layout"}}{{#block "name"}}...{{/block}}{{/extend}}`. Layouts are resolved
+//! when templates are discovered, across all stores, so a layout may itself extend another
+//! one and the registered templates are plain Handlebars.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Matches the opening and closing tags of a block, capturing the name of opening ones.
+static BLOCK_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\{\{#block\s+"([^"]+)"\s*\}\}|\{\{/block\s*\}\}"#).unwrap());
+
+/// Matches a whole extending template, capturing the layout name and the overrides.
+static EXTEND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)\A\s*\{\{#extend\s+"([^"]+)"\s*\}\}(.*)\{\{/extend\s*\}\}\s*\z"#).unwrap()
+});
+
+/// A top-level block of a template, as byte ranges of the source.
+struct Block<'a> {
+    name: &'a str,
+    /// The range of the block, tags included.
+    outer: (usize, usize),
+    /// The range of the content of the block.
+    inner: (usize, usize),
+}
+
+/// Lists the top-level blocks of a template, nested blocks being part of their content.
+fn blocks(source: &str) -> Result<Vec<Block<'_>>, String> {
+    let mut found = Vec::new();
+    let mut open: Vec<(&str, usize, usize)> = Vec::new();
+    for tag in BLOCK_TAG.captures_iter(source) {
+        let whole = tag.get(0).unwrap();
+        match tag.get(1) {
+            Some(name) => open.push((name.as_str(), whole.start(), whole.end())),
+            None => {
+                let (name, start, inner_start) = open
+                    .pop()
+                    .ok_or_else(|| "`{{/block}}` does not close any block".to_string())?;
+                if open.is_empty() {
+                    found.push(Block {
+                        name,
+                        outer: (start, whole.end()),
+                        inner: (inner_start, whole.start()),
+                    });
+                }
+            }
+        }
+    }
+    match open.last() {
+        Some((name, ..)) => Err(format!("Block `{name}` is never closed")),
+        None => Ok(found),
+    }
+}
+
+/// Whether a template uses blocks or extends a layout.
+pub(crate) fn uses_layout(source: &str) -> bool {
+    source.contains("{{#extend") || BLOCK_TAG.is_match(source)
+}
+
+/// Replaces the blocks of a layout with the given overrides, keeping the block tags so that
+/// templates extending the result can override them again.
+fn fill(layout: &str, overrides: &HashMap<&str, &str>) -> Result<String, String> {
+    let mut filled = String::with_capacity(layout.len());
+    let mut last = 0;
+    for block in blocks(layout)? {
+        filled.push_str(&layout[last..block.inner.0]);
+        match overrides.get(block.name) {
+            Some(content) => filled.push_str(content),
+            None => filled.push_str(&fill(&layout[block.inner.0..block.inner.1], overrides)?),
+        }
+        filled.push_str(&layout[block.inner.1..block.outer.1]);
+        last = block.outer.1;
+    }
+    filled.push_str(&layout[last..]);
+    Ok(filled)
+}
+
+/// Expands the layout a template extends, recursively, keeping the block tags.
+fn expand(
+    name: &str,
+    sources: &HashMap<String, String>,
+    stack: &mut Vec<String>,
+) -> Result<String, String> {
+    if stack.iter().any(|seen| seen == name) {
+        stack.push(name.to_string());
+        return Err(format!("Cyclic layouts: {}", stack.join(" -> ")));
+    }
+    let source = sources
+        .get(name)
+        .ok_or_else(|| format!("Unknown layout `{name}`"))?;
+    let Some(extend) = EXTEND.captures(source) else {
+        if source.contains("{{#extend") {
+            return Err(format!(
+                "`{{{{#extend}}}}` must wrap the whole of template `{name}`"
+            ));
+        }
+        return Ok(source.clone());
+    };
+
+    let body = extend.get(2).unwrap().as_str();
+    let mut overrides = HashMap::new();
+    for block in blocks(body)? {
+        let content = &body[block.inner.0..block.inner.1];
+        if overrides.insert(block.name, content).is_some() {
+            return Err(format!(
+                "Block `{}` is overridden twice in `{name}`",
+                block.name
+            ));
+        }
+    }
+
+    stack.push(name.to_string());
+    let layout = expand(extend.get(1).unwrap().as_str(), sources, stack)?;
+    stack.pop();
+    fill(&layout, &overrides)
+}
+
+/// Resolves the layout of a template into plain Handlebars.
+///
+/// Args:
+///     name: The name of the template.
+///     sources: The sources of all discovered templates, by name.
+///
+/// Returns:
+///     The source with its layout expanded and the block tags removed, or a description of
+///     the problem if a layout is unknown, cyclic or has unbalanced blocks.
+pub(crate) fn resolve(name: &str, sources: &HashMap<String, String>) -> Result<String, String> {
+    let expanded = expand(name, sources, &mut Vec::new())?;
+    Ok(BLOCK_TAG.replace_all(&expanded, "").into_owned())
+}
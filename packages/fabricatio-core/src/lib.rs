@@ -5,23 +5,32 @@ extern crate core;
 use cfg_if::cfg_if;
 use fabricatio_config::Config;
 use fabricatio_constants::*;
-use fabricatio_logger::{Logger, init_logger};
+use fabricatio_logger::{Logger, LoggerOptions, Style, Throttle, init_logger};
 
+mod bus;
+mod cas;
 mod event;
+mod event_journal;
 mod formatter;
 mod hash;
 mod hbs_helpers;
 mod language;
+mod layout;
+mod lint;
 mod parser;
+mod project;
 pub mod router_usage;
 mod scan;
 pub mod templates;
 mod text_file;
+mod topics;
 mod word_split;
+mod workspace_path;
 
-use fabricatio_config::SecretStr;
+use fabricatio_config::{SecretReport, SecretStr};
 pub use fabricatio_router::Router;
 use fabricatio_router::init_router_from_config;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
 cfg_if!(
@@ -48,15 +57,31 @@ cfg_if!(
 #[pymodule]
 fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SecretStr>()?;
+    m.add_class::<SecretReport>()?;
     m.add_class::<Config>()?;
     init_logger(
-        fabricatio_config::CONFIG.debug.log_level.as_str(),
-        fabricatio_config::CONFIG.debug.log_dir.clone(),
-        fabricatio_config::CONFIG
-            .debug
-            .rotation
-            .as_ref()
-            .map(|r| r.parse().unwrap_or_default()),
+        LoggerOptions::new(fabricatio_config::CONFIG.debug.log_level.as_str())
+            .with_log_dir(fabricatio_config::CONFIG.debug.log_dir.clone())
+            .with_rotation(
+                fabricatio_config::CONFIG
+                    .debug
+                    .rotation
+                    .as_ref()
+                    .map(|r| r.parse().unwrap_or_default()),
+            )
+            .with_file_pattern(fabricatio_config::CONFIG.debug.log_file_pattern.clone())
+            .with_throttle(Throttle {
+                suppress_duplicates: fabricatio_config::CONFIG.debug.suppress_duplicates,
+                max_records_per_second: fabricatio_config::CONFIG.debug.max_records_per_second,
+            })
+            .with_style(
+                Style::new(
+                    fabricatio_config::CONFIG.debug.log_format.as_deref(),
+                    &fabricatio_config::CONFIG.debug.level_colors,
+                )
+                .map_err(PyValueError::new_err)?
+                .compact(fabricatio_config::CONFIG.debug.compact),
+            ),
     );
 
     let r = init_router_from_config()?;
@@ -75,8 +100,14 @@ fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     hash::register(python, m)?;
     word_split::register(python, m)?;
     event::register(python, m)?;
+    topics::register(python, m)?;
+    bus::register(python, m)?;
+    event_journal::register(python, m)?;
     scan::register(python, m)?;
     text_file::register(python, m)?;
+    workspace_path::register(python, m)?;
+    project::register(python, m)?;
+    cas::register(python, m)?;
     m.add_function(wrap_pyfunction!(fabricatio_router::tokens_of, m)?)?;
     m.add_class::<fabricatio_router::ProviderType>()?;
     m.add_class::<fabricatio_router::Router>()?;
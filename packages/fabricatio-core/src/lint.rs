@@ -0,0 +1,325 @@
+//! Linting templates: the variables they read, the helpers they call and the partials they
+//! include, found by walking their syntax tree, and the data they would miss.
+//!
+//! Only the variables of the data a template is rendered with are reported, as dotted paths
+//! such as `user.name`. Paths relative to the context of an `each` or `with` block, block
+//! parameters and `@` locals such as `@index` depend on the data and are left out, except
+//! when they reach the root with `@root` or enough `../`.
+
+use handlebars::Handlebars;
+use handlebars::template::{BlockParam, Parameter, Template, TemplateElement};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+
+/// The helpers Handlebars registers itself.
+const BUILTIN_HELPERS: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "gt", "gte", "lt", "lte",
+    "and", "or", "not", "len",
+];
+
+/// The variables, helpers and partials a template references.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVariables {
+    /// The variables the template always renders, as sorted dotted paths.
+    variables: Vec<String>,
+    /// The variables only tested by an `if` or `unless`, or only rendered in a conditional
+    /// block, which the data may leave out.
+    optional: Vec<String>,
+    /// The helpers called, sorted.
+    helpers: Vec<String>,
+    /// The partials included, sorted, those they include in turn too.
+    partials: Vec<String>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl TemplateVariables {
+    fn __repr__(&self) -> String {
+        format!(
+            "TemplateVariables(variables={:?}, optional={:?}, helpers={:?}, partials={:?})",
+            self.variables, self.optional, self.helpers, self.partials
+        )
+    }
+}
+
+/// Where an element is in a template.
+#[derive(Clone, Default)]
+struct Scope {
+    /// The number of blocks changing the context around the element.
+    depth: usize,
+    /// The block parameters and hash keys of partials in scope.
+    locals: Vec<String>,
+    /// Whether the element is only rendered under a condition.
+    conditional: bool,
+}
+
+/// Walks the syntax tree of templates, gathering their references.
+struct Walker<'a> {
+    registry: &'a Handlebars<'static>,
+    helpers: &'a HashSet<String>,
+    variables: BTreeSet<String>,
+    optional: BTreeSet<String>,
+    called: BTreeSet<String>,
+    partials: BTreeSet<String>,
+    /// The partials being walked, so that recursive ones are walked once.
+    including: Vec<String>,
+}
+
+/// Splits a path into its segments, on `.` and `/` outside of `[...]` keys.
+fn segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let (mut start, mut in_key) = (0, false);
+    for (i, c) in path.char_indices() {
+        match c {
+            '[' => in_key = true,
+            ']' => in_key = false,
+            '.' | '/' if !in_key => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+/// The path from the root of the data a reference resolves to, None if it is relative to a
+/// block context, a local or the context itself.
+fn root_path(raw: &str, scope: &Scope) -> Option<String> {
+    let normalize = |path: &str| Some(segments(path).join("."));
+    if let Some(rest) = raw.strip_prefix("@root") {
+        return rest.strip_prefix(['.', '/']).and_then(normalize);
+    }
+    if raw.starts_with('@') {
+        return None;
+    }
+
+    let mut rest = raw;
+    let mut current = false;
+    for prefix in ["this.", "this/", "./"] {
+        if let Some(stripped) = rest.strip_prefix(prefix) {
+            rest = stripped;
+            current = true;
+        }
+    }
+    if matches!(rest, "" | "this" | ".") {
+        return None;
+    }
+    let mut ups = 0;
+    while let Some(stripped) = rest
+        .strip_prefix("../")
+        .or_else(|| rest.strip_prefix("..."))
+    {
+        rest = stripped;
+        ups += 1;
+    }
+    if ups < scope.depth {
+        return None;
+    }
+    let first = segments(rest)[0];
+    if !current && ups == 0 && scope.locals.iter().any(|local| local == first) {
+        return None;
+    }
+    normalize(rest)
+}
+
+impl Walker<'_> {
+    fn is_helper(&self, name: &str) -> bool {
+        BUILTIN_HELPERS.contains(&name) || self.helpers.contains(name)
+    }
+
+    fn variable(&mut self, raw: &str, scope: &Scope, optional: bool) {
+        if let Some(path) = root_path(raw, scope) {
+            if optional || scope.conditional {
+                self.optional.insert(path);
+            } else {
+                self.variables.insert(path);
+            }
+        }
+    }
+
+    fn template(&mut self, template: &Template, scope: &Scope) {
+        for element in &template.elements {
+            self.element(element, scope);
+        }
+    }
+
+    fn param(&mut self, param: &Parameter, scope: &Scope, optional: bool) {
+        match param {
+            Parameter::Path(_) => {
+                if let Some(raw) = param.as_name() {
+                    self.variable(raw, scope, optional);
+                }
+            }
+            Parameter::Subexpression(sub) => self.element(&sub.element, scope),
+            _ => {}
+        }
+    }
+
+    fn element(&mut self, element: &TemplateElement, scope: &Scope) {
+        match element {
+            TemplateElement::Expression(ht) | TemplateElement::HtmlExpression(ht) => {
+                let name = ht.name.as_name().unwrap_or_default();
+                if ht.params.is_empty() && ht.hash.is_empty() && !self.is_helper(name) {
+                    self.param(&ht.name, scope, false);
+                    return;
+                }
+                self.called.insert(name.to_string());
+                for param in ht.params.iter().chain(ht.hash.values()) {
+                    self.param(param, scope, false);
+                }
+            }
+            TemplateElement::HelperBlock(ht) => {
+                let name = ht.name.as_name().unwrap_or_default();
+                let mut body = scope.clone();
+                if ht.params.is_empty() && ht.hash.is_empty() && !self.is_helper(name) {
+                    // A section, rendered with the value as context when it is truthy.
+                    self.param(&ht.name, scope, true);
+                    body.depth += 1;
+                    body.conditional = true;
+                } else {
+                    self.called.insert(name.to_string());
+                    let condition = matches!(name, "if" | "unless");
+                    for param in ht.params.iter().chain(ht.hash.values()) {
+                        self.param(param, scope, condition);
+                    }
+                    if matches!(name, "each" | "with") {
+                        body.depth += 1;
+                    }
+                    body.conditional |= matches!(name, "if" | "unless" | "each" | "with");
+                }
+                if let Some(block_param) = &ht.block_param {
+                    let names = match block_param {
+                        BlockParam::Single(p) => vec![p],
+                        BlockParam::Pair((p1, p2)) => vec![p1, p2],
+                        _ => Vec::new(),
+                    };
+                    body.locals.extend(
+                        names
+                            .into_iter()
+                            .filter_map(|p| p.as_name().map(String::from)),
+                    );
+                }
+                if let Some(template) = &ht.template {
+                    self.template(template, &body);
+                }
+                if let Some(inverse) = &ht.inverse {
+                    let inverse_scope = Scope {
+                        conditional: true,
+                        ..scope.clone()
+                    };
+                    self.template(inverse, &inverse_scope);
+                }
+            }
+            TemplateElement::DecoratorExpression(d) | TemplateElement::DecoratorBlock(d) => {
+                if let Some(template) = &d.template {
+                    self.template(template, scope);
+                }
+            }
+            TemplateElement::PartialExpression(d) | TemplateElement::PartialBlock(d) => {
+                for param in d.params.iter().chain(d.hash.values()) {
+                    self.param(param, scope, false);
+                }
+                if let Some(template) = &d.template {
+                    self.template(template, scope);
+                }
+                if matches!(d.name, Parameter::Subexpression(_)) {
+                    self.param(&d.name, scope, false);
+                    return;
+                }
+                let Some(name) = d.name.as_name() else {
+                    return;
+                };
+                let name = name.trim_matches(['[', ']', '\'', '"']).to_string();
+                self.partials.insert(name.clone());
+
+                let mut inner = scope.clone();
+                if !d.params.is_empty() {
+                    inner.depth += 1;
+                }
+                inner.locals.extend(d.hash.keys().cloned());
+                let registry = self.registry;
+                if let Some(partial) = registry.get_template(&name)
+                    && !self.including.contains(&name)
+                {
+                    self.including.push(name);
+                    self.template(partial, &inner);
+                    self.including.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TemplateVariables {
+    /// Gathers the references of a registered template, None if it is not registered.
+    ///
+    /// # Arguments
+    /// * `registry` - The registry holding the template and the partials it includes
+    /// * `helpers` - The helpers registered besides those of Handlebars, to tell helpers
+    ///   without parameters from variables
+    /// * `name` - The name of the template
+    pub(crate) fn collect(
+        registry: &Handlebars<'static>,
+        helpers: &HashSet<String>,
+        name: &str,
+    ) -> Option<Self> {
+        let template = registry.get_template(name)?;
+        let mut walker = Walker {
+            registry,
+            helpers,
+            variables: BTreeSet::new(),
+            optional: BTreeSet::new(),
+            called: BTreeSet::new(),
+            partials: BTreeSet::new(),
+            including: vec![name.to_string()],
+        };
+        walker.template(template, &Scope::default());
+        let Walker {
+            variables,
+            optional,
+            called,
+            partials,
+            ..
+        } = walker;
+        Some(Self {
+            optional: optional.difference(&variables).cloned().collect(),
+            variables: variables.into_iter().collect(),
+            helpers: called.into_iter().collect(),
+            partials: partials.into_iter().collect(),
+        })
+    }
+
+    /// The variables the template always renders that the data does not hold, sorted.
+    ///
+    /// A variable holding `null` counts as present, as Handlebars renders it empty.
+    pub(crate) fn missing(&self, data: &Value) -> Vec<String> {
+        self.variables
+            .iter()
+            .filter(|path| {
+                segments(path)
+                    .into_iter()
+                    .try_fold(data, |value, segment| {
+                        let segment = segment.trim_start_matches('[').trim_end_matches(']');
+                        match value {
+                            Value::Object(map) => map.get(segment),
+                            Value::Array(items) => segment
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|index| items.get(index)),
+                            _ => None,
+                        }
+                    })
+                    .is_none()
+            })
+            .cloned()
+            .collect()
+    }
+}
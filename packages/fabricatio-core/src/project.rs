@@ -0,0 +1,90 @@
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::path::PathBuf;
+use utils::project::{self, Project};
+
+/// The project a path belongs to, as described by its manifests.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct ProjectManifest {
+    /// The directory holding the manifests.
+    root: PathBuf,
+    /// The kinds of the project, among `python`, `rust`, `node` and `typst`, in that order.
+    kinds: Vec<String>,
+    /// The manifest of each kind, in the same order.
+    manifests: Vec<PathBuf>,
+    /// Name of the project, from the first manifest declaring one.
+    name: Option<String>,
+    /// Version of the project, from the first manifest declaring one.
+    version: Option<String>,
+    /// Description of the project, from the first manifest declaring one.
+    description: Option<String>,
+    /// Patterns in the `.gitignore` syntax of the build outputs, caches and installed
+    /// dependencies the tooling of the project generates.
+    ignore: Vec<String>,
+}
+
+impl From<Project> for ProjectManifest {
+    fn from(project: Project) -> Self {
+        Self {
+            kinds: project
+                .kinds
+                .iter()
+                .map(|kind| kind.name().to_string())
+                .collect(),
+            manifests: project
+                .kinds
+                .iter()
+                .map(|kind| project.manifest(*kind))
+                .collect(),
+            ignore: project.ignore().into_iter().map(str::to_string).collect(),
+            root: project.root,
+            name: project.name,
+            version: project.version,
+            description: project.description,
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl ProjectManifest {
+    fn __repr__(&self) -> String {
+        format!(
+            "ProjectManifest(root={:?}, kinds={:?}, name={:?}, version={:?})",
+            self.root, self.kinds, self.name, self.version
+        )
+    }
+}
+
+/// Detects the project a path belongs to from the manifest at its root.
+///
+/// The root is the nearest directory, starting from the path and going up, holding a
+/// `pyproject.toml`, `Cargo.toml`, `package.json` or `typst.toml`. A directory holding several
+/// of them makes a project of several kinds. Manifests that cannot be parsed still identify
+/// the kind, without metadata.
+///
+/// Args:
+///     path: A file or directory of the project.
+///     boundary: The directory the search stops at, included, such as a workspace root. The
+///         search goes up to the root of the filesystem if None.
+///
+/// Returns:
+///     The manifest of the nearest project, or None if the path belongs to none.
+///
+/// Raises:
+///     FileNotFoundError: If the path or the boundary does not exist.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (path, boundary = None))]
+fn detect_project(path: PathBuf, boundary: Option<PathBuf>) -> PyResult<Option<ProjectManifest>> {
+    Ok(project::detect(&path, boundary.as_deref())?.map(ProjectManifest::from))
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ProjectManifest>()?;
+    m.add_function(wrap_pyfunction!(detect_project, m)?)?;
+    Ok(())
+}
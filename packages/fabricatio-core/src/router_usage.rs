@@ -58,9 +58,7 @@ impl Batch<Value> {
                 TEMPLATE_MANAGER.render(template, &data).into_pyresult()?,
             )),
             Batch::Batch(data) => Ok(Batch::Batch(
-                TEMPLATE_MANAGER
-                    .render_batch(template, &data)
-                    .into_pyresult()?,
+                TEMPLATE_MANAGER.render_batch(template, &data)?,
             )),
         }
     }
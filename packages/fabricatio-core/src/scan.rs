@@ -1,14 +1,20 @@
+use error_mapping::AsPyErr;
 use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use scanner::PythonPackageScanner;
+use std::collections::HashMap;
 
 /// A static scanner instance used to check Python package installations and extras.
 static SCANNER: Lazy<PythonPackageScanner> = Lazy::new(PythonPackageScanner::default);
 
 /// Checks if a Python package is installed.
 ///
+/// Packages managed by conda are detected even if they have no `.dist-info` directory, and
+/// so are legacy `.egg-info` installs and editable installs made with `pip install -e`.
+///
 /// Args:
 ///     pkg_name: The name of the package to check.
 ///
@@ -30,8 +36,182 @@ fn list_installed() -> Vec<String> {
     SCANNER.list_installed()
 }
 
+/// Rescans the site-packages directory, picking up packages installed or removed since the
+/// last scan.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn refresh_installed() {
+    SCANNER.refresh()
+}
+
+/// Watches the site-packages directory so that packages installed or removed later, e.g.
+/// by `pip install`, are picked up without calling `refresh_installed`.
+///
+/// Calling it again while already watching does nothing.
+///
+/// Raises:
+///     OSError: If the directory cannot be watched.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn watch_installed() -> PyResult<()> {
+    SCANNER.watch().into_pyresult()
+}
+
+/// Stops watching the site-packages directory.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn unwatch_installed() {
+    SCANNER.unwatch()
+}
+
+/// Returns the version of a conda-managed package.
+///
+/// Args:
+///     pkg_name: The name of the package.
+///
+/// Returns:
+///     The version recorded in the environment's `conda-meta` directory, or None if the
+///     package is not managed by conda or no conda environment is active.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn conda_version(pkg_name: &str) -> Option<String> {
+    SCANNER.conda_version(pkg_name)
+}
+
+/// Returns the installed version of a Python package.
+///
+/// The version is read from the `Version` field of the package's METADATA, falling back to
+/// the name of its `.dist-info` or `.egg-info` record and then to the `conda-meta` records.
+///
+/// Args:
+///     pkg_name: The name of the package.
+///
+/// Returns:
+///     The installed version, or None if the package is not installed.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn get_version(pkg_name: &str) -> Option<String> {
+    SCANNER.get_version(pkg_name)
+}
+
+/// Checks if the installed version of a Python package matches PEP 440 version specifiers.
+///
+/// Args:
+///     pkg_name: The name of the package.
+///     specifiers: Comma-separated PEP 440 version specifiers, e.g. `">=2.1,<3"`.
+///
+/// Returns:
+///     True if the package is installed with a matching version, False if it is missing or
+///     its version is not PEP 440 compliant.
+///
+/// Raises:
+///     ValueError: If the specifiers are invalid.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn version_satisfies(pkg_name: &str, specifiers: &str) -> PyResult<bool> {
+    SCANNER
+        .version_satisfies(pkg_name, specifiers)
+        .map_err(PyValueError::new_err)
+}
+
+/// A node of the dependency tree of an installed package.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct DependencyNode {
+    /// Name of the package.
+    name: String,
+    /// Installed version of the package, None if it is missing.
+    version: Option<String>,
+    /// Version specifiers the parent requires, empty for the root.
+    specifier: String,
+    /// The extra of the parent that pulls the package, None if it is always required.
+    extra: Option<String>,
+    /// Whether the package is installed with a version matching the specifiers.
+    satisfied: bool,
+    /// Dependencies of the package; empty at the depth limit and for cycles.
+    children: Vec<DependencyNode>,
+}
+
+impl From<scanner::DependencyNode> for DependencyNode {
+    fn from(node: scanner::DependencyNode) -> Self {
+        Self {
+            name: node.name,
+            version: node.version,
+            specifier: node.specifier,
+            extra: node.extra,
+            satisfied: node.satisfied,
+            children: node.children.into_iter().map(Self::from).collect(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl DependencyNode {
+    fn __repr__(&self) -> String {
+        format!(
+            "DependencyNode(name={:?}, version={:?}, specifier={:?}, satisfied={}, children={})",
+            self.name,
+            self.version,
+            self.specifier,
+            self.satisfied,
+            self.children.len()
+        )
+    }
+}
+
+/// Builds the dependency tree of an installed Python package from its `Requires-Dist` entries.
+///
+/// Nodes whose installed version does not match the specifiers of their parent are marked
+/// as unsatisfied, which reveals conflicts. Packages already on the path to a node are not
+/// expanded again, and requirements whose environment markers exclude the running
+/// interpreter are left out.
+///
+/// Args:
+///     pkg_name: The name of the root package.
+///     depth: Maximum depth of the tree below the root, unlimited if None.
+///     extras: Extras of the root package whose dependencies are included as well.
+///
+/// Returns:
+///     The root node, or None if the package is not installed.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (pkg_name, depth = None, extras = Vec::new()))]
+fn dependency_tree(
+    pkg_name: &str,
+    depth: Option<usize>,
+    extras: Vec<String>,
+) -> Option<DependencyNode> {
+    SCANNER
+        .dependency_tree(pkg_name, depth, &extras)
+        .map(DependencyNode::from)
+}
+
+/// Lists the installed Python packages requiring a package.
+///
+/// Args:
+///     pkg_name: The name of the required package.
+///
+/// Returns:
+///     A list of `(package, specifier, extra)` tuples sorted by package, where `extra` is the
+///     extra of the requiring package that pulls the dependency, or None if it is always
+///     required.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn reverse_dependencies(pkg_name: &str) -> Vec<(String, String, Option<String>)> {
+    SCANNER
+        .reverse_dependencies(pkg_name)
+        .into_iter()
+        .map(|(package, dep)| (package, dep.specifier, dep.extra))
+        .collect()
+}
+
 /// Checks if a specific extra (optional dependency) of a Python package is satisfied.
 ///
+/// Dependencies whose environment markers, e.g. `sys_platform == "win32"`, exclude the
+/// running interpreter are not required.
+///
 /// Args:
 ///     pkg_name: The name of the package.
 ///     extra_name: The name of the extra/optional dependency.
@@ -46,6 +226,8 @@ fn extra_satisfied(pkg_name: &str, extra_name: &str) -> bool {
 
 /// Checks if all specified extras (optional dependencies) of a Python package are satisfied.
 ///
+/// Dependencies whose environment markers exclude the running interpreter are not required.
+///
 /// Args:
 ///     pkg_name: The name of the package.
 ///     extras: A list of extra/optional dependency names to check.
@@ -58,6 +240,84 @@ pub fn extras_satisfied(pkg_name: &str, extras: Vec<String>) -> bool {
     SCANNER.extras_satisfied(pkg_name, extras)
 }
 
+/// An entry point declared by an installed Python package.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct EntryPoint {
+    /// Name of the entry point, e.g. the command of a console script.
+    name: String,
+    /// Group of the entry point, e.g. `console_scripts`.
+    group: String,
+    /// The object reference, e.g. `package.module:function [extra]`.
+    value: String,
+    /// The module part of the object reference.
+    module: String,
+    /// The attribute part of the object reference, None if the module itself is referenced.
+    attr: Option<String>,
+    /// Extras of the package the entry point requires.
+    extras: Vec<String>,
+    /// Name of the package declaring the entry point.
+    package: String,
+}
+
+impl From<scanner::EntryPoint> for EntryPoint {
+    fn from(entry: scanner::EntryPoint) -> Self {
+        Self {
+            name: entry.name,
+            group: entry.group,
+            value: entry.value,
+            module: entry.module,
+            attr: entry.attr,
+            extras: entry.extras,
+            package: entry.package,
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl EntryPoint {
+    fn __repr__(&self) -> String {
+        format!(
+            "EntryPoint(name={:?}, group={:?}, value={:?}, package={:?})",
+            self.name, self.group, self.value, self.package
+        )
+    }
+}
+
+/// Lists the entry points declared by installed Python packages in their `entry_points.txt`.
+///
+/// Unlike `importlib.metadata.entry_points`, the files are parsed once and cached, so plugin
+/// groups can be queried repeatedly at runtime.
+///
+/// Args:
+///     group: Only list the entry points of this group, e.g. `console_scripts`. Defaults to
+///         None, listing all groups.
+///
+/// Returns:
+///     The entry points sorted by group, name and package.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (group = None))]
+fn list_entry_points(group: Option<&str>) -> Vec<EntryPoint> {
+    SCANNER
+        .list_entry_points(group)
+        .into_iter()
+        .map(EntryPoint::from)
+        .collect()
+}
+
+/// Lists the console scripts installed with Python packages.
+///
+/// Returns:
+///     A mapping of each command to its object reference, e.g. `{"pytest": "pytest:console_main"}`.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn get_console_scripts() -> HashMap<String, String> {
+    SCANNER.get_console_scripts().into_iter().collect()
+}
+
 /// Registers the Python package scanning functions with the module.
 ///
 /// Args:
@@ -69,6 +329,18 @@ pub fn extras_satisfied(pkg_name: &str, extras: Vec<String>) -> bool {
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(is_installed, m)?)?;
     m.add_function(wrap_pyfunction!(list_installed, m)?)?;
+    m.add_function(wrap_pyfunction!(refresh_installed, m)?)?;
+    m.add_function(wrap_pyfunction!(watch_installed, m)?)?;
+    m.add_function(wrap_pyfunction!(unwatch_installed, m)?)?;
+    m.add_function(wrap_pyfunction!(conda_version, m)?)?;
+    m.add_function(wrap_pyfunction!(get_version, m)?)?;
+    m.add_function(wrap_pyfunction!(version_satisfies, m)?)?;
+    m.add_function(wrap_pyfunction!(dependency_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(reverse_dependencies, m)?)?;
+    m.add_class::<DependencyNode>()?;
+    m.add_function(wrap_pyfunction!(list_entry_points, m)?)?;
+    m.add_function(wrap_pyfunction!(get_console_scripts, m)?)?;
+    m.add_class::<EntryPoint>()?;
     m.add_function(wrap_pyfunction!(extra_satisfied, m)?)?;
     m.add_function(wrap_pyfunction!(extras_satisfied, m)?)?;
     Ok(())
@@ -1,21 +1,37 @@
 use crate::hbs_helpers::*;
+use crate::layout;
+use crate::lint::TemplateVariables;
 use error_mapping::*;
 use fabricatio_constants::*;
 use fabricatio_logger::*;
-use handlebars::{Handlebars, no_escape};
-use pyo3::exceptions::PyRuntimeError;
+use handlebars::{Handlebars, HelperDef, no_escape};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyList, PyString};
 use pyo3_stub_gen::derive::*;
 
+use pyo3_async_runtimes::tokio::future_into_py;
 use pythonize::depythonize;
 
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::task::spawn_blocking;
 use walkdir::WalkDir;
 
+/// Separator between a template name and its variant label, e.g. `summarize@v2`.
+const VARIANT_SEPARATOR: char = '@';
+/// Label of the variant registered under the bare template name.
+const DEFAULT_VARIANT: &str = "default";
+/// Subdirectory of a store whose templates are registered under their path relative to it,
+/// to be included with `{{> name}}`.
+const PARTIALS_DIRNAME: &str = "partials";
+
 /// Python bindings for the TemplateManager struct.
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
 #[derive(Clone)]
@@ -23,8 +39,70 @@ use walkdir::WalkDir;
 pub struct TemplateManager {
     #[pyo3(get)]
     templates_stores: Vec<PathBuf>,
-    handlebars: Handlebars<'static>,
+    /// The registry, shared with the renderings in progress and copied on write.
+    handlebars: Arc<Handlebars<'static>>,
     suffix: String,
+    env_allowlist: Allowlist,
+    config_allowlist: Allowlist,
+    /// Templates registered from Python, kept across discoveries.
+    sources: HashMap<String, String>,
+    /// Partials registered from Python, kept across discoveries.
+    partials: HashMap<String, String>,
+    /// The names of the helpers registered besides those of Handlebars.
+    helpers: HashSet<String>,
+    /// Whether rendering a list of data fails if any item fails, used when `strict` is not
+    /// given to `render_template` or `render_template_raw`.
+    #[pyo3(get, set)]
+    strict: bool,
+}
+
+/// The outcome of rendering a single item or each item of a list.
+enum Rendered {
+    One(Result<String, handlebars::RenderError>),
+    Each(Vec<Result<String, handlebars::RenderError>>),
+}
+
+/// The items of a batch that failed to render.
+#[derive(Debug)]
+pub struct BatchRenderError {
+    /// The number of items in the batch.
+    pub total: usize,
+    /// The index of each failing item, with its error, in the order of the batch.
+    pub failures: Vec<(usize, handlebars::RenderError)>,
+}
+
+impl fmt::Display for BatchRenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} items failed to render",
+            self.failures.len(),
+            self.total
+        )?;
+        for (index, error) in &self.failures {
+            write!(f, "\n  item {index}: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BatchRenderError {}
+
+/// Raised as a `RuntimeError` whose `failures` attribute lists the failing items as
+/// `(index, message)` tuples.
+impl From<BatchRenderError> for PyErr {
+    fn from(error: BatchRenderError) -> Self {
+        let err = PyRuntimeError::new_err(error.to_string());
+        let failures: Vec<(usize, String)> = error
+            .failures
+            .iter()
+            .map(|(index, e)| (*index, e.to_string()))
+            .collect();
+        Python::attach(|py| {
+            let _ = err.value(py).setattr("failures", failures);
+        });
+        err
+    }
 }
 
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
@@ -73,6 +151,207 @@ impl TemplateManager {
         slf
     }
 
+    /// The environment variables templates may read with the `env` helper, sorted.
+    #[getter]
+    fn env_allowlist(&self) -> Vec<String> {
+        Self::sorted(&self.env_allowlist)
+    }
+
+    /// The configuration keys templates may read with the `config` helper, sorted.
+    #[getter]
+    fn config_allowlist(&self) -> Vec<String> {
+        Self::sorted(&self.config_allowlist)
+    }
+
+    /// Allows templates to read environment variables with `{{env "VAR"}}`.
+    ///
+    /// The helper renders the variable, or its optional second parameter if the variable is
+    /// unset. Reading a variable that was not allowed fails the rendering.
+    ///
+    /// Args:
+    ///     names: The names of the environment variables.
+    ///
+    /// Returns:
+    ///     A mutable reference to self for method chaining.
+    fn allow_env(slf: PyRefMut<Self>, names: Vec<String>) -> PyRefMut<Self> {
+        slf.env_allowlist
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(names);
+        slf
+    }
+
+    /// Allows templates to read configuration values with `{{config "llm.model"}}`.
+    ///
+    /// A key such as `llm` also allows every key below it, such as `llm.model`. Secrets are
+    /// rendered redacted, and reading a key that was not allowed fails the rendering.
+    ///
+    /// Args:
+    ///     keys: The dotted configuration keys.
+    ///
+    /// Returns:
+    ///     A mutable reference to self for method chaining.
+    fn allow_config(slf: PyRefMut<Self>, keys: Vec<String>) -> PyRefMut<Self> {
+        slf.config_allowlist
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(keys);
+        slf
+    }
+
+    /// Registers a template from its source, to be rendered by name like a discovered one.
+    ///
+    /// Templates registered this way, such as generated ones, are kept when templates are
+    /// discovered again and take precedence over templates of the stores with the same name.
+    /// They are registered as given, so they cannot extend a layout, but may include partials.
+    ///
+    /// Args:
+    ///     name: The name to render the template by.
+    ///     source: The source of the template.
+    ///
+    /// Returns:
+    ///     A mutable reference to self for method chaining.
+    ///
+    /// Raises:
+    ///     ValueError: If the source is not a valid template.
+    fn register_template(
+        mut slf: PyRefMut<Self>,
+        name: String,
+        source: String,
+    ) -> PyResult<PyRefMut<Self>> {
+        Arc::make_mut(&mut slf.handlebars)
+            .register_template_string(&name, &source)
+            .map_err(|e| PyValueError::new_err(format!("Invalid template `{name}`: {e}")))?;
+        slf.sources.insert(name, source);
+        Ok(slf)
+    }
+
+    /// Unregisters a template or partial, whether registered from Python or discovered.
+    ///
+    /// A discovered template comes back at the next discovery, as long as its file exists.
+    ///
+    /// Args:
+    ///     name: The name of the template.
+    ///
+    /// Returns:
+    ///     Whether a template was registered under that name.
+    fn unregister_template(&mut self, name: &str) -> bool {
+        let registered = self.handlebars.get_template(name).is_some();
+        Arc::make_mut(&mut self.handlebars).unregister_template(name);
+        self.sources.remove(name);
+        self.partials.remove(name);
+        registered
+    }
+
+    /// Registers a partial, a template other templates include with `{{> name}}`.
+    ///
+    /// Partials registered this way are kept when templates are discovered again, and take
+    /// precedence over templates and partials of the stores with the same name. Partials
+    /// can also be put in the `partials/` subdirectory of a store, where `partials/base.hbs`
+    /// registers `base`.
+    ///
+    /// Args:
+    ///     name: The name to include the partial by.
+    ///     source: The source of the partial, which may itself include partials.
+    ///
+    /// Returns:
+    ///     A mutable reference to self for method chaining.
+    ///
+    /// Raises:
+    ///     ValueError: If the source is not a valid template.
+    fn register_partial(
+        mut slf: PyRefMut<Self>,
+        name: String,
+        source: String,
+    ) -> PyResult<PyRefMut<Self>> {
+        Arc::make_mut(&mut slf.handlebars)
+            .register_partial(&name, &source)
+            .map_err(|e| PyValueError::new_err(format!("Invalid partial `{name}`: {e}")))?;
+        slf.partials.insert(name, source);
+        Ok(slf)
+    }
+
+    /// Registers a Python function as a helper, called as `{{name arg ... key=value}}`.
+    ///
+    /// The parameters of the helper are converted to Python objects and passed as positional
+    /// arguments, its hash as keyword arguments. What the function returns is converted back
+    /// and rendered, or passed on when the helper is used in a subexpression, so it must be
+    /// serializable like template data; `None` renders as nothing. Helpers are kept when
+    /// templates are discovered again, and replace a helper of the same name, built-in or not.
+    ///
+    /// Args:
+    ///     name: The name to call the helper by.
+    ///     function: The function to call, which may be called from any thread.
+    ///
+    /// Returns:
+    ///     A mutable reference to self for method chaining.
+    ///
+    /// Raises:
+    ///     TypeError: If the function is not callable.
+    ///     ValueError: If the name is empty or contains whitespace.
+    fn register_helper(
+        mut slf: PyRefMut<Self>,
+        name: String,
+        function: Bound<'_, PyAny>,
+    ) -> PyResult<PyRefMut<Self>> {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid helper name `{name}`"
+            )));
+        }
+        if !function.is_callable() {
+            return Err(PyTypeError::new_err(format!(
+                "Helper `{name}` must be callable"
+            )));
+        }
+        let helper = PyHelper {
+            name: name.clone(),
+            function: function.unbind(),
+        };
+        slf.register_helper_def(&name, Box::new(helper));
+        Ok(slf)
+    }
+
+    /// Lists the variables, helpers and partials a registered template references.
+    ///
+    /// Partials are followed, so the variables they read are listed too. Variables are given
+    /// as dotted paths from the root of the data, such as `user.name`; those relative to the
+    /// items of an `each` or to a `with` block depend on the data and are left out.
+    ///
+    /// Args:
+    ///     name: The name of the template.
+    ///
+    /// Returns:
+    ///     A `TemplateVariables` with the required and optional variables, the helpers and
+    ///     the partials, each sorted.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the template is missing.
+    fn template_variables(&self, name: &str) -> PyResult<TemplateVariables> {
+        TemplateVariables::collect(&self.handlebars, &self.helpers, name)
+            .ok_or_else(|| PyRuntimeError::new_err(format!("Template '{name}' not found")))
+    }
+
+    /// Checks that data holds every variable a template always renders, before rendering it.
+    ///
+    /// Variables only tested by an `if` or `unless`, or only rendered inside a conditional
+    /// block, are not required. A variable holding `None` counts as present.
+    ///
+    /// Args:
+    ///     name: The name of the template.
+    ///     data: A dictionary containing template variables.
+    ///
+    /// Returns:
+    ///     The sorted dotted paths of the missing variables, empty if the data is complete.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the template is missing.
+    fn validate_data(&self, name: &str, data: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+        let variables = self.template_variables(name)?;
+        let json_data = depythonize::<Value>(data).into_pyresult()?;
+        Ok(variables.missing(&json_data))
+    }
+
     /// Discovers and registers all templates from the configured directories.
     ///
     /// Returns:
@@ -84,18 +363,31 @@ impl TemplateManager {
 
     /// Renders a template with the given data.
     ///
+    /// Items of a list are rendered in parallel, each on its own. In strict mode, any failing
+    /// item fails the whole call. Otherwise failing items are rendered as `None` and logged,
+    /// so the other items are kept at their positions.
+    ///
     /// Args:
     ///     name: The path to the template file.
     ///     data: A dictionary or list of dictionaries containing template variables.
+    ///     strict: Whether a failing item of a list fails the call, defaults to `strict` of
+    ///         the manager.
     ///
     /// Returns:
     ///     The rendered template string, or a list of strings if data is a list.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the template is missing or fails to render. For a list in strict
+    ///         mode, the `failures` attribute of the error lists every failing item as an
+    ///         `(index, message)` tuple.
     #[gen_stub(skip)]
+    #[pyo3(signature = (name, data, strict = None))]
     fn render_template<'a>(
         &self,
         py: Python<'a>,
         name: String,
         data: &Bound<'_, PyAny>,
+        strict: Option<bool>,
     ) -> PyResult<Bound<'a, PyAny>> {
         if data.is_instance_of::<PyList>() {
             trace!("Rendering list of templates: {name}");
@@ -105,41 +397,171 @@ impl TemplateManager {
                 )));
             }
             let seq = depythonize::<Vec<Value>>(data).into_pyresult()?;
-            let rendered = self.render_batch(&name, &seq).into_pyresult()?;
-            let py_list = PyList::new(py, rendered)?;
-            Ok(py_list.as_any().clone())
+            let results = py.detach(|| self.render_each(&name, &seq));
+            Self::batch_to_py(py, results, strict.unwrap_or(self.strict))
         } else {
             trace!("Rendering single template: {name}");
             let json_data = depythonize::<Value>(data).into_pyresult()?;
-            let rendered_content = self.render(&name, &json_data).into_pyresult()?;
+            let rendered_content = py
+                .detach(|| self.render(&name, &json_data))
+                .into_pyresult()?;
             let py_string = PyString::new(py, &rendered_content);
             Ok(py_string.as_any().clone())
         }
     }
 
+    /// Renders a template with the given data without blocking the event loop.
+    ///
+    /// Rendering runs on a worker thread without holding the GIL, so concurrent tasks keep
+    /// running meanwhile. Templates registered or discovered while it runs do not affect it.
+    ///
+    /// Args:
+    ///     name: The path to the template file.
+    ///     data: A dictionary or list of dictionaries containing template variables.
+    ///     strict: Whether a failing item of a list fails the call, defaults to `strict` of
+    ///         the manager.
+    ///
+    /// Returns:
+    ///     An awaitable of the rendered template string, or of a list of strings if data is
+    ///     a list.
+    ///
+    /// Raises:
+    ///     RuntimeError: As `render_template`, when awaited.
+    #[gen_stub(skip)]
+    #[pyo3(signature = (name, data, strict = None))]
+    fn render_template_async<'a>(
+        &self,
+        py: Python<'a>,
+        name: String,
+        data: &Bound<'_, PyAny>,
+        strict: Option<bool>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let is_list = data.is_instance_of::<PyList>();
+        let json_data = depythonize::<Value>(data).into_pyresult()?;
+        let strict = strict.unwrap_or(self.strict);
+        let manager = self.clone();
+        future_into_py(py, async move {
+            if manager.handlebars.get_template(&name).is_none() {
+                return Err(PyRuntimeError::new_err(format!(
+                    "Template '{name}' not found"
+                )));
+            }
+            let rendered = spawn_blocking(move || match json_data {
+                Value::Array(items) if is_list => {
+                    Rendered::Each(manager.render_each(&name, &items))
+                }
+                data => Rendered::One(manager.render(&name, &data)),
+            })
+            .await
+            .map_err(|e| PyRuntimeError::new_err(format!("Rendering failed: {e}")))?;
+            Python::attach(|py| match rendered {
+                Rendered::One(result) => Ok(PyString::new(py, &result.into_pyresult()?)
+                    .into_any()
+                    .unbind()),
+                Rendered::Each(results) => Ok(Self::batch_to_py(py, results, strict)?.unbind()),
+            })
+        })
+    }
+
+    /// Lists the variants registered for a template.
+    ///
+    /// A template file named `summarize@v2.hbs` registers the variant `v2` of `summarize`,
+    /// while the bare `summarize.hbs` is reported as the `default` variant.
+    ///
+    /// Args:
+    ///     name: The base name of the template.
+    ///
+    /// Returns:
+    ///     The sorted list of variant labels.
+    fn template_variants(&self, name: &str) -> Vec<String> {
+        self.variants_of(name)
+    }
+
+    /// Renders one variant of a template with the given data.
+    ///
+    /// If `variant` is given, that variant is rendered. Otherwise a variant is picked at
+    /// random among the registered ones, weighted by `weights` (variants absent from
+    /// `weights` get a weight of 1.0). The chosen variant is logged as the structured
+    /// `template_variant` field and returned alongside the rendered text.
+    ///
+    /// Args:
+    ///     name: The base name of the template.
+    ///     data: A dictionary containing template variables.
+    ///     variant: The variant label to render, `default` refers to the bare template.
+    ///     weights: Optional mapping of variant labels to selection weights.
+    ///
+    /// Returns:
+    ///     A tuple of (variant label, rendered string).
+    ///
+    /// Raises:
+    ///     RuntimeError: If the template has no variants or the requested variant does not exist.
+    #[pyo3(signature = (name, data, variant = None, weights = None))]
+    fn render_variant(
+        &self,
+        name: &str,
+        data: &Bound<'_, PyAny>,
+        variant: Option<String>,
+        weights: Option<HashMap<String, f64>>,
+    ) -> PyResult<(String, String)> {
+        let variant = match variant {
+            Some(variant) => variant,
+            None => self
+                .pick_variant(name, &weights.unwrap_or_default())
+                .ok_or_else(|| {
+                    PyRuntimeError::new_err(format!("Template '{name}' has no variants"))
+                })?,
+        };
+        let full_name = Self::variant_name(name, &variant);
+        if self.handlebars.get_template(&full_name).is_none() {
+            return Err(PyRuntimeError::new_err(format!(
+                "Template '{full_name}' not found"
+            )));
+        }
+
+        let json_data = depythonize::<Value>(data).into_pyresult()?;
+        let rendered = self.render(&full_name, &json_data).into_pyresult()?;
+        debug!(
+            template = name,
+            template_variant = variant.as_str(),
+            "Rendered template variant"
+        );
+        Ok((variant, rendered))
+    }
+
     /// Renders a template from a raw template string.
     ///
+    /// Lists are rendered as with `render_template`, including the handling of failing items.
+    ///
     /// Args:
     ///     template: The raw template string.
     ///     data: A dictionary or list of dictionaries containing template variables.
+    ///     strict: Whether a failing item of a list fails the call, defaults to `strict` of
+    ///         the manager.
     ///
     /// Returns:
     ///     The rendered template string, or a list of strings if data is a list.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the template fails to render, with the `failures` attribute for
+    ///         a list in strict mode.
     #[gen_stub(skip)]
+    #[pyo3(signature = (template, data, strict = None))]
     fn render_template_raw<'a>(
         &self,
         py: Python<'a>,
         template: &str,
         data: &Bound<'_, PyAny>,
+        strict: Option<bool>,
     ) -> PyResult<Bound<'a, PyAny>> {
         if data.is_instance_of::<PyList>() {
             let seq = depythonize::<Vec<Value>>(data).into_pyresult()?;
-            let rendered = self.render_raw_batch(template, &seq).into_pyresult()?;
-            let py_list = PyList::new(py, &rendered)?;
-            Ok(py_list.as_any().clone())
+            let results = py.detach(|| self.render_raw_each(template, &seq));
+            Self::batch_to_py(py, results, strict.unwrap_or(self.strict))
         } else {
             let json_data = depythonize::<Value>(data).into_pyresult()?;
-            let rendered_content = self.render_raw(template, &json_data).into_pyresult()?;
+            let rendered_content = py
+                .detach(|| self.render_raw(template, &json_data))
+                .into_pyresult()?;
             let py_string = PyString::new(py, &rendered_content);
             Ok(py_string.as_any().clone())
         }
@@ -148,17 +570,24 @@ impl TemplateManager {
 
 impl TemplateManager {
     fn from_config() -> Self {
-        Self::new(
-            fabricatio_config::CONFIG
-                .template_manager
-                .template_stores
-                .clone(),
-            fabricatio_config::CONFIG
-                .template_manager
-                .template_suffix
-                .clone(),
-            fabricatio_config::CONFIG.template_manager.active_loading,
-        )
+        let config = &fabricatio_config::CONFIG.template_manager;
+        let mut manager = Self::new(
+            config.template_stores.clone(),
+            config.template_suffix.clone(),
+            config.active_loading,
+        );
+        manager.strict = config.strict;
+        manager
+            .env_allowlist
+            .write()
+            .unwrap()
+            .extend(config.env_allowlist.iter().cloned());
+        manager
+            .config_allowlist
+            .write()
+            .unwrap()
+            .extend(config.config_allowlist.iter().cloned());
+        manager
     }
     fn new(template_dir: Vec<PathBuf>, suffix: String, active_loading: bool) -> Self {
         // Convert Python paths to Rust PathBufs
@@ -169,14 +598,17 @@ impl TemplateManager {
 
         let mut manager = Self {
             templates_stores: template_dir,
-            handlebars,
+            handlebars: Arc::new(handlebars),
             suffix,
+            env_allowlist: Allowlist::default(),
+            config_allowlist: Allowlist::default(),
+            sources: HashMap::new(),
+            partials: HashMap::new(),
+            helpers: HashSet::new(),
+            strict: true,
         };
 
-        manager
-            .discover_templates_inner()
-            .register_builtin_helper()
-            .register_partials();
+        manager.discover_templates_inner().register_builtin_helper();
         manager
     }
 
@@ -192,21 +624,41 @@ impl TemplateManager {
     ///     the template from the directory that appears later in the `templates_dir` vector
     ///     will take precedence and override any previously registered template with the same name.
     ///
+    /// Templates using blocks or extending a layout are registered with their layout resolved,
+    /// so active loading does not pick up their changes until the next discovery. Those whose
+    /// layout cannot be resolved are logged and left out.
+    ///
+    /// The templates and partials registered from Python are registered again afterwards.
+    ///
     /// Returns:
     ///     A mutable reference to self for method chaining.
     fn discover_templates_inner(&mut self) -> &mut Self {
-        self.handlebars.clear_templates();
-        self.gather_templates().iter().for_each(|(name, path)| {
-            self.handlebars.register_template_file(name, path).unwrap();
+        let gathered = self.gather_templates();
+        let sources: HashMap<String, String> = gathered
+            .iter()
+            .filter_map(|(name, path)| fs::read_to_string(path).ok().map(|s| (name.clone(), s)))
+            .collect();
+        let handlebars = Arc::make_mut(&mut self.handlebars);
+        handlebars.clear_templates();
+        gathered.iter().for_each(|(name, path)| {
+            if !sources.get(name).is_some_and(|s| layout::uses_layout(s)) {
+                handlebars.register_template_file(name, path).unwrap();
+                return;
+            }
+            match layout::resolve(name, &sources) {
+                Ok(resolved) => handlebars.register_template_string(name, resolved).unwrap(),
+                Err(e) => error!("Failed to resolve the layout of template {name}: {e}"),
+            }
         });
-        self
+        self.register_partials()
     }
 
     /// Gathers template files from all registered template stores.
     ///
     /// Scans through directories in reverse order to collect template files that match
     /// the configured suffix. For each matching file, generates a tuple containing:
-    /// - Template name (derived from file path, without directory prefix and suffix)
+    /// - Template name (derived from file path, without directory prefix and suffix, nor the
+    ///   `partials/` prefix of partials)
     /// - Full path to the template file
     ///
     /// Later directories in the list take precedence over earlier ones when template names conflict.
@@ -231,6 +683,11 @@ impl TemplateManager {
                                 .to_string_lossy()
                                 .replace("\\", "/")
                                 .strip_suffix(format!(".{}", self.suffix).as_str())
+                                .map(|name| {
+                                    name.strip_prefix(PARTIALS_DIRNAME)
+                                        .and_then(|rest| rest.strip_prefix('/'))
+                                        .unwrap_or(name)
+                                })
                                 .unwrap()
                                 .to_string(),
                             e.path().to_path_buf(),
@@ -249,26 +706,100 @@ impl TemplateManager {
         res
     }
 
+    /// Returns the sorted entries of an allowlist.
+    fn sorted(allowlist: &Allowlist) -> Vec<String> {
+        let mut entries: Vec<String> = allowlist
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    /// Returns the registered name of a template variant.
+    fn variant_name(name: &str, variant: &str) -> String {
+        if variant == DEFAULT_VARIANT {
+            name.to_string()
+        } else {
+            format!("{name}{VARIANT_SEPARATOR}{variant}")
+        }
+    }
+
+    /// Collects the sorted variant labels registered for the given base name.
+    pub fn variants_of(&self, name: &str) -> Vec<String> {
+        let mut variants: Vec<String> = self
+            .handlebars
+            .get_templates()
+            .keys()
+            .filter_map(|registered| {
+                if registered == name {
+                    Some(DEFAULT_VARIANT.to_string())
+                } else {
+                    registered
+                        .strip_prefix(name)
+                        .and_then(|rest| rest.strip_prefix(VARIANT_SEPARATOR))
+                        .map(str::to_string)
+                }
+            })
+            .collect();
+        variants.sort();
+        variants
+    }
+
+    /// Picks a variant of the given template at random, weighted by `weights`.
+    ///
+    /// Variants absent from `weights` get a weight of 1.0, variants with a non-positive
+    /// weight are never picked.
+    fn pick_variant(&self, name: &str, weights: &HashMap<String, f64>) -> Option<String> {
+        let candidates: Vec<(String, f64)> = self
+            .variants_of(name)
+            .into_iter()
+            .map(|variant| {
+                let weight = weights.get(&variant).copied().unwrap_or(1.0);
+                (variant, weight)
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+
+        let total: f64 = candidates.iter().map(|(_, weight)| weight).sum();
+        let mut point = fastrand::f64() * total;
+        candidates
+            .iter()
+            .find(|(_, weight)| {
+                point -= weight;
+                point < 0.0
+            })
+            .or(candidates.last())
+            .map(|(variant, _)| variant.clone())
+    }
+
     /// Renders a registered template by name with the given data.
     pub fn render(&self, name: &str, data: &Value) -> Result<String, handlebars::RenderError> {
         self.handlebars.render(name, data)
     }
 
-    /// Renders a registered template for each data item in parallel via rayon.
-    /// Preserves input order via index-sorting after parallel collection.
+    /// Renders a registered template for each data item in parallel via rayon, keeping the
+    /// result of every item in input order.
+    pub fn render_each(
+        &self,
+        name: &str,
+        data: &[Value],
+    ) -> Vec<Result<String, handlebars::RenderError>> {
+        data.par_iter()
+            .map(|item| self.handlebars.render(name, item))
+            .collect()
+    }
+
+    /// Renders a registered template for each data item in parallel via rayon, failing with
+    /// every failing item if any.
     pub fn render_batch(
         &self,
         name: &str,
         data: &[Value],
-    ) -> Result<Vec<String>, handlebars::RenderError> {
-        let mut results: Vec<(usize, String)> = data
-            .iter()
-            .enumerate()
-            .par_bridge()
-            .map(|(idx, item)| self.handlebars.render(name, item).map(|s| (idx, s)))
-            .collect::<Result<Vec<_>, _>>()?;
-        results.sort_by_key(|x| x.0);
-        Ok(results.into_iter().map(|x| x.1).collect())
+    ) -> Result<Vec<String>, BatchRenderError> {
+        Self::collect_batch(self.render_each(name, data))
     }
 
     /// Renders a raw template string with the given data.
@@ -280,46 +811,125 @@ impl TemplateManager {
         self.handlebars.render_template(template, data)
     }
 
-    /// Renders a raw template string for each data item in parallel via rayon.
-    /// Preserves input order via index-sorting after parallel collection.
+    /// Renders a raw template string for each data item in parallel via rayon, keeping the
+    /// result of every item in input order.
+    pub fn render_raw_each(
+        &self,
+        template: &str,
+        data: &[Value],
+    ) -> Vec<Result<String, handlebars::RenderError>> {
+        data.par_iter()
+            .map(|item| self.handlebars.render_template(template, item))
+            .collect()
+    }
+
+    /// Renders a raw template string for each data item in parallel via rayon, failing with
+    /// every failing item if any.
     pub fn render_raw_batch(
         &self,
         template: &str,
         data: &[Value],
-    ) -> Result<Vec<String>, handlebars::RenderError> {
-        let mut results: Vec<(usize, String)> = data
-            .iter()
+    ) -> Result<Vec<String>, BatchRenderError> {
+        Self::collect_batch(self.render_raw_each(template, data))
+    }
+
+    /// Gathers the rendered items of a batch, or the errors of the failing ones.
+    fn collect_batch(
+        results: Vec<Result<String, handlebars::RenderError>>,
+    ) -> Result<Vec<String>, BatchRenderError> {
+        let total = results.len();
+        let mut rendered = Vec::with_capacity(total);
+        let mut failures = Vec::new();
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(text) => rendered.push(text),
+                Err(e) => failures.push((index, e)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(rendered)
+        } else {
+            Err(BatchRenderError { total, failures })
+        }
+    }
+
+    /// Converts the results of a batch to a Python list, raising in strict mode if any item
+    /// failed, or putting `None` in place of the failing items and logging them otherwise.
+    fn batch_to_py<'a>(
+        py: Python<'a>,
+        results: Vec<Result<String, handlebars::RenderError>>,
+        strict: bool,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        if strict {
+            let rendered = Self::collect_batch(results)?;
+            return Ok(PyList::new(py, rendered)?.into_any());
+        }
+        let total = results.len();
+        let rendered: Vec<Option<String>> = results
+            .into_iter()
             .enumerate()
-            .par_bridge()
-            .map(|(idx, item)| {
-                self.handlebars
-                    .render_template(template, item)
-                    .map(|s| (idx, s))
+            .map(|(index, result)| {
+                result
+                    .inspect_err(|e| warn!(item = index, "Skipped an item failing to render: {e}"))
+                    .ok()
             })
-            .collect::<Result<Vec<_>, _>>()?;
-        results.sort_by_key(|x| x.0);
-        Ok(results.into_iter().map(|x| x.1).collect())
+            .collect();
+        let skipped = rendered.iter().filter(|text| text.is_none()).count();
+        if skipped > 0 {
+            warn!("Skipped {skipped} of {total} items failing to render");
+        }
+        Ok(PyList::new(py, rendered)?.into_any())
+    }
+
+    /// Registers a helper, remembering its name to tell it from variables when linting.
+    fn register_helper_def(&mut self, name: &str, helper: Box<dyn HelperDef + Send + Sync>) {
+        Arc::make_mut(&mut self.handlebars).register_helper(name, helper);
+        self.helpers.insert(name.to_string());
     }
 
     fn register_builtin_helper(&mut self) -> &mut Self {
-        self.handlebars.register_helper("len", Box::new(len));
-        self.handlebars.register_helper("lang", Box::new(getlang));
-        self.handlebars.register_helper("hash", Box::new(hash));
-        self.handlebars
-            .register_helper("words", Box::new(word_count));
-        self.handlebars.register_helper("block", Box::new(block));
-        self.handlebars
-            .register_helper("ls", Box::new(list_out_string));
-        self.handlebars.register_helper("code", Box::new(code));
-
-        self.handlebars
-            .register_helper("date", Box::new(timestamp_to_date));
-        self.handlebars.register_helper("head", Box::new(head));
-        self.handlebars.register_helper("join", Box::new(join));
+        self.register_helper_def("len", Box::new(len));
+        self.register_helper_def("lang", Box::new(getlang));
+        self.register_helper_def("hash", Box::new(hash));
+        self.register_helper_def("words", Box::new(word_count));
+        self.register_helper_def("block", Box::new(block));
+        self.register_helper_def("ls", Box::new(list_out_string));
+        self.register_helper_def("code", Box::new(code));
+
+        self.register_helper_def("date", Box::new(timestamp_to_date));
+        self.register_helper_def("head", Box::new(head));
+        self.register_helper_def("join", Box::new(join));
+        self.register_helper_def("truncate", Box::new(truncate_by_tokens));
+        self.register_helper_def("replace", Box::new(RegexReplaceHelper));
+        self.register_helper_def("json", Box::new(json_pretty));
+        self.register_helper_def("table", Box::new(table));
+        self.register_helper_def("snake_case", Box::new(snake_case));
+        self.register_helper_def("camel_case", Box::new(camel_case));
+        let env = EnvHelper {
+            allowlist: Arc::clone(&self.env_allowlist),
+        };
+        self.register_helper_def("env", Box::new(env));
+        let config = ConfigHelper {
+            allowlist: Arc::clone(&self.config_allowlist),
+        };
+        self.register_helper_def("config", Box::new(config));
         self
     }
 
+    /// Registers the templates and partials registered from Python, over the discovered
+    /// templates.
     fn register_partials(&mut self) -> &mut Self {
+        let handlebars = Arc::make_mut(&mut self.handlebars);
+        for (name, source) in &self.sources {
+            if let Err(e) = handlebars.register_template_string(name, source) {
+                error!("Failed to register template {name}: {e}");
+            }
+        }
+        for (name, source) in &self.partials {
+            if let Err(e) = handlebars.register_partial(name, source) {
+                error!("Failed to register partial {name}: {e}");
+            }
+        }
         self
     }
 }
@@ -330,18 +940,29 @@ pyo3_stub_gen::inventory::submit! {
         r#"
         class TemplateManager:
             @overload
-            def render_template(self,name:str,data: typing.Dict[str,typing.Any]) -> str: ...
+            def render_template(self,name:str,data: typing.Dict[str,typing.Any],strict: typing.Optional[bool] = None) -> str: ...
             @overload
-            def render_template(self,name:str,data: typing.List[typing.Dict[str,typing.Any]]) -> typing.List[str]: ...
+            def render_template(self,name:str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Literal[True]) -> typing.List[str]: ...
+            @overload
+            def render_template(self,name:str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Optional[bool] = None) -> typing.List[typing.Optional[str]]: ...
             @typing.overload
             def render_template(
-                self, name: str, data: typing.List[typing.Dict[str, typing.Any]] | typing.Dict[str, typing.Any]
-            ) -> typing.List[str] | str: ...
+                self, name: str, data: typing.List[typing.Dict[str, typing.Any]] | typing.Dict[str, typing.Any], strict: typing.Optional[bool] = None
+            ) -> typing.List[typing.Optional[str]] | str: ...
+
+            @overload
+            def render_template_async(self,name:str,data: typing.Dict[str,typing.Any],strict: typing.Optional[bool] = None) -> typing.Awaitable[str]: ...
+            @overload
+            def render_template_async(self,name:str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Literal[True]) -> typing.Awaitable[typing.List[str]]: ...
+            @overload
+            def render_template_async(self,name:str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Optional[bool] = None) -> typing.Awaitable[typing.List[typing.Optional[str]]]: ...
 
             @overload
-            def render_template_raw(self,template: str,data: typing.Dict[str,typing.Any]) -> str: ...
+            def render_template_raw(self,template: str,data: typing.Dict[str,typing.Any],strict: typing.Optional[bool] = None) -> str: ...
+            @overload
+            def render_template_raw(self,template: str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Literal[True]) -> typing.List[str]: ...
             @overload
-            def render_template_raw(self,template: str,data: typing.List[typing.Dict[str,typing.Any]]) -> typing.List[str]: ...
+            def render_template_raw(self,template: str,data: typing.List[typing.Dict[str,typing.Any]],strict: typing.Optional[bool] = None) -> typing.List[typing.Optional[str]]: ...
 
         "#
     }
@@ -366,6 +987,7 @@ pub static TEMPLATE_MANAGER: Lazy<TemplateManager> = Lazy::new(TemplateManager::
 ///     PyResult<()> indicating success.
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<TemplateManager>()?;
+    m.add_class::<TemplateVariables>()?;
     m.add(TEMPLATE_MANAGER_VARNAME, TEMPLATE_MANAGER.to_owned())?;
     Ok(())
 }
@@ -0,0 +1,252 @@
+//! Matching event names against subscription patterns with a trie of their segments.
+//!
+//! Event names are split on the emitter delimiter into hierarchical topics, such as
+//! `task::write::Finished`. In a pattern, a `*` segment matches exactly one segment and a
+//! `**` segment any number of them, none included, so `task::*::Finished` observes every
+//! finished task and `task::**` everything under `task`. Other segments match literally.
+//! Emitting an event walks the trie once, whatever the number of subscriptions.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// The segment matching exactly one segment.
+const SINGLE: &str = "*";
+/// The segment matching any number of segments.
+const MULTI: &str = "**";
+
+/// A node of the trie, for a prefix of the patterns.
+#[derive(Debug, Default)]
+struct Node {
+    literal: HashMap<String, Node>,
+    single: Option<Box<Node>>,
+    multi: Option<Box<Node>>,
+    /// The subscriptions whose pattern ends at this node.
+    subscriptions: Vec<u64>,
+}
+
+impl Node {
+    fn child(&mut self, segment: &str) -> &mut Node {
+        match segment {
+            SINGLE => self.single.get_or_insert_default(),
+            MULTI => self.multi.get_or_insert_default(),
+            _ => self.literal.entry(segment.to_string()).or_default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+            && self.literal.is_empty()
+            && self.single.is_none()
+            && self.multi.is_none()
+    }
+
+    /// Removes the subscriptions of the pattern below this node, all of them or only `id`, and
+    /// prunes the emptied nodes.
+    fn remove(&mut self, segments: &[&str], id: Option<u64>) -> Vec<u64> {
+        let Some((first, rest)) = segments.split_first() else {
+            return match id {
+                Some(id) => match self.subscriptions.iter().position(|s| *s == id) {
+                    Some(index) => vec![self.subscriptions.remove(index)],
+                    None => Vec::new(),
+                },
+                None => std::mem::take(&mut self.subscriptions),
+            };
+        };
+        let removed;
+        match *first {
+            SINGLE | MULTI => {
+                let slot = if *first == SINGLE {
+                    &mut self.single
+                } else {
+                    &mut self.multi
+                };
+                let Some(node) = slot else {
+                    return Vec::new();
+                };
+                removed = node.remove(rest, id);
+                if node.is_empty() {
+                    *slot = None;
+                }
+            }
+            literal => {
+                let Some(node) = self.literal.get_mut(literal) else {
+                    return Vec::new();
+                };
+                removed = node.remove(rest, id);
+                if node.is_empty() {
+                    self.literal.remove(literal);
+                }
+            }
+        }
+        removed
+    }
+
+    /// Collects the subscriptions of the patterns below this node matching the segments.
+    fn collect(&self, segments: &[&str], found: &mut BTreeSet<u64>) {
+        if let Some(multi) = &self.multi {
+            for skipped in 0..=segments.len() {
+                multi.collect(&segments[skipped..], found);
+            }
+        }
+        let Some((first, rest)) = segments.split_first() else {
+            found.extend(&self.subscriptions);
+            return;
+        };
+        if let Some(node) = self.literal.get(*first) {
+            node.collect(rest, found);
+        }
+        if let Some(single) = &self.single {
+            single.collect(rest, found);
+        }
+    }
+}
+
+/// Subscriptions to event patterns, matched against emitted events in a single walk.
+///
+/// Subscriptions are identified by the integer `subscribe` returns, which the emitter maps to
+/// its handlers.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass]
+#[derive(Debug)]
+pub struct TopicTrie {
+    root: Node,
+    delimiter: String,
+    next_id: u64,
+    len: usize,
+}
+
+impl TopicTrie {
+    /// Removes a single subscription to a pattern.
+    ///
+    /// Returns:
+    ///     Whether the subscription was found.
+    pub(crate) fn remove(&mut self, pattern: &str, id: u64) -> PyResult<bool> {
+        let segments = self.segments(pattern)?;
+        let removed = !self.root.remove(&segments, Some(id)).is_empty();
+        if removed {
+            self.len -= 1;
+        }
+        Ok(removed)
+    }
+
+    fn segments<'a>(&self, name: &'a str) -> PyResult<Vec<&'a str>> {
+        if name.is_empty() {
+            return Err(PyValueError::new_err("Pattern cannot be empty"));
+        }
+        Ok(name.split(self.delimiter.as_str()).collect())
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl TopicTrie {
+    /// Creates an empty trie.
+    ///
+    /// Args:
+    ///     delimiter: The delimiter of the segments of event names, the `emitter.delimiter`
+    ///         config value if None.
+    ///
+    /// Raises:
+    ///     ValueError: If the delimiter is empty.
+    #[new]
+    #[pyo3(signature = (delimiter = None))]
+    pub(crate) fn new(delimiter: Option<String>) -> PyResult<Self> {
+        let delimiter =
+            delimiter.unwrap_or_else(|| fabricatio_config::CONFIG.emitter.delimiter.clone());
+        if delimiter.is_empty() {
+            return Err(PyValueError::new_err("The delimiter must not be empty"));
+        }
+        Ok(Self {
+            root: Node::default(),
+            delimiter,
+            next_id: 0,
+            len: 0,
+        })
+    }
+
+    /// The delimiter of the segments of event names.
+    #[getter]
+    pub(crate) fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    /// Subscribes to the events matching a pattern.
+    ///
+    /// Args:
+    ///     pattern: The pattern, such as `task::*::Finished` or `task::**`.
+    ///
+    /// Returns:
+    ///     The identifier of the subscription, increasing with each subscription.
+    ///
+    /// Raises:
+    ///     ValueError: If the pattern is empty.
+    pub(crate) fn subscribe(&mut self, pattern: &str) -> PyResult<u64> {
+        let segments = self.segments(pattern)?;
+        let node = segments
+            .into_iter()
+            .fold(&mut self.root, |node, segment| node.child(segment));
+        let id = self.next_id;
+        node.subscriptions.push(id);
+        self.next_id += 1;
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// Removes every subscription to a pattern.
+    ///
+    /// Args:
+    ///     pattern: The pattern as subscribed to.
+    ///
+    /// Returns:
+    ///     The identifiers of the removed subscriptions, empty if there were none.
+    ///
+    /// Raises:
+    ///     ValueError: If the pattern is empty.
+    fn unsubscribe(&mut self, pattern: &str) -> PyResult<Vec<u64>> {
+        let segments = self.segments(pattern)?;
+        let removed = self.root.remove(&segments, None);
+        self.len -= removed.len();
+        Ok(removed)
+    }
+
+    /// Finds the subscriptions whose pattern matches an event.
+    ///
+    /// Args:
+    ///     event: The name of the emitted event, such as `task::write::Finished`.
+    ///
+    /// Returns:
+    ///     The identifiers of the matching subscriptions, in the order they were made.
+    pub(crate) fn matches(&self, event: &str) -> Vec<u64> {
+        let segments: Vec<&str> = event.split(self.delimiter.as_str()).collect();
+        let mut found = BTreeSet::new();
+        self.root.collect(&segments, &mut found);
+        found.into_iter().collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TopicTrie(delimiter={:?}, subscriptions={})",
+            self.delimiter, self.len
+        )
+    }
+}
+
+/// Registers the topic trie with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<TopicTrie>()?;
+    Ok(())
+}
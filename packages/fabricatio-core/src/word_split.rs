@@ -2,6 +2,126 @@ use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use unicode_segmentation::UnicodeSegmentation;
+use whichlang::{Lang, detect_language};
+
+/// Abbreviations ending with a period that does not end a sentence, lowercase and without
+/// their final period, by language code.
+const ABBREVIATIONS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "cf",
+            "al", "fig", "figs", "eq", "vol", "pp", "ch", "approx", "dept", "inc", "ltd", "co",
+            "corp", "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov",
+            "dec", "u.s", "u.k",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "z.b", "bzw", "usw", "ca", "dr", "prof", "nr", "vgl", "d.h", "u.a", "evtl", "ggf",
+            "str", "abs", "bzgl", "inkl", "s",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "m", "mme", "mlle", "dr", "pr", "etc", "cf", "env", "p.ex", "av", "bd", "ch", "vol",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "sr", "sra", "srta", "dr", "dra", "etc", "p.ej", "ud", "uds", "pág", "núm", "av",
+        ],
+    ),
+    (
+        "it",
+        &["sig", "sigg", "dott", "prof", "ecc", "es", "pag", "vol"],
+    ),
+    ("pt", &["sr", "sra", "dr", "dra", "etc", "pág", "av", "vol"]),
+    (
+        "nl",
+        &[
+            "dhr", "mevr", "dr", "prof", "bijv", "enz", "o.a", "d.w.z", "blz",
+        ],
+    ),
+];
+
+/// Returns the code of the language of a text, such as `en` or `zh`.
+fn language_code(text: &str) -> &'static str {
+    match detect_language(text) {
+        Lang::Ara => "ar",
+        Lang::Cmn => "zh",
+        Lang::Deu => "de",
+        Lang::Eng => "en",
+        Lang::Fra => "fr",
+        Lang::Hin => "hi",
+        Lang::Ita => "it",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Nld => "nl",
+        Lang::Por => "pt",
+        Lang::Rus => "ru",
+        Lang::Spa => "es",
+        Lang::Swe => "sv",
+        Lang::Tur => "tr",
+        Lang::Vie => "vi",
+    }
+}
+
+/// Whether a sentence found by Unicode segmentation actually ends with an abbreviation or
+/// an initial, such as `Dr.` or `J.`, rather than with the end of a sentence.
+fn ends_with_abbreviation(sentence: &str, abbreviations: &[&str]) -> bool {
+    let Some(word) = sentence
+        .trim_end()
+        .strip_suffix('.')
+        .and_then(|rest| rest.split_whitespace().last())
+    else {
+        return false;
+    };
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let mut chars = word.chars();
+    let is_initial = matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase());
+    is_initial || abbreviations.contains(&word.to_lowercase().as_str())
+}
+
+/// Splits a text into sentences, keeping the whitespace after each of them so that they
+/// join back into the text.
+///
+/// Sentence ends follow Unicode segmentation, which also covers the CJK terminators such as
+/// `。`, `！` and `？` and the closing quotes and brackets after them. Ends found after an
+/// abbreviation of the language or an initial are undone.
+///
+/// Args:
+///     text: The text to split.
+///     lang: The code of the language of the text, such as `en` or `en-US`, detected if None.
+pub(crate) fn sentence_spans<'a>(text: &'a str, lang: Option<&str>) -> Vec<&'a str> {
+    let code = match lang {
+        Some(lang) => lang
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase(),
+        None => language_code(text).to_string(),
+    };
+    let abbreviations = ABBREVIATIONS
+        .iter()
+        .find(|(lang, _)| *lang == code)
+        .map_or(&[][..], |(_, abbreviations)| *abbreviations);
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (offset, sentence) in text.split_sentence_bound_indices() {
+        let end = offset + sentence.len();
+        if end < text.len() && ends_with_abbreviation(&text[start..end], abbreviations) {
+            continue;
+        }
+        spans.push(&text[start..end]);
+        start = end;
+    }
+    spans
+}
 
 /// Splits a string into words using Unicode word boundaries.
 ///
@@ -38,6 +158,65 @@ fn split_sentence_bounds(string: &str) -> Vec<String> {
         .collect()
 }
 
+/// Splits a text into sentences, trimmed and non-empty.
+///
+/// Unlike `split_sentence_bounds`, a period after an abbreviation such as `Dr.` or `e.g.`
+/// or after an initial does not end a sentence. CJK terminators such as `。`, `！` and `？`
+/// end sentences even without a following space, along with the closing quotes after them.
+///
+/// Args:
+///     text: The text to split.
+///     lang: The code of the language of the text, such as `en` or `de`, which selects the
+///         abbreviations. Detected from the text if None.
+///
+/// Returns:
+///     A list of sentence strings.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (text, lang = None))]
+fn split_sentences(text: &str, lang: Option<&str>) -> Vec<String> {
+    sentence_spans(text, lang)
+        .into_iter()
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits a text into paragraphs, trimmed and non-empty.
+///
+/// Paragraphs are separated by blank lines. A line indented with an ideographic space
+/// (`U+3000`), as in CJK typesetting, also starts a paragraph. The lines of a paragraph are
+/// kept as they are.
+///
+/// Args:
+///     text: The text to split.
+///
+/// Returns:
+///     A list of paragraph strings.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn split_paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if line.trim().is_empty() || line.starts_with('\u{3000}') {
+            paragraphs.push(std::mem::take(&mut current));
+        }
+        if !line.trim().is_empty() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    paragraphs.push(current);
+    paragraphs
+        .iter()
+        .map(|paragraph| paragraph.trim())
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Splits a string into chunks based on maximum size and overlapping rate.
 ///
 /// The function prioritizes splitting at sentence boundaries. If a sentence
@@ -58,14 +237,14 @@ fn split_into_chunks(
     max_chunk_size: usize,
     max_overlapping_rate: f64,
 ) -> Vec<String> {
-    let sentences = split_sentence_bounds(string);
+    let sentences = sentence_spans(string, None);
     let mut res = vec![];
     let max_overlapping_size = (max_overlapping_rate * max_chunk_size as f64) as usize;
     let mut overlapping = String::new();
     let mut current_chunk = String::new();
 
     for s in sentences {
-        current_chunk.push_str(&s);
+        current_chunk.push_str(s);
         let overlapping_word_count = word_count(overlapping.as_str());
         let current_word_count = word_count(current_chunk.as_str());
         if overlapping_word_count + current_word_count > max_chunk_size {
@@ -98,7 +277,7 @@ fn split_into_chunks(
 ///     A list of sentence strings that form the tail.
 fn get_tail_sentences(string: &str, max_size: usize) -> Vec<String> {
     let mut res: Vec<String> = vec![];
-    for s in split_sentence_bounds(string).iter().rev() {
+    for s in sentence_spans(string, None).iter().rev() {
         if word_count(s.as_str()) + word_count(res.join("").as_str()) <= max_size {
             res.push(s.to_string());
         } else {
@@ -128,6 +307,37 @@ pub(crate) fn word_count(string: &str) -> usize {
         .count()
 }
 
+/// Estimates the number of tokens of a word segment: none for whitespace, one per Chinese or
+/// Japanese character and one per four characters otherwise, rounded up.
+fn segment_tokens(segment: &str) -> usize {
+    if segment.trim().is_empty() {
+        return 0;
+    }
+    let unspaced = segment
+        .chars()
+        .filter(|&c| matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF))
+        .count();
+    unspaced + (segment.chars().count() - unspaced).div_ceil(4)
+}
+
+/// Cuts a string to the longest prefix of whole words estimated to fit in a number of tokens,
+/// without trailing whitespace, None if the whole string fits.
+///
+/// The estimate follows the usual rule of thumb of about four characters per token, and a
+/// token per character for Chinese and Japanese, so it needs no tokenizer.
+pub(crate) fn truncate_tokens(string: &str, max_tokens: usize) -> Option<&str> {
+    let mut used = 0;
+    let mut end = 0;
+    for (start, segment) in string.split_word_bound_indices() {
+        used += segment_tokens(segment);
+        if used > max_tokens {
+            return Some(string[..end].trim_end());
+        }
+        end = start + segment.len();
+    }
+    None
+}
+
 /// Registers the word splitting functions with the Python module.
 ///
 /// Args:
@@ -140,6 +350,8 @@ pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(split_word_bounds, m)?)?;
     m.add_function(wrap_pyfunction!(word_count, m)?)?;
     m.add_function(wrap_pyfunction!(split_sentence_bounds, m)?)?;
+    m.add_function(wrap_pyfunction!(split_sentences, m)?)?;
+    m.add_function(wrap_pyfunction!(split_paragraphs, m)?)?;
     m.add_function(wrap_pyfunction!(split_into_chunks, m)?)?;
     Ok(())
 }
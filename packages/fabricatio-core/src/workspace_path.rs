@@ -0,0 +1,66 @@
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::path::PathBuf;
+use utils::workspace_path;
+
+/// Resolves a path against a workspace root, refusing paths that lead out of it.
+///
+/// A relative path is taken relative to the root, an absolute one must lie below it.
+/// Symlinks are followed before checking, so a link inside the root cannot reach a file
+/// outside of it, and the path may name a file that does not exist yet.
+///
+/// Args:
+///     root: The workspace root, which must exist.
+///     candidate: The path to resolve.
+///
+/// Returns:
+///     The canonical path the candidate designates, a verbatim path on Windows.
+///
+/// Raises:
+///     PermissionError: If the path leads out of the root.
+///     FileNotFoundError: If the root does not exist or the path goes through a dangling
+///         symlink.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn confine(root: PathBuf, candidate: PathBuf) -> PyResult<PathBuf> {
+    Ok(workspace_path::confine(&root, &candidate)?)
+}
+
+/// Makes a path relative to a workspace root, normalizing `.` and `..` components.
+///
+/// Args:
+///     root: The workspace root.
+///     path: An absolute path below the root, or a path relative to it.
+///
+/// Returns:
+///     The path relative to the root, `.` for the root itself.
+///
+/// Raises:
+///     PermissionError: If the path is not below the root.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn relativize(root: PathBuf, path: PathBuf) -> PyResult<PathBuf> {
+    Ok(workspace_path::relativize(&root, &path)?)
+}
+
+/// Turns an absolute path into a verbatim `\\?\` path on Windows, which is not subject to the
+/// 260 characters limit. Other paths, and any path on other platforms, are returned as is.
+///
+/// Args:
+///     path: The path to convert.
+///
+/// Returns:
+///     The converted path.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn long_path(path: PathBuf) -> PathBuf {
+    workspace_path::long_path(&path)
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(confine, m)?)?;
+    m.add_function(wrap_pyfunction!(relativize, m)?)?;
+    m.add_function(wrap_pyfunction!(long_path, m)?)?;
+    Ok(())
+}
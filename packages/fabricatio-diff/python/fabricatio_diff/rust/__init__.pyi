@@ -1,21 +1,270 @@
 # This file is automatically generated by pyo3_stub_gen
 
 import builtins
+import os
+import pathlib
 import typing
 
 __all__ = [
+    "DirDiff",
+    "EditOp",
+    "FileDiff",
+    "HunkReport",
+    "InlineDiff",
+    "InlineSpan",
+    "MergeConflict",
+    "MergeResult",
+    "PatchResult",
     "apply_insert_after",
+    "apply_patch",
     "apply_replace",
     "apply_replace_lines",
     "apply_set_line",
     "compute_hash",
+    "diff_dirs",
+    "edit_script",
     "format_hashes",
+    "inline_diff",
     "match_lines",
+    "merge3",
     "parse_hashline_anchor",
     "rate",
     "show_diff",
 ]
 
+@typing.final
+class DirDiff:
+    r"""The differences between two directories."""
+    @property
+    def files(self) -> builtins.list[FileDiff]:
+        r"""The files that differ, sorted by path."""
+    @property
+    def unchanged(self) -> builtins.int:
+        r"""The number of files identical in both directories."""
+    @property
+    def identical(self) -> builtins.bool:
+        r"""Whether both directories hold the same files with the same content."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class EditOp:
+    r"""A single operation of an edit script, covering a range of lines in both texts.
+
+    Ranges are 0-based and end-exclusive line indices, where lines keep their line endings,
+    matching `str.splitlines(keepends=True)`. An `insert` has an empty old range at the
+    position it inserts at, a `delete` an empty new range at the position it deletes at.
+    """
+    @property
+    def tag(self) -> builtins.str:
+        r"""The kind of the operation, one of `equal`, `insert` or `delete`."""
+    @property
+    def old_start(self) -> builtins.int:
+        r"""The first line of the range in the old text."""
+    @property
+    def old_end(self) -> builtins.int:
+        r"""The line after the range in the old text."""
+    @property
+    def new_start(self) -> builtins.int:
+        r"""The first line of the range in the new text."""
+    @property
+    def new_end(self) -> builtins.int:
+        r"""The line after the range in the new text."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class FileDiff:
+    r"""How a file differs between two directories."""
+    @property
+    def path(self) -> builtins.str:
+        r"""The path of the file relative to the directories, with `/` separators."""
+    @property
+    def status(self) -> builtins.str:
+        r"""One of `added` (only in the second directory), `removed` (only in the first one) or
+        `modified`.
+        """
+    @property
+    def binary(self) -> builtins.bool:
+        r"""Whether either version of the file is not UTF-8 text, in which case lines are not
+        compared.
+        """
+    @property
+    def hunks(self) -> builtins.int:
+        r"""The number of hunks of a unified diff of the file with 3 lines of context, 1 for a
+        non-empty text file added or removed, 0 for a binary file.
+        """
+    @property
+    def insertions(self) -> builtins.int:
+        r"""The number of lines added."""
+    @property
+    def deletions(self) -> builtins.int:
+        r"""The number of lines removed."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class HunkReport:
+    r"""How a hunk of a patch was applied, or why it was not.
+
+    Line numbers are 1-based, like those of the hunk headers.
+    """
+    @property
+    def index(self) -> builtins.int:
+        r"""The position of the hunk in the patch, from 0."""
+    @property
+    def header(self) -> builtins.str:
+        r"""The `@@` line of the hunk."""
+    @property
+    def applied(self) -> builtins.bool:
+        r"""Whether the hunk was applied."""
+    @property
+    def expected_line(self) -> typing.Optional[builtins.int]:
+        r"""The line of the original text the hunk starts at according to its header, None if the
+        header names none.
+        """
+    @property
+    def applied_line(self) -> typing.Optional[builtins.int]:
+        r"""The line of the original text the hunk was applied at, None if it was not."""
+    @property
+    def offset(self) -> builtins.int:
+        r"""The number of lines between where the hunk was applied and where its header placed
+        it, negative if it was applied before.
+        """
+    @property
+    def fuzz(self) -> builtins.int:
+        r"""The number of context lines left out at each end of the hunk to apply it."""
+    @property
+    def error(self) -> typing.Optional[builtins.str]:
+        r"""Why the hunk was not applied, None if it was."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class InlineDiff:
+    r"""The changes between two texts as a sequence of spans covering both of them."""
+    @property
+    def spans(self) -> builtins.list[InlineSpan]:
+        r"""The spans in the order of both texts, without gaps. Concatenating the texts of the
+        `equal` and `delete` spans gives the old text, those of the `equal` and `insert`
+        spans the new one.
+        """
+    @property
+    def ratio(self) -> builtins.float:
+        r"""The similarity of both texts, from 0.0 for nothing in common to 1.0 for equal texts,
+        as the share of characters kept.
+        """
+    def to_ansi(self) -> builtins.str:
+        r"""Renders the changes for a terminal, deleted text struck through in red and inserted
+        text underlined in green.
+
+        Styles are closed before line breaks and opened again after them, so that the output
+        can be split into lines or paged.
+
+        Returns:
+            The text with ANSI escape codes around the changes.
+        """
+    def to_html(
+        self, delete_class: builtins.str = "diff-delete", insert_class: builtins.str = "diff-insert"
+    ) -> builtins.str:
+        r"""Renders the changes as HTML, deleted text in `<del>` and inserted text in `<ins>`.
+
+        All text is escaped, so the output can be put in a page as is. Line breaks are kept,
+        which shows them in an element styled with `white-space: pre-wrap`.
+
+        Args:
+            delete_class: The class of the `<del>` elements.
+            insert_class: The class of the `<ins>` elements.
+
+        Returns:
+            The HTML fragment.
+        """
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class InlineSpan:
+    r"""A run of text kept, inserted or deleted between two texts.
+
+    Offsets are 0-based and end-exclusive character indices, which slice Python strings. An
+    `insert` has an empty old range at the position it inserts at, a `delete` an empty new
+    range at the position it deletes at.
+    """
+    @property
+    def tag(self) -> builtins.str:
+        r"""The kind of the span, one of `equal`, `insert` or `delete`."""
+    @property
+    def text(self) -> builtins.str:
+        r"""The text of the span, from the new text for an `insert`, from the old one otherwise."""
+    @property
+    def old_start(self) -> builtins.int:
+        r"""The first character of the span in the old text."""
+    @property
+    def old_end(self) -> builtins.int:
+        r"""The character after the span in the old text."""
+    @property
+    def new_start(self) -> builtins.int:
+        r"""The first character of the span in the new text."""
+    @property
+    def new_end(self) -> builtins.int:
+        r"""The character after the span in the new text."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class MergeConflict:
+    r"""A region both sides changed differently, left to be resolved by hand.
+
+    Ranges are 0-based and end-exclusive line indices, like those of `EditOp`.
+    """
+    @property
+    def base_start(self) -> builtins.int:
+        r"""The first line of the region in the base text."""
+    @property
+    def base_end(self) -> builtins.int:
+        r"""The line after the region in the base text."""
+    @property
+    def merged_start(self) -> builtins.int:
+        r"""The first line of the conflict block in the merged text, at its `<<<<<<<` marker."""
+    @property
+    def merged_end(self) -> builtins.int:
+        r"""The line after the conflict block in the merged text, past its `>>>>>>>` marker."""
+    @property
+    def base(self) -> builtins.str:
+        r"""The lines of the region in the base text."""
+    @property
+    def ours(self) -> builtins.str:
+        r"""The lines of the region in our text."""
+    @property
+    def theirs(self) -> builtins.str:
+        r"""The lines of the region in their text."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class MergeResult:
+    r"""The outcome of a three-way merge."""
+    @property
+    def merged(self) -> builtins.str:
+        r"""The merged text, with conflict markers around the conflicting regions."""
+    @property
+    def conflicts(self) -> builtins.list[MergeConflict]:
+        r"""The conflicting regions, in the order of the merged text."""
+    @property
+    def clean(self) -> builtins.bool:
+        r"""Whether the merge has no conflicts."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class PatchResult:
+    r"""The outcome of applying a patch."""
+    @property
+    def patched(self) -> builtins.str:
+        r"""The patched text, without the hunks that failed."""
+    @property
+    def hunks(self) -> builtins.list[HunkReport]:
+        r"""A report for every hunk of the patch, in the order of the patch."""
+    @property
+    def clean(self) -> builtins.bool:
+        r"""Whether every hunk was applied."""
+    @property
+    def failed(self) -> builtins.list[HunkReport]:
+        r"""The reports of the hunks that were not applied."""
+    def __repr__(self) -> builtins.str: ...
+
 def apply_insert_after(content: builtins.str, anchor: builtins.str, text: builtins.str) -> builtins.str:
     r"""Applies an insert_after edit to content based on a hashline anchor.
 
@@ -28,6 +277,29 @@ def apply_insert_after(content: builtins.str, anchor: builtins.str, text: builti
         The modified content after applying the edit.
     """
 
+def apply_patch(original: builtins.str, unified_diff: builtins.str, fuzz: builtins.int = 2) -> PatchResult:
+    r"""Applies a unified diff to a text.
+
+    Each hunk is looked for at the line its header names, shifted by the offset the previous
+    hunks were applied at, then at the nearest place its lines match, as `patch` does. If its
+    lines match nowhere, it is tried again without up to `fuzz` context lines at each end.
+    Hunks that still match nowhere are left out and reported, so a partly wrong patch, such as
+    one written by a language model, applies as far as it can. Headers without line ranges,
+    `@@ ... @@`, and blank lines standing for empty context lines are accepted.
+
+    Args:
+        original: The text to patch.
+        unified_diff: The patch, for a single file. File headers and text around the hunks are
+            ignored.
+        fuzz: The number of context lines that may be left out at each end of a hunk.
+
+    Returns:
+        A `PatchResult` with the patched text and a `HunkReport` per hunk.
+
+    Raises:
+        ValueError: If the patch holds no hunk or a malformed hunk header.
+    """
+
 def apply_replace(
     content: builtins.str, old_text: builtins.str, new_text: builtins.str, all: builtins.bool
 ) -> builtins.str:
@@ -80,6 +352,48 @@ def compute_hash(line: builtins.str) -> builtins.str:
         A hex string representing the line's hash.
     """
 
+def diff_dirs(
+    dir_a: builtins.str | os.PathLike | pathlib.Path,
+    dir_b: builtins.str | os.PathLike | pathlib.Path,
+    include_globs: typing.Sequence[builtins.str] = [],
+    exclude_globs: typing.Sequence[builtins.str] = [],
+) -> DirDiff:
+    r"""Compares two directories file by file, such as two snapshots of an agent workspace.
+
+    Files are matched by their path relative to each directory and compared in parallel.
+    Hidden files and files ignored by `.gitignore` are compared too; leave them out with
+    `exclude_globs`. Symbolic links are not followed.
+
+    Args:
+        dir_a: The first directory, such as the older snapshot.
+        dir_b: The second directory, such as the newer snapshot.
+        include_globs: Globs of the files to compare, relative to the directories, in the
+            `.gitignore` syntax. All files are compared if empty.
+        exclude_globs: Globs of the files and directories not to compare, such as `.git/` or `*.pyc`.
+
+    Returns:
+        A `DirDiff` with a `FileDiff` per added, removed or modified file.
+
+    Raises:
+        FileNotFoundError: If either path is not a directory.
+        ValueError: If a glob is invalid.
+    """
+
+def edit_script(old: builtins.str, new: builtins.str) -> builtins.list[EditOp]:
+    r"""Computes a line-level edit script turning `old` into `new`.
+
+    The operations cover both texts in order without gaps, so applying them means copying
+    `equal` ranges from `old`, taking `insert` ranges from `new` and skipping `delete` ranges.
+    Replaced lines are reported as a `delete` followed by an `insert`.
+
+    Args:
+        old: The original text.
+        new: The modified text.
+
+    Returns:
+        A list of `EditOp` with line ranges into both texts.
+    """
+
 def format_hashes(content: builtins.str, start_line: builtins.int = 1) -> builtins.str:
     r"""Formats content with LINE:HASH anchors for each line.
 
@@ -91,6 +405,27 @@ def format_hashes(content: builtins.str, start_line: builtins.int = 1) -> builti
         A string where each line is prefixed with its line number and hash.
     """
 
+def inline_diff(old: builtins.str, new: builtins.str, granularity: builtins.str = "word") -> InlineDiff:
+    r"""Computes the changes between two texts by line, word or character, as spans of text.
+
+    With `word`, words are runs of letters, digits and underscores, runs of whitespace, and
+    single punctuation marks. Chinese and Japanese characters, written without spaces, are
+    words of their own, so changes within prose in those languages are precise too. With
+    `char`, every character is compared. Replaced text is reported as a `delete` followed by
+    an `insert`.
+
+    Args:
+        old: The original text.
+        new: The modified text.
+        granularity: The units to compare, one of `line`, `word` or `char`.
+
+    Returns:
+        An `InlineDiff` with the spans, which renders them with `to_ansi` and `to_html`.
+
+    Raises:
+        ValueError: If the granularity is unknown.
+    """
+
 def match_lines(
     haystack: builtins.str, needle: builtins.str, match_precision: builtins.float = 0.9
 ) -> typing.Optional[builtins.str]:
@@ -109,6 +444,34 @@ def match_lines(
         or None if no match meets the precision threshold.
     """
 
+def merge3(
+    base: builtins.str,
+    ours: builtins.str,
+    theirs: builtins.str,
+    ours_label: builtins.str = "ours",
+    theirs_label: builtins.str = "theirs",
+    show_base: builtins.bool = False,
+) -> MergeResult:
+    r"""Merges the changes two sides made to a common base, line by line.
+
+    Changes made by one side only are taken as is, and so are the identical changes made by
+    both. Regions both sides changed differently, or changed next to each other, are
+    conflicts: the merged text holds both versions between `<<<<<<<`, `=======` and `>>>>>>>`
+    markers, as `git merge` writes them.
+
+    Args:
+        base: The common ancestor of both texts.
+        ours: Our version of the text, such as the one an agent edited.
+        theirs: Their version of the text, such as the one a person edited meanwhile.
+        ours_label: The label of our side, written after the `<<<<<<<` marker.
+        theirs_label: The label of their side, written after the `>>>>>>>` marker.
+        show_base: Whether conflict blocks also hold the base version, after a `|||||||`
+            marker, like the `diff3` conflict style of git.
+
+    Returns:
+        A `MergeResult` with the merged text and its conflicts.
+    """
+
 def parse_hashline_anchor(anchor: builtins.str) -> tuple[builtins.int, builtins.str]:
     r"""Parses a hashline anchor in the format "LINE:HASH".
 
@@ -0,0 +1,71 @@
+"""Tests for the unified patch engine of fabricatio-diff Rust bindings."""
+
+import pytest
+from fabricatio_diff.rust import apply_patch, show_diff
+
+ORIGINAL = "a\nb\nc\nd\ne\nf\ng\n"
+
+
+def test_apply_patch_exact() -> None:
+    """A patch matching the text applies at the lines of its header."""
+    patch = "--- a/file.txt\n+++ b/file.txt\n@@ -2,3 +2,3 @@\n b\n-c\n+C\n d\n"
+    result = apply_patch(ORIGINAL, patch)
+    assert result.clean
+    assert result.patched == "a\nb\nC\nd\ne\nf\ng\n"
+    [hunk] = result.hunks
+    assert (hunk.expected_line, hunk.applied_line, hunk.offset, hunk.fuzz) == (2, 2, 0, 0)
+
+
+def test_apply_patch_offset_and_bare_headers() -> None:
+    """Hunks are found away from their header, and headers without ranges are accepted."""
+    result = apply_patch(ORIGINAL, "@@ -5,3 +5,3 @@\n b\n-c\n+C\n d\n@@ @@\n f\n+F\n g\n")
+    assert result.clean
+    assert result.patched == "a\nb\nC\nd\ne\nf\nF\ng\n"
+    assert result.hunks[0].offset == -3
+    assert result.hunks[1].expected_line is None
+
+
+def test_apply_patch_fuzz() -> None:
+    """Mismatched outer context is dropped up to the fuzz factor."""
+    patch = "@@ -2,3 +2,3 @@\n X\n-c\n+C\n d\n"
+    strict = apply_patch(ORIGINAL, patch, fuzz=0)
+    assert not strict.clean
+    assert strict.patched == ORIGINAL
+    [failed] = strict.failed
+    assert failed.index == 0
+    assert failed.error is not None
+
+    fuzzy = apply_patch(ORIGINAL, patch, fuzz=1)
+    assert fuzzy.clean
+    assert fuzzy.patched == "a\nb\nC\nd\ne\nf\ng\n"
+    assert fuzzy.hunks[0].fuzz == 1
+
+
+def test_apply_patch_partial() -> None:
+    """Failing hunks are reported while the others still apply."""
+    result = apply_patch(ORIGINAL, "@@ -1,2 +1,2 @@\n-a\n+A\n b\n@@ -6,2 +6,2 @@\n-x\n+y\n z\n")
+    assert result.patched == "A\nb\nc\nd\ne\nf\ng\n"
+    assert [hunk.applied for hunk in result.hunks] == [True, False]
+
+
+def test_apply_patch_line_endings() -> None:
+    """Line endings of the text are kept, and missing final newlines are honored."""
+    assert apply_patch("a\r\nb\r\n", "@@ -1,2 +1,3 @@\n a\n+z\n b\n").patched == "a\r\nz\r\nb\r\n"
+    patch = "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+B\n"
+    assert apply_patch("a\nb", patch).patched == "a\nB\n"
+    assert apply_patch("", "@@ -0,0 +1,2 @@\n+x\n+y\n").patched == "x\ny\n"
+
+
+def test_apply_patch_round_trip() -> None:
+    """Generated diffs apply back onto their source."""
+    new = "a\nb\nc\nD\ne\nf\ng\nh\n"
+    diff = "@@ -1,7 +1,8 @@\n" + show_diff(ORIGINAL, new)
+    assert apply_patch(ORIGINAL, diff).patched == new
+
+
+def test_apply_patch_invalid() -> None:
+    """Patches without hunks or with malformed headers are refused."""
+    with pytest.raises(ValueError, match="no hunk"):
+        apply_patch(ORIGINAL, "just some text")
+    with pytest.raises(ValueError, match="Malformed"):
+        apply_patch(ORIGINAL, "@@ -x +y @@\n a\n")
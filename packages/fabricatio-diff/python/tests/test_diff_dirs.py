@@ -0,0 +1,93 @@
+"""Tests for the directory-to-directory diff of fabricatio-diff Rust bindings."""
+
+from pathlib import Path
+
+import pytest
+from fabricatio_diff.rust import diff_dirs
+
+
+def write(root: Path, files: dict[str, str | bytes]) -> Path:
+    """Writes files below a directory, creating their parents."""
+    for name, content in files.items():
+        path = root / name
+        path.parent.mkdir(parents=True, exist_ok=True)
+        if isinstance(content, bytes):
+            path.write_bytes(content)
+        else:
+            path.write_text(content, encoding="utf-8")
+    return root
+
+
+@pytest.fixture
+def snapshots(tmp_path: Path) -> tuple[Path, Path]:
+    """Two snapshots of a workspace with an added, a removed, a modified and an unchanged file."""
+    before = write(
+        tmp_path / "before",
+        {
+            "README.md": "# Project\n",
+            "src/main.py": "".join(f"line {i}\n" for i in range(20)),
+            "src/old.py": "print('old')\nprint('gone')\n",
+        },
+    )
+    lines = [f"line {i}\n" for i in range(20)]
+    lines[1], lines[18] = "changed 1\n", "changed 18\n"
+    after = write(
+        tmp_path / "after",
+        {
+            "README.md": "# Project\n",
+            "src/main.py": "".join(lines),
+            "src/new.py": "print('new')\n",
+        },
+    )
+    return before, after
+
+
+def test_diff_dirs_summary(snapshots: tuple[Path, Path]) -> None:
+    """Each differing file is reported once, sorted by path, with its status and counts."""
+    diff = diff_dirs(*snapshots)
+    assert not diff.identical
+    assert diff.unchanged == 1
+    assert [(f.path, f.status) for f in diff.files] == [
+        ("src/main.py", "modified"),
+        ("src/new.py", "added"),
+        ("src/old.py", "removed"),
+    ]
+    modified, added, removed = diff.files
+    assert (modified.hunks, modified.insertions, modified.deletions) == (2, 2, 2)
+    assert (added.hunks, added.insertions, added.deletions) == (1, 1, 0)
+    assert (removed.hunks, removed.insertions, removed.deletions) == (1, 0, 2)
+    assert not any(f.binary for f in diff.files)
+
+
+def test_diff_dirs_identical(tmp_path: Path) -> None:
+    """Directories with the same files are identical."""
+    files = {"a.txt": "a\n", "nested/b.txt": "b\n"}
+    diff = diff_dirs(write(tmp_path / "a", files), write(tmp_path / "b", files))
+    assert diff.identical
+    assert diff.unchanged == 2
+
+
+def test_diff_dirs_binary(tmp_path: Path) -> None:
+    """Files that are not UTF-8 text are reported as binary, without line counts."""
+    before = write(tmp_path / "a", {"image.png": b"\x89PNG\x00\x01"})
+    after = write(tmp_path / "b", {"image.png": b"\x89PNG\x00\x02"})
+    (file,) = diff_dirs(before, after).files
+    assert file.binary
+    assert (file.status, file.hunks, file.insertions, file.deletions) == ("modified", 0, 0, 0)
+
+
+def test_diff_dirs_globs(snapshots: tuple[Path, Path]) -> None:
+    """Include globs select the files to compare, exclude globs leave files and directories out."""
+    assert [f.path for f in diff_dirs(*snapshots, include_globs=["*.md", "new.py"]).files] == ["src/new.py"]
+    assert [f.path for f in diff_dirs(*snapshots, exclude_globs=["main.py"]).files] == ["src/new.py", "src/old.py"]
+    diff = diff_dirs(*snapshots, exclude_globs=["src/"])
+    assert diff.identical
+    assert diff.unchanged == 1
+
+
+def test_diff_dirs_invalid(tmp_path: Path) -> None:
+    """A missing directory or an invalid glob is an error."""
+    with pytest.raises(FileNotFoundError):
+        diff_dirs(tmp_path, tmp_path / "missing")
+    with pytest.raises(ValueError):
+        diff_dirs(tmp_path, tmp_path, include_globs=["a[b"])
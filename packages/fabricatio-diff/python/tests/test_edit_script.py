@@ -0,0 +1,38 @@
+"""Tests for the line-level edit script of fabricatio-diff Rust bindings."""
+
+import pytest
+from fabricatio_diff.rust import EditOp, edit_script
+
+
+def apply(old: str, new: str, script: list[EditOp]) -> str:
+    """Rebuild the new text from the old one by following an edit script."""
+    old_lines = old.splitlines(keepends=True)
+    new_lines = new.splitlines(keepends=True)
+    out = []
+    for op in script:
+        if op.tag == "equal":
+            out.extend(old_lines[op.old_start : op.old_end])
+        elif op.tag == "insert":
+            out.extend(new_lines[op.new_start : op.new_end])
+    return "".join(out)
+
+
+def test_edit_script_ranges() -> None:
+    """Replaced lines become a delete followed by an insert."""
+    script = edit_script("a\nb\nc\n", "a\nx\nc\nd\n")
+    assert [(op.tag, op.old_start, op.old_end, op.new_start, op.new_end) for op in script] == [
+        ("equal", 0, 1, 0, 1),
+        ("delete", 1, 2, 1, 1),
+        ("insert", 2, 2, 1, 2),
+        ("equal", 2, 3, 2, 3),
+        ("insert", 3, 3, 3, 4),
+    ]
+
+
+@pytest.mark.parametrize(
+    ("old", "new"),
+    [("", ""), ("", "a\n"), ("a\n", ""), ("a\nb\n", "a\nb\n"), ("one\ntwo\nthree", "zero\none\nthree\nfour")],
+)
+def test_edit_script_rebuilds_new_text(old: str, new: str) -> None:
+    """Following the script from the old text yields the new text."""
+    assert apply(old, new, edit_script(old, new)) == new
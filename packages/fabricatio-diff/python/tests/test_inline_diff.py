@@ -0,0 +1,52 @@
+"""Tests for the word- and character-level diffs of fabricatio-diff Rust bindings."""
+
+import pytest
+from fabricatio_diff.rust import inline_diff
+
+
+def test_inline_diff_words() -> None:
+    """Changed words are reported as spans with character offsets into both texts."""
+    old, new = "the quick brown fox", "the slow brown fox"
+    diff = inline_diff(old, new)
+    assert [(span.tag, span.text) for span in diff.spans] == [
+        ("equal", "the "),
+        ("delete", "quick"),
+        ("insert", "slow"),
+        ("equal", " brown fox"),
+    ]
+    deleted, inserted = diff.spans[1], diff.spans[2]
+    assert old[deleted.old_start : deleted.old_end] == "quick"
+    assert new[inserted.new_start : inserted.new_end] == "slow"
+    assert "".join(span.text for span in diff.spans if span.tag != "insert") == old
+    assert "".join(span.text for span in diff.spans if span.tag != "delete") == new
+
+
+def test_inline_diff_cjk() -> None:
+    """Chinese characters are compared one by one, as they are not separated by spaces."""
+    diff = inline_diff("我今天很高兴。", "我明天很高兴。")
+    assert [(span.tag, span.text) for span in diff.spans if span.tag != "equal"] == [
+        ("delete", "今"),
+        ("insert", "明"),
+    ]
+
+
+def test_inline_diff_granularity() -> None:
+    """Character mode is finer than word mode, line mode coarser."""
+    assert [span.text for span in inline_diff("colour", "color", "char").spans if span.tag == "delete"] == ["u"]
+    assert [span.tag for span in inline_diff("colour", "color").spans] == ["delete", "insert"]
+    assert [span.tag for span in inline_diff("a\nb c\n", "a\nb d\n", "line").spans] == ["equal", "delete", "insert"]
+    assert inline_diff("same", "same").ratio == 1.0
+    with pytest.raises(ValueError, match="granularity"):
+        inline_diff("a", "b", "sentence")
+
+
+def test_inline_diff_render() -> None:
+    """Changes render as escaped HTML and as ANSI styles closed at line breaks."""
+    diff = inline_diff("a <b>\nold", "a <i>\nnew")
+    assert diff.to_html() == (
+        'a &lt;<del class="diff-delete">b</del><ins class="diff-insert">i</ins>&gt;\n'
+        '<del class="diff-delete">old</del><ins class="diff-insert">new</ins>'
+    )
+    assert diff.to_html(delete_class="d", insert_class="i").count('class="d"') == 2
+    ansi = inline_diff("one\ntwo", "").to_ansi()
+    assert ansi == "\x1b[9;31mone\x1b[0m\n\x1b[9;31mtwo\x1b[0m"
@@ -0,0 +1,38 @@
+"""Tests for the three-way merge of fabricatio-diff Rust bindings."""
+
+from fabricatio_diff.rust import merge3
+
+BASE = "a\nb\nc\nd\ne\n"
+
+
+def test_merge3_takes_both_sides() -> None:
+    """Changes to separate regions are all kept."""
+    result = merge3(BASE, "a\nB\nc\nd\ne\n", "a\nb\nc\nd\ne\nf\n")
+    assert result.clean
+    assert result.merged == "a\nB\nc\nd\ne\nf\n"
+
+
+def test_merge3_identical_changes() -> None:
+    """The same change made by both sides is not a conflict."""
+    result = merge3(BASE, "a\nb\nX\nd\ne\n", "a\nb\nX\nd\ne\n")
+    assert result.clean
+    assert result.merged == "a\nb\nX\nd\ne\n"
+
+
+def test_merge3_conflict() -> None:
+    """Different changes to a region are left between conflict markers."""
+    result = merge3(BASE, "a\nb\nX\nd\ne\n", "a\nb\nY\nd\ne\n")
+    assert not result.clean
+    assert result.merged == "a\nb\n<<<<<<< ours\nX\n=======\nY\n>>>>>>> theirs\nd\ne\n"
+    [conflict] = result.conflicts
+    assert (conflict.base_start, conflict.base_end) == (2, 3)
+    lines = result.merged.splitlines()[conflict.merged_start : conflict.merged_end]
+    assert lines[0] == "<<<<<<< ours"
+    assert lines[-1] == ">>>>>>> theirs"
+    assert (conflict.base, conflict.ours, conflict.theirs) == ("c\n", "X\n", "Y\n")
+
+
+def test_merge3_labels_and_base() -> None:
+    """Conflict blocks carry the labels, the base on request, and end their lines."""
+    result = merge3("x", "y", "z", ours_label="agent", theirs_label="human", show_base=True)
+    assert result.merged == "<<<<<<< agent\ny\n||||||| base\nx\n=======\nz\n>>>>>>> human\n"
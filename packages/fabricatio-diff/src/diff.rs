@@ -2,7 +2,7 @@ use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use rayon::prelude::*;
-use similar::{ChangeTag, TextDiff};
+use similar::{ChangeTag, DiffTag, TextDiff};
 use strsim::normalized_damerau_levenshtein;
 /// Calculates the similarity rate between two strings using the normalized Damerau-Levenshtein distance.
 ///
@@ -98,6 +98,90 @@ fn show_diff(a: &str, b: &str) -> String {
     result
 }
 
+/// A single operation of an edit script, covering a range of lines in both texts.
+///
+/// Ranges are 0-based and end-exclusive line indices, where lines keep their line endings,
+/// matching `str.splitlines(keepends=True)`. An `insert` has an empty old range at the
+/// position it inserts at, a `delete` an empty new range at the position it deletes at.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct EditOp {
+    /// The kind of the operation, one of `equal`, `insert` or `delete`.
+    tag: String,
+    /// The first line of the range in the old text.
+    old_start: usize,
+    /// The line after the range in the old text.
+    old_end: usize,
+    /// The first line of the range in the new text.
+    new_start: usize,
+    /// The line after the range in the new text.
+    new_end: usize,
+}
+
+impl EditOp {
+    fn new(tag: &str, old: std::ops::Range<usize>, new: std::ops::Range<usize>) -> Self {
+        Self {
+            tag: tag.to_string(),
+            old_start: old.start,
+            old_end: old.end,
+            new_start: new.start,
+            new_end: new.end,
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl EditOp {
+    fn __repr__(&self) -> String {
+        format!(
+            "EditOp(tag={:?}, old=[{}, {}), new=[{}, {}))",
+            self.tag, self.old_start, self.old_end, self.new_start, self.new_end
+        )
+    }
+}
+
+/// Computes a line-level edit script turning `old` into `new`.
+///
+/// The operations cover both texts in order without gaps, so applying them means copying
+/// `equal` ranges from `old`, taking `insert` ranges from `new` and skipping `delete` ranges.
+/// Replaced lines are reported as a `delete` followed by an `insert`.
+///
+/// Args:
+///     old: The original text.
+///     new: The modified text.
+///
+/// Returns:
+///     A list of `EditOp` with line ranges into both texts.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+fn edit_script(old: &str, new: &str) -> Vec<EditOp> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut script = Vec::with_capacity(diff.ops().len());
+    for op in diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => script.push(EditOp::new("equal", old_range, new_range)),
+            DiffTag::Delete => script.push(EditOp::new("delete", old_range, new_range)),
+            DiffTag::Insert => script.push(EditOp::new("insert", old_range, new_range)),
+            DiffTag::Replace => {
+                script.push(EditOp::new(
+                    "delete",
+                    old_range.clone(),
+                    new_range.start..new_range.start,
+                ));
+                script.push(EditOp::new(
+                    "insert",
+                    old_range.end..old_range.end,
+                    new_range,
+                ));
+            }
+        }
+    }
+    script
+}
+
 /// Registers the diff functions with the Python module.
 ///
 /// Args:
@@ -108,6 +192,8 @@ fn show_diff(a: &str, b: &str) -> String {
 ///     PyResult<()> indicating success.
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(show_diff, m)?)?;
+    m.add_function(wrap_pyfunction!(edit_script, m)?)?;
+    m.add_class::<EditOp>()?;
     m.add_function(wrap_pyfunction!(rate, m)?)?;
     m.add_function(wrap_pyfunction!(match_lines, m)?)?;
     Ok(())
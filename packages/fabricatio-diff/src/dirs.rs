@@ -0,0 +1,246 @@
+//! Comparing two directory trees file by file, such as two snapshots of an agent workspace.
+
+use ignore::WalkBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
+use pyo3::exceptions::{PyFileNotFoundError, PyValueError};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use rayon::prelude::*;
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of unchanged lines around the changes of a hunk, as in `diff -u`.
+const CONTEXT_LINES: usize = 3;
+
+/// How a file differs between two directories.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    /// The path of the file relative to the directories, with `/` separators.
+    path: String,
+    /// One of `added` (only in the second directory), `removed` (only in the first one) or
+    /// `modified`.
+    status: String,
+    /// Whether either version of the file is not UTF-8 text, in which case lines are not
+    /// compared.
+    binary: bool,
+    /// The number of hunks of a unified diff of the file with 3 lines of context, 1 for a
+    /// non-empty text file added or removed, 0 for a binary file.
+    hunks: usize,
+    /// The number of lines added.
+    insertions: usize,
+    /// The number of lines removed.
+    deletions: usize,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl FileDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "FileDiff(path={:?}, status={:?}, hunks={}, +{}, -{})",
+            self.path, self.status, self.hunks, self.insertions, self.deletions
+        )
+    }
+}
+
+/// The differences between two directories.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct DirDiff {
+    /// The files that differ, sorted by path.
+    files: Vec<FileDiff>,
+    /// The number of files identical in both directories.
+    unchanged: usize,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl DirDiff {
+    /// Whether both directories hold the same files with the same content.
+    #[getter]
+    fn identical(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        let count = |status: &str| self.files.iter().filter(|f| f.status == status).count();
+        format!(
+            "DirDiff(added={}, removed={}, modified={}, unchanged={})",
+            count("added"),
+            count("removed"),
+            count("modified"),
+            self.unchanged
+        )
+    }
+}
+
+/// Builds the overrides matching any of the glob patterns.
+fn globs(root: &Path, patterns: &[String]) -> PyResult<Override> {
+    let mut builder = OverrideBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add(pattern)
+            .map_err(|e| PyValueError::new_err(format!("Invalid glob `{pattern}`: {e}")))?;
+    }
+    builder
+        .build()
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Lists the files below a directory, relative to it, that the globs select.
+///
+/// Hidden and ignored files are listed too, as snapshots are compared whole. Excluded
+/// directories are not entered.
+fn list_files(root: &Path, include: &Override, exclude: &Override) -> BTreeSet<PathBuf> {
+    let (base, exclude) = (root.to_path_buf(), exclude.clone());
+    WalkBuilder::new(root)
+        .standard_filters(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.path().strip_prefix(&base).is_ok_and(|path| {
+                path.as_os_str().is_empty() || !exclude.matched(path, is_dir).is_whitelist()
+            })
+        })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .filter(|path| include.is_empty() || include.matched(path, false).is_whitelist())
+        .collect()
+}
+
+/// Counts the inserted and deleted lines between two texts, and the hunks of their diff.
+fn count_changes(old: &str, new: &str) -> (usize, usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    let (mut insertions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => insertions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    (diff.grouped_ops(CONTEXT_LINES).len(), insertions, deletions)
+}
+
+/// Decodes the content of a file as text, None if it is binary.
+fn as_text(content: &[u8]) -> Option<&str> {
+    if content.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(content).ok()
+}
+
+/// Compares the versions of a file in both directories, None if they are identical.
+fn compare(path: &Path, old: Option<Vec<u8>>, new: Option<Vec<u8>>) -> Option<FileDiff> {
+    let (status, old, new) = match (old, new) {
+        (Some(old), Some(new)) if old == new => return None,
+        (Some(old), Some(new)) => ("modified", old, new),
+        (Some(old), None) => ("removed", old, Vec::new()),
+        (None, Some(new)) => ("added", Vec::new(), new),
+        (None, None) => return None,
+    };
+    let (binary, hunks, insertions, deletions) = match (as_text(&old), as_text(&new)) {
+        (Some(old), Some(new)) => {
+            let (hunks, insertions, deletions) = count_changes(old, new);
+            (false, hunks, insertions, deletions)
+        }
+        _ => (true, 0, 0, 0),
+    };
+    Some(FileDiff {
+        path: path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        status: status.to_string(),
+        binary,
+        hunks,
+        insertions,
+        deletions,
+    })
+}
+
+/// Compares two directories file by file, such as two snapshots of an agent workspace.
+///
+/// Files are matched by their path relative to each directory and compared in parallel.
+/// Hidden files and files ignored by `.gitignore` are compared too; leave them out with
+/// `exclude_globs`. Symbolic links are not followed.
+///
+/// Args:
+///     dir_a: The first directory, such as the older snapshot.
+///     dir_b: The second directory, such as the newer snapshot.
+///     include_globs: Globs of the files to compare, relative to the directories, in the
+///         `.gitignore` syntax. All files are compared if empty.
+///     exclude_globs: Globs of the files and directories not to compare, such as `.git/` or `*.pyc`.
+///
+/// Returns:
+///     A `DirDiff` with a `FileDiff` per added, removed or modified file.
+///
+/// Raises:
+///     FileNotFoundError: If either path is not a directory.
+///     ValueError: If a glob is invalid.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (dir_a, dir_b, include_globs = Vec::new(), exclude_globs = Vec::new()))]
+pub fn diff_dirs(
+    python: Python,
+    dir_a: PathBuf,
+    dir_b: PathBuf,
+    include_globs: Vec<String>,
+    exclude_globs: Vec<String>,
+) -> PyResult<DirDiff> {
+    for dir in [&dir_a, &dir_b] {
+        if !dir.is_dir() {
+            return Err(PyFileNotFoundError::new_err(format!(
+                "{} is not a directory",
+                dir.display()
+            )));
+        }
+    }
+    let include = globs(&dir_a, &include_globs)?;
+    let exclude = globs(&dir_a, &exclude_globs)?;
+
+    Ok(python.detach(|| {
+        let files_a = list_files(&dir_a, &include, &exclude);
+        let files_b = list_files(&dir_b, &include, &exclude);
+        let paths = files_a.union(&files_b).collect::<Vec<_>>();
+        let files = paths
+            .par_iter()
+            .filter_map(|path| {
+                let read = |dir: &Path, files: &BTreeSet<PathBuf>| {
+                    files
+                        .contains(*path)
+                        .then(|| fs::read(dir.join(path)).ok())
+                        .flatten()
+                };
+                compare(path, read(&dir_a, &files_a), read(&dir_b, &files_b))
+            })
+            .collect::<Vec<_>>();
+        DirDiff {
+            unchanged: paths.len() - files.len(),
+            files,
+        }
+    }))
+}
+
+/// Registers the directory diff function and its result classes with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(diff_dirs, m)?)?;
+    m.add_class::<DirDiff>()?;
+    m.add_class::<FileDiff>()?;
+    Ok(())
+}
@@ -0,0 +1,345 @@
+//! Diffs within lines, by word or by character, and their rendering for terminals and pages.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use similar::{DiffTag, TextDiff};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// The units texts are compared by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Line,
+    Word,
+    Char,
+}
+
+impl Granularity {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "line" => Ok(Self::Line),
+            "word" => Ok(Self::Word),
+            "char" => Ok(Self::Char),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown granularity `{name}`, expected `line`, `word` or `char`"
+            ))),
+        }
+    }
+}
+
+/// Whether a character is written without spaces around it, as in Chinese or Japanese, so
+/// that each one is a word of its own.
+fn is_unspaced(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // Hiragana, Katakana
+        | 0x3400..=0x4DBF    // CJK Extension A
+        | 0x4E00..=0x9FFF    // CJK Unified Ideographs
+        | 0xF900..=0xFAFF    // CJK Compatibility Ideographs
+        | 0x20000..=0x2FFFF) // CJK Extensions B and later
+}
+
+/// The class of a character, runs of the same class forming a word.
+#[derive(PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    /// Punctuation and unspaced scripts, one token per character.
+    Single,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if (c.is_alphanumeric() && !is_unspaced(c)) || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Single
+    }
+}
+
+/// Splits a text into the tokens of a granularity, which concatenate back to the text.
+fn tokenize(text: &str, granularity: Granularity) -> Vec<&str> {
+    match granularity {
+        Granularity::Line => text.split_inclusive('\n').collect(),
+        Granularity::Char => text
+            .char_indices()
+            .map(|(i, c)| &text[i..i + c.len_utf8()])
+            .collect(),
+        Granularity::Word => {
+            let mut tokens = Vec::new();
+            let mut start = 0;
+            let mut previous: Option<CharClass> = None;
+            for (i, c) in text.char_indices() {
+                let class = char_class(c);
+                if i > start && (class == CharClass::Single || previous.as_ref() != Some(&class)) {
+                    tokens.push(&text[start..i]);
+                    start = i;
+                }
+                previous = Some(class);
+            }
+            if start < text.len() {
+                tokens.push(&text[start..]);
+            }
+            tokens
+        }
+    }
+}
+
+/// The character offset each token starts at, followed by the length of the text.
+fn char_offsets(tokens: &[&str]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    let mut offset = 0;
+    offsets.push(0);
+    for token in tokens {
+        offset += token.chars().count();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/// A run of text kept, inserted or deleted between two texts.
+///
+/// Offsets are 0-based and end-exclusive character indices, which slice Python strings. An
+/// `insert` has an empty old range at the position it inserts at, a `delete` an empty new
+/// range at the position it deletes at.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct InlineSpan {
+    /// The kind of the span, one of `equal`, `insert` or `delete`.
+    tag: String,
+    /// The text of the span, from the new text for an `insert`, from the old one otherwise.
+    text: String,
+    /// The first character of the span in the old text.
+    old_start: usize,
+    /// The character after the span in the old text.
+    old_end: usize,
+    /// The first character of the span in the new text.
+    new_start: usize,
+    /// The character after the span in the new text.
+    new_end: usize,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl InlineSpan {
+    fn __repr__(&self) -> String {
+        format!(
+            "InlineSpan(tag={:?}, text={:?}, old=[{}, {}), new=[{}, {}))",
+            self.tag, self.text, self.old_start, self.old_end, self.new_start, self.new_end
+        )
+    }
+}
+
+/// Escapes the characters with a meaning in HTML.
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// The changes between two texts as a sequence of spans covering both of them.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct InlineDiff {
+    /// The spans in the order of both texts, without gaps. Concatenating the texts of the
+    /// `equal` and `delete` spans gives the old text, those of the `equal` and `insert`
+    /// spans the new one.
+    spans: Vec<InlineSpan>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl InlineDiff {
+    /// The similarity of both texts, from 0.0 for nothing in common to 1.0 for equal texts,
+    /// as the share of characters kept.
+    #[getter]
+    fn ratio(&self) -> f64 {
+        let (mut kept, mut total) = (0, 0);
+        for span in &self.spans {
+            let (old, new) = (span.old_end - span.old_start, span.new_end - span.new_start);
+            total += old + new;
+            if span.tag == "equal" {
+                kept += old + new;
+            }
+        }
+        if total == 0 {
+            1.0
+        } else {
+            kept as f64 / total as f64
+        }
+    }
+
+    /// Renders the changes for a terminal, deleted text struck through in red and inserted
+    /// text underlined in green.
+    ///
+    /// Styles are closed before line breaks and opened again after them, so that the output
+    /// can be split into lines or paged.
+    ///
+    /// Returns:
+    ///     The text with ANSI escape codes around the changes.
+    fn to_ansi(&self) -> String {
+        const RESET: &str = "\x1b[0m";
+        let mut out = String::new();
+        for span in &self.spans {
+            let style = match span.tag.as_str() {
+                "delete" => "\x1b[9;31m",
+                "insert" => "\x1b[4;32m",
+                _ => {
+                    out.push_str(&span.text);
+                    continue;
+                }
+            };
+            for (i, line) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                }
+                if !line.is_empty() {
+                    let _ = write!(out, "{style}{line}{RESET}");
+                }
+            }
+        }
+        out
+    }
+
+    /// Renders the changes as HTML, deleted text in `<del>` and inserted text in `<ins>`.
+    ///
+    /// All text is escaped, so the output can be put in a page as is. Line breaks are kept,
+    /// which shows them in an element styled with `white-space: pre-wrap`.
+    ///
+    /// Args:
+    ///     delete_class: The class of the `<del>` elements.
+    ///     insert_class: The class of the `<ins>` elements.
+    ///
+    /// Returns:
+    ///     The HTML fragment.
+    #[pyo3(signature = (delete_class = "diff-delete", insert_class = "diff-insert"))]
+    fn to_html(&self, delete_class: &str, insert_class: &str) -> String {
+        let mut out = String::new();
+        for span in &self.spans {
+            let (element, class) = match span.tag.as_str() {
+                "delete" => ("del", delete_class),
+                "insert" => ("ins", insert_class),
+                _ => {
+                    escape_html(&span.text, &mut out);
+                    continue;
+                }
+            };
+            let _ = write!(out, "<{element} class=\"");
+            escape_html(class, &mut out);
+            out.push_str("\">");
+            escape_html(&span.text, &mut out);
+            let _ = write!(out, "</{element}>");
+        }
+        out
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "InlineDiff(spans={}, changed={})",
+            self.spans.len(),
+            self.spans.iter().filter(|span| span.tag != "equal").count()
+        )
+    }
+}
+
+/// Computes the changes between two texts by line, word or character, as spans of text.
+///
+/// With `word`, words are runs of letters, digits and underscores, runs of whitespace, and
+/// single punctuation marks. Chinese and Japanese characters, written without spaces, are
+/// words of their own, so changes within prose in those languages are precise too. With
+/// `char`, every character is compared. Replaced text is reported as a `delete` followed by
+/// an `insert`.
+///
+/// Args:
+///     old: The original text.
+///     new: The modified text.
+///     granularity: The units to compare, one of `line`, `word` or `char`.
+///
+/// Returns:
+///     An `InlineDiff` with the spans, which renders them with `to_ansi` and `to_html`.
+///
+/// Raises:
+///     ValueError: If the granularity is unknown.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (old, new, granularity = "word"))]
+pub fn inline_diff(old: &str, new: &str, granularity: &str) -> PyResult<InlineDiff> {
+    let granularity = Granularity::parse(granularity)?;
+    let old_tokens = tokenize(old, granularity);
+    let new_tokens = tokenize(new, granularity);
+    let old_offsets = char_offsets(&old_tokens);
+    let new_offsets = char_offsets(&new_tokens);
+    let diff = TextDiff::configure().diff_slices(&old_tokens, &new_tokens);
+
+    let span = |tag: &str, old_range: Range<usize>, new_range: Range<usize>| InlineSpan {
+        text: if tag == "insert" {
+            new_tokens[new_range.clone()].concat()
+        } else {
+            old_tokens[old_range.clone()].concat()
+        },
+        tag: tag.to_string(),
+        old_start: old_offsets[old_range.start],
+        old_end: old_offsets[old_range.end],
+        new_start: new_offsets[new_range.start],
+        new_end: new_offsets[new_range.end],
+    };
+
+    // Consecutive changes are gathered into one deletion followed by one insertion, which
+    // reads better than alternating fragments.
+    let mut spans = Vec::new();
+    let mut changed: Option<(Range<usize>, Range<usize>)> = None;
+    let flush = |changed: &mut Option<(Range<usize>, Range<usize>)>, spans: &mut Vec<_>| {
+        if let Some((old_range, new_range)) = changed.take() {
+            if !old_range.is_empty() {
+                spans.push(span(
+                    "delete",
+                    old_range.clone(),
+                    new_range.start..new_range.start,
+                ));
+            }
+            if !new_range.is_empty() {
+                spans.push(span("insert", old_range.end..old_range.end, new_range));
+            }
+        }
+    };
+    for op in diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            flush(&mut changed, &mut spans);
+            spans.push(span("equal", old_range, new_range));
+        } else {
+            changed = Some(match changed {
+                Some((old, new)) => (old.start..old_range.end, new.start..new_range.end),
+                None => (old_range, new_range),
+            });
+        }
+    }
+    flush(&mut changed, &mut spans);
+    Ok(InlineDiff { spans })
+}
+
+/// Registers the inline diff function and its result classes with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(inline_diff, m)?)?;
+    m.add_class::<InlineDiff>()?;
+    m.add_class::<InlineSpan>()?;
+    Ok(())
+}
@@ -4,7 +4,11 @@ use pyo3::prelude::*;
 use pyo3_stub_gen::define_stub_info_gatherer;
 
 mod diff;
+mod dirs;
 mod hashline;
+mod inline;
+mod merge;
+mod patch;
 /// A Python module implemented in Rust. The name of this function must match
 /// the `lib.name` setting in the `Cargo.toml`, else Python will not be able to
 /// import the module.
@@ -12,7 +16,11 @@ mod hashline;
 #[pymodule]
 fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     diff::register(python, m)?;
+    dirs::register(python, m)?;
     hashline::register(python, m)?;
+    inline::register(python, m)?;
+    merge::register(python, m)?;
+    patch::register(python, m)?;
     Ok(())
 }
 
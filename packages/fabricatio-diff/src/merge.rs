@@ -0,0 +1,248 @@
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use similar::{DiffTag, TextDiff};
+use std::ops::Range;
+
+/// A run of changed lines of one side, as line ranges into the base and into the side.
+struct Hunk {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+/// Lists the changes of `side` against `base`, adjacent operations merged into one hunk.
+fn hunks(base: &str, side: &str) -> Vec<Hunk> {
+    let diff = TextDiff::from_lines(base, side);
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in diff.ops() {
+        let (tag, base_range, side_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match hunks.last_mut() {
+            Some(last) if last.base.end == base_range.start => {
+                last.base.end = base_range.end;
+                last.side.end = side_range.end;
+            }
+            _ => hunks.push(Hunk {
+                base: base_range,
+                side: side_range,
+            }),
+        }
+    }
+    hunks
+}
+
+/// Maps a range of the base to the lines of a side, given the hunks of the side within it.
+fn side_range(hunks: &[Hunk], base: &Range<usize>) -> Range<usize> {
+    match (hunks.first(), hunks.last()) {
+        (Some(first), Some(last)) => {
+            first.side.start - (first.base.start - base.start)
+                ..last.side.end + (base.end - last.base.end)
+        }
+        _ => base.clone(),
+    }
+}
+
+/// A region both sides changed differently, left to be resolved by hand.
+///
+/// Ranges are 0-based and end-exclusive line indices, like those of `EditOp`.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    /// The first line of the region in the base text.
+    base_start: usize,
+    /// The line after the region in the base text.
+    base_end: usize,
+    /// The first line of the conflict block in the merged text, at its `<<<<<<<` marker.
+    merged_start: usize,
+    /// The line after the conflict block in the merged text, past its `>>>>>>>` marker.
+    merged_end: usize,
+    /// The lines of the region in the base text.
+    base: String,
+    /// The lines of the region in our text.
+    ours: String,
+    /// The lines of the region in their text.
+    theirs: String,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl MergeConflict {
+    fn __repr__(&self) -> String {
+        format!(
+            "MergeConflict(base=[{}, {}), merged=[{}, {}))",
+            self.base_start, self.base_end, self.merged_start, self.merged_end
+        )
+    }
+}
+
+/// The outcome of a three-way merge.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The merged text, with conflict markers around the conflicting regions.
+    merged: String,
+    /// The conflicting regions, in the order of the merged text.
+    conflicts: Vec<MergeConflict>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl MergeResult {
+    /// Whether the merge has no conflicts.
+    #[getter]
+    fn clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("MergeResult(conflicts={})", self.conflicts.len())
+    }
+}
+
+/// Builds the merged text line by line, counting the lines written.
+#[derive(Default)]
+struct Merged {
+    text: String,
+    lines: usize,
+}
+
+impl Merged {
+    fn push_lines(&mut self, lines: &[&str]) {
+        for line in lines {
+            self.text.push_str(line);
+        }
+        self.lines += lines.len();
+    }
+
+    /// Writes a conflict marker on a line of its own.
+    fn push_marker(&mut self, marker: &str) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+        self.text.push_str(marker);
+        self.text.push('\n');
+        self.lines += 1;
+    }
+}
+
+/// Merges the changes two sides made to a common base, line by line.
+///
+/// Changes made by one side only are taken as is, and so are the identical changes made by
+/// both. Regions both sides changed differently, or changed next to each other, are
+/// conflicts: the merged text holds both versions between `<<<<<<<`, `=======` and `>>>>>>>`
+/// markers, as `git merge` writes them.
+///
+/// Args:
+///     base: The common ancestor of both texts.
+///     ours: Our version of the text, such as the one an agent edited.
+///     theirs: Their version of the text, such as the one a person edited meanwhile.
+///     ours_label: The label of our side, written after the `<<<<<<<` marker.
+///     theirs_label: The label of their side, written after the `>>>>>>>` marker.
+///     show_base: Whether conflict blocks also hold the base version, after a `|||||||`
+///         marker, like the `diff3` conflict style of git.
+///
+/// Returns:
+///     A `MergeResult` with the merged text and its conflicts.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (base, ours, theirs, ours_label = "ours", theirs_label = "theirs", show_base = false))]
+pub fn merge3(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+    show_base: bool,
+) -> MergeResult {
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let ours_lines = ours.split_inclusive('\n').collect::<Vec<_>>();
+    let theirs_lines = theirs.split_inclusive('\n').collect::<Vec<_>>();
+    let ours_hunks = hunks(base, ours);
+    let theirs_hunks = hunks(base, theirs);
+
+    let mut merged = Merged::default();
+    let mut conflicts = Vec::new();
+    let (mut i, mut j, mut pos) = (0, 0, 0);
+    while i < ours_hunks.len() || j < theirs_hunks.len() {
+        // Start a region at the earliest hunk, then absorb the hunks of either side that
+        // overlap or touch it, until it stops growing.
+        let start = match (ours_hunks.get(i), theirs_hunks.get(j)) {
+            (Some(o), Some(t)) => o.base.start.min(t.base.start),
+            (Some(o), None) => o.base.start,
+            (None, Some(t)) => t.base.start,
+            (None, None) => unreachable!(),
+        };
+        let (mut i_end, mut j_end, mut end) = (i, j, start);
+        loop {
+            if let Some(hunk) = ours_hunks.get(i_end)
+                && hunk.base.start <= end
+            {
+                end = end.max(hunk.base.end);
+                i_end += 1;
+            } else if let Some(hunk) = theirs_hunks.get(j_end)
+                && hunk.base.start <= end
+            {
+                end = end.max(hunk.base.end);
+                j_end += 1;
+            } else {
+                break;
+            }
+        }
+
+        let region = start..end;
+        let ours_part = &ours_lines[side_range(&ours_hunks[i..i_end], &region)];
+        let theirs_part = &theirs_lines[side_range(&theirs_hunks[j..j_end], &region)];
+        merged.push_lines(&base_lines[pos..start]);
+        if j == j_end || ours_part == theirs_part {
+            merged.push_lines(ours_part);
+        } else if i == i_end {
+            merged.push_lines(theirs_part);
+        } else {
+            let merged_start = merged.lines;
+            merged.push_marker(&format!("<<<<<<< {ours_label}"));
+            merged.push_lines(ours_part);
+            if show_base {
+                merged.push_marker("||||||| base");
+                merged.push_lines(&base_lines[region.clone()]);
+            }
+            merged.push_marker("=======");
+            merged.push_lines(theirs_part);
+            merged.push_marker(&format!(">>>>>>> {theirs_label}"));
+            conflicts.push(MergeConflict {
+                base_start: start,
+                base_end: end,
+                merged_start,
+                merged_end: merged.lines,
+                base: base_lines[region].concat(),
+                ours: ours_part.concat(),
+                theirs: theirs_part.concat(),
+            });
+        }
+        (i, j, pos) = (i_end, j_end, end);
+    }
+    merged.push_lines(&base_lines[pos..]);
+
+    MergeResult {
+        merged: merged.text,
+        conflicts,
+    }
+}
+
+/// Registers the merge function and its result classes with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(merge3, m)?)?;
+    m.add_class::<MergeResult>()?;
+    m.add_class::<MergeConflict>()?;
+    Ok(())
+}
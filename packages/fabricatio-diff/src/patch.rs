@@ -0,0 +1,409 @@
+//! Applying unified diffs, such as those written by `diff -u`, `git diff` or a language model.
+//!
+//! Hunks are located like `patch` does: at the line their header names, shifted by the offset
+//! of the hunks applied before, or at the nearest place their lines match. With a fuzz factor,
+//! a hunk whose context does not match as a whole is tried again without some of its outer
+//! context lines. Hunks that still do not match are left out and reported, the others are
+//! applied.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+
+/// A line of a hunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Context(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// A hunk of a unified diff.
+#[derive(Debug)]
+struct Hunk<'a> {
+    /// The `@@` line of the hunk.
+    header: &'a str,
+    /// The 1-based line the hunk starts at in the original text, None if the header names none.
+    old_start: Option<usize>,
+    lines: Vec<Line<'a>>,
+    /// Whether the original text ends within the hunk without a line ending.
+    old_no_newline: bool,
+    /// Whether the patched text ends within the hunk without a line ending.
+    new_no_newline: bool,
+}
+
+impl Hunk<'_> {
+    /// The lines the hunk expects in the original text.
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Context(text) | Line::Delete(text) => Some(*text),
+                Line::Insert(_) => None,
+            })
+            .collect()
+    }
+
+    /// The lines the hunk leaves in the patched text.
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Context(text) | Line::Insert(text) => Some(*text),
+                Line::Delete(_) => None,
+            })
+            .collect()
+    }
+
+    /// The number of context lines before the first change, and after the last one.
+    fn context(&self) -> (usize, usize) {
+        let is_context = |line: &&Line| matches!(line, Line::Context(_));
+        let leading = self.lines.iter().take_while(is_context).count();
+        let trailing = self.lines.iter().rev().take_while(is_context).count();
+        (leading, trailing.min(self.lines.len() - leading))
+    }
+}
+
+/// Parses a hunk header, `@@ -start,count +start,count @@`, into the start and the line
+/// counts of both sides. Language models often leave out the ranges, which is accepted.
+fn parse_header(header: &str) -> Option<(Option<usize>, Option<(usize, usize)>)> {
+    let ranges = header.strip_prefix("@@")?;
+    let ranges = &ranges[..ranges.find("@@").unwrap_or(ranges.len())];
+    let mut parts = ranges.split_whitespace();
+    let (Some(old), Some(new)) = (parts.next(), parts.next()) else {
+        return ranges.trim().is_empty().then_some((None, None));
+    };
+    let range = |part: &str, sign: char| -> Option<(usize, usize)> {
+        let part = part.strip_prefix(sign)?;
+        match part.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((part.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old, '-')?;
+    let (_, new_count) = range(new, '+')?;
+    Some((Some(old_start), Some((old_count, new_count))))
+}
+
+/// Parses the hunks of a unified diff, skipping the file headers and any text around them.
+///
+/// The line counts of the headers end the hunks when given, so that a deleted line starting
+/// with `--` is not taken for a file header. Without them, a hunk ends at the first line that
+/// is not part of a hunk, and blank lines, which editors and models write for empty context
+/// lines, are taken as such unless they end the hunk.
+fn parse_patch(patch: &str) -> PyResult<Vec<Hunk<'_>>> {
+    let lines = patch.lines().collect::<Vec<_>>();
+    let mut hunks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let header = lines[i];
+        i += 1;
+        if !header.starts_with("@@") {
+            continue;
+        }
+        let (old_start, counts) = parse_header(header).ok_or_else(|| {
+            PyValueError::new_err(format!("Malformed hunk header at line {i}: `{header}`"))
+        })?;
+        let mut hunk = Hunk {
+            header,
+            old_start,
+            lines: Vec::new(),
+            old_no_newline: false,
+            new_no_newline: false,
+        };
+        let (mut old_left, mut new_left) = counts.unwrap_or((usize::MAX, usize::MAX));
+        let mut blank_tail = 0;
+        while i < lines.len() && (old_left > 0 || new_left > 0) {
+            let line = lines[i];
+            let parsed = match line.chars().next() {
+                Some(' ') => Line::Context(&line[1..]),
+                Some('-') if counts.is_some() || !line.starts_with("--- ") => {
+                    Line::Delete(&line[1..])
+                }
+                Some('+') if counts.is_some() || !line.starts_with("+++ ") => {
+                    Line::Insert(&line[1..])
+                }
+                None => Line::Context(""),
+                Some('\\') => {
+                    match hunk.lines.last() {
+                        Some(Line::Delete(_)) => hunk.old_no_newline = true,
+                        Some(Line::Insert(_)) => hunk.new_no_newline = true,
+                        _ => (hunk.old_no_newline, hunk.new_no_newline) = (true, true),
+                    }
+                    i += 1;
+                    continue;
+                }
+                _ => break,
+            };
+            blank_tail = if line.is_empty() { blank_tail + 1 } else { 0 };
+            match parsed {
+                Line::Context(_) => {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+                Line::Delete(_) => old_left = old_left.saturating_sub(1),
+                Line::Insert(_) => new_left = new_left.saturating_sub(1),
+            }
+            hunk.lines.push(parsed);
+            i += 1;
+        }
+        if counts.is_none() {
+            hunk.lines.truncate(hunk.lines.len() - blank_tail);
+        }
+        hunks.push(hunk);
+    }
+    if hunks.is_empty() {
+        return Err(PyValueError::new_err("The patch holds no hunk"));
+    }
+    Ok(hunks)
+}
+
+/// Finds where `pattern` occurs in `lines` within `bounds`, the nearest to `expected` first,
+/// looking forward before backward at equal distance.
+fn locate(
+    lines: &[&str],
+    pattern: &[&str],
+    bounds: (usize, usize),
+    expected: usize,
+) -> Option<usize> {
+    let (low, high) = bounds;
+    if high < low + pattern.len() {
+        return None;
+    }
+    let last = high - pattern.len();
+    let expected = expected.clamp(low, last);
+    let matches = |at: usize| lines[at..at + pattern.len()] == *pattern;
+    (0..=last - low).find_map(|distance| {
+        [
+            expected.checked_add(distance),
+            expected.checked_sub(distance),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|&at| at >= low && at <= last && matches(at))
+    })
+}
+
+/// How a hunk of a patch was applied, or why it was not.
+///
+/// Line numbers are 1-based, like those of the hunk headers.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct HunkReport {
+    /// The position of the hunk in the patch, from 0.
+    index: usize,
+    /// The `@@` line of the hunk.
+    header: String,
+    /// Whether the hunk was applied.
+    applied: bool,
+    /// The line of the original text the hunk starts at according to its header, None if the
+    /// header names none.
+    expected_line: Option<usize>,
+    /// The line of the original text the hunk was applied at, None if it was not.
+    applied_line: Option<usize>,
+    /// The number of lines between where the hunk was applied and where its header placed
+    /// it, negative if it was applied before.
+    offset: isize,
+    /// The number of context lines left out at each end of the hunk to apply it.
+    fuzz: usize,
+    /// Why the hunk was not applied, None if it was.
+    error: Option<String>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl HunkReport {
+    fn __repr__(&self) -> String {
+        match &self.error {
+            None => format!(
+                "HunkReport(index={}, applied_line={}, offset={}, fuzz={})",
+                self.index,
+                self.applied_line.unwrap_or_default(),
+                self.offset,
+                self.fuzz
+            ),
+            Some(error) => format!("HunkReport(index={}, error={:?})", self.index, error),
+        }
+    }
+}
+
+/// The outcome of applying a patch.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct PatchResult {
+    /// The patched text, without the hunks that failed.
+    patched: String,
+    /// A report for every hunk of the patch, in the order of the patch.
+    hunks: Vec<HunkReport>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl PatchResult {
+    /// Whether every hunk was applied.
+    #[getter]
+    fn clean(&self) -> bool {
+        self.hunks.iter().all(|hunk| hunk.applied)
+    }
+
+    /// The reports of the hunks that were not applied.
+    #[getter]
+    fn failed(&self) -> Vec<HunkReport> {
+        self.hunks
+            .iter()
+            .filter(|hunk| !hunk.applied)
+            .cloned()
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PatchResult(hunks={}, failed={})",
+            self.hunks.len(),
+            self.hunks.iter().filter(|hunk| !hunk.applied).count()
+        )
+    }
+}
+
+/// Applies a unified diff to a text.
+///
+/// Each hunk is looked for at the line its header names, shifted by the offset the previous
+/// hunks were applied at, then at the nearest place its lines match, as `patch` does. If its
+/// lines match nowhere, it is tried again without up to `fuzz` context lines at each end.
+/// Hunks that still match nowhere are left out and reported, so a partly wrong patch, such as
+/// one written by a language model, applies as far as it can. Headers without line ranges,
+/// `@@ ... @@`, and blank lines standing for empty context lines are accepted.
+///
+/// Args:
+///     original: The text to patch.
+///     unified_diff: The patch, for a single file. File headers and text around the hunks are
+///         ignored.
+///     fuzz: The number of context lines that may be left out at each end of a hunk.
+///
+/// Returns:
+///     A `PatchResult` with the patched text and a `HunkReport` per hunk.
+///
+/// Raises:
+///     ValueError: If the patch holds no hunk or a malformed hunk header.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (original, unified_diff, fuzz = 2))]
+pub fn apply_patch(original: &str, unified_diff: &str, fuzz: usize) -> PyResult<PatchResult> {
+    let hunks = parse_patch(unified_diff)?;
+    let eol = if original.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines = original.lines().collect::<Vec<_>>();
+    let mut trailing_newline = original.is_empty() || original.ends_with('\n');
+
+    let mut patched: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut reports = Vec::with_capacity(hunks.len());
+    let (mut pos, mut offset) = (0usize, 0isize);
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        let new = hunk.new_lines();
+        let (leading, trailing) = hunk.context();
+        // A header `-start,0` names the line the hunk inserts after, others the first line.
+        let expected = hunk.old_start.map(|start| {
+            let start = if old.is_empty() {
+                start
+            } else {
+                start.saturating_sub(1)
+            };
+            start.saturating_add_signed(offset)
+        });
+
+        let found = (0..=fuzz.min(leading.max(trailing))).find_map(|level| {
+            let (top, bottom) = (level.min(leading), level.min(trailing));
+            let pattern = &old[top..old.len() - bottom];
+            if pattern.is_empty() && (level > 0 || expected.is_none() && pos < lines.len()) {
+                // Nothing tells where lines inserted without context go.
+                return None;
+            }
+            let at = locate(
+                &lines,
+                pattern,
+                (pos, lines.len()),
+                expected.map_or(pos, |e| e + top),
+            )?;
+            Some((level, top, bottom, at))
+        });
+
+        let mut report = HunkReport {
+            index,
+            header: hunk.header.to_string(),
+            applied: false,
+            expected_line: hunk.old_start,
+            applied_line: None,
+            offset: 0,
+            fuzz: 0,
+            error: None,
+        };
+        match found {
+            Some((level, top, bottom, at)) => {
+                let start = at.saturating_sub(top);
+                report.applied = true;
+                report.applied_line = Some(start + 1);
+                report.fuzz = level;
+                if let Some(expected) = expected {
+                    report.offset = start as isize - expected as isize + offset;
+                    offset = report.offset;
+                }
+                let end = at + old.len() - top - bottom;
+                patched.extend_from_slice(&lines[pos..at]);
+                patched.extend_from_slice(&new[top..new.len() - bottom]);
+                pos = end;
+                if end == lines.len() && bottom == 0 {
+                    if hunk.new_no_newline {
+                        trailing_newline = false;
+                    } else if hunk.old_no_newline {
+                        trailing_newline = true;
+                    }
+                }
+            }
+            None => {
+                report.error = Some(if old.is_empty() {
+                    "the hunk has no context and no line to insert at".to_string()
+                } else if fuzz == 0 || leading.max(trailing) == 0 {
+                    "the lines of the hunk match nowhere".to_string()
+                } else {
+                    format!(
+                        "the lines of the hunk match nowhere, even with fuzz {}",
+                        fuzz.min(leading.max(trailing))
+                    )
+                });
+            }
+        }
+        reports.push(report);
+    }
+    patched.extend_from_slice(&lines[pos..]);
+
+    let mut text = patched.join(eol);
+    if trailing_newline && !patched.is_empty() {
+        text.push_str(eol);
+    }
+    Ok(PatchResult {
+        patched: text,
+        hunks: reports,
+    })
+}
+
+/// Registers the patch function and its result classes with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(apply_patch, m)?)?;
+    m.add_class::<PatchResult>()?;
+    m.add_class::<HunkReport>()?;
+    Ok(())
+}
@@ -1,10 +1,11 @@
 """Provide a memory system to remember things."""
 
 from abc import ABC
-from typing import Optional, Self, Unpack
+from typing import List, Optional, Self, Unpack
 
 from fabricatio_core import TEMPLATE_MANAGER, logger
 from fabricatio_core.capabilities.propose import Propose
+from fabricatio_core.capabilities.usages import UseEmbedding
 from fabricatio_core.models.generic import ScopedConfig
 from fabricatio_core.models.kwargs_types import LLMKwargs, ValidateKwargs
 from fabricatio_core.utils import fallback_kwargs, ok
@@ -23,16 +24,24 @@ class RememberScopedConfig(ScopedConfig):
     """Configuration for LLM generation parameters used in memory operations."""
     memory_store_name: Optional[str] = Field(default=None)
     """The memory system instance used for storing and retrieving memories."""
+    memory_search_mode: Optional[str] = Field(default=None)
+    """How memories are recalled: "keyword", "vector" or "hybrid". Defaults to the configured search mode."""
+    memory_namespace: Optional[str] = Field(default=None)
+    """The namespace memories are recorded in and recalled from, so roles sharing a store keep apart. Defaults to all namespaces."""
+    memory_actor: Optional[str] = Field(default=None)
+    """The actor the mutations of the mounted store are recorded as made by in the audit log, e.g. the name of the role."""
 
     _memory_store: Optional[MemoryStore] = PrivateAttr(default=None)
 
 
-class Remember(Propose, RememberScopedConfig, ABC):
+class Remember(Propose, UseEmbedding, RememberScopedConfig, ABC):
     """Provide a memory system to remember things."""
 
     def mount_memory_store(self, memory_store: Optional[MemoryStore] = None) -> Self:
         """Mount a memory system to the capability."""
-        self._memory_store = memory_store or get_memory_service().get_store(ok(self.memory_store_name))
+        self._memory_store = memory_store or get_memory_service().get_store(
+            ok(self.memory_store_name), self.memory_actor
+        )
         return self
 
     def unmount_memory_system(self) -> Self:
@@ -40,6 +49,11 @@ class Remember(Propose, RememberScopedConfig, ABC):
         self._memory_store = None
         return self
 
+    @property
+    def search_mode(self) -> str:
+        """The search mode used to recall memories."""
+        return self.memory_search_mode or memory_config.search_mode
+
     def access_memory_store(self, fallback_default: Optional[MemoryStore] = None) -> MemoryStore:
         """Access the memory system."""
         if self._memory_store is None and fallback_default is not None:
@@ -69,23 +83,41 @@ class Remember(Propose, RememberScopedConfig, ABC):
             note.content,
             note.importance,
             note.tags,
+            embedding=None if self.search_mode == "keyword" else await self.vectorize(note.content),
+            namespace=self.memory_namespace,
         )
         logger.debug(f"Memory recorded: {mem_id}")
         return note
 
-    async def recall(self, query: str, top_k: int = 100, boost_recent: bool = True, **kwargs: Unpack[LLMKwargs]) -> str:
+    async def recall(
+        self,
+        query: str,
+        top_k: int = 100,
+        boost_recent: bool = True,
+        memory_filter: Optional[str] = None,
+        **kwargs: Unpack[LLMKwargs],
+    ) -> str:
         """Recall information from the memory system based on a query, Process with llm, which make a summary over memories.
 
         Args:
             query: The query string to search for relevant memories.
             top_k: The number of top memories to retrieve.
             boost_recent: Whether to boost the relevance of more recent memories.
+            memory_filter: A filter expression the recalled memories must satisfy, e.g. `metadata.project == "x"`.
             **kwargs: Additional keyword arguments for generation.
 
         Returns:
             A string containing the recalled information.
         """
-        mem_seq = self.access_memory_store().search_memories(query, top_k, boost_recent)
+        mem_seq = self.access_memory_store().search_memories(
+            query,
+            top_k,
+            boost_recent,
+            mode=self.search_mode,
+            query_embedding=None if self.search_mode == "keyword" else await self.vectorize(query),
+            namespace=self.memory_namespace,
+            filter=memory_filter,
+        )
         logger.debug(f"{len(mem_seq)} memories recalled, ids: {[mem.uuid for mem in mem_seq]}")
         return await self.aask(
             TEMPLATE_MANAGER.render_template(
@@ -93,3 +125,46 @@ class Remember(Propose, RememberScopedConfig, ABC):
             ),
             **fallback_kwargs(kwargs, **self.memory_llm),
         )
+
+    async def consolidate(
+        self,
+        window: Optional[int] = None,
+        threshold: float = 0.5,
+        **kwargs: Unpack[ValidateKwargs[Note]],
+    ) -> List[Note]:
+        """Merge the near-duplicate memories of the namespace, each cluster into a single note summarized by the LLM.
+
+        Args:
+            window: Only consolidate the memories created within this number of days, all of them if None.
+            threshold: The estimated similarity, between 0 and 1, from which two memories are merged.
+            **kwargs: Additional keyword arguments for generation.
+
+        Returns:
+            The notes that replaced a cluster, the clusters the LLM failed to summarize being left untouched.
+        """
+        store = self.access_memory_store()
+        clusters = store.consolidate(self.memory_namespace, window, threshold)
+        if not clusters:
+            return []
+        notes = await self.propose(
+            Note,
+            TEMPLATE_MANAGER.render_template(
+                memory_config.memory_consolidate_template,
+                [{"mem_seq": [mem.to_dict() for mem in cluster]} for cluster in clusters],
+            ),
+            **fallback_kwargs(kwargs, **self.memory_llm),
+        )
+
+        merged = []
+        for cluster, note in zip(clusters, notes, strict=True):
+            if note is None:
+                continue
+            mem_id = store.replace_cluster(
+                [mem.uuid for mem in cluster],
+                note.model_dump()
+                | {"embedding": None if self.search_mode == "keyword" else await self.vectorize(note.content)},
+            )
+            logger.debug(f"Memories {[mem.uuid for mem in cluster]} consolidated into {mem_id}")
+            merged.append(note)
+        store.write()
+        return merged
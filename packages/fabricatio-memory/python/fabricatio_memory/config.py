@@ -2,6 +2,7 @@
 
 from dataclasses import dataclass
 from pathlib import Path
+from typing import Optional
 
 from fabricatio_core import CONFIG
 
@@ -18,6 +19,8 @@ class MemoryConfig:
     """Template for recalling memory."""
     sremember_template: str = "built-in/sremember"
     """Template for selective remembering."""
+    memory_consolidate_template: str = "built-in/memory_consolidate"
+    """Template for merging near-duplicate memories into one."""
 
     memory_store_root: Path = store_root
     """Root directory for memory store."""
@@ -25,6 +28,18 @@ class MemoryConfig:
     """Buffer size for memory store writer. In bytes."""
     cache_size: int = 10
     """Cache size for memory store."""
+    metadata_sidecar: bool = False
+    """Whether to mirror the metadata of memory stores in a SQLite database for cheap ordered listings."""
+    auto_commit: Optional[int] = None
+    """Number of staged changes after which a memory store commits them without an explicit write."""
+    default_ttl: Optional[int] = None
+    """Lifetime in seconds of the memories recorded without an expiry, they are kept until deleted if None."""
+    max_memories_per_namespace: Optional[int] = None
+    """Number of memories per namespace beyond which `MemoryStore.expire` evicts the least relevant ones."""
+    audit_log: bool = False
+    """Whether to record every mutation of memory stores in an append-only log, so they can be inspected and undone."""
+    search_mode: str = "keyword"
+    """How `Remember` recalls memories: "keyword", "vector" or "hybrid". The last two embed notes and queries."""
 
 
 memory_config = CONFIG.load("memory", MemoryConfig)
@@ -13,4 +13,13 @@ from fabricatio_memory.rust import MemoryService
 @once
 def get_memory_service() -> MemoryService:
     """Get the singleton instance of the MemoryService."""
-    return MemoryService(memory_config.memory_store_root, memory_config.writer_buffer_size, memory_config.cache_size)
+    return MemoryService(
+        memory_config.memory_store_root,
+        memory_config.writer_buffer_size,
+        memory_config.cache_size,
+        memory_config.metadata_sidecar,
+        memory_config.auto_commit,
+        memory_config.default_ttl,
+        memory_config.max_memories_per_namespace,
+        memory_config.audit_log,
+    )
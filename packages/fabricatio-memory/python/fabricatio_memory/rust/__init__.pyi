@@ -8,7 +8,10 @@ import typing
 __all__ = [
     "MAX_IMPORTANCE_SCORE",
     "MIN_IMPORTANCE_SCORE",
+    "AuditEntry",
     "Memory",
+    "MemoryCluster",
+    "MemoryQuery",
     "MemoryService",
     "MemoryStats",
     "MemoryStore",
@@ -17,6 +20,37 @@ __all__ = [
 MAX_IMPORTANCE_SCORE: builtins.int = 100
 MIN_IMPORTANCE_SCORE: builtins.int = 0
 
+@typing.final
+class AuditEntry:
+    r"""A mutation of a memory recorded in the audit log."""
+    @property
+    def seq(self) -> builtins.int:
+        r"""Sequence number of the entry, increasing from 1 in the order of the log"""
+    @property
+    def timestamp(self) -> builtins.int:
+        r"""Unix timestamp when the mutation was recorded"""
+    @property
+    def uuid(self) -> builtins.str:
+        r"""Unique identifier of the mutated memory"""
+    @property
+    def action(self) -> builtins.str:
+        r"""Kind of mutation: `add`, `update` or `delete`"""
+    @property
+    def actor(self) -> typing.Optional[builtins.str]:
+        r"""Actor the store was opened for, if any"""
+    @property
+    def before(self) -> typing.Optional[Memory]:
+        r"""The memory before the mutation, None for an addition"""
+    @property
+    def after(self) -> typing.Optional[Memory]:
+        r"""The memory after the mutation, None for a deletion"""
+    def to_dict(self) -> dict:
+        r"""Converts the entry to a Python dictionary.
+
+        Returns:
+            A Python dictionary representation of the AuditEntry.
+        """
+
 @typing.final
 class Memory:
     r"""Represents a memory object with content, importance, tags, and access statistics."""
@@ -41,9 +75,162 @@ class Memory:
     @property
     def last_accessed(self) -> builtins.int:
         r"""Unix timestamp when the memory was last accessed."""
+    @property
+    def sensitivity(self) -> builtins.str:
+        r"""Access control label of the memory: "public", "internal" or "secret"."""
+    @property
+    def embedding(self) -> typing.Optional[builtins.list[builtins.float]]:
+        r"""Embedding vector of the content, used by vector and hybrid search."""
+    @property
+    def namespace(self) -> builtins.str:
+        r"""Namespace the memory belongs to, so several agents or threads can share one store."""
+    @property
+    def expires_at(self) -> typing.Optional[builtins.int]:
+        r"""Unix timestamp after which the memory is no longer returned, if any."""
+    @property
+    def metadata(self) -> builtins.dict[builtins.str, builtins.bool | builtins.int | builtins.float | builtins.str]:
+        r"""Arbitrary key/value metadata, matched by the `metadata.<key>` paths of search filters."""
     def to_dict(self) -> dict:
         r"""Convert the memory to a Python dictionary."""
 
+@typing.final
+class MemoryCluster:
+    r"""A group of memories sharing vocabulary, as reported by `MemoryStore.cluster`."""
+    @property
+    def label(self) -> builtins.str:
+        r"""The heaviest terms of the cluster joined by commas, such as `editor, dark, mode`."""
+    @property
+    def terms(self) -> builtins.list[builtins.str]:
+        r"""The terms weighing the most in the centroid of the cluster, heaviest first."""
+    @property
+    def size(self) -> builtins.int:
+        r"""Number of memories in the cluster."""
+    @property
+    def representatives(self) -> builtins.list[Memory]:
+        r"""The memories closest to the centroid of the cluster, closest first."""
+
+@typing.final
+class MemoryQuery:
+    r"""A structured query builder for memories.
+
+    Each builder method returns a new `MemoryQuery`, so calls can be chained:
+    `MemoryQuery().text("rust").tag_any(["infra"]).importance_gte(70).after(days=30)`.
+
+    The query is compiled directly into a tantivy `BooleanQuery`, so no query
+    string parsing is involved and quotes or special characters in the input are
+    matched literally.
+    """
+    def __new__(cls) -> MemoryQuery:
+        r"""Creates an empty query that matches all memories."""
+    def text(self, text: builtins.str) -> MemoryQuery:
+        r"""Requires the content to match the given text.
+
+        The text is tokenized like the indexed content, and memories matching any
+        of the resulting tokens are kept, ranked by relevance.
+
+        Args:
+            text (str): The free text to match against memory content.
+
+        Returns:
+            MemoryQuery: A new query with the text clause applied.
+        """
+    def tag_any(self, tags: typing.Sequence[builtins.str]) -> MemoryQuery:
+        r"""Requires the memory to carry at least one of the given tags.
+
+        Args:
+            tags (list[str]): The candidate tags.
+
+        Returns:
+            MemoryQuery: A new query with the tag clause applied.
+        """
+    def tag_all(self, tags: typing.Sequence[builtins.str]) -> MemoryQuery:
+        r"""Requires the memory to carry all of the given tags.
+
+        Args:
+            tags (list[str]): The required tags.
+
+        Returns:
+            MemoryQuery: A new query with the tag clause applied.
+        """
+    def tag_none(self, tags: typing.Sequence[builtins.str]) -> MemoryQuery:
+        r"""Excludes memories carrying any of the given tags.
+
+        Args:
+            tags (list[str]): The tags to exclude.
+
+        Returns:
+            MemoryQuery: A new query with the exclusion applied.
+        """
+    def importance_gte(self, importance: builtins.int) -> MemoryQuery:
+        r"""Requires the importance score to be greater than or equal to the given value.
+
+        Args:
+            importance (int): The inclusive lower bound.
+
+        Returns:
+            MemoryQuery: A new query with the importance bound applied.
+        """
+    def importance_lte(self, importance: builtins.int) -> MemoryQuery:
+        r"""Requires the importance score to be less than or equal to the given value.
+
+        Args:
+            importance (int): The inclusive upper bound.
+
+        Returns:
+            MemoryQuery: A new query with the importance bound applied.
+        """
+    def after(
+        self, days: typing.Optional[builtins.int] = None, timestamp: typing.Optional[builtins.int] = None
+    ) -> MemoryQuery:
+        r"""Requires the memory to be created at or after the given point in time.
+
+        Args:
+            days (int | None, optional): Number of days back from now.
+            timestamp (int | None, optional): Absolute unix timestamp.
+
+        Returns:
+            MemoryQuery: A new query with the lower time bound applied.
+
+        Raises:
+            ValueError: If not exactly one of `days` or `timestamp` is provided.
+        """
+    def before(
+        self, days: typing.Optional[builtins.int] = None, timestamp: typing.Optional[builtins.int] = None
+    ) -> MemoryQuery:
+        r"""Requires the memory to be created before the given point in time.
+
+        Args:
+            days (int | None, optional): Number of days back from now.
+            timestamp (int | None, optional): Absolute unix timestamp.
+
+        Returns:
+            MemoryQuery: A new query with the upper time bound applied.
+
+        Raises:
+            ValueError: If not exactly one of `days` or `timestamp` is provided.
+        """
+    def max_sensitivity(self, sensitivity: builtins.str) -> MemoryQuery:
+        r"""Excludes memories more sensitive than the given label.
+
+        Args:
+            sensitivity (str): The most sensitive label to include: "public", "internal" or "secret".
+
+        Returns:
+            MemoryQuery: A new query with the sensitivity bound applied.
+
+        Raises:
+            ValueError: If the label is unknown.
+        """
+    def namespace(self, namespace: builtins.str) -> MemoryQuery:
+        r"""Requires the memory to belong to the given namespace.
+
+        Args:
+            namespace (str): The namespace to search in.
+
+        Returns:
+            MemoryQuery: A new query with the namespace clause applied.
+        """
+
 @typing.final
 class MemoryService:
     r"""Service class for managing memory stores and indexes."""
@@ -52,6 +239,11 @@ class MemoryService:
         store_root_directory: builtins.str | os.PathLike | pathlib.Path,
         writer_buffer_size: builtins.int = 15000000,
         cache_size: builtins.int = 10,
+        metadata_sidecar: builtins.bool = False,
+        auto_commit: typing.Optional[builtins.int] = None,
+        default_ttl: typing.Optional[builtins.int] = None,
+        max_memories_per_namespace: typing.Optional[builtins.int] = None,
+        audit_log: builtins.bool = False,
     ) -> MemoryService:
         r"""Creates a new MemoryService instance.
 
@@ -59,11 +251,16 @@ class MemoryService:
             store_root_directory (pathlib.Path): The root directory where indexes will be stored.
             writer_buffer_size (int, optional): The buffer size for index writers in bytes. Defaults to 15,000,000 (15MB).
             cache_size (int, optional): The maximum number of indexes to keep in cache. Defaults to 10.
+            metadata_sidecar (bool, optional): If True, mirrors the metadata of each store in a SQLite database next to its index, serving `list_memories` and other ordered listings without going through the full-text index. Defaults to False.
+            auto_commit (int | None, optional): Commits the changes of a store once that many are staged, even if no operation asks to write. Defaults to None, committing only on explicit writes.
+            default_ttl (int | None, optional): Lifetime in seconds of the memories added without an expiry. Defaults to None, keeping them until deleted.
+            max_memories_per_namespace (int | None, optional): Number of memories per namespace beyond which `MemoryStore.expire` evicts the least relevant ones. Defaults to None, keeping them all.
+            audit_log (bool, optional): If True, records every addition, update and deletion of each store in an append-only log next to its index, see `MemoryStore.audit_log` and `MemoryStore.undo`. Defaults to False.
 
         Returns:
             MemoryService: A new instance of the MemoryService.
         """
-    def get_store(self, store_name: builtins.str) -> MemoryStore:
+    def get_store(self, store_name: builtins.str, actor: typing.Optional[builtins.str] = None) -> MemoryStore:
         r"""Gets a MemoryStore instance for the given store name.
 
         This method retrieves or creates an index for the given store name,
@@ -72,6 +269,7 @@ class MemoryService:
 
         Args:
             store_name (str): The name of the store to get.
+            actor (str | None, optional): Who the mutations made through the store are recorded as made by in the audit log, e.g. the name of a role. Defaults to None.
 
         Returns:
             MemoryStore: A MemoryStore instance for the given store name.
@@ -112,6 +310,17 @@ class MemoryStats:
     @property
     def avg_age_days(self) -> builtins.float:
         r"""Average age of memories in days."""
+    @property
+    def importance_histogram(self) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""Number of memories per importance bucket, as pairs of the lower bound of the bucket and
+        the count, the last bucket including MAX_IMPORTANCE_SCORE.
+        """
+    @property
+    def age_histogram(self) -> builtins.list[tuple[builtins.str, builtins.int]]:
+        r"""Number of memories per age bucket, as pairs of a label such as `7d-30d` and the count."""
+    @property
+    def tag_frequency(self) -> builtins.list[tuple[builtins.str, builtins.int]]:
+        r"""Number of memories carrying each tag, the most frequent first."""
     def display(self) -> builtins.str:
         r"""Display memory statistics in a formatted string."""
 
@@ -129,13 +338,35 @@ class MemoryStore:
     The implementation uses a Tantivy index with fields for content, tags, importance, timestamps,
     and access counts. It includes PyO3 bindings to allow Python usage.
     """
+    @typing.overload
     def add_memory(
         self,
         content: builtins.str,
         importance: builtins.int,
         tags: typing.Sequence[builtins.str],
         write: builtins.bool = False,
-    ) -> builtins.str:
+        sensitivity: builtins.str = "public",
+        embedding: typing.Optional[typing.Sequence[builtins.float]] = None,
+        namespace: typing.Optional[builtins.str] = None,
+        expires_at: typing.Optional[builtins.int] = None,
+        metadata: typing.Optional[typing.Mapping[builtins.str, builtins.bool | builtins.int | builtins.float | builtins.str]] = None,
+        related: None = None,
+    ) -> builtins.str: ...
+    @typing.overload
+    def add_memory(
+        self,
+        content: builtins.str,
+        importance: builtins.int,
+        tags: typing.Sequence[builtins.str],
+        write: builtins.bool = False,
+        sensitivity: builtins.str = "public",
+        embedding: typing.Optional[typing.Sequence[builtins.float]] = None,
+        namespace: typing.Optional[builtins.str] = None,
+        expires_at: typing.Optional[builtins.int] = None,
+        metadata: typing.Optional[typing.Mapping[builtins.str, builtins.bool | builtins.int | builtins.float | builtins.str]] = None,
+        *,
+        related: builtins.int,
+    ) -> tuple[builtins.str, builtins.list[builtins.str]]:
         r"""Adds a new memory to the system and returns its unique ID.
 
         Args:
@@ -143,11 +374,18 @@ class MemoryStore:
             importance (int): The importance score of the memory.
             tags (list[str]): A list of tags associated with the memory.
             write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+            sensitivity (str, optional): The access control label: "public", "internal" or "secret". Defaults to "public".
+            embedding (list[float] | None, optional): The embedding vector of the content, used by vector and hybrid search. Defaults to None.
+            namespace (str | None, optional): The namespace to add the memory to. Defaults to None, adding it to the "default" namespace.
+            expires_at (int | None, optional): The unix timestamp after which the memory is no longer returned. Defaults to None, using the default lifetime of the service if any.
+            metadata (dict[str, bool | int | float | str] | None, optional): Arbitrary key/value metadata, matched by the `metadata.<key>` paths of search filters. Defaults to None.
+            related (int | None, optional): The number of existing memories similar to the new one to return along with its ID, found by a MoreLikeThis query on the contents of the committed memories of its namespace, so that near-duplicates can be linked or merged. Defaults to None, returning the ID alone.
 
         Returns:
-            str: The UUID of the newly added memory.
+            str | tuple[str, list[str]]: The UUID of the newly added memory, followed by the UUIDs of the at most `related` most similar existing memories, the most similar first, if `related` is given.
 
         Raises:
+            ValueError: If the sensitivity label is unknown or the namespace is empty.
             Exception: If there is an error adding the memory or writing to the index.
         """
     def write(self) -> None:
@@ -159,6 +397,32 @@ class MemoryStore:
         Raises:
             Exception: If there is an error committing the changes.
         """
+    def add_memories_bulk(
+        self,
+        memories: typing.Sequence[typing.Mapping[builtins.str, typing.Any]],
+        write: builtins.bool = False,
+        namespace: typing.Optional[builtins.str] = None,
+    ) -> builtins.list[builtins.str]:
+        r"""Adds many memories at once, staging them under a single lock of the writer.
+
+        Much faster than repeated calls to `add_memory` for bulk ingestion, especially when
+        committing only once at the end.
+
+        Args:
+            memories (list[dict]): The memories to add, each a mapping with `content`, `importance` and optionally `tags`, `sensitivity`, `embedding`, `namespace`, `expires_at` and `metadata`, e.g. dumped `Note` models.
+            write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+            namespace (str | None, optional): The namespace of the memories that do not name one. Defaults to None, adding them to the "default" namespace.
+
+        Returns:
+            list[str]: The UUIDs of the added memories, in the same order.
+
+        Raises:
+            ValueError: If a memory is malformed, more important than the maximum, carries an unknown sensitivity label or an empty namespace. Nothing is added then.
+            Exception: If there is an error adding the memories or writing to the index.
+        """
+    @property
+    def pending_changes(self) -> builtins.int:
+        r"""The number of changes staged since the last commit, not yet visible to searches."""
     def get_memory(self, uuid: builtins.str, write: builtins.bool = False) -> typing.Optional[Memory]:
         r"""Retrieves a memory by its ID and updates its access count.
 
@@ -167,7 +431,7 @@ class MemoryStore:
             write (bool, optional): If True, commits the access update to disk immediately. Defaults to False.
 
         Returns:
-            Memory | None: The retrieved Memory object, or None if not found.
+            Memory | None: The retrieved Memory object, or None if not found or expired.
 
         Raises:
             Exception: If there is an error retrieving the memory or updating the index.
@@ -179,6 +443,10 @@ class MemoryStore:
         importance: typing.Optional[builtins.int] = None,
         tags: typing.Optional[typing.Sequence[builtins.str]] = None,
         write: builtins.bool = False,
+        sensitivity: typing.Optional[builtins.str] = None,
+        embedding: typing.Optional[typing.Sequence[builtins.float]] = None,
+        expires_at: typing.Optional[builtins.int] = None,
+        metadata: typing.Optional[typing.Mapping[builtins.str, builtins.bool | builtins.int | builtins.float | builtins.str]] = None,
     ) -> builtins.bool:
         r"""Updates an existing memory's content, importance, or tags.
 
@@ -188,16 +456,23 @@ class MemoryStore:
             importance (int | None, optional): The new importance score. Defaults to None.
             tags (list[str] | None, optional): The new list of tags. Defaults to None.
             write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+            sensitivity (str | None, optional): The new access control label. Defaults to None.
+            embedding (list[float] | None, optional): The new embedding vector. Defaults to None, which drops the stored embedding if the content changes, as it would no longer match.
+            expires_at (int | None, optional): The new expiry as a unix timestamp. Defaults to None, keeping the current one.
+            metadata (dict[str, bool | int | float | str] | None, optional): The new metadata, replacing the current one. Defaults to None, keeping the current one.
 
         Returns:
             bool: True if the memory was found and updated, False otherwise.
 
         Raises:
+            ValueError: If the sensitivity label is unknown.
             Exception: If there is an error updating the memory or writing to the index.
         """
     def delete_memory(self, uuid: builtins.str, write: builtins.bool = False) -> builtins.bool:
         r"""Deletes a memory by its ID.
 
+        The deletion is recorded in the audit log if the service keeps one and the memory exists.
+
         Args:
             uuid (str): The unique identifier of the memory to delete.
             write (bool, optional): If True, commits the deletion to disk immediately. Defaults to False.
@@ -214,23 +489,68 @@ class MemoryStore:
         top_k: builtins.int = 20,
         boost_recent: builtins.bool = False,
         write: builtins.bool = False,
+        max_sensitivity: typing.Optional[builtins.str] = None,
+        redact: typing.Optional[typing.Callable[[Memory], builtins.str]] = None,
+        mode: builtins.str = "keyword",
+        query_embedding: typing.Optional[typing.Sequence[builtins.float]] = None,
+        namespace: typing.Optional[builtins.str] = None,
+        filter: typing.Optional[builtins.str] = None,
     ) -> builtins.list[Memory]:
         r"""Searches memories by query string with optional recency boosting.
 
+        The `keyword` mode ranks memories by BM25 over their content and tags. The `vector`
+        mode ranks the memories carrying an embedding by cosine similarity to `query_embedding`,
+        so paraphrased queries still recall them. The `hybrid` mode fuses both rankings by
+        reciprocal rank fusion.
+
         Args:
-            query_str (str): The search query string.
+            query_str (str): The search query string, ignored in `vector` mode.
             top_k (int, optional): The maximum number of results to return. Defaults to 20.
-            boost_recent (bool, optional): If True, boosts the score of more recent memories. Defaults to False.
+            boost_recent (bool, optional): If True, boosts the score of more recent memories in the keyword ranking. Defaults to False.
             write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+            redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+            mode (str, optional): "keyword", "vector" or "hybrid". Defaults to "keyword".
+            query_embedding (list[float] | None, optional): The embedding vector of the query, required by the `vector` and `hybrid` modes. Defaults to None.
+            namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
+            filter (str | None, optional): A filter expression the memories must satisfy, such as `metadata.project == "x" AND importance > 50`. Defaults to None.
 
         Returns:
             list[Memory]: A list of matching Memory objects, sorted by relevance.
 
         Raises:
+            ValueError: If the sensitivity label or the mode is unknown, the mode requires a missing query embedding, or the filter is invalid.
             Exception: If there is an error parsing the query or searching the index.
         """
+    def query(
+        self,
+        query: MemoryQuery,
+        top_k: builtins.int = 20,
+        write: builtins.bool = False,
+        redact: typing.Optional[typing.Callable[[Memory], builtins.str]] = None,
+    ) -> builtins.list[Memory]:
+        r"""Searches memories with a structured `MemoryQuery`.
+
+        Args:
+            query (MemoryQuery): The structured query to run.
+            top_k (int, optional): The maximum number of results to return. Defaults to 20.
+            write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+
+        Returns:
+            list[Memory]: A list of matching Memory objects, sorted by relevance.
+
+        Raises:
+            Exception: If there is an error building the query or searching the index.
+        """
     def search_by_tags(
-        self, tags: typing.Sequence[builtins.str], top_k: builtins.int = 20, write: builtins.bool = False
+        self,
+        tags: typing.Sequence[builtins.str],
+        top_k: builtins.int = 20,
+        write: builtins.bool = False,
+        max_sensitivity: typing.Optional[builtins.str] = None,
+        redact: typing.Optional[typing.Callable[[Memory], builtins.str]] = None,
+        namespace: typing.Optional[builtins.str] = None,
     ) -> builtins.list[Memory]:
         r"""Searches memories by specific tags.
 
@@ -238,51 +558,111 @@ class MemoryStore:
             tags (list[str]): A list of tags to search for. Memories matching any of these tags will be returned.
             top_k (int, optional): The maximum number of results to return. Defaults to 20.
             write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+            redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+            namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
 
         Returns:
             list[Memory]: A list of matching Memory objects.
 
         Raises:
+            ValueError: If the sensitivity label is unknown.
             Exception: If there is an error searching the index.
         """
     def get_memories_by_importance(
-        self, min_importance: builtins.int, top_k: builtins.int = 20, write: builtins.bool = False
+        self,
+        min_importance: builtins.int,
+        top_k: builtins.int = 20,
+        write: builtins.bool = False,
+        namespace: typing.Optional[builtins.str] = None,
     ) -> builtins.list[Memory]:
-        r"""Gets memories filtered by a minimum importance level.
+        r"""Gets memories filtered by a minimum importance level, most important first.
 
         Args:
             min_importance (int): The minimum importance score.
             top_k (int, optional): The maximum number of results to return. Defaults to 20.
             write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
 
         Returns:
-            list[Memory]: A list of Memory objects with importance >= min_importance.
+            list[Memory]: A list of Memory objects with importance >= min_importance, sorted by importance in descending order.
 
         Raises:
             Exception: If there is an error searching the index.
         """
     def get_recent_memories(
-        self, days: builtins.int, top_k: builtins.int = 20, write: builtins.bool = False
+        self,
+        days: builtins.int,
+        top_k: builtins.int = 20,
+        write: builtins.bool = False,
+        namespace: typing.Optional[builtins.str] = None,
     ) -> builtins.list[Memory]:
-        r"""Gets memories from the last N days.
+        r"""Gets memories from the last N days, newest first.
 
         Args:
             days (int): The number of days to look back.
             top_k (int, optional): The maximum number of results to return. Defaults to 20.
             write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
 
         Returns:
-            list[Memory]: A list of Memory objects created within the last N days.
+            list[Memory]: A list of Memory objects created within the last N days, sorted by timestamp in descending order.
 
         Raises:
             Exception: If there is an error searching the index.
         """
-    def get_frequently_accessed(self, top_k: builtins.int = 20, write: builtins.bool = False) -> builtins.list[Memory]:
+    def cleanup_old_memories(
+        self,
+        days: builtins.int,
+        max_importance: typing.Optional[builtins.int] = None,
+        write: builtins.bool = False,
+        namespace: typing.Optional[builtins.str] = None,
+    ) -> builtins.int:
+        r"""Deletes the memories older than N days.
+
+        The memories are selected by range queries over the timestamp and importance fields
+        and deleted by query, so no memory is loaded from the index unless the metadata
+        sidecar needs their uuids. Memories meant to be forgotten after a while are better
+        given an expiry, see `expire`.
+
+        Args:
+            days (int): The age in days beyond which memories are deleted.
+            max_importance (int | None, optional): Only delete memories at most this important. Defaults to None, deleting regardless of importance.
+            write (bool, optional): If True, commits the deletions to disk immediately. Defaults to False.
+            namespace (str | None, optional): Only delete memories of this namespace. Defaults to None, deleting in all namespaces.
+
+        Returns:
+            int: The number of memories deleted.
+
+        Raises:
+            Exception: If there is an error searching or updating the index.
+        """
+    def expire(self, write: builtins.bool = False) -> builtins.int:
+        r"""Sweeps the expired memories and enforces the retention policy of the service.
+
+        Expired memories are already left out of searches and listings, the sweep deletes them
+        from the index. When the service caps the number of memories per namespace, the least
+        relevant memories of the namespaces over the cap are evicted too, the least recently
+        accessed first among equally relevant ones. Only committed memories are swept.
+
+        Args:
+            write (bool, optional): If True, commits the deletions to disk immediately. Defaults to False.
+
+        Returns:
+            int: The number of memories deleted.
+
+        Raises:
+            Exception: If there is an error searching or updating the index.
+        """
+    def get_frequently_accessed(
+        self, top_k: builtins.int = 20, write: builtins.bool = False, namespace: typing.Optional[builtins.str] = None
+    ) -> builtins.list[Memory]:
         r"""Gets memories sorted by access frequency (most accessed first).
 
         Args:
             top_k (int, optional): The maximum number of results to return. Defaults to 20.
             write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+            namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
 
         Returns:
             list[Memory]: A list of Memory objects sorted by access count in descending order.
@@ -290,19 +670,355 @@ class MemoryStore:
         Raises:
             Exception: If there is an error searching the index.
         """
-    def count_memories(self) -> builtins.int:
-        r"""Counts the total number of memories in the system.
+    def list_memories(
+        self,
+        order_by: builtins.str = "timestamp",
+        descending: builtins.bool = True,
+        limit: builtins.int = 20,
+        offset: builtins.int = 0,
+        tag: typing.Optional[builtins.str] = None,
+        namespace: typing.Optional[builtins.str] = None,
+    ) -> builtins.list[Memory]:
+        r"""Lists memories ordered by a metadata attribute, without updating access statistics.
+
+        Served by the metadata sidecar when the service enables it, so paging through a large
+        store does not go through the full-text index.
+
+        Args:
+            order_by (str, optional): "timestamp", "last_accessed", "access_count" or "importance". Defaults to "timestamp".
+            descending (bool, optional): If True, lists the highest values first. Defaults to True.
+            limit (int, optional): The maximum number of memories to return. Defaults to 20.
+            offset (int, optional): The number of memories to skip. Defaults to 0.
+            tag (str | None, optional): Only list memories carrying this tag. Defaults to None.
+            namespace (str | None, optional): Only list memories of this namespace. Defaults to None, listing all namespaces.
+
+        Returns:
+            list[Memory]: The memories of the requested page.
+
+        Raises:
+            ValueError: If the sort key is unknown.
+            Exception: If there is an error reading the index or the sidecar.
+        """
+    def export_markdown(
+        self,
+        dir: builtins.str | os.PathLike | pathlib.Path,
+        group_by: builtins.str = "tag",
+        namespace: typing.Optional[builtins.str] = None,
+    ) -> builtins.int:
+        r"""Exports all memories as Obsidian-style Markdown notes.
+
+        Each note is titled after the first line of the memory content and carries the uuid,
+        importance, tags, sensitivity, namespace and access statistics in its frontmatter, so that
+        `import_markdown` restores the memory. Existing notes with the same path are overwritten.
+
+        Args:
+            dir (str | Path): The directory to write the notes to, created if missing.
+            group_by (str, optional): "tag" to put each note in a subdirectory named after its first tag, or "none" to keep all notes at the top level. Defaults to "tag".
+            namespace (str | None, optional): Only export the memories of this namespace. Defaults to None, exporting all namespaces.
+
+        Returns:
+            int: The number of notes written.
+
+        Raises:
+            ValueError: If the grouping is unknown.
+            OSError: If a note cannot be written.
+        """
+    def import_markdown(self, dir: builtins.str | os.PathLike | pathlib.Path, write: builtins.bool = False) -> builtins.int:
+        r"""Imports the Markdown notes below a directory as memories.
+
+        Notes whose frontmatter has the uuid of an existing memory replace it, keeping its
+        embedding if the content is unchanged, the others are added. Notes without frontmatter become new memories with the minimum importance and no
+        tags. Hidden files and directories, such as `.obsidian`, are skipped.
+
+        Args:
+            dir (str | Path): The directory to read the notes from, recursively.
+            write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+
+        Returns:
+            int: The number of imported notes.
+
+        Raises:
+            ValueError: If a frontmatter attribute is invalid, nothing is imported then.
+            OSError: If the notes cannot be read.
+        """
+    def export_jsonl(
+        self, path: builtins.str | os.PathLike | pathlib.Path, namespace: typing.Optional[builtins.str] = None
+    ) -> builtins.int:
+        r"""Exports memories as JSON Lines, one memory per line with all its attributes.
+
+        Unlike Markdown notes, the lines keep embeddings and access statistics, so
+        `import_jsonl` restores the memories exactly, e.g. on another machine. Only committed
+        memories are exported, expired ones included. An existing file is overwritten.
+
+        Args:
+            path (str | Path): The file to write, its parent directory is created if missing.
+            namespace (str | None, optional): Only export the memories of this namespace. Defaults to None, exporting all namespaces.
 
         Returns:
-            int: The total number of documents in the index.
+            int: The number of memories written.
+
+        Raises:
+            OSError: If the file cannot be written.
         """
-    def stats(self) -> MemoryStats:
+    def import_jsonl(
+        self, path: builtins.str | os.PathLike | pathlib.Path, write: builtins.bool = False, dedup: builtins.bool = True
+    ) -> builtins.int:
+        r"""Imports memories from JSON Lines, as written by `export_jsonl` or prepared from a dataset.
+
+        Each line holds a memory with at least `uuid`, `content`, `timestamp`, `importance`,
+        `tags`, `access_count`, `last_accessed` and `sensitivity`. Lines with the uuid of an
+        existing memory replace it. With `dedup`, lines whose content is already held by another
+        memory of the same namespace, in the store or earlier in the file, are skipped, so
+        importing the same dataset twice does not duplicate it. Blank lines are ignored.
+
+        Args:
+            path (str | Path): The file to read.
+            write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+            dedup (bool, optional): If True, skips the memories whose content is already stored in their namespace. Defaults to True.
+
+        Returns:
+            int: The number of memories added or replaced.
+
+        Raises:
+            ValueError: If a line is not a valid memory, nothing is imported then.
+            OSError: If the file cannot be read.
+        """
+    def snapshot(self, dir: builtins.str | os.PathLike | pathlib.Path) -> builtins.int:
+        r"""Writes a snapshot of the store to a new index directory, as a backup to `restore` from.
+
+        The snapshot is a compact copy of the committed memories, expired ones included, and
+        can be opened as a store of its own by moving it below the root of a service.
+
+        Args:
+            dir (str | Path): The directory to write the snapshot to, which must not hold an index already.
+
+        Returns:
+            int: The number of memories in the snapshot.
+
+        Raises:
+            ValueError: If the directory already holds an index.
+            Exception: If the snapshot cannot be written.
+        """
+    def restore(self, dir: builtins.str | os.PathLike | pathlib.Path, write: builtins.bool = False) -> builtins.int:
+        r"""Replaces every memory of the store with those of a snapshot.
+
+        The current memories, of all namespaces, are deleted, so pending changes are lost
+        unless written first. The replacement is recorded in the audit log if enabled, so it
+        can be undone memory by memory.
+
+        Args:
+            dir (str | Path): The directory holding a snapshot written by `snapshot`.
+            write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+
+        Returns:
+            int: The number of memories restored.
+
+        Raises:
+            ValueError: If the directory does not hold a snapshot.
+            Exception: If the snapshot cannot be read or the index updated.
+        """
+    def audit_log(
+        self,
+        uuid: typing.Optional[builtins.str] = None,
+        actor: typing.Optional[builtins.str] = None,
+        action: typing.Optional[builtins.str] = None,
+        since: typing.Optional[builtins.int] = None,
+        until: typing.Optional[builtins.int] = None,
+        limit: builtins.int = 100,
+    ) -> builtins.list[AuditEntry]:
+        r"""Lists the recorded mutations of the store, newest first.
+
+        Every addition, update and deletion is recorded before it is staged, together with the
+        memory as it was before and after it, so an entry may describe a change that was never
+        committed. Access statistics updated by reads are not recorded.
+
+        Args:
+            uuid (str | None, optional): Only list the mutations of this memory. Defaults to None.
+            actor (str | None, optional): Only list the mutations made through stores opened for this actor. Defaults to None.
+            action (str | None, optional): Only list mutations of this kind: "add", "update" or "delete". Defaults to None.
+            since (int | None, optional): Only list mutations recorded at or after this unix timestamp. Defaults to None.
+            until (int | None, optional): Only list mutations recorded before this unix timestamp. Defaults to None.
+            limit (int, optional): The maximum number of entries to return. Defaults to 100.
+
+        Returns:
+            list[AuditEntry]: The matching entries, newest first.
+
+        Raises:
+            ValueError: If the action is unknown.
+            RuntimeError: If the service does not keep an audit log.
+            OSError: If the log cannot be read.
+        """
+    def undo(self, seq: builtins.int, write: builtins.bool = False) -> builtins.bool:
+        r"""Undoes a recorded mutation, restoring the memory as it was before it.
+
+        Undoing a deletion or an update restores the recorded memory, replacing its current
+        version if any, undoing an addition deletes the memory. Later mutations of the same
+        memory are overwritten, and the undo is recorded in the audit log like any mutation.
+
+        Args:
+            seq (int): The sequence number of the entry to undo.
+            write (bool, optional): If True, commits the change to disk immediately. Defaults to False.
+
+        Returns:
+            bool: True if a change was staged, False if the entry is unknown or the memory it added is already gone.
+
+        Raises:
+            RuntimeError: If the service does not keep an audit log.
+            OSError: If the log cannot be read or written.
+            Exception: If there is an error updating the index.
+        """
+    def count_memories(self, namespace: typing.Optional[builtins.str] = None) -> builtins.int:
+        r"""Counts the unexpired memories in the system.
+
+        Args:
+            namespace (str | None, optional): Only count the memories of this namespace. Defaults to None, counting all namespaces.
+
+        Returns:
+            int: The number of matching documents in the index.
+
+        Raises:
+            Exception: If there is an error searching the index.
+        """
+    def stats(self, namespace: typing.Optional[builtins.str] = None) -> MemoryStats:
         r"""Gets aggregated statistics about all memories.
 
+        Args:
+            namespace (str | None, optional): Only aggregate the memories of this namespace. Defaults to None, aggregating all namespaces.
+
         Returns:
             MemoryStats: An object containing total memories, average importance,
-                         average access count, and average age in days.
+                         average access count, average age in days, and the importance,
+                         age and tag histograms.
 
         Raises:
             Exception: If there is an error calculating aggregations.
         """
+    def stale_memories(
+        self,
+        threshold: builtins.float,
+        top_k: builtins.int = 20,
+        decay_factor: builtins.float = 0.01,
+        namespace: typing.Optional[builtins.str] = None,
+    ) -> builtins.list[tuple[builtins.float, Memory]]:
+        r"""Reports the memories whose relevance decayed below a threshold, the least relevant first.
+
+        The relevance score multiplies the importance by an exponential decay of the age and by
+        the logarithm of the access count, as used to evict memories, so the report shows which
+        memories the next sweep would drop first and which are worth consolidating or deleting.
+        Access statistics are not updated.
+
+        Args:
+            threshold (float): The relevance score below which a memory is stale.
+            top_k (int, optional): The maximum number of memories to report. Defaults to 20.
+            decay_factor (float, optional): The daily decay rate of the age factor. Defaults to 0.01.
+            namespace (str | None, optional): Only report the memories of this namespace. Defaults to None, reporting all namespaces.
+
+        Returns:
+            list[tuple[float, Memory]]: Pairs of the relevance score and the memory, ascending by score.
+
+        Raises:
+            Exception: If there is an error searching the index.
+        """
+    def hot_memories(
+        self, top_k: builtins.int = 20, decay_factor: builtins.float = 0.1, namespace: typing.Optional[builtins.str] = None
+    ) -> builtins.list[tuple[builtins.float, Memory]]:
+        r"""Reports the memories accessed the most lately, the hottest first.
+
+        The heat of a memory is its access count decayed exponentially with the days since its
+        last access, so memories read often but long ago cool down. Unlike
+        `get_frequently_accessed`, access statistics are not updated.
+
+        Args:
+            top_k (int, optional): The maximum number of memories to report. Defaults to 20.
+            decay_factor (float, optional): The daily decay rate of the access count. Defaults to 0.1.
+            namespace (str | None, optional): Only report the memories of this namespace. Defaults to None, reporting all namespaces.
+
+        Returns:
+            list[tuple[float, Memory]]: Pairs of the heat and the memory, descending by heat, never accessed memories left out.
+
+        Raises:
+            Exception: If there is an error searching the index.
+        """
+    def consolidate(
+        self,
+        namespace: typing.Optional[builtins.str] = None,
+        window: typing.Optional[builtins.int] = None,
+        threshold: builtins.float = 0.5,
+        min_size: builtins.int = 2,
+    ) -> builtins.list[builtins.list[Memory]]:
+        r"""Groups near-duplicate memories into clusters, the candidates for being summarized into
+        one memory each and replaced with `replace_cluster`.
+
+        Similarity is the overlap of the word pairs of the contents, estimated with MinHash, so
+        rephrasings sharing few words are missed. Memories of different namespaces are never
+        clustered together, and access statistics are not updated.
+
+        Args:
+            namespace (str | None, optional): Only consolidate the memories of this namespace. Defaults to None, consolidating every namespace separately.
+            window (int | None, optional): Only consolidate the memories created within this number of days. Defaults to None, consolidating all memories.
+            threshold (float, optional): The estimated similarity, between 0 and 1, from which two memories are linked. Defaults to 0.5.
+            min_size (int, optional): The minimum number of memories of a cluster. Defaults to 2.
+
+        Returns:
+            list[list[Memory]]: The clusters, largest first, each ordered from the oldest memory to the newest.
+
+        Raises:
+            ValueError: If the threshold is not between 0 and 1.
+            Exception: If there is an error searching the index.
+        """
+    def cluster(
+        self,
+        tag: typing.Optional[builtins.str] = None,
+        k: builtins.int = 8,
+        representatives: builtins.int = 3,
+        namespace: typing.Optional[builtins.str] = None,
+        max_sensitivity: typing.Optional[builtins.str] = None,
+    ) -> builtins.list[MemoryCluster]:
+        r"""Groups memories by the terms of their contents and tags, a cheap overview of what the
+        store holds.
+
+        Memories are compared by the cosine similarity of their TF-IDF vectors and grouped with
+        k-means, without any embedding service, so clusters reflect shared vocabulary rather
+        than meaning. Memories without any significant word are left out, and access
+        statistics are not updated.
+
+        Args:
+            tag (str | None, optional): Only cluster the memories carrying this tag. Defaults to None, clustering all memories.
+            k (int, optional): The number of clusters to form, fewer if there are fewer memories. Defaults to 8.
+            representatives (int, optional): The number of memories reported per cluster. Defaults to 3.
+            namespace (str | None, optional): Only cluster the memories of this namespace. Defaults to None, clustering all namespaces together.
+            max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+
+        Returns:
+            list[MemoryCluster]: The non-empty clusters, largest first, with their label, terms, size and representative memories.
+
+        Raises:
+            ValueError: If the sensitivity label is unknown.
+            Exception: If there is an error searching the index.
+        """
+    def replace_cluster(
+        self,
+        uuids: typing.Sequence[builtins.str],
+        memory: typing.Mapping[builtins.str, typing.Any],
+        write: builtins.bool = False,
+    ) -> builtins.str:
+        r"""Replaces memories, typically a cluster returned by `consolidate`, with a new one
+        summarizing them.
+
+        The deletions and the addition are staged under a single lock of the writer and
+        appended to the audit log at once, so they become visible together at the next
+        commit. The new memory is at least as sensitive as the most sensitive of the memories
+        it replaces, so a summary cannot leak secrets under a lower label.
+
+        Args:
+            uuids (list[str]): The unique identifiers of the memories to replace.
+            memory (dict): The new memory, a mapping with `content`, `importance` and optionally `tags`, `sensitivity`, `embedding`, `namespace`, `expires_at` and `metadata`, e.g. a dumped `Note` model.
+            write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+
+        Returns:
+            str: The UUID of the new memory.
+
+        Raises:
+            ValueError: If no uuid is given, a memory is not found, or the new memory is malformed. Nothing is changed then.
+            Exception: If there is an error updating the index.
+        """
@@ -0,0 +1,47 @@
+"""Tests for the histograms of memory statistics and the decay reports."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories read a varying number of times."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    hot = store.add_memory("Deploys go through Argo", 90, ["ops", "k8s"])
+    store.add_memory("Old office address", 10, ["admin"])
+    warm = store.add_memory("Standup at ten", 50, ["ops"])
+    store.write()
+    for mem_id in (hot, hot, hot, warm):
+        store.get_memory(mem_id, write=True)
+    return store
+
+
+def test_histograms(store: MemoryStore) -> None:
+    """Statistics bucket memories by importance and age and count their tags."""
+    stats = store.stats()
+    assert len(stats.importance_histogram) == 10
+    assert dict(stats.importance_histogram) == {**{i * 10: 0 for i in range(10)}, 10: 1, 50: 1, 90: 1}
+    assert stats.age_histogram[0] == ("<1d", 3)
+    assert sum(count for _, count in stats.age_histogram) == 3
+    assert stats.tag_frequency == [("ops", 2), ("admin", 1), ("k8s", 1)]
+    assert "Tag Frequency: ops: 2" in stats.display()
+
+
+def test_stale_memories(store: MemoryStore) -> None:
+    """Memories below the relevance threshold are reported, the least relevant first."""
+    report = store.stale_memories(50.0)
+    assert [m.content for _, m in report] == ["Old office address", "Standup at ten"]
+    assert report[0][0] == pytest.approx(0.0)
+    assert store.stale_memories(50.0, top_k=1)[0][1].content == "Old office address"
+
+
+def test_hot_memories(store: MemoryStore) -> None:
+    """Accessed memories are reported by decayed access count, without being accessed again."""
+    report = store.hot_memories()
+    assert [m.content for _, m in report] == ["Deploys go through Argo", "Standup at ten"]
+    assert report[0][0] == pytest.approx(3.0, rel=1e-3)
+    assert store.hot_memories()[0][1].access_count == 3
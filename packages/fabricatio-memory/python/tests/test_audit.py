@@ -0,0 +1,116 @@
+"""Tests for the audit log of memory store mutations."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_core.utils import ok
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def service(tmp_path: Path) -> MemoryService:
+    """Fixture to create a service keeping an audit log."""
+    return MemoryService(tmp_path, audit_log=True)
+
+
+@pytest.fixture
+def store(service: MemoryService) -> MemoryStore:
+    """Fixture to create a store opened for an agent."""
+    return service.get_store(uuid.uuid4().hex, actor="agent")
+
+
+def test_mutations_are_recorded(store: MemoryStore) -> None:
+    """Additions, updates and deletions are recorded with the memory before and after them."""
+    mem_id = store.add_memory("User prefers tea", 50, ["drink"], write=True)
+    store.update_memory(mem_id, content="User prefers coffee", write=True)
+    store.get_memory(mem_id, write=True)
+    store.delete_memory(mem_id, write=True)
+    store.delete_memory("missing", write=True)
+
+    entries = store.audit_log()
+    assert [e.action for e in entries] == ["delete", "update", "add"]
+    assert [e.seq for e in entries] == [3, 2, 1]
+    assert all(e.uuid == mem_id and e.actor == "agent" for e in entries)
+
+    delete, update, add = entries
+    assert add.before is None
+    assert ok(add.after).content == "User prefers tea"
+    assert ok(update.before).content == "User prefers tea"
+    assert ok(update.after).content == "User prefers coffee"
+    assert delete.after is None
+
+
+def test_filters(store: MemoryStore) -> None:
+    """Entries can be filtered by memory, actor, action and time."""
+    first = store.add_memory("First", 10, [])
+    store.add_memory("Second", 10, [])
+    store.write()
+
+    assert store.audit_log(action="delete") == []
+    assert [e.uuid for e in store.audit_log(uuid=first)] == [first]
+    assert len(store.audit_log(actor="agent", limit=1)) == 1
+    assert store.audit_log(actor="someone else") == []
+    assert store.audit_log(since=0, until=1) == []
+
+    with pytest.raises(ValueError, match="Unknown audit action"):
+        store.audit_log(action="purge")
+
+
+def test_bulk_deletions_are_recorded(store: MemoryStore) -> None:
+    """Deletions by query record every deleted memory."""
+    store.add_memories_bulk([{"content": "One", "importance": 10}, {"content": "Two", "importance": 10}], write=True)
+    assert store.cleanup_old_memories(-1, write=True) == 2
+    assert {ok(e.before).content for e in store.audit_log(action="delete")} == {"One", "Two"}
+
+
+def test_undo(store: MemoryStore) -> None:
+    """Undoing restores the memory as it was, and is recorded in turn."""
+    mem_id = store.add_memory("User lives in Berlin", 50, ["profile"], write=True)
+    store.update_memory(mem_id, importance=90, write=True)
+    store.delete_memory(mem_id, write=True)
+    assert store.count_memories() == 0
+
+    assert store.undo(3, write=True)
+    assert ok(store.get_memory(mem_id)).importance == 90
+    assert store.undo(2, write=True)
+    assert ok(store.get_memory(mem_id)).importance == 50
+    assert store.undo(1, write=True)
+    assert store.get_memory(mem_id) is None
+
+    assert not store.undo(1)
+    assert not store.undo(42)
+    assert [e.action for e in store.audit_log()] == ["delete", "update", "add", "delete", "update", "add"]
+
+
+def test_log_survives_reopening(tmp_path: Path) -> None:
+    """Sequence numbers continue from the existing log."""
+    name = uuid.uuid4().hex
+    MemoryService(tmp_path, audit_log=True).get_store(name).add_memory("Kept", 10, [], write=True)
+    store = MemoryService(tmp_path, audit_log=True).get_store(name)
+    store.add_memory("Later", 10, [], write=True)
+    assert [e.seq for e in store.audit_log()] == [2, 1]
+
+
+def test_truncated_line_is_skipped(tmp_path: Path) -> None:
+    """A line cut by a crash is skipped when reading, and reopening starts a new line."""
+    name = uuid.uuid4().hex
+    MemoryService(tmp_path, audit_log=True).get_store(name).add_memory("Kept", 10, [], write=True)
+    path = tmp_path / name / "audit.jsonl"
+    with path.open("ab") as file:
+        file.write('{"seq": 2, "uuid": "caf\u00e9'.encode()[:-1])
+
+    store = MemoryService(tmp_path, audit_log=True).get_store(name)
+    assert [e.seq for e in store.audit_log()] == [1]
+    store.add_memory("Later", 10, [], write=True)
+    assert [e.seq for e in store.audit_log()] == [2, 1]
+    assert path.read_bytes().count(b"\n") == 3
+
+
+def test_disabled(tmp_path: Path) -> None:
+    """Stores of services without an audit log refuse to query it."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    with pytest.raises(RuntimeError, match="audit log is disabled"):
+        store.audit_log()
+    with pytest.raises(RuntimeError, match="audit log is disabled"):
+        store.undo(1)
@@ -0,0 +1,50 @@
+"""Tests for bulk ingestion and batched commits."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.models.note import Note
+from fabricatio_memory.rust import MemoryService
+
+
+def test_add_memories_bulk(tmp_path: Path) -> None:
+    """Bulk additions are staged until written and keep their order."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    notes = [Note(content=f"note {i}", importance=i, tags=["bulk"]) for i in range(5)]
+    ids = store.add_memories_bulk([note.model_dump() for note in notes] + [{"content": "bare", "importance": 1}])
+
+    assert len(ids) == 6
+    assert store.pending_changes == 6
+    assert store.count_memories() == 0
+    store.write()
+    assert store.pending_changes == 0
+    assert store.count_memories() == 6
+    assert store.get_memory(ids[2]).content == "note 2"
+    assert store.get_memory(ids[5]).tags == []
+
+
+def test_add_memories_bulk_invalid(tmp_path: Path) -> None:
+    """A malformed memory rejects the whole batch."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    with pytest.raises(ValueError, match="Invalid memory"):
+        store.add_memories_bulk([{"content": "missing importance"}])
+    with pytest.raises(ValueError, match="Unknown sensitivity"):
+        store.add_memories_bulk([{"content": "ok", "importance": 1}, {"content": "x", "importance": 1, "sensitivity": "top"}])
+    assert store.pending_changes == 0
+
+
+def test_auto_commit(tmp_path: Path) -> None:
+    """Changes are committed once the threshold is reached, shared by the stores of an index."""
+    service = MemoryService(tmp_path, auto_commit=3)
+    name = uuid.uuid4().hex
+    first, second = service.get_store(name), service.get_store(name)
+
+    first.add_memory("one", 1, [])
+    second.add_memory("two", 1, [])
+    assert first.count_memories() == 0
+    assert second.pending_changes == 2
+
+    first.add_memory("three", 1, [])
+    assert first.pending_changes == 0
+    assert first.count_memories() == 3
@@ -0,0 +1,45 @@
+"""Tests for clustering memories by their terms."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories about two topics."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    store.add_memory("User prefers dark mode in the editor", 40, ["ui"])
+    store.add_memory("Editor font should be large, dark theme preferred", 50, ["ui"])
+    store.add_memory("The editor uses dark colors at night", 30, ["ui"], sensitivity="secret")
+    store.add_memory("Deploy the backend service with docker compose", 60, ["ops"])
+    store.add_memory("Backend deploy needs the docker registry token", 70, ["ops"])
+    store.add_memory("!!", 10, [])
+    store.write()
+    return store
+
+
+def test_cluster_topics(store: MemoryStore) -> None:
+    """Memories sharing vocabulary end up together, labelled by their heaviest terms."""
+    clusters = store.cluster(k=2)
+    assert [c.size for c in clusters] == [3, 2]
+    assert "editor" in clusters[0].terms
+    assert "docker" in clusters[1].terms
+    assert clusters[0].label == ", ".join(clusters[0].terms[:3])
+    assert {m.content for m in clusters[1].representatives} == {
+        "Deploy the backend service with docker compose",
+        "Backend deploy needs the docker registry token",
+    }
+    assert all(m.access_count == 0 for m in store.list_memories())
+
+
+def test_cluster_options(store: MemoryStore) -> None:
+    """Tags, sensitivity, k and the number of representatives restrict the report."""
+    assert [c.size for c in store.cluster(tag="ops", k=4)] == [1, 1]
+    assert sum(c.size for c in store.cluster(k=1, max_sensitivity="internal")) == 4
+    assert all(len(c.representatives) <= 1 for c in store.cluster(k=2, representatives=1))
+    assert store.cluster(tag="missing") == []
+    with pytest.raises(ValueError):
+        store.cluster(max_sensitivity="top-secret")
@@ -0,0 +1,78 @@
+"""Tests for clustering near-duplicate memories and replacing clusters with summaries."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_core.utils import ok
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding near-duplicate and unrelated memories."""
+    store = MemoryService(tmp_path, audit_log=True).get_store(uuid.uuid4().hex)
+    store.add_memory("User prefers dark mode in the editor", 40, ["ui"])
+    store.add_memory("The user prefers dark mode in the editor.", 60, ["preferences"], sensitivity="internal")
+    store.add_memory("User prefers dark mode in the editor and terminal", 50, ["ui"])
+    store.add_memory("Standup is at ten every morning", 30, ["team"])
+    store.add_memory("User prefers dark mode in the editor", 40, ["ui"], namespace="bob")
+    store.write()
+    return store
+
+
+def test_consolidate(store: MemoryStore) -> None:
+    """Near-duplicates of a namespace are clustered, oldest first, and unrelated memories left out."""
+    clusters = store.consolidate(namespace="default")
+    assert len(clusters) == 1
+    assert [m.importance for m in clusters[0]] == [40, 60, 50]
+    assert all(m.namespace == "default" for m in clusters[0])
+
+    assert len(store.consolidate()) == 1
+    assert store.consolidate(namespace="default", min_size=4) == []
+    assert store.consolidate(namespace="default", threshold=1.0) == []
+    assert all(m.access_count == 0 for m in store.list_memories())
+    with pytest.raises(ValueError, match="between 0 and 1"):
+        store.consolidate(threshold=1.5)
+
+
+def test_replace_cluster(store: MemoryStore) -> None:
+    """A cluster is replaced by its summary, which keeps the namespace and the highest sensitivity."""
+    cluster = store.consolidate(namespace="default")[0]
+    mem_id = store.replace_cluster(
+        [m.uuid for m in cluster],
+        {"content": "User prefers dark mode in the editor and terminal", "importance": 60, "tags": ["ui"]},
+        write=True,
+    )
+
+    assert store.count_memories(namespace="default") == 2
+    summary = ok(store.get_memory(mem_id))
+    assert summary.namespace == "default"
+    assert summary.sensitivity == "internal"
+    assert {entry.action for entry in store.audit_log(limit=4)} == {"add", "delete"}
+    assert store.consolidate(namespace="default") == []
+
+
+def test_replace_cluster_invalid(store: MemoryStore) -> None:
+    """Nothing is changed if a memory is missing or the summary malformed."""
+    uuids = [m.uuid for m in store.consolidate(namespace="default")[0]]
+    with pytest.raises(ValueError, match="not found"):
+        store.replace_cluster([*uuids, "missing"], {"content": "Summary", "importance": 50})
+    with pytest.raises(ValueError, match="Invalid memory"):
+        store.replace_cluster(uuids, {"content": "Summary"})
+    with pytest.raises(ValueError, match="No memory"):
+        store.replace_cluster([], {"content": "Summary", "importance": 50})
+    assert store.pending_changes == 0
+
+
+def test_add_memory_related(store: MemoryStore) -> None:
+    """Adding a memory with `related` returns the most similar committed memories of its namespace."""
+    mem_id, related = store.add_memory("User prefers dark mode", 50, ["ui"], related=2)
+    assert len(related) == 2
+    assert mem_id not in related
+    default = {m.uuid for m in store.list_memories(namespace="default")}
+    assert set(related) <= default
+
+    _, unrelated = store.add_memory("Deploys go through Argo", 50, ["ops"], related=3)
+    assert unrelated == []
+    assert isinstance(store.add_memory("No suggestions asked", 10, []), str)
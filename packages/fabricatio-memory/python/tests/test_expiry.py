@@ -0,0 +1,76 @@
+"""Tests for memory expiry and the retention policy of memory services."""
+
+import time
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture(params=[False, True], ids=["index", "sidecar"])
+def service(request: pytest.FixtureRequest, tmp_path: Path) -> MemoryService:
+    """Fixture to create a service capping namespaces at two memories."""
+    return MemoryService(tmp_path, metadata_sidecar=request.param, max_memories_per_namespace=2)
+
+
+@pytest.fixture
+def store(service: MemoryService) -> MemoryStore:
+    """Fixture to create a store holding one expired and two live memories."""
+    store = service.get_store(uuid.uuid4().hex)
+    now = int(time.time())
+    store.add_memory("Expired build status", 50, ["ci"], expires_at=now - 10)
+    store.add_memory("Live build status", 50, ["ci"], expires_at=now + 3600)
+    store.add_memory("Permanent preference", 50, ["ui"])
+    store.write()
+    return store
+
+
+def test_expired_memories_are_hidden(store: MemoryStore) -> None:
+    """Expired memories are left out of reads before being swept."""
+    assert [m.content for m in store.search_memories("build")] == ["Live build status"]
+    assert {m.content for m in store.list_memories()} == {"Live build status", "Permanent preference"}
+    assert len(store.get_recent_memories(1)) == 2
+    assert store.count_memories() == 2
+    assert store.stats().total_memories == 2
+
+    live = store.search_by_tags(["ci"])[0]
+    assert live.content == "Live build status"
+    assert store.get_memory(live.uuid) is not None
+
+
+def test_expire_sweeps_expired(store: MemoryStore) -> None:
+    """The sweep deletes expired memories from the index."""
+    assert store.expire(write=True) == 1
+    assert store.expire(write=True) == 0
+    assert store.count_memories() == 2
+
+
+def test_update_expiry(store: MemoryStore) -> None:
+    """Updating the expiry hides a memory from then on."""
+    permanent = store.search_memories("preference")[0]
+    assert permanent.expires_at is None
+    store.update_memory(permanent.uuid, expires_at=int(time.time()) - 1, write=True)
+    assert store.get_memory(permanent.uuid) is None
+
+
+def test_eviction_per_namespace(service: MemoryService) -> None:
+    """Namespaces over the cap lose their least relevant memories."""
+    store = service.get_store(uuid.uuid4().hex)
+    for importance in (10, 90, 50):
+        store.add_memory(f"alpha {importance}", importance, [], namespace="alpha")
+    store.add_memory("beta", 10, [], namespace="beta")
+    store.write()
+    store.search_memories("alpha", write=True)
+
+    assert store.expire(write=True) == 1
+    assert sorted(m.importance for m in store.list_memories(namespace="alpha")) == [50, 90]
+    assert store.count_memories("beta") == 1
+
+
+def test_default_ttl(tmp_path: Path) -> None:
+    """Memories added without an expiry get the default lifetime of the service."""
+    store = MemoryService(tmp_path, default_ttl=60).get_store("ttl")
+    store.add_memory("Short lived", 10, [], write=True)
+    store.add_memories_bulk([{"content": "Bulk short lived", "importance": 10}], write=True)
+    assert all(m.expires_at is not None and m.expires_at - m.timestamp == 60 for m in store.list_memories())
@@ -0,0 +1,85 @@
+"""Tests for the importance, timestamp and tag clauses of structured memory queries."""
+
+import json
+import time
+import uuid
+from pathlib import Path
+from typing import List
+
+import pytest
+from fabricatio_memory.rust import MemoryQuery, MemoryService, MemoryStore
+
+DAY = 86400
+NOW = int(time.time())
+
+
+@pytest.fixture(params=[False, True], ids=["index", "sidecar"])
+def store(request: pytest.FixtureRequest, tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories of distinct ages, importance and tags."""
+    vault = tmp_path / "vault"
+    vault.mkdir()
+    for age, importance, tags in [
+        (0, 0, ["infra", "rust"]),
+        (2, 100, ["infra"]),
+        (10, 50, ["rust", "docs"]),
+        (40, 75, ["docs"]),
+        (40, 30, []),
+    ]:
+        (vault / f"age{age}-{importance}.md").write_text(
+            f"---\nuuid: {uuid.uuid4()}\nimportance: {importance}\ntags: {json.dumps(tags)}\n"
+            f"timestamp: {NOW - age * DAY}\n---\n\nmemory aged {age} days with importance {importance}\n",
+            encoding="utf-8",
+        )
+    store = MemoryService(tmp_path / "stores", metadata_sidecar=request.param).get_store(uuid.uuid4().hex)
+    store.import_markdown(vault, write=True)
+    return store
+
+
+def importances(store: MemoryStore, query: MemoryQuery) -> List[int]:
+    """Runs a query and returns the sorted importance scores of the matches."""
+    return sorted(m.importance for m in store.query(query))
+
+
+def test_empty_query(store: MemoryStore) -> None:
+    """A query without clauses matches every memory."""
+    assert importances(store, MemoryQuery()) == [0, 30, 50, 75, 100]
+
+
+def test_importance_bounds(store: MemoryStore) -> None:
+    """Importance bounds are inclusive, down to 0 and up to 100."""
+    assert importances(store, MemoryQuery().importance_gte(0)) == [0, 30, 50, 75, 100]
+    assert importances(store, MemoryQuery().importance_lte(100)) == [0, 30, 50, 75, 100]
+    assert importances(store, MemoryQuery().importance_gte(100)) == [100]
+    assert importances(store, MemoryQuery().importance_lte(0)) == [0]
+    assert importances(store, MemoryQuery().importance_gte(50).importance_lte(75)) == [50, 75]
+    assert importances(store, MemoryQuery().importance_gte(101)) == []
+    assert importances(store, MemoryQuery().importance_gte(80).importance_lte(20)) == []
+
+
+def test_timestamp_bounds(store: MemoryStore) -> None:
+    """`after` includes its bound and `before` excludes it."""
+    assert importances(store, MemoryQuery().after(days=5)) == [0, 100]
+    assert importances(store, MemoryQuery().before(days=5)) == [30, 50, 75]
+    assert importances(store, MemoryQuery().after(timestamp=NOW - 10 * DAY)) == [0, 50, 100]
+    assert importances(store, MemoryQuery().before(timestamp=NOW - 10 * DAY)) == [30, 75]
+    assert importances(store, MemoryQuery().after(days=30).before(days=1)) == [50, 100]
+    with pytest.raises(ValueError):
+        MemoryQuery().after()
+    with pytest.raises(ValueError):
+        MemoryQuery().before(days=1, timestamp=NOW)
+
+
+def test_tag_clauses(store: MemoryStore) -> None:
+    """Tags can be required in any or all combinations and excluded, each call adding to the previous ones."""
+    assert importances(store, MemoryQuery().tag_any(["infra", "docs"])) == [0, 50, 75, 100]
+    assert importances(store, MemoryQuery().tag_any(["infra"]).tag_any(["docs"])) == [0, 50, 75, 100]
+    assert importances(store, MemoryQuery().tag_all(["infra", "rust"])) == [0]
+    assert importances(store, MemoryQuery().tag_none(["docs"])) == [0, 30, 100]
+    assert importances(store, MemoryQuery().tag_any(["rust"]).tag_none(["docs"])) == [0]
+    assert importances(store, MemoryQuery().tag_all(["missing"])) == []
+
+
+def test_combined_clauses(store: MemoryStore) -> None:
+    """Clauses of different kinds must all hold."""
+    query = MemoryQuery().tag_any(["infra", "rust"]).importance_gte(50).after(days=30)
+    assert importances(store, query) == [50, 100]
@@ -1,6 +1,7 @@
 """Tests for the memory."""
 
 import uuid
+from pathlib import Path
 
 import pytest
 
@@ -308,3 +309,66 @@ def test_get_memory_stats(store: MemoryStore, memories: list, expected_avg_impor
     assert abs(stats.avg_importance - expected_avg) < 1e-6  # exact match for integers
     assert stats.avg_access_count == 0
     assert stats.avg_age_days >= 0  # Age depends on when memories were created
+
+
+def test_sensitivity_filter(store: MemoryStore) -> None:
+    """Test that searches exclude memories above the maximum sensitivity."""
+    store.add_memory("deploy notes public", 50, ["deploy"])
+    store.add_memory("deploy notes internal", 50, ["deploy"], sensitivity="internal")
+    store.add_memory("deploy notes secret", 50, ["deploy"], sensitivity="secret")
+    store.write()
+
+    assert len(store.search_memories("deploy")) == 3
+    assert {m.sensitivity for m in store.search_memories("deploy", max_sensitivity="internal")} == {
+        "public",
+        "internal",
+    }
+    assert [m.sensitivity for m in store.search_by_tags(["deploy"], max_sensitivity="public")] == ["public"]
+
+    with pytest.raises(ValueError, match="sensitivity"):
+        store.search_memories("deploy", max_sensitivity="top-secret")
+
+
+def test_redact_secret_content(store: MemoryStore) -> None:
+    """Test that the redaction callback only rewrites secret memories and is not persisted."""
+    public_id = store.add_memory("api endpoint is /v1", 50, ["api"])
+    secret_id = store.add_memory("api key is hunter2", 50, ["api"], sensitivity="secret")
+    store.write()
+
+    redacted = store.search_memories("api", write=True, redact=lambda m: f"[redacted {m.sensitivity}]")
+    results = {m.uuid: m for m in redacted}
+    assert results[public_id].content == "api endpoint is /v1"
+    assert results[secret_id].content == "[redacted secret]"
+
+    stored = store.get_memory(secret_id)
+    assert stored is not None
+    assert stored.content == "api key is hunter2"
+
+    assert store.update_memory(secret_id, sensitivity="public", write=True)
+    assert store.get_memory(secret_id).sensitivity == "public"
+
+
+def test_markdown_round_trip(store: MemoryStore, tmp_path: Path) -> None:
+    """Test exporting memories as Markdown notes and importing the edited notes back."""
+    rust_id = store.add_memory("Ownership rules\nEach value has one owner.", 60, ["rust", "lang"])
+    store.add_memory("untagged note", 10, [], sensitivity="internal")
+    store.write()
+
+    assert store.export_markdown(tmp_path) == 2
+    note = tmp_path / "rust" / "Ownership rules.md"
+    assert note.read_text().startswith(f"---\nuuid: {rust_id}\nimportance: 60\n")
+    assert (tmp_path / "untagged note.md").exists()
+
+    note.write_text(note.read_text().replace("importance: 60", "importance: 80"))
+    (tmp_path / "fresh.md").write_text("Fresh idea")
+    assert store.import_markdown(tmp_path, write=True) == 3
+    assert store.count_memories() == 3
+
+    memory = store.get_memory(rust_id)
+    assert memory is not None
+    assert memory.importance == 80
+    assert memory.tags == ["rust", "lang"]
+    assert memory.content == "Ownership rules\nEach value has one owner."
+
+    with pytest.raises(ValueError, match="grouping"):
+        store.export_markdown(tmp_path, group_by="date")
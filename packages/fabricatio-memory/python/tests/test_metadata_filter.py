@@ -0,0 +1,59 @@
+"""Tests for memory metadata and search filter expressions."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_core.utils import ok
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories of two projects."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    store.add_memory("Deploy atlas with argo", 80, ["ops"], metadata={"project": "atlas", "verified": True, "cost": 1.5})
+    store.add_memory("Deploy zephyr by hand", 30, ["ops"], metadata={"project": "zephyr", "cost": 3})
+    store.add_memory("Deploy notes without metadata", 60, ["draft"])
+    store.write()
+    return store
+
+
+def test_metadata_round_trip(store: MemoryStore) -> None:
+    """Metadata keeps the type of its values."""
+    memory = store.search_memories("argo")[0]
+    assert memory.metadata == {"project": "atlas", "verified": True, "cost": 1.5}
+    assert memory.to_dict()["metadata"]["verified"] is True
+
+    assert store.update_memory(memory.uuid, metadata={"project": "atlas-2"}, write=True)
+    assert ok(store.get_memory(memory.uuid)).metadata == {"project": "atlas-2"}
+
+
+@pytest.mark.parametrize(
+    ("expr", "expected"),
+    [
+        ('metadata.project == "atlas"', {"Deploy atlas with argo"}),
+        ("metadata.project != 'atlas'", {"Deploy zephyr by hand"}),
+        ("metadata.cost >= 1.5 AND importance < 50", {"Deploy zephyr by hand"}),
+        ("metadata.verified == true OR tags == \"draft\"", {"Deploy atlas with argo", "Deploy notes without metadata"}),
+        ('NOT metadata.project == "atlas"', {"Deploy zephyr by hand", "Deploy notes without metadata"}),
+        ('(importance > 50) and not tags == "draft"', {"Deploy atlas with argo"}),
+        ("metadata.missing == 1", set()),
+    ],
+)
+def test_filter_expressions(store: MemoryStore, expr: str, expected: set[str]) -> None:
+    """Searches only return the memories satisfying the filter."""
+    assert {m.content for m in store.search_memories("deploy", filter=expr)} == expected
+
+
+def test_filter_does_not_starve_top_k(store: MemoryStore) -> None:
+    """The filter applies before results are truncated to `top_k`."""
+    hits = store.search_memories("deploy", top_k=1, filter='metadata.project == "zephyr"')
+    assert [m.content for m in hits] == ["Deploy zephyr by hand"]
+
+
+@pytest.mark.parametrize("expr", ["importance >", "colour == 'red'", 'tags > "a"', "(importance > 1", "importance = 1"])
+def test_invalid_filters(store: MemoryStore, expr: str) -> None:
+    """Malformed expressions are rejected."""
+    with pytest.raises(ValueError, match="Invalid filter"):
+        store.search_memories("deploy", filter=expr)
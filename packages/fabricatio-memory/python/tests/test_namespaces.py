@@ -0,0 +1,71 @@
+"""Tests for scoping memory store operations by namespace."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryQuery, MemoryService, MemoryStore
+
+
+@pytest.fixture(params=[False, True], ids=["index", "sidecar"])
+def store(request: pytest.FixtureRequest, tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store shared by two threads and the default namespace."""
+    store = MemoryService(tmp_path, metadata_sidecar=request.param).get_store(uuid.uuid4().hex)
+    store.add_memory("Alpha prefers tea", 80, ["drink"], namespace="alpha")
+    store.add_memory("Alpha lives in Oslo", 20, ["profile"], namespace="alpha")
+    store.add_memory("Beta prefers coffee", 60, ["drink"], namespace="beta")
+    store.add_memory("Shared note about drinks", 40, ["drink"])
+    store.write()
+    return store
+
+
+def test_default_namespace(store: MemoryStore) -> None:
+    """Memories added without a namespace land in the default one."""
+    assert {m.content for m in store.list_memories(namespace="default")} == {"Shared note about drinks"}
+    assert store.count_memories() == 4
+    assert store.count_memories("alpha") == 2
+
+
+def test_scoped_search(store: MemoryStore) -> None:
+    """Searches only see the memories of the requested namespace."""
+    assert [m.content for m in store.search_memories("prefers", namespace="beta")] == ["Beta prefers coffee"]
+    assert {m.namespace for m in store.search_by_tags(["drink"], namespace="alpha")} == {"alpha"}
+    assert len(store.search_by_tags(["drink"])) == 3
+    assert [m.content for m in store.get_memories_by_importance(50, namespace="alpha")] == ["Alpha prefers tea"]
+    assert len(store.get_recent_memories(1, namespace="beta")) == 1
+    assert len(store.query(MemoryQuery().tag_any(["drink"]).namespace("default"))) == 1
+
+
+def test_scoped_listing_and_stats(store: MemoryStore) -> None:
+    """Listings and statistics are computed within a namespace."""
+    assert [m.importance for m in store.list_memories("importance", namespace="alpha")] == [80, 20]
+    assert [m.namespace for m in store.get_frequently_accessed(namespace="beta")] == ["beta"]
+    assert store.stats("alpha").total_memories == 2
+    assert store.stats("alpha").avg_importance == 50.0
+
+
+def test_scoped_cleanup(store: MemoryStore) -> None:
+    """Cleanups leave the other namespaces alone."""
+    assert store.cleanup_old_memories(-1, namespace="alpha", write=True) == 2
+    assert store.count_memories("alpha") == 0
+    assert store.count_memories() == 2
+
+
+def test_bulk_and_markdown(store: MemoryStore, tmp_path: Path) -> None:
+    """Bulk drafts default to the given namespace, and notes keep it."""
+    store.add_memories_bulk(
+        [{"content": "Gamma one", "importance": 10}, {"content": "Elsewhere", "importance": 10, "namespace": "beta"}],
+        write=True,
+        namespace="gamma",
+    )
+    assert store.count_memories("gamma") == 1
+    assert store.count_memories("beta") == 2
+
+    assert store.export_markdown(tmp_path / "notes", namespace="beta") == 2
+    assert all("namespace: beta" in note.read_text() for note in (tmp_path / "notes").rglob("*.md"))
+
+
+def test_empty_namespace(store: MemoryStore) -> None:
+    """Empty namespaces are rejected."""
+    with pytest.raises(ValueError, match="Namespace"):
+        store.add_memory("Nowhere", 10, [], namespace="")
@@ -0,0 +1,50 @@
+"""Tests for the range queries over importance and timestamp."""
+
+import time
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+DAY = 86400
+
+
+@pytest.fixture(params=[False, True], ids=["index", "sidecar"])
+def store(request: pytest.FixtureRequest, tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories of distinct ages and importance."""
+    vault = tmp_path / "vault"
+    vault.mkdir()
+    now = int(time.time())
+    for age, importance in [(0, 20), (2, 80), (10, 50), (40, 10), (40, 90)]:
+        (vault / f"age{age}-{importance}.md").write_text(
+            f"---\nuuid: {uuid.uuid4()}\nimportance: {importance}\ntimestamp: {now - age * DAY}\n---\n\n"
+            f"memory aged {age} days with importance {importance}\n",
+            encoding="utf-8",
+        )
+    store = MemoryService(tmp_path / "stores", metadata_sidecar=request.param).get_store(uuid.uuid4().hex)
+    store.import_markdown(vault, write=True)
+    return store
+
+
+def test_importance_ordered(store: MemoryStore) -> None:
+    """Memories above the threshold come most important first."""
+    assert [m.importance for m in store.get_memories_by_importance(50)] == [90, 80, 50]
+    assert [m.importance for m in store.get_memories_by_importance(0, top_k=2)] == [90, 80]
+
+
+def test_recent_ordered(store: MemoryStore) -> None:
+    """Memories within the window come newest first."""
+    assert [m.importance for m in store.get_recent_memories(30)] == [20, 80, 50]
+    assert [m.importance for m in store.get_recent_memories(30, top_k=1)] == [20]
+
+
+def test_cleanup_old_memories(store: MemoryStore) -> None:
+    """Only memories older than the window, and at most as important as the bound, are deleted."""
+    assert store.cleanup_old_memories(30, max_importance=50, write=True) == 1
+    assert sorted(m.importance for m in store.list_memories()) == [20, 50, 80, 90]
+
+    assert store.cleanup_old_memories(5, write=True) == 2
+    assert sorted(m.importance for m in store.list_memories()) == [20, 80]
+    assert store.count_memories() == 2
+    assert store.cleanup_old_memories(5) == 0
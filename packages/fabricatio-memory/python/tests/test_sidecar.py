@@ -0,0 +1,66 @@
+"""Tests for ordered listings with and without the metadata sidecar."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture(params=[False, True], ids=["index", "sidecar"])
+def memory_service(request: pytest.FixtureRequest, tmp_path: Path) -> MemoryService:
+    """Fixture to create a service with or without the metadata sidecar."""
+    return MemoryService(tmp_path, metadata_sidecar=request.param)
+
+
+@pytest.fixture
+def store(memory_service: MemoryService) -> MemoryStore:
+    """Fixture to create a store holding memories of distinct importance."""
+    store = memory_service.get_store(uuid.uuid4().hex)
+    for importance, tags in [(10, ["a"]), (40, ["a", "b"]), (70, ["b"]), (90, ["a"])]:
+        store.add_memory(f"memory {importance}", importance, tags)
+    store.write()
+    return store
+
+
+def test_list_by_importance(store: MemoryStore) -> None:
+    """Memories are listed in order and paged by limit and offset."""
+    assert [m.importance for m in store.list_memories("importance")] == [90, 70, 40, 10]
+    assert [m.importance for m in store.list_memories("importance", descending=False, limit=2)] == [10, 40]
+    assert [m.importance for m in store.list_memories("importance", limit=2, offset=1)] == [70, 40]
+    assert store.list_memories(limit=0) == []
+
+
+def test_list_by_tag(store: MemoryStore) -> None:
+    """Only memories carrying the tag are listed."""
+    assert [m.importance for m in store.list_memories("importance", tag="a")] == [90, 40, 10]
+    assert store.list_memories(tag="missing") == []
+
+
+def test_list_follows_changes(store: MemoryStore) -> None:
+    """Updates and deletions are reflected once written, without touching access statistics."""
+    top, second = store.list_memories("importance", limit=2)
+    store.update_memory(second.uuid, importance=100)
+    store.delete_memory(top.uuid, write=True)
+    listed = store.list_memories("importance")
+    assert [m.importance for m in listed] == [100, 40, 10]
+    assert all(m.access_count == 0 for m in listed)
+
+
+def test_list_unknown_key(store: MemoryStore) -> None:
+    """Unknown sort keys are rejected."""
+    with pytest.raises(ValueError, match="Unknown sort key"):
+        store.list_memories("content")
+
+
+def test_sidecar_rebuilt_for_existing_index(tmp_path: Path) -> None:
+    """Enabling the sidecar on an existing store fills it from the index."""
+    plain = MemoryService(tmp_path).get_store("existing")
+    for importance in (20, 60):
+        plain.add_memory(f"memory {importance}", importance, [])
+    plain.write()
+    del plain
+
+    store = MemoryService(tmp_path, metadata_sidecar=True).get_store("existing")
+    assert (tmp_path / "existing" / "metadata.sqlite3").exists()
+    assert [m.importance for m in store.list_memories("importance")] == [60, 20]
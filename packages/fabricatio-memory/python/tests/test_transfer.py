@@ -0,0 +1,89 @@
+"""Tests for exporting, importing, snapshotting and restoring memories."""
+
+import json
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_core.utils import ok
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def service(tmp_path: Path) -> MemoryService:
+    """Fixture to create a service below a temporary directory."""
+    return MemoryService(tmp_path / "stores")
+
+
+@pytest.fixture
+def store(service: MemoryService) -> MemoryStore:
+    """Fixture to create a store holding a few memories."""
+    store = service.get_store(uuid.uuid4().hex)
+    store.add_memory("User prefers dark mode", 70, ["ui"], embedding=[1.0, 0.0])
+    store.add_memory("User lives in Berlin", 40, ["profile"], namespace="alice")
+    store.write()
+    return store
+
+
+def test_jsonl_round_trip(service: MemoryService, store: MemoryStore, tmp_path: Path) -> None:
+    """Exported memories are imported with all their attributes."""
+    path = tmp_path / "out" / "memories.jsonl"
+    assert store.export_jsonl(path) == 2
+    assert len(path.read_text().splitlines()) == 2
+
+    other = service.get_store(uuid.uuid4().hex)
+    assert other.import_jsonl(path, write=True) == 2
+    by_content = {m.content: m for m in other.list_memories()}
+    assert by_content["User prefers dark mode"].embedding == [1.0, 0.0]
+    assert by_content["User lives in Berlin"].namespace == "alice"
+    assert {m.uuid for m in other.list_memories()} == {m.uuid for m in store.list_memories()}
+
+    assert store.export_jsonl(path, namespace="alice") == 1
+
+
+def test_import_dedup(store: MemoryStore, tmp_path: Path) -> None:
+    """Memories whose content is already stored in their namespace are skipped."""
+    path = tmp_path / "dataset.jsonl"
+    lines = [
+        {"uuid": "a", "content": "User prefers dark mode", "namespace": "default"},
+        {"uuid": "b", "content": "User prefers dark mode", "namespace": "bob"},
+        {"uuid": "c", "content": "New fact", "namespace": "default"},
+        {"uuid": "d", "content": "New fact", "namespace": "default"},
+    ]
+    base = {"timestamp": 0, "importance": 10, "tags": [], "access_count": 0, "last_accessed": 0, "sensitivity": "public"}
+    path.write_text("\n".join(json.dumps(base | line) for line in lines) + "\n\n")
+
+    assert store.import_jsonl(path, write=True) == 2
+    assert store.count_memories() == 4
+    assert store.import_jsonl(path, write=True) == 2
+    assert store.count_memories() == 4
+    assert store.import_jsonl(path, write=True, dedup=False) == 4
+    assert store.count_memories() == 6
+
+
+def test_import_invalid(store: MemoryStore, tmp_path: Path) -> None:
+    """Invalid lines are reported with their number, and nothing is imported."""
+    path = tmp_path / "broken.jsonl"
+    path.write_text('{"content": "missing fields"}\n')
+    with pytest.raises(ValueError, match="line 1"):
+        store.import_jsonl(path)
+    assert store.pending_changes == 0
+
+
+def test_snapshot_and_restore(store: MemoryStore, tmp_path: Path) -> None:
+    """Restoring a snapshot brings back the memories it held and drops the others."""
+    snapshot = tmp_path / "snapshot"
+    assert store.snapshot(snapshot) == 2
+    with pytest.raises(ValueError, match="already exists"):
+        store.snapshot(snapshot)
+
+    kept = store.list_memories(namespace="alice")[0]
+    store.delete_memory(kept.uuid)
+    store.add_memory("Added after the snapshot", 10, [], write=True)
+
+    assert store.restore(snapshot, write=True) == 2
+    assert {m.content for m in store.list_memories()} == {"User prefers dark mode", "User lives in Berlin"}
+    assert ok(store.get_memory(kept.uuid)).namespace == "alice"
+
+    with pytest.raises(ValueError, match="No snapshot"):
+        store.restore(tmp_path / "nowhere")
@@ -0,0 +1,73 @@
+"""Tests for vector and hybrid search over memory embeddings."""
+
+import uuid
+from pathlib import Path
+
+import pytest
+from fabricatio_core.utils import ok
+from fabricatio_memory.rust import MemoryService, MemoryStore
+
+
+@pytest.fixture
+def store(tmp_path: Path) -> MemoryStore:
+    """Fixture to create a store holding memories with hand-made embeddings."""
+    store = MemoryService(tmp_path).get_store(uuid.uuid4().hex)
+    store.add_memory("User prefers dark mode", 50, ["ui"], embedding=[1.0, 0.0, 0.0])
+    store.add_memory("User lives in Berlin", 50, ["profile"], embedding=[0.0, 1.0, 0.0])
+    store.add_memory("Dark chocolate is the favourite snack", 50, ["food"], embedding=[0.0, 0.5, 1.0])
+    store.add_memory("Keyword only note about dark themes", 50, ["ui"])
+    store.write()
+    return store
+
+
+def test_embedding_round_trip(store: MemoryStore) -> None:
+    """Embeddings are stored with the memory, and memories without one have none."""
+    by_content = {m.content: m for m in store.list_memories()}
+    assert by_content["User prefers dark mode"].embedding == [1.0, 0.0, 0.0]
+    assert by_content["Keyword only note about dark themes"].embedding is None
+
+
+def test_vector_search(store: MemoryStore) -> None:
+    """Memories are ranked by cosine similarity, ignoring the query string."""
+    hits = store.search_memories("unrelated", mode="vector", query_embedding=[0.9, 0.1, 0.0])
+    assert [m.content for m in hits] == [
+        "User prefers dark mode",
+        "User lives in Berlin",
+        "Dark chocolate is the favourite snack",
+    ]
+    assert len(store.search_memories("", top_k=1, mode="vector", query_embedding=[0.0, 0.0, 1.0])) == 1
+
+
+def test_hybrid_search(store: MemoryStore) -> None:
+    """Memories found by both rankings come first."""
+    hits = store.search_memories("dark mode", mode="hybrid", query_embedding=[1.0, 0.0, 0.0])
+    assert hits[0].content == "User prefers dark mode"
+    assert {m.content for m in hits} == {
+        "User prefers dark mode",
+        "User lives in Berlin",
+        "Dark chocolate is the favourite snack",
+        "Keyword only note about dark themes",
+    }
+
+
+def test_invalid_mode(store: MemoryStore) -> None:
+    """Unknown modes and missing query embeddings are rejected."""
+    with pytest.raises(ValueError, match="Unknown search mode"):
+        store.search_memories("dark", mode="semantic")
+    with pytest.raises(ValueError, match="query_embedding"):
+        store.search_memories("dark", mode="vector")
+
+
+def test_content_update_drops_embedding(store: MemoryStore) -> None:
+    """Changing the content drops a stale embedding unless a new one is given."""
+    ids = {m.content: m.uuid for m in store.list_memories()}
+    store.update_memory(ids["User lives in Berlin"], content="User lives in Paris", write=True)
+    assert ok(store.get_memory(ids["User lives in Berlin"])).embedding is None
+
+    store.update_memory(ids["User prefers dark mode"], importance=90, write=True)
+    assert ok(store.get_memory(ids["User prefers dark mode"])).embedding == [1.0, 0.0, 0.0]
+
+    store.update_memory(ids["User prefers dark mode"], content="User prefers light mode", embedding=[0.0, 0.0, 1.0])
+    store.write()
+    assert ok(store.get_memory(ids["User prefers dark mode"])).embedding == [0.0, 0.0, 1.0]
+
@@ -0,0 +1,248 @@
+//! Append-only audit log of the mutations of a memory store.
+//!
+//! Every addition, update and deletion is appended to a JSON Lines file next to the index
+//! before it is staged in the writer, together with the memory as it was before and after the
+//! change, so unwanted changes, such as deletions made by a misbehaving agent, can be
+//! investigated and undone. Access statistics updated by reads are not recorded.
+//!
+//! Entries are written whole with a single call, so a crash leaves at most one truncated
+//! line. Reading skips it with a warning, and opening the log again terminates it so the next
+//! entry starts a line of its own.
+
+use crate::memory::Memory;
+use chrono::Utc;
+use error_mapping::AsPyErr;
+use fabricatio_logger::warn;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use pythonize::pythonize;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// The kind of a recorded mutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AuditAction {
+    Add,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    /// Name of the action as recorded in the log.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+
+    /// Infers the action turning the memory `before` into the memory `after`.
+    fn of(before: Option<&Memory>, after: Option<&Memory>) -> Option<Self> {
+        match (before, after) {
+            (None, Some(_)) => Some(Self::Add),
+            (Some(_), Some(_)) => Some(Self::Update),
+            (Some(_), None) => Some(Self::Delete),
+            (None, None) => None,
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "add" => Ok(Self::Add),
+            "update" => Ok(Self::Update),
+            "delete" => Ok(Self::Delete),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown audit action `{s}`, expected one of `add`, `update`, `delete`"
+            ))),
+        }
+    }
+}
+
+/// A mutation of a memory recorded in the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+pub struct AuditEntry {
+    /// Sequence number of the entry, increasing from 1 in the order of the log
+    pub seq: u64,
+    /// Unix timestamp when the mutation was recorded
+    pub timestamp: i64,
+    /// Unique identifier of the mutated memory
+    pub uuid: String,
+    /// Kind of mutation: `add`, `update` or `delete`
+    pub action: String,
+    /// Actor the store was opened for, if any
+    pub actor: Option<String>,
+    /// The memory before the mutation, None for an addition
+    pub before: Option<Memory>,
+    /// The memory after the mutation, None for a deletion
+    pub after: Option<Memory>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl AuditEntry {
+    /// Converts the entry to a Python dictionary.
+    ///
+    /// Returns:
+    ///     A Python dictionary representation of the AuditEntry.
+    pub fn to_dict<'py>(&self, python: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        Ok(pythonize(python, self)?.cast_into::<PyDict>()?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AuditEntry(seq={}, action={}, uuid={}, actor={:?})",
+            self.seq, self.action, self.uuid, self.actor
+        )
+    }
+}
+
+/// The filters of an audit log query, all optional.
+#[derive(Debug, Default)]
+pub(crate) struct AuditFilter<'a> {
+    pub uuid: Option<&'a str>,
+    pub actor: Option<&'a str>,
+    pub action: Option<AuditAction>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl AuditFilter<'_> {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.uuid.is_none_or(|uuid| entry.uuid == uuid)
+            && self
+                .actor
+                .is_none_or(|actor| entry.actor.as_deref() == Some(actor))
+            && self
+                .action
+                .is_none_or(|action| entry.action == action.name())
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
+
+/// The audit log of one index, shared by every store opened on it.
+pub struct AuditLog {
+    path: PathBuf,
+    /// The file appended to and the sequence number of its last entry
+    file: Mutex<(File, u64)>,
+}
+
+impl AuditLog {
+    /// Opens the audit log at the given path, creating it if needed.
+    ///
+    /// A last line left truncated by a crash is terminated, so the next entry is appended on
+    /// a line of its own.
+    pub fn open(path: &Path) -> PyResult<Self> {
+        let log = fs::read(path).unwrap_or_default();
+        let last_seq = String::from_utf8_lossy(&log)
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .map_or(0, |entry| entry.seq);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .into_pyresult()?;
+        if !log.is_empty() && !log.ends_with(b"\n") {
+            file.write_all(b"\n").into_pyresult()?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new((file, last_seq)),
+        })
+    }
+
+    /// Appends one entry per change, given as the memory before and after it, and syncs the
+    /// file so the entries outlive a crash of the process.
+    pub(crate) fn append(
+        &self,
+        actor: Option<&str>,
+        changes: &[(Option<&Memory>, Option<&Memory>)],
+    ) -> PyResult<()> {
+        let mut guard = self.file.lock().into_pyresult()?;
+        let (file, last_seq) = &mut *guard;
+        let timestamp = Utc::now().timestamp();
+        let mut lines = String::new();
+        let mut seq = *last_seq;
+        for (before, after) in changes {
+            let Some(action) = AuditAction::of(*before, *after) else {
+                continue;
+            };
+            seq += 1;
+            let entry = AuditEntry {
+                seq,
+                timestamp,
+                uuid: before
+                    .or(*after)
+                    .map(|m| m.uuid.clone())
+                    .unwrap_or_default(),
+                action: action.name().to_string(),
+                actor: actor.map(str::to_string),
+                before: before.cloned(),
+                after: after.cloned(),
+            };
+            lines.push_str(&serde_json::to_string(&entry).into_pyresult()?);
+            lines.push('\n');
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        file.write_all(lines.as_bytes()).into_pyresult()?;
+        file.sync_data().into_pyresult()?;
+        *last_seq = seq;
+        Ok(())
+    }
+
+    /// Reads every entry of the log, oldest first, skipping the lines that cannot be parsed.
+    fn entries(&self) -> PyResult<Vec<AuditEntry>> {
+        let _guard = self.file.lock().into_pyresult()?;
+        let log = fs::read(&self.path).into_pyresult()?;
+        Ok(String::from_utf8_lossy(&log)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(number, line)| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!(
+                        "Skipping line {} of {}: {e}",
+                        number + 1,
+                        self.path.display()
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Lists the entries matching a filter, newest first.
+    pub(crate) fn query(&self, filter: &AuditFilter, limit: usize) -> PyResult<Vec<AuditEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(limit)
+            .collect())
+    }
+
+    /// Finds the entry with the given sequence number.
+    pub(crate) fn get(&self, seq: u64) -> PyResult<Option<AuditEntry>> {
+        Ok(self.entries()?.into_iter().find(|entry| entry.seq == seq))
+    }
+}
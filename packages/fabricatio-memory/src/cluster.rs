@@ -0,0 +1,255 @@
+//! Clustering of memories by the terms they use, an overview of the topics of a store.
+//!
+//! Each memory becomes the TF-IDF vector of the words of its content and of its tags, scaled
+//! to unit length, and the vectors are grouped by spherical k-means: every memory joins the
+//! centroid it has the highest cosine similarity with. Seeds are picked by farthest-first
+//! traversal from the memory closest to the mean, so the clusters of a given set of memories
+//! are always the same. No embedding is involved, clusters reflect shared vocabulary rather
+//! than meaning.
+
+use crate::memory::Memory;
+use pyo3::pyclass;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Maximum number of assignment rounds of k-means.
+const MAX_ITERATIONS: usize = 20;
+
+/// Minimum number of characters of a word to be a term.
+const MIN_TERM_LEN: usize = 3;
+
+/// Number of terms reported per cluster.
+const NUM_TERMS: usize = 8;
+
+/// Number of terms making the label of a cluster.
+const NUM_LABEL_TERMS: usize = 3;
+
+/// Frequent English words carrying no topic, on top of those shorter than `MIN_TERM_LEN`.
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "all", "also", "and", "any", "are", "been", "before", "being", "but", "can",
+    "could", "did", "does", "for", "from", "had", "has", "have", "her", "his", "how", "into",
+    "its", "more", "most", "not", "now", "only", "other", "our", "out", "over", "she", "should",
+    "some", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this",
+    "those", "very", "was", "were", "what", "when", "where", "which", "while", "who", "will",
+    "with", "would", "you", "your",
+];
+
+/// A group of memories sharing vocabulary, as reported by `MemoryStore.cluster`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+pub struct MemoryCluster {
+    /// The heaviest terms of the cluster joined by commas, such as `editor, dark, mode`
+    pub label: String,
+    /// The terms weighing the most in the centroid of the cluster, heaviest first
+    pub terms: Vec<String>,
+    /// Number of memories in the cluster
+    pub size: usize,
+    /// The memories closest to the centroid of the cluster, closest first
+    pub representatives: Vec<Memory>,
+}
+
+/// A cluster of the memories given to `term_clusters`, by index.
+pub(crate) struct TermCluster {
+    /// Indices of the memories of the cluster, the closest to its centroid first
+    pub(crate) members: Vec<usize>,
+    /// The terms weighing the most in the centroid, heaviest first
+    pub(crate) terms: Vec<String>,
+}
+
+impl TermCluster {
+    /// Builds the report of the cluster, taking its representatives out of `slots`.
+    pub(crate) fn into_report(
+        self,
+        slots: &mut [Option<Memory>],
+        representatives: usize,
+    ) -> MemoryCluster {
+        MemoryCluster {
+            label: self
+                .terms
+                .iter()
+                .take(NUM_LABEL_TERMS)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", "),
+            size: self.members.len(),
+            representatives: self
+                .members
+                .iter()
+                .take(representatives)
+                .filter_map(|&i| slots[i].take())
+                .collect(),
+            terms: self.terms,
+        }
+    }
+}
+
+/// Term indices and weights, sorted by index.
+type SparseVector = Vec<(usize, f64)>;
+
+/// The terms of a memory: the significant words of its content, lowercase, and its tags.
+fn terms_of(memory: &Memory) -> impl Iterator<Item = String> + '_ {
+    memory
+        .content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_LEN)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .chain(memory.tags.iter().map(|tag| tag.to_lowercase()))
+}
+
+fn dot(sparse: &SparseVector, dense: &[f64]) -> f64 {
+    sparse.iter().map(|(i, w)| w * dense[*i]).sum()
+}
+
+/// The normalized sum of vectors, the zero vector if there are none.
+fn centroid<'a>(vectors: impl Iterator<Item = &'a SparseVector>, dims: usize) -> Vec<f64> {
+    let mut sum = vec![0.0; dims];
+    vectors.flatten().for_each(|(i, w)| sum[*i] += w);
+    let norm = sum.iter().map(|w| w * w).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        sum.iter_mut().for_each(|w| *w /= norm);
+    }
+    sum
+}
+
+/// Index of the greatest value, the first one on ties.
+fn argmax(values: impl Iterator<Item = f64>) -> usize {
+    values
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (i, value)| {
+            if value > best.1 { (i, value) } else { best }
+        })
+        .0
+}
+
+/// Index of the centroid most similar to a vector.
+fn nearest(vector: &SparseVector, centroids: &[Vec<f64>]) -> usize {
+    argmax(centroids.iter().map(|centroid| dot(vector, centroid)))
+}
+
+/// Groups the memories into at most `k` clusters of memories sharing terms.
+///
+/// Memories without any term are left out. Returns the non-empty clusters, largest first.
+pub(crate) fn term_clusters(memories: &[Memory], k: usize) -> Vec<TermCluster> {
+    let mut vocabulary: HashMap<String, usize> = HashMap::new();
+    let counts: Vec<HashMap<usize, u32>> = memories
+        .iter()
+        .map(|memory| {
+            let mut counts = HashMap::new();
+            for term in terms_of(memory) {
+                let next = vocabulary.len();
+                *counts
+                    .entry(*vocabulary.entry(term).or_insert(next))
+                    .or_default() += 1;
+            }
+            counts
+        })
+        .collect();
+    let dims = vocabulary.len();
+    let mut doc_freq = vec![0u32; dims];
+    counts
+        .iter()
+        .flat_map(HashMap::keys)
+        .for_each(|&i| doc_freq[i] += 1);
+
+    // Sublinear term frequency and smoothed inverse document frequency.
+    let n = memories.len() as f64;
+    let vectors: Vec<(usize, SparseVector)> = counts
+        .into_iter()
+        .enumerate()
+        .filter(|(_, counts)| !counts.is_empty())
+        .map(|(m, counts)| {
+            let mut vector: SparseVector = counts
+                .into_iter()
+                .map(|(i, tf)| {
+                    let idf = ((1.0 + n) / (1.0 + doc_freq[i] as f64)).ln() + 1.0;
+                    (i, (1.0 + (tf as f64).ln()) * idf)
+                })
+                .collect();
+            vector.sort_by_key(|(i, _)| *i);
+            let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+            vector.iter_mut().for_each(|(_, w)| *w /= norm);
+            (m, vector)
+        })
+        .collect();
+
+    let k = k.min(vectors.len());
+    if k == 0 {
+        return vec![];
+    }
+
+    let mean = centroid(vectors.iter().map(|(_, v)| v), dims);
+    let first = argmax(vectors.iter().map(|(_, v)| dot(v, &mean)));
+    let mut centroids = vec![centroid(std::iter::once(&vectors[first].1), dims)];
+    let mut closest: Vec<f64> = vectors.iter().map(|(_, v)| dot(v, &centroids[0])).collect();
+    while centroids.len() < k {
+        let farthest = argmax(closest.iter().map(|similarity| -similarity));
+        let seed = centroid(std::iter::once(&vectors[farthest].1), dims);
+        closest
+            .iter_mut()
+            .zip(&vectors)
+            .for_each(|(closest, (_, v))| *closest = closest.max(dot(v, &seed)));
+        centroids.push(seed);
+    }
+
+    let mut assignment: Vec<usize> = Vec::new();
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors
+            .par_iter()
+            .map(|(_, v)| nearest(v, &centroids))
+            .collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+        centroids = (0..k)
+            .map(|c| {
+                centroid(
+                    vectors
+                        .iter()
+                        .zip(&assignment)
+                        .filter(|(_, a)| **a == c)
+                        .map(|((_, v), _)| v),
+                    dims,
+                )
+            })
+            .collect();
+    }
+
+    let mut names = vec![String::new(); dims];
+    vocabulary.into_iter().for_each(|(term, i)| names[i] = term);
+    let mut clusters: Vec<TermCluster> = centroids
+        .iter()
+        .enumerate()
+        .map(|(c, centroid)| {
+            let mut members: Vec<(usize, f64)> = vectors
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, a)| **a == c)
+                .map(|((m, v), _)| (*m, dot(v, centroid)))
+                .collect();
+            members.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            let mut weights: Vec<(usize, f64)> = centroid
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(_, w)| *w > 0.0)
+                .collect();
+            weights.sort_by(|a, b| b.1.total_cmp(&a.1).then(names[a.0].cmp(&names[b.0])));
+            TermCluster {
+                members: members.into_iter().map(|(m, _)| m).collect(),
+                terms: weights
+                    .into_iter()
+                    .take(NUM_TERMS)
+                    .map(|(i, _)| names[i].clone())
+                    .collect(),
+            }
+        })
+        .filter(|cluster| !cluster.members.is_empty())
+        .collect();
+    clusters.sort_by(|a, b| b.members.len().cmp(&a.members.len()));
+    clusters
+}
@@ -0,0 +1,138 @@
+//! Detection of near-duplicate memories, the candidates of a consolidation.
+//!
+//! The content of each memory is reduced to its set of word bigrams, whose Jaccard similarity
+//! is estimated from MinHash signatures. Signatures are split into bands hashed into buckets,
+//! so only the memories sharing a bucket are compared, and the pairs similar enough are
+//! merged into clusters. Memories of different namespaces are never clustered together.
+
+use crate::memory::Memory;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Number of hash functions of a signature.
+const NUM_HASHES: usize = 128;
+
+/// Number of signature values per band, pairs sharing a whole band are compared.
+const ROWS_PER_BAND: usize = 4;
+
+/// Number of words per shingle.
+const SHINGLE_WORDS: usize = 2;
+
+/// Mixes the bits of a value, the finalizer of SplitMix64.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes the word bigrams of a content, case-insensitively and ignoring punctuation. A
+/// content of a single word has that word as only shingle.
+fn shingles(content: &str) -> HashSet<u64> {
+    let words: Vec<String> = content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < SHINGLE_WORDS {
+        return words.iter().map(|word| hash_of(word)).collect();
+    }
+    words
+        .windows(SHINGLE_WORDS)
+        .map(|window| hash_of(window))
+        .collect()
+}
+
+/// The MinHash signature of a set of shingles, `None` for an empty set.
+fn signature(shingles: &HashSet<u64>) -> Option<[u64; NUM_HASHES]> {
+    if shingles.is_empty() {
+        return None;
+    }
+    let mut signature = [u64::MAX; NUM_HASHES];
+    for shingle in shingles {
+        for (i, min) in signature.iter_mut().enumerate() {
+            *min = (*min).min(mix(shingle ^ mix(i as u64 + 1)));
+        }
+    }
+    Some(signature)
+}
+
+/// The estimated Jaccard similarity of the sets two signatures were computed from.
+fn similarity(a: &[u64; NUM_HASHES], b: &[u64; NUM_HASHES]) -> f64 {
+    a.iter().zip(b).filter(|(a, b)| a == b).count() as f64 / NUM_HASHES as f64
+}
+
+/// Finds the root of a set of a union-find forest, compressing the path to it.
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Groups the memories whose contents are near-duplicates of each other.
+///
+/// Two memories are linked if their estimated similarity reaches the threshold, and clusters
+/// are the connected groups of linked memories, so the members of a cluster can be less
+/// similar to each other than the threshold through intermediaries.
+///
+/// Returns the indices of the memories of each cluster of at least `min_size` memories.
+pub(crate) fn near_duplicate_clusters(
+    memories: &[Memory],
+    threshold: f64,
+    min_size: usize,
+) -> Vec<Vec<usize>> {
+    let signatures: Vec<Option<[u64; NUM_HASHES]>> = memories
+        .par_iter()
+        .map(|memory| signature(&shingles(&memory.content)))
+        .collect();
+
+    let mut buckets: HashMap<(&str, usize, u64), Vec<usize>> = HashMap::new();
+    for (i, signature) in signatures.iter().enumerate() {
+        let Some(signature) = signature else {
+            continue;
+        };
+        for (band, rows) in signature.chunks(ROWS_PER_BAND).enumerate() {
+            buckets
+                .entry((memories[i].namespace.as_str(), band, hash_of(rows)))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut parents: Vec<usize> = (0..memories.len()).collect();
+    let mut compared = HashSet::new();
+    for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+        for (k, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[k + 1..] {
+                if !compared.insert((a, b)) {
+                    continue;
+                }
+                let (Some(sa), Some(sb)) = (&signatures[a], &signatures[b]) else {
+                    continue;
+                };
+                if similarity(sa, sb) >= threshold {
+                    let (ra, rb) = (find(&mut parents, a), find(&mut parents, b));
+                    parents[ra.max(rb)] = ra.min(rb);
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..memories.len() {
+        let root = find(&mut parents, i);
+        clusters.entry(root).or_default().push(i);
+    }
+    clusters
+        .into_values()
+        .filter(|cluster| cluster.len() >= min_size.max(2))
+        .collect()
+}
@@ -9,6 +9,11 @@ pub mod field_names {
     pub const IMPORTANCE: &str = "importance";
     pub const ACCESS_COUNT: &str = "access_count";
     pub const LAST_ACCESSED: &str = "last_accessed";
+    pub const SENSITIVITY: &str = "sensitivity";
+    pub const EMBEDDING: &str = "embedding";
+    pub const NAMESPACE: &str = "namespace";
+    pub const EXPIRES_AT: &str = "expires_at";
+    pub const METADATA: &str = "metadata";
 }
 
 pub static MAX_IMPORTANCE_SCORE: u64 = 100;
@@ -16,10 +21,18 @@ pub static MAX_IMPORTANCE_SCORE_VARNAME: &str = "MAX_IMPORTANCE_SCORE";
 pub static MIN_IMPORTANCE_SCORE: u64 = 0;
 pub static MIN_IMPORTANCE_SCORE_VARNAME: &str = "MIN_IMPORTANCE_SCORE";
 
+/// Namespace of the memories added without one.
+pub static DEFAULT_NAMESPACE: &str = "default";
+
 #[cfg(feature = "stubgen")]
 pub(crate) static MODULE_NAME: &str = concat!(env!("CARGO_CRATE_NAME"), ".rust");
 
 pub static METADATA_FILE_NAME: &str = "meta.json";
+pub static SIDECAR_FILE_NAME: &str = "metadata.sqlite3";
+pub static AUDIT_LOG_FILE_NAME: &str = "audit.jsonl";
+
+/// Buffer size in bytes of the writer filling a snapshot, the minimum tantivy accepts.
+pub const SNAPSHOT_WRITER_BUFFER_SIZE: usize = 15_000_000;
 
 pub static SCHEMA: Lazy<Schema> = Lazy::new(|| {
     let mut schema_builder = Schema::builder();
@@ -31,6 +44,11 @@ pub static SCHEMA: Lazy<Schema> = Lazy::new(|| {
     schema_builder.add_u64_field(field_names::IMPORTANCE, STORED | INDEXED | FAST);
     schema_builder.add_u64_field(field_names::ACCESS_COUNT, STORED | INDEXED | FAST);
     schema_builder.add_i64_field(field_names::LAST_ACCESSED, STORED | INDEXED | FAST);
+    schema_builder.add_u64_field(field_names::SENSITIVITY, STORED | INDEXED | FAST);
+    schema_builder.add_bytes_field(field_names::EMBEDDING, STORED);
+    schema_builder.add_text_field(field_names::NAMESPACE, STRING | STORED | FAST);
+    schema_builder.add_i64_field(field_names::EXPIRES_AT, STORED | INDEXED | FAST);
+    schema_builder.add_json_field(field_names::METADATA, STRING | STORED);
 
     schema_builder.build()
 });
@@ -43,6 +61,11 @@ pub struct MemoryFields {
     pub importance: Field,
     pub access_count: Field,
     pub last_accessed: Field,
+    pub sensitivity: Field,
+    pub embedding: Field,
+    pub namespace: Field,
+    pub expires_at: Field,
+    pub metadata: Field,
 }
 
 pub static FIELDS: Lazy<MemoryFields> = Lazy::new(|| MemoryFields {
@@ -53,4 +76,9 @@ pub static FIELDS: Lazy<MemoryFields> = Lazy::new(|| MemoryFields {
     importance: SCHEMA.get_field(field_names::IMPORTANCE).unwrap(),
     access_count: SCHEMA.get_field(field_names::ACCESS_COUNT).unwrap(),
     last_accessed: SCHEMA.get_field(field_names::LAST_ACCESSED).unwrap(),
+    sensitivity: SCHEMA.get_field(field_names::SENSITIVITY).unwrap(),
+    embedding: SCHEMA.get_field(field_names::EMBEDDING).unwrap(),
+    namespace: SCHEMA.get_field(field_names::NAMESPACE).unwrap(),
+    expires_at: SCHEMA.get_field(field_names::EXPIRES_AT).unwrap(),
+    metadata: SCHEMA.get_field(field_names::METADATA).unwrap(),
 });
@@ -0,0 +1,324 @@
+//! Filter expressions constraining the memories returned by a search.
+//!
+//! A filter compares attributes of memories with literals and combines the comparisons with
+//! `AND`, `OR`, `NOT` and parentheses:
+//!
+//! ```text
+//! metadata.project == "fabricatio" AND (importance >= 50 OR NOT tags == "draft")
+//! ```
+//!
+//! The attributes are `metadata.<key>`, the numeric `importance`, `timestamp`, `access_count`,
+//! `last_accessed` and `expires_at`, and `namespace`, `sensitivity` and `tags`, which only
+//! support `==` and `!=`. On `tags`, `==` holds if the memory carries the tag. Literals are
+//! quoted strings, numbers, `true` and `false`. A comparison with a missing attribute or a
+//! literal of another type never holds, whatever its operator.
+
+use crate::memory::{Memory, MetadataValue};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+/// The prefix of the paths of metadata keys.
+const METADATA_PREFIX: &str = "metadata.";
+
+/// The attributes of a memory a filter compares.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Attribute {
+    Metadata(String),
+    Importance,
+    Timestamp,
+    AccessCount,
+    LastAccessed,
+    ExpiresAt,
+    Namespace,
+    Sensitivity,
+    Tags,
+}
+
+impl Attribute {
+    /// Whether the attribute can be ordered, rather than only compared for equality.
+    fn is_ordered(&self) -> bool {
+        !matches!(self, Self::Namespace | Self::Sensitivity | Self::Tags)
+    }
+
+    /// The value of the attribute for a memory, if it has one. Tags are handled by the caller.
+    fn value_of(&self, memory: &Memory) -> Option<MetadataValue> {
+        match self {
+            Self::Metadata(key) => memory.metadata.get(key).cloned(),
+            Self::Importance => i64::try_from(memory.importance)
+                .ok()
+                .map(MetadataValue::Int),
+            Self::Timestamp => Some(MetadataValue::Int(memory.timestamp)),
+            Self::AccessCount => i64::try_from(memory.access_count)
+                .ok()
+                .map(MetadataValue::Int),
+            Self::LastAccessed => Some(MetadataValue::Int(memory.last_accessed)),
+            Self::ExpiresAt => memory.expires_at.map(MetadataValue::Int),
+            Self::Namespace => Some(MetadataValue::Str(memory.namespace.clone())),
+            Self::Sensitivity => Some(MetadataValue::Str(memory.sensitivity.clone())),
+            Self::Tags => None,
+        }
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "importance" => Ok(Self::Importance),
+            "timestamp" => Ok(Self::Timestamp),
+            "access_count" => Ok(Self::AccessCount),
+            "last_accessed" => Ok(Self::LastAccessed),
+            "expires_at" => Ok(Self::ExpiresAt),
+            "namespace" => Ok(Self::Namespace),
+            "sensitivity" => Ok(Self::Sensitivity),
+            "tags" => Ok(Self::Tags),
+            _ => match s.strip_prefix(METADATA_PREFIX) {
+                Some(key) if !key.is_empty() => Ok(Self::Metadata(key.to_string())),
+                _ => Err(format!(
+                    "unknown attribute `{s}`, expected `metadata.<key>` or one of `importance`, `timestamp`, `access_count`, `last_accessed`, `expires_at`, `namespace`, `sensitivity`, `tags`"
+                )),
+            },
+        }
+    }
+}
+
+/// The comparison operators of a filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Operator {
+    /// Whether two values ordered as given satisfy the operator.
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+        }
+    }
+}
+
+/// Orders two values of the same type, integers and floats being comparable with each other.
+fn compare(a: &MetadataValue, b: &MetadataValue) -> Option<Ordering> {
+    match (a, b) {
+        (MetadataValue::Bool(a), MetadataValue::Bool(b)) => Some(a.cmp(b)),
+        (MetadataValue::Int(a), MetadataValue::Int(b)) => Some(a.cmp(b)),
+        (MetadataValue::Int(a), MetadataValue::Float(b)) => (*a as f64).partial_cmp(b),
+        (MetadataValue::Float(a), MetadataValue::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (MetadataValue::Float(a), MetadataValue::Float(b)) => a.partial_cmp(b),
+        (MetadataValue::Str(a), MetadataValue::Str(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MemoryFilter {
+    Compare(Attribute, Operator, MetadataValue),
+    And(Box<MemoryFilter>, Box<MemoryFilter>),
+    Or(Box<MemoryFilter>, Box<MemoryFilter>),
+    Not(Box<MemoryFilter>),
+}
+
+impl MemoryFilter {
+    /// Whether the memory satisfies the filter.
+    pub(crate) fn matches(&self, memory: &Memory) -> bool {
+        match self {
+            Self::Compare(Attribute::Tags, operator, MetadataValue::Str(tag)) => {
+                memory.tags.contains(tag) == (*operator == Operator::Eq)
+            }
+            Self::Compare(attribute, operator, literal) => attribute
+                .value_of(memory)
+                .and_then(|value| compare(&value, literal))
+                .is_some_and(|ordering| operator.holds(ordering)),
+            Self::And(a, b) => a.matches(memory) && b.matches(memory),
+            Self::Or(a, b) => a.matches(memory) || b.matches(memory),
+            Self::Not(a) => !a.matches(memory),
+        }
+    }
+}
+
+impl FromStr for MemoryFilter {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+        };
+        let filter = parser
+            .disjunction()
+            .and_then(|filter| match parser.tokens.next() {
+                None => Ok(filter),
+                Some(token) => Err(format!("unexpected {token:?}")),
+            })
+            .map_err(|reason| PyValueError::new_err(format!("Invalid filter `{s}`: {reason}")))?;
+        Ok(filter)
+    }
+}
+
+/// The tokens of a filter expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(MetadataValue),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// Reads a quoted string whose opening quote was consumed, unescaping backslashes.
+fn read_string(chars: &mut Peekable<CharIndices<'_>>, quote: char) -> Option<String> {
+    let mut string = String::new();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\\' => string.push(chars.next()?.1),
+            c if c == quote => return Some(string),
+            c => string.push(c),
+        }
+    }
+    None
+}
+
+/// Splits a filter expression into tokens.
+fn tokenize(s: &str) -> PyResult<Vec<Token>> {
+    let invalid = |reason: String| PyValueError::new_err(format!("Invalid filter `{s}`: {reason}"));
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '"' | '\'' => Token::Literal(MetadataValue::Str(
+                read_string(&mut chars, c)
+                    .ok_or_else(|| invalid(format!("unterminated string at {start}")))?,
+            )),
+            '=' | '!' | '<' | '>' => {
+                let eq = chars.next_if(|(_, next)| *next == '=').is_some();
+                Token::Operator(match (c, eq) {
+                    ('=', true) => Operator::Eq,
+                    ('!', true) => Operator::Ne,
+                    ('<', false) => Operator::Lt,
+                    ('<', true) => Operator::Le,
+                    ('>', false) => Operator::Gt,
+                    ('>', true) => Operator::Ge,
+                    _ => return Err(invalid(format!("unknown operator at {start}"))),
+                })
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, next)) =
+                    chars.next_if(|(_, next)| next.is_ascii_digit() || *next == '.')
+                {
+                    end = i + next.len_utf8();
+                }
+                let number = &s[start..end];
+                Token::Literal(match number.parse() {
+                    Ok(int) => MetadataValue::Int(int),
+                    Err(_) => MetadataValue::Float(
+                        number
+                            .parse()
+                            .map_err(|_| invalid(format!("invalid number `{number}`")))?,
+                    ),
+                })
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, next)) = chars
+                    .next_if(|(_, next)| next.is_alphanumeric() || *next == '_' || *next == '.')
+                {
+                    end = i + next.len_utf8();
+                }
+                let word = &s[start..end];
+                match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "true" => Token::Literal(MetadataValue::Bool(true)),
+                    "false" => Token::Literal(MetadataValue::Bool(false)),
+                    _ => Token::Ident(word.to_string()),
+                }
+            }
+            c => return Err(invalid(format!("unexpected `{c}` at {start}"))),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of a filter, `OR` binding looser than `AND`,
+/// which binds looser than `NOT`.
+struct Parser {
+    tokens: Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn disjunction(&mut self) -> Result<MemoryFilter, String> {
+        let mut filter = self.conjunction()?;
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            filter = MemoryFilter::Or(Box::new(filter), Box::new(self.conjunction()?));
+        }
+        Ok(filter)
+    }
+
+    fn conjunction(&mut self) -> Result<MemoryFilter, String> {
+        let mut filter = self.negation()?;
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            filter = MemoryFilter::And(Box::new(filter), Box::new(self.negation()?));
+        }
+        Ok(filter)
+    }
+
+    fn negation(&mut self) -> Result<MemoryFilter, String> {
+        if self.tokens.next_if_eq(&Token::Not).is_some() {
+            return Ok(MemoryFilter::Not(Box::new(self.negation()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<MemoryFilter, String> {
+        match self.tokens.next() {
+            Some(Token::Open) => {
+                let filter = self.disjunction()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("missing `)`".to_string()),
+                }
+            }
+            Some(Token::Ident(path)) => {
+                let attribute = path.parse::<Attribute>()?;
+                let Some(Token::Operator(operator)) = self.tokens.next() else {
+                    return Err(format!("expected an operator after `{path}`"));
+                };
+                let Some(Token::Literal(literal)) = self.tokens.next() else {
+                    return Err(format!("expected a literal after `{path}`"));
+                };
+                if !attribute.is_ordered() && !matches!(operator, Operator::Eq | Operator::Ne) {
+                    return Err(format!("`{path}` only supports `==` and `!=`"));
+                }
+                if attribute == Attribute::Tags && !matches!(literal, MetadataValue::Str(_)) {
+                    return Err("`tags` can only be compared with a string".to_string());
+                }
+                Ok(MemoryFilter::Compare(attribute, operator, literal))
+            }
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("unexpected end of the expression".to_string()),
+        }
+    }
+}
@@ -1,15 +1,28 @@
 #![cfg_attr(feature = "stubgen", allow(dead_code, unused,))]
 
+mod audit;
+mod cluster;
+mod consolidate;
 mod constants;
+mod filter;
+mod markdown;
 mod memory;
+mod query;
+mod retention;
+mod sensitivity;
 mod service;
+mod sidecar;
 mod stat;
 mod store;
 mod traits;
 mod utils;
+mod vector;
 
+use crate::audit::AuditEntry;
+use crate::cluster::MemoryCluster;
 use crate::constants::*;
 use crate::memory::Memory;
+use crate::query::MemoryQuery;
 use crate::service::MemoryService;
 use crate::stat::MemoryStats;
 use crate::store::MemoryStore;
@@ -43,6 +56,9 @@ fn rust(_python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<MemoryService>()?;
     m.add_class::<MemoryStore>()?;
     m.add_class::<MemoryStats>()?;
+    m.add_class::<MemoryQuery>()?;
+    m.add_class::<AuditEntry>()?;
+    m.add_class::<MemoryCluster>()?;
 
     m.add(MAX_IMPORTANCE_SCORE_VARNAME, MAX_IMPORTANCE_SCORE)?;
     m.add(MIN_IMPORTANCE_SCORE_VARNAME, MIN_IMPORTANCE_SCORE)?;
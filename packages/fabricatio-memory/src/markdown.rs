@@ -0,0 +1,266 @@
+//! Conversion of memories to and from Obsidian-style Markdown notes.
+//!
+//! A note carries the memory attributes in its YAML frontmatter and the content as its body:
+//!
+//! ```markdown
+//! ---
+//! uuid: 0199f0c2-7a4e-7d31-9c55-3f1b2d6e8a90
+//! importance: 50
+//! tags: ["project", "rust"]
+//! sensitivity: public
+//! namespace: default
+//! timestamp: 1760572800
+//! access_count: 3
+//! last_accessed: 1760572800
+//! ---
+//!
+//! The content of the memory.
+//! ```
+//!
+//! Memories with an expiry carry it as an `expires_at` timestamp after `last_accessed`, and
+//! memories with metadata carry it as a `metadata` flow mapping after that.
+//!
+//! Only a subset of YAML is understood: `key: value` pairs, with tags either as a flow
+//! list or as a block list of `- tag` lines.
+
+use crate::constants::MIN_IMPORTANCE_SCORE;
+use crate::memory::Memory;
+use crate::sensitivity::Sensitivity;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Longest note title derived from the content, in characters.
+const MAX_TITLE_CHARS: usize = 60;
+
+/// How exported notes are laid out in directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GroupBy {
+    /// One subdirectory per first tag, untagged notes at the top level
+    Tag,
+    /// All notes at the top level
+    None,
+}
+
+impl FromStr for GroupBy {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tag" => Ok(Self::Tag),
+            "none" => Ok(Self::None),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown grouping `{s}`, expected one of `tag`, `none`"
+            ))),
+        }
+    }
+}
+
+/// Renders a memory as a note.
+pub(crate) fn to_markdown(memory: &Memory) -> String {
+    format!(
+        "---\nuuid: {}\nimportance: {}\ntags: {}\nsensitivity: {}\nnamespace: {}\ntimestamp: {}\naccess_count: {}\nlast_accessed: {}\n{}{}---\n\n{}\n",
+        memory.uuid,
+        memory.importance,
+        // A JSON array of strings is a valid YAML flow list.
+        serde_json::to_string(&memory.tags).unwrap_or_else(|_| "[]".to_string()),
+        memory.sensitivity,
+        memory.namespace,
+        memory.timestamp,
+        memory.access_count,
+        memory.last_accessed,
+        memory
+            .expires_at
+            .map(|expires_at| format!("expires_at: {expires_at}\n"))
+            .unwrap_or_default(),
+        // A JSON object is a valid YAML flow mapping.
+        (!memory.metadata.is_empty())
+            .then(|| serde_json::to_string(&memory.metadata).ok())
+            .flatten()
+            .map(|metadata| format!("metadata: {metadata}\n"))
+            .unwrap_or_default(),
+        memory.content
+    )
+}
+
+/// Splits a note into its frontmatter, if any, and its body.
+fn split_frontmatter(note: &str) -> (Option<&str>, &str) {
+    let note = note.strip_prefix('\u{feff}').unwrap_or(note);
+    let Some(rest) = note
+        .strip_prefix("---\n")
+        .or_else(|| note.strip_prefix("---\r\n"))
+    else {
+        return (None, note);
+    };
+    let end = if rest.starts_with("---") {
+        Some(0)
+    } else {
+        rest.find("\n---").map(|i| i + 1)
+    };
+    match end {
+        Some(end) => {
+            let body = rest[end + 3..].trim_start_matches(['\r', '\n']);
+            (Some(&rest[..end]), body)
+        }
+        None => (None, note),
+    }
+}
+
+/// Removes the quotes around a scalar, unescaping double-quoted ones.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        serde_json::from_str(value).unwrap_or_else(|_| value[1..value.len() - 1].to_string())
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("''", "'")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parses a flow list such as `[a, "b c"]`.
+fn parse_flow_list(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_else(|_| {
+        value
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(unquote)
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
+/// Parses a frontmatter value, naming the key in the error.
+fn parse_field<T: FromStr>(fields: &HashMap<&str, String>, key: &str) -> PyResult<Option<T>> {
+    fields
+        .get(key)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                PyValueError::new_err(format!("Invalid `{key}` in frontmatter: {value}"))
+            })
+        })
+        .transpose()
+}
+
+/// Parses a note into a memory.
+///
+/// Notes without a `uuid` get a new one, missing attributes take the defaults of a new memory
+/// with the minimum importance.
+pub(crate) fn from_markdown(note: &str) -> PyResult<Memory> {
+    let (frontmatter, body) = split_frontmatter(note);
+
+    let mut fields = HashMap::new();
+    let mut tags = vec![];
+    let mut lines = frontmatter.unwrap_or_default().lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key != "tags" {
+            fields.insert(key, unquote(value));
+        } else if value.is_empty() {
+            while let Some(next) = lines.peek()
+                && let Some(item) = next.trim_start().strip_prefix('-')
+            {
+                tags.push(unquote(item));
+                lines.next();
+            }
+        } else {
+            tags = parse_flow_list(value);
+        }
+    }
+
+    let importance = parse_field(&fields, "importance")?.unwrap_or(MIN_IMPORTANCE_SCORE);
+    let sensitivity = parse_field::<Sensitivity>(&fields, "sensitivity")?.unwrap_or_default();
+    let mut memory = Memory::new(
+        body.trim().to_string(),
+        importance,
+        tags,
+        sensitivity,
+        None,
+        fields.get("namespace").filter(|ns| !ns.is_empty()).cloned(),
+    )?;
+    if let Some(uuid) = fields.get("uuid").filter(|uuid| !uuid.is_empty()) {
+        memory.uuid = uuid.clone();
+    }
+    if let Some(timestamp) = parse_field(&fields, "timestamp")? {
+        memory.timestamp = timestamp;
+    }
+    if let Some(access_count) = parse_field(&fields, "access_count")? {
+        memory.access_count = access_count;
+    }
+    if let Some(last_accessed) = parse_field(&fields, "last_accessed")? {
+        memory.last_accessed = last_accessed;
+    }
+    memory.expires_at = parse_field(&fields, "expires_at")?;
+    if let Some(metadata) = fields.get("metadata") {
+        memory.metadata = serde_json::from_str(metadata).map_err(|_| {
+            PyValueError::new_err(format!("Invalid `metadata` in frontmatter: {metadata}"))
+        })?;
+    }
+    Ok(memory)
+}
+
+/// Chooses the path of the note of a memory, titled after the first line of its content.
+///
+/// Paths already in `taken` are avoided by appending the UUID to the title.
+pub(crate) fn note_path(
+    dir: &Path,
+    memory: &Memory,
+    group_by: GroupBy,
+    taken: &mut HashSet<PathBuf>,
+) -> PathBuf {
+    let dir = match (group_by, memory.tags.first()) {
+        (GroupBy::Tag, Some(tag)) => dir.join(sanitize_filename::sanitize(tag)),
+        _ => dir.to_path_buf(),
+    };
+    let first_line = memory
+        .content
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+    let title = sanitize_filename::sanitize(
+        first_line
+            .trim()
+            .trim_start_matches('#')
+            .trim()
+            .chars()
+            .take(MAX_TITLE_CHARS)
+            .collect::<String>(),
+    );
+
+    let mut path = if title.is_empty() {
+        dir.join(format!("{}.md", memory.uuid))
+    } else {
+        dir.join(format!("{title}.md"))
+    };
+    if taken.contains(&path) {
+        path = dir.join(format!("{title} {}.md", memory.uuid));
+    }
+    taken.insert(path.clone());
+    path
+}
+
+/// Lists the Markdown files below a directory, skipping hidden entries such as `.obsidian`.
+pub(crate) fn markdown_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            files.extend(markdown_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -5,12 +5,40 @@ use pyo3::types::PyDict;
 use pyo3_stub_gen::derive::*;
 use pythonize::pythonize;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 use tantivy::doc;
 
-use crate::constants::MAX_IMPORTANCE_SCORE;
+use crate::constants::{DEFAULT_NAMESPACE, MAX_IMPORTANCE_SCORE};
+use crate::retention::RetentionPolicy;
+use crate::sensitivity::Sensitivity;
 use uuid::Uuid;
 
+/// A value of the metadata of a memory.
+///
+/// Variants are tried in order when converting from Python or JSON, so booleans are not taken
+/// for integers, nor integers for floats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromPyObject, IntoPyObject)]
+#[serde(untagged)]
+pub enum MetadataValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+#[cfg(feature = "stubgen")]
+impl pyo3_stub_gen::PyStubType for MetadataValue {
+    fn type_output() -> pyo3_stub_gen::TypeInfo {
+        use pyo3_stub_gen::PyStubType;
+        bool::type_output() | i64::type_output() | f64::type_output() | String::type_output()
+    }
+}
+
+/// Arbitrary key/value metadata attached to a memory.
+pub type Metadata = BTreeMap<String, MetadataValue>;
+
 /// Represents a memory object with content, importance, tags, and access statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
@@ -30,6 +58,24 @@ pub struct Memory {
     pub access_count: u64,
     /// Unix timestamp when the memory was last accessed
     pub last_accessed: i64,
+    /// Access control label of the memory: "public", "internal" or "secret"
+    pub sensitivity: String,
+    /// Embedding vector of the content, used by vector and hybrid search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    /// Namespace the memory belongs to, so several agents or threads can share one store
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    /// Unix timestamp after which the memory is no longer returned, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Arbitrary key/value metadata, matched by the `metadata.<key>` paths of search filters
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: Metadata,
+}
+
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
 }
 
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
@@ -51,13 +97,23 @@ impl Memory {
     ///     content: The text content of the memory.
     ///     importance: The importance score of the memory (0 to MAX_IMPORTANCE_SCORE).
     ///     tags: A list of tags associated with the memory.
+    ///     sensitivity: The access control label of the memory.
+    ///     embedding: The embedding vector of the content, if any.
+    ///     namespace: The namespace of the memory, `DEFAULT_NAMESPACE` if not given.
     ///
     /// Returns:
     ///     A new Memory instance.
     ///
     /// Raises:
-    ///     PyValueError: If importance exceeds MAX_IMPORTANCE_SCORE.
-    pub fn new(content: String, importance: u64, tags: Vec<String>) -> PyResult<Self> {
+    ///     PyValueError: If importance exceeds MAX_IMPORTANCE_SCORE or the namespace is empty.
+    pub fn new(
+        content: String,
+        importance: u64,
+        tags: Vec<String>,
+        sensitivity: Sensitivity,
+        embedding: Option<Vec<f32>>,
+        namespace: Option<String>,
+    ) -> PyResult<Self> {
         let now = Utc::now().timestamp();
 
         if importance > MAX_IMPORTANCE_SCORE {
@@ -66,6 +122,11 @@ impl Memory {
                 MAX_IMPORTANCE_SCORE
             )));
         }
+        if namespace.as_ref().is_some_and(String::is_empty) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Namespace cannot be empty",
+            ));
+        }
 
         Ok(Memory {
             uuid: Uuid::now_v7().to_string(),
@@ -75,9 +136,48 @@ impl Memory {
             tags,
             access_count: 0,
             last_accessed: now,
+            sensitivity: sensitivity.to_string(),
+            embedding,
+            namespace: namespace.unwrap_or_else(default_namespace),
+            expires_at: None,
+            metadata: Metadata::new(),
         })
     }
 
+    /// Checks the invariants `new` enforces, for memories read from outside of the store.
+    ///
+    /// Raises:
+    ///     PyValueError: If the importance exceeds MAX_IMPORTANCE_SCORE, the namespace is empty
+    ///     or the sensitivity label is unknown.
+    pub fn validate(&self) -> PyResult<()> {
+        if self.importance > MAX_IMPORTANCE_SCORE {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Importance score cannot be greater than {}",
+                MAX_IMPORTANCE_SCORE
+            )));
+        }
+        if self.namespace.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Namespace cannot be empty",
+            ));
+        }
+        self.sensitivity.parse::<Sensitivity>().map(|_| ())
+    }
+
+    /// Hashes the content of the memory within its namespace, so that the same content
+    /// recorded in two namespaces is not considered a duplicate.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.namespace.hash(&mut hasher);
+        self.content.trim().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether the memory expired at the given unix timestamp.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
     /// Updates the access count and last accessed timestamp.
     ///
     /// This method should be called whenever the memory is accessed
@@ -87,6 +187,45 @@ impl Memory {
         self.last_accessed = Utc::now().timestamp();
     }
 
+    /// Computes the cosine similarity between the embedding of the memory and a query vector.
+    ///
+    /// Returns:
+    ///     The similarity in `[-1, 1]`, or `None` if the memory has no embedding, the
+    ///     dimensions differ, or either vector is zero.
+    pub fn cosine_similarity(&self, query: &[f32]) -> Option<f32> {
+        let embedding = self.embedding.as_deref()?;
+        if embedding.len() != query.len() {
+            return None;
+        }
+        let (dot, norm_a, norm_b) = embedding
+            .iter()
+            .zip(query)
+            .fold((0.0f32, 0.0f32, 0.0f32), |(dot, norm_a, norm_b), (a, b)| {
+                (dot + a * b, norm_a + a * a, norm_b + b * b)
+            });
+        let norm = norm_a.sqrt() * norm_b.sqrt();
+        (norm > 0.0).then(|| dot / norm)
+    }
+
+    /// Returns the parsed access control label, treating an unknown label as secret.
+    pub fn sensitivity_level(&self) -> Sensitivity {
+        self.sensitivity.parse().unwrap_or(Sensitivity::Secret)
+    }
+
+    /// Calculates how hot the memory is, its access count decayed exponentially with the days
+    /// since it was last accessed.
+    ///
+    /// Args:
+    ///     decay_factor: The rate at which the access count fades after the last access.
+    ///     now: The unix timestamp to measure the time since the last access from.
+    ///
+    /// Returns:
+    ///     The decayed access count as a float.
+    pub fn heat(&self, decay_factor: f64, now: i64) -> f64 {
+        let idle_days = (now - self.last_accessed).max(0) as f64 / 86400.0;
+        self.access_count as f64 * (-idle_days * decay_factor).exp()
+    }
+
     /// Calculates a relevance score based on importance, recency, and access frequency.
     ///
     /// The score combines three factors:
@@ -106,3 +245,50 @@ impl Memory {
         self.importance as f64 * recency_score * frequency_score
     }
 }
+
+/// The fields of a memory to be added, as accepted by `add_memories_bulk`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct MemoryDraft {
+    content: String,
+    importance: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    sensitivity: Option<String>,
+    #[serde(default)]
+    embedding: Option<Vec<f32>>,
+    #[serde(default)]
+    namespace: Option<String>,
+    #[serde(default)]
+    expires_at: Option<i64>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+impl MemoryDraft {
+    /// Creates the memory, validating its importance and sensitivity label.
+    ///
+    /// Drafts without a namespace are put in the given one, and drafts without an expiry get the
+    /// one resolved by the retention policy.
+    pub(crate) fn into_memory(
+        self,
+        namespace: Option<&str>,
+        policy: &RetentionPolicy,
+    ) -> PyResult<Memory> {
+        let sensitivity = match self.sensitivity {
+            Some(label) => label.parse()?,
+            None => Sensitivity::default(),
+        };
+        let mut memory = Memory::new(
+            self.content,
+            self.importance,
+            self.tags,
+            sensitivity,
+            self.embedding,
+            self.namespace.or_else(|| namespace.map(str::to_string)),
+        )?;
+        memory.expires_at = policy.expiry_of(self.expires_at, memory.timestamp);
+        memory.metadata = self.metadata;
+        Ok(memory)
+    }
+}
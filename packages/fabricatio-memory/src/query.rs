@@ -0,0 +1,325 @@
+use crate::constants::{FIELDS, MAX_IMPORTANCE_SCORE};
+use crate::sensitivity::Sensitivity;
+use crate::utils::{
+    importance_term_of, max_sensitivity_query_of, namespace_query_of, timestamp_term_of,
+};
+use chrono::Utc;
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::ops::Bound;
+use tantivy::query::{AllQuery, BooleanQuery, FastFieldRangeQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, Term};
+
+/// A structured query builder for memories.
+///
+/// Each builder method returns a new `MemoryQuery`, so calls can be chained:
+/// `MemoryQuery().text("rust").tag_any(["infra"]).importance_gte(70).after(days=30)`.
+///
+/// The query is compiled directly into a tantivy `BooleanQuery`, so no query
+/// string parsing is involved and quotes or special characters in the input are
+/// matched literally.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(from_py_object)]
+pub struct MemoryQuery {
+    text: Option<String>,
+    tags_any: Vec<String>,
+    tags_all: Vec<String>,
+    tags_none: Vec<String>,
+    importance_min: Option<u64>,
+    importance_max: Option<u64>,
+    after: Option<i64>,
+    before: Option<i64>,
+    max_sensitivity: Option<Sensitivity>,
+    namespace: Option<String>,
+}
+
+/// Resolves a point in time given either a day offset from now or an absolute unix timestamp.
+fn resolve_timestamp(days: Option<i64>, timestamp: Option<i64>) -> PyResult<i64> {
+    match (days, timestamp) {
+        (Some(days), None) => Ok(Utc::now().timestamp() - days * 86400),
+        (None, Some(timestamp)) => Ok(timestamp),
+        _ => Err(PyValueError::new_err(
+            "Exactly one of `days` or `timestamp` must be provided",
+        )),
+    }
+}
+
+/// Creates a boxed TermQuery matching a raw tag value.
+#[inline]
+fn tag_query_of(tag: &str) -> Box<dyn Query> {
+    Box::new(TermQuery::new(
+        Term::from_field_text(FIELDS.tags, tag),
+        IndexRecordOption::Basic,
+    ))
+}
+
+impl MemoryQuery {
+    /// Tokenizes the given text with the tokenizer registered for the field,
+    /// returning one term per produced token.
+    fn tokenize(index: &Index, field: Field, text: &str) -> PyResult<Vec<Term>> {
+        let mut analyzer = index.tokenizer_for_field(field).into_pyresult()?;
+        let mut terms = Vec::new();
+        analyzer
+            .token_stream(text)
+            .process(&mut |token| terms.push(Term::from_field_text(field, &token.text)));
+        Ok(terms)
+    }
+
+    /// Compiles the query into a tantivy query against the given index.
+    ///
+    /// An empty query matches all memories.
+    pub fn build(&self, index: &Index) -> PyResult<Box<dyn Query>> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        if let Some(text) = &self.text {
+            let terms: Vec<(Occur, Box<dyn Query>)> = Self::tokenize(index, FIELDS.content, text)?
+                .into_iter()
+                .map(|term| {
+                    (
+                        Occur::Should,
+                        Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+                            as Box<dyn Query>,
+                    )
+                })
+                .collect();
+            if !terms.is_empty() {
+                clauses.push((Occur::Must, Box::new(BooleanQuery::new(terms))));
+            }
+        }
+
+        if !self.tags_any.is_empty() {
+            let any = self
+                .tags_any
+                .iter()
+                .map(|tag| (Occur::Should, tag_query_of(tag)))
+                .collect();
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(any))));
+        }
+
+        self.tags_all
+            .iter()
+            .for_each(|tag| clauses.push((Occur::Must, tag_query_of(tag))));
+
+        self.tags_none
+            .iter()
+            .for_each(|tag| clauses.push((Occur::MustNot, tag_query_of(tag))));
+
+        if self.importance_min.is_some() || self.importance_max.is_some() {
+            clauses.push((
+                Occur::Must,
+                Box::new(FastFieldRangeQuery::new(
+                    Bound::Included(importance_term_of(self.importance_min.unwrap_or(0))),
+                    Bound::Included(importance_term_of(
+                        self.importance_max.unwrap_or(MAX_IMPORTANCE_SCORE),
+                    )),
+                )),
+            ));
+        }
+
+        if self.after.is_some() || self.before.is_some() {
+            clauses.push((
+                Occur::Must,
+                Box::new(FastFieldRangeQuery::new(
+                    self.after
+                        .map_or(Bound::Unbounded, |t| Bound::Included(timestamp_term_of(t))),
+                    self.before
+                        .map_or(Bound::Unbounded, |t| Bound::Excluded(timestamp_term_of(t))),
+                )),
+            ));
+        }
+
+        if let Some(max_sensitivity) = self.max_sensitivity {
+            clauses.push((
+                Occur::Must,
+                Box::new(max_sensitivity_query_of(max_sensitivity)),
+            ));
+        }
+
+        if let Some(namespace) = &self.namespace {
+            clauses.push((Occur::Must, Box::new(namespace_query_of(namespace))));
+        }
+
+        if clauses.is_empty() {
+            return Ok(Box::new(AllQuery));
+        }
+
+        // A query made only of exclusions matches nothing in tantivy, so anchor it on all documents.
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            clauses.push((Occur::Must, Box::new(AllQuery)));
+        }
+
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl MemoryQuery {
+    /// Creates an empty query that matches all memories.
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the content to match the given text.
+    ///
+    /// The text is tokenized like the indexed content, and memories matching any
+    /// of the resulting tokens are kept, ranked by relevance.
+    ///
+    /// Args:
+    ///     text (str): The free text to match against memory content.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the text clause applied.
+    pub fn text(&self, text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..self.clone()
+        }
+    }
+
+    /// Requires the memory to carry at least one of the given tags.
+    ///
+    /// Args:
+    ///     tags (list[str]): The candidate tags.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the tag clause applied.
+    pub fn tag_any(&self, tags: Vec<String>) -> Self {
+        let mut query = self.clone();
+        query.tags_any.extend(tags);
+        query
+    }
+
+    /// Requires the memory to carry all of the given tags.
+    ///
+    /// Args:
+    ///     tags (list[str]): The required tags.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the tag clause applied.
+    pub fn tag_all(&self, tags: Vec<String>) -> Self {
+        let mut query = self.clone();
+        query.tags_all.extend(tags);
+        query
+    }
+
+    /// Excludes memories carrying any of the given tags.
+    ///
+    /// Args:
+    ///     tags (list[str]): The tags to exclude.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the exclusion applied.
+    pub fn tag_none(&self, tags: Vec<String>) -> Self {
+        let mut query = self.clone();
+        query.tags_none.extend(tags);
+        query
+    }
+
+    /// Requires the importance score to be greater than or equal to the given value.
+    ///
+    /// Args:
+    ///     importance (int): The inclusive lower bound.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the importance bound applied.
+    pub fn importance_gte(&self, importance: u64) -> Self {
+        Self {
+            importance_min: Some(importance),
+            ..self.clone()
+        }
+    }
+
+    /// Requires the importance score to be less than or equal to the given value.
+    ///
+    /// Args:
+    ///     importance (int): The inclusive upper bound.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the importance bound applied.
+    pub fn importance_lte(&self, importance: u64) -> Self {
+        Self {
+            importance_max: Some(importance),
+            ..self.clone()
+        }
+    }
+
+    /// Requires the memory to be created at or after the given point in time.
+    ///
+    /// Args:
+    ///     days (int | None, optional): Number of days back from now.
+    ///     timestamp (int | None, optional): Absolute unix timestamp.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the lower time bound applied.
+    ///
+    /// Raises:
+    ///     ValueError: If not exactly one of `days` or `timestamp` is provided.
+    #[pyo3(signature = (days = None, timestamp = None))]
+    pub fn after(&self, days: Option<i64>, timestamp: Option<i64>) -> PyResult<Self> {
+        Ok(Self {
+            after: Some(resolve_timestamp(days, timestamp)?),
+            ..self.clone()
+        })
+    }
+
+    /// Requires the memory to be created before the given point in time.
+    ///
+    /// Args:
+    ///     days (int | None, optional): Number of days back from now.
+    ///     timestamp (int | None, optional): Absolute unix timestamp.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the upper time bound applied.
+    ///
+    /// Raises:
+    ///     ValueError: If not exactly one of `days` or `timestamp` is provided.
+    #[pyo3(signature = (days = None, timestamp = None))]
+    pub fn before(&self, days: Option<i64>, timestamp: Option<i64>) -> PyResult<Self> {
+        Ok(Self {
+            before: Some(resolve_timestamp(days, timestamp)?),
+            ..self.clone()
+        })
+    }
+
+    /// Excludes memories more sensitive than the given label.
+    ///
+    /// Args:
+    ///     sensitivity (str): The most sensitive label to include: "public", "internal" or "secret".
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the sensitivity bound applied.
+    ///
+    /// Raises:
+    ///     ValueError: If the label is unknown.
+    pub fn max_sensitivity(&self, sensitivity: &str) -> PyResult<Self> {
+        Ok(Self {
+            max_sensitivity: Some(sensitivity.parse()?),
+            ..self.clone()
+        })
+    }
+
+    /// Requires the memory to belong to the given namespace.
+    ///
+    /// Args:
+    ///     namespace (str): The namespace to search in.
+    ///
+    /// Returns:
+    ///     MemoryQuery: A new query with the namespace clause applied.
+    pub fn namespace(&self, namespace: String) -> Self {
+        Self {
+            namespace: Some(namespace),
+            ..self.clone()
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
@@ -0,0 +1,60 @@
+//! Expiry and retention policies of memory stores.
+//!
+//! A memory may carry an `expires_at` timestamp, after which searches and listings no longer
+//! return it. Expired memories stay in the index until `MemoryStore.expire` sweeps them, which
+//! also evicts the least relevant memories of the namespaces holding more memories than the
+//! policy allows.
+
+use crate::memory::Memory;
+use std::collections::HashMap;
+
+/// Decay factor of the relevance score memories are evicted by.
+const EVICTION_DECAY_FACTOR: f64 = 0.01;
+
+/// The retention policy applied by the stores of a service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Lifetime in seconds of the memories added without an expiry, if any
+    pub default_ttl: Option<i64>,
+    /// Maximum number of memories kept per namespace by the sweep, if any
+    pub max_per_namespace: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// Resolves the expiry of a new memory, falling back to the default lifetime.
+    pub fn expiry_of(&self, expires_at: Option<i64>, now: i64) -> Option<i64> {
+        expires_at.or_else(|| self.default_ttl.map(|ttl| now + ttl))
+    }
+
+    /// Selects the memories to evict so no namespace holds more than the policy allows.
+    ///
+    /// Within a namespace, memories are evicted by ascending relevance score, the least
+    /// recently accessed first among equally relevant ones.
+    pub fn evictions(&self, memories: Vec<Memory>) -> Vec<Memory> {
+        let Some(max) = self.max_per_namespace else {
+            return vec![];
+        };
+        let mut by_namespace: HashMap<String, Vec<Memory>> = HashMap::new();
+        for memory in memories {
+            by_namespace
+                .entry(memory.namespace.clone())
+                .or_default()
+                .push(memory);
+        }
+        by_namespace
+            .into_values()
+            .filter(|memories| memories.len() > max)
+            .flat_map(|mut memories| {
+                let excess = memories.len() - max;
+                memories.sort_by(|a, b| {
+                    a.calculate_relevance_score(EVICTION_DECAY_FACTOR)
+                        .total_cmp(&b.calculate_relevance_score(EVICTION_DECAY_FACTOR))
+                        .then(a.last_accessed.cmp(&b.last_accessed))
+                        .then(a.timestamp.cmp(&b.timestamp))
+                });
+                memories.truncate(excess);
+                memories
+            })
+            .collect()
+    }
+}
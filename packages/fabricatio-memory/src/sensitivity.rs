@@ -0,0 +1,62 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Access control label of a memory, ordered from least to most sensitive.
+///
+/// The label is indexed as its numeric level, so a maximum sensitivity can be
+/// enforced with a range query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Sensitivity {
+    #[default]
+    Public = 0,
+    Internal = 1,
+    Secret = 2,
+}
+
+impl Sensitivity {
+    /// The numeric level stored in the index.
+    #[inline]
+    pub fn level(self) -> u64 {
+        self as u64
+    }
+
+    /// Resolves an indexed level, treating unknown levels as the most sensitive one.
+    pub fn from_level(level: u64) -> Self {
+        match level {
+            0 => Self::Public,
+            1 => Self::Internal,
+            _ => Self::Secret,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Secret => "secret",
+        }
+    }
+}
+
+impl FromStr for Sensitivity {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Self::Public),
+            "internal" => Ok(Self::Internal),
+            "secret" => Ok(Self::Secret),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown sensitivity `{s}`, expected one of `public`, `internal`, `secret`"
+            ))),
+        }
+    }
+}
+
+impl Display for Sensitivity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
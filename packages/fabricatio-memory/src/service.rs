@@ -1,5 +1,8 @@
-use crate::constants::SCHEMA;
-use crate::store::MemoryStore;
+use crate::audit::AuditLog;
+use crate::constants::{AUDIT_LOG_FILE_NAME, SCHEMA, SIDECAR_FILE_NAME};
+use crate::retention::RetentionPolicy;
+use crate::sidecar::MetadataSidecar;
+use crate::store::{MemoryStore, SharedWriter};
 use crate::utils::{is_valid_index_dir, sanitize_index_name};
 use error_mapping::AsPyErr;
 use moka::sync::Cache;
@@ -8,9 +11,9 @@ use pyo3::prelude::*;
 use pyo3_stub_gen::derive::*;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use tantivy::Index;
 use tantivy::directory::*;
-use tantivy::{Index, IndexWriter};
 
 type IndexName = String;
 
@@ -20,8 +23,14 @@ type IndexName = String;
 pub struct MemoryService {
     store_root_directory: PathBuf,
     index_cache: Cache<IndexName, Arc<Index>>,
-    index_writer_cache: Cache<IndexName, Arc<Mutex<IndexWriter>>>,
+    index_writer_cache: Cache<IndexName, Arc<SharedWriter>>,
+    sidecar_cache: Cache<IndexName, Arc<MetadataSidecar>>,
+    audit_cache: Cache<IndexName, Arc<AuditLog>>,
     writer_buffer_size: usize,
+    metadata_sidecar: bool,
+    audit_log: bool,
+    auto_commit: Option<usize>,
+    policy: RetentionPolicy,
 }
 
 impl MemoryService {
@@ -45,15 +54,39 @@ impl MemoryService {
             .into_pyresult()
     }
 
-    fn get_index_writer(&self, index_name: IndexName) -> PyResult<Arc<Mutex<IndexWriter>>> {
+    fn get_index_writer(&self, index_name: IndexName) -> PyResult<Arc<SharedWriter>> {
         self.index_writer_cache
             .try_get_with(index_name.clone(), || {
                 let index = self.get_index(index_name)?;
                 let index_writer = index.writer(self.writer_buffer_size).into_pyresult()?;
-                Ok(Arc::new(Mutex::new(index_writer)))
+                Ok(Arc::new(SharedWriter::new(index_writer)))
             })
             .map_err(|e: Arc<PyErr>| Arc::try_unwrap(e).expect("Unable to unwrap Arc"))
     }
+
+    fn get_sidecar(&self, index_name: IndexName) -> PyResult<Option<Arc<MetadataSidecar>>> {
+        if !self.metadata_sidecar {
+            return Ok(None);
+        }
+        let sidecar_path = self.index_path_of(&index_name)?.join(SIDECAR_FILE_NAME);
+        self.sidecar_cache
+            .try_get_with(index_name, || {
+                MetadataSidecar::open(&sidecar_path).map(Arc::new)
+            })
+            .map(Some)
+            .map_err(|e: Arc<PyErr>| Arc::try_unwrap(e).expect("Unable to unwrap Arc"))
+    }
+
+    fn get_audit_log(&self, index_name: IndexName) -> PyResult<Option<Arc<AuditLog>>> {
+        if !self.audit_log {
+            return Ok(None);
+        }
+        let audit_path = self.index_path_of(&index_name)?.join(AUDIT_LOG_FILE_NAME);
+        self.audit_cache
+            .try_get_with(index_name, || AuditLog::open(&audit_path).map(Arc::new))
+            .map(Some)
+            .map_err(|e: Arc<PyErr>| Arc::try_unwrap(e).expect("Unable to unwrap Arc"))
+    }
 }
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
 #[pymethods]
@@ -64,17 +97,40 @@ impl MemoryService {
     ///     store_root_directory (pathlib.Path): The root directory where indexes will be stored.
     ///     writer_buffer_size (int, optional): The buffer size for index writers in bytes. Defaults to 15,000,000 (15MB).
     ///     cache_size (int, optional): The maximum number of indexes to keep in cache. Defaults to 10.
+    ///     metadata_sidecar (bool, optional): If True, mirrors the metadata of each store in a SQLite database next to its index, serving `list_memories` and other ordered listings without going through the full-text index. Defaults to False.
+    ///     auto_commit (int | None, optional): Commits the changes of a store once that many are staged, even if no operation asks to write. Defaults to None, committing only on explicit writes.
+    ///     default_ttl (int | None, optional): Lifetime in seconds of the memories added without an expiry. Defaults to None, keeping them until deleted.
+    ///     max_memories_per_namespace (int | None, optional): Number of memories per namespace beyond which `MemoryStore.expire` evicts the least relevant ones. Defaults to None, keeping them all.
+    ///     audit_log (bool, optional): If True, records every addition, update and deletion of each store in an append-only log next to its index, see `MemoryStore.audit_log` and `MemoryStore.undo`. Defaults to False.
     ///
     /// Returns:
     ///     MemoryService: A new instance of the MemoryService.
     #[new]
-    #[pyo3(signature = (store_root_directory , writer_buffer_size = 15_000_000,cache_size = 10))]
-    pub fn new(store_root_directory: PathBuf, writer_buffer_size: usize, cache_size: u64) -> Self {
+    #[pyo3(signature = (store_root_directory , writer_buffer_size = 15_000_000,cache_size = 10, metadata_sidecar = false, auto_commit = None, default_ttl = None, max_memories_per_namespace = None, audit_log = false))]
+    pub fn new(
+        store_root_directory: PathBuf,
+        writer_buffer_size: usize,
+        cache_size: u64,
+        metadata_sidecar: bool,
+        auto_commit: Option<usize>,
+        default_ttl: Option<i64>,
+        max_memories_per_namespace: Option<usize>,
+        audit_log: bool,
+    ) -> Self {
         MemoryService {
             store_root_directory,
             index_cache: Cache::new(cache_size),
             index_writer_cache: Cache::new(cache_size),
+            sidecar_cache: Cache::new(cache_size),
+            audit_cache: Cache::new(cache_size),
             writer_buffer_size,
+            metadata_sidecar,
+            audit_log,
+            auto_commit,
+            policy: RetentionPolicy {
+                default_ttl,
+                max_per_namespace: max_memories_per_namespace,
+            },
         }
     }
 
@@ -86,6 +142,7 @@ impl MemoryService {
     ///
     /// Args:
     ///     store_name (str): The name of the store to get.
+    ///     actor (str | None, optional): Who the mutations made through the store are recorded as made by in the audit log, e.g. the name of a role. Defaults to None.
     ///
     /// Returns:
     ///     MemoryStore: A MemoryStore instance for the given store name.
@@ -93,10 +150,19 @@ impl MemoryService {
     /// Raises:
     ///     Exception: If the store name is invalid, if there's an error creating or opening the index,
     ///                or if there's an error creating the MemoryStore instance.
-    pub fn get_store(&self, store_name: IndexName) -> PyResult<MemoryStore> {
+    #[pyo3(signature = (store_name, actor = None))]
+    pub fn get_store(&self, store_name: IndexName, actor: Option<String>) -> PyResult<MemoryStore> {
         let index = self.get_index(store_name.clone())?;
 
-        MemoryStore::new(index, self.get_index_writer(store_name)?)
+        MemoryStore::new(
+            index,
+            self.get_index_writer(store_name.clone())?,
+            self.get_sidecar(store_name.clone())?,
+            self.get_audit_log(store_name)?,
+            self.auto_commit,
+            self.policy,
+            actor,
+        )
     }
 
     /// Lists all stores in the system.
@@ -0,0 +1,266 @@
+//! Optional SQLite sidecar holding the metadata of memories.
+//!
+//! The tantivy index is built for ranked full-text search, so exact lookups and ordered
+//! listings over it have to collect every document with an `AllQuery`. The sidecar mirrors
+//! the metadata of each memory (uuid, timestamps, access count, importance, sensitivity,
+//! namespace, expiry and tags) in a SQLite database next to the index and answers those queries with plain SQL.
+//!
+//! Changes are staged together with the index writer and applied once the index commits,
+//! so the sidecar only ever describes committed memories.
+
+use crate::memory::Memory;
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rusqlite::{Connection, params};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+const SCHEMA_SQL: &str = "
+CREATE TABLE IF NOT EXISTS memories (
+    uuid TEXT PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    last_accessed INTEGER NOT NULL,
+    access_count INTEGER NOT NULL,
+    importance INTEGER NOT NULL,
+    sensitivity TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    expires_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS tags (
+    uuid TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (uuid, tag)
+);
+CREATE INDEX IF NOT EXISTS tags_by_tag ON tags (tag);
+CREATE INDEX IF NOT EXISTS memories_by_timestamp ON memories (timestamp);
+CREATE INDEX IF NOT EXISTS memories_by_last_accessed ON memories (last_accessed);
+CREATE INDEX IF NOT EXISTS memories_by_access_count ON memories (access_count);
+CREATE INDEX IF NOT EXISTS memories_by_importance ON memories (importance);
+CREATE INDEX IF NOT EXISTS memories_by_namespace ON memories (namespace);
+";
+
+/// The attribute memories are listed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortKey {
+    Timestamp,
+    LastAccessed,
+    AccessCount,
+    Importance,
+}
+
+impl SortKey {
+    /// Name of the attribute, shared by the sidecar column and the index field.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Timestamp => "timestamp",
+            Self::LastAccessed => "last_accessed",
+            Self::AccessCount => "access_count",
+            Self::Importance => "importance",
+        }
+    }
+
+    /// Whether the index stores the attribute as an `i64` rather than a `u64`.
+    pub(crate) fn is_signed(self) -> bool {
+        matches!(self, Self::Timestamp | Self::LastAccessed)
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "timestamp" => Ok(Self::Timestamp),
+            "last_accessed" => Ok(Self::LastAccessed),
+            "access_count" => Ok(Self::AccessCount),
+            "importance" => Ok(Self::Importance),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown sort key `{s}`, expected one of `timestamp`, `last_accessed`, `access_count`, `importance`"
+            ))),
+        }
+    }
+}
+
+/// A change waiting for the index to commit.
+enum Change {
+    Upsert(Memory),
+    Delete(String),
+}
+
+/// SQLite mirror of the metadata of the memories of one index.
+pub struct MetadataSidecar {
+    connection: Mutex<Connection>,
+    pending: Mutex<Vec<Change>>,
+}
+
+impl MetadataSidecar {
+    /// Opens the sidecar database at the given path, creating its tables if needed.
+    pub fn open(path: &Path) -> PyResult<Self> {
+        let connection = Connection::open(path).into_pyresult()?;
+        connection.execute_batch(SCHEMA_SQL).into_pyresult()?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+            pending: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Stages the insertion or replacement of a memory.
+    pub fn stage_upsert(&self, memory: &Memory) {
+        self.stage(Change::Upsert(memory.clone()));
+    }
+
+    /// Stages the deletion of a memory.
+    pub fn stage_delete(&self, uuid: &str) {
+        self.stage(Change::Delete(uuid.to_string()));
+    }
+
+    fn stage(&self, change: Change) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(change);
+    }
+
+    /// Applies the staged changes, to be called once the index has committed them.
+    pub fn flush(&self) -> PyResult<()> {
+        let changes = std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let mut connection = self.connection.lock().into_pyresult()?;
+        let tx = connection.transaction().into_pyresult()?;
+        for change in &changes {
+            match change {
+                Change::Upsert(memory) => Self::upsert(&tx, memory)?,
+                Change::Delete(uuid) => Self::delete(&tx, uuid)?,
+            }
+        }
+        tx.commit().into_pyresult()
+    }
+
+    /// Replaces the whole content of the sidecar with the given memories.
+    pub fn rebuild(&self, memories: &[Memory]) -> PyResult<()> {
+        let mut connection = self.connection.lock().into_pyresult()?;
+        let tx = connection.transaction().into_pyresult()?;
+        tx.execute_batch("DELETE FROM tags; DELETE FROM memories;")
+            .into_pyresult()?;
+        for memory in memories {
+            Self::upsert(&tx, memory)?;
+        }
+        tx.commit().into_pyresult()
+    }
+
+    /// Counts the memories in the sidecar.
+    pub fn count(&self) -> PyResult<u64> {
+        self.connection
+            .lock()
+            .into_pyresult()?
+            .query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))
+            .into_pyresult()
+    }
+
+    /// Lists the uuids of the memories ordered by an attribute, ties broken by uuid.
+    ///
+    /// Args:
+    ///     key: The attribute to order by.
+    ///     descending: Whether to start with the highest values.
+    ///     limit: The maximum number of uuids to return.
+    ///     offset: The number of uuids to skip.
+    ///     tag: Only list memories carrying this tag, if given.
+    ///     namespace: Only list memories of this namespace, if given.
+    ///     now: The unix timestamp at which memories expire, expired ones are left out.
+    #[allow(clippy::too_many_arguments)]
+    pub fn list(
+        &self,
+        key: SortKey,
+        descending: bool,
+        limit: usize,
+        offset: usize,
+        tag: Option<&str>,
+        namespace: Option<&str>,
+        now: i64,
+    ) -> PyResult<Vec<String>> {
+        let direction = if descending { "DESC" } else { "ASC" };
+        let sql = format!(
+            "SELECT m.uuid FROM memories m {} (?4 IS NULL OR m.namespace = ?4) AND (m.expires_at IS NULL OR m.expires_at > ?5) ORDER BY m.{} {direction}, m.uuid {direction} LIMIT ?1 OFFSET ?2",
+            if tag.is_some() {
+                "JOIN tags t ON t.uuid = m.uuid AND t.tag = ?3 WHERE"
+            } else {
+                "WHERE ?3 IS NULL AND"
+            },
+            key.name(),
+        );
+        let connection = self.connection.lock().into_pyresult()?;
+        let mut statement = connection.prepare_cached(&sql).into_pyresult()?;
+        statement
+            .query_map(
+                params![limit as i64, offset as i64, tag, namespace, now],
+                |row| row.get(0),
+            )
+            .into_pyresult()?
+            .collect::<Result<Vec<String>, _>>()
+            .into_pyresult()
+    }
+
+    /// Lists the uuids of all memories, oldest first.
+    pub fn uuids(&self) -> PyResult<Vec<String>> {
+        let connection = self.connection.lock().into_pyresult()?;
+        let mut statement = connection
+            .prepare_cached("SELECT uuid FROM memories ORDER BY timestamp, uuid")
+            .into_pyresult()?;
+        statement
+            .query_map([], |row| row.get(0))
+            .into_pyresult()?
+            .collect::<Result<Vec<String>, _>>()
+            .into_pyresult()
+    }
+
+    fn upsert(connection: &Connection, memory: &Memory) -> PyResult<()> {
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO memories
+                 (uuid, timestamp, last_accessed, access_count, importance, sensitivity, namespace, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    memory.uuid,
+                    memory.timestamp,
+                    memory.last_accessed,
+                    memory.access_count as i64,
+                    memory.importance as i64,
+                    memory.sensitivity,
+                    memory.namespace,
+                    memory.expires_at,
+                ],
+            )
+            .into_pyresult()?;
+        connection
+            .execute("DELETE FROM tags WHERE uuid = ?1", params![memory.uuid])
+            .into_pyresult()?;
+        for tag in &memory.tags {
+            connection
+                .execute(
+                    "INSERT OR IGNORE INTO tags (uuid, tag) VALUES (?1, ?2)",
+                    params![memory.uuid, tag],
+                )
+                .into_pyresult()?;
+        }
+        Ok(())
+    }
+
+    fn delete(connection: &Connection, uuid: &str) -> PyResult<()> {
+        connection
+            .execute("DELETE FROM tags WHERE uuid = ?1", params![uuid])
+            .into_pyresult()?;
+        connection
+            .execute("DELETE FROM memories WHERE uuid = ?1", params![uuid])
+            .into_pyresult()?;
+        Ok(())
+    }
+}
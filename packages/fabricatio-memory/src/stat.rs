@@ -1,7 +1,23 @@
+use crate::constants::MAX_IMPORTANCE_SCORE;
+use crate::memory::Memory;
 use pyo3::{pyclass, pymethods};
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Width of the buckets of the importance histogram.
+const IMPORTANCE_BUCKET_WIDTH: u64 = 10;
+
+/// Labels of the buckets of the age histogram, with the age in days they end before.
+const AGE_BUCKETS: &[(&str, f64)] = &[
+    ("<1d", 1.0),
+    ("1d-7d", 7.0),
+    ("7d-30d", 30.0),
+    ("30d-90d", 90.0),
+    ("90d-365d", 365.0),
+    (">=365d", f64::INFINITY),
+];
 
 /// Memory statistics structure containing aggregated metrics about stored memories
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -16,6 +32,58 @@ pub struct MemoryStats {
     pub avg_access_count: f64,
     /// Average age of memories in days
     pub avg_age_days: f64,
+    /// Number of memories per importance bucket, as pairs of the lower bound of the bucket and
+    /// the count, the last bucket including MAX_IMPORTANCE_SCORE
+    #[serde(default)]
+    pub importance_histogram: Vec<(u64, u64)>,
+    /// Number of memories per age bucket, as pairs of a label such as `7d-30d` and the count
+    #[serde(default)]
+    pub age_histogram: Vec<(String, u64)>,
+    /// Number of memories carrying each tag, the most frequent first
+    #[serde(default)]
+    pub tag_frequency: Vec<(String, u64)>,
+}
+
+impl MemoryStats {
+    /// Fills the histograms from the memories the statistics cover.
+    pub(crate) fn with_histograms(mut self, memories: &[Memory], now: i64) -> Self {
+        let buckets = (MAX_IMPORTANCE_SCORE / IMPORTANCE_BUCKET_WIDTH) as usize;
+        let mut importance = vec![0; buckets];
+        let mut age = vec![0; AGE_BUCKETS.len()];
+        let mut tags: HashMap<&str, u64> = HashMap::new();
+
+        for memory in memories {
+            importance
+                [((memory.importance / IMPORTANCE_BUCKET_WIDTH) as usize).min(buckets - 1)] += 1;
+            let days = (now - memory.timestamp) as f64 / 86400.0;
+            age[AGE_BUCKETS
+                .iter()
+                .position(|(_, end)| days < *end)
+                .unwrap_or(AGE_BUCKETS.len() - 1)] += 1;
+            memory
+                .tags
+                .iter()
+                .for_each(|tag| *tags.entry(tag.as_str()).or_default() += 1);
+        }
+
+        self.importance_histogram = importance
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (i as u64 * IMPORTANCE_BUCKET_WIDTH, count))
+            .collect();
+        self.age_histogram = AGE_BUCKETS
+            .iter()
+            .zip(age)
+            .map(|((label, _), count)| (label.to_string(), count))
+            .collect();
+        let mut tags: Vec<(String, u64)> = tags
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        self.tag_frequency = tags;
+        self
+    }
 }
 
 #[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
@@ -26,9 +94,31 @@ impl MemoryStats {
     /// Returns:
     ///     A human-readable string representation of the MemoryStats.
     fn display(&self) -> String {
+        let pairs = |pairs: Vec<String>| pairs.join(", ");
         format!(
-            "Total Memories: {}\nAverage Importance: {}\nAverage Access Count: {}\nAverage Age (Days): {}",
-            self.total_memories, self.avg_importance, self.avg_access_count, self.avg_age_days
+            "Total Memories: {}\nAverage Importance: {}\nAverage Access Count: {}\nAverage Age (Days): {}\nImportance Histogram: {}\nAge Histogram: {}\nTag Frequency: {}",
+            self.total_memories,
+            self.avg_importance,
+            self.avg_access_count,
+            self.avg_age_days,
+            pairs(
+                self.importance_histogram
+                    .iter()
+                    .map(|(lower, count)| format!("{lower}+: {count}"))
+                    .collect()
+            ),
+            pairs(
+                self.age_histogram
+                    .iter()
+                    .map(|(label, count)| format!("{label}: {count}"))
+                    .collect()
+            ),
+            pairs(
+                self.tag_frequency
+                    .iter()
+                    .map(|(tag, count)| format!("{tag}: {count}"))
+                    .collect()
+            ),
         )
     }
 }
@@ -1,23 +1,78 @@
-use crate::constants::{FIELDS, MAX_IMPORTANCE_SCORE, field_names};
-use crate::memory::Memory;
+use crate::audit::{AuditEntry, AuditFilter, AuditLog};
+use crate::cluster::{MemoryCluster, term_clusters};
+use crate::consolidate::near_duplicate_clusters;
+use crate::constants::{
+    FIELDS, MAX_IMPORTANCE_SCORE, MIN_IMPORTANCE_SCORE, SCHEMA, SNAPSHOT_WRITER_BUFFER_SIZE,
+    field_names,
+};
+use crate::filter::MemoryFilter;
+use crate::markdown::{GroupBy, from_markdown, markdown_files, note_path, to_markdown};
+use crate::memory::{Memory, MemoryDraft, Metadata};
+use crate::query::MemoryQuery;
+use crate::retention::RetentionPolicy;
+use crate::sensitivity::Sensitivity;
+use crate::sidecar::{MetadataSidecar, SortKey};
 use crate::stat::MemoryStats;
 use crate::utils::{
-    add_memory_inner, cast_into_items, delete_memory_inner, extract_avg, extract_memory,
-    importance_term_of, timestamp_term_of, update_memory_inner, uuid_query_of,
+    add_memory_inner, cast_into_items, delete_memory_inner, expired_query_of, extract_avg,
+    extract_memory, importance_term_of, is_valid_index_dir, max_sensitivity_query_of,
+    namespace_query_of, timestamp_term_of, update_memory_inner, uuid_query_of,
 };
+use crate::vector::{SearchMode, rank_by_similarity, reciprocal_rank_fusion};
 use chrono::Utc;
 use error_mapping::AsPyErr;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
+use pythonize::depythonize;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
 use tantivy::aggregation::AggregationCollector;
 use tantivy::aggregation::agg_req::Aggregations;
 use tantivy::aggregation::agg_result::{AggregationResult, MetricResult};
-use tantivy::collector::TopDocs;
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
 use tantivy::query::*;
-use tantivy::{Index, IndexReader, IndexWriter, Order, ReloadPolicy, Score, Searcher, doc};
+use tantivy::schema::{IndexRecordOption, OwnedValue};
+use tantivy::{Index, IndexReader, IndexWriter, Order, ReloadPolicy, Score, Searcher, Term, doc};
+
+/// The index writer shared by every store opened on an index, kept alive between operations.
+pub struct SharedWriter {
+    writer: Mutex<IndexWriter>,
+    /// Number of changes staged in the writer since its last commit
+    pending: AtomicUsize,
+}
+
+impl SharedWriter {
+    pub fn new(writer: IndexWriter) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            pending: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// What `add_memory` returns: the UUID of the new memory, with the existing memories most
+/// similar to it when asked for.
+#[derive(Debug, IntoPyObject)]
+pub enum AddedMemory {
+    Id(String),
+    WithRelated(String, Vec<String>),
+}
+
+#[cfg(feature = "stubgen")]
+impl pyo3_stub_gen::PyStubType for AddedMemory {
+    fn type_output() -> pyo3_stub_gen::TypeInfo {
+        use pyo3_stub_gen::PyStubType;
+        String::type_output() | <(String, Vec<String>)>::type_output()
+    }
+}
 
 /// MemoryStore is a struct that provides an interface for storing, retrieving, and searching memories in a Tantivy search index.
 ///
@@ -35,13 +90,31 @@ use tantivy::{Index, IndexReader, IndexWriter, Order, ReloadPolicy, Score, Searc
 pub struct MemoryStore {
     index: Arc<Index>,
     /// tantivy allows only one writer at a time
-    writer: Arc<Mutex<IndexWriter>>,
+    writer: Arc<SharedWriter>,
     reader: IndexReader,
+    /// Mirrors the metadata of committed memories for listings, if enabled
+    sidecar: Option<Arc<MetadataSidecar>>,
+    /// Records every mutation before it is staged, if enabled
+    audit: Option<Arc<AuditLog>>,
+    /// Who the mutations are recorded as made by, if known
+    actor: Option<String>,
+    /// Number of staged changes after which they are committed without an explicit write
+    auto_commit: Option<usize>,
+    /// Expiry and eviction rules of the memories
+    policy: RetentionPolicy,
 }
 
 impl MemoryStore {
-    pub fn new(index: Arc<Index>, index_writer: Arc<Mutex<IndexWriter>>) -> PyResult<Self> {
-        Ok(Self {
+    pub fn new(
+        index: Arc<Index>,
+        index_writer: Arc<SharedWriter>,
+        sidecar: Option<Arc<MetadataSidecar>>,
+        audit: Option<Arc<AuditLog>>,
+        auto_commit: Option<usize>,
+        policy: RetentionPolicy,
+        actor: Option<String>,
+    ) -> PyResult<Self> {
+        let store = Self {
             reader: index
                 .reader_builder()
                 .reload_policy(ReloadPolicy::Manual)
@@ -49,16 +122,35 @@ impl MemoryStore {
                 .into_pyresult()?,
             writer: index_writer,
             index,
-        })
+            sidecar,
+            audit,
+            actor,
+            auto_commit,
+            policy,
+        };
+        // A sidecar created for an existing index, or left behind by a crash between the
+        // index commit and the sidecar flush, is rebuilt from the index.
+        if let Some(sidecar) = &store.sidecar
+            && sidecar.count()? != store.num_docs()
+        {
+            sidecar.rebuild(&store.all_memories()?)?;
+        }
+        Ok(store)
     }
     #[inline]
     fn searcher(&self) -> Searcher {
         self.reader.searcher()
     }
 
+    /// Counts the committed documents of the index, expired memories included.
+    #[inline]
+    fn num_docs(&self) -> u64 {
+        self.searcher().num_docs()
+    }
+
     #[inline(always)]
     fn access_writer(&'_ self) -> PyResult<MutexGuard<'_, IndexWriter>> {
-        self.writer.lock().into_pyresult()
+        self.writer.writer.lock().into_pyresult()
     }
 
     fn top_k<Q: Query>(&self, term_query: Q, k: usize) -> PyResult<Vec<(Score, Memory)>> {
@@ -74,14 +166,364 @@ impl MemoryStore {
         self.top_k(term_query, 1).map(|mut vec| vec.pop())
     }
 
+    /// The UUIDs of the committed memories of the namespace of a memory whose contents are the
+    /// most similar to its content, by a MoreLikeThis query, the most similar first.
+    fn related_to(&self, memory: &Memory, k: usize) -> PyResult<Vec<String>> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_document_fields(vec![(
+                FIELDS.content,
+                vec![OwnedValue::Str(memory.content.clone())],
+            )]);
+        let query = Self::restrict(Box::new(query), None, Some(&memory.namespace));
+        Ok(self
+            .top_k(query, k)?
+            .into_iter()
+            .map(|(_, related)| related.uuid)
+            .collect())
+    }
+
+    /// Commits the staged changes if asked to, or if they reached the auto-commit threshold.
     #[inline]
     fn write_inner(&self, mut w: MutexGuard<IndexWriter>, write_now: bool) -> PyResult<()> {
-        if write_now {
+        let due = self
+            .auto_commit
+            .is_some_and(|every| self.writer.pending.load(Ordering::Relaxed) >= every);
+        if write_now || due {
             w.commit().into_pyresult()?;
-            self.reader.reload().into_pyresult()
+            self.writer.pending.store(0, Ordering::Relaxed);
+            self.reader.reload().into_pyresult()?;
+            if let Some(sidecar) = &self.sidecar {
+                sidecar.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the changes, given as the memory before and after each, to the audit log if
+    /// enabled. Called before the changes are staged, with the writer locked.
+    fn record(&self, changes: &[(Option<&Memory>, Option<&Memory>)]) -> PyResult<()> {
+        match &self.audit {
+            Some(audit) => audit.append(self.actor.as_deref(), changes),
+            None => Ok(()),
+        }
+    }
+
+    /// The audit log of the store, failing if the service does not keep one.
+    fn audit(&self) -> PyResult<&AuditLog> {
+        self.audit.as_deref().ok_or_else(|| {
+            PyRuntimeError::new_err(
+                "The audit log is disabled, enable it with `MemoryService(audit_log=True)`",
+            )
+        })
+    }
+
+    /// Stages the addition of a new memory in the writer and the sidecar.
+    fn stage_add(&self, w: &IndexWriter, memory: &Memory) -> PyResult<()> {
+        add_memory_inner(w, memory)?;
+        self.writer.pending.fetch_add(1, Ordering::Relaxed);
+        if let Some(sidecar) = &self.sidecar {
+            sidecar.stage_upsert(memory);
+        }
+        Ok(())
+    }
+
+    /// Stages the replacement of a memory in the writer and the sidecar.
+    fn stage_update(&self, w: &IndexWriter, memory: &Memory) -> PyResult<()> {
+        update_memory_inner(w, memory)?;
+        self.writer.pending.fetch_add(1, Ordering::Relaxed);
+        if let Some(sidecar) = &self.sidecar {
+            sidecar.stage_upsert(memory);
+        }
+        Ok(())
+    }
+
+    /// Stages the deletion of a memory in the writer and the sidecar.
+    fn stage_delete(&self, w: &IndexWriter, uuid: &str) {
+        delete_memory_inner(w, uuid);
+        self.writer.pending.fetch_add(1, Ordering::Relaxed);
+        if let Some(sidecar) = &self.sidecar {
+            sidecar.stage_delete(uuid);
+        }
+    }
+
+    /// Stages the deletion of every committed memory matching a query, returning their number.
+    ///
+    /// The memories are deleted by query, so none is loaded from the index unless the sidecar
+    /// needs their uuids or the audit log their contents.
+    fn stage_delete_matching(&self, w: &IndexWriter, query: Box<dyn Query>) -> PyResult<usize> {
+        let searcher = self.searcher();
+        let count = searcher.search(&query, &Count).into_pyresult()?;
+        if count == 0 {
+            return Ok(0);
+        }
+        if self.sidecar.is_some() || self.audit.is_some() {
+            let deleted = searcher
+                .search(&query, &DocSetCollector)
+                .into_pyresult()?
+                .into_iter()
+                .map(|address| searcher.doc::<Memory>(address).into_pyresult())
+                .collect::<PyResult<Vec<_>>>()?;
+            self.record(
+                &deleted
+                    .iter()
+                    .map(|memory| (Some(memory), None))
+                    .collect::<Vec<_>>(),
+            )?;
+            if let Some(sidecar) = &self.sidecar {
+                deleted
+                    .iter()
+                    .for_each(|memory| sidecar.stage_delete(&memory.uuid));
+            }
+        }
+        w.delete_query(query).into_pyresult()?;
+        self.writer.pending.fetch_add(count, Ordering::Relaxed);
+        Ok(count)
+    }
+
+    /// Retrieves every memory without updating access statistics.
+    fn all_memories(&self) -> PyResult<Vec<Memory>> {
+        match self.num_docs() as usize {
+            0 => Ok(vec![]),
+            count => self.top_k(AllQuery, count).map(extract_memory),
+        }
+    }
+
+    /// Retrieves every unexpired memory, at most as sensitive as the given label and in the
+    /// given namespace, if any.
+    fn live_memories(
+        &self,
+        max_sensitivity: Option<Sensitivity>,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<Memory>> {
+        match self.num_docs() as usize {
+            0 => Ok(vec![]),
+            count => self
+                .top_k(
+                    Self::restrict(Box::new(AllQuery), max_sensitivity, namespace),
+                    count,
+                )
+                .map(extract_memory),
+        }
+    }
+
+    /// Retrieves the memories with the given uuids, in the same order.
+    fn memories_by_uuids(&self, uuids: &[String]) -> PyResult<Vec<Memory>> {
+        if uuids.is_empty() {
+            return Ok(vec![]);
+        }
+        let query = BooleanQuery::new(
+            uuids
+                .iter()
+                .map(|uuid| {
+                    (
+                        Occur::Should,
+                        Box::new(uuid_query_of(uuid)) as Box<dyn Query>,
+                    )
+                })
+                .collect(),
+        );
+        let mut found: HashMap<String, Memory> = self
+            .top_k(query, uuids.len())?
+            .into_iter()
+            .map(|(_, memory)| (memory.uuid.clone(), memory))
+            .collect();
+        Ok(uuids.iter().filter_map(|uuid| found.remove(uuid)).collect())
+    }
+
+    /// Lists memories ordered by a metadata attribute without updating access statistics.
+    ///
+    /// Served by the sidecar when enabled, by the fast fields of the index otherwise.
+    fn list_inner(
+        &self,
+        key: SortKey,
+        descending: bool,
+        limit: usize,
+        offset: usize,
+        tag: Option<&str>,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<Memory>> {
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        if let Some(sidecar) = &self.sidecar {
+            return self.memories_by_uuids(&sidecar.list(
+                key,
+                descending,
+                limit,
+                offset,
+                tag,
+                namespace,
+                Utc::now().timestamp(),
+            )?);
+        }
+
+        let query: Box<dyn Query> = match tag {
+            Some(tag) => Box::new(TermQuery::new(
+                Term::from_field_text(FIELDS.tags, tag),
+                IndexRecordOption::Basic,
+            )),
+            None => Box::new(AllQuery),
+        };
+        self.top_by_field(
+            &*Self::restrict(query, None, namespace),
+            key,
+            descending,
+            limit,
+            offset,
+        )
+    }
+
+    /// Retrieves the memories matching a query ordered by a fast field instead of by score,
+    /// so only the requested page is collected and loaded from the index.
+    fn top_by_field(
+        &self,
+        query: &dyn Query,
+        key: SortKey,
+        descending: bool,
+        limit: usize,
+        offset: usize,
+    ) -> PyResult<Vec<Memory>> {
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        let order = if descending { Order::Desc } else { Order::Asc };
+        let top_docs = TopDocs::with_limit(limit).and_offset(offset);
+        let searcher = self.searcher();
+        if key.is_signed() {
+            searcher
+                .search(
+                    query,
+                    &top_docs.order_by_fast_field::<i64>(key.name(), order),
+                )
+                .into_pyresult()
+                .map(|seq| extract_memory(cast_into_items(searcher, seq)))
         } else {
-            Ok(())
+            searcher
+                .search(query, &top_docs.order_by_u64_field(key.name(), order))
+                .into_pyresult()
+                .map(|seq| extract_memory(cast_into_items(searcher, seq)))
+        }
+    }
+
+    /// Restricts a query to unexpired memories, at most as sensitive as the given label and in
+    /// the given namespace, if any.
+    fn restrict(
+        query: Box<dyn Query>,
+        max_sensitivity: Option<Sensitivity>,
+        namespace: Option<&str>,
+    ) -> Box<dyn Query> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![
+            (Occur::Must, query),
+            (
+                Occur::MustNot,
+                Box::new(expired_query_of(Utc::now().timestamp())),
+            ),
+        ];
+        if let Some(max_sensitivity) = max_sensitivity {
+            clauses.push((
+                Occur::Must,
+                Box::new(max_sensitivity_query_of(max_sensitivity)),
+            ));
+        }
+        if let Some(namespace) = namespace {
+            clauses.push((Occur::Must, Box::new(namespace_query_of(namespace))));
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    /// Ranks memories matching a query string by BM25, optionally boosted by recency.
+    ///
+    /// With a filter, every match is ranked so the filter does not starve the results.
+    fn keyword_top_k(
+        &self,
+        query_str: &str,
+        k: usize,
+        boost_recent: bool,
+        max_sensitivity: Option<Sensitivity>,
+        namespace: Option<&str>,
+        filter: Option<&MemoryFilter>,
+    ) -> PyResult<Vec<Memory>> {
+        let query_parser = QueryParser::for_index(&self.index, vec![FIELDS.content, FIELDS.tags]);
+        let query = Self::restrict(
+            query_parser.parse_query(query_str).into_pyresult()?,
+            max_sensitivity,
+            namespace,
+        );
+        let limit = match filter {
+            Some(_) => (self.num_docs() as usize).max(1),
+            None => k * 2,
+        };
+
+        let mut top_docs = self
+            .top_k(query, limit)?
+            .into_iter()
+            .filter(|(_, memory)| filter.is_none_or(|filter| filter.matches(memory)))
+            .map(|(score, memory)| {
+                (
+                    score as f64
+                        + if boost_recent {
+                            memory.calculate_relevance_score(0.01)
+                        } else {
+                            0.0
+                        },
+                    memory,
+                )
+            })
+            .collect::<Vec<(f64, Memory)>>();
+
+        if boost_recent {
+            top_docs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        Ok(top_docs
+            .into_iter()
+            .take(k)
+            .map(|(_, memory)| memory)
+            .collect())
+    }
+
+    /// Ranks the memories carrying an embedding by cosine similarity to a query vector.
+    ///
+    /// Embeddings are compared exhaustively, which is fine for the size of an agent memory.
+    fn vector_top_k(
+        &self,
+        query_embedding: &[f32],
+        k: usize,
+        max_sensitivity: Option<Sensitivity>,
+        namespace: Option<&str>,
+        filter: Option<&MemoryFilter>,
+    ) -> PyResult<Vec<Memory>> {
+        let mut memories = self.live_memories(max_sensitivity, namespace)?;
+        if let Some(filter) = filter {
+            memories.retain(|memory| filter.matches(memory));
         }
+        Ok(rank_by_similarity(memories, query_embedding, k))
+    }
+
+    /// Replaces the content of secret memories with the output of the redaction callback.
+    ///
+    /// Must run after the access updates are staged, so redacted content is never persisted.
+    fn redact_secrets(
+        memories: Vec<Memory>,
+        redact: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Vec<Memory>> {
+        let Some(redact) = redact else {
+            return Ok(memories);
+        };
+        memories
+            .into_iter()
+            .map(|mut memory| {
+                if memory.sensitivity_level() == Sensitivity::Secret {
+                    memory.content = redact.call1((memory.clone(),))?.extract()?;
+                }
+                Ok(memory)
+            })
+            .collect()
     }
 
     // --- Helper function for batch access updates ---
@@ -110,7 +552,7 @@ impl MemoryStore {
             })
             .collect::<Vec<Memory>>()
             .iter()
-            .try_for_each(|mem| update_memory_inner(&w, mem))?;
+            .try_for_each(|mem| self.stage_update(&w, mem))?;
 
         // Only flush to disk if `write` is true
         self.write_inner(w, write)?;
@@ -128,26 +570,55 @@ impl MemoryStore {
     ///     importance (int): The importance score of the memory.
     ///     tags (list[str]): A list of tags associated with the memory.
     ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///     sensitivity (str, optional): The access control label: "public", "internal" or "secret". Defaults to "public".
+    ///     embedding (list[float] | None, optional): The embedding vector of the content, used by vector and hybrid search. Defaults to None.
+    ///     namespace (str | None, optional): The namespace to add the memory to. Defaults to None, adding it to the "default" namespace.
+    ///     expires_at (int | None, optional): The unix timestamp after which the memory is no longer returned. Defaults to None, using the default lifetime of the service if any.
+    ///     metadata (dict[str, bool | int | float | str] | None, optional): Arbitrary key/value metadata, matched by the `metadata.<key>` paths of search filters. Defaults to None.
+    ///     related (int | None, optional): The number of existing memories similar to the new one to return along with its ID, found by a MoreLikeThis query on the contents of the committed memories of its namespace, so that near-duplicates can be linked or merged. Defaults to None, returning the ID alone.
     ///
     /// Returns:
-    ///     str: The UUID of the newly added memory.
+    ///     str | tuple[str, list[str]]: The UUID of the newly added memory, followed by the UUIDs of the at most `related` most similar existing memories, the most similar first, if `related` is given.
     ///
     /// Raises:
+    ///     ValueError: If the sensitivity label is unknown or the namespace is empty.
     ///     Exception: If there is an error adding the memory or writing to the index.
-    #[pyo3(signature = (content, importance, tags, write = false))]
+    #[pyo3(signature = (content, importance, tags, write = false, sensitivity = "public", embedding = None, namespace = None, expires_at = None, metadata = None, related = None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn add_memory(
         &self,
         content: String,
         importance: u64,
         tags: Vec<String>,
         write: bool,
-    ) -> PyResult<String> {
-        let memory = Memory::new(content, importance, tags)?;
+        sensitivity: &str,
+        embedding: Option<Vec<f32>>,
+        namespace: Option<String>,
+        expires_at: Option<i64>,
+        metadata: Option<Metadata>,
+        related: Option<usize>,
+    ) -> PyResult<AddedMemory> {
+        let mut memory = Memory::new(
+            content,
+            importance,
+            tags,
+            sensitivity.parse()?,
+            embedding,
+            namespace,
+        )?;
+        memory.expires_at = self.policy.expiry_of(expires_at, memory.timestamp);
+        memory.metadata = metadata.unwrap_or_default();
+        // Looked up before the memory is staged, as a commit would make it its own best match.
+        let related = related.map(|k| self.related_to(&memory, k)).transpose()?;
         let w = self.access_writer()?;
 
-        add_memory_inner(&w, &memory)?;
+        self.record(&[(None, Some(&memory))])?;
+        self.stage_add(&w, &memory)?;
         self.write_inner(w, write)?;
-        Ok(memory.uuid)
+        Ok(match related {
+            Some(related) => AddedMemory::WithRelated(memory.uuid, related),
+            None => AddedMemory::Id(memory.uuid),
+        })
     }
 
     /// Writes all pending changes to disk.
@@ -158,7 +629,56 @@ impl MemoryStore {
     /// Raises:
     ///     Exception: If there is an error committing the changes.
     pub fn write(&self) -> PyResult<()> {
-        self.write_inner(self.writer.lock().into_pyresult()?, true)
+        self.write_inner(self.access_writer()?, true)
+    }
+
+    /// Adds many memories at once, staging them under a single lock of the writer.
+    ///
+    /// Much faster than repeated calls to `add_memory` for bulk ingestion, especially when
+    /// committing only once at the end.
+    ///
+    /// Args:
+    ///     memories (list[dict]): The memories to add, each a mapping with `content`, `importance` and optionally `tags`, `sensitivity`, `embedding`, `namespace`, `expires_at` and `metadata`, e.g. dumped `Note` models.
+    ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///     namespace (str | None, optional): The namespace of the memories that do not name one. Defaults to None, adding them to the "default" namespace.
+    ///
+    /// Returns:
+    ///     list[str]: The UUIDs of the added memories, in the same order.
+    ///
+    /// Raises:
+    ///     ValueError: If a memory is malformed, more important than the maximum, carries an unknown sensitivity label or an empty namespace. Nothing is added then.
+    ///     Exception: If there is an error adding the memories or writing to the index.
+    #[pyo3(signature = (memories, write = false, namespace = None))]
+    pub fn add_memories_bulk(
+        &self,
+        memories: &Bound<'_, PyAny>,
+        write: bool,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<String>> {
+        let memories = depythonize::<Vec<MemoryDraft>>(memories)
+            .map_err(|e| PyValueError::new_err(format!("Invalid memory: {e}")))?
+            .into_iter()
+            .map(|draft| draft.into_memory(namespace, &self.policy))
+            .collect::<PyResult<Vec<Memory>>>()?;
+        let w = self.access_writer()?;
+
+        self.record(
+            &memories
+                .iter()
+                .map(|memory| (None, Some(memory)))
+                .collect::<Vec<_>>(),
+        )?;
+        memories
+            .iter()
+            .try_for_each(|memory| self.stage_add(&w, memory))?;
+        self.write_inner(w, write)?;
+        Ok(memories.into_iter().map(|memory| memory.uuid).collect())
+    }
+
+    /// The number of changes staged since the last commit, not yet visible to searches.
+    #[getter]
+    pub fn pending_changes(&self) -> usize {
+        self.writer.pending.load(Ordering::Relaxed)
     }
 
     /// Retrieves a memory by its ID and updates its access count.
@@ -168,16 +688,18 @@ impl MemoryStore {
     ///     write (bool, optional): If True, commits the access update to disk immediately. Defaults to False.
     ///
     /// Returns:
-    ///     Memory | None: The retrieved Memory object, or None if not found.
+    ///     Memory | None: The retrieved Memory object, or None if not found or expired.
     ///
     /// Raises:
     ///     Exception: If there is an error retrieving the memory or updating the index.
     #[pyo3(signature = (uuid, write = false))]
     pub fn get_memory(&self, uuid: &str, write: bool) -> PyResult<Option<Memory>> {
-        if let Some((_, mut memory)) = self.top(uuid_query_of(uuid))? {
+        if let Some((_, mut memory)) = self.top(uuid_query_of(uuid))?
+            && !memory.is_expired(Utc::now().timestamp())
+        {
             memory.update_access();
             let w = self.access_writer()?;
-            update_memory_inner(&w, &memory)?;
+            self.stage_update(&w, &memory)?;
             self.write_inner(w, write)?;
             Ok(Some(memory))
         } else {
@@ -193,13 +715,19 @@ impl MemoryStore {
     ///     importance (int | None, optional): The new importance score. Defaults to None.
     ///     tags (list[str] | None, optional): The new list of tags. Defaults to None.
     ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///     sensitivity (str | None, optional): The new access control label. Defaults to None.
+    ///     embedding (list[float] | None, optional): The new embedding vector. Defaults to None, which drops the stored embedding if the content changes, as it would no longer match.
+    ///     expires_at (int | None, optional): The new expiry as a unix timestamp. Defaults to None, keeping the current one.
+    ///     metadata (dict[str, bool | int | float | str] | None, optional): The new metadata, replacing the current one. Defaults to None, keeping the current one.
     ///
     /// Returns:
     ///     bool: True if the memory was found and updated, False otherwise.
     ///
     /// Raises:
+    ///     ValueError: If the sensitivity label is unknown.
     ///     Exception: If there is an error updating the memory or writing to the index.
-    #[pyo3(signature = (uuid, content = None, importance = None, tags = None, write = false))]
+    #[pyo3(signature = (uuid, content = None, importance = None, tags = None, write = false, sensitivity = None, embedding = None, expires_at = None, metadata = None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn update_memory(
         &self,
         uuid: &str,
@@ -207,12 +735,21 @@ impl MemoryStore {
         importance: Option<u64>,
         tags: Option<Vec<String>>,
         write: bool,
+        sensitivity: Option<&str>,
+        embedding: Option<Vec<f32>>,
+        expires_at: Option<i64>,
+        metadata: Option<Metadata>,
     ) -> PyResult<bool> {
+        let sensitivity = sensitivity.map(str::parse::<Sensitivity>).transpose()?;
         if let Some((_, mut memory)) = self.top(uuid_query_of(uuid))? {
+            let before = memory.clone();
             let mut updated = false;
 
-            if let Some(new_content) = content {
+            if let Some(new_content) = content
+                && new_content != memory.content
+            {
                 memory.content = new_content.to_string();
+                memory.embedding = None;
                 updated = true;
             }
 
@@ -226,9 +763,30 @@ impl MemoryStore {
                 updated = true;
             }
 
+            if let Some(new_sensitivity) = sensitivity {
+                memory.sensitivity = new_sensitivity.to_string();
+                updated = true;
+            }
+
+            if embedding.is_some() {
+                memory.embedding = embedding;
+                updated = true;
+            }
+
+            if expires_at.is_some() {
+                memory.expires_at = expires_at;
+                updated = true;
+            }
+
+            if let Some(new_metadata) = metadata {
+                memory.metadata = new_metadata;
+                updated = true;
+            }
+
             if updated {
                 let w = self.access_writer()?;
-                update_memory_inner(&w, &memory)?;
+                self.record(&[(Some(&before), Some(&memory))])?;
+                self.stage_update(&w, &memory)?;
                 self.write_inner(w, write)?;
             }
 
@@ -240,6 +798,8 @@ impl MemoryStore {
 
     /// Deletes a memory by its ID.
     ///
+    /// The deletion is recorded in the audit log if the service keeps one and the memory exists.
+    ///
     /// Args:
     ///     uuid (str): The unique identifier of the memory to delete.
     ///     write (bool, optional): If True, commits the deletion to disk immediately. Defaults to False.
@@ -251,63 +811,134 @@ impl MemoryStore {
     ///     Exception: If there is an error deleting the memory or writing to the index.
     #[pyo3(signature = (uuid, write = false))]
     pub fn delete_memory(&self, uuid: &str, write: bool) -> PyResult<bool> {
+        // Only the deletions of existing memories are worth recording.
+        let deleted = if self.audit.is_some() {
+            self.top(uuid_query_of(uuid))?.map(|(_, memory)| memory)
+        } else {
+            None
+        };
         let w = self.access_writer()?;
-        delete_memory_inner(&w, uuid);
+        self.record(&[(deleted.as_ref(), None)])?;
+        self.stage_delete(&w, uuid);
         self.write_inner(w, write)?;
         Ok(true)
     }
 
     /// Searches memories by query string with optional recency boosting.
     ///
+    /// The `keyword` mode ranks memories by BM25 over their content and tags. The `vector`
+    /// mode ranks the memories carrying an embedding by cosine similarity to `query_embedding`,
+    /// so paraphrased queries still recall them. The `hybrid` mode fuses both rankings by
+    /// reciprocal rank fusion.
+    ///
     /// Args:
-    ///     query_str (str): The search query string.
+    ///     query_str (str): The search query string, ignored in `vector` mode.
     ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
-    ///     boost_recent (bool, optional): If True, boosts the score of more recent memories. Defaults to False.
+    ///     boost_recent (bool, optional): If True, boosts the score of more recent memories in the keyword ranking. Defaults to False.
     ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+    ///     redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+    ///     mode (str, optional): "keyword", "vector" or "hybrid". Defaults to "keyword".
+    ///     query_embedding (list[float] | None, optional): The embedding vector of the query, required by the `vector` and `hybrid` modes. Defaults to None.
+    ///     namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
+    ///     filter (str | None, optional): A filter expression the memories must satisfy, such as `metadata.project == "x" AND importance > 50`. Defaults to None.
     ///
     /// Returns:
     ///     list[Memory]: A list of matching Memory objects, sorted by relevance.
     ///
     /// Raises:
+    ///     ValueError: If the sensitivity label or the mode is unknown, the mode requires a missing query embedding, or the filter is invalid.
     ///     Exception: If there is an error parsing the query or searching the index.
-    #[pyo3(signature = (query_str, top_k = 20, boost_recent = false, write = false))]
+    #[pyo3(signature = (query_str, top_k = 20, boost_recent = false, write = false, max_sensitivity = None, redact = None, mode = "keyword", query_embedding = None, namespace = None, filter = None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn search_memories(
         &self,
         query_str: &str,
         top_k: usize,
         boost_recent: bool,
         write: bool,
+        max_sensitivity: Option<&str>,
+        redact: Option<&Bound<'_, PyAny>>,
+        mode: &str,
+        query_embedding: Option<Vec<f32>>,
+        namespace: Option<&str>,
+        filter: Option<&str>,
     ) -> PyResult<Vec<Memory>> {
-        let query_parser = QueryParser::for_index(&self.index, vec![FIELDS.content, FIELDS.tags]);
-        let query = query_parser.parse_query(query_str).into_pyresult()?;
-
-        let mut top_docs = self
-            .top_k(query, top_k * 2)?
-            .into_iter()
-            .map(|(score, memory)| {
-                (
-                    score as f64
-                        + if boost_recent {
-                            memory.calculate_relevance_score(0.01)
-                        } else {
-                            0.0
-                        },
-                    memory,
-                )
-            })
-            .collect::<Vec<(f64, Memory)>>();
+        let max_sensitivity = max_sensitivity.map(str::parse::<Sensitivity>).transpose()?;
+        let mode = mode.parse::<SearchMode>()?;
+        let filter = filter.map(str::parse::<MemoryFilter>).transpose()?;
+        let filter = filter.as_ref();
+        let retrieved_memories = match (mode, query_embedding) {
+            (SearchMode::Keyword, _) => self.keyword_top_k(
+                query_str,
+                top_k,
+                boost_recent,
+                max_sensitivity,
+                namespace,
+                filter,
+            )?,
+            (SearchMode::Vector, Some(query_embedding)) => {
+                self.vector_top_k(&query_embedding, top_k, max_sensitivity, namespace, filter)?
+            }
+            (SearchMode::Hybrid, Some(query_embedding)) => reciprocal_rank_fusion(
+                vec![
+                    self.keyword_top_k(
+                        query_str,
+                        top_k * 2,
+                        boost_recent,
+                        max_sensitivity,
+                        namespace,
+                        filter,
+                    )?,
+                    self.vector_top_k(
+                        &query_embedding,
+                        top_k * 2,
+                        max_sensitivity,
+                        namespace,
+                        filter,
+                    )?,
+                ],
+                top_k,
+            ),
+            (_, None) => {
+                return Err(PyValueError::new_err(
+                    "`query_embedding` is required by the `vector` and `hybrid` search modes",
+                ));
+            }
+        };
 
-        if boost_recent {
-            top_docs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        }
+        Self::redact_secrets(
+            self.update_access_and_write_batch(retrieved_memories, write)?,
+            redact,
+        )
+    }
 
-        let retrieved_memories: Vec<Memory> = top_docs
-            .into_iter()
-            .take(top_k)
-            .map(|(_, memory)| memory)
-            .collect();
+    /// Searches memories with a structured `MemoryQuery`.
+    ///
+    /// Args:
+    ///     query (MemoryQuery): The structured query to run.
+    ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
+    ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+    ///
+    /// Returns:
+    ///     list[Memory]: A list of matching Memory objects, sorted by relevance.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error building the query or searching the index.
+    #[pyo3(signature = (query, top_k = 20, write = false, redact = None))]
+    pub fn query(
+        &self,
+        query: &MemoryQuery,
+        top_k: usize,
+        write: bool,
+        redact: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Vec<Memory>> {
+        let memories = self
+            .top_k(Self::restrict(query.build(&self.index)?, None, None), top_k)
+            .map(extract_memory)?;
 
-        self.update_access_and_write_batch(retrieved_memories, write)
+        Self::redact_secrets(self.update_access_and_write_batch(memories, write)?, redact)
     }
 
     /// Searches memories by specific tags.
@@ -316,139 +947,691 @@ impl MemoryStore {
     ///     tags (list[str]): A list of tags to search for. Memories matching any of these tags will be returned.
     ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
     ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+    ///     redact (Callable[[Memory], str] | None, optional): Called with each secret memory returned, its result replaces the content. Defaults to None.
+    ///
+    ///     namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
     ///
     /// Returns:
     ///     list[Memory]: A list of matching Memory objects.
     ///
     /// Raises:
+    ///     ValueError: If the sensitivity label is unknown.
     ///     Exception: If there is an error searching the index.
-    #[pyo3(signature = (tags, top_k = 20, write = false))]
+    #[pyo3(signature = (tags, top_k = 20, write = false, max_sensitivity = None, redact = None, namespace = None))]
     pub fn search_by_tags(
         &self,
         tags: Vec<String>,
         top_k: usize,
         write: bool,
+        max_sensitivity: Option<&str>,
+        redact: Option<&Bound<'_, PyAny>>,
+        namespace: Option<&str>,
     ) -> PyResult<Vec<Memory>> {
         let query_str = tags
             .iter()
             .map(|tag| format!("\"{}\"", tag))
             .collect::<Vec<String>>()
             .join(" OR ");
-        self.search_memories(&query_str, top_k, false, write)
+        self.search_memories(
+            &query_str,
+            top_k,
+            false,
+            write,
+            max_sensitivity,
+            redact,
+            "keyword",
+            None,
+            namespace,
+            None,
+        )
     }
 
-    /// Gets memories filtered by a minimum importance level.
+    /// Gets memories filtered by a minimum importance level, most important first.
     ///
     /// Args:
     ///     min_importance (int): The minimum importance score.
     ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
     ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
     ///
     /// Returns:
-    ///     list[Memory]: A list of Memory objects with importance >= min_importance.
+    ///     list[Memory]: A list of Memory objects with importance >= min_importance, sorted by importance in descending order.
     ///
     /// Raises:
     ///     Exception: If there is an error searching the index.
-    #[pyo3(signature = (min_importance, top_k = 20, write = false))]
+    #[pyo3(signature = (min_importance, top_k = 20, write = false, namespace = None))]
     pub fn get_memories_by_importance(
         &self,
         min_importance: u64,
         top_k: usize,
         write: bool,
+        namespace: Option<&str>,
     ) -> PyResult<Vec<Memory>> {
-        use std::ops::Bound;
-        let memories = self
-            .top_k(
-                FastFieldRangeQuery::new(
+        let memories = self.top_by_field(
+            &*Self::restrict(
+                Box::new(FastFieldRangeQuery::new(
                     Bound::Included(importance_term_of(min_importance)),
                     Bound::Included(importance_term_of(MAX_IMPORTANCE_SCORE)),
-                ),
-                top_k,
-            )
-            .map(extract_memory)?;
+                )),
+                None,
+                namespace,
+            ),
+            SortKey::Importance,
+            true,
+            top_k,
+            0,
+        )?;
 
         self.update_access_and_write_batch(memories, write)
     }
 
-    /// Gets memories from the last N days.
+    /// Gets memories from the last N days, newest first.
     ///
     /// Args:
     ///     days (int): The number of days to look back.
     ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
     ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
     ///
     /// Returns:
-    ///     list[Memory]: A list of Memory objects created within the last N days.
+    ///     list[Memory]: A list of Memory objects created within the last N days, sorted by timestamp in descending order.
     ///
     /// Raises:
     ///     Exception: If there is an error searching the index.
-    #[pyo3(signature = (days, top_k = 20, write = false))]
+    #[pyo3(signature = (days, top_k = 20, write = false, namespace = None))]
     pub fn get_recent_memories(
         &self,
         days: i64,
         top_k: usize,
         write: bool,
+        namespace: Option<&str>,
     ) -> PyResult<Vec<Memory>> {
         let cutoff = Utc::now().timestamp() - (days * 86400);
 
-        use std::ops::Bound;
-        let memories = self
-            .top_k(
-                FastFieldRangeQuery::new(
+        let memories = self.top_by_field(
+            &*Self::restrict(
+                Box::new(FastFieldRangeQuery::new(
                     Bound::Included(timestamp_term_of(cutoff)),
                     Bound::Unbounded,
-                ),
-                top_k,
-            )
-            .map(extract_memory)?;
+                )),
+                None,
+                namespace,
+            ),
+            SortKey::Timestamp,
+            true,
+            top_k,
+            0,
+        )?;
 
         self.update_access_and_write_batch(memories, write)
     }
 
+    /// Deletes the memories older than N days.
+    ///
+    /// The memories are selected by range queries over the timestamp and importance fields
+    /// and deleted by query, so no memory is loaded from the index unless the metadata
+    /// sidecar needs their uuids. Memories meant to be forgotten after a while are better
+    /// given an expiry, see `expire`.
+    ///
+    /// Args:
+    ///     days (int): The age in days beyond which memories are deleted.
+    ///     max_importance (int | None, optional): Only delete memories at most this important. Defaults to None, deleting regardless of importance.
+    ///     write (bool, optional): If True, commits the deletions to disk immediately. Defaults to False.
+    ///     namespace (str | None, optional): Only delete memories of this namespace. Defaults to None, deleting in all namespaces.
+    ///
+    /// Returns:
+    ///     int: The number of memories deleted.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error searching or updating the index.
+    #[pyo3(signature = (days, max_importance = None, write = false, namespace = None))]
+    pub fn cleanup_old_memories(
+        &self,
+        days: i64,
+        max_importance: Option<u64>,
+        write: bool,
+        namespace: Option<&str>,
+    ) -> PyResult<usize> {
+        let cutoff = Utc::now().timestamp() - (days * 86400);
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(
+            Occur::Must,
+            Box::new(FastFieldRangeQuery::new(
+                Bound::Unbounded,
+                Bound::Excluded(timestamp_term_of(cutoff)),
+            )),
+        )];
+        if let Some(max_importance) = max_importance {
+            clauses.push((
+                Occur::Must,
+                Box::new(FastFieldRangeQuery::new(
+                    Bound::Included(importance_term_of(MIN_IMPORTANCE_SCORE)),
+                    Bound::Included(importance_term_of(max_importance)),
+                )),
+            ));
+        }
+        if let Some(namespace) = namespace {
+            clauses.push((Occur::Must, Box::new(namespace_query_of(namespace))));
+        }
+
+        let w = self.access_writer()?;
+        let count = self.stage_delete_matching(&w, Box::new(BooleanQuery::new(clauses)))?;
+        self.write_inner(w, write)?;
+        Ok(count)
+    }
+
+    /// Sweeps the expired memories and enforces the retention policy of the service.
+    ///
+    /// Expired memories are already left out of searches and listings, the sweep deletes them
+    /// from the index. When the service caps the number of memories per namespace, the least
+    /// relevant memories of the namespaces over the cap are evicted too, the least recently
+    /// accessed first among equally relevant ones. Only committed memories are swept.
+    ///
+    /// Args:
+    ///     write (bool, optional): If True, commits the deletions to disk immediately. Defaults to False.
+    ///
+    /// Returns:
+    ///     int: The number of memories deleted.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error searching or updating the index.
+    #[pyo3(signature = (write = false))]
+    pub fn expire(&self, write: bool) -> PyResult<usize> {
+        let now = Utc::now().timestamp();
+        let w = self.access_writer()?;
+        let mut count = self.stage_delete_matching(&w, Box::new(expired_query_of(now)))?;
+        if self.policy.max_per_namespace.is_some() {
+            let evictions = self.policy.evictions(
+                self.all_memories()?
+                    .into_iter()
+                    .filter(|memory| !memory.is_expired(now))
+                    .collect(),
+            );
+            self.record(
+                &evictions
+                    .iter()
+                    .map(|memory| (Some(memory), None))
+                    .collect::<Vec<_>>(),
+            )?;
+            evictions
+                .iter()
+                .for_each(|memory| self.stage_delete(&w, &memory.uuid));
+            count += evictions.len();
+        }
+        self.write_inner(w, write)?;
+        Ok(count)
+    }
+
     /// Gets memories sorted by access frequency (most accessed first).
     ///
     /// Args:
     ///     top_k (int, optional): The maximum number of results to return. Defaults to 20.
     ///     write (bool, optional): If True, commits access updates to disk immediately. Defaults to False.
+    ///     namespace (str | None, optional): Only search the memories of this namespace. Defaults to None, searching all namespaces.
     ///
     /// Returns:
     ///     list[Memory]: A list of Memory objects sorted by access count in descending order.
     ///
     /// Raises:
     ///     Exception: If there is an error searching the index.
-    #[pyo3(signature = (top_k = 20, write = false))]
-    pub fn get_frequently_accessed(&self, top_k: usize, write: bool) -> PyResult<Vec<Memory>> {
-        let searcher = self.searcher();
-        let memories = searcher
-            .search(
-                &AllQuery,
-                &TopDocs::with_limit(top_k)
-                    .order_by_u64_field(field_names::ACCESS_COUNT, Order::Desc), // Fixed: Desc for most frequent
-            )
-            .into_pyresult()
-            .map(|seq| cast_into_items(searcher, seq))
-            .map(extract_memory)?;
+    #[pyo3(signature = (top_k = 20, write = false, namespace = None))]
+    pub fn get_frequently_accessed(
+        &self,
+        top_k: usize,
+        write: bool,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<Memory>> {
+        let memories = self.list_inner(SortKey::AccessCount, true, top_k, 0, None, namespace)?;
 
         self.update_access_and_write_batch(memories, write)
     }
 
-    /// Counts the total number of memories in the system.
+    /// Lists memories ordered by a metadata attribute, without updating access statistics.
+    ///
+    /// Served by the metadata sidecar when the service enables it, so paging through a large
+    /// store does not go through the full-text index.
+    ///
+    /// Args:
+    ///     order_by (str, optional): "timestamp", "last_accessed", "access_count" or "importance". Defaults to "timestamp".
+    ///     descending (bool, optional): If True, lists the highest values first. Defaults to True.
+    ///     limit (int, optional): The maximum number of memories to return. Defaults to 20.
+    ///     offset (int, optional): The number of memories to skip. Defaults to 0.
+    ///     tag (str | None, optional): Only list memories carrying this tag. Defaults to None.
+    ///     namespace (str | None, optional): Only list memories of this namespace. Defaults to None, listing all namespaces.
     ///
     /// Returns:
-    ///     int: The total number of documents in the index.
-    pub fn count_memories(&self) -> u64 {
-        self.searcher().num_docs()
+    ///     list[Memory]: The memories of the requested page.
+    ///
+    /// Raises:
+    ///     ValueError: If the sort key is unknown.
+    ///     Exception: If there is an error reading the index or the sidecar.
+    #[pyo3(signature = (order_by = "timestamp", descending = true, limit = 20, offset = 0, tag = None, namespace = None))]
+    pub fn list_memories(
+        &self,
+        order_by: &str,
+        descending: bool,
+        limit: usize,
+        offset: usize,
+        tag: Option<&str>,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<Memory>> {
+        self.list_inner(order_by.parse()?, descending, limit, offset, tag, namespace)
+    }
+
+    /// Exports all memories as Obsidian-style Markdown notes.
+    ///
+    /// Each note is titled after the first line of the memory content and carries the uuid,
+    /// importance, tags, sensitivity, namespace and access statistics in its frontmatter, so that
+    /// `import_markdown` restores the memory. Existing notes with the same path are overwritten.
+    ///
+    /// Args:
+    ///     dir (str | Path): The directory to write the notes to, created if missing.
+    ///     group_by (str, optional): "tag" to put each note in a subdirectory named after its first tag, or "none" to keep all notes at the top level. Defaults to "tag".
+    ///     namespace (str | None, optional): Only export the memories of this namespace. Defaults to None, exporting all namespaces.
+    ///
+    /// Returns:
+    ///     int: The number of notes written.
+    ///
+    /// Raises:
+    ///     ValueError: If the grouping is unknown.
+    ///     OSError: If a note cannot be written.
+    #[pyo3(signature = (dir, group_by = "tag", namespace = None))]
+    pub fn export_markdown(
+        &self,
+        dir: PathBuf,
+        group_by: &str,
+        namespace: Option<&str>,
+    ) -> PyResult<usize> {
+        let group_by = group_by.parse::<GroupBy>()?;
+        let memories: Vec<Memory> = self
+            .all_memories()?
+            .into_iter()
+            .filter(|memory| namespace.is_none_or(|namespace| memory.namespace == namespace))
+            .collect();
+        let mut taken = HashSet::new();
+        for memory in &memories {
+            let path = note_path(&dir, memory, group_by, &mut taken);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).into_pyresult()?;
+            }
+            fs::write(&path, to_markdown(memory)).into_pyresult()?;
+        }
+        Ok(memories.len())
+    }
+
+    /// Imports the Markdown notes below a directory as memories.
+    ///
+    /// Notes whose frontmatter has the uuid of an existing memory replace it, keeping its
+    /// embedding if the content is unchanged, the others are added. Notes without frontmatter become new memories with the minimum importance and no
+    /// tags. Hidden files and directories, such as `.obsidian`, are skipped.
+    ///
+    /// Args:
+    ///     dir (str | Path): The directory to read the notes from, recursively.
+    ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///
+    /// Returns:
+    ///     int: The number of imported notes.
+    ///
+    /// Raises:
+    ///     ValueError: If a frontmatter attribute is invalid, nothing is imported then.
+    ///     OSError: If the notes cannot be read.
+    #[pyo3(signature = (dir, write = false))]
+    pub fn import_markdown(&self, dir: PathBuf, write: bool) -> PyResult<usize> {
+        let mut memories = markdown_files(&dir)
+            .into_pyresult()?
+            .iter()
+            .map(|path| {
+                let note = fs::read_to_string(path).into_pyresult()?;
+                from_markdown(&note).map_err(|e| {
+                    PyValueError::new_err(format!("Invalid note {}: {e}", path.display()))
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        // Notes do not carry embeddings, so those of unchanged memories are kept.
+        let existing: HashMap<String, Memory> = self
+            .memories_by_uuids(
+                &memories
+                    .iter()
+                    .map(|memory| memory.uuid.clone())
+                    .collect::<Vec<_>>(),
+            )?
+            .into_iter()
+            .map(|memory| (memory.uuid.clone(), memory))
+            .collect();
+        for memory in &mut memories {
+            if let Some(old) = existing.get(&memory.uuid)
+                && old.content == memory.content
+            {
+                memory.embedding.clone_from(&old.embedding);
+            }
+        }
+
+        let w = self.access_writer()?;
+        self.record(
+            &memories
+                .iter()
+                .map(|memory| (existing.get(&memory.uuid), Some(memory)))
+                .collect::<Vec<_>>(),
+        )?;
+        memories
+            .iter()
+            .try_for_each(|memory| self.stage_update(&w, memory))?;
+        self.write_inner(w, write)?;
+        Ok(memories.len())
+    }
+
+    /// Exports memories as JSON Lines, one memory per line with all its attributes.
+    ///
+    /// Unlike Markdown notes, the lines keep embeddings and access statistics, so
+    /// `import_jsonl` restores the memories exactly, e.g. on another machine. Only committed
+    /// memories are exported, expired ones included. An existing file is overwritten.
+    ///
+    /// Args:
+    ///     path (str | Path): The file to write, its parent directory is created if missing.
+    ///     namespace (str | None, optional): Only export the memories of this namespace. Defaults to None, exporting all namespaces.
+    ///
+    /// Returns:
+    ///     int: The number of memories written.
+    ///
+    /// Raises:
+    ///     OSError: If the file cannot be written.
+    #[pyo3(signature = (path, namespace = None))]
+    pub fn export_jsonl(&self, path: PathBuf, namespace: Option<&str>) -> PyResult<usize> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).into_pyresult()?;
+        }
+        let mut file = BufWriter::new(File::create(&path).into_pyresult()?);
+        let mut count = 0;
+        for memory in self.all_memories()? {
+            if namespace.is_none_or(|namespace| memory.namespace == namespace) {
+                serde_json::to_writer(&mut file, &memory).into_pyresult()?;
+                file.write_all(b"\n").into_pyresult()?;
+                count += 1;
+            }
+        }
+        file.flush().into_pyresult()?;
+        Ok(count)
+    }
+
+    /// Imports memories from JSON Lines, as written by `export_jsonl` or prepared from a dataset.
+    ///
+    /// Each line holds a memory with at least `uuid`, `content`, `timestamp`, `importance`,
+    /// `tags`, `access_count`, `last_accessed` and `sensitivity`. Lines with the uuid of an
+    /// existing memory replace it. With `dedup`, lines whose content is already held by another
+    /// memory of the same namespace, in the store or earlier in the file, are skipped, so
+    /// importing the same dataset twice does not duplicate it. Blank lines are ignored.
+    ///
+    /// Args:
+    ///     path (str | Path): The file to read.
+    ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///     dedup (bool, optional): If True, skips the memories whose content is already stored in their namespace. Defaults to True.
+    ///
+    /// Returns:
+    ///     int: The number of memories added or replaced.
+    ///
+    /// Raises:
+    ///     ValueError: If a line is not a valid memory, nothing is imported then.
+    ///     OSError: If the file cannot be read.
+    #[pyo3(signature = (path, write = false, dedup = true))]
+    pub fn import_jsonl(&self, path: PathBuf, write: bool, dedup: bool) -> PyResult<usize> {
+        let mut memories = Vec::new();
+        for (i, line) in BufReader::new(File::open(&path).into_pyresult()?)
+            .lines()
+            .enumerate()
+        {
+            let line = line.into_pyresult()?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let memory = serde_json::from_str::<Memory>(&line)
+                .map_err(|e| e.to_string())
+                .and_then(|memory| memory.validate().map(|_| memory).map_err(|e| e.to_string()))
+                .map_err(|e| {
+                    PyValueError::new_err(format!(
+                        "Invalid memory at line {} of {}: {e}",
+                        i + 1,
+                        path.display()
+                    ))
+                })?;
+            memories.push(memory);
+        }
+
+        let stored = self.all_memories()?;
+        let mut seen: HashSet<u64> = if dedup {
+            stored.iter().map(Memory::content_hash).collect()
+        } else {
+            HashSet::new()
+        };
+        let existing: HashMap<String, Memory> = stored
+            .into_iter()
+            .map(|memory| (memory.uuid.clone(), memory))
+            .collect();
+        let memories: Vec<Memory> = memories
+            .into_iter()
+            .filter(|memory| {
+                existing.contains_key(&memory.uuid) || !dedup || seen.insert(memory.content_hash())
+            })
+            .collect();
+
+        let w = self.access_writer()?;
+        self.record(
+            &memories
+                .iter()
+                .map(|memory| (existing.get(&memory.uuid), Some(memory)))
+                .collect::<Vec<_>>(),
+        )?;
+        memories
+            .iter()
+            .try_for_each(|memory| self.stage_update(&w, memory))?;
+        self.write_inner(w, write)?;
+        Ok(memories.len())
+    }
+
+    /// Writes a snapshot of the store to a new index directory, as a backup to `restore` from.
+    ///
+    /// The snapshot is a compact copy of the committed memories, expired ones included, and
+    /// can be opened as a store of its own by moving it below the root of a service.
+    ///
+    /// Args:
+    ///     dir (str | Path): The directory to write the snapshot to, which must not hold an index already.
+    ///
+    /// Returns:
+    ///     int: The number of memories in the snapshot.
+    ///
+    /// Raises:
+    ///     ValueError: If the directory already holds an index.
+    ///     Exception: If the snapshot cannot be written.
+    pub fn snapshot(&self, dir: PathBuf) -> PyResult<usize> {
+        if is_valid_index_dir(&dir) {
+            return Err(PyValueError::new_err(format!(
+                "An index already exists at {}",
+                dir.display()
+            )));
+        }
+        fs::create_dir_all(&dir).into_pyresult()?;
+        let snapshot = Index::create_in_dir(&dir, SCHEMA.clone()).into_pyresult()?;
+        let mut w: IndexWriter = snapshot
+            .writer(SNAPSHOT_WRITER_BUFFER_SIZE)
+            .into_pyresult()?;
+        let memories = self.all_memories()?;
+        memories
+            .iter()
+            .try_for_each(|memory| add_memory_inner(&w, memory))?;
+        w.commit().into_pyresult()?;
+        w.wait_merging_threads().into_pyresult()?;
+        Ok(memories.len())
+    }
+
+    /// Replaces every memory of the store with those of a snapshot.
+    ///
+    /// The current memories, of all namespaces, are deleted, so pending changes are lost
+    /// unless written first. The replacement is recorded in the audit log if enabled, so it
+    /// can be undone memory by memory.
+    ///
+    /// Args:
+    ///     dir (str | Path): The directory holding a snapshot written by `snapshot`.
+    ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///
+    /// Returns:
+    ///     int: The number of memories restored.
+    ///
+    /// Raises:
+    ///     ValueError: If the directory does not hold a snapshot.
+    ///     Exception: If the snapshot cannot be read or the index updated.
+    #[pyo3(signature = (dir, write = false))]
+    pub fn restore(&self, dir: PathBuf, write: bool) -> PyResult<usize> {
+        if !is_valid_index_dir(&dir) {
+            return Err(PyValueError::new_err(format!(
+                "No snapshot found at {}",
+                dir.display()
+            )));
+        }
+        let snapshot = Index::open_in_dir(&dir).into_pyresult()?;
+        let searcher = snapshot
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .into_pyresult()
+            .map(|reader: IndexReader| reader.searcher())?;
+        let memories = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .into_pyresult()?
+            .into_iter()
+            .map(|address| searcher.doc::<Memory>(address).into_pyresult())
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let w = self.access_writer()?;
+        self.stage_delete_matching(&w, Box::new(AllQuery))?;
+        self.record(
+            &memories
+                .iter()
+                .map(|memory| (None, Some(memory)))
+                .collect::<Vec<_>>(),
+        )?;
+        memories
+            .iter()
+            .try_for_each(|memory| self.stage_add(&w, memory))?;
+        self.write_inner(w, write)?;
+        Ok(memories.len())
+    }
+
+    /// Lists the recorded mutations of the store, newest first.
+    ///
+    /// Every addition, update and deletion is recorded before it is staged, together with the
+    /// memory as it was before and after it, so an entry may describe a change that was never
+    /// committed. Access statistics updated by reads are not recorded.
+    ///
+    /// Args:
+    ///     uuid (str | None, optional): Only list the mutations of this memory. Defaults to None.
+    ///     actor (str | None, optional): Only list the mutations made through stores opened for this actor. Defaults to None.
+    ///     action (str | None, optional): Only list mutations of this kind: "add", "update" or "delete". Defaults to None.
+    ///     since (int | None, optional): Only list mutations recorded at or after this unix timestamp. Defaults to None.
+    ///     until (int | None, optional): Only list mutations recorded before this unix timestamp. Defaults to None.
+    ///     limit (int, optional): The maximum number of entries to return. Defaults to 100.
+    ///
+    /// Returns:
+    ///     list[AuditEntry]: The matching entries, newest first.
+    ///
+    /// Raises:
+    ///     ValueError: If the action is unknown.
+    ///     RuntimeError: If the service does not keep an audit log.
+    ///     OSError: If the log cannot be read.
+    #[pyo3(signature = (uuid = None, actor = None, action = None, since = None, until = None, limit = 100))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn audit_log(
+        &self,
+        uuid: Option<&str>,
+        actor: Option<&str>,
+        action: Option<&str>,
+        since: Option<i64>,
+        until: Option<i64>,
+        limit: usize,
+    ) -> PyResult<Vec<AuditEntry>> {
+        let filter = AuditFilter {
+            uuid,
+            actor,
+            action: action.map(str::parse).transpose()?,
+            since,
+            until,
+        };
+        self.audit()?.query(&filter, limit)
+    }
+
+    /// Undoes a recorded mutation, restoring the memory as it was before it.
+    ///
+    /// Undoing a deletion or an update restores the recorded memory, replacing its current
+    /// version if any, undoing an addition deletes the memory. Later mutations of the same
+    /// memory are overwritten, and the undo is recorded in the audit log like any mutation.
+    ///
+    /// Args:
+    ///     seq (int): The sequence number of the entry to undo.
+    ///     write (bool, optional): If True, commits the change to disk immediately. Defaults to False.
+    ///
+    /// Returns:
+    ///     bool: True if a change was staged, False if the entry is unknown or the memory it added is already gone.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the service does not keep an audit log.
+    ///     OSError: If the log cannot be read or written.
+    ///     Exception: If there is an error updating the index.
+    #[pyo3(signature = (seq, write = false))]
+    pub fn undo(&self, seq: u64, write: bool) -> PyResult<bool> {
+        let Some(entry) = self.audit()?.get(seq)? else {
+            return Ok(false);
+        };
+        let current = self
+            .top(uuid_query_of(&entry.uuid))?
+            .map(|(_, memory)| memory);
+        let w = self.access_writer()?;
+        match (&entry.before, &current) {
+            (Some(before), _) => {
+                self.record(&[(current.as_ref(), Some(before))])?;
+                self.stage_update(&w, before)?;
+            }
+            (None, Some(current)) => {
+                self.record(&[(Some(current), None)])?;
+                self.stage_delete(&w, &current.uuid);
+            }
+            (None, None) => return Ok(false),
+        }
+        self.write_inner(w, write)?;
+        Ok(true)
+    }
+
+    /// Counts the unexpired memories in the system.
+    ///
+    /// Args:
+    ///     namespace (str | None, optional): Only count the memories of this namespace. Defaults to None, counting all namespaces.
+    ///
+    /// Returns:
+    ///     int: The number of matching documents in the index.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error searching the index.
+    #[pyo3(signature = (namespace = None))]
+    pub fn count_memories(&self, namespace: Option<&str>) -> PyResult<u64> {
+        self.searcher()
+            .search(&Self::restrict(Box::new(AllQuery), None, namespace), &Count)
+            .map(|count| count as u64)
+            .into_pyresult()
     }
 
     /// Gets aggregated statistics about all memories.
     ///
+    /// Args:
+    ///     namespace (str | None, optional): Only aggregate the memories of this namespace. Defaults to None, aggregating all namespaces.
+    ///
     /// Returns:
     ///     MemoryStats: An object containing total memories, average importance,
-    ///                  average access count, and average age in days.
+    ///                  average access count, average age in days, and the importance,
+    ///                  age and tag histograms.
     ///
     /// Raises:
     ///     Exception: If there is an error calculating aggregations.
-    pub fn stats(&self) -> PyResult<MemoryStats> {
+    #[pyo3(signature = (namespace = None))]
+    pub fn stats(&self, namespace: Option<&str>) -> PyResult<MemoryStats> {
         let searcher = self.searcher();
 
         let agg_req_json = format!(
@@ -469,7 +1652,13 @@ impl MemoryStore {
         let aggs: Aggregations = serde_json::from_str(&agg_req_json).into_pyresult()?;
 
         let collector = AggregationCollector::from_aggs(aggs, Default::default());
-        let result = searcher.search(&AllQuery, &collector).into_pyresult()?.0;
+        let result = searcher
+            .search(
+                &*Self::restrict(Box::new(AllQuery), None, namespace),
+                &collector,
+            )
+            .into_pyresult()?
+            .0;
 
         let total_memories = if let AggregationResult::MetricResult(res) = result
             .get("total_memories")
@@ -516,6 +1705,275 @@ impl MemoryStore {
             avg_importance,
             avg_access_count,
             avg_age_days,
-        })
+            ..Default::default()
+        }
+        .with_histograms(
+            &self.live_memories(None, namespace)?,
+            Utc::now().timestamp(),
+        ))
+    }
+
+    /// Reports the memories whose relevance decayed below a threshold, the least relevant first.
+    ///
+    /// The relevance score multiplies the importance by an exponential decay of the age and by
+    /// the logarithm of the access count, as used to evict memories, so the report shows which
+    /// memories the next sweep would drop first and which are worth consolidating or deleting.
+    /// Access statistics are not updated.
+    ///
+    /// Args:
+    ///     threshold (float): The relevance score below which a memory is stale.
+    ///     top_k (int, optional): The maximum number of memories to report. Defaults to 20.
+    ///     decay_factor (float, optional): The daily decay rate of the age factor. Defaults to 0.01.
+    ///     namespace (str | None, optional): Only report the memories of this namespace. Defaults to None, reporting all namespaces.
+    ///
+    /// Returns:
+    ///     list[tuple[float, Memory]]: Pairs of the relevance score and the memory, ascending by score.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error searching the index.
+    #[pyo3(signature = (threshold, top_k = 20, decay_factor = 0.01, namespace = None))]
+    pub fn stale_memories(
+        &self,
+        threshold: f64,
+        top_k: usize,
+        decay_factor: f64,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<(f64, Memory)>> {
+        let mut report: Vec<(f64, Memory)> = self
+            .live_memories(None, namespace)?
+            .into_par_iter()
+            .map(|memory| (memory.calculate_relevance_score(decay_factor), memory))
+            .filter(|(score, _)| *score < threshold)
+            .collect();
+        report.sort_by(|a, b| {
+            a.0.total_cmp(&b.0)
+                .then(a.1.last_accessed.cmp(&b.1.last_accessed))
+        });
+        report.truncate(top_k);
+        Ok(report)
+    }
+
+    /// Reports the memories accessed the most lately, the hottest first.
+    ///
+    /// The heat of a memory is its access count decayed exponentially with the days since its
+    /// last access, so memories read often but long ago cool down. Unlike
+    /// `get_frequently_accessed`, access statistics are not updated.
+    ///
+    /// Args:
+    ///     top_k (int, optional): The maximum number of memories to report. Defaults to 20.
+    ///     decay_factor (float, optional): The daily decay rate of the access count. Defaults to 0.1.
+    ///     namespace (str | None, optional): Only report the memories of this namespace. Defaults to None, reporting all namespaces.
+    ///
+    /// Returns:
+    ///     list[tuple[float, Memory]]: Pairs of the heat and the memory, descending by heat, never accessed memories left out.
+    ///
+    /// Raises:
+    ///     Exception: If there is an error searching the index.
+    #[pyo3(signature = (top_k = 20, decay_factor = 0.1, namespace = None))]
+    pub fn hot_memories(
+        &self,
+        top_k: usize,
+        decay_factor: f64,
+        namespace: Option<&str>,
+    ) -> PyResult<Vec<(f64, Memory)>> {
+        let now = Utc::now().timestamp();
+        let mut report: Vec<(f64, Memory)> = self
+            .live_memories(None, namespace)?
+            .into_par_iter()
+            .filter(|memory| memory.access_count > 0)
+            .map(|memory| (memory.heat(decay_factor, now), memory))
+            .collect();
+        report.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then(b.1.last_accessed.cmp(&a.1.last_accessed))
+        });
+        report.truncate(top_k);
+        Ok(report)
+    }
+
+    /// Groups near-duplicate memories into clusters, the candidates for being summarized into
+    /// one memory each and replaced with `replace_cluster`.
+    ///
+    /// Similarity is the overlap of the word pairs of the contents, estimated with MinHash, so
+    /// rephrasings sharing few words are missed. Memories of different namespaces are never
+    /// clustered together, and access statistics are not updated.
+    ///
+    /// Args:
+    ///     namespace (str | None, optional): Only consolidate the memories of this namespace. Defaults to None, consolidating every namespace separately.
+    ///     window (int | None, optional): Only consolidate the memories created within this number of days. Defaults to None, consolidating all memories.
+    ///     threshold (float, optional): The estimated similarity, between 0 and 1, from which two memories are linked. Defaults to 0.5.
+    ///     min_size (int, optional): The minimum number of memories of a cluster. Defaults to 2.
+    ///
+    /// Returns:
+    ///     list[list[Memory]]: The clusters, largest first, each ordered from the oldest memory to the newest.
+    ///
+    /// Raises:
+    ///     ValueError: If the threshold is not between 0 and 1.
+    ///     Exception: If there is an error searching the index.
+    #[pyo3(signature = (namespace = None, window = None, threshold = 0.5, min_size = 2))]
+    pub fn consolidate(
+        &self,
+        namespace: Option<&str>,
+        window: Option<i64>,
+        threshold: f64,
+        min_size: usize,
+    ) -> PyResult<Vec<Vec<Memory>>> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(PyValueError::new_err(format!(
+                "The threshold must be between 0 and 1, got {threshold}"
+            )));
+        }
+        let since = window.map(|days| Utc::now().timestamp() - days * 86400);
+        let memories: Vec<Memory> = self
+            .live_memories(None, namespace)?
+            .into_iter()
+            .filter(|memory| since.is_none_or(|since| memory.timestamp >= since))
+            .collect();
+        let indices = near_duplicate_clusters(&memories, threshold, min_size);
+
+        let mut slots: Vec<Option<Memory>> = memories.into_iter().map(Some).collect();
+        let mut clusters: Vec<Vec<Memory>> = indices
+            .into_iter()
+            .map(|indices| {
+                let mut cluster: Vec<Memory> = indices
+                    .into_iter()
+                    .filter_map(|i| slots[i].take())
+                    .collect();
+                cluster.sort_by(|a, b| {
+                    a.timestamp
+                        .cmp(&b.timestamp)
+                        .then_with(|| a.uuid.cmp(&b.uuid))
+                });
+                cluster
+            })
+            .collect();
+        clusters.sort_by(|a, b| {
+            b.len()
+                .cmp(&a.len())
+                .then(a[0].timestamp.cmp(&b[0].timestamp))
+        });
+        Ok(clusters)
+    }
+
+    /// Groups memories by the terms of their contents and tags, a cheap overview of what the
+    /// store holds.
+    ///
+    /// Memories are compared by the cosine similarity of their TF-IDF vectors and grouped with
+    /// k-means, without any embedding service, so clusters reflect shared vocabulary rather
+    /// than meaning. Memories without any significant word are left out, and access
+    /// statistics are not updated.
+    ///
+    /// Args:
+    ///     tag (str | None, optional): Only cluster the memories carrying this tag. Defaults to None, clustering all memories.
+    ///     k (int, optional): The number of clusters to form, fewer if there are fewer memories. Defaults to 8.
+    ///     representatives (int, optional): The number of memories reported per cluster. Defaults to 3.
+    ///     namespace (str | None, optional): Only cluster the memories of this namespace. Defaults to None, clustering all namespaces together.
+    ///     max_sensitivity (str | None, optional): The most sensitive label to include. Defaults to None, including all labels.
+    ///
+    /// Returns:
+    ///     list[MemoryCluster]: The non-empty clusters, largest first, with their label, terms, size and representative memories.
+    ///
+    /// Raises:
+    ///     ValueError: If the sensitivity label is unknown.
+    ///     Exception: If there is an error searching the index.
+    #[pyo3(signature = (tag = None, k = 8, representatives = 3, namespace = None, max_sensitivity = None))]
+    pub fn cluster(
+        &self,
+        tag: Option<&str>,
+        k: usize,
+        representatives: usize,
+        namespace: Option<&str>,
+        max_sensitivity: Option<&str>,
+    ) -> PyResult<Vec<MemoryCluster>> {
+        let max_sensitivity = max_sensitivity.map(str::parse::<Sensitivity>).transpose()?;
+        let mut memories: Vec<Memory> = self
+            .live_memories(max_sensitivity, namespace)?
+            .into_iter()
+            .filter(|memory| tag.is_none_or(|tag| memory.tags.iter().any(|t| t == tag)))
+            .collect();
+        // Clusters only depend on the memories, not on the order the index returns them in.
+        memories.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+        let clusters = term_clusters(&memories, k);
+
+        let mut slots: Vec<Option<Memory>> = memories.into_iter().map(Some).collect();
+        Ok(clusters
+            .into_iter()
+            .map(|cluster| cluster.into_report(&mut slots, representatives))
+            .collect())
+    }
+
+    /// Replaces memories, typically a cluster returned by `consolidate`, with a new one
+    /// summarizing them.
+    ///
+    /// The deletions and the addition are staged under a single lock of the writer and
+    /// appended to the audit log at once, so they become visible together at the next
+    /// commit. The new memory is at least as sensitive as the most sensitive of the memories
+    /// it replaces, so a summary cannot leak secrets under a lower label.
+    ///
+    /// Args:
+    ///     uuids (list[str]): The unique identifiers of the memories to replace.
+    ///     memory (dict): The new memory, a mapping with `content`, `importance` and optionally `tags`, `sensitivity`, `embedding`, `namespace`, `expires_at` and `metadata`, e.g. a dumped `Note` model.
+    ///     write (bool, optional): If True, commits the changes to disk immediately. Defaults to False.
+    ///
+    /// Returns:
+    ///     str: The UUID of the new memory.
+    ///
+    /// Raises:
+    ///     ValueError: If no uuid is given, a memory is not found, or the new memory is malformed. Nothing is changed then.
+    ///     Exception: If there is an error updating the index.
+    #[pyo3(signature = (uuids, memory, write = false))]
+    pub fn replace_cluster(
+        &self,
+        uuids: Vec<String>,
+        memory: &Bound<'_, PyAny>,
+        write: bool,
+    ) -> PyResult<String> {
+        let mut seen = HashSet::new();
+        let uuids: Vec<String> = uuids
+            .into_iter()
+            .filter(|uuid| seen.insert(uuid.clone()))
+            .collect();
+        if uuids.is_empty() {
+            return Err(PyValueError::new_err("No memory to replace"));
+        }
+        let originals = self.memories_by_uuids(&uuids)?;
+        if let Some(missing) = uuids
+            .iter()
+            .find(|uuid| !originals.iter().any(|memory| &memory.uuid == *uuid))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Memory `{missing}` not found"
+            )));
+        }
+
+        // The namespace of the originals is the natural home of their summary, if they share one.
+        let namespace = originals
+            .iter()
+            .all(|memory| memory.namespace == originals[0].namespace)
+            .then(|| originals[0].namespace.as_str());
+        let mut summary = depythonize::<MemoryDraft>(memory)
+            .map_err(|e| PyValueError::new_err(format!("Invalid memory: {e}")))?
+            .into_memory(namespace, &self.policy)?;
+        let sensitivity = originals
+            .iter()
+            .map(|memory| memory.sensitivity.parse::<Sensitivity>())
+            .try_fold(summary.sensitivity.parse::<Sensitivity>()?, |max, label| {
+                label.map(|label| max.max(label))
+            })?;
+        summary.sensitivity = sensitivity.to_string();
+
+        let w = self.access_writer()?;
+        let mut changes: Vec<(Option<&Memory>, Option<&Memory>)> = originals
+            .iter()
+            .map(|memory| (Some(memory), None))
+            .collect();
+        changes.push((None, Some(&summary)));
+        self.record(&changes)?;
+        originals
+            .iter()
+            .for_each(|memory| self.stage_delete(&w, &memory.uuid));
+        self.stage_add(&w, &summary)?;
+        self.write_inner(w, write)?;
+        Ok(summary.uuid)
     }
 }
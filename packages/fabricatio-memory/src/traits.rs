@@ -1,8 +1,10 @@
-use crate::constants::{FIELDS, SCHEMA};
-use crate::memory::Memory;
+use crate::constants::{DEFAULT_NAMESPACE, FIELDS, SCHEMA};
+use crate::memory::{Memory, MetadataValue};
+use crate::sensitivity::Sensitivity;
+use crate::utils::decode_embedding;
 use fabricatio_logger::trace;
-use tantivy::schema::Value;
 use tantivy::schema::document::{DeserializeError, DocumentDeserialize, DocumentDeserializer};
+use tantivy::schema::{OwnedValue, Value};
 use tantivy::{Document, TantivyDocument};
 
 impl DocumentDeserialize for Memory {
@@ -55,6 +57,51 @@ impl DocumentDeserialize for Memory {
                 .expect("Field 'last_accessed' missing")
                 .as_i64()
                 .expect("Field 'last_accessed' is not an i64"),
+            // Documents indexed before labels existed carry no sensitivity and are public.
+            sensitivity: doc
+                .get_first(FIELDS.sensitivity)
+                .and_then(|value| value.as_u64())
+                .map(Sensitivity::from_level)
+                .unwrap_or_default()
+                .to_string(),
+            embedding: doc
+                .get_first(FIELDS.embedding)
+                .and_then(|value| value.as_bytes())
+                .map(decode_embedding),
+            namespace: doc
+                .get_first(FIELDS.namespace)
+                .and_then(|value| value.as_str())
+                .unwrap_or(DEFAULT_NAMESPACE)
+                .to_string(),
+            expires_at: doc
+                .get_first(FIELDS.expires_at)
+                .and_then(|value| value.as_i64()),
+            metadata: doc
+                .get_first(FIELDS.metadata)
+                .and_then(|value| value.as_object())
+                .map(|object| {
+                    object
+                        .filter_map(|(key, value)| {
+                            metadata_value_of(value).map(|value| (key.to_string(), value))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
         })
     }
 }
+
+/// Reads a stored metadata value, skipping the nested ones `add_memory_inner` never writes.
+fn metadata_value_of(value: &OwnedValue) -> Option<MetadataValue> {
+    match value {
+        OwnedValue::Bool(value) => Some(MetadataValue::Bool(*value)),
+        OwnedValue::I64(value) => Some(MetadataValue::Int(*value)),
+        OwnedValue::U64(value) => Some(
+            i64::try_from(*value)
+                .map_or_else(|_| MetadataValue::Float(*value as f64), MetadataValue::Int),
+        ),
+        OwnedValue::F64(value) => Some(MetadataValue::Float(*value)),
+        OwnedValue::Str(value) => Some(MetadataValue::Str(value.clone())),
+        _ => None,
+    }
+}
@@ -1,14 +1,17 @@
 use crate::constants::{FIELDS, METADATA_FILE_NAME};
-use crate::memory::Memory;
+use crate::memory::{Memory, MetadataValue};
+use crate::sensitivity::Sensitivity;
 use error_mapping::AsPyErr;
 use pyo3::PyResult;
 use pyo3::exceptions::PyValueError;
 use rayon::iter::IntoParallelIterator;
 use rayon::prelude::*;
+use std::ops::Bound;
 use std::path::Path;
+use std::str::FromStr;
 use tantivy::aggregation::agg_result::{AggregationResult, MetricResult};
-use tantivy::query::TermQuery;
-use tantivy::schema::IndexRecordOption;
+use tantivy::query::{FastFieldRangeQuery, TermQuery};
+use tantivy::schema::{IndexRecordOption, OwnedValue};
 use tantivy::{DocAddress, IndexWriter, Searcher, Term, doc};
 
 /// Validates that an index name contains only valid characters.
@@ -80,6 +83,61 @@ pub(crate) fn timestamp_term_of(timestamp: i64) -> Term {
     Term::from_field_i64(FIELDS.timestamp, timestamp)
 }
 
+/// Creates a Term for filtering by sensitivity level.
+///
+/// Args:
+///     sensitivity: The sensitivity label to search for.
+///
+/// Returns:
+///     A Term for the sensitivity field.
+#[inline]
+pub(crate) fn sensitivity_term_of(sensitivity: Sensitivity) -> Term {
+    Term::from_field_u64(FIELDS.sensitivity, sensitivity.level())
+}
+
+/// Creates a query matching memories at most as sensitive as the given label.
+///
+/// Args:
+///     max_sensitivity: The most sensitive label to include.
+///
+/// Returns:
+///     A range query over the sensitivity field.
+#[inline]
+pub(crate) fn max_sensitivity_query_of(max_sensitivity: Sensitivity) -> FastFieldRangeQuery {
+    FastFieldRangeQuery::new(
+        Bound::Included(sensitivity_term_of(Sensitivity::Public)),
+        Bound::Included(sensitivity_term_of(max_sensitivity)),
+    )
+}
+
+/// Creates a query matching the memories expired at the given time.
+///
+/// Args:
+///     now: The unix timestamp to compare the expiry of memories with.
+///
+/// Returns:
+///     A range query over the expiry field, never matching memories without one.
+#[inline]
+pub(crate) fn expired_query_of(now: i64) -> FastFieldRangeQuery {
+    FastFieldRangeQuery::new(
+        Bound::Unbounded,
+        Bound::Included(Term::from_field_i64(FIELDS.expires_at, now)),
+    )
+}
+
+/// Creates a TermQuery matching the memories of a namespace.
+///
+/// Args:
+///     namespace: The namespace to query.
+///
+/// Returns:
+///     A TermQuery configured to search the namespace field.
+#[inline]
+pub(crate) fn namespace_query_of(namespace: &str) -> TermQuery {
+    let term = Term::from_field_text(FIELDS.namespace, namespace);
+    TermQuery::new(term, IndexRecordOption::Basic)
+}
+
 /// Extracts Memory objects from a list of tuples.
 ///
 /// Args:
@@ -136,6 +194,29 @@ pub(crate) fn cast_into_items<N: Sized + Send>(
         .collect()
 }
 
+/// Converts a metadata value to the value stored in the JSON field of the index.
+fn owned_value_of(value: &MetadataValue) -> OwnedValue {
+    match value {
+        MetadataValue::Bool(value) => OwnedValue::Bool(*value),
+        MetadataValue::Int(value) => OwnedValue::I64(*value),
+        MetadataValue::Float(value) => OwnedValue::F64(*value),
+        MetadataValue::Str(value) => OwnedValue::Str(value.clone()),
+    }
+}
+
+/// Encodes an embedding vector as little-endian `f32` bytes for the index.
+pub(crate) fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// Decodes an embedding vector stored by `encode_embedding`.
+pub(crate) fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
 /// Deletes a memory from the index by its UUID.
 ///
 /// Args:
@@ -173,14 +254,32 @@ pub(crate) fn add_memory_inner(index_writer: &IndexWriter, memory: &Memory) -> P
     let mut doc = doc!(
         FIELDS.uuid => memory.uuid,
         FIELDS.content => memory.content.as_str(),
+        FIELDS.namespace => memory.namespace.as_str(),
         FIELDS.timestamp => memory.timestamp,
         FIELDS.importance => memory.importance,
         FIELDS.access_count => memory.access_count,
-        FIELDS.last_accessed => memory.last_accessed
+        FIELDS.last_accessed => memory.last_accessed,
+        FIELDS.sensitivity => Sensitivity::from_str(&memory.sensitivity)?.level()
     );
     memory.tags.iter().for_each(|tag| {
         doc.add_text(FIELDS.tags, tag);
     });
+    if let Some(embedding) = &memory.embedding {
+        doc.add_bytes(FIELDS.embedding, encode_embedding(embedding));
+    }
+    if let Some(expires_at) = memory.expires_at {
+        doc.add_i64(FIELDS.expires_at, expires_at);
+    }
+    if !memory.metadata.is_empty() {
+        doc.add_object(
+            FIELDS.metadata,
+            memory
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), owned_value_of(value)))
+                .collect(),
+        );
+    }
     index_writer.add_document(doc).into_pyresult()?;
     Ok(())
 }
@@ -0,0 +1,75 @@
+//! Semantic search over the embeddings of memories.
+//!
+//! Embeddings are computed outside of the store, e.g. by an embedding model routed through
+//! fabricatio-core or a TEI server registered by fabricatio-tei, and stored with each memory.
+//! Vector search ranks the memories carrying an embedding by cosine similarity to the query
+//! vector, and hybrid search fuses that ranking with the BM25 one by reciprocal rank fusion.
+
+use crate::memory::Memory;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Constant damping the weight of the top ranks in reciprocal rank fusion.
+const RRF_K: f64 = 60.0;
+
+/// How `search_memories` ranks memories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SearchMode {
+    /// BM25 over the content and tags
+    Keyword,
+    /// Cosine similarity of the embeddings
+    Vector,
+    /// Reciprocal rank fusion of both rankings
+    Hybrid,
+}
+
+impl FromStr for SearchMode {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyword" => Ok(Self::Keyword),
+            "vector" => Ok(Self::Vector),
+            "hybrid" => Ok(Self::Hybrid),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown search mode `{s}`, expected one of `keyword`, `vector`, `hybrid`"
+            ))),
+        }
+    }
+}
+
+/// Ranks memories by the cosine similarity of their embedding to the query vector.
+///
+/// Memories without an embedding, or with one of another dimension, are left out.
+pub(crate) fn rank_by_similarity(memories: Vec<Memory>, query: &[f32], k: usize) -> Vec<Memory> {
+    let mut scored: Vec<(f32, Memory)> = memories
+        .into_iter()
+        .filter_map(|memory| Some((memory.cosine_similarity(query)?, memory)))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(k)
+        .map(|(_, memory)| memory)
+        .collect()
+}
+
+/// Fuses rankings by summing `1 / (RRF_K + rank)` over the rankings each memory appears in.
+pub(crate) fn reciprocal_rank_fusion(rankings: Vec<Vec<Memory>>, k: usize) -> Vec<Memory> {
+    let mut fused: HashMap<String, (f64, Memory)> = HashMap::new();
+    for ranking in rankings {
+        for (rank, memory) in ranking.into_iter().enumerate() {
+            fused.entry(memory.uuid.clone()).or_insert((0.0, memory)).0 +=
+                1.0 / (RRF_K + rank as f64 + 1.0);
+        }
+    }
+    let mut fused: Vec<(f64, Memory)> = fused.into_values().collect();
+    fused.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.uuid.cmp(&b.1.uuid)));
+    fused
+        .into_iter()
+        .take(k)
+        .map(|(_, memory)| memory)
+        .collect()
+}
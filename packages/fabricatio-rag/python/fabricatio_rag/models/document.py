@@ -8,6 +8,8 @@ from fabricatio_capabilities.models.generic import AsPrompt
 from fabricatio_core.models.generic import Base, Vectorizable
 from fabricatio_core.rust import split_into_chunks
 
+from fabricatio_rag.rust import load_document
+
 
 class StoredDocumentModel[ST](Base, Vectorizable, metaclass=ABCMeta):
     """A base class for document models."""
@@ -36,6 +38,30 @@ class StoredDocumentModel[ST](Base, Vectorizable, metaclass=ABCMeta):
             for c in split_into_chunks(f.read_text(encoding="utf-8"), chunk_size, overlap)
         ]
 
+    @classmethod
+    def from_documents[S: "StoredDocumentModel[ST]"](
+        cls: Type[S], files: Sequence[Path], chunk_size: int = 512, overlap: float = 0.2, cache: bool = False
+    ) -> List[S]:
+        """Create documents by extracting PDF, DOCX or HTML files and splitting them into chunks.
+
+        Each page or heading section of a file is chunked on its own, so no chunk spans two of them.
+
+        Args:
+            files: Sequence of PDF, DOCX or HTML file paths to load.
+            chunk_size: Maximum word count per chunk.
+            overlap: Overlap ratio between consecutive chunks (0.0-1.0).
+            cache: Whether to reuse the extraction of files whose content was loaded before, see `load_document`.
+
+        Returns:
+            List of text chunk document model instances, one per chunk.
+        """
+        return [
+            cls.with_text_chunk(chunk=c)
+            for f in files
+            for section in load_document(f, cache=cache).sections
+            for c in split_into_chunks(section.text, chunk_size, overlap)
+        ]
+
     @classmethod
     def with_text_chunk(cls, chunk: str) -> Self:
         """Create with a text chunk."""
@@ -0,0 +1,146 @@
+# This file is automatically generated by pyo3_stub_gen
+
+import builtins
+import os
+import pathlib
+import typing
+
+__all__ = [
+    "DocumentSection",
+    "LoadedDocument",
+    "clear_document_cache",
+    "load_document",
+    "load_docx",
+    "load_html",
+    "load_pdf",
+]
+
+@typing.final
+class DocumentSection:
+    r"""A part of a document, either a page or the content under a heading."""
+    @property
+    def text(self) -> builtins.str:
+        r"""The cleaned text of the section, starting with its heading if it has one."""
+    @property
+    def heading(self) -> typing.Optional[builtins.str]:
+        r"""The heading the section starts with, `None` for the content before the first heading."""
+    @property
+    def level(self) -> typing.Optional[builtins.int]:
+        r"""The level of the heading, 1 for a top-level heading."""
+    @property
+    def page(self) -> typing.Optional[builtins.int]:
+        r"""The 1-based page number the section was found on, for paginated formats."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class LoadedDocument:
+    r"""A document extracted from a file, ready to be chunked."""
+    @property
+    def path(self) -> pathlib.Path:
+        r"""The path of the file the document was loaded from."""
+    @property
+    def format(self) -> builtins.str:
+        r"""The format of the file, one of `pdf`, `docx` or `html`."""
+    @property
+    def title(self) -> typing.Optional[builtins.str]:
+        r"""The title declared by the document, if any."""
+    @property
+    def sections(self) -> builtins.list[DocumentSection]:
+        r"""The sections of the document, in reading order."""
+    def text(self) -> builtins.str:
+        r"""Returns the text of the whole document, sections separated by blank lines."""
+    def __len__(self) -> builtins.int: ...
+    def __repr__(self) -> builtins.str: ...
+
+def clear_document_cache(store_root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None) -> builtins.int:
+    r"""Empties the cache of extracted documents.
+
+    The cached documents stop being referenced, so the next collection of the store past its
+    grace period removes them.
+
+    Args:
+        store_root: The directory of the content-addressed store. Defaults to None, using the
+            `cas` directory of the roaming directory shared by all packages.
+
+    Returns:
+        The number of documents dropped from the cache.
+
+    Raises:
+        OSError: If the store cannot be written.
+    """
+
+def load_document(
+    path: builtins.str | os.PathLike | pathlib.Path,
+    cache: builtins.bool = False,
+    store_root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+) -> LoadedDocument:
+    r"""Loads a PDF, DOCX or HTML document, picking the loader from the file extension.
+
+    Args:
+        path: The path of the document.
+        cache: Whether to go through the cache of extracted documents, kept in the
+            content-addressed store shared by the packages. A file whose content was extracted
+            before, under any path, is then read back from the cache instead of being parsed
+            again, and a newly extracted one is added to it. Defaults to False.
+        store_root: The directory of the content-addressed store used by the cache. Defaults
+            to None, using the `cas` directory of the roaming directory.
+
+    Returns:
+        The extracted document.
+
+    Raises:
+        ValueError: If the extension is not supported or the file is malformed.
+        OSError: If the file cannot be read, or the cache cannot be written.
+    """
+
+def load_docx(path: builtins.str | os.PathLike | pathlib.Path) -> LoadedDocument:
+    r"""Loads the text of a DOCX file.
+
+    The body is split into sections at its headings, each section carrying the text and level
+    of the heading it starts with. The title comes from the document properties, falling back
+    to the first paragraph styled as a title.
+
+    Args:
+        path: The path of the DOCX file.
+
+    Returns:
+        The extracted document.
+
+    Raises:
+        ValueError: If the file is not a valid DOCX document.
+        OSError: If the file cannot be read.
+    """
+
+def load_html(path: builtins.str | os.PathLike | pathlib.Path) -> LoadedDocument:
+    r"""Loads the text of an HTML file.
+
+    The visible text is split into sections at `h1` to `h6` headings, each section carrying
+    the text and level of the heading it starts with. Scripts, styles and other non-textual
+    elements are dropped, and block elements such as `p`, `li` or `div` become paragraphs.
+
+    Args:
+        path: The path of the HTML file.
+
+    Returns:
+        The extracted document, titled by its `title` element.
+
+    Raises:
+        OSError: If the file cannot be read.
+    """
+
+def load_pdf(path: builtins.str | os.PathLike | pathlib.Path) -> LoadedDocument:
+    r"""Loads the text of a PDF file.
+
+    Each page with text becomes a section carrying its page number. Words hyphenated across
+    line breaks are joined back together.
+
+    Args:
+        path: The path of the PDF file.
+
+    Returns:
+        The extracted document.
+
+    Raises:
+        ValueError: If the file is not a valid PDF, truncated or otherwise malformed.
+        OSError: If the file cannot be read.
+    """
@@ -0,0 +1,215 @@
+"""Tests for the PDF, DOCX and HTML document loaders."""
+
+import zipfile
+from pathlib import Path
+from typing import List, Self
+
+import pytest
+from fabricatio_rag.models.document import StoredDocumentModel
+from fabricatio_rag.rust import clear_document_cache, load_document, load_docx, load_html, load_pdf
+
+HTML = """<!DOCTYPE html>
+<html>
+<head><title> Loader   Guide </title><style>body { color: red; }</style></head>
+<body>
+<p>Intro   text
+spanning lines.</p>
+<h1>Install</h1>
+<p>Run <b>pip</b> install.</p>
+<script>console.log("hidden");</script>
+<h2>Extras</h2>
+<ul><li>first</li><li>second</li></ul>
+</body>
+</html>
+"""
+
+
+def write_docx(path: Path, paragraphs: List[tuple[str | None, str]], title: str | None = None) -> None:
+    """Write a minimal DOCX file made of (style, text) paragraphs."""
+    body = "".join(
+        "<w:p>"
+        + (f'<w:pPr><w:pStyle w:val="{style}"/></w:pPr>' if style else "")
+        + f"<w:r><w:t>{text}</w:t></w:r></w:p>"
+        for style, text in paragraphs
+    )
+    with zipfile.ZipFile(path, "w") as archive:
+        archive.writestr(
+            "word/document.xml",
+            '<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">'
+            f"<w:body>{body}</w:body></w:document>",
+        )
+        if title is not None:
+            archive.writestr(
+                "docProps/core.xml",
+                '<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties"'
+                ' xmlns:dc="http://purl.org/dc/elements/1.1/">'
+                f"<dc:title>{title}</dc:title></cp:coreProperties>",
+            )
+
+
+def write_pdf(path: Path, pages: List[str]) -> None:
+    """Write a minimal PDF file with one line of Helvetica text per page."""
+    font = 3 + 2 * len(pages)
+    objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        f"<< /Type /Pages /Kids [{' '.join(f'{3 + 2 * i} 0 R' for i in range(len(pages)))}] /Count {len(pages)} >>",
+    ]
+    for i, text in enumerate(pages):
+        stream = f"BT /F1 12 Tf 72 720 Td ({text}) Tj ET"
+        objects.append(
+            f"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents {4 + 2 * i} 0 R"
+            f" /Resources << /Font << /F1 {font} 0 R >> >> >>"
+        )
+        objects.append(f"<< /Length {len(stream)} >>\nstream\n{stream}\nendstream")
+    objects.append("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>")
+
+    content = "%PDF-1.4\n"
+    offsets = []
+    for number, body in enumerate(objects, start=1):
+        offsets.append(len(content))
+        content += f"{number} 0 obj\n{body}\nendobj\n"
+    xref = len(content)
+    content += f"xref\n0 {len(objects) + 1}\n0000000000 65535 f \n"
+    content += "".join(f"{offset:010d} 00000 n \n" for offset in offsets)
+    content += f"trailer\n<< /Size {len(objects) + 1} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n"
+    path.write_bytes(content.encode("latin-1"))
+
+
+def test_load_html(tmp_path: Path) -> None:
+    """HTML is split at headings, with scripts and styles dropped."""
+    path = tmp_path / "guide.html"
+    path.write_text(HTML, encoding="utf-8")
+    document = load_html(path)
+
+    assert document.format == "html"
+    assert document.title == "Loader Guide"
+    assert [(s.heading, s.level) for s in document.sections] == [(None, None), ("Install", 1), ("Extras", 2)]
+    assert document.sections[0].text == "Intro text spanning lines."
+    assert document.sections[1].text == "Install\n\nRun pip install."
+    assert document.sections[2].text == "Extras\n\nfirst\n\nsecond"
+    assert "hidden" not in document.text()
+    assert "color" not in document.text()
+
+
+def test_load_docx(tmp_path: Path) -> None:
+    """DOCX paragraphs are grouped under their heading styles."""
+    path = tmp_path / "report.docx"
+    write_docx(
+        path,
+        [
+            ("Title", "Quarterly Report"),
+            (None, "Summary &amp; scope."),
+            ("Heading2", "Numbers"),
+            (None, "Revenue grew."),
+            (None, "Costs fell."),
+        ],
+    )
+    document = load_docx(path)
+
+    assert document.format == "docx"
+    assert document.title == "Quarterly Report"
+    assert [(s.heading, s.level) for s in document.sections] == [("Quarterly Report", 1), ("Numbers", 2)]
+    assert document.sections[0].text == "Quarterly Report\n\nSummary & scope."
+    assert document.sections[1].text == "Numbers\n\nRevenue grew.\n\nCosts fell."
+
+
+def test_load_docx_core_title(tmp_path: Path) -> None:
+    """The title of the document properties takes precedence over a title paragraph."""
+    path = tmp_path / "titled.docx"
+    write_docx(path, [("Title", "Body Title"), (None, "Text.")], title="Core Title")
+    assert load_docx(path).title == "Core Title"
+
+
+def test_load_docx_invalid(tmp_path: Path) -> None:
+    """Files that are not zip archives are rejected."""
+    path = tmp_path / "broken.docx"
+    path.write_text("not a docx", encoding="utf-8")
+    with pytest.raises(ValueError, match="Invalid DOCX"):
+        load_docx(path)
+
+
+def test_load_pdf(tmp_path: Path) -> None:
+    """Each PDF page becomes a section carrying its page number."""
+    path = tmp_path / "paper.pdf"
+    write_pdf(path, ["Hello first page", "Second page text"])
+    document = load_pdf(path)
+
+    assert document.format == "pdf"
+    assert len(document) == 2
+    assert [s.page for s in document.sections] == [1, 2]
+    assert "Hello first page" in document.sections[0].text
+    assert "Second page text" in document.sections[1].text
+
+
+def test_load_pdf_malformed(tmp_path: Path) -> None:
+    """Truncated or malformed PDFs raise a ValueError, even those the parser panics on."""
+    path = tmp_path / "paper.pdf"
+    write_pdf(path, ["Hello first page", "Second page text"])
+    content = path.read_bytes()
+    for length in (len(content) // 2, len(content) - 40, 20):
+        path.write_bytes(content[:length])
+        with pytest.raises(ValueError, match="Invalid PDF"):
+            load_pdf(path)
+
+    # Blanking the required page size out keeps the offsets of the cross-reference table valid.
+    media_box = b"/MediaBox [0 0 612 792]"
+    path.write_bytes(content.replace(media_box, b" " * len(media_box)))
+    with pytest.raises(ValueError, match="Invalid PDF"):
+        load_pdf(path)
+
+
+def test_load_document_dispatch(tmp_path: Path) -> None:
+    """The loader is picked from the extension, and unknown extensions are rejected."""
+    path = tmp_path / "page.HTM"
+    path.write_text(HTML, encoding="utf-8")
+    assert load_document(path).format == "html"
+
+    with pytest.raises(ValueError, match="Unknown document extension"):
+        load_document(tmp_path / "notes.txt")
+    with pytest.raises(OSError):
+        load_document(tmp_path / "missing.pdf")
+
+
+def test_load_document_cache(tmp_path: Path) -> None:
+    """Cached documents are shared by files of the same content and dropped when the cache is cleared."""
+    store_root = tmp_path / "cas"
+    first = tmp_path / "first.html"
+    second = tmp_path / "copy" / "second.html"
+    second.parent.mkdir()
+    for path in (first, second):
+        path.write_text(HTML, encoding="utf-8")
+
+    loaded = load_document(first, cache=True, store_root=store_root)
+    cached = load_document(second, cache=True, store_root=store_root)
+    assert cached.path == second
+    assert cached.title == loaded.title == "Loader Guide"
+    assert [s.text for s in cached.sections] == [s.text for s in loaded.sections]
+    assert [s.level for s in cached.sections] == [s.level for s in loaded.sections]
+
+    assert clear_document_cache(store_root) == 1
+    assert clear_document_cache(store_root) == 0
+
+
+class ChunkDoc(StoredDocumentModel[str]):
+    """Minimal stored document model for chunking tests."""
+
+    content: str
+
+    def prepare_insertion(self, vector: List[float]) -> str:
+        """Return the content as the record."""
+        return self.content
+
+    @classmethod
+    def with_text_chunk(cls, chunk: str) -> Self:
+        """Create from a text chunk."""
+        return cls(content=chunk)
+
+
+def test_from_documents(tmp_path: Path) -> None:
+    """Chunks never span two sections of a document."""
+    path = tmp_path / "guide.html"
+    path.write_text(HTML, encoding="utf-8")
+    docs = ChunkDoc.from_documents([path], chunk_size=100, overlap=0.0)
+    assert len(docs) == 3
+    assert "pip" in docs[1].content
+    assert "Extras" not in docs[1].content
@@ -0,0 +1,124 @@
+//! Cache of extracted documents in the content-addressed store shared by the packages.
+//!
+//! A document is cached as the JSON of its title and sections, referenced under the `rag`
+//! owner by a name made of its format, the version of the extraction and the BLAKE3 hash of
+//! the file. Loading a file whose content was extracted before, wherever it lives, reads the
+//! JSON back instead of parsing the file again.
+
+use crate::document::{DocumentSection, LoadedDocument};
+use fabricatio_constants::CAS;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::PathBuf;
+use utils::cas::ContentStore;
+
+/// Owner of the references of the cached documents in the store.
+const OWNER: &str = "rag";
+
+/// Version of the extraction, to bump whenever the loaders extract documents differently so
+/// that documents cached by older versions are extracted again.
+const EXTRACTION_VERSION: u32 = 1;
+
+/// What the cache keeps of a document, its path being that of whichever file is loaded.
+#[derive(Serialize, Deserialize)]
+struct CachedDocument {
+    title: Option<String>,
+    sections: Vec<DocumentSection>,
+}
+
+/// Opens the store at the given root, the shared one by default.
+fn open_store(store_root: Option<PathBuf>) -> PyResult<ContentStore> {
+    Ok(ContentStore::open(
+        store_root.unwrap_or_else(|| CAS.clone()),
+    )?)
+}
+
+/// Loads a document through the cache, extracting it with `load` on a miss.
+///
+/// A cached document that cannot be read back, such as one left corrupted, counts as a miss.
+pub(crate) fn load_cached<F>(
+    python: Python,
+    path: PathBuf,
+    format: &str,
+    store_root: Option<PathBuf>,
+    load: F,
+) -> PyResult<LoadedDocument>
+where
+    F: FnOnce(Python, PathBuf) -> PyResult<LoadedDocument>,
+{
+    let store = open_store(store_root)?;
+    let name = python.detach(|| -> PyResult<String> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(File::open(&path)?)?;
+        Ok(format!(
+            "{format}-v{EXTRACTION_VERSION}-{}",
+            hasher.finalize().to_hex()
+        ))
+    })?;
+
+    let cached = python.detach(|| {
+        store
+            .reference(OWNER, &name)
+            .ok()
+            .flatten()
+            .and_then(|hash| store.get(&hash).ok())
+            .and_then(|json| serde_json::from_slice::<CachedDocument>(&json).ok())
+    });
+    if let Some(cached) = cached {
+        return Ok(LoadedDocument::new(
+            &path,
+            format,
+            cached.title,
+            cached.sections,
+        ));
+    }
+
+    let document = load(python, path)?;
+    let json = serde_json::to_vec(&CachedDocument {
+        title: document.title.clone(),
+        sections: document.sections.clone(),
+    })
+    .expect("documents serialize to JSON");
+    python.detach(|| -> PyResult<()> {
+        let hash = store.put_bytes(&json)?;
+        Ok(store.set_reference(OWNER, &name, &hash)?)
+    })?;
+    Ok(document)
+}
+
+/// Empties the cache of extracted documents.
+///
+/// The cached documents stop being referenced, so the next collection of the store past its
+/// grace period removes them.
+///
+/// Args:
+///     store_root: The directory of the content-addressed store. Defaults to None, using the
+///         `cas` directory of the roaming directory shared by all packages.
+///
+/// Returns:
+///     The number of documents dropped from the cache.
+///
+/// Raises:
+///     OSError: If the store cannot be written.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (store_root = None))]
+fn clear_document_cache(python: Python, store_root: Option<PathBuf>) -> PyResult<usize> {
+    let store = open_store(store_root)?;
+    let mut dropped = 0;
+    python.detach(|| {
+        store.update_references(OWNER, |refs| {
+            dropped = refs.len();
+            refs.clear();
+        })
+    })?;
+    Ok(dropped)
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(clear_document_cache, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,247 @@
+//! Documents extracted by the loaders.
+//!
+//! Every loader turns a file into a `LoadedDocument`, a list of sections of cleaned text that
+//! keep the structure of the source: the page of a PDF, or the heading a part of a DOCX or
+//! HTML document belongs to. Each section can be handed to the chunker on its own, so chunks
+//! never straddle a page or a heading.
+
+use crate::{cache, docx, html, pdf};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A part of a document, either a page or the content under a heading.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentSection {
+    /// The cleaned text of the section, starting with its heading if it has one.
+    pub(crate) text: String,
+    /// The heading the section starts with, `None` for the content before the first heading.
+    pub(crate) heading: Option<String>,
+    /// The level of the heading, 1 for a top-level heading.
+    pub(crate) level: Option<u8>,
+    /// The 1-based page number the section was found on, for paginated formats.
+    pub(crate) page: Option<usize>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl DocumentSection {
+    fn __repr__(&self) -> String {
+        format!(
+            "DocumentSection(heading={:?}, level={:?}, page={:?}, text={:?})",
+            self.heading,
+            self.level,
+            self.page,
+            self.text.chars().take(40).collect::<String>()
+        )
+    }
+}
+
+/// A document extracted from a file, ready to be chunked.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedDocument {
+    /// The path of the file the document was loaded from.
+    pub(crate) path: PathBuf,
+    /// The format of the file, one of `pdf`, `docx` or `html`.
+    pub(crate) format: String,
+    /// The title declared by the document, if any.
+    pub(crate) title: Option<String>,
+    /// The sections of the document, in reading order.
+    pub(crate) sections: Vec<DocumentSection>,
+}
+
+impl LoadedDocument {
+    pub(crate) fn new(
+        path: &Path,
+        format: &str,
+        title: Option<String>,
+        sections: Vec<DocumentSection>,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format: format.to_string(),
+            title,
+            sections,
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl LoadedDocument {
+    /// Returns the text of the whole document, sections separated by blank lines.
+    fn text(&self) -> String {
+        self.sections
+            .iter()
+            .map(|section| section.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn __len__(&self) -> usize {
+        self.sections.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LoadedDocument(path={:?}, format={:?}, title={:?}, sections={})",
+            self.path,
+            self.format,
+            self.title,
+            self.sections.len()
+        )
+    }
+}
+
+/// Cleans extracted text.
+///
+/// Runs of whitespace within a line are collapsed into a single space, lines are trimmed,
+/// and runs of blank lines are collapsed into a single one.
+pub(crate) fn clean_text(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut blank = false;
+    for line in text.lines() {
+        let mut words = line.split_whitespace().peekable();
+        if words.peek().is_none() {
+            blank = !cleaned.is_empty();
+            continue;
+        }
+        if !cleaned.is_empty() {
+            cleaned.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        blank = false;
+        for (i, word) in words.enumerate() {
+            if i > 0 {
+                cleaned.push(' ');
+            }
+            cleaned.push_str(word);
+        }
+    }
+    cleaned
+}
+
+/// Collects the paragraphs of a document into sections split at its headings.
+#[derive(Default)]
+pub(crate) struct SectionBuilder {
+    sections: Vec<DocumentSection>,
+    heading: Option<String>,
+    level: Option<u8>,
+    paragraphs: Vec<String>,
+    current: String,
+}
+
+impl SectionBuilder {
+    /// Appends text to the current paragraph.
+    pub(crate) fn push_text(&mut self, text: &str) {
+        self.current.push_str(text);
+    }
+
+    /// Ends the current paragraph, dropping it if it holds no text.
+    pub(crate) fn end_paragraph(&mut self) {
+        let paragraph = clean_text(&std::mem::take(&mut self.current));
+        if !paragraph.is_empty() {
+            self.paragraphs.push(paragraph);
+        }
+    }
+
+    /// Ends the current section and starts a new one under the given heading.
+    pub(crate) fn start_section(&mut self, heading: &str, level: u8) {
+        self.end_section();
+        let heading = clean_text(heading);
+        if !heading.is_empty() {
+            self.heading = Some(heading);
+            self.level = Some(level);
+        }
+    }
+
+    fn end_section(&mut self) {
+        self.end_paragraph();
+        let heading = self.heading.take();
+        let level = self.level.take();
+        let paragraphs = std::mem::take(&mut self.paragraphs);
+        if heading.is_none() && paragraphs.is_empty() {
+            return;
+        }
+        self.sections.push(DocumentSection {
+            text: heading
+                .iter()
+                .chain(paragraphs.iter())
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            heading,
+            level,
+            page: None,
+        });
+    }
+
+    /// Ends the last section and returns all sections.
+    pub(crate) fn finish(mut self) -> Vec<DocumentSection> {
+        self.end_section();
+        self.sections
+    }
+}
+
+/// Extracts a document from the file at a path.
+type Loader = fn(Python, PathBuf) -> PyResult<LoadedDocument>;
+
+/// Loads a PDF, DOCX or HTML document, picking the loader from the file extension.
+///
+/// Args:
+///     path: The path of the document.
+///     cache: Whether to go through the cache of extracted documents, kept in the
+///         content-addressed store shared by the packages. A file whose content was extracted
+///         before, under any path, is then read back from the cache instead of being parsed
+///         again, and a newly extracted one is added to it. Defaults to False.
+///     store_root: The directory of the content-addressed store used by the cache. Defaults
+///         to None, using the `cas` directory of the roaming directory.
+///
+/// Returns:
+///     The extracted document.
+///
+/// Raises:
+///     ValueError: If the extension is not supported or the file is malformed.
+///     OSError: If the file cannot be read, or the cache cannot be written.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (path, cache = false, store_root = None))]
+fn load_document(
+    python: Python,
+    path: PathBuf,
+    cache: bool,
+    store_root: Option<PathBuf>,
+) -> PyResult<LoadedDocument> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let (format, load): (&str, Loader) = match extension.as_str() {
+        "pdf" => ("pdf", pdf::load_pdf),
+        "docx" => ("docx", docx::load_docx),
+        "html" | "htm" | "xhtml" => ("html", html::load_html),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown document extension `{extension}`, expected one of `pdf`, `docx`, `html`, `htm`, `xhtml`"
+            )));
+        }
+    };
+    if cache {
+        cache::load_cached(python, path, format, store_root, load)
+    } else {
+        load(python, path)
+    }
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<DocumentSection>()?;
+    m.add_class::<LoadedDocument>()?;
+    m.add_function(wrap_pyfunction!(load_document, m)?)?;
+    Ok(())
+}
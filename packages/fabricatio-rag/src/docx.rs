@@ -0,0 +1,168 @@
+//! Text extraction from DOCX files, split into sections at headings.
+//!
+//! A DOCX file is a zip archive whose `word/document.xml` holds the body as `w:p` paragraphs
+//! made of `w:t` text runs. Headings are recognised by their `HeadingN` or `Title` paragraph
+//! style, or by an explicit outline level.
+
+use crate::document::{LoadedDocument, SectionBuilder, clean_text};
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use zip::ZipArchive;
+
+const DOCUMENT_PART: &str = "word/document.xml";
+const CORE_PROPERTIES_PART: &str = "docProps/core.xml";
+
+/// Reads the value of the `w:val` attribute of an element.
+fn val(element: &BytesStart) -> quick_xml::Result<Option<String>> {
+    Ok(match element.try_get_attribute("w:val")? {
+        Some(attribute) => Some(attribute.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// Maps a paragraph style to a heading level, `0` standing for the title.
+fn heading_level(style: &str) -> Option<u8> {
+    if style.eq_ignore_ascii_case("title") {
+        return Some(0);
+    }
+    let digits = style
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("heading"))
+        .map(|_| style[7..].trim())?;
+    digits.parse().ok().filter(|level| (1..=9).contains(level))
+}
+
+/// Parses the body of a document into sections, returning the text of its title paragraph.
+fn parse_body(xml: &str, builder: &mut SectionBuilder) -> quick_xml::Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut title = None;
+    let mut paragraph = String::new();
+    let mut level: Option<u8> = None;
+    let mut in_text = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) | Event::Empty(element) => match element.name().as_ref() {
+                b"w:p" => {
+                    paragraph.clear();
+                    level = None;
+                }
+                b"w:pStyle" => {
+                    level = level.or(val(&element)?.as_deref().and_then(heading_level));
+                }
+                b"w:outlineLvl" => {
+                    level = level.or(val(&element)?
+                        .and_then(|value| value.parse::<u8>().ok())
+                        .filter(|value| *value < 9)
+                        .map(|value| value + 1));
+                }
+                b"w:t" => in_text = true,
+                b"w:tab" => paragraph.push('\t'),
+                b"w:br" | b"w:cr" => paragraph.push('\n'),
+                _ => {}
+            },
+            Event::Text(text) if in_text => paragraph.push_str(&text.unescape()?),
+            Event::End(element) => match element.name().as_ref() {
+                b"w:t" => in_text = false,
+                b"w:p" => match level {
+                    Some(0) => {
+                        let text = clean_text(&paragraph);
+                        builder.start_section(&text, 1);
+                        title = title.or(Some(text).filter(|text| !text.is_empty()));
+                    }
+                    Some(level) => builder.start_section(&paragraph, level),
+                    None => {
+                        builder.push_text(&paragraph);
+                        builder.end_paragraph();
+                    }
+                },
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(title)
+}
+
+/// Reads the `dc:title` of the core properties of a document.
+fn parse_core_title(xml: &str) -> quick_xml::Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut title = String::new();
+    let mut in_title = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) if element.name().as_ref() == b"dc:title" => in_title = true,
+            Event::End(element) if element.name().as_ref() == b"dc:title" => in_title = false,
+            Event::Text(text) if in_title => title.push_str(&text.unescape()?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(Some(clean_text(&title)).filter(|title| !title.is_empty()))
+}
+
+/// Reads a part of the archive as a string, `None` if the archive has no such part.
+fn read_part(
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    let mut part = match archive.by_name(name) {
+        Ok(part) => part,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut content = String::new();
+    part.read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    Ok(Some(content))
+}
+
+fn parse(bytes: Vec<u8>) -> Result<(Option<String>, SectionBuilder), String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let body = read_part(&mut archive, DOCUMENT_PART)?
+        .ok_or_else(|| format!("missing `{DOCUMENT_PART}`"))?;
+    let mut builder = SectionBuilder::default();
+    let body_title = parse_body(&body, &mut builder).map_err(|e| e.to_string())?;
+    let core_title = match read_part(&mut archive, CORE_PROPERTIES_PART)? {
+        Some(core) => parse_core_title(&core).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    Ok((core_title.or(body_title), builder))
+}
+
+/// Loads the text of a DOCX file.
+///
+/// The body is split into sections at its headings, each section carrying the text and level
+/// of the heading it starts with. The title comes from the document properties, falling back
+/// to the first paragraph styled as a title.
+///
+/// Args:
+///     path: The path of the DOCX file.
+///
+/// Returns:
+///     The extracted document.
+///
+/// Raises:
+///     ValueError: If the file is not a valid DOCX document.
+///     OSError: If the file cannot be read.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+pub(crate) fn load_docx(python: Python, path: PathBuf) -> PyResult<LoadedDocument> {
+    let bytes = std::fs::read(&path).into_pyresult()?;
+    let (title, builder) = python
+        .detach(|| parse(bytes))
+        .map_err(|e| PyValueError::new_err(format!("Invalid DOCX {}: {e}", path.display())))?;
+    Ok(LoadedDocument::new(&path, "docx", title, builder.finish()))
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(load_docx, m)?)?;
+    Ok(())
+}
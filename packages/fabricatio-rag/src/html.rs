@@ -0,0 +1,114 @@
+//! Text extraction from HTML files, split into sections at `h1` to `h6` headings.
+
+use crate::document::{LoadedDocument, SectionBuilder, clean_text};
+use error_mapping::AsPyErr;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use scraper::{ElementRef, Html, Selector};
+use std::path::PathBuf;
+
+/// Elements whose content is never part of the text.
+const SKIPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "object", "canvas",
+];
+
+/// Elements that start and end a paragraph.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+fn walk(element: ElementRef, builder: &mut SectionBuilder) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            builder.push_text(&text.replace(['\n', '\r', '\t'], " "));
+            continue;
+        }
+        let Some(child) = ElementRef::wrap(child) else {
+            continue;
+        };
+        let name = child.value().name();
+        if SKIPPED.contains(&name) {
+            continue;
+        }
+        if let Some(level) = name
+            .strip_prefix('h')
+            .and_then(|level| level.parse::<u8>().ok())
+            .filter(|level| (1..=6).contains(level))
+        {
+            builder.start_section(&child.text().collect::<String>(), level);
+        } else if name == "br" {
+            builder.push_text("\n");
+        } else if BLOCKS.contains(&name) {
+            builder.end_paragraph();
+            walk(child, builder);
+            builder.end_paragraph();
+        } else {
+            walk(child, builder);
+        }
+    }
+}
+
+fn parse(source: &str) -> (Option<String>, SectionBuilder) {
+    let html = Html::parse_document(source);
+    let title = Selector::parse("title")
+        .ok()
+        .and_then(|selector| html.select(&selector).next())
+        .map(|title| clean_text(&title.text().collect::<String>()))
+        .filter(|title| !title.is_empty());
+    let mut builder = SectionBuilder::default();
+    walk(html.root_element(), &mut builder);
+    (title, builder)
+}
+
+/// Loads the text of an HTML file.
+///
+/// The visible text is split into sections at `h1` to `h6` headings, each section carrying
+/// the text and level of the heading it starts with. Scripts, styles and other non-textual
+/// elements are dropped, and block elements such as `p`, `li` or `div` become paragraphs.
+///
+/// Args:
+///     path: The path of the HTML file.
+///
+/// Returns:
+///     The extracted document, titled by its `title` element.
+///
+/// Raises:
+///     OSError: If the file cannot be read.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+pub(crate) fn load_html(python: Python, path: PathBuf) -> PyResult<LoadedDocument> {
+    let source = std::fs::read_to_string(&path).into_pyresult()?;
+    let (title, builder) = python.detach(|| parse(&source));
+    Ok(LoadedDocument::new(&path, "html", title, builder.finish()))
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(load_html, m)?)?;
+    Ok(())
+}
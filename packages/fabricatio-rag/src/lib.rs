@@ -0,0 +1,25 @@
+#![cfg_attr(feature = "stubgen", allow(dead_code, unused,))]
+
+use pyo3::prelude::*;
+use pyo3_stub_gen::define_stub_info_gatherer;
+
+mod cache;
+mod document;
+mod docx;
+mod html;
+mod pdf;
+/// A Python module implemented in Rust. The name of this function must match
+/// the `lib.name` setting in the `Cargo.toml`, else Python will not be able to
+/// import the module.
+#[cfg(not(feature = "stubgen"))]
+#[pymodule]
+fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    document::register(python, m)?;
+    cache::register(python, m)?;
+    pdf::register(python, m)?;
+    docx::register(python, m)?;
+    html::register(python, m)?;
+    Ok(())
+}
+
+define_stub_info_gatherer!(stub_info);
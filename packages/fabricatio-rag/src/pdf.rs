@@ -0,0 +1,100 @@
+//! Text extraction from PDF files, one section per page.
+
+use crate::document::{DocumentSection, LoadedDocument, clean_text};
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::any::Any;
+use std::fmt::Display;
+use std::panic;
+use std::path::PathBuf;
+
+/// Joins words hyphenated across a line break, e.g. `exam-` followed by `ple`.
+fn join_hyphenated(text: &str) -> String {
+    let mut joined = String::with_capacity(text.len());
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end();
+        let hyphenated = line
+            .strip_suffix('-')
+            .filter(|head| head.chars().last().is_some_and(char::is_alphabetic))
+            .filter(|_| {
+                lines
+                    .peek()
+                    .and_then(|next| next.trim_start().chars().next())
+                    .is_some_and(char::is_lowercase)
+            });
+        match hyphenated {
+            Some(head) => {
+                joined.push_str(head);
+                if let Some(next) = lines.next() {
+                    joined.push_str(next.trim_start());
+                    joined.push('\n');
+                }
+            }
+            None => {
+                joined.push_str(line);
+                joined.push('\n');
+            }
+        }
+    }
+    joined
+}
+
+/// The message a panic was raised with.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("the parser panicked")
+}
+
+/// Loads the text of a PDF file.
+///
+/// Each page with text becomes a section carrying its page number. Words hyphenated across
+/// line breaks are joined back together.
+///
+/// Args:
+///     path: The path of the PDF file.
+///
+/// Returns:
+///     The extracted document.
+///
+/// Raises:
+///     ValueError: If the file is not a valid PDF, truncated or otherwise malformed.
+///     OSError: If the file cannot be read.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+pub(crate) fn load_pdf(python: Python, path: PathBuf) -> PyResult<LoadedDocument> {
+    let bytes = std::fs::read(&path).into_pyresult()?;
+    let invalid = |reason: &dyn Display| {
+        PyValueError::new_err(format!("Invalid PDF {}: {reason}", path.display()))
+    };
+    // The parser panics on some malformed files, such as truncated ones, instead of failing.
+    let pages = python
+        .detach(|| panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(&bytes)))
+        .map_err(|payload| invalid(&panic_message(payload.as_ref())))?
+        .map_err(|e| invalid(&e))?;
+    let sections = pages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, page)| {
+            let text = clean_text(&join_hyphenated(page));
+            (!text.is_empty()).then_some(DocumentSection {
+                text,
+                heading: None,
+                level: None,
+                page: Some(i + 1),
+            })
+        })
+        .collect();
+    Ok(LoadedDocument::new(&path, "pdf", None, sections))
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(load_pdf, m)?)?;
+    Ok(())
+}
@@ -4,15 +4,29 @@ from abc import ABC
 from itertools import count
 from typing import Optional, Unpack
 
-from fabricatio_core import logger
+from fabricatio_core import Event, logger
 from fabricatio_core.capabilities.propose import Propose
+from fabricatio_core.emitter import EMITTER
 from fabricatio_core.models.kwargs_types import ValidateKwargs
 from fabricatio_core.utils import ok, wrap_in_block
 
-from fabricatio_thinking.models.thinking import Thought
+from fabricatio_thinking.config import thinking_config
+from fabricatio_thinking.models.thinking import Thought, ThinkingProgress
 from fabricatio_thinking.rust import ThoughtVCS
 
 
+def progress_event(branch: Optional[str] = None) -> str:
+    """Return the event the progress of a branch is emitted on.
+
+    Args:
+        branch (Optional[str]): The name of the branch, None for the default branch.
+
+    Returns:
+        str: The configured progress event followed by the branch, `default` for the default branch.
+    """
+    return Event.instantiate_from(thinking_config.progress_event).push(branch or "default").collapse()
+
+
 class Thinking(Propose, ABC):
     """This class contains the capabilities for the thinking."""
 
@@ -30,10 +44,14 @@ class Thinking(Propose, ABC):
         thought process. The loop continues until a maximum number of steps is reached or
         the end of the thinking process is signaled.
 
+        Whenever a commit brings a branch to one of the milestones of the VCS, a `ThinkingProgress`
+        is emitted on `progress_event(branch)`, so that orchestrators can budget further LLM calls.
+
         Args:
             question (str): The input question or task to be processed.
             vcs (Optional[ThoughtVCS]): An optional ThoughtVCS instance for managing
-                version-controlled thoughts. If not provided, a new instance will be created.
+                version-controlled thoughts. If not provided, a new instance with the configured
+                milestones will be created.
             max_steps (Optional[int]): The maximum number of thinking steps to perform.
                 If not specified, an effectively infinite number of steps will be allowed.
             **kwargs: Additional keyword arguments passed to the underlying propose method.
@@ -42,7 +60,7 @@ class Thinking(Propose, ABC):
             ThoughtVCS: The final state of the ThoughtVCS after completing the thinking
                 process.
         """
-        vcs = vcs or ThoughtVCS()
+        vcs = vcs or ThoughtVCS(thinking_config.milestones)
         logger.debug("Initialized ThoughtVCS")
 
         for step in range(1, max_steps + 1) if max_steps is not None else count():
@@ -72,6 +90,19 @@ class Thinking(Propose, ABC):
             vcs.commit(
                 content=thought.thought, serial=thought.serial, estimated=thought.estimated, branch=thought.branch
             )
+            for milestone in vcs.take_milestones(thought.branch):
+                logger.debug(f"Branch {thought.branch or 'default'} reached {milestone:.0%} of its estimate")
+                await EMITTER.emit(
+                    progress_event(thought.branch),
+                    ThinkingProgress(
+                        question=question,
+                        branch=thought.branch,
+                        milestone=milestone,
+                        commits=len(vcs.export_branch(thought.branch)),
+                        estimated=thought.estimated,
+                        remaining=ok(vcs.remaining(thought.branch)),
+                    ),
+                )
             if thought.end:
                 logger.debug("End of thinking process reached.")
                 break
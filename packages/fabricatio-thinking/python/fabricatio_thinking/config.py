@@ -1,6 +1,7 @@
 """Module containing configuration classes for fabricatio-thinking."""
 
-from dataclasses import dataclass
+from dataclasses import dataclass, field
+from typing import List
 
 from fabricatio_core import CONFIG
 
@@ -9,6 +10,11 @@ from fabricatio_core import CONFIG
 class ThinkingConfig:
     """Configuration for fabricatio-thinking."""
 
+    milestones: List[float] = field(default_factory=lambda: [0.25, 0.5, 0.75, 1.0])
+    """Fractions of the estimated number of thoughts at which a progress event is emitted."""
+    progress_event: str = "thinking_progress"
+    """Event the progress is emitted on, followed by the name of the branch, `default` for the default branch."""
+
 
 thinking_config = CONFIG.load("thinking", ThinkingConfig)
 
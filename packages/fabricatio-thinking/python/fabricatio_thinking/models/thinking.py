@@ -7,7 +7,7 @@ details.
 
 from typing import Optional
 
-from fabricatio_core.models.generic import SketchedAble
+from fabricatio_core.models.generic import Base, SketchedAble
 
 
 class Thought(SketchedAble):
@@ -29,3 +29,20 @@ class Thought(SketchedAble):
     """The step number from which a branch is created."""
     branch: Optional[str] = None
     """Unique identifier for the branch."""
+
+
+class ThinkingProgress(Base):
+    """The progress of a branch of thoughts, emitted when it reaches a milestone of its estimate."""
+
+    question: str
+    """The question being thought about."""
+    branch: Optional[str]
+    """The branch that progressed, None for the default branch."""
+    milestone: float
+    """The fraction of the estimate reached."""
+    commits: int
+    """The number of thoughts committed to the branch."""
+    estimated: int
+    """The estimated total number of thoughts of the branch."""
+    remaining: int
+    """The number of thoughts still expected, 0 once the estimate is exceeded."""
@@ -10,11 +10,23 @@ __all__ = [
 @typing.final
 class ThoughtVCS:
     r"""Represents a simple version control system for managing branches and their commits."""
-    def __new__(cls) -> ThoughtVCS:
+    def __new__(cls, milestones: typing.Sequence[builtins.float] = []) -> ThoughtVCS:
         r"""Creates a new instance of `ThoughtVCS` with default branches.
 
+        Args:
+            milestones: Fractions of the estimated commit count, each in `(0, 1]`, to report
+                through `take_milestones` when a branch reaches them.
+
         Returns:
             A new `ThoughtVCS` instance initialized with default branch data.
+
+        Raises:
+            ValueError: If a milestone is not in `(0, 1]`.
+        """
+    @property
+    def milestones(self) -> builtins.list[builtins.float]:
+        r"""The fractions of the estimated commit count reported when a branch reaches them, in
+        increasing order.
         """
     def commit(
         self,
@@ -36,6 +48,40 @@ class ThoughtVCS:
         Returns:
             Some(new_commit_count) if the commit was added, or None otherwise.
         """
+    def remaining(self, branch: typing.Optional[builtins.str] = None) -> typing.Optional[builtins.int]:
+        r"""Returns the number of commits a branch still expects according to its estimate.
+
+        Args:
+            branch: The name of the branch, or None for the default branch.
+
+        Returns:
+            The estimated number of commits minus the number of commits, 0 once the estimate
+            is exceeded, or None if the branch does not exist.
+        """
+    def progress(self, branch: typing.Optional[builtins.str] = None) -> typing.Optional[builtins.float]:
+        r"""Returns how far a branch has progressed against its estimate.
+
+        Args:
+            branch: The name of the branch, or None for the default branch.
+
+        Returns:
+            The number of commits divided by the estimated number, which exceeds 1.0 once the
+            estimate is exceeded, or None if the branch does not exist or has no estimate.
+        """
+    def take_milestones(self, branch: typing.Optional[builtins.str] = None) -> builtins.list[builtins.float]:
+        r"""Takes the milestones a branch has reached since they were last taken.
+
+        Each milestone is reported once per branch, when a commit brings the commit count to
+        that fraction of the estimate or beyond. A checkout below a milestone lets it be
+        reported again.
+
+        Args:
+            branch: The name of the branch, or None for the default branch.
+
+        Returns:
+            The milestones reached, in increasing order, empty if none or if the branch does
+            not exist.
+        """
     def revise(
         self, content: builtins.str, serial: builtins.int, branch: typing.Optional[builtins.str]
     ) -> typing.Optional[builtins.int]:
@@ -1,11 +1,12 @@
 """Tests for the thinking."""
 
 import pytest
+from fabricatio_core.emitter import EMITTER
 from fabricatio_mock.models.mock_role import LLMTestRole
 from fabricatio_mock.models.mock_router import return_model_json_router_usage
 from fabricatio_mock.utils import install_router_usage
-from fabricatio_thinking.capabilities.thinking import Thinking
-from fabricatio_thinking.models.thinking import Thought
+from fabricatio_thinking.capabilities.thinking import Thinking, progress_event
+from fabricatio_thinking.models.thinking import ThinkingProgress, Thought
 from fabricatio_thinking.rust import ThoughtVCS
 
 
@@ -114,3 +115,57 @@ async def test_thinking_with_router_param(role: ThinkingRole, vcs: ThoughtVCS, t
     with install_router_usage(*responses):
         result_vcs = await role.thinking("Test", vcs=vcs, max_steps=10)
         assert result_vcs is not None
+
+
+def test_vcs_milestones() -> None:
+    """Milestones are reported once when reached, and again after a checkout below them."""
+    vcs = ThoughtVCS([0.5, 1.0, 0.25])
+    assert vcs.milestones == [0.25, 0.5, 1.0]
+    assert vcs.remaining() is None
+    assert vcs.take_milestones() == []
+
+    vcs.commit("one", 1, 4)
+    assert vcs.take_milestones() == [0.25]
+    vcs.commit("two", 2, 4)
+    vcs.commit("three", 3, 4)
+    assert vcs.take_milestones() == [0.5]
+    assert vcs.take_milestones() == []
+    assert vcs.remaining() == 1
+    assert vcs.progress() == 0.75
+
+    vcs.commit("four", 4, 4)
+    vcs.commit("five", 5, 4)
+    assert vcs.take_milestones() == [1.0]
+    assert vcs.remaining() == 0
+
+    vcs.commit("main", 1, 2, branch="alt")
+    vcs.checkout("alt", 0)
+    vcs.commit("again", 1, 2, branch="alt")
+    assert vcs.take_milestones("alt") == [0.5]
+
+    with pytest.raises(ValueError, match="Milestone"):
+        ThoughtVCS([1.5])
+
+
+@pytest.mark.asyncio
+async def test_thinking_emits_progress(role: ThinkingRole) -> None:
+    """Committing thoughts emits progress events for the milestones reached."""
+    received: list[ThinkingProgress] = []
+
+    async def on_progress(progress: ThinkingProgress) -> None:
+        received.append(progress)
+
+    thoughts = [
+        Thought(thought="First", end=False, serial=1, estimated=2),
+        Thought(thought="Second", end=True, serial=2, estimated=2),
+    ]
+    EMITTER.on(progress_event(), on_progress)
+    try:
+        with install_router_usage(*return_model_json_router_usage(*thoughts)):
+            await role.thinking("Test", vcs=ThoughtVCS([0.5, 1.0]), max_steps=10)
+    finally:
+        EMITTER.off(progress_event())
+    assert [(progress.milestone, progress.commits, progress.remaining) for progress in received] == [
+        (0.5, 1, 1),
+        (1.0, 2, 0),
+    ]
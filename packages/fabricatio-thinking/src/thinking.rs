@@ -1,5 +1,6 @@
 use fabricatio_logger::*;
 use fabricatio_logger::*;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
@@ -11,6 +12,10 @@ struct Branch {
     estimated: usize,
     /// List of commits in this branch.
     commits: Vec<String>,
+    /// Fractions of the estimate the commit count has reached, in increasing order.
+    reached: Vec<f64>,
+    /// Fractions reached since they were last taken.
+    pending: Vec<f64>,
 }
 
 impl Branch {
@@ -36,6 +41,29 @@ impl Branch {
         }
     }
 
+    /// Records the milestones the commit count has reached against the estimate and not
+    /// reached before.
+    ///
+    /// Args:
+    ///     milestones: Fractions of the estimate, in increasing order.
+    fn cross(&mut self, milestones: &[f64]) {
+        if self.estimated == 0 {
+            return;
+        }
+        let progress = self.commits.len() as f64 / self.estimated as f64;
+        for &milestone in milestones {
+            if milestone <= progress && !self.reached.contains(&milestone) {
+                self.reached.push(milestone);
+                self.pending.push(milestone);
+            }
+        }
+    }
+
+    /// Returns the number of commits still expected according to the estimate.
+    fn remaining(&self) -> usize {
+        self.estimated.saturating_sub(self.commits.len())
+    }
+
     /// Sets the estimated total number of commits for this branch.
     ///
     /// Args:
@@ -50,6 +78,9 @@ impl Branch {
 
     /// Returns a new `Branch` instance containing commits up to the given serial number.
     ///
+    /// Milestones above the progress of the truncated branch are forgotten, so that they are
+    /// reported again once the branch reaches them anew.
+    ///
     /// Args:
     ///     serial: The number of commits to include in the new branch (exclusive).
     ///
@@ -59,9 +90,17 @@ impl Branch {
         if serial > self.commits.len() {
             None
         } else {
+            let progress = serial as f64 / self.estimated.max(1) as f64;
             Some(Branch {
                 commits: self.commits[..serial].to_vec(),
                 estimated: self.estimated,
+                reached: self
+                    .reached
+                    .iter()
+                    .copied()
+                    .filter(|&milestone| milestone <= progress)
+                    .collect(),
+                pending: Vec::new(),
             })
         }
     }
@@ -91,6 +130,8 @@ impl Branch {
 struct ThoughtVCS {
     /// Map of branch names to their corresponding `Branch` instances.
     branches: HashMap<Option<String>, Branch>,
+    /// Fractions of the estimated commit count reported when a branch reaches them.
+    milestones: Vec<f64>,
 }
 
 impl ThoughtVCS {
@@ -115,11 +156,36 @@ impl ThoughtVCS {
 impl ThoughtVCS {
     /// Creates a new instance of `ThoughtVCS` with default branches.
     ///
+    /// Args:
+    ///     milestones: Fractions of the estimated commit count, each in `(0, 1]`, to report
+    ///         through `take_milestones` when a branch reaches them.
+    ///
     /// Returns:
     ///     A new `ThoughtVCS` instance initialized with default branch data.
+    ///
+    /// Raises:
+    ///     ValueError: If a milestone is not in `(0, 1]`.
     #[new]
-    fn new() -> Self {
-        ThoughtVCS::default()
+    #[pyo3(signature = (milestones = Vec::new()))]
+    fn new(mut milestones: Vec<f64>) -> PyResult<Self> {
+        if let Some(milestone) = milestones.iter().find(|&&m| !(m > 0.0 && m <= 1.0)) {
+            return Err(PyValueError::new_err(format!(
+                "Milestone {milestone} is not within (0, 1]"
+            )));
+        }
+        milestones.sort_by(f64::total_cmp);
+        milestones.dedup();
+        Ok(ThoughtVCS {
+            milestones,
+            ..Default::default()
+        })
+    }
+
+    /// The fractions of the estimated commit count reported when a branch reaches them, in
+    /// increasing order.
+    #[getter]
+    fn milestones(&self) -> Vec<f64> {
+        self.milestones.clone()
     }
     /// Commits new content to a branch, creating the branch if necessary.
     ///
@@ -141,8 +207,59 @@ impl ThoughtVCS {
         branch: Option<String>,
         insert: bool,
     ) -> Option<usize> {
-        self.branch(branch, insert)
-            .and_then(|branch| branch.estimate(estimated).commit(content, serial))
+        let milestones = self.milestones.clone();
+        let branch = self.branch(branch, insert)?;
+        let count = branch.estimate(estimated).commit(content, serial)?;
+        branch.cross(&milestones);
+        Some(count)
+    }
+
+    /// Returns the number of commits a branch still expects according to its estimate.
+    ///
+    /// Args:
+    ///     branch: The name of the branch, or None for the default branch.
+    ///
+    /// Returns:
+    ///     The estimated number of commits minus the number of commits, 0 once the estimate
+    ///     is exceeded, or None if the branch does not exist.
+    #[pyo3(signature = (branch = None))]
+    fn remaining(&self, branch: Option<String>) -> Option<usize> {
+        self.branches.get(&branch).map(Branch::remaining)
+    }
+
+    /// Returns how far a branch has progressed against its estimate.
+    ///
+    /// Args:
+    ///     branch: The name of the branch, or None for the default branch.
+    ///
+    /// Returns:
+    ///     The number of commits divided by the estimated number, which exceeds 1.0 once the
+    ///     estimate is exceeded, or None if the branch does not exist or has no estimate.
+    #[pyo3(signature = (branch = None))]
+    fn progress(&self, branch: Option<String>) -> Option<f64> {
+        self.branches
+            .get(&branch)
+            .filter(|branch| branch.estimated > 0)
+            .map(|branch| branch.commits.len() as f64 / branch.estimated as f64)
+    }
+
+    /// Takes the milestones a branch has reached since they were last taken.
+    ///
+    /// Each milestone is reported once per branch, when a commit brings the commit count to
+    /// that fraction of the estimate or beyond. A checkout below a milestone lets it be
+    /// reported again.
+    ///
+    /// Args:
+    ///     branch: The name of the branch, or None for the default branch.
+    ///
+    /// Returns:
+    ///     The milestones reached, in increasing order, empty if none or if the branch does
+    ///     not exist.
+    #[pyo3(signature = (branch = None))]
+    fn take_milestones(&mut self, branch: Option<String>) -> Vec<f64> {
+        self.branch(branch, false)
+            .map(|branch| std::mem::take(&mut branch.pending))
+            .unwrap_or_default()
     }
 
     /// Revises the content of an existing commit in a branch.
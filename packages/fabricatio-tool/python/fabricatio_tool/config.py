@@ -1,5 +1,6 @@
 """Module containing configuration classes for fabricatio-tool."""
 
+from pathlib import Path
 from typing import Dict, List, Literal, Optional, Set, TypedDict
 
 from fabricatio_core import CONFIG
@@ -41,6 +42,28 @@ class ServiceConfig(TypedDict, total=False):
     env: Dict[str, JsonValue]
     """Environment variables to set for service process"""
 
+    headers: Dict[str, str]
+    """HTTP headers sent with every request to stream services"""
+
+    allowed_tools: List[str]
+    """Glob patterns of tool names that may be invoked, all tools are allowed if empty"""
+
+    blocked_tools: List[str]
+    """Glob patterns of tool names that may never be invoked, takes precedence over allowed_tools"""
+
+    max_concurrency: int
+    """Maximum number of concurrent tool calls to this service, excess calls are queued"""
+
+    max_result_size: int
+    """Maximum size in bytes of a tool result, results are not limited if unset"""
+
+    oversize_policy: Literal["truncate", "spill", "error"]
+    """What to do with results over max_result_size: cut them, save them to a file and return its path, or fail"""
+
+    spill_dir: str
+    """Directory spilled results are written to, `fabricatio-mcp-<uid>` in the system temp directory by default,
+    created with mode 0700 and refused if another user owns it"""
+
 
 class ToolConfig(BaseModel):
     """Configuration for fabricatio-tool."""
@@ -58,10 +81,17 @@ class ToolConfig(BaseModel):
         )
     )
     """Calls that are forbidden/allowed to be used."""
+    check_attributes: CheckConfigModel = Field(default_factory=lambda: CheckConfigModel(mode="blacklist"))
+    """Attribute names that are forbidden/allowed to be accessed."""
+    allow_dangerous_attributes: bool = False
+    """Whether to allow dangerous dunders like `__subclasses__` or `__globals__`, which are denied by default."""
 
     mcp_servers: Dict[str, ServiceConfig] = Field(default_factory=dict)
     """MCP servers that are allowed to be used."""
 
+    mcp_config_file: Optional[Path] = None
+    """Path to an `mcp.json` file (`mcpServers` layout) whose servers are added to `mcp_servers`."""
+
     confirm_on_ops: bool = True
     """Whether to confirm operations before executing them."""
 
@@ -11,9 +11,10 @@ from fabricatio_tool.fs.curd import (
     move_file,
 )
 from fabricatio_tool.fs.readers import safe_json_read, safe_text_read
-from fabricatio_tool.rust import treeview
+from fabricatio_tool.rust import TreeOptions, inspect_tree, outline, treeview
 
 __all__ = [
+    "TreeOptions",
     "absolute_path",
     "copy_file",
     "create_directory",
@@ -21,7 +22,9 @@ __all__ = [
     "delete_file",
     "dump_text",
     "gather_files",
+    "inspect_tree",
     "move_file",
+    "outline",
     "safe_json_read",
     "safe_text_read",
     "treeview",
@@ -12,7 +12,12 @@ from fabricatio_tool.rust import MCPManager
 
 @once
 async def get_global_mcp_manager(conf: Dict[str, ServiceConfig] = tool_config.mcp_servers) -> MCPManager:
-    """Get the global MCP manager instance."""
+    """Get the global MCP manager instance.
+
+    Servers from `tool_config.mcp_config_file` are included, entries of `conf` take precedence.
+    """
+    if tool_config.mcp_config_file is not None:
+        conf = {**MCPManager.load_config_file(tool_config.mcp_config_file), **conf}
     return await MCPManager.create(conf)
 
 
@@ -108,6 +108,7 @@ class ToolExecutor:
         check_modules: Optional[CheckConfigModel] = None,
         check_imports: Optional[CheckConfigModel] = None,
         check_calls: Optional[CheckConfigModel] = None,
+        check_attributes: Optional[CheckConfigModel] = None,
         err_key: str = tool_config.error_key,
     ) -> ResultCollector:
         """Execute the sequence of tools with the provided context.
@@ -121,6 +122,7 @@ class ToolExecutor:
             check_modules (Optional[CheckConfigModel]): Configuration for module-related checks.
             check_imports (Optional[CheckConfigModel]): Configuration for import-related checks.
             check_calls (Optional[CheckConfigModel]): Configuration for call-related checks.
+            check_attributes (Optional[CheckConfigModel]): Configuration for attribute-related checks.
             err_key (str): The key to use for error handling.
 
         Returns:
@@ -135,6 +137,8 @@ class ToolExecutor:
             CheckConfig(**(check_modules or tool_config.check_modules).model_dump()),
             CheckConfig(**(check_imports or tool_config.check_imports).model_dump()),
             CheckConfig(**self.validate_callcheck_config(check_calls or tool_config.check_calls).model_dump()),
+            CheckConfig(**(check_attributes or tool_config.check_attributes).model_dump()),
+            tool_config.allow_dangerous_attributes,
         ):
             raise ValueError(f"Violations found in code: \n{source}\n\n{'\n'.join(vio)}")
         logger.debug(f"Starting compile and execution of function: \n{source}")
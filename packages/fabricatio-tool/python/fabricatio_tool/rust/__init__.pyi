@@ -7,9 +7,23 @@ import typing
 
 __all__ = [
     "CheckConfig",
+    "CommandResult",
+    "CommandRunner",
+    "HttpResponse",
+    "HttpTool",
+    "LintConfig",
+    "LintDiagnostic",
     "MCPManager",
+    "ShellExecutor",
+    "Symbol",
     "ToolMetaData",
+    "ToolRegistry",
+    "TreeOptions",
     "gather_violations",
+    "inspect_tree",
+    "lint_source",
+    "outline",
+    "sanitize_source",
     "treeview",
 ]
 
@@ -19,6 +33,271 @@ class CheckConfig:
     def __new__(cls, targets: builtins.set[builtins.str], mode: builtins.str = "whitelist") -> CheckConfig:
         r"""Create a new CheckConfig instance with specified targets and mode."""
 
+@typing.final
+class CommandResult:
+    r"""Outcome of a command run by a `CommandRunner`."""
+    @property
+    def argv(self) -> builtins.list[builtins.str]:
+        r"""The command and its arguments."""
+    @property
+    def exit_code(self) -> typing.Optional[builtins.int]:
+        r"""Exit code of the command, None if it timed out or was killed by a signal."""
+    @property
+    def stdout(self) -> builtins.str:
+        r"""Captured standard output, decoded lossily as UTF-8."""
+    @property
+    def stderr(self) -> builtins.str:
+        r"""Captured standard error, decoded lossily as UTF-8."""
+    @property
+    def timed_out(self) -> builtins.bool:
+        r"""Whether the command was killed for exceeding its timeout."""
+    @property
+    def truncated(self) -> builtins.bool:
+        r"""Whether the standard output or error was cut at the output limit of the runner."""
+    @property
+    def duration(self) -> builtins.float:
+        r"""Wall-clock time the command ran for, in seconds."""
+    @property
+    def success(self) -> builtins.bool:
+        r"""Whether the command exited with code 0 within its timeout."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class CommandRunner:
+    r"""Runs local commands with an allowlist and denylist of executables, a confined working
+    directory, a timeout, an output limit and a scrubbed environment.
+
+    Every invocation is logged with its outcome and duration.
+    """
+    def __new__(
+        cls,
+        root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+        allow: typing.Optional[typing.Sequence[builtins.str]] = None,
+        deny: typing.Sequence[builtins.str] = [],
+        timeout: typing.Optional[builtins.float] = 30.0,
+        max_output: typing.Optional[builtins.int] = 1048576,
+        inherit_env: typing.Optional[typing.Sequence[builtins.str]] = None,
+        env: typing.Mapping[builtins.str, builtins.str] = {},
+    ) -> CommandRunner:
+        r"""Create a new CommandRunner instance.
+
+        Args:
+            root: Directory commands run in, and the working directories must lie inside.
+                Defaults to the current directory.
+            allow: Executables that may run, by full path or by name (e.g. `git`), a name only
+                matching commands given without a directory. All executables may run if None.
+            deny: Executables that may never run, by name or full path. Takes precedence
+                over `allow`.
+            timeout: Default timeout of a command in seconds, None for no timeout.
+            max_output: Number of bytes captured from each of the standard output and error
+                of a command, None for no limit. Defaults to 1 MiB.
+            inherit_env: Environment variables passed through from the current process.
+                Defaults to `PATH`, `HOME`, `USER`, the locale, the terminal and the
+                temporary directory variables.
+            env: Additional environment variables set for every command.
+
+        Raises:
+            OSError: If the root directory does not exist.
+        """
+    @property
+    def root(self) -> pathlib.Path:
+        r"""The directory commands are confined to."""
+    def run(
+        self,
+        argv: typing.Sequence[builtins.str],
+        cwd: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+        stdin: typing.Optional[builtins.str] = None,
+        timeout: typing.Optional[builtins.float] = None,
+        on_output: typing.Optional[typing.Any] = None,
+    ) -> typing.Any:
+        r"""Runs a command and captures its output.
+
+        The executable is resolved through `PATH` and checked against the allow and deny
+        lists before anything runs. A command exceeding its timeout is killed, on Unix along
+        with the processes it spawned, and its output so far is returned. Output beyond the
+        limit of the runner is dropped and the result marked as truncated.
+
+        Args:
+            argv: The executable followed by its arguments. No shell is involved.
+            cwd: Working directory, relative to the root. Defaults to the root.
+            stdin: Text written to the standard input of the command.
+            timeout: Timeout in seconds, overriding the default of the runner.
+            on_output: Callable invoked with the stream name (`stdout` or `stderr`) and
+                each line as it is produced, without its line ending.
+
+        Returns:
+            An awaitable that resolves to a CommandResult.
+
+        Raises:
+            ValueError: If `argv` is empty.
+            FileNotFoundError: If the executable or the working directory cannot be found.
+            PermissionError: If the executable is not permitted or the working directory is
+                outside of the root.
+            OSError: If the command cannot be started.
+        """
+
+@typing.final
+class HttpResponse:
+    r"""Response to a request made by an `HttpTool`."""
+    @property
+    def url(self) -> builtins.str:
+        r"""The final URL of the response, after redirects."""
+    @property
+    def status(self) -> builtins.int:
+        r"""The HTTP status code."""
+    @property
+    def headers(self) -> builtins.dict[builtins.str, builtins.str]:
+        r"""The response headers, with lowercase names."""
+    @property
+    def body(self) -> builtins.str:
+        r"""The body, decoded lossily as UTF-8."""
+    @property
+    def truncated(self) -> builtins.bool:
+        r"""Whether the body was cut at the size limit of the tool."""
+    @property
+    def from_cache(self) -> builtins.bool:
+        r"""Whether the response was served from the cache."""
+    @property
+    def attempts(self) -> builtins.int:
+        r"""Number of attempts made, 0 if served from the cache."""
+    @property
+    def duration(self) -> builtins.float:
+        r"""Wall-clock time the request took, in seconds."""
+    @property
+    def ok(self) -> builtins.bool:
+        r"""Whether the status code is in the 2xx range."""
+    def json(self) -> typing.Any:
+        r"""Parses the body as JSON.
+
+        Raises:
+            RuntimeError: If the body is not valid JSON.
+        """
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class HttpTool:
+    r"""Makes HTTP requests restricted to allowed domains, with a response size limit, a
+    timeout, automatic retries and an optional persistent cache of `GET` responses.
+
+    Every request is logged with its status and duration.
+    """
+    def __new__(
+        cls,
+        allow_domains: typing.Optional[typing.Sequence[builtins.str]] = None,
+        deny_domains: typing.Sequence[builtins.str] = [],
+        timeout: typing.Optional[builtins.float] = 30.0,
+        max_response_size: typing.Optional[builtins.int] = 1048576,
+        retries: builtins.int = 2,
+        backoff: builtins.float = 0.5,
+        cache_dir: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+        cache_ttl: typing.Optional[builtins.float] = None,
+        headers: typing.Mapping[builtins.str, builtins.str] = {},
+    ) -> HttpTool:
+        r"""Create a new HttpTool instance.
+
+        Args:
+            allow_domains: Domains requests may reach, subdomains included. All domains may
+                be reached if None.
+            deny_domains: Domains requests may never reach, subdomains included. Takes
+                precedence over `allow_domains`.
+            timeout: Default timeout of a request in seconds, None for no timeout.
+            max_response_size: Number of bytes read from the body of a response, None for
+                no limit. Defaults to 1 MiB.
+            retries: Number of times an idempotent request is retried on a transport error
+                or a 429, 502, 503 or 504 status.
+            backoff: Delay before the first retry in seconds, doubled on each retry up to a
+                minute.
+            cache_dir: Directory of the persistent cache of successful `GET` responses. No
+                caching if None. A directory must not be shared by two live tools.
+            cache_ttl: Age in seconds after which a cached response is fetched again, None
+                to keep cached responses forever.
+            headers: Headers sent with every request.
+
+        Raises:
+            ValueError: If a header is invalid or a duration is negative or not finite.
+            RuntimeError: If the cache cannot be opened.
+        """
+    def request(
+        self,
+        method: builtins.str,
+        url: builtins.str,
+        headers: typing.Mapping[builtins.str, builtins.str] = {},
+        body: typing.Optional[builtins.str] = None,
+        timeout: typing.Optional[builtins.float] = None,
+    ) -> typing.Any:
+        r"""Sends a request and reads its response.
+
+        The URL is checked against the domain lists before anything is sent, and so is the
+        target of every redirect. The body beyond the size limit of the tool is dropped and
+        the response marked as truncated. Successful `GET` responses to requests without
+        headers of their own are served from and stored in the cache, if the tool has one.
+
+        Args:
+            method: The HTTP method, such as `GET` or `POST`.
+            url: The URL, with an `http` or `https` scheme.
+            headers: Headers sent with this request, on top of those of the tool.
+            body: The body of the request.
+            timeout: Timeout in seconds, overriding the default of the tool.
+
+        Returns:
+            An awaitable that resolves to an HttpResponse.
+
+        Raises:
+            ValueError: If the method, the URL, a header or the timeout is invalid.
+            PermissionError: If the host or a redirect target is not permitted.
+            TimeoutError: If the last attempt timed out.
+            ConnectionError: If the last attempt failed.
+        """
+    def get(
+        self,
+        url: builtins.str,
+        headers: typing.Mapping[builtins.str, builtins.str] = {},
+        timeout: typing.Optional[builtins.float] = None,
+    ) -> typing.Any:
+        r"""Sends a `GET` request, see `request`.
+
+        Args:
+            url: The URL, with an `http` or `https` scheme.
+            headers: Headers sent with this request, on top of those of the tool.
+            timeout: Timeout in seconds, overriding the default of the tool.
+
+        Returns:
+            An awaitable that resolves to an HttpResponse.
+        """
+
+@typing.final
+class LintConfig:
+    r"""Complete linter configuration, bundling the checks applied to a source."""
+    def __new__(
+        cls,
+        modules: typing.Optional[CheckConfig] = None,
+        imports: typing.Optional[CheckConfig] = None,
+        calls: typing.Optional[CheckConfig] = None,
+        attributes: typing.Optional[CheckConfig] = None,
+        allow_dangerous_attributes: builtins.bool = False,
+    ) -> LintConfig:
+        r"""Create a new LintConfig instance, unset checks are disabled."""
+
+@typing.final
+class LintDiagnostic:
+    r"""A rule violation found by the linter, located in the source."""
+    @property
+    def rule(self) -> builtins.str:
+        r"""Identifier of the broken rule, e.g. `forbidden-call` or `dangerous-attribute`."""
+    @property
+    def message(self) -> builtins.str:
+        r"""Human readable description of the violation."""
+    @property
+    def line(self) -> builtins.int:
+        r"""Line of the offending code, 1-based."""
+    @property
+    def column(self) -> builtins.int:
+        r"""Column of the offending code within the line, 1-based."""
+    @property
+    def snippet(self) -> builtins.str:
+        r"""Text of the offending line."""
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class MCPManager:
     r"""Python-exposed MCP manager."""
@@ -32,6 +311,48 @@ class MCPManager:
         Returns:
             An awaitable that resolves to a new MCPManager instance.
         """
+    @staticmethod
+    def from_file(path: builtins.str | os.PathLike | pathlib.Path) -> typing.Any:
+        r"""Creates a new MCP manager from an `mcp.json` file.
+
+        The file uses the common `mcpServers` layout, entries may define `command`, `args`,
+        `env`, `url`, `headers` and `type`. Entries marked `"disabled": true` are skipped.
+
+        Args:
+            path: Path to the `mcp.json` file.
+
+        Returns:
+            An awaitable that resolves to a new MCPManager instance.
+
+        Raises:
+            RuntimeError: If the file cannot be read or parsed.
+        """
+    @staticmethod
+    def load_config_file(path: builtins.str | os.PathLike | pathlib.Path) -> typing.Any:
+        r"""Loads the server configurations of an `mcp.json` file.
+
+        Args:
+            path: Path to the `mcp.json` file.
+
+        Returns:
+            A dict mapping server names to service configurations, suitable for `create`.
+
+        Raises:
+            RuntimeError: If the file cannot be read or parsed.
+        """
+    def close(self) -> typing.Any:
+        r"""Shuts down all servers and terminates their child processes.
+
+        Returns:
+            An awaitable that resolves once all servers are shut down.
+        """
+    async def __aenter__(self) -> MCPManager: ...
+    async def __aexit__(
+        self,
+        _exc_type: typing.Optional[typing.Any] = None,
+        _exc_value: typing.Optional[typing.Any] = None,
+        _traceback: typing.Optional[typing.Any] = None,
+    ) -> None: ...
     def list_tools(self, client_id: builtins.str) -> typing.Any:
         r"""Retrieves list of tools from a client.
 
@@ -81,6 +402,65 @@ class MCPManager:
         Returns:
             The number of servers.
         """
+    def failed_servers(self) -> builtins.dict[builtins.str, builtins.str]:
+        r"""Returns the servers that failed to start, mapped to the reason of the failure.
+
+        Returns:
+            A dict mapping server names to error messages.
+        """
+    def set_confirmation_callback(
+        self,
+        callback: typing.Optional[
+            typing.Callable[[builtins.str, builtins.str, typing.Optional[dict]], builtins.bool]
+        ] = None,
+    ) -> None:
+        r"""Sets the callback consulted before a destructive tool is invoked.
+
+        The callback receives the client ID, the tool name and the arguments dict (or None),
+        and must return True to let the call proceed. Exceptions raised by the callback
+        reject the call. Tools without annotations count as destructive, and while a callback
+        is set, calls to tools missing from the tool list of their server are refused.
+
+        Args:
+            callback: The confirmation callable, or None to disable confirmation.
+        """
+    def set_sampling_callback(
+        self,
+        callback: typing.Optional[
+            typing.Callable[[builtins.str, dict], typing.Union[dict, typing.Awaitable[dict]]]
+        ] = None,
+    ) -> None:
+        r"""Sets the callback answering sampling requests sent by servers.
+
+        The callback receives the client ID and the `sampling/createMessage` parameters as a
+        dict, and returns (or resolves to) a `CreateMessageResult` dict with `model`, `role`,
+        `content` and optionally `stopReason`. Async callbacks run on the current event loop.
+
+        Args:
+            callback: The sampling callable, or None to report sampling as unsupported.
+
+        Raises:
+            RuntimeError: If a callback is given while no event loop is running.
+        """
+    def set_elicitation_callback(
+        self,
+        callback: typing.Optional[
+            typing.Callable[[builtins.str, dict], typing.Union[dict, typing.Awaitable[dict]]]
+        ] = None,
+    ) -> None:
+        r"""Sets the callback answering elicitation requests sent by servers.
+
+        The callback receives the client ID and the `elicitation/create` parameters as a dict,
+        and returns (or resolves to) a dict with an `action` of `accept`, `decline` or `cancel`
+        and, when accepting, the `content` conforming to the requested schema. Async callbacks
+        run on the current event loop.
+
+        Args:
+            callback: The elicitation callable, or None to decline all elicitation requests.
+
+        Raises:
+            RuntimeError: If a callback is given while no event loop is running.
+        """
     def ping(self, client_id: builtins.str) -> typing.Any:
         r"""Checks if a client is still connected and responsive.
 
@@ -91,7 +471,11 @@ class MCPManager:
             An awaitable that resolves to True if connected, False otherwise.
         """
     def call_tool(
-        self, client_id: builtins.str, tool_name: builtins.str, arguments: typing.Optional[dict]
+        self,
+        client_id: builtins.str,
+        tool_name: builtins.str,
+        arguments: typing.Optional[dict] = None,
+        on_event: typing.Optional[typing.Any] = None,
     ) -> typing.Any:
         r"""Executes a tool on a client and returns the result.
 
@@ -99,9 +483,42 @@ class MCPManager:
             client_id: The ID of the client.
             tool_name: The name of the tool to execute.
             arguments: Optional dictionary of tool arguments.
+            on_event: Optional callable receiving what the server reports while the tool runs,
+                as dicts: `{"type": "progress", "progress", "total", "message"}` for progress
+                notifications and `{"type": "log", "level", "logger", "data"}` for log messages,
+                through which servers stream partial output.
+
+        Arguments are checked against the input schema of the tool before the call is sent,
+        so that mistakes are reported with the path of each offending value rather than by an
+        opaque server error.
+
+        Results larger than the `max_result_size` of the server are cut, spilled to a file or
+        refused, as its `oversize_policy` says, before they are converted to Python objects.
 
         Returns:
             An awaitable that resolves to a list of result strings.
+
+        Raises:
+            RuntimeError: If the arguments do not match the input schema of the tool, listing
+                every violation such as `$.limit: expected integer, got string "ten"`, if the
+                call fails, or if the result is too large under the `error` policy.
+        """
+    def call_tools(
+        self,
+        requests: typing.Sequence[tuple[builtins.str, builtins.str, typing.Optional[dict]]],
+        concurrency: builtins.int = 8,
+        return_exceptions: builtins.bool = False,
+    ) -> typing.Any:
+        r"""Executes multiple tools concurrently and returns their results in request order.
+
+        Args:
+            requests: A list of (client_id, tool_name, arguments) tuples, arguments may be None.
+            concurrency: Maximum number of calls in flight at once.
+            return_exceptions: If True, failed calls yield their exception in place of a result
+                instead of raising the first error.
+
+        Returns:
+            An awaitable that resolves to a list with one list of result strings per request.
         """
     def has_client(self, client_id: builtins.str) -> builtins.bool:
         r"""Checks if a client exists in the manager.
@@ -112,6 +529,63 @@ class MCPManager:
         Returns:
             True if the client exists, False otherwise.
         """
+    def export_catalog(self, path: builtins.str | os.PathLike | pathlib.Path) -> typing.Any:
+        r"""Writes the tools of every connected server to a JSON catalog.
+
+        The catalog keeps the names, descriptions, input schemas and annotations of the tools,
+        so that `load_catalog` can list them where the servers are not available, such as in CI.
+
+        Args:
+            path: Path of the `.json` file to write, missing parent directories are created.
+
+        Returns:
+            An awaitable that resolves to the number of tools written.
+
+        Raises:
+            RuntimeError: If the tools of a server cannot be listed or the file cannot be written.
+        """
+    def load_catalog(self, path: builtins.str | os.PathLike | pathlib.Path) -> builtins.int:
+        r"""Loads a catalog written by `export_catalog`, replacing any loaded before.
+
+        The tools of servers that are not connected are then listed from the catalog, by
+        `list_tools`, `get_tool`, `has_tool` and `generate_stub_module` alike. Calling them still
+        fails as the server is not connected.
+
+        Args:
+            path: Path of the catalog file.
+
+        Returns:
+            The number of servers in the catalog.
+
+        Raises:
+            RuntimeError: If the file cannot be read or parsed.
+        """
+    def catalog_servers(self) -> builtins.list[builtins.str]:
+        r"""Returns the servers of the loaded catalog, connected or not.
+
+        Returns:
+            A list of server names, sorted.
+        """
+    def generate_stub_module(
+        self, client_id: builtins.str, path: builtins.str | os.PathLike | pathlib.Path
+    ) -> typing.Any:
+        r"""Writes a Python module wrapping every tool of a client in a typed async function.
+
+        Each wrapper gets the signature and docstring derived from the tool's input schema and
+        calls the tool through `fabricatio_tool.mcp.get_global_mcp_manager`, so the module can be
+        imported by IDEs and agents. Call again to refresh it after the server's tools changed.
+
+        Args:
+            client_id: The ID of the client whose tools are wrapped.
+            path: Path of the `.py` file to write, missing parent directories are created.
+
+        Returns:
+            An awaitable that resolves to the number of wrapped tools.
+
+        Raises:
+            RuntimeError: If the tools cannot be listed or a tool has an invalid input schema.
+            OSError: If the file cannot be written.
+        """
     def has_tool(self, client_id: builtins.str, tool_name: builtins.str) -> typing.Any:
         r"""Checks if a tool exists for a specific client.
 
@@ -123,6 +597,104 @@ class MCPManager:
             An awaitable that resolves to True if the tool exists, False otherwise.
         """
 
+@typing.final
+class ShellExecutor:
+    r"""Executes the commands agents need, such as test runners and linters, restricted to an
+    allowlist of executables, with the confinement, timeout and output limit of a
+    `CommandRunner`.
+
+    Command lines are split like a POSIX shell would, but never run by one. Every
+    invocation is logged with its outcome and duration.
+    """
+    def __new__(
+        cls,
+        allow: typing.Sequence[builtins.str],
+        root: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+        timeout: typing.Optional[builtins.float] = 30.0,
+        max_output: typing.Optional[builtins.int] = 1048576,
+        inherit_env: typing.Optional[typing.Sequence[builtins.str]] = None,
+        env: typing.Mapping[builtins.str, builtins.str] = {},
+    ) -> ShellExecutor:
+        r"""Create a new ShellExecutor instance.
+
+        Args:
+            allow: Executables that may run, by full path or by name (e.g. `pytest`), a name
+                only matching commands given without a directory.
+            root: Directory commands run in, and the working directories must lie inside.
+                Defaults to the current directory.
+            timeout: Default timeout of a command in seconds, None for no timeout.
+            max_output: Number of bytes captured from each of the standard output and error
+                of a command, None for no limit. Defaults to 1 MiB.
+            inherit_env: Environment variables passed through from the current process.
+                Defaults to those of `CommandRunner`.
+            env: Additional environment variables set for every command.
+
+        Raises:
+            OSError: If the root directory does not exist.
+        """
+    @property
+    def root(self) -> pathlib.Path:
+        r"""The directory commands are confined to."""
+    @property
+    def allow(self) -> builtins.list[builtins.str]:
+        r"""The executables that may run."""
+    def execute(
+        self,
+        command: builtins.str | typing.Sequence[builtins.str],
+        cwd: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+        stdin: typing.Optional[builtins.str] = None,
+        timeout: typing.Optional[builtins.float] = None,
+        on_output: typing.Optional[typing.Any] = None,
+    ) -> typing.Any:
+        r"""Executes a command and captures its output.
+
+        Args:
+            command: The command line, split into arguments with POSIX shell quoting rules,
+                or the executable followed by its arguments. Pipes, redirections and command
+                separators are refused.
+            cwd: Working directory, relative to the root. Defaults to the root.
+            stdin: Text written to the standard input of the command.
+            timeout: Timeout in seconds, overriding the default of the executor.
+            on_output: Callable invoked with the stream name (`stdout` or `stderr`) and
+                each line as it is produced, without its line ending.
+
+        Returns:
+            An awaitable that resolves to a CommandResult.
+
+        Raises:
+            ValueError: If the command is empty, has unbalanced quotes or uses a shell operator.
+            FileNotFoundError: If the executable or the working directory cannot be found.
+            PermissionError: If the executable is not allowed or the working directory is
+                outside of the root.
+            OSError: If the command cannot be started.
+        """
+
+@typing.final
+class Symbol:
+    r"""A class, function or other definition found in a source file."""
+    @property
+    def kind(self) -> builtins.str:
+        r"""Kind of the definition, e.g. `class`, `function`, `async_function`, `struct` or `impl`."""
+    @property
+    def name(self) -> builtins.str:
+        r"""Name of the definition."""
+    @property
+    def start_line(self) -> builtins.int:
+        r"""First line of the definition, 1-based."""
+    @property
+    def end_line(self) -> builtins.int:
+        r"""Last line of the definition, 1-based and inclusive."""
+    @property
+    def children(self) -> builtins.list[Symbol]:
+        r"""Definitions nested inside this one, such as methods."""
+    @property
+    def docstring(self) -> typing.Optional[builtins.str]:
+        r"""Docstring of a Python definition or doc comment of a Rust item, dedented."""
+    @property
+    def signature(self) -> typing.Optional[builtins.str]:
+        r"""Parameters and return annotation of a Python function, e.g. `(self, x: int = 1) -> str`."""
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class ToolMetaData:
     r"""Python representation of tool metadata."""
@@ -196,13 +768,239 @@ class ToolMetaData:
             A Python dictionary representation of the tool metadata.
         """
 
+@typing.final
+class ToolRegistry:
+    r"""A unified registry of MCP tools and local Python callables.
+
+    Tools are registered under namespaced names of the form `namespace.tool`, where the
+    namespace is the MCP client ID for MCP tools, so agent code can look up, search and
+    invoke any tool through a single object.
+    """
+    def __new__(cls) -> ToolRegistry:
+        r"""Creates an empty tool registry."""
+    def add_mcp_client(self, manager: MCPManager, client_id: builtins.str) -> typing.Any:
+        r"""Registers all tools of an MCP client under the `client_id.tool` names.
+
+        Tools blocked by the client's policy are not registered.
+
+        Args:
+            manager: The MCP manager serving the client.
+            client_id: The ID of the client.
+
+        Returns:
+            An awaitable that resolves to the list of registered names.
+        """
+    def add_callable(
+        self,
+        name: builtins.str,
+        callable: typing.Callable[..., typing.Any],
+        description: typing.Optional[builtins.str] = None,
+        namespace: builtins.str = "local",
+    ) -> builtins.str:
+        r"""Registers a Python callable under the `namespace.name` name.
+
+        Args:
+            name: The name of the tool within its namespace.
+            callable: The function to invoke, sync or async. It receives the call arguments
+                as keyword arguments.
+            description: The description used for search, defaults to the callable's docstring.
+            namespace: The namespace of the tool.
+
+        Returns:
+            The registered name.
+        """
+    def remove(self, name: builtins.str) -> builtins.bool:
+        r"""Removes a tool from the registry.
+
+        Args:
+            name: The namespaced name of the tool.
+
+        Returns:
+            True if the tool was registered, False otherwise.
+        """
+    def names(self, namespace: typing.Optional[builtins.str] = None) -> builtins.list[builtins.str]:
+        r"""Lists the namespaced names of all registered tools, sorted alphabetically.
+
+        Args:
+            namespace: If given, only tools of this namespace are listed.
+
+        Returns:
+            A list of tool names.
+        """
+    def search(self, query: builtins.str) -> builtins.list[builtins.str]:
+        r"""Searches tools whose name or description contains the query, ignoring case.
+
+        Args:
+            query: The substring to look for.
+
+        Returns:
+            A list of matching tool names, sorted alphabetically.
+        """
+    def describe(self, name: builtins.str) -> builtins.str:
+        r"""Returns the description of a tool.
+
+        Args:
+            name: The namespaced name of the tool.
+
+        Returns:
+            The tool description.
+
+        Raises:
+            KeyError: If no tool is registered under the name.
+        """
+    def metadata(self, name: builtins.str) -> typing.Optional[ToolMetaData]:
+        r"""Returns the MCP metadata of a tool.
+
+        Args:
+            name: The namespaced name of the tool.
+
+        Returns:
+            The ToolMetaData of an MCP tool, or None for local callables.
+
+        Raises:
+            KeyError: If no tool is registered under the name.
+        """
+    def call(self, name: builtins.str, arguments: typing.Optional[dict] = None) -> typing.Any:
+        r"""Invokes a tool by its namespaced name.
+
+        MCP tools resolve to the list of their result strings, local callables resolve to
+        whatever they return. Async callables are awaited.
+
+        Args:
+            name: The namespaced name of the tool.
+            arguments: Optional dictionary of tool arguments.
+
+        Returns:
+            An awaitable that resolves to the tool result.
+
+        Raises:
+            KeyError: If no tool is registered under the name.
+        """
+    def __len__(self) -> builtins.int: ...
+    def __contains__(self, name: builtins.str) -> builtins.bool: ...
+
+@typing.final
+class TreeOptions:
+    r"""Options of `inspect_tree`."""
+    def __new__(
+        cls,
+        max_depth: builtins.int = 10,
+        include: typing.Sequence[builtins.str] = [],
+        exclude: typing.Sequence[builtins.str] = [],
+        gitignore: builtins.bool = True,
+        hidden: builtins.bool = False,
+        sizes: builtins.bool = True,
+        format: builtins.str = "text",
+    ) -> TreeOptions:
+        r"""Create a new TreeOptions instance.
+
+        Args:
+            max_depth: Maximum depth to traverse.
+            include: Glob patterns of the files to list, all files are listed if empty.
+                Directories left without listed files are omitted.
+            exclude: Glob patterns of the files and directories to skip.
+            gitignore: Whether to respect `.gitignore`, `.ignore` and git exclude files.
+            hidden: Whether to list hidden files and directories.
+            sizes: Whether to show sizes and file counts in the text output.
+            format: `text` for a tree drawing, or `json` for nested objects.
+
+        Raises:
+            ValueError: If the format is unknown.
+        """
+
 def gather_violations(
     source: builtins.str,
     modules: typing.Optional[CheckConfig] = None,
     imports: typing.Optional[CheckConfig] = None,
     calls: typing.Optional[CheckConfig] = None,
+    attributes: typing.Optional[CheckConfig] = None,
+    allow_dangerous_attributes: builtins.bool = False,
 ) -> builtins.list[builtins.str]:
-    r"""Gathers violations in the provided source code based on the given configuration."""
+    r"""Gathers violations in the provided source code based on the given configuration.
+
+    Dangerous dunders and introspection attributes such as `__subclasses__`, `__globals__` or
+    `f_back` are always reported, through attribute access or `getattr`-style builtins, unless
+    `allow_dangerous_attributes` is set. Code passed to `exec`, `eval` or `compile` and modules
+    passed to `__import__` as string literals are checked like regular code and imports, and
+    decorators naming a forbidden function are reported as well.
+    """
+
+def inspect_tree(
+    path: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None, options: typing.Optional[TreeOptions] = None
+) -> builtins.str:
+    r"""Builds a directory tree with filtering, size and file count statistics.
+
+    Sizes and file counts of a directory only cover the files listed below it, within
+    the depth limit and the filters.
+
+    Args:
+        path: Root path to inspect (default: current directory).
+        options: The traversal and output options, see `TreeOptions`.
+
+    Returns:
+        A tree drawing like `treeview`, or a JSON document of nested objects with `name`,
+        `type` (`directory` or `file`), `size` and, for directories, `file_count` and
+        `children`.
+
+    Raises:
+        ValueError: If a glob pattern is invalid.
+    """
+
+def lint_source(source: builtins.str, config: typing.Optional[LintConfig] = None) -> builtins.list[LintDiagnostic]:
+    r"""Lints the provided source code and returns located diagnostics.
+
+    Unlike `gather_violations`, a source that cannot be parsed is reported as a
+    `syntax-error` diagnostic instead of raising.
+
+    Args:
+        source: The Python source code to lint.
+        config: The checks to apply. If omitted, only dangerous attributes are checked.
+
+    Returns:
+        The diagnostics in source order.
+    """
+
+def outline(path_or_source: builtins.str | os.PathLike | pathlib.Path) -> builtins.list[Symbol]:
+    r"""Extracts the outline of a Python or Rust source file, or of Python source code.
+
+    Lists the classes, functions, structs, traits and impl blocks defined in the file
+    with their line ranges, docstrings and, for Python functions, signatures, so a large
+    file can be understood without reading it fully.
+
+    Args:
+        path_or_source: Path to a `.py`, `.pyi` or `.rs` file, or Python source code. A
+            single-line string ending with one of these extensions is taken as a path.
+
+    Returns:
+        The top-level definitions in source order, with methods and nested definitions
+        as their children.
+
+    Raises:
+        OSError: If the file cannot be read.
+        ValueError: If the file type is not supported or the source cannot be parsed.
+    """
+
+def sanitize_source(
+    source: builtins.str, config: typing.Optional[LintConfig] = None, mode: builtins.str = "comment"
+) -> tuple[builtins.str, builtins.list[LintDiagnostic]]:
+    r"""Rewrites the provided source code so that it passes the import and call checks.
+
+    Statements containing a forbidden import or call are removed or commented out, and
+    blocks left empty get a `pass`. Attribute violations are not rewritten, so the result
+    should be linted again before running it.
+
+    Args:
+        source: The Python source code to sanitize.
+        config: The checks to apply. If omitted, only the hard-coded module blacklist applies.
+        mode: `comment` to comment the statements out, or `strip` to remove them.
+
+    Returns:
+        A tuple of the sanitized source and the diagnostics it fixed, located in the
+        original source.
+
+    Raises:
+        ValueError: If the mode is unknown or the source cannot be parsed.
+    """
 
 def treeview(
     directory: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None, max_depth: builtins.int = 10
@@ -9,6 +9,7 @@ from fabricatio_tool.fs import (
     dump_text,
     gather_files,
     move_file,
+    outline,
     safe_json_read,
     safe_text_read,
     treeview,
@@ -22,6 +23,7 @@ fs_toolbox = (
     .add_tool(move_file)
     .add_tool(delete_file)
     .add_tool(treeview)
+    .add_tool(outline)
     .add_tool(delete_directory)
     .add_tool(create_directory)
     .add_tool(absolute_path)
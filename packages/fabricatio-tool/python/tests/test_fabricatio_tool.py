@@ -1,12 +1,32 @@
 """Tests for the tool."""
 
+import asyncio
+import json
+import os
+import sys
+import threading
 from dataclasses import dataclass
-from typing import Any, Callable, Dict
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from pathlib import Path
+from typing import Any, Callable, Dict, Iterator
 
 import pytest
 from fabricatio_tool.models.collector import ResultCollector
 from fabricatio_tool.models.executor import ToolExecutor
 from fabricatio_tool.models.tool import Tool, ToolBox
+from fabricatio_tool.rust import (
+    CheckConfig,
+    CommandRunner,
+    HttpTool,
+    LintConfig,
+    ShellExecutor,
+    TreeOptions,
+    gather_violations,
+    inspect_tree,
+    lint_source,
+    outline,
+    sanitize_source,
+)
 
 
 # Fixtures
@@ -257,6 +277,80 @@ async def test_forbidden_import_check(tool_executor: ToolExecutor, sample_func:
         await tool_executor.execute(source)
 
 
+
+@pytest.mark.asyncio
+async def test_forbidden_attribute_check(tool_executor: ToolExecutor) -> None:
+    """Test detection of dangerous dunders and configured attribute rules."""
+    source = "x=().__class__.__bases__[0].__subclasses__()"
+    with pytest.raises(ValueError, match=r"Forbidden attribute access: \.__subclasses__"):
+        await tool_executor.execute(source)
+
+    source = "g=print.__self__"
+    with pytest.raises(ValueError, match=r"Forbidden attribute access: \.__self__"):
+        await tool_executor.execute(source)
+
+    assert gather_violations('x=getattr(object, "__subclasses__")') == [
+        'Forbidden attribute access: getattr(..., "__subclasses__")'
+    ]
+    assert gather_violations("x=getattr(object, name)") == [
+        "Forbidden dynamic attribute access: getattr() with a non-literal name"
+    ]
+    assert gather_violations("x=__builtins__") == ["Forbidden name: __builtins__"]
+    assert gather_violations("x=print.__self__", allow_dangerous_attributes=True) == []
+    assert gather_violations("x=s.upper()", attributes=CheckConfig({"lower"})) == [
+        "Forbidden attribute access: .upper"
+    ]
+    assert gather_violations("x=s.upper()", attributes=CheckConfig({"upper"}, "blacklist")) == [
+        "Forbidden attribute access: .upper"
+    ]
+
+
+
+def test_lint_source() -> None:
+    """Test located diagnostics returned by the linter."""
+    source = "import math\nx = 1\ny = eval('x') + print.__self__\n"
+    diagnostics = lint_source(source, LintConfig(calls=CheckConfig({"eval"}, "blacklist")))
+    assert [(d.rule, d.line, d.column) for d in diagnostics] == [
+        ("forbidden-call", 3, 5),
+        ("dangerous-attribute", 3, 17),
+    ]
+    assert diagnostics[0].message == "Forbidden function call: eval()"
+    assert diagnostics[0].snippet == "y = eval('x') + print.__self__"
+
+    (syntax_error,) = lint_source("def broken(:\n")
+    assert syntax_error.rule == "syntax-error"
+    assert syntax_error.line == 1
+
+
+def test_dynamic_code_check() -> None:
+    """Test checks of code and modules passed as strings, and of decorators."""
+    config = LintConfig(modules=CheckConfig({"json"}, "blacklist"), calls=CheckConfig({"open"}, "blacklist"))
+    source = "exec('import json')\n__import__('json')\neval(code)\n@open\ndef f(): ...\n"
+    assert [(d.rule, d.line) for d in lint_source(source, config)] == [
+        ("forbidden-module", 1),
+        ("forbidden-module", 2),
+        ("dangerous-attribute", 2),
+        ("dynamic-code", 3),
+        ("forbidden-decorator", 4),
+    ]
+    assert lint_source("eval('1 + 1')\n") == []
+
+def test_sanitize_source() -> None:
+    """Test rewriting forbidden imports and calls out of a source."""
+    code, fixed = sanitize_source("import os\nx = 1\n")
+    assert code == "# import os\nx = 1\n"
+    assert [d.rule for d in fixed] == ["forbidden-module"]
+
+    config = LintConfig(calls=CheckConfig({"eval"}, "blacklist"))
+    code, fixed = sanitize_source("def f():\n    eval('1')\nx = 2; eval('3')\n", config, mode="strip")
+    assert code == "def f():\n    pass\nx = 2; pass\n"
+    assert [(d.rule, d.line) for d in fixed] == [("forbidden-call", 2), ("forbidden-call", 3)]
+    assert lint_source(code, config) == []
+
+    with pytest.raises(ValueError):
+        sanitize_source("x = 1\n", mode="delete")
+
+
 def test_from_recipe(toolbox: ToolBox, sample_func: Callable[[int, str], str]) -> None:
     """Test ToolExecutor creation from recipe."""
     toolbox.add_tool(sample_func)
@@ -320,3 +414,218 @@ class TestApplicationError:
         col = await tool_executor.execute(source)
         assert "__error__" in col.container
         assert col.container["__error__"] is col.error()
+
+
+def test_outline(tmp_path: Path) -> None:
+    """Test outline extraction for Python and Rust sources."""
+    py_file = tmp_path / "sample.py"
+    py_file.write_text(
+        "import os\n\nclass A:\n    def f(self):\n        pass\n\n    async def g(self):\n        pass\n"
+    )
+    (cls,) = outline(py_file)
+    assert (cls.kind, cls.name, cls.start_line, cls.end_line) == ("class", "A", 3, 8)
+    assert [(c.kind, c.name, c.start_line) for c in cls.children] == [("function", "f", 4), ("async_function", "g", 7)]
+
+    rs_file = tmp_path / "sample.rs"
+    rs_file.write_text("struct S;\n\nimpl Clone for S {\n    fn clone(&self) -> Self {\n        S\n    }\n}\n")
+    struct, imp = outline(rs_file)
+    assert (struct.kind, struct.name, struct.start_line) == ("struct", "S", 1)
+    assert (imp.kind, imp.name, imp.start_line, imp.end_line) == ("impl", "Clone for S", 3, 7)
+    assert [c.name for c in imp.children] == ["clone"]
+
+    with pytest.raises(ValueError, match="Unsupported file type"):
+        outline(tmp_path / "notes.txt")
+
+    (func,) = outline(
+        'def load(\n    path: str,\n    *,\n    strict: bool = False,\n) -> dict:\n    """Load a file.\n\n    Details.\n    """\n'
+    )
+    assert func.signature == "(path: str, *, strict: bool = False) -> dict"
+    assert func.docstring == "Load a file.\n\nDetails."
+    assert cls.docstring is None
+    assert cls.signature is None
+
+
+def test_inspect_tree(tmp_path: Path) -> None:
+    """Test filtered directory trees with sizes in text and JSON output."""
+    (tmp_path / "src").mkdir()
+    (tmp_path / "src" / "a.py").write_text("print(1)\n")
+    (tmp_path / "src" / "b.txt").write_text("abc")
+    (tmp_path / "docs").mkdir()
+    (tmp_path / "docs" / "readme.md").write_text("hello")
+
+    text = inspect_tree(tmp_path, TreeOptions(exclude=["docs"], sizes=False))
+    assert text.splitlines()[1:] == ["└ src/", "  ├ a.py", "  └ b.txt"]
+
+    tree = json.loads(inspect_tree(tmp_path, TreeOptions(include=["*.py"], format="json")))
+    assert (tree["file_count"], tree["size"]) == (1, 9)
+    (src,) = tree["children"]
+    assert (src["name"], src["type"], src["file_count"]) == ("src", "directory", 1)
+    assert src["children"] == [{"name": "a.py", "type": "file", "size": 9}]
+
+    with pytest.raises(ValueError):
+        TreeOptions(format="yaml")
+
+
+@pytest.mark.asyncio
+async def test_command_runner(tmp_path: Path, monkeypatch: pytest.MonkeyPatch) -> None:
+    """Test allowlists, confinement, streaming, env scrubbing and timeouts of commands."""
+    monkeypatch.setenv("SECRET", "token")
+    (tmp_path / "sub").mkdir()
+    runner = CommandRunner(tmp_path, allow=["sh", "echo", "sleep"], deny=["rm"], timeout=5, env={"GREETING": "hi"})
+
+    lines = []
+    result = await runner.run(
+        ["sh", "-c", 'echo "$GREETING $SECRET"; echo oops >&2; pwd'],
+        cwd="sub",
+        on_output=lambda stream, line: lines.append((stream, line)),
+    )
+    assert result.success
+    assert result.stdout.splitlines() == ["hi ", str((tmp_path / "sub").resolve())]
+    assert ("stderr", "oops") in lines
+
+    result = await runner.run(["sleep", "5"], timeout=0.2)
+    assert result.timed_out
+    assert result.exit_code is None
+
+    with pytest.raises(PermissionError):
+        await runner.run(["rm", "-rf", "sub"])
+    with pytest.raises(PermissionError):
+        await runner.run(["cat", "/etc/hostname"])
+    with pytest.raises(PermissionError):
+        await runner.run(["echo"], cwd="..")
+    with pytest.raises(FileNotFoundError):
+        await runner.run(["definitely-not-a-command"])
+
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(sys.platform == "win32", reason="shell scripts and process groups are Unix only")
+async def test_command_runner_paths_and_process_group(tmp_path: Path) -> None:
+    """Test that names in the allowlist only match bare commands and that timeouts kill spawned processes."""
+    fake = tmp_path / "echo"
+    fake.write_text("#!/bin/sh\necho fake\n")
+    fake.chmod(0o755)
+    runner = CommandRunner(tmp_path, allow=["sh", "echo"], timeout=5)
+    with pytest.raises(PermissionError):
+        await runner.run([str(fake)])
+    result = await CommandRunner(tmp_path, allow=[str(fake)]).run([str(fake)])
+    assert result.stdout == "fake\n"
+
+    result = await runner.run(["sh", "-c", 'sh -c "sleep 1; touch late" & wait'], timeout=0.2)
+    assert result.timed_out
+    await asyncio.sleep(1.5)
+    assert not (tmp_path / "late").exists()
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(sys.platform == "win32", reason="creating symlinks needs privileges")
+async def test_command_runner_symlinked_cwd(tmp_path: Path) -> None:
+    """Test that a working directory reaching a symlink out of the root is refused."""
+    root = tmp_path / "root"
+    (root / "sub").mkdir(parents=True)
+    outside = tmp_path / "outside"
+    outside.mkdir()
+    os.symlink(outside, root / "escape")
+    os.symlink(outside, root / "sub" / "parent")
+    runner = CommandRunner(root, allow=["pwd"])
+
+    for cwd in ("escape", "missing/../escape", "sub/missing/../parent"):
+        with pytest.raises(PermissionError):
+            await runner.run(["pwd"], cwd=cwd)
+    result = await runner.run(["pwd"], cwd="missing/../sub")
+    assert result.stdout.strip() == str((root / "sub").resolve())
+
+
+@pytest.mark.asyncio
+async def test_command_runner_output_limit(tmp_path: Path) -> None:
+    """Test that output is capped per stream and the duration is reported."""
+    runner = CommandRunner(tmp_path, allow=["sh"], max_output=10)
+
+    result = await runner.run(["sh", "-c", "printf 'abcdefghijklmnop'; printf 'short' >&2"])
+    assert result.success
+    assert result.truncated
+    assert result.stdout == "abcdefghij"
+    assert result.stderr == "short"
+    assert result.duration >= 0
+
+    result = await CommandRunner(tmp_path, allow=["sh"], max_output=None).run(["sh", "-c", "printf 'abcdefghijklmnop'"])
+    assert not result.truncated
+    assert result.stdout == "abcdefghijklmnop"
+
+    # A single line far larger than the limit is not buffered whole.
+    result = await runner.run(["sh", "-c", "head -c 100000000 /dev/zero"])
+    assert result.truncated
+    assert result.stdout == "\0" * 10
+
+    result = await CommandRunner(tmp_path, allow=["sh"], max_output=2).run(["sh", "-c", "printf 'h\\303\\251llo'"])
+    assert result.stdout == "h"
+
+
+@pytest.mark.asyncio
+async def test_shell_executor(tmp_path: Path) -> None:
+    """Test that command lines are split without a shell and only allowed executables run."""
+    (tmp_path / "sub").mkdir()
+    shell = ShellExecutor(["echo", "pwd"], root=tmp_path, timeout=5)
+    assert shell.allow == ["echo", "pwd"]
+    assert shell.root == tmp_path.resolve()
+
+    result = await shell.execute("echo 'two  spaces' $HOME")
+    assert result.argv == ["echo", "two  spaces", "$HOME"]
+    assert result.stdout == "two  spaces $HOME\n"
+    result = await shell.execute(["pwd"], cwd="sub")
+    assert result.stdout.strip() == str((tmp_path / "sub").resolve())
+
+    for command in ("echo a | cat", "echo a > out.txt", "echo a && pwd", "echo 'unbalanced", ""):
+        with pytest.raises(ValueError):
+            await shell.execute(command)
+    assert not (tmp_path / "out.txt").exists()
+    with pytest.raises(PermissionError):
+        await shell.execute("sh -c 'echo hi'")
+
+
+@pytest.fixture
+def http_server() -> Iterator[str]:
+    """Serve a small JSON document and a larger body on localhost."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self) -> None:  # noqa: N802
+            body = b'{"answer": 42}' if self.path == "/json" else b"x" * 100
+            self.send_response(200)
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *_args: Any) -> None:
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{server.server_address[1]}"
+    server.shutdown()
+
+
+@pytest.mark.asyncio
+async def test_http_tool(http_server: str, tmp_path: Path) -> None:
+    """Test domain policies, the size limit and the response cache of the HTTP tool."""
+    http = HttpTool(allow_domains=["127.0.0.1"], max_response_size=10, cache_dir=tmp_path / "cache")
+
+    response = await http.get(f"{http_server}/json")
+    assert response.ok
+    assert response.json() == {"answer": 42}
+    assert not response.from_cache
+    assert (await http.get(f"{http_server}/json")).from_cache
+
+    response = await http.get(f"{http_server}/large")
+    assert response.truncated
+    assert response.body == "x" * 10
+
+    with pytest.raises(PermissionError):
+        await http.get("https://example.com/")
+    with pytest.raises(PermissionError):
+        await HttpTool(deny_domains=["127.0.0.1"]).get(f"{http_server}/json")
+    with pytest.raises(PermissionError):
+        await http.get("file:///etc/hostname")
+    for durations in ({"timeout": -1}, {"timeout": float("nan")}, {"backoff": float("inf")}, {"cache_ttl": 1e300}):
+        with pytest.raises(ValueError):
+            HttpTool(**durations)
+    with pytest.raises(ValueError):
+        await http.get(f"{http_server}/json", timeout=float("inf"))
@@ -0,0 +1,494 @@
+//! HTTP requests under a domain allowlist, a response size limit, a timeout and retries.
+//!
+//! Redirects are checked against the same domain lists as the requested URL, so an allowed
+//! host cannot bounce a request elsewhere. Successful `GET` responses can be cached in a
+//! persistent cache shared across runs.
+
+use error_mapping::AsPyErr;
+use fabricatio_logger::{debug, info, warn};
+use pyo3::exceptions::{PyConnectionError, PyPermissionError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect;
+use reqwest::{Client, Method, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thryd::PersistentCache;
+
+/// Default number of bytes read from the body of a response.
+const DEFAULT_MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+
+/// Maximum number of redirects followed by a request.
+const MAX_REDIRECTS: usize = 10;
+
+/// Statuses worth retrying an idempotent request on.
+const RETRIED_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+/// Longest delay between two attempts of a request.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Response to a request made by an `HttpTool`.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpResponse {
+    /// The final URL of the response, after redirects.
+    url: String,
+    /// The HTTP status code.
+    status: u16,
+    /// The response headers, with lowercase names.
+    headers: HashMap<String, String>,
+    /// The body, decoded lossily as UTF-8.
+    body: String,
+    /// Whether the body was cut at the size limit of the tool.
+    truncated: bool,
+    /// Whether the response was served from the cache.
+    from_cache: bool,
+    /// Number of attempts made, 0 if served from the cache.
+    attempts: u32,
+    /// Wall-clock time the request took, in seconds.
+    duration: f64,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl HttpResponse {
+    /// Whether the status code is in the 2xx range.
+    #[getter]
+    fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Parses the body as JSON.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the body is not valid JSON.
+    fn json<'py>(&self, python: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value: serde_json::Value = serde_json::from_str(&self.body).into_pyresult()?;
+        pythonize::pythonize(python, &value).into_pyresult()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "HttpResponse(url={:?}, status={}, truncated={}, from_cache={}, duration={:.3})",
+            self.url, self.status, self.truncated, self.from_cache, self.duration
+        )
+    }
+}
+
+/// The hosts requests may reach.
+#[derive(Debug)]
+struct Domains {
+    allow: Option<Vec<String>>,
+    deny: Vec<String>,
+}
+
+impl Domains {
+    /// Whether a host is the given domain or one of its subdomains.
+    fn matches(host: &str, domain: &str) -> bool {
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|rest| rest.ends_with('.'))
+    }
+
+    /// Checks the scheme and host of a URL against the domain lists.
+    fn check(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Scheme of {url} is not HTTP"));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("{url} has no host"))?
+            .to_lowercase();
+        if self.deny.iter().any(|domain| Self::matches(&host, domain)) {
+            return Err(format!("Host `{host}` is denied"));
+        }
+        if let Some(allow) = &self.allow
+            && !allow.iter().any(|domain| Self::matches(&host, domain))
+        {
+            return Err(format!("Host `{host}` is not in the allowlist"));
+        }
+        Ok(())
+    }
+}
+
+/// A cached response and the time it was fetched.
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    response: HttpResponse,
+    /// Seconds since the Unix epoch.
+    fetched_at: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Maps a transport error to the matching Python exception.
+fn request_error(url: &str, e: reqwest::Error) -> PyErr {
+    if e.is_timeout() {
+        PyTimeoutError::new_err(format!("Request to {url} timed out"))
+    } else if e.is_redirect() {
+        PyPermissionError::new_err(format!("Redirect from {url} refused: {e}"))
+    } else {
+        PyConnectionError::new_err(format!("Request to {url} failed: {e}"))
+    }
+}
+
+/// The restrictions and resources shared by the requests of a tool.
+struct Policy {
+    client: Client,
+    domains: Arc<Domains>,
+    max_response_size: Option<usize>,
+    timeout: Option<Duration>,
+    retries: u32,
+    backoff: Duration,
+    cache: Option<PersistentCache>,
+    cache_ttl: Option<f64>,
+}
+
+impl Policy {
+    /// The cached response to a request, if fresh enough.
+    fn cached(&self, key: &str) -> Option<HttpResponse> {
+        let cached = self.cache.as_ref()?.get_de::<CachedResponse>(key)?;
+        let age = now_secs().saturating_sub(cached.fetched_at) as f64;
+        if self.cache_ttl.is_some_and(|ttl| age > ttl) {
+            return None;
+        }
+        Some(HttpResponse {
+            from_cache: true,
+            attempts: 0,
+            duration: 0.0,
+            ..cached.response
+        })
+    }
+
+    fn store(&self, key: &str, response: &HttpResponse) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let entry = CachedResponse {
+            response: response.clone(),
+            fetched_at: now_secs(),
+        };
+        if let Err(e) = cache.set_ser(key, &entry) {
+            warn!("Failed to cache the response of {key}: {e}");
+        }
+    }
+
+    /// Reads the body of a response up to the size limit.
+    async fn read_body(&self, mut response: reqwest::Response) -> reqwest::Result<(Vec<u8>, bool)> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let room = self
+                .max_response_size
+                .map_or(usize::MAX, |max| max.saturating_sub(body.len()));
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                return Ok((body, true));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((body, false))
+    }
+
+    /// Sends a request, retrying idempotent ones on transport errors and on the statuses
+    /// of [`RETRIED_STATUSES`] with an exponential backoff.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<String>,
+        timeout: Option<Duration>,
+    ) -> PyResult<HttpResponse> {
+        let started = Instant::now();
+        let retries = if method.is_idempotent() {
+            self.retries
+        } else {
+            0
+        };
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut request = self
+                .client
+                .request(method.clone(), url.clone())
+                .headers(headers.clone());
+            if let Some(timeout) = timeout.or(self.timeout) {
+                request = request.timeout(timeout);
+            }
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+
+            let outcome = match request.send().await {
+                Ok(response)
+                    if attempts <= retries
+                        && RETRIED_STATUSES.contains(&response.status().as_u16()) =>
+                {
+                    Err(format!("status {}", response.status()))
+                }
+                Ok(response) => Ok(response),
+                Err(e) if attempts <= retries && !e.is_redirect() => Err(e.to_string()),
+                Err(e) => return Err(request_error(url.as_str(), e)),
+            };
+            let response = match outcome {
+                Ok(response) => response,
+                Err(reason) => {
+                    let delay = 2u32
+                        .checked_pow(attempts - 1)
+                        .and_then(|factor| self.backoff.checked_mul(factor))
+                        .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF));
+                    debug!("Retrying {method} {url} in {delay:.2?} after {reason}");
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+            };
+
+            let final_url = response.url().to_string();
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.as_str().to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect();
+            let (body, truncated) = self
+                .read_body(response)
+                .await
+                .map_err(|e| request_error(url.as_str(), e))?;
+            let duration = started.elapsed().as_secs_f64();
+            info!("{method} {url} returned {status} in {duration:.3}s after {attempts} attempt(s)");
+            return Ok(HttpResponse {
+                url: final_url,
+                status,
+                headers,
+                body: String::from_utf8_lossy(&body).into_owned(),
+                truncated,
+                from_cache: false,
+                attempts,
+                duration,
+            });
+        }
+    }
+}
+
+/// Makes HTTP requests restricted to allowed domains, with a response size limit, a
+/// timeout, automatic retries and an optional persistent cache of `GET` responses.
+///
+/// Every request is logged with its status and duration.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(skip_from_py_object)]
+pub struct HttpTool {
+    policy: Arc<Policy>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl HttpTool {
+    /// Create a new HttpTool instance.
+    ///
+    /// Args:
+    ///     allow_domains: Domains requests may reach, subdomains included. All domains may
+    ///         be reached if None.
+    ///     deny_domains: Domains requests may never reach, subdomains included. Takes
+    ///         precedence over `allow_domains`.
+    ///     timeout: Default timeout of a request in seconds, None for no timeout.
+    ///     max_response_size: Number of bytes read from the body of a response, None for
+    ///         no limit. Defaults to 1 MiB.
+    ///     retries: Number of times an idempotent request is retried on a transport error
+    ///         or a 429, 502, 503 or 504 status.
+    ///     backoff: Delay before the first retry in seconds, doubled on each retry up to a
+    ///         minute.
+    ///     cache_dir: Directory of the persistent cache of successful `GET` responses. No
+    ///         caching if None. A directory must not be shared by two live tools.
+    ///     cache_ttl: Age in seconds after which a cached response is fetched again, None
+    ///         to keep cached responses forever.
+    ///     headers: Headers sent with every request.
+    ///
+    /// Raises:
+    ///     ValueError: If a header is invalid or a duration is negative or not finite.
+    ///     RuntimeError: If the cache cannot be opened.
+    #[new]
+    #[pyo3(signature = (allow_domains = None, deny_domains = vec![], timeout = Some(30.0), max_response_size = Some(DEFAULT_MAX_RESPONSE_SIZE), retries = 2, backoff = 0.5, cache_dir = None, cache_ttl = None, headers = HashMap::new()))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        allow_domains: Option<Vec<String>>,
+        deny_domains: Vec<String>,
+        timeout: Option<f64>,
+        max_response_size: Option<usize>,
+        retries: u32,
+        backoff: f64,
+        cache_dir: Option<PathBuf>,
+        cache_ttl: Option<f64>,
+        headers: HashMap<String, String>,
+    ) -> PyResult<Self> {
+        if let Some(ttl) = cache_ttl {
+            duration(ttl)?;
+        }
+        let timeout = timeout.map(duration).transpose()?;
+        let backoff = duration(backoff)?;
+        let lowercase = |domains: Vec<String>| -> Vec<String> {
+            domains.into_iter().map(|d| d.to_lowercase()).collect()
+        };
+        let domains = Arc::new(Domains {
+            allow: allow_domains.map(lowercase),
+            deny: lowercase(deny_domains),
+        });
+        let redirect_domains = domains.clone();
+        let client = Client::builder()
+            .default_headers(header_map(headers)?)
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                match redirect_domains.check(attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(reason) => attempt.error(reason),
+                }
+            }))
+            .build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let cache = cache_dir
+            .map(PersistentCache::create_or_open)
+            .transpose()
+            .into_pyresult()?;
+        Ok(Self {
+            policy: Arc::new(Policy {
+                client,
+                domains,
+                max_response_size,
+                timeout,
+                retries,
+                backoff,
+                cache,
+                cache_ttl,
+            }),
+        })
+    }
+
+    /// Sends a request and reads its response.
+    ///
+    /// The URL is checked against the domain lists before anything is sent, and so is the
+    /// target of every redirect. The body beyond the size limit of the tool is dropped and
+    /// the response marked as truncated. Successful `GET` responses to requests without
+    /// headers of their own are served from and stored in the cache, if the tool has one.
+    ///
+    /// Args:
+    ///     method: The HTTP method, such as `GET` or `POST`.
+    ///     url: The URL, with an `http` or `https` scheme.
+    ///     headers: Headers sent with this request, on top of those of the tool.
+    ///     body: The body of the request.
+    ///     timeout: Timeout in seconds, overriding the default of the tool.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to an HttpResponse.
+    ///
+    /// Raises:
+    ///     ValueError: If the method, the URL, a header or the timeout is invalid.
+    ///     PermissionError: If the host or a redirect target is not permitted.
+    ///     TimeoutError: If the last attempt timed out.
+    ///     ConnectionError: If the last attempt failed.
+    #[pyo3(signature = (method, url, headers = HashMap::new(), body = None, timeout = None))]
+    fn request<'a>(
+        &self,
+        python: Python<'a>,
+        method: &str,
+        url: &str,
+        headers: HashMap<String, String>,
+        body: Option<String>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| PyValueError::new_err(format!("Invalid method `{method}`")))?;
+        let url = Url::parse(url).map_err(|e| PyValueError::new_err(format!("{url}: {e}")))?;
+        let timeout = timeout.map(duration).transpose()?;
+        self.policy
+            .domains
+            .check(&url)
+            .map_err(PyPermissionError::new_err)?;
+        let cache_key =
+            (method == Method::GET && self.policy.cache.is_some() && headers.is_empty())
+                .then(|| format!("{method} {url}"));
+        let headers = header_map(headers)?;
+        let policy = self.policy.clone();
+
+        future_into_py(python, async move {
+            if let Some(cached) = cache_key.as_deref().and_then(|key| policy.cached(key)) {
+                debug!("Serving {url} from the cache");
+                return Ok(cached);
+            }
+            let response = policy.send(method, url, headers, body, timeout).await?;
+            if let Some(key) = &cache_key
+                && response.ok()
+                && !response.truncated
+            {
+                policy.store(key, &response);
+            }
+            Ok(response)
+        })
+    }
+
+    /// Sends a `GET` request, see `request`.
+    ///
+    /// Args:
+    ///     url: The URL, with an `http` or `https` scheme.
+    ///     headers: Headers sent with this request, on top of those of the tool.
+    ///     timeout: Timeout in seconds, overriding the default of the tool.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to an HttpResponse.
+    #[pyo3(signature = (url, headers = HashMap::new(), timeout = None))]
+    fn get<'a>(
+        &self,
+        python: Python<'a>,
+        url: &str,
+        headers: HashMap<String, String>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        self.request(python, "GET", url, headers, None, timeout)
+    }
+}
+
+/// Converts a duration given in seconds, refusing negative and non-finite ones.
+fn duration(secs: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| PyValueError::new_err(format!("Invalid duration {secs}")))
+}
+
+/// Converts headers given as strings to a header map.
+fn header_map(headers: HashMap<String, String>) -> PyResult<HeaderMap> {
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            let invalid = |e: &dyn std::fmt::Display| {
+                PyValueError::new_err(format!("Invalid header `{name}`: {e}"))
+            };
+            Ok((
+                HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?,
+                HeaderValue::from_str(&value).map_err(|e| invalid(&e))?,
+            ))
+        })
+        .collect()
+}
+
+/// Registers the HTTP tool with the Python module.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<HttpTool>()?;
+    m.add_class::<HttpResponse>()?;
+    Ok(())
+}
@@ -1,8 +1,9 @@
+mod outline;
 mod tree;
 
 use pyo3::prelude::*;
 
-/// Registers the tree inspection functions with the Python module.
+/// Registers the inspection functions with the Python module.
 ///
 /// Args:
 ///     py: The Python interpreter instance.
@@ -12,5 +13,6 @@ use pyo3::prelude::*;
 ///     PyResult<()> indicating success.
 pub(super) fn register(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     tree::register(py, m)?;
+    outline::register(py, m)?;
     Ok(())
 }
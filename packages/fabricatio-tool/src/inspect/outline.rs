@@ -0,0 +1,372 @@
+use crate::line_index::LineIndex;
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use quote::ToTokens;
+use rustpython_ast::text_size::TextRange;
+use rustpython_ast::{Constant, Expr, ExprConstant, Ranged, Stmt};
+use rustpython_parser::{Mode, parse};
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr as RustExpr, ImplItem, Item, Lit, Meta, Signature, TraitItem, Type};
+
+/// A class, function or other definition found in a source file.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    /// Kind of the definition, e.g. `class`, `function`, `async_function`, `struct` or `impl`.
+    kind: String,
+    /// Name of the definition.
+    name: String,
+    /// First line of the definition, 1-based.
+    start_line: usize,
+    /// Last line of the definition, 1-based and inclusive.
+    end_line: usize,
+    /// Definitions nested inside this one, such as methods.
+    children: Vec<Symbol>,
+    /// Docstring of a Python definition or doc comment of a Rust item, dedented.
+    docstring: Option<String>,
+    /// Parameters and return annotation of a Python function, e.g. `(self, x: int = 1) -> str`.
+    signature: Option<String>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl Symbol {
+    fn __repr__(&self) -> String {
+        format!(
+            "{} {} (L{}-L{})",
+            self.kind, self.name, self.start_line, self.end_line
+        )
+    }
+}
+
+/// Dedents a docstring the way `inspect.cleandoc` does.
+fn clean_docstring(doc: &str) -> String {
+    let mut lines = doc.trim().lines();
+    let first = lines.next().unwrap_or_default().trim();
+    let rest = lines.collect::<Vec<_>>();
+    let indent = rest
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    std::iter::once(first)
+        .chain(
+            rest.iter()
+                .map(|line| line.get(indent..).unwrap_or_default().trim_end()),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the docstring of a Python block, if its first statement is a string literal.
+fn python_docstring(body: &[Stmt]) -> Option<String> {
+    match body.first()?.as_expr_stmt()?.value.as_ref() {
+        Expr::Constant(ExprConstant {
+            value: Constant::Str(doc),
+            ..
+        }) => Some(clean_docstring(doc)),
+        _ => None,
+    }
+}
+
+/// Extracts the parameters and return annotation of a Python function from its source.
+///
+/// The header is taken from the source between the definition start and its body, so
+/// defaults and annotations are kept as written, with line breaks collapsed.
+fn python_signature(source: &str, name: &str, range: TextRange, body: &[Stmt]) -> Option<String> {
+    let body_start = body.first().map_or(range.end(), |stmt| stmt.start());
+    let header = source.get(usize::from(range.start())..usize::from(body_start))?;
+    // Skip decorators, which may mention the name too.
+    let open = header.match_indices("def").find_map(|(i, _)| {
+        let after = header[i + 3..].trim_start();
+        let after = after.strip_prefix(name)?.trim_start();
+        after.starts_with('(').then(|| header.len() - after.len())
+    })?;
+
+    let mut depth = 0;
+    let mut quote = None;
+    let mut close = None;
+    for (i, c) in header[open..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+
+    let params = header[open + 1..close]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let params = params.trim_end_matches(',').trim();
+    let returns = header[close + 1..]
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(if returns.is_empty() {
+        format!("({params})")
+    } else {
+        format!("({params}) {returns}")
+    })
+}
+
+/// Collects the classes and functions defined directly in a Python block.
+fn python_symbols(body: &[Stmt], source: &str, lines: &LineIndex) -> Vec<Symbol> {
+    body.iter()
+        .filter_map(|stmt| {
+            let (kind, name, range, body) = match stmt {
+                Stmt::ClassDef(class) => ("class", class.name.as_str(), class.range, &class.body),
+                Stmt::FunctionDef(func) => ("function", func.name.as_str(), func.range, &func.body),
+                Stmt::AsyncFunctionDef(func) => {
+                    ("async_function", func.name.as_str(), func.range, &func.body)
+                }
+                _ => return None,
+            };
+            let (start_line, end_line) = lines.lines_of(range);
+            Some(Symbol {
+                kind: kind.to_string(),
+                name: name.to_string(),
+                start_line,
+                end_line,
+                children: python_symbols(body, source, lines),
+                docstring: python_docstring(body),
+                signature: (kind != "class")
+                    .then(|| python_signature(source, name, range, body))
+                    .flatten(),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the outline of a Python source.
+fn python_outline(source: &str, source_path: &str) -> PyResult<Vec<Symbol>> {
+    let module = parse(source, Mode::Module, source_path)
+        .map_err(|e| PyValueError::new_err(format!("Failed to parse Python source: {e}")))?;
+    let body = &module
+        .as_module()
+        .ok_or_else(|| PyValueError::new_err("No module found"))?
+        .body;
+    Ok(python_symbols(body, source, &LineIndex::new(source)))
+}
+
+/// Joins the `///` doc comments of a Rust item, dedented.
+fn rust_docs(attrs: &[Attribute]) -> Option<String> {
+    let docs = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                RustExpr::Lit(lit) => match &lit.lit {
+                    Lit::Str(doc) => Some(doc.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    (!docs.is_empty()).then(|| clean_docstring(&format!("\n{}", docs.join("\n"))))
+}
+
+/// Creates a symbol spanning the given syntax node.
+fn rust_symbol<S: Spanned>(
+    kind: &str,
+    name: String,
+    node: &S,
+    attrs: &[Attribute],
+    children: Vec<Symbol>,
+) -> Symbol {
+    let span = node.span();
+    Symbol {
+        kind: kind.to_string(),
+        name,
+        start_line: span.start().line,
+        end_line: span.end().line,
+        children,
+        docstring: rust_docs(attrs),
+        signature: None,
+    }
+}
+
+/// Returns the kind of a Rust function given its signature.
+fn rust_fn_kind(sig: &Signature) -> &'static str {
+    if sig.asyncness.is_some() {
+        "async_function"
+    } else {
+        "function"
+    }
+}
+
+/// Renders a type by its last path segment, e.g. `Vec` for `std::vec::Vec<T>`.
+fn rust_type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        other => other.to_token_stream().to_string(),
+    }
+}
+
+/// Converts a Rust item into a symbol, skipping items that are not definitions of interest.
+fn rust_item_symbol(item: &Item) -> Option<Symbol> {
+    let symbol = match item {
+        Item::Fn(func) => rust_symbol(
+            rust_fn_kind(&func.sig),
+            func.sig.ident.to_string(),
+            item,
+            &func.attrs,
+            vec![],
+        ),
+        Item::Struct(s) => rust_symbol("struct", s.ident.to_string(), item, &s.attrs, vec![]),
+        Item::Enum(e) => rust_symbol("enum", e.ident.to_string(), item, &e.attrs, vec![]),
+        Item::Union(u) => rust_symbol("union", u.ident.to_string(), item, &u.attrs, vec![]),
+        Item::Trait(t) => {
+            let children = t
+                .items
+                .iter()
+                .filter_map(|trait_item| match trait_item {
+                    TraitItem::Fn(func) => Some(rust_symbol(
+                        rust_fn_kind(&func.sig),
+                        func.sig.ident.to_string(),
+                        trait_item,
+                        &func.attrs,
+                        vec![],
+                    )),
+                    _ => None,
+                })
+                .collect();
+            rust_symbol("trait", t.ident.to_string(), item, &t.attrs, children)
+        }
+        Item::Impl(i) => {
+            let self_ty = rust_type_name(&i.self_ty);
+            let name = match &i.trait_ {
+                Some((_, path, _)) => match path.segments.last() {
+                    Some(segment) => format!("{} for {}", segment.ident, self_ty),
+                    None => self_ty,
+                },
+                None => self_ty,
+            };
+            let children = i
+                .items
+                .iter()
+                .filter_map(|impl_item| match impl_item {
+                    ImplItem::Fn(func) => Some(rust_symbol(
+                        rust_fn_kind(&func.sig),
+                        func.sig.ident.to_string(),
+                        impl_item,
+                        &func.attrs,
+                        vec![],
+                    )),
+                    _ => None,
+                })
+                .collect();
+            rust_symbol("impl", name, item, &i.attrs, children)
+        }
+        Item::Mod(m) => {
+            let children = m
+                .content
+                .as_ref()
+                .map(|(_, items)| items.iter().filter_map(rust_item_symbol).collect())
+                .unwrap_or_default();
+            rust_symbol("module", m.ident.to_string(), item, &m.attrs, children)
+        }
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// Extracts the outline of a Rust source.
+fn rust_outline(source: &str) -> PyResult<Vec<Symbol>> {
+    let file = syn::parse_file(source)
+        .map_err(|e| PyValueError::new_err(format!("Failed to parse Rust source: {e}")))?;
+    Ok(file.items.iter().filter_map(rust_item_symbol).collect())
+}
+
+/// Whether a string passed to `outline` names a source file rather than holding Python source.
+fn looks_like_path(text: &str) -> bool {
+    !text.contains('\n')
+        && matches!(
+            Path::new(text.trim())
+                .extension()
+                .and_then(|ext| ext.to_str()),
+            Some("py" | "pyi" | "rs")
+        )
+}
+
+/// Extracts the outline of a Python or Rust source file, or of Python source code.
+///
+/// Lists the classes, functions, structs, traits and impl blocks defined in the file
+/// with their line ranges, docstrings and, for Python functions, signatures, so a large
+/// file can be understood without reading it fully.
+///
+/// Args:
+///     path_or_source: Path to a `.py`, `.pyi` or `.rs` file, or Python source code. A
+///         single-line string ending with one of these extensions is taken as a path.
+///
+/// Returns:
+///     The top-level definitions in source order, with methods and nested definitions
+///     as their children.
+///
+/// Raises:
+///     OSError: If the file cannot be read.
+///     ValueError: If the file type is not supported or the source cannot be parsed.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+pub fn outline(path_or_source: &Bound<'_, PyAny>) -> PyResult<Vec<Symbol>> {
+    if let Ok(text) = path_or_source.cast::<PyString>() {
+        let text = text.to_cow()?;
+        if !looks_like_path(&text) {
+            return python_outline(&text, "<string>");
+        }
+    }
+    let path = path_or_source.extract::<PathBuf>()?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("py" | "pyi") => python_outline(
+            &fs::read_to_string(&path).into_pyresult()?,
+            &path.to_string_lossy(),
+        ),
+        Some("rs") => rust_outline(&fs::read_to_string(&path).into_pyresult()?),
+        _ => Err(PyValueError::new_err(format!(
+            "Unsupported file type for outline: {}",
+            path.display()
+        ))),
+    }
+}
+
+/// Registers the outline function with the Python module.
+///
+/// Args:
+///     _py: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(super) fn register(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(outline, m)?)?;
+    m.add_class::<Symbol>()?;
+    Ok(())
+}
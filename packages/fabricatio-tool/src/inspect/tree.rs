@@ -1,10 +1,16 @@
 use ignore::WalkBuilder;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use rayon::prelude::*;
+use serde_json::{Value, json};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use utils::file_walk::{self, WalkRules};
+
+const TEXT: &str = "text";
+const JSON: &str = "json";
 #[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
 #[pyfunction]
 #[pyo3(signature = (directory=None, max_depth = 10))]
@@ -122,6 +128,260 @@ struct TreeEntry {
     is_last: bool,
 }
 
+/// Options of `inspect_tree`.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(from_py_object)]
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    max_depth: usize,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    gitignore: bool,
+    hidden: bool,
+    sizes: bool,
+    format: String,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            include: vec![],
+            exclude: vec![],
+            gitignore: true,
+            hidden: false,
+            sizes: true,
+            format: TEXT.to_string(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl TreeOptions {
+    /// Create a new TreeOptions instance.
+    ///
+    /// Args:
+    ///     max_depth: Maximum depth to traverse.
+    ///     include: Glob patterns of the files to list, all files are listed if empty.
+    ///         Directories left without listed files are omitted.
+    ///     exclude: Glob patterns of the files and directories to skip.
+    ///     gitignore: Whether to respect `.gitignore`, `.ignore` and git exclude files.
+    ///     hidden: Whether to list hidden files and directories.
+    ///     sizes: Whether to show sizes and file counts in the text output.
+    ///     format: `text` for a tree drawing, or `json` for nested objects.
+    ///
+    /// Raises:
+    ///     ValueError: If the format is unknown.
+    #[new]
+    #[pyo3(signature = (max_depth = 10, include = vec![], exclude = vec![], gitignore = true, hidden = false, sizes = true, format = TEXT.to_string()))]
+    fn new(
+        max_depth: usize,
+        include: Vec<String>,
+        exclude: Vec<String>,
+        gitignore: bool,
+        hidden: bool,
+        sizes: bool,
+        format: String,
+    ) -> PyResult<Self> {
+        if format != TEXT && format != JSON {
+            return Err(PyValueError::new_err(format!(
+                "Invalid format: {format}, Must be one of {TEXT} or {JSON}"
+            )));
+        }
+        Ok(Self {
+            max_depth,
+            include,
+            exclude,
+            gitignore,
+            hidden,
+            sizes,
+            format,
+        })
+    }
+}
+
+/// A file or directory of the tree built by `inspect_tree`.
+struct TreeNode {
+    name: String,
+    is_dir: bool,
+    /// Size of the file, or total size of the files listed below the directory.
+    size: u64,
+    /// Number of files listed below the directory, 1 for a file.
+    file_count: usize,
+    children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn new(name: String, is_dir: bool, size: u64) -> Self {
+        Self {
+            name,
+            is_dir,
+            size,
+            file_count: usize::from(!is_dir),
+            children: vec![],
+        }
+    }
+
+    /// Attaches a finished child, pruning directories without files if `prune` is set.
+    fn attach(&mut self, child: TreeNode, prune: bool) {
+        if prune && child.is_dir && child.file_count == 0 {
+            return;
+        }
+        self.size += child.size;
+        self.file_count += child.file_count;
+        self.children.push(child);
+    }
+
+    fn label(&self, sizes: bool) -> String {
+        match (self.is_dir, sizes) {
+            (true, true) => format!(
+                "{}/ ({} files, {})",
+                self.name,
+                self.file_count,
+                human_size(self.size)
+            ),
+            (true, false) => format!("{}/", self.name),
+            (false, true) => format!("{} ({})", self.name, human_size(self.size)),
+            (false, false) => self.name.clone(),
+        }
+    }
+
+    /// Flattens the children into the entries drawn by `build_tree_lines`.
+    fn tree_entries(&self, depth: usize, sizes: bool, entries: &mut Vec<TreeEntry>) {
+        let last = self.children.len().saturating_sub(1);
+        for (i, child) in self.children.iter().enumerate() {
+            entries.push(TreeEntry {
+                depth,
+                name: child.label(sizes),
+                is_last: i == last,
+            });
+            child.tree_entries(depth + 1, sizes, entries);
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        if self.is_dir {
+            json!({
+                "name": self.name,
+                "type": "directory",
+                "size": self.size,
+                "file_count": self.file_count,
+                "children": self.children.iter().map(TreeNode::to_json).collect::<Vec<_>>(),
+            })
+        } else {
+            json!({ "name": self.name, "type": "file", "size": self.size })
+        }
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 KiB`.
+fn human_size(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Walks a directory into a tree according to the options.
+///
+/// The entries are those the file browser of the web UI lists under the same rules, see
+/// `utils::file_walk`.
+fn build_tree(root: &Path, options: &TreeOptions) -> PyResult<TreeNode> {
+    let prune = !options.include.is_empty();
+    let walker = file_walk::walk(
+        root,
+        &WalkRules {
+            max_depth: options.max_depth,
+            include: options.include.clone(),
+            exclude: options.exclude.clone(),
+            gitignore: options.gitignore,
+            hidden: options.hidden,
+        },
+    )
+    .map_err(PyValueError::new_err)?;
+
+    let name = root
+        .file_name()
+        .map_or_else(|| ".".to_string(), |n| n.to_string_lossy().into_owned());
+    // Directories still being filled, from the root down to the parent of the next entry.
+    let mut stack = vec![TreeNode::new(name, true, 0)];
+    for entry in walker {
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        close_dirs(&mut stack, entry.depth(), prune);
+        let size = entry
+            .metadata()
+            .map_or(0, |m| if is_dir { 0 } else { m.len() });
+        let node = TreeNode::new(
+            entry.file_name().to_string_lossy().into_owned(),
+            is_dir,
+            size,
+        );
+        if is_dir {
+            stack.push(node);
+        } else if let Some(parent) = stack.last_mut() {
+            parent.attach(node, prune);
+        }
+    }
+    close_dirs(&mut stack, 1, prune);
+    Ok(stack.remove(0))
+}
+
+/// Closes the directories deeper than `depth`, attaching each one to its parent.
+fn close_dirs(stack: &mut Vec<TreeNode>, depth: usize, prune: bool) {
+    while stack.len() > depth.max(1) {
+        if let Some(child) = stack.pop()
+            && let Some(parent) = stack.last_mut()
+        {
+            parent.attach(child, prune);
+        }
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (path=None, options=None))]
+/// Builds a directory tree with filtering, size and file count statistics.
+///
+/// Sizes and file counts of a directory only cover the files listed below it, within
+/// the depth limit and the filters.
+///
+/// Args:
+///     path: Root path to inspect (default: current directory).
+///     options: The traversal and output options, see `TreeOptions`.
+///
+/// Returns:
+///     A tree drawing like `treeview`, or a JSON document of nested objects with `name`,
+///     `type` (`directory` or `file`), `size` and, for directories, `file_count` and
+///     `children`.
+///
+/// Raises:
+///     ValueError: If a glob pattern is invalid.
+pub fn inspect_tree(path: Option<PathBuf>, options: Option<TreeOptions>) -> PyResult<String> {
+    let path = path.unwrap_or_else(|| PathBuf::from("."));
+    let options = options.unwrap_or_default();
+    let tree = build_tree(&path, &options)?;
+
+    if options.format == JSON {
+        return Ok(tree.to_json().to_string());
+    }
+    let mut entries = vec![];
+    tree.tree_entries(1, options.sizes, &mut entries);
+    Ok(format!(
+        "{}\n{}",
+        tree.label(options.sizes),
+        build_tree_lines(entries)
+    ))
+}
+
 /// Registers the treeview function with the Python module.
 ///
 /// Args:
@@ -132,5 +392,7 @@ struct TreeEntry {
 ///     PyResult<()> indicating success.
 pub(super) fn register(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(treeview, m)?)?;
+    m.add_function(wrap_pyfunction!(inspect_tree, m)?)?;
+    m.add_class::<TreeOptions>()?;
     Ok(())
 }
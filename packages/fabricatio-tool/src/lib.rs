@@ -3,9 +3,12 @@
 use fabricatio_logger::init_logger_auto;
 use pyo3::prelude::*;
 
+mod http;
 mod inspect;
+mod line_index;
 mod linter;
 mod mcp;
+mod shell;
 mod tool;
 
 /// A Python module implemented in Rust. The name of this function must match
@@ -18,6 +21,8 @@ fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     tool::register(python, m)?;
     mcp::register(python, m)?;
     inspect::register(python, m)?;
+    shell::register(python, m)?;
+    http::register(python, m)?;
     Ok(())
 }
 
@@ -0,0 +1,45 @@
+use rustpython_ast::text_size::TextRange;
+
+/// Maps byte offsets of a source text to 1-based line and column numbers.
+pub(crate) struct LineIndex(Vec<usize>);
+
+impl LineIndex {
+    pub(crate) fn new(source: &str) -> Self {
+        Self(
+            std::iter::once(0)
+                .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+        )
+    }
+
+    /// Returns the line containing the offset.
+    pub(crate) fn line_of(&self, offset: usize) -> usize {
+        match self.0.binary_search(&offset) {
+            Ok(line) => line + 1,
+            Err(line) => line,
+        }
+    }
+
+    /// Returns the line and the character column of the offset.
+    pub(crate) fn position_of(&self, source: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_of(offset);
+        let column = source
+            .get(self.0[line - 1]..offset)
+            .map_or(0, |prefix| prefix.chars().count());
+        (line, column + 1)
+    }
+
+    /// Returns the text of a line, without its line break.
+    pub(crate) fn line_text<'a>(&self, source: &'a str, line: usize) -> &'a str {
+        let start = self.0[line - 1];
+        let end = self.0.get(line).copied().unwrap_or(source.len());
+        source[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// Returns the first and last line covered by the range.
+    pub(crate) fn lines_of(&self, range: TextRange) -> (usize, usize) {
+        let start = usize::from(range.start());
+        let end = usize::from(range.end()).saturating_sub(1).max(start);
+        (self.line_of(start), self.line_of(end))
+    }
+}
@@ -1,8 +1,51 @@
 use fabricatio_logger::{debug, error, info, warn};
 use rustpython_ast::text_size::TextRange;
-use rustpython_ast::{Expr, Stmt, Visitor};
+use rustpython_ast::{Constant, ExceptHandler, Expr, ExprConstant, Ranged, Stmt, Visitor};
 use rustpython_parser::{Mode, parse};
 use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Dunders and introspection attributes that let sandboxed code reach objects it was not handed,
+/// such as the builtins, other modules or the caller's frame.
+const DANGEROUS_ATTRIBUTES: &[&str] = &[
+    "__base__",
+    "__bases__",
+    "__builtins__",
+    "__class__",
+    "__closure__",
+    "__code__",
+    "__delattr__",
+    "__dict__",
+    "__func__",
+    "__getattr__",
+    "__getattribute__",
+    "__globals__",
+    "__import__",
+    "__init_subclass__",
+    "__loader__",
+    "__mro__",
+    "__reduce__",
+    "__reduce_ex__",
+    "__self__",
+    "__setattr__",
+    "__spec__",
+    "__subclasses__",
+    "ag_frame",
+    "cr_frame",
+    "f_back",
+    "f_builtins",
+    "f_globals",
+    "f_locals",
+    "gi_frame",
+    "tb_frame",
+];
+
+/// Builtins taking an attribute name as their second argument
+const ATTRIBUTE_BUILTINS: &[&str] = &["getattr", "setattr", "delattr", "hasattr"];
+
+/// Builtins running or compiling the source passed as their first argument
+const CODE_BUILTINS: &[&str] = &["exec", "eval", "compile"];
 
 /// Configuration struct for defining allowlist/denylist rules
 #[derive(Default)]
@@ -10,6 +53,9 @@ pub struct LinterConfig {
     module_mode: CheckMode,
     import_mode: CheckMode,
     call_mode: CheckMode,
+    attribute_mode: CheckMode,
+    /// Skips the default-deny check of [`DANGEROUS_ATTRIBUTES`]
+    permit_dangerous_attributes: bool,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -89,12 +135,92 @@ impl LinterConfig {
         self.call_mode = CheckMode::Blacklist(calls);
         self
     }
+
+    // --- Attribute-related configurations ---
+    /// Sets allowed attribute names (whitelist mode)
+    pub fn with_allowed_attributes(mut self, attributes: HashSet<String>) -> Self {
+        debug!(
+            "Setting allowed attributes (whitelist) with {} entries",
+            attributes.len()
+        );
+        self.attribute_mode = CheckMode::Whitelist(attributes);
+        self
+    }
+
+    /// Sets forbidden attribute names (blacklist mode)
+    pub fn with_forbidden_attributes(mut self, attributes: HashSet<String>) -> Self {
+        debug!(
+            "Setting forbidden attributes (blacklist) with {} entries",
+            attributes.len()
+        );
+        self.attribute_mode = CheckMode::Blacklist(attributes);
+        self
+    }
+
+    /// Sets whether dangerous dunders and introspection attributes are permitted
+    ///
+    /// They are denied by default, regardless of the attribute mode.
+    pub fn with_dangerous_attributes_permitted(mut self, permitted: bool) -> Self {
+        debug!("Setting dangerous attributes permitted: {}", permitted);
+        self.permit_dangerous_attributes = permitted;
+        self
+    }
+}
+
+/// The rule a violation breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    SyntaxError,
+    ForbiddenModule,
+    ForbiddenImport,
+    ForbiddenCall,
+    ForbiddenAttribute,
+    DangerousAttribute,
+    DynamicAttribute,
+    DynamicCode,
+    DynamicImport,
+    ForbiddenDecorator,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::SyntaxError => "syntax-error",
+            Self::ForbiddenModule => "forbidden-module",
+            Self::ForbiddenImport => "forbidden-import",
+            Self::ForbiddenCall => "forbidden-call",
+            Self::ForbiddenAttribute => "forbidden-attribute",
+            Self::DangerousAttribute => "dangerous-attribute",
+            Self::DynamicAttribute => "dynamic-attribute",
+            Self::DynamicCode => "dynamic-code",
+            Self::DynamicImport => "dynamic-import",
+            Self::ForbiddenDecorator => "forbidden-decorator",
+        })
+    }
+}
+
+impl Rule {
+    /// Whether the rewrite pass can neutralize violations of this rule
+    pub fn is_fixable(self) -> bool {
+        matches!(
+            self,
+            Self::ForbiddenModule | Self::ForbiddenImport | Self::ForbiddenCall
+        )
+    }
+}
+
+/// A rule violation located in the source
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub rule: Rule,
+    pub message: String,
+    pub range: TextRange,
 }
 
 /// AST visitor for checking linting rules
 struct LinterVisitor<'a> {
     config: &'a LinterConfig,
-    violations: Vec<String>,
+    violations: Vec<Violation>,
 }
 
 impl<'a> LinterVisitor<'a> {
@@ -106,25 +232,57 @@ impl<'a> LinterVisitor<'a> {
             violations: vec![],
         }
     }
+
+    /// Records a violation, skipping duplicates of an already recorded one
+    fn report(&mut self, (rule, message): (Rule, String), range: TextRange) {
+        if self
+            .violations
+            .iter()
+            .any(|v| v.rule == rule && v.range == range)
+        {
+            return;
+        }
+        self.violations.push(Violation {
+            rule,
+            message,
+            range,
+        });
+    }
 }
 
 impl<'a> Visitor for LinterVisitor<'a> {
     /// Visits statements to check for violations
     fn visit_stmt(&mut self, node: Stmt<TextRange>) {
-        if let Some(violation) = node
-            .as_import_stmt()
-            .and_then(|stmt| check_import(&Stmt::Import(stmt.clone()), self.config))
+        if let Some(stmt) = node.as_import_stmt()
+            && let Some(violation) = check_import(&Stmt::Import(stmt.clone()), self.config)
         {
-            info!("Detected import violation: {}", violation);
-            self.violations.push(violation);
+            info!("Detected import violation: {}", violation.1);
+            self.report(violation, stmt.range);
         }
 
-        if let Some(violation) = node
-            .as_import_from_stmt()
-            .and_then(|stmt| check_import(&Stmt::ImportFrom(stmt.clone()), self.config))
+        if let Some(stmt) = node.as_import_from_stmt()
+            && let Some(violation) = check_import(&Stmt::ImportFrom(stmt.clone()), self.config)
         {
-            info!("Detected import-from violation: {}", violation);
-            self.violations.push(violation);
+            info!("Detected import-from violation: {}", violation.1);
+            self.report(violation, stmt.range);
+        }
+
+        let decorators = match &node {
+            Stmt::FunctionDef(def) => def.decorator_list.as_slice(),
+            Stmt::AsyncFunctionDef(def) => def.decorator_list.as_slice(),
+            Stmt::ClassDef(def) => def.decorator_list.as_slice(),
+            _ => &[],
+        };
+        let violations = decorators
+            .iter()
+            .filter_map(|decorator| {
+                check_decorator(decorator, self.config)
+                    .map(|violation| (violation, decorator.range()))
+            })
+            .collect::<Vec<_>>();
+        for (violation, range) in violations {
+            info!("Detected decorator violation: {}", violation.1);
+            self.report(violation, range);
         }
 
         self.generic_visit_stmt(node)
@@ -132,12 +290,23 @@ impl<'a> Visitor for LinterVisitor<'a> {
 
     /// Visits expressions to check for violations
     fn visit_expr(&mut self, node: Expr<TextRange>) {
-        if let Some(violation) = node
-            .as_call_expr()
-            .and_then(|expr| check_call(&Expr::Call(expr.clone()), self.config))
+        if let Some(expr) = node.as_call_expr()
+            && let Some(violation) = check_call(&Expr::Call(expr.clone()), self.config)
         {
-            info!("Detected function call violation: {}", violation);
-            self.violations.push(violation);
+            info!("Detected function call violation: {}", violation.1);
+            self.report(violation, expr.range);
+        }
+
+        if let Some(expr) = node.as_call_expr() {
+            for violation in check_dynamic_call(&Expr::Call(expr.clone()), self.config) {
+                info!("Detected dynamic code violation: {}", violation.1);
+                self.report(violation, expr.range);
+            }
+        }
+
+        if let Some(violation) = check_attribute(&node, self.config) {
+            info!("Detected attribute access violation: {}", violation.1);
+            self.report(violation, node.range());
         }
 
         self.generic_visit_expr(node)
@@ -173,23 +342,29 @@ fn check_in_mode<T: ToString + AsRef<str>>(value: &T, mode: &CheckMode) -> Optio
 }
 
 /// Checks import statements against configured rules
-fn check_import(stmt: &Stmt, config: &LinterConfig) -> Option<String> {
+fn check_import(stmt: &Stmt, config: &LinterConfig) -> Option<(Rule, String)> {
     match stmt {
         Stmt::Import(a) => a.names.iter().find_map(|alias| {
-            check_in_mode(&alias.name, &config.module_mode)
-                .map(|m| format!("Forbidden import module: {}", m))
+            check_in_mode(&alias.name, &config.module_mode).map(|m| {
+                (
+                    Rule::ForbiddenModule,
+                    format!("Forbidden import module: {}", m),
+                )
+            })
         }),
         Stmt::ImportFrom(a) => {
             if let Some(module_str) = &a.module
-                && let Some(msg) = check_in_mode(module_str, &config.module_mode)
-                    .map(|m| format!("Forbidden import module: {}", m))
+                && let Some(m) = check_in_mode(module_str, &config.module_mode)
             {
-                return Some(msg);
+                return Some((
+                    Rule::ForbiddenModule,
+                    format!("Forbidden import module: {}", m),
+                ));
             }
 
             a.names.iter().find_map(|alias| {
                 check_in_mode(&alias.name, &config.import_mode)
-                    .map(|n| format!("Forbidden import: {}", n))
+                    .map(|n| (Rule::ForbiddenImport, format!("Forbidden import: {}", n)))
             })
         }
         _ => None,
@@ -197,49 +372,428 @@ fn check_import(stmt: &Stmt, config: &LinterConfig) -> Option<String> {
 }
 
 /// Checks function calls against configured rules
-fn check_call(expr: &Expr, config: &LinterConfig) -> Option<String> {
+fn check_call(expr: &Expr, config: &LinterConfig) -> Option<(Rule, String)> {
     if let Expr::Call(call) = expr
         && let Some(name) = call.func.clone().name_expr()
     {
         let call_name = name.id.as_str();
-        return check_in_mode(&call_name, &config.call_mode)
-            .map(|_| format!("Forbidden function call: {}()", call_name));
+        return check_in_mode(&call_name, &config.call_mode).map(|_| {
+            (
+                Rule::ForbiddenCall,
+                format!("Forbidden function call: {}()", call_name),
+            )
+        });
     }
     None
 }
 
-/// Main function: analyzes source code and collects violations
+/// Checks a decorator naming a function directly, e.g. `@eval`, against the call rules
+///
+/// Decorators written as calls are covered by the regular call checks.
+fn check_decorator(expr: &Expr, config: &LinterConfig) -> Option<(Rule, String)> {
+    let Expr::Name(name) = expr else {
+        return None;
+    };
+    let id = name.id.as_str();
+    check_in_mode(&id, &config.call_mode).map(|_| {
+        (
+            Rule::ForbiddenDecorator,
+            format!("Forbidden decorator: @{}", id),
+        )
+    })
+}
+
+/// Checks calls that run code or import modules given as strings
+///
+/// The source passed to `exec`, `eval` or `compile` as a literal is linted with the same
+/// configuration, and the module passed to `__import__` or `importlib.import_module` as a
+/// literal is checked against the module rules. Non-literal arguments are reported whenever
+/// the corresponding rules are in effect, since they cannot be checked.
+fn check_dynamic_call(expr: &Expr, config: &LinterConfig) -> Vec<(Rule, String)> {
+    let Expr::Call(call) = expr else {
+        return vec![];
+    };
+    let func = match call.func.as_ref() {
+        Expr::Name(name) => name.id.as_str(),
+        Expr::Attribute(attribute) if attribute.attr.as_str() == "import_module" => "import_module",
+        _ => return vec![],
+    };
+    let literal = match call.args.first() {
+        Some(Expr::Constant(ExprConstant {
+            value: Constant::Str(literal),
+            ..
+        })) => Some(literal.as_str()),
+        Some(_) => None,
+        None => return vec![],
+    };
+
+    if CODE_BUILTINS.contains(&func) {
+        match literal {
+            Some(code) => lint(code, config)
+                .into_iter()
+                .filter(|v| v.rule != Rule::SyntaxError)
+                .map(|v| {
+                    (
+                        v.rule,
+                        format!("{} (in code passed to {}())", v.message, func),
+                    )
+                })
+                .collect(),
+            None if config.call_mode != CheckMode::Disabled => vec![(
+                Rule::DynamicCode,
+                format!(
+                    "Forbidden dynamic code execution: {}() with a non-literal source",
+                    func
+                ),
+            )],
+            None => vec![],
+        }
+    } else if func == "__import__" || func == "import_module" {
+        match literal {
+            Some(module) => check_in_mode(&module, &config.module_mode)
+                .map(|m| {
+                    (
+                        Rule::ForbiddenModule,
+                        format!("Forbidden import module: {} (via {}())", m, func),
+                    )
+                })
+                .into_iter()
+                .collect(),
+            None if config.module_mode != CheckMode::Disabled => vec![(
+                Rule::DynamicImport,
+                format!(
+                    "Forbidden dynamic import: {}() with a non-literal name",
+                    func
+                ),
+            )],
+            None => vec![],
+        }
+    } else {
+        vec![]
+    }
+}
+
+/// Checks an attribute name against the dangerous attributes and the configured rules
+fn check_attribute_name(name: &str, config: &LinterConfig) -> Option<(Rule, String)> {
+    if !config.permit_dangerous_attributes && DANGEROUS_ATTRIBUTES.contains(&name) {
+        warn!("Dangerous attribute access detected: {}", name);
+        return Some((Rule::DangerousAttribute, name.to_string()));
+    }
+    if config.attribute_mode == CheckMode::Disabled {
+        return None;
+    }
+    check_in_mode(&name, &config.attribute_mode).map(|a| (Rule::ForbiddenAttribute, a))
+}
+
+/// Checks attribute accesses, dunder names and `getattr`-style builtins against configured rules
+///
+/// `getattr(obj, name)` with a non-literal name is reported whenever attribute rules are in
+/// effect, since the accessed attribute cannot be checked.
+fn check_attribute(expr: &Expr, config: &LinterConfig) -> Option<(Rule, String)> {
+    match expr {
+        Expr::Attribute(attribute) => check_attribute_name(attribute.attr.as_str(), config)
+            .map(|(rule, a)| (rule, format!("Forbidden attribute access: .{}", a))),
+        Expr::Name(name) => {
+            let id = name.id.as_str();
+            (!config.permit_dangerous_attributes && DANGEROUS_ATTRIBUTES.contains(&id))
+                .then(|| (Rule::DangerousAttribute, format!("Forbidden name: {}", id)))
+        }
+        Expr::Call(call) => {
+            let Expr::Name(func) = call.func.as_ref() else {
+                return None;
+            };
+            let func = func.id.as_str();
+            if !ATTRIBUTE_BUILTINS.contains(&func) {
+                return None;
+            }
+            match call.args.get(1) {
+                Some(Expr::Constant(ExprConstant {
+                    value: Constant::Str(attr),
+                    ..
+                })) => check_attribute_name(attr, config).map(|(rule, a)| {
+                    (
+                        rule,
+                        format!("Forbidden attribute access: {}(..., \"{}\")", func, a),
+                    )
+                }),
+                Some(_)
+                    if !config.permit_dangerous_attributes
+                        || config.attribute_mode != CheckMode::Disabled =>
+                {
+                    Some((
+                        Rule::DynamicAttribute,
+                        format!(
+                            "Forbidden dynamic attribute access: {}() with a non-literal name",
+                            func
+                        ),
+                    ))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Analyzes source code and collects the located violations
+///
+/// A source that cannot be parsed yields a single [`Rule::SyntaxError`] violation.
+pub fn lint<S: AsRef<str>>(source: S, config: &LinterConfig) -> Vec<Violation> {
+    info!("Starting code analysis with linting rules");
+
+    let module = match parse(source.as_ref(), Mode::Module, "<string>") {
+        Ok(module) => module,
+        Err(err) => {
+            error!("Parsing failed: {}", err);
+            return vec![Violation {
+                rule: Rule::SyntaxError,
+                message: err.to_string(),
+                range: TextRange::empty(err.offset),
+            }];
+        }
+    };
+
+    let Some(module) = module.as_module() else {
+        error!("No module structure found in source code");
+        return vec![Violation {
+            rule: Rule::SyntaxError,
+            message: "No module found".to_string(),
+            range: TextRange::default(),
+        }];
+    };
+
+    let mut vis = LinterVisitor::with(config);
+    module.body.iter().for_each(|stmt| {
+        vis.visit_stmt(stmt.clone());
+        if let Some(expr) = stmt.as_expr_stmt() {
+            vis.visit_expr(expr.value.as_ref().clone())
+        }
+    });
+
+    info!(
+        "Analysis completed. Found {} violations",
+        vis.violations.len()
+    );
+    vis.violations
+}
+
+/// Main function: analyzes source code and collects violation messages
 pub fn gather_violations<S: AsRef<str>>(
     source: S,
     config: LinterConfig,
 ) -> Result<Vec<String>, String> {
-    info!("Starting code analysis with linting rules");
+    let violations = lint(source, &config);
+    if let Some(syntax_error) = violations.iter().find(|v| v.rule == Rule::SyntaxError) {
+        return Err(syntax_error.message.clone());
+    }
+    Ok(violations.into_iter().map(|v| v.message).collect())
+}
+
+/// How the rewrite pass neutralizes statements containing fixable violations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FixMode {
+    /// Removes the statements
+    Strip,
+    /// Turns the statements into comments
+    #[default]
+    Comment,
+}
 
-    let module = parse(source.as_ref(), Mode::Module, "<string>").map_err(|err| {
-        error!("Parsing failed: {}", err);
-        err.to_string()
-    })?;
+impl FromStr for FixMode {
+    type Err = String;
 
-    let mut vis = LinterVisitor::with(&config);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strip" => Ok(Self::Strip),
+            "comment" => Ok(Self::Comment),
+            _ => Err(format!(
+                "Unknown fix mode `{s}`, expected one of `strip`, `comment`"
+            )),
+        }
+    }
+}
 
-    module
-        .as_module()
-        .ok_or_else(|| {
-            error!("No module structure found in source code");
-            "No module found".to_string()
-        })?
-        .body
-        .iter()
-        .for_each(|stmt| {
-            vis.visit_stmt(stmt.clone());
-            if let Some(expr) = stmt.as_expr_stmt() {
-                vis.visit_expr(expr.value.as_ref().clone())
+/// A source rewritten by [`fix`]
+#[derive(Debug, Clone)]
+pub struct Fixed {
+    /// The sanitized source
+    pub source: String,
+    /// The violations neutralized by the rewrite, located in the original source
+    pub fixed: Vec<Violation>,
+}
+
+/// Returns the blocks nested in a compound statement that can be rewritten on their own
+fn child_blocks<'a>(source: &str, stmt: &'a Stmt) -> Vec<&'a [Stmt]> {
+    match stmt {
+        Stmt::FunctionDef(s) => vec![s.body.as_slice()],
+        Stmt::AsyncFunctionDef(s) => vec![s.body.as_slice()],
+        Stmt::ClassDef(s) => vec![s.body.as_slice()],
+        Stmt::For(s) => vec![s.body.as_slice(), s.orelse.as_slice()],
+        Stmt::AsyncFor(s) => vec![s.body.as_slice(), s.orelse.as_slice()],
+        Stmt::While(s) => vec![s.body.as_slice(), s.orelse.as_slice()],
+        Stmt::If(s) => {
+            // An `elif` branch is a nested `If` starting at the `elif` keyword, replacing it
+            // would leave a dangling branch, so it is only rewritten along with its parent.
+            let elif = s
+                .orelse
+                .first()
+                .is_some_and(|first| source[usize::from(first.start())..].starts_with("elif"));
+            if elif {
+                vec![s.body.as_slice()]
+            } else {
+                vec![s.body.as_slice(), s.orelse.as_slice()]
             }
+        }
+        Stmt::With(s) => vec![s.body.as_slice()],
+        Stmt::AsyncWith(s) => vec![s.body.as_slice()],
+        Stmt::Match(s) => s.cases.iter().map(|case| case.body.as_slice()).collect(),
+        Stmt::Try(s) => std::iter::once(s.body.as_slice())
+            .chain(
+                s.handlers
+                    .iter()
+                    .map(|ExceptHandler::ExceptHandler(h)| h.body.as_slice()),
+            )
+            .chain([s.orelse.as_slice(), s.finalbody.as_slice()])
+            .collect(),
+        Stmt::TryStar(s) => std::iter::once(s.body.as_slice())
+            .chain(
+                s.handlers
+                    .iter()
+                    .map(|ExceptHandler::ExceptHandler(h)| h.body.as_slice()),
+            )
+            .chain([s.orelse.as_slice(), s.finalbody.as_slice()])
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Collects the statements of a block to rewrite, descending into compound statements whose
+/// violations all lie in their nested blocks
+///
+/// Returns the rewritten statements with whether each must be replaced by `pass` to keep its
+/// block non-empty.
+fn plan_block(
+    source: &str,
+    block: &[Stmt],
+    violations: &[TextRange],
+    needs_body: bool,
+) -> Vec<(TextRange, bool)> {
+    let mut nested = vec![];
+    let mut rewritten = vec![];
+    for stmt in block {
+        let inside = violations
+            .iter()
+            .filter(|v| stmt.range().contains_range(**v))
+            .collect::<Vec<_>>();
+        if inside.is_empty() {
+            continue;
+        }
+        let children = child_blocks(source, stmt);
+        let in_children = inside.iter().all(|v| {
+            children
+                .iter()
+                .flat_map(|child| child.iter())
+                .any(|s| s.range().contains_range(**v))
         });
+        if !children.is_empty() && in_children {
+            children
+                .into_iter()
+                .for_each(|child| nested.extend(plan_block(source, child, violations, true)));
+        } else {
+            rewritten.push(stmt.range());
+        }
+    }
 
-    info!(
-        "Analysis completed. Found {} violations",
-        vis.violations.len()
-    );
-    Ok(vis.violations)
+    let emptied = needs_body && rewritten.len() == block.len();
+    let last = rewritten.len().saturating_sub(1);
+    rewritten
+        .into_iter()
+        .enumerate()
+        .map(|(i, range)| (range, emptied && i == last))
+        .chain(nested)
+        .collect()
+}
+
+/// Builds the edit neutralizing a statement, as the replaced range and its replacement
+///
+/// A statement sharing its lines with other code is replaced by `pass` in both modes.
+fn neutralize(
+    source: &str,
+    range: TextRange,
+    mode: FixMode,
+    keep_pass: bool,
+) -> (usize, usize, String) {
+    let (start, end) = (usize::from(range.start()), usize::from(range.end()));
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+    let indent = &source[line_start..start];
+    let trailing = source[end..line_end].trim();
+    if !indent.trim().is_empty() || !(trailing.is_empty() || trailing.starts_with('#')) {
+        return (start, end, "pass".to_string());
+    }
+
+    match mode {
+        FixMode::Strip if keep_pass => (line_start, line_end, format!("{indent}pass")),
+        // Also removes the line break so no blank line is left behind.
+        FixMode::Strip => (line_start, (line_end + 1).min(source.len()), String::new()),
+        FixMode::Comment => {
+            let mut commented = source[line_start..line_end]
+                .lines()
+                .map(|line| {
+                    let code = line.strip_prefix(indent).unwrap_or(line.trim_start());
+                    format!("{indent}# {code}").trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if keep_pass {
+                commented.push_str(&format!("\n{indent}pass"));
+            }
+            (line_start, line_end, commented)
+        }
+    }
+}
+
+/// Rewrites a source so that it passes the forbidden import and call checks
+///
+/// Every statement containing a [fixable](Rule::is_fixable) violation is stripped or commented
+/// out, blocks left empty get a `pass`. Other violations are left in place, so the result
+/// should be linted again before running it.
+pub fn fix<S: AsRef<str>>(
+    source: S,
+    config: &LinterConfig,
+    mode: FixMode,
+) -> Result<Fixed, String> {
+    let source = source.as_ref();
+    let module = parse(source, Mode::Module, "<string>").map_err(|e| e.to_string())?;
+    let module = module
+        .as_module()
+        .ok_or_else(|| "No module found".to_string())?;
+
+    let violations = lint(source, config)
+        .into_iter()
+        .filter(|v| v.rule.is_fixable())
+        .collect::<Vec<_>>();
+    let ranges = violations.iter().map(|v| v.range).collect::<Vec<_>>();
+    let mut rewritten = plan_block(source, &module.body, &ranges, false);
+    rewritten.sort_by_key(|(range, _)| range.start());
+
+    let mut fixed_source = source.to_string();
+    for (range, keep_pass) in rewritten.iter().rev() {
+        let (start, end, replacement) = neutralize(source, *range, mode, *keep_pass);
+        fixed_source.replace_range(start..end, &replacement);
+    }
+    info!("Rewrote {} statement(s)", rewritten.len());
+
+    let fixed = violations
+        .into_iter()
+        .filter(|v| {
+            rewritten
+                .iter()
+                .any(|(range, _)| range.contains_range(v.range))
+        })
+        .collect();
+    Ok(Fixed {
+        source: fixed_source,
+        fixed,
+    })
 }
@@ -1,30 +1,193 @@
 use error_mapping::AsPyErr;
-use mcp_manager::{MCPConfig, MCPManager as MCPManagerInner, ServiceConfig};
+use fabricatio_logger::warn;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use mcp_manager::{
+    CallEvent, ConfirmationCallback, MCPConfig, MCPManager as MCPManagerInner, RequestCallback,
+    ServiceConfig,
+};
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use pyo3::{Bound, PyResult, Python};
-use pyo3_async_runtimes::tokio::future_into_py;
+use pyo3_async_runtimes::tokio::{future_into_py, get_current_locals};
+use pyo3_async_runtimes::into_future_with_locals;
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
 use pythonize::{depythonize, pythonize};
 use rmcp::model::{CallToolResult, Tool};
 use serde_json::Value;
-use signify::{schema_to_docstring_args, schema_to_signature};
-use std::collections::HashMap;
+use signify::{schema_to_docstring_args, schema_to_parameter_names, schema_to_signature};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::sync::mpsc::unbounded_channel;
+
+/// Python keywords that cannot be used as function names in generated stubs.
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Extracts the text content of a tool call result.
+pub(crate) fn result_texts(result: CallToolResult) -> Vec<String> {
+    result
+        .content
+        .into_iter()
+        .map(|v| v.as_text().map(|t| t.text.clone()).unwrap_or_default())
+        .collect()
+}
+
+/// Wraps a Python callable into a request callback.
+///
+/// The callable receives the client ID and the request parameters as a dict, and may return
+/// the result dict directly or a coroutine resolving to it. Coroutines run on the event loop
+/// that is running when this function is called.
+fn request_callback_of(python: Python, callback: Py<PyAny>) -> PyResult<RequestCallback> {
+    let locals = get_current_locals(python)?;
+    let callback = Arc::new(callback);
+    Ok(Arc::new(move |client_id: String, params: Value| {
+        let callback = callback.clone();
+        let locals = locals.clone();
+        async move {
+            let pending = Python::attach(|python| {
+                let params = pythonize(python, &params).into_pyresult()?;
+                let result = callback.bind(python).call1((client_id, params))?;
+                if result.hasattr("__await__")? {
+                    Ok(into_future_with_locals(&locals, result)?.boxed())
+                } else {
+                    let result = result.unbind();
+                    Ok(async move { Ok(result) }.boxed())
+                }
+            })
+            .map_err(|e: PyErr| e.to_string())?;
+
+            let result = pending.await.map_err(|e| e.to_string())?;
+            Python::attach(|python| depythonize::<Value>(result.bind(python)))
+                .map_err(|e| e.to_string())
+        }
+        .boxed() as BoxFuture<'static, Result<Value, String>>
+    }))
+}
+
+/// Turns a tool name into a valid Python identifier that is not in `taken`.
+fn python_identifier(name: &str, taken: &HashSet<String>) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if PYTHON_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    let base = ident.clone();
+    let mut suffix = 2;
+    while taken.contains(&ident) {
+        ident = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+    ident
+}
+
+/// Renders a string as a Python string literal.
+fn python_string_literal(s: &str) -> String {
+    // JSON string escapes are valid Python string escapes.
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Renders the source of a Python module wrapping every tool of a client in an async function.
+fn stub_module_source(client_id: &str, tools: Vec<Tool>) -> PyResult<String> {
+    let mut taken = HashSet::new();
+    let mut functions = Vec::new();
+    for tool in tools {
+        let name = python_identifier(&tool.name, &taken);
+        taken.insert(name.clone());
+
+        let schema = serde_json::to_value(tool.input_schema.clone()).into_pyresult()?;
+        let signature = schema_to_signature(&schema).ok_or_else(|| {
+            PyRuntimeError::new_err(format!("Invalid input schema of tool `{}`", tool.name))
+        })?;
+        let arguments = schema_to_parameter_names(&schema)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(py_name, schema_name)| {
+                format!("{}: {}", python_string_literal(&schema_name), py_name)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let docstring = ToolMetaData::from(tool.clone())
+            .function_docstring()?
+            .replace('\\', "\\\\")
+            .replace("\"\"\"", "\\\"\\\"\\\"")
+            .lines()
+            .map(|line| format!("    {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        functions.push(format!(
+            "async def {name}{signature} -> list[str]:\n    \"\"\"\n{docstring}\n    \"\"\"\n    return await _call({}, {{{arguments}}})\n",
+            python_string_literal(&tool.name)
+        ));
+    }
+
+    let mut exports = taken
+        .iter()
+        .map(|name| format!("    {},\n", python_string_literal(name)))
+        .collect::<Vec<_>>();
+    exports.sort();
+
+    Ok(format!(
+        r#""""Typed wrappers for the tools of the MCP server `{client_id}`.
+
+Generated by `MCPManager.generate_stub_module`, regenerate it instead of editing by hand.
+"""
+
+from __future__ import annotations
+
+from typing import Any, Dict, List, Optional
+
+from fabricatio_tool.mcp import get_global_mcp_manager
+
+__all__ = [
+{}]
+
+_CLIENT_ID = {}
+
+
+async def _call(tool_name: str, arguments: Dict[str, Any]) -> List[str]:
+    manager = await get_global_mcp_manager()
+    return await manager.call_tool(_CLIENT_ID, tool_name, {{k: v for k, v in arguments.items() if v is not None}})
+
+
+{}"#,
+        exports.concat(),
+        python_string_literal(client_id),
+        functions.join("\n\n")
+    ))
+}
 
 /// Python-exposed MCP manager
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
 #[pyclass]
-struct MCPManager {
-    inner: Arc<MCPManagerInner>,
+pub(crate) struct MCPManager {
+    pub(crate) inner: Arc<MCPManagerInner>,
 }
 
 /// Python representation of tool metadata
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
 #[pyclass]
-struct ToolMetaData {
+pub(crate) struct ToolMetaData {
     inner: Tool,
 }
 
@@ -178,6 +341,72 @@ impl MCPManager {
         })
     }
 
+    /// Creates a new MCP manager from an `mcp.json` file.
+    ///
+    /// The file uses the common `mcpServers` layout, entries may define `command`, `args`,
+    /// `env`, `url`, `headers` and `type`. Entries marked `"disabled": true` are skipped.
+    ///
+    /// Args:
+    ///     path: Path to the `mcp.json` file.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to a new MCPManager instance.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the file cannot be read or parsed.
+    #[staticmethod]
+    fn from_file(python: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+        let conf = MCPConfig::from_file(path).into_pyresult()?;
+        future_into_py(python, async move {
+            Ok(Self {
+                inner: Arc::new(MCPManagerInner::create(conf).await),
+            })
+        })
+    }
+
+    /// Loads the server configurations of an `mcp.json` file.
+    ///
+    /// Args:
+    ///     path: Path to the `mcp.json` file.
+    ///
+    /// Returns:
+    ///     A dict mapping server names to service configurations, suitable for `create`.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the file cannot be read or parsed.
+    #[staticmethod]
+    fn load_config_file(python: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+        let conf = MCPConfig::from_file(path).into_pyresult()?;
+        pythonize(python, &conf.servers).into_pyresult()
+    }
+
+    /// Shuts down all servers and terminates their child processes.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves once all servers are shut down.
+    fn close<'a>(&self, python: Python<'a>) -> PyResult<Bound<'a, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(python, async move {
+            inner.shutdown().await;
+            Ok(())
+        })
+    }
+
+    fn __aenter__<'a>(slf: Py<Self>, python: Python<'a>) -> PyResult<Bound<'a, PyAny>> {
+        future_into_py(python, async move { Ok(slf) })
+    }
+
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __aexit__<'a>(
+        &self,
+        python: Python<'a>,
+        _exc_type: Option<Bound<'a, PyAny>>,
+        _exc_value: Option<Bound<'a, PyAny>>,
+        _traceback: Option<Bound<'a, PyAny>>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        self.close(python)
+    }
+
     /// Retrieves list of tools from a client.
     ///
     /// Args:
@@ -297,6 +526,92 @@ impl MCPManager {
         self.inner.server_count()
     }
 
+    /// Returns the servers that failed to start, mapped to the reason of the failure.
+    ///
+    /// Returns:
+    ///     A dict mapping server names to error messages.
+    fn failed_servers(&self) -> HashMap<String, String> {
+        self.inner.failed_servers().clone()
+    }
+
+    /// Sets the callback consulted before a destructive tool is invoked.
+    ///
+    /// The callback receives the client ID, the tool name and the arguments dict (or None),
+    /// and must return True to let the call proceed. Exceptions raised by the callback
+    /// reject the call. Tools without annotations count as destructive, and while a callback
+    /// is set, calls to tools missing from the tool list of their server are refused.
+    ///
+    /// Args:
+    ///     callback: The confirmation callable, or None to disable confirmation.
+    #[pyo3(signature = (callback = None))]
+    fn set_confirmation_callback(&self, callback: Option<Py<PyAny>>) {
+        self.inner.set_confirmation_callback(callback.map(|callback| {
+            Arc::new(
+                move |client_id: &str,
+                      tool_name: &str,
+                      arguments: Option<&serde_json::Map<String, Value>>| {
+                    Python::attach(|python| {
+                        let arguments = arguments.map(|a| pythonize(python, a)).transpose()?;
+                        callback
+                            .call1(python, (client_id, tool_name, arguments))?
+                            .extract::<bool>(python)
+                    })
+                    .unwrap_or_else(|e| {
+                        warn!("Confirmation callback failed for tool `{tool_name}`: {e}");
+                        false
+                    })
+                },
+            ) as ConfirmationCallback
+        }));
+    }
+
+    /// Sets the callback answering sampling requests sent by servers.
+    ///
+    /// The callback receives the client ID and the `sampling/createMessage` parameters as a
+    /// dict, and returns (or resolves to) a `CreateMessageResult` dict with `model`, `role`,
+    /// `content` and optionally `stopReason`. Async callbacks run on the current event loop.
+    ///
+    /// Args:
+    ///     callback: The sampling callable, or None to report sampling as unsupported.
+    ///
+    /// Raises:
+    ///     RuntimeError: If a callback is given while no event loop is running.
+    #[pyo3(signature = (callback = None))]
+    fn set_sampling_callback(&self, python: Python, callback: Option<Py<PyAny>>) -> PyResult<()> {
+        self.inner.set_sampling_callback(
+            callback
+                .map(|callback| request_callback_of(python, callback))
+                .transpose()?,
+        );
+        Ok(())
+    }
+
+    /// Sets the callback answering elicitation requests sent by servers.
+    ///
+    /// The callback receives the client ID and the `elicitation/create` parameters as a dict,
+    /// and returns (or resolves to) a dict with an `action` of `accept`, `decline` or `cancel`
+    /// and, when accepting, the `content` conforming to the requested schema. Async callbacks
+    /// run on the current event loop.
+    ///
+    /// Args:
+    ///     callback: The elicitation callable, or None to decline all elicitation requests.
+    ///
+    /// Raises:
+    ///     RuntimeError: If a callback is given while no event loop is running.
+    #[pyo3(signature = (callback = None))]
+    fn set_elicitation_callback(
+        &self,
+        python: Python,
+        callback: Option<Py<PyAny>>,
+    ) -> PyResult<()> {
+        self.inner.set_elicitation_callback(
+            callback
+                .map(|callback| request_callback_of(python, callback))
+                .transpose()?,
+        );
+        Ok(())
+    }
+
     /// Checks if a client is still connected and responsive.
     ///
     /// Args:
@@ -316,15 +631,33 @@ impl MCPManager {
     ///     client_id: The ID of the client.
     ///     tool_name: The name of the tool to execute.
     ///     arguments: Optional dictionary of tool arguments.
+    ///     on_event: Optional callable receiving what the server reports while the tool runs,
+    ///         as dicts: `{"type": "progress", "progress", "total", "message"}` for progress
+    ///         notifications and `{"type": "log", "level", "logger", "data"}` for log messages,
+    ///         through which servers stream partial output.
+    ///
+    /// Arguments are checked against the input schema of the tool before the call is sent,
+    /// so that mistakes are reported with the path of each offending value rather than by an
+    /// opaque server error.
+    ///
+    /// Results larger than the `max_result_size` of the server are cut, spilled to a file or
+    /// refused, as its `oversize_policy` says, before they are converted to Python objects.
     ///
     /// Returns:
     ///     An awaitable that resolves to a list of result strings.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the arguments do not match the input schema of the tool, listing
+    ///         every violation such as `$.limit: expected integer, got string "ten"`, if the
+    ///         call fails, or if the result is too large under the `error` policy.
+    #[pyo3(signature = (client_id, tool_name, arguments = None, on_event = None))]
     fn call_tool<'a>(
         &self,
         python: Python<'a>,
         client_id: String,
         tool_name: String,
         arguments: Option<Bound<'_, PyDict>>,
+        on_event: Option<Py<PyAny>>,
     ) -> PyResult<Bound<'a, PyAny>> {
         let arguments = if let Some(arguments) = arguments {
             let arguments =
@@ -336,15 +669,82 @@ impl MCPManager {
 
         let inner = self.inner.clone();
         future_into_py(python, async move {
-            let result: CallToolResult = inner
-                .call_tool(client_id.as_str(), tool_name.as_str(), arguments)
-                .await
-                .into_pyresult()?;
-            Ok(result
-                .content
-                .into_iter()
-                .map(|v| v.as_text().map(|t| t.text.clone()).unwrap_or_default())
-                .collect::<Vec<String>>())
+            let Some(on_event) = on_event else {
+                let result: CallToolResult = inner
+                    .call_tool(client_id.as_str(), tool_name.as_str(), arguments)
+                    .await
+                    .into_pyresult()?;
+                return Ok(result_texts(result));
+            };
+
+            let (sender, mut events) = unbounded_channel::<CallEvent>();
+            // Ends once the call is over, as the manager then drops the sender.
+            let forward = async move {
+                while let Some(event) = events.recv().await {
+                    Python::attach(|python| -> PyResult<()> {
+                        let event = pythonize(python, &event).into_pyresult()?;
+                        on_event.bind(python).call1((event,))?;
+                        Ok(())
+                    })
+                    .unwrap_or_else(|e| warn!("Event callback failed for tool `{tool_name}`: {e}"));
+                }
+            };
+            let call = inner.call_tool_streaming(
+                client_id.as_str(),
+                tool_name.as_str(),
+                arguments,
+                sender,
+            );
+            let (result, ()) = futures::join!(call, forward);
+            Ok(result_texts(result.into_pyresult()?))
+        })
+    }
+
+    /// Executes multiple tools concurrently and returns their results in request order.
+    ///
+    /// Args:
+    ///     requests: A list of (client_id, tool_name, arguments) tuples, arguments may be None.
+    ///     concurrency: Maximum number of calls in flight at once.
+    ///     return_exceptions: If True, failed calls yield their exception in place of a result
+    ///         instead of raising the first error.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to a list with one list of result strings per request.
+    #[pyo3(signature = (requests, concurrency = 8, return_exceptions = false))]
+    fn call_tools<'a>(
+        &self,
+        python: Python<'a>,
+        requests: Vec<(String, String, Option<Bound<'a, PyDict>>)>,
+        concurrency: usize,
+        return_exceptions: bool,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let requests = requests
+            .into_iter()
+            .map(|(client_id, tool_name, arguments)| {
+                let arguments = arguments
+                    .map(|a| depythonize::<serde_json::Map<String, Value>>(&a))
+                    .transpose()
+                    .into_pyresult()?;
+                Ok((client_id, tool_name, arguments))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let inner = self.inner.clone();
+        future_into_py(python, async move {
+            let results = inner.call_tools_batch(requests, concurrency).await;
+            Python::attach(|python| {
+                results
+                    .into_iter()
+                    .map(|res| match res.into_pyresult() {
+                        Ok(result) => Ok(result_texts(result)
+                            .into_pyobject(python)?
+                            .into_any()
+                            .unbind()),
+                        Err(e) if return_exceptions => Ok(e.into_value(python).into_any()),
+                        Err(e) => Err(e),
+                    })
+                    .collect::<PyResult<Vec<Py<PyAny>>>>()
+            })
         })
     }
 
@@ -359,6 +759,88 @@ impl MCPManager {
         self.inner.has_client(client_id.as_str())
     }
 
+    /// Writes the tools of every connected server to a JSON catalog.
+    ///
+    /// The catalog keeps the names, descriptions, input schemas and annotations of the tools,
+    /// so that `load_catalog` can list them where the servers are not available, such as in CI.
+    ///
+    /// Args:
+    ///     path: Path of the `.json` file to write, missing parent directories are created.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to the number of tools written.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the tools of a server cannot be listed or the file cannot be written.
+    fn export_catalog<'a>(&self, python: Python<'a>, path: PathBuf) -> PyResult<Bound<'a, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(python, async move {
+            inner.export_catalog(path).await.into_pyresult()
+        })
+    }
+
+    /// Loads a catalog written by `export_catalog`, replacing any loaded before.
+    ///
+    /// The tools of servers that are not connected are then listed from the catalog, by
+    /// `list_tools`, `get_tool`, `has_tool` and `generate_stub_module` alike. Calling them still
+    /// fails as the server is not connected.
+    ///
+    /// Args:
+    ///     path: Path of the catalog file.
+    ///
+    /// Returns:
+    ///     The number of servers in the catalog.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the file cannot be read or parsed.
+    fn load_catalog(&self, path: PathBuf) -> PyResult<usize> {
+        self.inner.load_catalog(path).into_pyresult()
+    }
+
+    /// Returns the servers of the loaded catalog, connected or not.
+    ///
+    /// Returns:
+    ///     A list of server names, sorted.
+    fn catalog_servers(&self) -> Vec<String> {
+        self.inner.catalog_servers()
+    }
+
+    /// Writes a Python module wrapping every tool of a client in a typed async function.
+    ///
+    /// Each wrapper gets the signature and docstring derived from the tool's input schema and
+    /// calls the tool through `fabricatio_tool.mcp.get_global_mcp_manager`, so the module can be
+    /// imported by IDEs and agents. Call again to refresh it after the server's tools changed.
+    ///
+    /// Args:
+    ///     client_id: The ID of the client whose tools are wrapped.
+    ///     path: Path of the `.py` file to write, missing parent directories are created.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to the number of wrapped tools.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the tools cannot be listed or a tool has an invalid input schema.
+    ///     OSError: If the file cannot be written.
+    fn generate_stub_module<'a>(
+        &self,
+        python: Python<'a>,
+        client_id: String,
+        path: PathBuf,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let inner = self.inner.clone();
+
+        future_into_py(python, async move {
+            let tools = inner.list_tools(client_id.as_str()).await.into_pyresult()?;
+            let count = tools.len();
+            let source = stub_module_source(&client_id, tools)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).into_pyresult()?;
+            }
+            fs::write(&path, source).into_pyresult()?;
+            Ok(count)
+        })
+    }
+
     /// Checks if a tool exists for a specific client.
     ///
     /// Args:
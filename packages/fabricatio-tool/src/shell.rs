@@ -0,0 +1,636 @@
+//! Execution of local commands under an allowlist, a working directory and a timeout.
+//!
+//! This is not an OS-level sandbox: a permitted executable can still reach outside of the
+//! root through its arguments. The runner only controls what is started, where it starts
+//! and what environment it sees.
+
+use error_mapping::AsPyErr;
+use fabricatio_logger::{info, warn};
+use mcp_manager::resolve_command;
+use pyo3::exceptions::{PyFileNotFoundError, PyPermissionError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use pyo3_async_runtimes::tokio::future_into_py;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::time::timeout;
+use utils::workspace_path;
+
+/// Environment variables passed through to commands unless configured otherwise.
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+];
+
+/// How long the output of a killed command is still read, for its remaining buffered lines.
+const DRAIN_GRACE: Duration = Duration::from_millis(200);
+
+/// Default number of bytes captured from each output stream of a command.
+const DEFAULT_MAX_OUTPUT: usize = 1024 * 1024;
+
+/// Number of bytes read from an output stream at once.
+const CHUNK_LEN: usize = 8 * 1024;
+
+/// Length in bytes past which a line without line ending is reported to the output callback.
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// Outcome of a command run by a `CommandRunner`.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct CommandResult {
+    /// The command and its arguments.
+    argv: Vec<String>,
+    /// Exit code of the command, None if it timed out or was killed by a signal.
+    exit_code: Option<i32>,
+    /// Captured standard output, decoded lossily as UTF-8.
+    stdout: String,
+    /// Captured standard error, decoded lossily as UTF-8.
+    stderr: String,
+    /// Whether the command was killed for exceeding its timeout.
+    timed_out: bool,
+    /// Whether the standard output or error was cut at the output limit of the runner.
+    truncated: bool,
+    /// Wall-clock time the command ran for, in seconds.
+    duration: f64,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl CommandResult {
+    /// Whether the command exited with code 0 within its timeout.
+    #[getter]
+    fn success(&self) -> bool {
+        self.exit_code == Some(0) && !self.timed_out
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CommandResult(argv={:?}, exit_code={:?}, timed_out={}, duration={:.3})",
+            self.argv, self.exit_code, self.timed_out, self.duration
+        )
+    }
+}
+
+/// The restrictions applied to the commands of a runner.
+#[derive(Debug)]
+struct Sandbox {
+    root: PathBuf,
+    allow: Option<Vec<String>>,
+    deny: Vec<String>,
+    timeout: Option<f64>,
+    max_output: Option<usize>,
+    inherit_env: Vec<String>,
+    env: HashMap<String, String>,
+}
+
+impl Sandbox {
+    /// Resolves the executable of a command, checking it against the allow and deny lists.
+    ///
+    /// A deny entry matches by executable name or by full path, both as given and after
+    /// following symlinks, so a denied tool cannot be reached through a path or an alias.
+    /// An allow entry matches by full path the same way, but by name only when the program
+    /// is given as a bare name resolved through `PATH`, so allowing `pytest` does not allow
+    /// `./pytest` or any other file of that name.
+    fn executable(&self, program: &str) -> PyResult<PathBuf> {
+        let path = resolve_command(program)
+            .map_err(|e| PyFileNotFoundError::new_err(format!("{program}: {e}")))?;
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let full_paths = [
+            path.to_string_lossy().into_owned(),
+            canonical.to_string_lossy().into_owned(),
+        ];
+        let mut names = vec![program.to_string()];
+        names.extend(
+            [&path, &canonical]
+                .into_iter()
+                .filter_map(|p| executable_name(p)),
+        );
+
+        if let Some(denied) = full_paths
+            .iter()
+            .chain(&names)
+            .find(|name| self.deny.contains(name))
+        {
+            return Err(PyPermissionError::new_err(format!(
+                "Command `{program}` is denied (`{denied}`)"
+            )));
+        }
+        if let Some(allow) = &self.allow {
+            let bare = !program.chars().any(std::path::is_separator);
+            let allowed = full_paths
+                .iter()
+                .chain(names.iter().filter(|_| bare))
+                .any(|name| allow.contains(name));
+            if !allowed {
+                return Err(PyPermissionError::new_err(format!(
+                    "Command `{program}` is not in the allowlist"
+                )));
+            }
+        }
+        Ok(path)
+    }
+
+    /// Resolves the working directory of a command, which must lie inside the root.
+    fn working_dir(&self, cwd: Option<PathBuf>) -> PyResult<PathBuf> {
+        let Some(cwd) = cwd else {
+            return Ok(self.root.clone());
+        };
+        let dir = workspace_path::confine(&self.root, &cwd)?;
+        if !dir.is_dir() {
+            return Err(PyFileNotFoundError::new_err(format!(
+                "Working directory {} does not exist",
+                dir.display()
+            )));
+        }
+        Ok(dir)
+    }
+
+    /// Builds the command with a scrubbed environment.
+    fn command(&self, executable: &Path, args: &[String], dir: &Path) -> Command {
+        let mut command = Command::new(executable);
+        command
+            .args(args)
+            .current_dir(dir)
+            .env_clear()
+            .envs(
+                self.inherit_env
+                    .iter()
+                    .filter_map(|name| env::var_os(name).map(|value| (name, value))),
+            )
+            .envs(&self.env)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // The command leads a process group of its own, so a timeout kills what it spawned.
+        #[cfg(unix)]
+        command.process_group(0);
+        command
+    }
+
+    /// Runs a command and captures its output, see `CommandRunner.run`.
+    fn run<'a>(
+        &self,
+        python: Python<'a>,
+        argv: Vec<String>,
+        cwd: Option<PathBuf>,
+        stdin: Option<String>,
+        timeout: Option<f64>,
+        on_output: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let (program, args) = argv
+            .split_first()
+            .ok_or_else(|| PyValueError::new_err("Empty command"))?;
+        let executable = self.executable(program)?;
+        let dir = self.working_dir(cwd)?;
+        let limit = timeout
+            .or(self.timeout)
+            .map(|limit| {
+                Duration::try_from_secs_f64(limit)
+                    .map_err(|_| PyValueError::new_err(format!("Invalid timeout {limit}")))
+            })
+            .transpose()?;
+        let mut command = self.command(&executable, args, &dir);
+        if stdin.is_some() {
+            command.stdin(Stdio::piped());
+        } else {
+            command.stdin(Stdio::null());
+        }
+        let on_output = on_output.map(Arc::new);
+        let max_output = self.max_output;
+
+        future_into_py(python, async move {
+            info!("Running {argv:?} in {}", dir.display());
+            let started = Instant::now();
+            let mut child = command.spawn().into_pyresult()?;
+
+            if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+                tokio::spawn(async move {
+                    // The command may exit without reading its input.
+                    let _ = pipe.write_all(input.as_bytes()).await;
+                });
+            }
+            let stdout = Arc::new(Mutex::new(Capture::default()));
+            let stderr = Arc::new(Mutex::new(Capture::default()));
+            let pumps = [
+                child.stdout.take().map(|reader| {
+                    tokio::spawn(pump(
+                        reader,
+                        "stdout",
+                        stdout.clone(),
+                        max_output,
+                        on_output.clone(),
+                    ))
+                }),
+                child.stderr.take().map(|reader| {
+                    tokio::spawn(pump(
+                        reader,
+                        "stderr",
+                        stderr.clone(),
+                        max_output,
+                        on_output.clone(),
+                    ))
+                }),
+            ];
+
+            let status = match limit {
+                Some(limit) => match timeout(limit, child.wait()).await {
+                    Ok(status) => Some(status.into_pyresult()?),
+                    Err(_) => {
+                        kill_group(&mut child).await.into_pyresult()?;
+                        None
+                    }
+                },
+                None => Some(child.wait().await.into_pyresult()?),
+            };
+            for pump in pumps.into_iter().flatten() {
+                if status.is_some() {
+                    let _ = pump.await;
+                } else {
+                    // Processes spawned by a killed command may keep its pipes open.
+                    let abort = pump.abort_handle();
+                    if timeout(DRAIN_GRACE, pump).await.is_err() {
+                        abort.abort();
+                    }
+                }
+            }
+
+            let duration = started.elapsed().as_secs_f64();
+
+            let take = |buffer: Arc<Mutex<Capture>>| {
+                std::mem::take(
+                    &mut *buffer
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()),
+                )
+            };
+            let (stdout, stderr) = (take(stdout), take(stderr));
+            let exit_code = status.and_then(|status| status.code());
+            info!(
+                "Command {argv:?} finished in {duration:.3}s with exit code {exit_code:?}{}",
+                if status.is_none() { ", timed out" } else { "" }
+            );
+            Ok(CommandResult {
+                argv,
+                exit_code,
+                timed_out: status.is_none(),
+                truncated: stdout.truncated || stderr.truncated,
+                duration,
+                stdout: stdout.into_text(),
+                stderr: stderr.into_text(),
+            })
+        })
+    }
+}
+
+/// Kills a command along with the processes it spawned, which share its process group on
+/// Unix. Elsewhere only the command itself is killed.
+async fn kill_group(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: `kill` only sends a signal, the group is that of the command's own pid.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+    child.kill().await
+}
+
+/// Returns the file name of an executable, without the platform's executable suffix.
+fn executable_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    let name = name.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(&name);
+    Some(name.to_string())
+}
+
+/// Output captured from a child stream, up to a byte limit.
+#[derive(Default)]
+struct Capture {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl Capture {
+    /// Appends as much of the chunk as the limit allows.
+    fn push(&mut self, chunk: &[u8], limit: Option<usize>) {
+        let room = limit.map_or(usize::MAX, |limit| limit.saturating_sub(self.bytes.len()));
+        if chunk.len() > room {
+            self.truncated = true;
+        }
+        self.bytes
+            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+    }
+
+    /// Decodes the output lossily, dropping a character cut by the limit.
+    fn into_text(mut self) -> String {
+        if self.truncated
+            && let Err(e) = std::str::from_utf8(&self.bytes)
+            && e.error_len().is_none()
+        {
+            self.bytes.truncate(e.valid_up_to());
+        }
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+}
+
+/// Reports a line of output to the callback, without its line ending.
+fn report(on_output: &Py<PyAny>, stream: &str, line: &[u8]) {
+    let line = String::from_utf8_lossy(line);
+    if let Err(e) = Python::attach(|python| {
+        on_output.call1(python, (stream, line.trim_end_matches(['\r', '\n'])))
+    }) {
+        warn!("Output callback failed on {stream}: {e}");
+    }
+}
+
+/// Reads a child stream in chunks into a buffer, reporting each line to the callback.
+///
+/// The stream is read to its end even past the output limit, so the command never blocks
+/// on a full pipe, but nothing past the limit is kept. Lines longer than `MAX_LINE_LEN` are
+/// reported in pieces.
+async fn pump<R: AsyncRead + Unpin>(
+    mut reader: R,
+    stream: &'static str,
+    buffer: Arc<Mutex<Capture>>,
+    limit: Option<usize>,
+    on_output: Option<Arc<Py<PyAny>>>,
+) {
+    let mut chunk = vec![0; CHUNK_LEN];
+    let mut line = Vec::new();
+    loop {
+        let read = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(&chunk[..read], limit);
+
+        let Some(callback) = &on_output else {
+            continue;
+        };
+        for piece in chunk[..read].split_inclusive(|byte| *byte == b'\n') {
+            line.extend_from_slice(piece);
+            if line.ends_with(b"\n") || line.len() >= MAX_LINE_LEN {
+                report(callback, stream, &line);
+                line.clear();
+            }
+        }
+    }
+    if let Some(callback) = &on_output
+        && !line.is_empty()
+    {
+        report(callback, stream, &line);
+    }
+}
+
+/// Runs local commands with an allowlist and denylist of executables, a confined working
+/// directory, a timeout, an output limit and a scrubbed environment.
+///
+/// Every invocation is logged with its outcome and duration.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(skip_from_py_object)]
+pub struct CommandRunner {
+    sandbox: Arc<Sandbox>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl CommandRunner {
+    /// Create a new CommandRunner instance.
+    ///
+    /// Args:
+    ///     root: Directory commands run in, and the working directories must lie inside.
+    ///         Defaults to the current directory.
+    ///     allow: Executables that may run, by full path or by name (e.g. `git`), a name only
+    ///         matching commands given without a directory. All executables may run if None.
+    ///     deny: Executables that may never run, by name or full path. Takes precedence
+    ///         over `allow`.
+    ///     timeout: Default timeout of a command in seconds, None for no timeout.
+    ///     max_output: Number of bytes captured from each of the standard output and error
+    ///         of a command, None for no limit. Defaults to 1 MiB.
+    ///     inherit_env: Environment variables passed through from the current process.
+    ///         Defaults to `PATH`, `HOME`, `USER`, the locale, the terminal and the
+    ///         temporary directory variables.
+    ///     env: Additional environment variables set for every command.
+    ///
+    /// Raises:
+    ///     OSError: If the root directory does not exist.
+    #[new]
+    #[pyo3(signature = (root = None, allow = None, deny = vec![], timeout = Some(30.0), max_output = Some(DEFAULT_MAX_OUTPUT), inherit_env = None, env = HashMap::new()))]
+    fn new(
+        root: Option<PathBuf>,
+        allow: Option<Vec<String>>,
+        deny: Vec<String>,
+        timeout: Option<f64>,
+        max_output: Option<usize>,
+        inherit_env: Option<Vec<String>>,
+        env: HashMap<String, String>,
+    ) -> PyResult<Self> {
+        let root = root
+            .unwrap_or_else(|| PathBuf::from("."))
+            .canonicalize()
+            .into_pyresult()?;
+        Ok(Self {
+            sandbox: Arc::new(Sandbox {
+                root,
+                allow,
+                deny,
+                timeout,
+                max_output,
+                inherit_env: inherit_env
+                    .unwrap_or_else(|| INHERITED_ENV.iter().map(|name| name.to_string()).collect()),
+                env,
+            }),
+        })
+    }
+
+    /// The directory commands are confined to.
+    #[getter]
+    fn root(&self) -> PathBuf {
+        self.sandbox.root.clone()
+    }
+
+    /// Runs a command and captures its output.
+    ///
+    /// The executable is resolved through `PATH` and checked against the allow and deny
+    /// lists before anything runs. A command exceeding its timeout is killed, on Unix along
+    /// with the processes it spawned, and its output so far is returned. Output beyond the
+    /// limit of the runner is dropped and the result marked as truncated.
+    ///
+    /// Args:
+    ///     argv: The executable followed by its arguments. No shell is involved.
+    ///     cwd: Working directory, relative to the root. Defaults to the root.
+    ///     stdin: Text written to the standard input of the command.
+    ///     timeout: Timeout in seconds, overriding the default of the runner.
+    ///     on_output: Callable invoked with the stream name (`stdout` or `stderr`) and
+    ///         each line as it is produced, without its line ending.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to a CommandResult.
+    ///
+    /// Raises:
+    ///     ValueError: If `argv` is empty.
+    ///     FileNotFoundError: If the executable or the working directory cannot be found.
+    ///     PermissionError: If the executable is not permitted or the working directory is
+    ///         outside of the root.
+    ///     OSError: If the command cannot be started.
+    #[pyo3(signature = (argv, cwd = None, stdin = None, timeout = None, on_output = None))]
+    fn run<'a>(
+        &self,
+        python: Python<'a>,
+        argv: Vec<String>,
+        cwd: Option<PathBuf>,
+        stdin: Option<String>,
+        timeout: Option<f64>,
+        on_output: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        self.sandbox
+            .run(python, argv, cwd, stdin, timeout, on_output)
+    }
+}
+
+/// Shell operators that `ShellExecutor` refuses, since no shell interprets them.
+const SHELL_OPERATORS: &[&str] = &["|", "||", "&", "&&", ";", "<", ">", ">>", "2>", "2>&1"];
+
+/// Executes the commands agents need, such as test runners and linters, restricted to an
+/// allowlist of executables, with the confinement, timeout and output limit of a
+/// `CommandRunner`.
+///
+/// Command lines are split like a POSIX shell would, but never run by one. Every
+/// invocation is logged with its outcome and duration.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(skip_from_py_object)]
+pub struct ShellExecutor {
+    sandbox: Arc<Sandbox>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl ShellExecutor {
+    /// Create a new ShellExecutor instance.
+    ///
+    /// Args:
+    ///     allow: Executables that may run, by full path or by name (e.g. `pytest`), a name
+    ///         only matching commands given without a directory.
+    ///     root: Directory commands run in, and the working directories must lie inside.
+    ///         Defaults to the current directory.
+    ///     timeout: Default timeout of a command in seconds, None for no timeout.
+    ///     max_output: Number of bytes captured from each of the standard output and error
+    ///         of a command, None for no limit. Defaults to 1 MiB.
+    ///     inherit_env: Environment variables passed through from the current process.
+    ///         Defaults to those of `CommandRunner`.
+    ///     env: Additional environment variables set for every command.
+    ///
+    /// Raises:
+    ///     OSError: If the root directory does not exist.
+    #[new]
+    #[pyo3(signature = (allow, root = None, timeout = Some(30.0), max_output = Some(DEFAULT_MAX_OUTPUT), inherit_env = None, env = HashMap::new()))]
+    fn new(
+        allow: Vec<String>,
+        root: Option<PathBuf>,
+        timeout: Option<f64>,
+        max_output: Option<usize>,
+        inherit_env: Option<Vec<String>>,
+        env: HashMap<String, String>,
+    ) -> PyResult<Self> {
+        let CommandRunner { sandbox } = CommandRunner::new(
+            root,
+            Some(allow),
+            vec![],
+            timeout,
+            max_output,
+            inherit_env,
+            env,
+        )?;
+        Ok(Self { sandbox })
+    }
+
+    /// The directory commands are confined to.
+    #[getter]
+    fn root(&self) -> PathBuf {
+        self.sandbox.root.clone()
+    }
+
+    /// The executables that may run.
+    #[getter]
+    fn allow(&self) -> Vec<String> {
+        self.sandbox.allow.clone().unwrap_or_default()
+    }
+
+    /// Executes a command and captures its output.
+    ///
+    /// Args:
+    ///     command: The command line, split into arguments with POSIX shell quoting rules,
+    ///         or the executable followed by its arguments. Pipes, redirections and command
+    ///         separators are refused.
+    ///     cwd: Working directory, relative to the root. Defaults to the root.
+    ///     stdin: Text written to the standard input of the command.
+    ///     timeout: Timeout in seconds, overriding the default of the executor.
+    ///     on_output: Callable invoked with the stream name (`stdout` or `stderr`) and
+    ///         each line as it is produced, without its line ending.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to a CommandResult.
+    ///
+    /// Raises:
+    ///     ValueError: If the command is empty, has unbalanced quotes or uses a shell operator.
+    ///     FileNotFoundError: If the executable or the working directory cannot be found.
+    ///     PermissionError: If the executable is not allowed or the working directory is
+    ///         outside of the root.
+    ///     OSError: If the command cannot be started.
+    #[pyo3(signature = (command, cwd = None, stdin = None, timeout = None, on_output = None))]
+    fn execute<'a>(
+        &self,
+        python: Python<'a>,
+        command: &Bound<'_, PyAny>,
+        cwd: Option<PathBuf>,
+        stdin: Option<String>,
+        timeout: Option<f64>,
+        on_output: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let argv = if let Ok(line) = command.cast::<PyString>() {
+            let line = line.to_cow()?;
+            shlex::split(&line)
+                .ok_or_else(|| PyValueError::new_err(format!("Unbalanced quotes in {line:?}")))?
+        } else {
+            command.extract::<Vec<String>>()?
+        };
+        if let Some(operator) = argv
+            .iter()
+            .find(|arg| SHELL_OPERATORS.contains(&arg.as_str()))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Shell operator {operator:?} is not supported, commands are not run by a shell"
+            )));
+        }
+        self.sandbox
+            .run(python, argv, cwd, stdin, timeout, on_output)
+    }
+}
+
+/// Registers the command runner and the shell executor with the Python module.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<CommandRunner>()?;
+    m.add_class::<CommandResult>()?;
+    m.add_class::<ShellExecutor>()?;
+    Ok(())
+}
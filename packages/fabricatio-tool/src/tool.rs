@@ -1,13 +1,28 @@
+use crate::line_index::LineIndex;
 use crate::linter;
-use pyo3::exceptions::PyRuntimeError;
+use crate::mcp::{MCPManager, ToolMetaData, result_texts};
+use error_mapping::AsPyErr;
+use mcp_manager::MCPManager as MCPManagerInner;
+use pyo3::exceptions::{PyKeyError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3_async_runtimes::tokio::{future_into_py, into_future};
 #[cfg(feature = "stubgen")]
 use pyo3_stub_gen::derive::*;
-use std::collections::HashSet;
+use pythonize::depythonize;
+use rmcp::model::Tool;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 const WHITELIST: &str = "whitelist";
 const BLACKLIST: &str = "blacklist";
 
+/// Separator between the namespace and the tool name in registry names.
+const NAMESPACE_SEPARATOR: char = '.';
+/// Namespace of locally registered callables if none is given.
+const LOCAL_NAMESPACE: &str = "local";
+
 /// Configuration for checks, specifying the mode and target items.
 #[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
 #[pyclass(from_py_object)]
@@ -35,47 +50,508 @@ impl CheckConfig {
     }
 }
 
+/// Complete linter configuration, bundling the checks applied to a source.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(from_py_object)]
+#[derive(Debug, Clone, Default)]
+struct LintConfig {
+    modules: Option<CheckConfig>,
+    imports: Option<CheckConfig>,
+    calls: Option<CheckConfig>,
+    attributes: Option<CheckConfig>,
+    allow_dangerous_attributes: bool,
+}
+
+impl LintConfig {
+    /// Builds the linter configuration applying the configured checks.
+    fn linter_config(&self) -> linter::LinterConfig {
+        let mut config = linter::LinterConfig::new()
+            .with_dangerous_attributes_permitted(self.allow_dangerous_attributes);
+        if let Some(modules) = self.modules.clone() {
+            if modules.mode == WHITELIST {
+                config = config.with_allowed_modules(modules.targets);
+            } else if modules.mode == BLACKLIST {
+                config = config.with_forbidden_modules(modules.targets);
+            }
+        };
+
+        if let Some(imports) = self.imports.clone() {
+            if imports.mode == WHITELIST {
+                config = config.with_allowed_imports(imports.targets);
+            } else if imports.mode == BLACKLIST {
+                config = config.with_forbidden_imports(imports.targets);
+            }
+        };
+
+        if let Some(calls) = self.calls.clone() {
+            if calls.mode == WHITELIST {
+                config = config.with_allowed_calls(calls.targets);
+            } else if calls.mode == BLACKLIST {
+                config = config.with_forbidden_calls(calls.targets);
+            }
+        };
+
+        if let Some(attributes) = self.attributes.clone() {
+            if attributes.mode == WHITELIST {
+                config = config.with_allowed_attributes(attributes.targets);
+            } else if attributes.mode == BLACKLIST {
+                config = config.with_forbidden_attributes(attributes.targets);
+            }
+        };
+        config
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl LintConfig {
+    /// Create a new LintConfig instance, unset checks are disabled.
+    #[new]
+    #[pyo3(signature = (modules=None, imports=None, calls=None, attributes=None, allow_dangerous_attributes=false))]
+    fn new(
+        modules: Option<CheckConfig>,
+        imports: Option<CheckConfig>,
+        calls: Option<CheckConfig>,
+        attributes: Option<CheckConfig>,
+        allow_dangerous_attributes: bool,
+    ) -> Self {
+        Self {
+            modules,
+            imports,
+            calls,
+            attributes,
+            allow_dangerous_attributes,
+        }
+    }
+}
+
+/// A rule violation found by the linter, located in the source.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+struct LintDiagnostic {
+    /// Identifier of the broken rule, e.g. `forbidden-call` or `dangerous-attribute`.
+    rule: String,
+    /// Human readable description of the violation.
+    message: String,
+    /// Line of the offending code, 1-based.
+    line: usize,
+    /// Column of the offending code within the line, 1-based.
+    column: usize,
+    /// Text of the offending line.
+    snippet: String,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl LintDiagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "{}:{}: [{}] {}",
+            self.line, self.column, self.rule, self.message
+        )
+    }
+}
+
 /// Gathers violations in the provided source code based on the given configuration.
+///
+/// Dangerous dunders and introspection attributes such as `__subclasses__`, `__globals__` or
+/// `f_back` are always reported, through attribute access or `getattr`-style builtins, unless
+/// `allow_dangerous_attributes` is set. Code passed to `exec`, `eval` or `compile` and modules
+/// passed to `__import__` as string literals are checked like regular code and imports, and
+/// decorators naming a forbidden function are reported as well.
 #[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
 #[pyfunction]
-#[pyo3(signature = (source, modules=None, imports=None, calls=None))]
+#[pyo3(signature = (source, modules=None, imports=None, calls=None, attributes=None, allow_dangerous_attributes=false))]
 fn gather_violations(
     source: &str,
     modules: Option<CheckConfig>,
     imports: Option<CheckConfig>,
     calls: Option<CheckConfig>,
+    attributes: Option<CheckConfig>,
+    allow_dangerous_attributes: bool,
 ) -> PyResult<Vec<String>> {
-    let mut config = linter::LinterConfig::new();
-    if let Some(modules) = modules {
-        if modules.mode == WHITELIST {
-            config = config.with_allowed_modules(modules.targets);
-        } else if modules.mode == BLACKLIST {
-            config = config.with_forbidden_modules(modules.targets);
-        }
-    };
+    let config = LintConfig::new(
+        modules,
+        imports,
+        calls,
+        attributes,
+        allow_dangerous_attributes,
+    );
+    linter::gather_violations(source, config.linter_config()).map_err(PyRuntimeError::new_err)
+}
+
+/// Lints the provided source code and returns located diagnostics.
+///
+/// Unlike `gather_violations`, a source that cannot be parsed is reported as a
+/// `syntax-error` diagnostic instead of raising.
+///
+/// Args:
+///     source: The Python source code to lint.
+///     config: The checks to apply. If omitted, only dangerous attributes are checked.
+///
+/// Returns:
+///     The diagnostics in source order.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, config=None))]
+fn lint_source(source: &str, config: Option<LintConfig>) -> Vec<LintDiagnostic> {
+    diagnostics_of(
+        source,
+        linter::lint(source, &config.unwrap_or_default().linter_config()),
+    )
+}
+
+/// Rewrites the provided source code so that it passes the import and call checks.
+///
+/// Statements containing a forbidden import or call are removed or commented out, and
+/// blocks left empty get a `pass`. Attribute violations are not rewritten, so the result
+/// should be linted again before running it.
+///
+/// Args:
+///     source: The Python source code to sanitize.
+///     config: The checks to apply. If omitted, only the hard-coded module blacklist applies.
+///     mode: `comment` to comment the statements out, or `strip` to remove them.
+///
+/// Returns:
+///     A tuple of the sanitized source and the diagnostics it fixed, located in the
+///     original source.
+///
+/// Raises:
+///     ValueError: If the mode is unknown or the source cannot be parsed.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, config=None, mode="comment"))]
+fn sanitize_source(
+    source: &str,
+    config: Option<LintConfig>,
+    mode: &str,
+) -> PyResult<(String, Vec<LintDiagnostic>)> {
+    let mode = mode.parse().map_err(PyValueError::new_err)?;
+    let fixed = linter::fix(source, &config.unwrap_or_default().linter_config(), mode)
+        .map_err(PyValueError::new_err)?;
+    Ok((fixed.source, diagnostics_of(source, fixed.fixed)))
+}
+
+/// Converts violations into diagnostics sorted by their position in the source.
+fn diagnostics_of(source: &str, mut violations: Vec<linter::Violation>) -> Vec<LintDiagnostic> {
+    let lines = LineIndex::new(source);
+    violations.sort_by_key(|v| v.range.start());
+    violations
+        .into_iter()
+        .map(|v| {
+            let (line, column) = lines.position_of(source, usize::from(v.range.start()));
+            LintDiagnostic {
+                rule: v.rule.to_string(),
+                message: v.message,
+                line,
+                column,
+                snippet: lines.line_text(source, line).to_string(),
+            }
+        })
+        .collect()
+}
+
+/// A tool known to a `ToolRegistry`.
+#[derive(Clone)]
+enum RegisteredTool {
+    /// A tool served by an MCP client.
+    Mcp {
+        manager: Arc<MCPManagerInner>,
+        client_id: String,
+        tool: Tool,
+    },
+    /// A Python callable registered locally.
+    Local {
+        callable: Arc<Py<PyAny>>,
+        description: String,
+    },
+}
 
-    if let Some(imports) = imports {
-        if imports.mode == WHITELIST {
-            config = config.with_allowed_imports(imports.targets);
-        } else if imports.mode == BLACKLIST {
-            config = config.with_forbidden_imports(imports.targets);
+impl RegisteredTool {
+    fn description(&self) -> String {
+        match self {
+            Self::Mcp { tool, .. } => tool.description.clone().unwrap_or_default().to_string(),
+            Self::Local { description, .. } => description.clone(),
         }
-    };
+    }
+}
+
+/// A unified registry of MCP tools and local Python callables.
+///
+/// Tools are registered under namespaced names of the form `namespace.tool`, where the
+/// namespace is the MCP client ID for MCP tools, so agent code can look up, search and
+/// invoke any tool through a single object.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(frozen)]
+#[derive(Default)]
+struct ToolRegistry {
+    tools: RwLock<BTreeMap<String, RegisteredTool>>,
+}
+
+impl ToolRegistry {
+    fn tools(&self) -> RwLockReadGuard<'_, BTreeMap<String, RegisteredTool>> {
+        self.tools
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert(&self, name: String, tool: RegisteredTool) {
+        self.tools
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(name, tool);
+    }
+
+    fn get(&self, name: &str) -> PyResult<RegisteredTool> {
+        self.tools()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err(format!("Tool `{name}` is not registered")))
+    }
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl ToolRegistry {
+    /// Creates an empty tool registry.
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers all tools of an MCP client under the `client_id.tool` names.
+    ///
+    /// Tools blocked by the client's policy are not registered.
+    ///
+    /// Args:
+    ///     manager: The MCP manager serving the client.
+    ///     client_id: The ID of the client.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to the list of registered names.
+    fn add_mcp_client<'a>(
+        slf: Py<Self>,
+        python: Python<'a>,
+        manager: PyRef<'a, MCPManager>,
+        client_id: String,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        let manager = manager.inner.clone();
+        future_into_py(python, async move {
+            let tools = manager.list_tools(&client_id).await.into_pyresult()?;
+            let registry = slf.get();
+            Ok(tools
+                .into_iter()
+                .map(|tool| {
+                    let name = format!("{client_id}{NAMESPACE_SEPARATOR}{}", tool.name);
+                    registry.insert(
+                        name.clone(),
+                        RegisteredTool::Mcp {
+                            manager: manager.clone(),
+                            client_id: client_id.clone(),
+                            tool,
+                        },
+                    );
+                    name
+                })
+                .collect::<Vec<_>>())
+        })
+    }
+
+    /// Registers a Python callable under the `namespace.name` name.
+    ///
+    /// Args:
+    ///     name: The name of the tool within its namespace.
+    ///     callable: The function to invoke, sync or async. It receives the call arguments
+    ///         as keyword arguments.
+    ///     description: The description used for search, defaults to the callable's docstring.
+    ///     namespace: The namespace of the tool.
+    ///
+    /// Returns:
+    ///     The registered name.
+    #[pyo3(signature = (name, callable, description = None, namespace = LOCAL_NAMESPACE.to_string()))]
+    fn add_callable(
+        &self,
+        python: Python,
+        name: String,
+        callable: Py<PyAny>,
+        description: Option<String>,
+        namespace: String,
+    ) -> PyResult<String> {
+        let description = match description {
+            Some(description) => description,
+            None => callable
+                .bind(python)
+                .getattr("__doc__")?
+                .extract::<Option<String>>()?
+                .unwrap_or_default(),
+        };
+        let full_name = format!("{namespace}{NAMESPACE_SEPARATOR}{name}");
+        self.insert(
+            full_name.clone(),
+            RegisteredTool::Local {
+                callable: Arc::new(callable),
+                description,
+            },
+        );
+        Ok(full_name)
+    }
+
+    /// Removes a tool from the registry.
+    ///
+    /// Args:
+    ///     name: The namespaced name of the tool.
+    ///
+    /// Returns:
+    ///     True if the tool was registered, False otherwise.
+    fn remove(&self, name: &str) -> bool {
+        self.tools
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(name)
+            .is_some()
+    }
+
+    /// Lists the namespaced names of all registered tools, sorted alphabetically.
+    ///
+    /// Args:
+    ///     namespace: If given, only tools of this namespace are listed.
+    ///
+    /// Returns:
+    ///     A list of tool names.
+    #[pyo3(signature = (namespace = None))]
+    fn names(&self, namespace: Option<&str>) -> Vec<String> {
+        self.tools()
+            .keys()
+            .filter(|name| {
+                namespace.is_none_or(|ns| {
+                    name.split_once(NAMESPACE_SEPARATOR)
+                        .is_some_and(|(prefix, _)| prefix == ns)
+                })
+            })
+            .cloned()
+            .collect()
+    }
 
-    if let Some(calls) = calls {
-        if calls.mode == WHITELIST {
-            config = config.with_allowed_calls(calls.targets);
-        } else if calls.mode == BLACKLIST {
-            config = config.with_forbidden_calls(calls.targets);
+    /// Searches tools whose name or description contains the query, ignoring case.
+    ///
+    /// Args:
+    ///     query: The substring to look for.
+    ///
+    /// Returns:
+    ///     A list of matching tool names, sorted alphabetically.
+    fn search(&self, query: &str) -> Vec<String> {
+        let query = query.to_lowercase();
+        self.tools()
+            .iter()
+            .filter(|(name, tool)| {
+                name.to_lowercase().contains(&query)
+                    || tool.description().to_lowercase().contains(&query)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Returns the description of a tool.
+    ///
+    /// Args:
+    ///     name: The namespaced name of the tool.
+    ///
+    /// Returns:
+    ///     The tool description.
+    ///
+    /// Raises:
+    ///     KeyError: If no tool is registered under the name.
+    fn describe(&self, name: &str) -> PyResult<String> {
+        self.get(name).map(|tool| tool.description())
+    }
+
+    /// Returns the MCP metadata of a tool.
+    ///
+    /// Args:
+    ///     name: The namespaced name of the tool.
+    ///
+    /// Returns:
+    ///     The ToolMetaData of an MCP tool, or None for local callables.
+    ///
+    /// Raises:
+    ///     KeyError: If no tool is registered under the name.
+    fn metadata(&self, name: &str) -> PyResult<Option<ToolMetaData>> {
+        Ok(match self.get(name)? {
+            RegisteredTool::Mcp { tool, .. } => Some(ToolMetaData::from(tool)),
+            RegisteredTool::Local { .. } => None,
+        })
+    }
+
+    /// Invokes a tool by its namespaced name.
+    ///
+    /// MCP tools resolve to the list of their result strings, local callables resolve to
+    /// whatever they return. Async callables are awaited.
+    ///
+    /// Args:
+    ///     name: The namespaced name of the tool.
+    ///     arguments: Optional dictionary of tool arguments.
+    ///
+    /// Returns:
+    ///     An awaitable that resolves to the tool result.
+    ///
+    /// Raises:
+    ///     KeyError: If no tool is registered under the name.
+    #[pyo3(signature = (name, arguments = None))]
+    fn call<'a>(
+        &self,
+        python: Python<'a>,
+        name: &str,
+        arguments: Option<Bound<'a, PyDict>>,
+    ) -> PyResult<Bound<'a, PyAny>> {
+        match self.get(name)? {
+            RegisteredTool::Mcp {
+                manager,
+                client_id,
+                tool,
+            } => {
+                let arguments = arguments
+                    .map(|a| depythonize::<serde_json::Map<String, Value>>(&a))
+                    .transpose()
+                    .into_pyresult()?;
+                future_into_py(python, async move {
+                    let result = manager
+                        .call_tool(&client_id, &tool.name, arguments)
+                        .await
+                        .into_pyresult()?;
+                    Ok(result_texts(result))
+                })
+            }
+            RegisteredTool::Local { callable, .. } => {
+                let result = callable.bind(python).call((), arguments.as_ref())?;
+                if result.hasattr("__await__")? {
+                    let pending = into_future(result)?;
+                    future_into_py(python, pending)
+                } else {
+                    let result = result.unbind();
+                    future_into_py(python, async move { Ok(result) })
+                }
+            }
         }
-    };
+    }
 
-    linter::gather_violations(source, config).map_err(PyRuntimeError::new_err)
+    fn __len__(&self) -> usize {
+        self.tools().len()
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.tools().contains_key(name)
+    }
 }
 
 /// Registers the gather_violations function with the Python module.
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(gather_violations, m)?)?;
+    m.add_function(wrap_pyfunction!(lint_source, m)?)?;
+    m.add_function(wrap_pyfunction!(sanitize_source, m)?)?;
     m.add_class::<CheckConfig>()?;
+    m.add_class::<LintConfig>()?;
+    m.add_class::<LintDiagnostic>()?;
+    m.add_class::<ToolRegistry>()?;
     Ok(())
 }
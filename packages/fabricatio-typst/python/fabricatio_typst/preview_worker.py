@@ -0,0 +1,44 @@
+"""Worker process compiling one snippet for `fabricatio_typst.rust.preview`.
+
+Run as `python -m fabricatio_typst.preview_worker <ppi> <page> <memory_limit> <cpu_limit>` with the snippet on the
+standard input. The address space and the CPU time of the process are capped before compiling where the platform has
+resource limits. The PNG image is written to the standard output, or, if the snippet fails, the type of the error and
+its message on two lines, with exit code 1.
+"""
+
+import sys
+
+
+def _limit(resource_id: int, limit: int) -> None:
+    """Lowers a resource limit of the process, within its hard limit."""
+    import resource
+
+    _, hard = resource.getrlimit(resource_id)
+    if hard != resource.RLIM_INFINITY:
+        limit = min(limit, hard)
+    resource.setrlimit(resource_id, (limit, hard))
+
+
+def main() -> None:
+    """Compiles the snippet read from the standard input under the limits given as arguments."""
+    ppi, page, memory_limit, cpu_limit = float(sys.argv[1]), int(sys.argv[2]), int(sys.argv[3]), int(sys.argv[4])
+    snippet = sys.stdin.buffer.read().decode()
+
+    from fabricatio_typst.rust import _render_preview  # pyright: ignore[reportAttributeAccessIssue]
+
+    if sys.platform != "win32":
+        import resource
+
+        _limit(resource.RLIMIT_AS, memory_limit)
+        _limit(resource.RLIMIT_CPU, cpu_limit)
+
+    try:
+        png = _render_preview(snippet, ppi, page)
+    except (ValueError, RuntimeError) as e:
+        sys.stdout.write(f"{type(e).__name__}\n{e}")
+        sys.exit(1)
+    sys.stdout.buffer.write(png)
+
+
+if __name__ == "__main__":
+    main()
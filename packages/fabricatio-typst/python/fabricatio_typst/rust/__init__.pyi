@@ -1,23 +1,71 @@
 # This file is automatically generated by pyo3_stub_gen
 
 import builtins
+import os
+import pathlib
 import typing
 
 __all__ = [
+    "Acronym",
+    "AcronymIssue",
     "BibManager",
+    "check_acronyms",
+    "collect_acronyms",
     "comment",
     "convert_all_tex_math",
     "extract_body",
     "extract_sections",
+    "figure_snippet",
     "fix_misplaced_labels",
+    "glossary_section",
+    "register_asset",
+    "register_figure",
     "replace_thesis_body",
     "split_out_metadata",
     "strip_comment",
     "tex_to_typst",
     "to_metadata",
     "uncomment",
+    "vendor_packages",
 ]
 
+@typing.final
+class Acronym:
+    r"""An acronym defined in a document."""
+    @property
+    def short(self) -> builtins.str:
+        r"""The acronym, e.g. `LLM`."""
+    @property
+    def long(self) -> builtins.str:
+        r"""The long form it stands for, as written at its first definition."""
+    @property
+    def line(self) -> builtins.int:
+        r"""The 1-based line of its first definition."""
+    @property
+    def uses(self) -> builtins.int:
+        r"""The number of times the acronym is used outside of its definitions, plurals included."""
+    def __repr__(self) -> builtins.str: ...
+
+@typing.final
+class AcronymIssue:
+    r"""A misuse of an acronym."""
+    @property
+    def kind(self) -> builtins.str:
+        r"""One of `used_before_definition` (used before the use expanding it),
+        `not_expanded` (a glossary acronym used but never expanded) or `redefined` (expanded
+        again after its definition).
+        """
+    @property
+    def short(self) -> builtins.str:
+        r"""The acronym concerned."""
+    @property
+    def line(self) -> builtins.int:
+        r"""The 1-based line of the offending use or definition."""
+    @property
+    def message(self) -> builtins.str:
+        r"""A description of the issue, for reports."""
+    def __repr__(self) -> builtins.str: ...
+
 @typing.final
 class BibManager:
     def __new__(cls, path: builtins.str) -> BibManager:
@@ -34,6 +82,35 @@ class BibManager:
     def get_title_by_key(self, key: builtins.str) -> typing.Optional[builtins.str]: ...
     def get_field_by_key(self, key: builtins.str, field: builtins.str) -> typing.Optional[builtins.str]: ...
 
+def check_acronyms(
+    source: builtins.str, glossary: typing.Optional[typing.Mapping[builtins.str, builtins.str]] = None
+) -> builtins.list[AcronymIssue]:
+    r"""Checks that every acronym of a document is expanded at its first use, and only there.
+
+    Args:
+        source: The Typst source of the document.
+        glossary: Acronyms that must be expanded even if the document never defines them,
+            mapped to their long forms, such as those of a shared glossary.
+
+    Returns:
+        The issues found, sorted by line.
+    """
+
+def collect_acronyms(source: builtins.str) -> builtins.list[Acronym]:
+    r"""Collects the acronyms defined in a document, as `long form (ACRONYM)`.
+
+    The long form is matched from the letters of the acronym in the words before the
+    parentheses, so `a large language model (LLM)` defines `LLM` as `large language model`.
+    Parenthesized words whose letters do not match the preceding words are ignored, and a
+    plural such as `(LLMs)` defines the singular acronym.
+
+    Args:
+        source: The Typst source of the document.
+
+    Returns:
+        The acronyms in the order of their first definition.
+    """
+
 def comment(string: builtins.str) -> builtins.str:
     r"""Adds comment prefix `//` to each line of the string.
 
@@ -81,6 +158,30 @@ def extract_sections(
         A list of tuples containing (header_text, section_content).
     """
 
+def figure_snippet(
+    path: builtins.str,
+    caption: typing.Optional[builtins.str] = None,
+    label: typing.Optional[builtins.str] = None,
+    width: typing.Optional[builtins.str] = None,
+) -> builtins.str:
+    r"""Generates a Typst figure snippet for an image.
+
+    Args:
+        path: The image path as it should appear in the document.
+        caption: Optional caption content, inserted as Typst markup with unbalanced
+            brackets escaped.
+        label: Optional label, with or without surrounding angle brackets, made of letters,
+            digits, `-`, `_`, `:` and `.`.
+        width: Optional image width, a number followed by `%`, `pt`, `mm`, `cm`, `in` or
+            `em`, e.g. `80%` or `10cm`.
+
+    Returns:
+        A snippet like `#figure(image("assets/a.png"), caption: [A]) <fig-a>`.
+
+    Raises:
+        ValueError: If the label or the width is invalid.
+    """
+
 def fix_misplaced_labels(string: builtins.str) -> builtins.str:
     r"""Fixes misplaced labels in a string by moving them outside display math blocks.
 
@@ -94,6 +195,118 @@ def fix_misplaced_labels(string: builtins.str) -> builtins.str:
         A string with misplaced labels moved outside display math blocks.
     """
 
+def glossary_section(
+    entries: typing.Mapping[builtins.str, builtins.str],
+    title: builtins.str = "Glossary",
+    level: builtins.int = 1,
+    label: typing.Optional[builtins.str] = None,
+) -> builtins.str:
+    r"""Generates a glossary section listing acronyms and their long forms as a Typst term list.
+
+    Args:
+        entries: The acronyms mapped to their long forms, such as those of
+            `collect_acronyms`.
+        title: The title of the section.
+        level: The level of the heading of the section, 1 for `=`.
+        label: Optional label of the heading, with or without surrounding angle brackets.
+
+    Returns:
+        The section, its terms sorted alphabetically, e.g.
+        `= Glossary\n\n/ LLM: Large Language Model\n`.
+
+    Raises:
+        ValueError: If the level is 0 or the label is empty or contains whitespace.
+    """
+
+def preview(
+    snippet: builtins.str,
+    ppi: builtins.float,
+    page: builtins.int = 1,
+    timeout: builtins.float = 5.0,
+    memory_limit: builtins.int = 1073741824,
+) -> builtins.bytes:
+    r"""Compiles a small Typst snippet in a sandbox and renders one of its pages as PNG.
+
+    The snippet can only use the standard library and the embedded fonts: imports of files
+    or packages fail, and so does `datetime.today()`. Meant for previews of math or figures,
+    e.g. in the template playground of the web UI, rather than for whole documents. Each
+    preview is compiled by a worker process, started with the current Python interpreter,
+    which is killed if it is still compiling after the timeout. The memory of the worker is
+    capped on platforms with resource limits, such as Linux and macOS, but not on Windows.
+
+    Args:
+        snippet: The Typst source to compile, at most 64 KiB.
+        ppi: The resolution of the preview in pixels per inch, between 1 and 1200.
+        page: The 1-based number of the page to render.
+        timeout: The number of seconds to wait for the worker, its startup included.
+        memory_limit: The number of bytes of address space the worker may use, 1 GiB by
+            default.
+
+    Returns:
+        The PNG encoded image of the page.
+
+    Raises:
+        ValueError: If the snippet is too long, the ppi out of range, the timeout invalid, the
+            page missing, or the page would render to more than 16 million pixels.
+        RuntimeError: If the snippet does not compile, with the compiler errors, if the
+            worker crashes, such as by exceeding its memory limit, or if 4 previews are
+            already compiling.
+        TimeoutError: If the compilation takes longer than the timeout.
+    """
+
+def register_asset(
+    source: builtins.str | os.PathLike | pathlib.Path,
+    assets_dir: builtins.str | os.PathLike | pathlib.Path,
+    document_dir: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+) -> builtins.str:
+    r"""Registers an image asset for a document.
+
+    The image is copied into `assets_dir` under a name derived from its BLAKE3 content hash,
+    so registering the same image twice stores it only once.
+
+    Args:
+        source: Path to the image file to register.
+        assets_dir: Directory the asset is copied into, created if missing.
+        document_dir: Directory of the Typst document referencing the asset. If omitted, the
+            current working directory is used.
+
+    Returns:
+        The path of the stored asset relative to `document_dir`, using forward slashes.
+
+    Raises:
+        OSError: If the image cannot be read or the asset cannot be written.
+    """
+
+def register_figure(
+    source: builtins.str | os.PathLike | pathlib.Path,
+    assets_dir: builtins.str | os.PathLike | pathlib.Path,
+    document_dir: typing.Optional[builtins.str | os.PathLike | pathlib.Path] = None,
+    caption: typing.Optional[builtins.str] = None,
+    label: typing.Optional[builtins.str] = None,
+    width: typing.Optional[builtins.str] = None,
+) -> builtins.str:
+    r"""Registers an image asset and generates the Typst figure snippet referencing it.
+
+    Args:
+        source: Path to the image file to register.
+        assets_dir: Directory the asset is copied into, created if missing.
+        document_dir: Directory of the Typst document referencing the asset. If omitted, the
+            current working directory is used.
+        caption: Optional caption content, inserted as Typst markup with unbalanced
+            brackets escaped.
+        label: Optional label, with or without surrounding angle brackets, made of letters,
+            digits, `-`, `_`, `:` and `.`.
+        width: Optional image width, a number followed by `%`, `pt`, `mm`, `cm`, `in` or
+            `em`, e.g. `80%` or `10cm`.
+
+    Returns:
+        The figure snippet with the image path resolved relative to `document_dir`.
+
+    Raises:
+        OSError: If the image cannot be read or the asset cannot be written.
+        ValueError: If the label or the width is invalid.
+    """
+
 def replace_thesis_body(
     string: builtins.str, wrapper: builtins.str, new_body: builtins.str
 ) -> typing.Optional[builtins.str]:
@@ -159,3 +372,36 @@ def uncomment(string: builtins.str) -> builtins.str:
     Returns:
         A string with comment prefixes removed from each line.
     """
+
+def vendor_packages(
+    source: builtins.str,
+    cache_dir: builtins.str | os.PathLike | pathlib.Path,
+    namespace: builtins.str = "vendor",
+    registry: builtins.str = "https://packages.typst.org",
+    proxy: typing.Optional[builtins.str] = None,
+) -> tuple[builtins.str, builtins.list[builtins.str]]:
+    r"""Vendors the `@preview` packages imported by a Typst source for offline compilation.
+
+    Every package imported as `"@preview/name:version"`, and every package those import in
+    turn, is downloaded into `<cache_dir>/<namespace>/<name>/<version>` unless it is there
+    already. The imports of the source and of the vendored packages are rewritten to
+    `"@<namespace>/name:version"`, which Typst resolves locally when compiling with
+    `--package-path <cache_dir>` instead of reaching the package registry.
+
+    Args:
+        source: The Typst source to vendor the packages of.
+        cache_dir: The local package directory to store the packages in.
+        namespace: The namespace imports are rewritten to. Must not be `preview`.
+        registry: Base URL of the package registry to download from.
+        proxy: Proxy URL used for downloads. If omitted, the `HTTPS_PROXY`, `ALL_PROXY` and
+            `NO_PROXY` environment variables are honored.
+
+    Returns:
+        A tuple of the rewritten source and the sorted specs of the vendored packages, as
+        they are named in the registry.
+
+    Raises:
+        ValueError: If the namespace or the proxy URL is invalid.
+        ConnectionError: If a package cannot be downloaded.
+        OSError: If a package cannot be unpacked or rewritten.
+    """
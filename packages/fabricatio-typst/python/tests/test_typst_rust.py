@@ -4,18 +4,36 @@ This module contains pytest test cases verifying the correctness of Rust-impleme
 functions including TeX to Typst conversion, comment handling, and metadata extraction.
 """
 
+import io
+import sys
+import tarfile
+import threading
+from functools import partial
+from http.server import SimpleHTTPRequestHandler, ThreadingHTTPServer
+from pathlib import Path
+from typing import Iterator
+
+import pytest
 from fabricatio_typst.rust import (
+    check_acronyms,
+    collect_acronyms,
     comment,
     convert_all_tex_math,
     extract_body,
     extract_sections,
+    figure_snippet,
     fix_misplaced_labels,
+    glossary_section,
+    preview,
+    register_asset,
+    register_figure,
     replace_thesis_body,
     split_out_metadata,
     strip_comment,
     tex_to_typst,
     to_metadata,
     uncomment,
+    vendor_packages,
 )
 
 
@@ -345,3 +363,222 @@ class TestFixMisplacedLabels:
     def test_empty_string(self) -> None:
         """Test with empty string."""
         assert fix_misplaced_labels("") == ""
+
+
+def _package_archive(path: Path, files: dict[str, str]) -> None:
+    """Write a gzipped package tarball with the given files."""
+    with tarfile.open(path, "w:gz") as archive:
+        for name, content in files.items():
+            data = content.encode()
+            info = tarfile.TarInfo(name)
+            info.size = len(data)
+            archive.addfile(info, io.BytesIO(data))
+
+
+class TestVendorPackages:
+    """Test suite for vendor_packages() function."""
+
+    @pytest.fixture
+    def registry(self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch) -> Iterator[str]:
+        """Serve a registry with a package importing another one."""
+        preview = tmp_path / "registry" / "preview"
+        preview.mkdir(parents=True)
+        _package_archive(
+            preview / "app-1.0.0.tar.gz",
+            {
+                "typst.toml": '[package]\nname = "app"\nversion = "1.0.0"\nentrypoint = "lib.typ"\n',
+                "lib.typ": '#import "@preview/base:0.1.0": helper\n',
+            },
+        )
+        _package_archive(
+            preview / "base-0.1.0.tar.gz",
+            {
+                "typst.toml": '[package]\nname = "base"\nversion = "0.1.0"\nentrypoint = "lib.typ"\n',
+                "lib.typ": "#let helper = 1\n",
+            },
+        )
+        server = ThreadingHTTPServer(
+            ("127.0.0.1", 0), partial(SimpleHTTPRequestHandler, directory=str(tmp_path / "registry"))
+        )
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        monkeypatch.setenv("NO_PROXY", "127.0.0.1")
+        yield f"http://127.0.0.1:{server.server_address[1]}"
+        server.shutdown()
+
+    def test_vendor_transitive(self, registry: str, tmp_path: Path) -> None:
+        """Test that imports are rewritten and dependencies vendored recursively."""
+        cache = tmp_path / "cache"
+        source, packages = vendor_packages('#import "@preview/app:1.0.0": *\n', cache, registry=registry)
+        assert source == '#import "@vendor/app:1.0.0": *\n'
+        assert packages == ["@preview/app:1.0.0", "@preview/base:0.1.0"]
+        assert (cache / "vendor" / "base" / "0.1.0" / "typst.toml").is_file()
+        assert (cache / "vendor" / "app" / "1.0.0" / "lib.typ").read_text() == '#import "@vendor/base:0.1.0": helper\n'
+
+        # Vendored packages are reused without reaching the registry.
+        again = vendor_packages('#import "@preview/app:1.0.0": *\n', cache, registry="http://127.0.0.1:9")
+        assert again == (source, packages)
+
+    def test_missing_package(self, registry: str, tmp_path: Path) -> None:
+        """Test that unknown packages raise a connection error."""
+        with pytest.raises(ConnectionError):
+            vendor_packages('#import "@preview/nope:0.0.1"', tmp_path, registry=registry)
+
+    def test_invalid_namespace(self, tmp_path: Path) -> None:
+        """Test that the preview namespace cannot be used as the target."""
+        with pytest.raises(ValueError):
+            vendor_packages("", tmp_path, namespace="preview")
+
+
+class TestPreview:
+    """Test suite for preview() function."""
+
+    SNIPPET = "#set page(width: 100pt, height: 50pt)\n$ x^2 $\n#pagebreak()\nSecond"
+
+    def test_renders_png(self) -> None:
+        """Test that pages are rendered as PNG at the requested resolution."""
+        png = preview(self.SNIPPET, 72)
+        assert png.startswith(b"\x89PNG")
+        assert int.from_bytes(png[16:20], "big") == 100
+        assert int.from_bytes(preview(self.SNIPPET, 144, page=2)[16:20], "big") == 200
+
+    def test_missing_page(self) -> None:
+        """Test that pages past the end are rejected."""
+        with pytest.raises(ValueError, match="2 pages"):
+            preview(self.SNIPPET, 72, page=3)
+
+    def test_compile_error(self) -> None:
+        """Test that compiler errors are raised."""
+        with pytest.raises(RuntimeError):
+            preview("#undefined-function()", 72)
+
+    def test_file_access_denied(self) -> None:
+        """Test that snippets cannot read files or packages."""
+        with pytest.raises(RuntimeError):
+            preview('#read("/etc/passwd")', 72)
+        with pytest.raises(RuntimeError):
+            preview('#import "@preview/cetz:0.3.1": canvas', 72)
+
+    def test_limits(self) -> None:
+        """Test that oversized snippets and pages are rejected."""
+        with pytest.raises(ValueError, match="snippet"):
+            preview("a" * (64 * 1024 + 1), 72)
+        with pytest.raises(ValueError, match="ppi"):
+            preview("a", 0)
+        with pytest.raises(ValueError, match="pixels"):
+            preview("#set page(width: 200cm, height: 200cm)\na", 1200)
+
+    def test_timeout(self) -> None:
+        """Test that long compilations time out."""
+        with pytest.raises(TimeoutError):
+            preview("#for i in range(100000000) { }", 72, timeout=0.01)
+        with pytest.raises(ValueError, match="timeout"):
+            preview("a", 72, timeout=float("nan"))
+
+    @pytest.mark.skipif(sys.platform == "win32", reason="resource limits are not available")
+    def test_memory_limit(self) -> None:
+        """Test that compilations exceeding the memory limit fail instead of exhausting memory."""
+        with pytest.raises(RuntimeError, match="memory limit of 768 MiB"):
+            preview('#let s = "x" * 2000000000\nDone', 72, timeout=30, memory_limit=768 * 1024 * 1024)
+
+
+ACRONYM_SOURCE = """We fine-tune an LLM first.
+A large language model (LLM) predicts tokens on Graphics Processing Units (GPUs).
+The LLM runs on two GPUs and calls an API.
+Our large language model (LLM) is small.
+"""
+
+
+class TestAcronyms:
+    """Test suite for the acronym and glossary helpers."""
+
+    def test_collect_acronyms(self) -> None:
+        """Definitions give the long form matched from the letters of the acronym."""
+        acronyms = collect_acronyms(ACRONYM_SOURCE)
+        assert [(a.short, a.long, a.line) for a in acronyms] == [
+            ("LLM", "large language model", 2),
+            ("GPU", "Graphics Processing Units", 2),
+        ]
+        assert acronyms[0].uses == 2
+        assert acronyms[1].uses == 1
+
+    def test_parenthesized_words_without_long_form(self) -> None:
+        """Parentheses whose content does not match the preceding words define nothing."""
+        assert collect_acronyms("Deployed on the cloud (AWS) with notes (see Appendix).") == []
+
+    def test_check_acronyms(self) -> None:
+        """Uses before the expansion, repeated expansions and unexpanded glossary acronyms are reported."""
+        issues = check_acronyms(ACRONYM_SOURCE, {"API": "Application Programming Interface", "CPU": "Central Unit"})
+        assert [(i.kind, i.short, i.line) for i in issues] == [
+            ("used_before_definition", "LLM", 1),
+            ("not_expanded", "API", 3),
+            ("redefined", "LLM", 4),
+        ]
+
+    def test_glossary_section(self) -> None:
+        """The glossary is a heading followed by a sorted term list with escaped markup."""
+        section = glossary_section({"LLM": "large language model", "I/O": "input/output"}, level=2, label="<glossary>")
+        assert section == "== Glossary <glossary>\n\n/ I\\/O: input\\/output\n/ LLM: large language model\n"
+        with pytest.raises(ValueError):
+            glossary_section({}, level=0)
+
+
+class TestFigureAssets:
+    """Test suite for register_asset(), figure_snippet() and register_figure()."""
+
+    def test_register_asset(self, tmp_path: Path) -> None:
+        """Images are copied under a content hash once, and referenced relative to the document."""
+        (tmp_path / "plots").mkdir()
+        (tmp_path / "plots" / "loss.PNG").write_bytes(b"png")
+        (tmp_path / "copy.png").write_bytes(b"png")
+        document = tmp_path / "paper"
+        document.mkdir()
+
+        path = register_asset(tmp_path / "plots" / "loss.PNG", tmp_path / "assets", document)
+        assert path.startswith("../assets/")
+        assert path.endswith(".png")
+        assert (document / path).read_bytes() == b"png"
+        assert register_asset(tmp_path / "copy.png", tmp_path / "assets", document) == path
+        assert len(list((tmp_path / "assets").iterdir())) == 1
+        assert register_asset(tmp_path / "copy.png", document / "assets", document).startswith("assets/")
+        with pytest.raises(OSError):
+            register_asset(tmp_path / "missing.png", tmp_path / "assets", document)
+
+    def test_figure_snippet(self) -> None:
+        """Paths are escaped as strings, unbalanced caption brackets escaped and labels checked."""
+        assert figure_snippet("a.png") == '#figure(image("a.png"))'
+        assert (
+            figure_snippet('dir\\"a".png', caption=" A *bold* [link] ", label="<fig:a>", width="80%")
+            == '#figure(image("dir\\\\\\"a\\".png", width: 80%), caption: [A *bold* [link]]) <fig:a>'
+        )
+        assert figure_snippet("a.png", caption="x] #evil[") == '#figure(image("a.png"), caption: [x\\] #evil\\[])'
+        for label in ("", "<>", "two words", "a>b"):
+            with pytest.raises(ValueError, match="label"):
+                figure_snippet("a.png", label=label)
+
+    def test_figure_width(self) -> None:
+        """Widths are a number with a unit or a percentage, nothing else reaches the snippet."""
+        for width in ("80%", "10cm", "12.5pt", "2in", "3em", "15mm"):
+            assert figure_snippet("a.png", width=width) == f'#figure(image("a.png", width: {width}))'
+        for width in ("", "80", "1fr", "cm", "-1cm", "1.cm", "80%) #evil(", "100% + 1pt"):
+            with pytest.raises(ValueError, match="width"):
+                figure_snippet("a.png", width=width)
+
+    def test_register_figure(self, tmp_path: Path) -> None:
+        """The snippet references the registered asset relative to the document."""
+        (tmp_path / "loss.png").write_bytes(b"png")
+        snippet = register_figure(
+            tmp_path / "loss.png", tmp_path / "paper" / "assets", tmp_path / "paper", caption="Loss", label="fig-loss"
+        )
+        path = snippet.split('"')[1]
+        assert path.startswith("assets/")
+        assert snippet == f'#figure(image("{path}"), caption: [Loss]) <fig-loss>'
+
+    def test_register_figure_invalid(self, tmp_path: Path) -> None:
+        """An invalid width or label is rejected before the image is copied into the assets."""
+        (tmp_path / "loss.png").write_bytes(b"png")
+        assets = tmp_path / "paper" / "assets"
+        with pytest.raises(ValueError, match="width"):
+            register_figure(tmp_path / "loss.png", assets, tmp_path / "paper", width="wide")
+        with pytest.raises(ValueError, match="label"):
+            register_figure(tmp_path / "loss.png", assets, tmp_path / "paper", label="two words")
+        assert not assets.exists()
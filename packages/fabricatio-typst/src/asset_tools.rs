@@ -0,0 +1,271 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use error_mapping::AsPyErr;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use regex::Regex;
+
+/// Number of hex characters of the content hash kept in asset file names.
+const ASSET_HASH_LEN: usize = 16;
+
+/// A length or ratio accepted as the width of a figure image, e.g. `80%` or `10.5cm`.
+static WIDTH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+(\.\d+)?(%|pt|mm|cm|in|em)$").unwrap());
+
+/// Computes the path of `target` relative to the directory `base`, both being absolute.
+fn relative_path(target: &Path, base: &Path) -> PathBuf {
+    let target: Vec<Component> = target.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = target
+        .iter()
+        .zip(base.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    base[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(target[common..].iter().copied())
+        .collect()
+}
+
+/// Renders a path the way Typst expects it, always using forward slashes.
+fn typst_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Escapes a string so it can be embedded in a Typst string literal.
+fn escape_typst_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Copies `source` into `assets_dir` under a content-addressed name, returning the stored path.
+///
+/// The file is only written if no asset with the same content exists yet.
+fn store_asset(source: &Path, assets_dir: &Path) -> PyResult<PathBuf> {
+    let content = fs::read(source).into_pyresult()?;
+    let digest = blake3::hash(&content).to_hex();
+    let stem = &digest[..ASSET_HASH_LEN];
+
+    let file_name = match source.extension() {
+        Some(ext) => format!("{}.{}", stem, ext.to_string_lossy().to_lowercase()),
+        None => stem.to_string(),
+    };
+
+    fs::create_dir_all(assets_dir).into_pyresult()?;
+    let dest = assets_dir.join(file_name);
+    if !dest.exists() {
+        fs::write(&dest, &content).into_pyresult()?;
+    }
+    Ok(dest)
+}
+
+/// Escapes the brackets of Typst markup that have no partner, so the markup stays inside
+/// the content block `[...]` it is embedded in.
+fn escape_unbalanced_brackets(markup: &str) -> String {
+    let mut unbalanced = Vec::new();
+    let mut open = Vec::new();
+    let mut chars = markup.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => open.push(i),
+            ']' => {
+                if open.pop().is_none() {
+                    unbalanced.push(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    unbalanced.extend(open);
+
+    let mut escaped = String::with_capacity(markup.len() + unbalanced.len());
+    for (i, c) in markup.char_indices() {
+        if unbalanced.contains(&i) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Checks a figure width, returning it trimmed.
+fn checked_width(width: &str) -> PyResult<&str> {
+    let width = width.trim();
+    if !WIDTH.is_match(width) {
+        return Err(PyValueError::new_err(format!(
+            "Invalid Typst width: `{}`",
+            width
+        )));
+    }
+    Ok(width)
+}
+
+/// Checks a figure label, returning it without surrounding whitespace and angle brackets.
+fn checked_label(label: &str) -> PyResult<&str> {
+    let label = label.trim().trim_start_matches('<').trim_end_matches('>');
+    if label.is_empty()
+        || !label
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+    {
+        return Err(PyValueError::new_err(format!(
+            "Invalid Typst label: `{}`",
+            label
+        )));
+    }
+    Ok(label)
+}
+
+/// Builds a `#figure(image(...))` snippet.
+fn figure_code(
+    path: &str,
+    caption: Option<&str>,
+    label: Option<&str>,
+    width: Option<&str>,
+) -> PyResult<String> {
+    let mut image = format!("image(\"{}\"", escape_typst_string(path));
+    if let Some(width) = width {
+        image.push_str(&format!(", width: {}", checked_width(width)?));
+    }
+    image.push(')');
+
+    let mut figure = format!("#figure({}", image);
+    if let Some(caption) = caption {
+        figure.push_str(&format!(
+            ", caption: [{}]",
+            escape_unbalanced_brackets(caption.trim())
+        ));
+    }
+    figure.push(')');
+
+    if let Some(label) = label {
+        figure.push_str(&format!(" <{}>", checked_label(label)?));
+    }
+    Ok(figure)
+}
+
+/// Registers an image asset for a document.
+///
+/// The image is copied into `assets_dir` under a name derived from its BLAKE3 content hash,
+/// so registering the same image twice stores it only once.
+///
+/// Args:
+///     source: Path to the image file to register.
+///     assets_dir: Directory the asset is copied into, created if missing.
+///     document_dir: Directory of the Typst document referencing the asset. If omitted, the
+///         current working directory is used.
+///
+/// Returns:
+///     The path of the stored asset relative to `document_dir`, using forward slashes.
+///
+/// Raises:
+///     OSError: If the image cannot be read or the asset cannot be written.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, assets_dir, document_dir = None))]
+fn register_asset(
+    source: PathBuf,
+    assets_dir: PathBuf,
+    document_dir: Option<PathBuf>,
+) -> PyResult<String> {
+    let dest = store_asset(&source, &assets_dir)?
+        .canonicalize()
+        .into_pyresult()?;
+    let base = document_dir
+        .map_or_else(std::env::current_dir, Ok)
+        .and_then(|dir| dir.canonicalize())
+        .into_pyresult()?;
+    Ok(typst_path(&relative_path(&dest, &base)))
+}
+
+/// Generates a Typst figure snippet for an image.
+///
+/// Args:
+///     path: The image path as it should appear in the document.
+///     caption: Optional caption content, inserted as Typst markup with unbalanced
+///         brackets escaped.
+///     label: Optional label, with or without surrounding angle brackets, made of letters,
+///         digits, `-`, `_`, `:` and `.`.
+///     width: Optional image width, a number followed by `%`, `pt`, `mm`, `cm`, `in` or
+///         `em`, e.g. `80%` or `10cm`.
+///
+/// Returns:
+///     A snippet like `#figure(image("assets/a.png"), caption: [A]) <fig-a>`.
+///
+/// Raises:
+///     ValueError: If the label or the width is invalid.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (path, caption = None, label = None, width = None))]
+fn figure_snippet(
+    path: &str,
+    caption: Option<&str>,
+    label: Option<&str>,
+    width: Option<&str>,
+) -> PyResult<String> {
+    figure_code(path, caption, label, width)
+}
+
+/// Registers an image asset and generates the Typst figure snippet referencing it.
+///
+/// Args:
+///     source: Path to the image file to register.
+///     assets_dir: Directory the asset is copied into, created if missing.
+///     document_dir: Directory of the Typst document referencing the asset. If omitted, the
+///         current working directory is used.
+///     caption: Optional caption content, inserted as Typst markup with unbalanced
+///         brackets escaped.
+///     label: Optional label, with or without surrounding angle brackets, made of letters,
+///         digits, `-`, `_`, `:` and `.`.
+///     width: Optional image width, a number followed by `%`, `pt`, `mm`, `cm`, `in` or
+///         `em`, e.g. `80%` or `10cm`.
+///
+/// Returns:
+///     The figure snippet with the image path resolved relative to `document_dir`.
+///
+/// Raises:
+///     OSError: If the image cannot be read or the asset cannot be written.
+///     ValueError: If the label or the width is invalid.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, assets_dir, document_dir = None, caption = None, label = None, width = None))]
+fn register_figure(
+    source: PathBuf,
+    assets_dir: PathBuf,
+    document_dir: Option<PathBuf>,
+    caption: Option<&str>,
+    label: Option<&str>,
+    width: Option<&str>,
+) -> PyResult<String> {
+    // Checked up front so that an invalid snippet leaves no asset behind.
+    width.map(checked_width).transpose()?;
+    label.map(checked_label).transpose()?;
+    let path = register_asset(source, assets_dir, document_dir)?;
+    figure_code(&path, caption, label, width)
+}
+
+/// Registers the asset management functions with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(register_asset, m)?)?;
+    m.add_function(wrap_pyfunction!(figure_snippet, m)?)?;
+    m.add_function(wrap_pyfunction!(register_figure, m)?)?;
+    Ok(())
+}
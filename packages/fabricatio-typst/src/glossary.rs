@@ -0,0 +1,365 @@
+//! Acronyms of a document: their definitions, their first-use expansions and a glossary.
+//!
+//! An acronym is defined the way theses usually do, by writing it in parentheses right after
+//! its long form, as in `large language model (LLM)`. Every acronym must be defined at its
+//! first use, and only once.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// An acronym in parentheses, with ASCII or full-width parentheses.
+static DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[(（]\s*([A-Za-z][A-Za-z0-9&\-]{0,11})\s*[)）]").unwrap());
+
+/// Characters ending the text the long form of an acronym is looked for in.
+const LONG_FORM_STOPS: &[char] = &['.', ';', ':', '!', '?', '(', ')', '\n', '。', '；', '：'];
+
+/// An acronym defined in a document.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct Acronym {
+    /// The acronym, e.g. `LLM`.
+    short: String,
+    /// The long form it stands for, as written at its first definition.
+    long: String,
+    /// The 1-based line of its first definition.
+    line: usize,
+    /// The number of times the acronym is used outside of its definitions, plurals included.
+    uses: usize,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl Acronym {
+    fn __repr__(&self) -> String {
+        format!(
+            "Acronym(short={:?}, long={:?}, line={}, uses={})",
+            self.short, self.long, self.line, self.uses
+        )
+    }
+}
+
+/// A misuse of an acronym.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass(get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct AcronymIssue {
+    /// One of `used_before_definition` (used before the use expanding it),
+    /// `not_expanded` (a glossary acronym used but never expanded) or `redefined` (expanded
+    /// again after its definition).
+    kind: String,
+    /// The acronym concerned.
+    short: String,
+    /// The 1-based line of the offending use or definition.
+    line: usize,
+    /// A description of the issue, for reports.
+    message: String,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl AcronymIssue {
+    fn __repr__(&self) -> String {
+        format!(
+            "AcronymIssue(kind={:?}, short={:?}, line={})",
+            self.kind, self.short, self.line
+        )
+    }
+}
+
+/// An acronym in parentheses and the long form found before it.
+struct Definition {
+    short: String,
+    long: String,
+    /// Byte range of the parentheses and the acronym.
+    start: usize,
+    end: usize,
+}
+
+/// Whether a parenthesized word looks like an acronym: at least two capitals and no more
+/// lowercase letters than a plural or a particle, as in `LLMs` or `MoE`.
+fn is_acronym(word: &str) -> bool {
+    let upper = word.chars().filter(char::is_ascii_uppercase).count();
+    let lower = word.chars().filter(char::is_ascii_lowercase).count();
+    upper >= 2 && lower <= upper
+}
+
+/// Finds the long form of an acronym at the end of the text preceding it.
+///
+/// Follows Schwartz and Hearst: the letters of the acronym are matched from the last one
+/// backwards in the preceding words, the first letter of the acronym starting a word. Only
+/// the last `min(n + 5, 2n)` words are considered, `n` being the length of the acronym.
+fn long_form(short: &str, before: &str) -> Option<String> {
+    let before = before.rsplit(LONG_FORM_STOPS).next().unwrap_or(before);
+    let letters: Vec<char> = short
+        .trim_end_matches('s')
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let n = letters.len();
+    if n < 2 {
+        return None;
+    }
+    let words: Vec<&str> = before.split_whitespace().collect();
+    let candidate = words[words.len().saturating_sub((n + 5).min(2 * n))..].join(" ");
+    let chars: Vec<char> = candidate.chars().collect();
+
+    let mut l = chars.len();
+    for (s, &letter) in letters.iter().enumerate().rev() {
+        loop {
+            if l == 0 {
+                return None;
+            }
+            l -= 1;
+            let starts_word = l == 0 || !chars[l - 1].is_alphanumeric();
+            if chars[l].to_ascii_lowercase() == letter && (s > 0 || starts_word) {
+                break;
+            }
+        }
+    }
+    let start = chars[..l]
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .map_or(0, |i| i + 1);
+    let long: String = chars[start..].iter().collect();
+    let long = long.trim_matches(|c: char| !c.is_alphanumeric());
+    (long.chars().count() > short.chars().count()).then(|| long.to_string())
+}
+
+/// Finds the acronym definitions of a source, in order.
+fn definitions(source: &str) -> Vec<Definition> {
+    DEFINITION
+        .captures_iter(source)
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            let short = caps.get(1)?.as_str();
+            if !is_acronym(short) {
+                return None;
+            }
+            let short = short.strip_suffix('s').unwrap_or(short);
+            let long = long_form(short, &source[..whole.start()])?;
+            Some(Definition {
+                short: short.to_string(),
+                long,
+                start: whole.start(),
+                end: whole.end(),
+            })
+        })
+        .collect()
+}
+
+/// The 1-based line a byte offset is on.
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+/// The byte offsets of the uses of an acronym, plurals included, outside of its definitions.
+fn uses(source: &str, short: &str, definitions: &[Definition]) -> Vec<usize> {
+    let word = Regex::new(&format!(r"\b{}s?\b", regex::escape(short))).unwrap();
+    word.find_iter(source)
+        .map(|m| m.start())
+        .filter(|&at| !definitions.iter().any(|d| d.start <= at && at < d.end))
+        .collect()
+}
+
+/// Collects the acronyms defined in a document, as `long form (ACRONYM)`.
+///
+/// The long form is matched from the letters of the acronym in the words before the
+/// parentheses, so `a large language model (LLM)` defines `LLM` as `large language model`.
+/// Parenthesized words whose letters do not match the preceding words are ignored, and a
+/// plural such as `(LLMs)` defines the singular acronym.
+///
+/// Args:
+///     source: The Typst source of the document.
+///
+/// Returns:
+///     The acronyms in the order of their first definition.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+pub fn collect_acronyms(source: &str) -> Vec<Acronym> {
+    let definitions = definitions(source);
+    let mut seen = HashSet::new();
+    definitions
+        .iter()
+        .filter(|d| seen.insert(d.short.clone()))
+        .map(|d| Acronym {
+            short: d.short.clone(),
+            long: d.long.clone(),
+            line: line_of(source, d.start),
+            uses: uses(source, &d.short, &definitions).len(),
+        })
+        .collect()
+}
+
+/// Checks that every acronym of a document is expanded at its first use, and only there.
+///
+/// Args:
+///     source: The Typst source of the document.
+///     glossary: Acronyms that must be expanded even if the document never defines them,
+///         mapped to their long forms, such as those of a shared glossary.
+///
+/// Returns:
+///     The issues found, sorted by line.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, glossary = None))]
+pub fn check_acronyms(
+    source: &str,
+    glossary: Option<HashMap<String, String>>,
+) -> Vec<AcronymIssue> {
+    let definitions = definitions(source);
+    let issue = |kind: &str, short: &str, at: usize, message: String| AcronymIssue {
+        kind: kind.to_string(),
+        short: short.to_string(),
+        line: line_of(source, at),
+        message,
+    };
+    let mut issues = Vec::new();
+    let mut checked = HashSet::new();
+
+    for definition in &definitions {
+        let short = definition.short.as_str();
+        if !checked.insert(short) {
+            continue;
+        }
+        let mut defined_at = definitions.iter().filter(|d| d.short == short);
+        let first = defined_at.next().map_or(0, |d| d.start);
+        if let Some(&used_at) = uses(source, short, &definitions).first()
+            && used_at < first
+        {
+            issues.push(issue(
+                "used_before_definition",
+                short,
+                used_at,
+                format!(
+                    "`{short}` is used before being expanded as `{}` on line {}",
+                    definition.long,
+                    line_of(source, first)
+                ),
+            ));
+        }
+        for again in defined_at {
+            issues.push(issue(
+                "redefined",
+                short,
+                again.start,
+                format!(
+                    "`{short}` is expanded again, after its definition on line {}",
+                    line_of(source, first)
+                ),
+            ));
+        }
+    }
+
+    let mut undefined: Vec<(String, String)> = glossary
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(short, _)| !checked.contains(short.as_str()))
+        .collect();
+    undefined.sort();
+    for (short, long) in undefined {
+        if let Some(&used_at) = uses(source, &short, &definitions).first() {
+            issues.push(issue(
+                "not_expanded",
+                &short,
+                used_at,
+                format!("`{short}` is used without being expanded as `{long} ({short})`"),
+            ));
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Escapes the characters with a meaning in Typst markup.
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '#' | '*' | '_' | '`' | '$' | '<' | '@' | '[' | ']' | '~' | '/' | ':'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Generates a glossary section listing acronyms and their long forms as a Typst term list.
+///
+/// Args:
+///     entries: The acronyms mapped to their long forms, such as those of
+///         `collect_acronyms`.
+///     title: The title of the section.
+///     level: The level of the heading of the section, 1 for `=`.
+///     label: Optional label of the heading, with or without surrounding angle brackets.
+///
+/// Returns:
+///     The section, its terms sorted alphabetically, e.g.
+///     `= Glossary\n\n/ LLM: Large Language Model\n`.
+///
+/// Raises:
+///     ValueError: If the level is 0 or the label is empty or contains whitespace.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (entries, title = "Glossary", level = 1, label = None))]
+pub fn glossary_section(
+    entries: HashMap<String, String>,
+    title: &str,
+    level: usize,
+    label: Option<&str>,
+) -> PyResult<String> {
+    if level == 0 {
+        return Err(PyValueError::new_err(
+            "The heading level must be at least 1",
+        ));
+    }
+    let mut section = format!("{} {}", "=".repeat(level), title.trim());
+    if let Some(label) = label {
+        let label = label.trim().trim_start_matches('<').trim_end_matches('>');
+        if label.is_empty() || label.chars().any(char::is_whitespace) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid Typst label: `{label}`"
+            )));
+        }
+        section.push_str(&format!(" <{label}>"));
+    }
+    section.push_str("\n\n");
+
+    let mut entries: Vec<(String, String)> = entries.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b)));
+    for (short, long) in entries {
+        section.push_str(&format!(
+            "/ {}: {}\n",
+            escape_markup(&short),
+            escape_markup(long.trim())
+        ));
+    }
+    Ok(section)
+}
+
+/// Registers the acronym and glossary functions with the Python module.
+///
+/// Args:
+///     _: The Python interpreter instance.
+///     m: The Python module to register with.
+///
+/// Returns:
+///     PyResult<()> indicating success.
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(collect_acronyms, m)?)?;
+    m.add_function(wrap_pyfunction!(check_acronyms, m)?)?;
+    m.add_function(wrap_pyfunction!(glossary_section, m)?)?;
+    m.add_class::<Acronym>()?;
+    m.add_class::<AcronymIssue>()?;
+    Ok(())
+}
@@ -1,6 +1,10 @@
 #![cfg_attr(feature = "stubgen", allow(dead_code, unused,))]
 
+mod asset_tools;
 mod bib_tools;
+mod glossary;
+mod packages;
+mod preview;
 mod typst_tools;
 
 use fabricatio_logger::init_logger_auto;
@@ -13,7 +17,11 @@ use pyo3::prelude::*;
 
 fn rust(python: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     init_logger_auto()?;
+    asset_tools::register(python, m)?;
     bib_tools::register(python, m)?;
+    glossary::register(python, m)?;
+    packages::register(python, m)?;
+    preview::register(python, m)?;
     typst_tools::register(python, m)?;
     Ok(())
 }
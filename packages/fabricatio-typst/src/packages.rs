@@ -0,0 +1,237 @@
+//! Vendoring of Typst packages for offline compilation.
+//!
+//! Packages imported from the `@preview` namespace are downloaded into a local package
+//! directory under another namespace, and the imports are rewritten to that namespace.
+//! Typst only downloads `@preview` packages, so a document compiled with
+//! `--package-path <cache_dir>` never reaches the registry.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use error_mapping::AsPyErr;
+use fabricatio_logger::{debug, info};
+use flate2::read::GzDecoder;
+use pyo3::exceptions::{PyConnectionError, PyValueError};
+use pyo3::prelude::*;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use regex::{Captures, Regex};
+use reqwest::Proxy;
+use reqwest::blocking::Client;
+use tar::Archive;
+
+/// The official Typst package registry.
+const DEFAULT_REGISTRY: &str = "https://packages.typst.org";
+
+/// The namespace Typst downloads packages of.
+const PREVIEW: &str = "preview";
+
+/// Matches package specs in string literals, e.g. `"@preview/cetz:0.3.1"`.
+static PACKAGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""@([a-z0-9][a-z0-9_-]*)/([a-z0-9][a-z0-9_-]*):(\d+\.\d+\.\d+)""#).unwrap()
+});
+
+/// A package of the `@preview` namespace.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct PackageSpec {
+    name: String,
+    version: String,
+}
+
+impl Display for PackageSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@{PREVIEW}/{}:{}", self.name, self.version)
+    }
+}
+
+/// Rewrites the `@preview` imports of a source to `namespace`, collecting the packages
+/// imported from either namespace.
+fn rewrite_imports(source: &str, namespace: &str, found: &mut BTreeSet<PackageSpec>) -> String {
+    PACKAGE_RE
+        .replace_all(source, |caps: &Captures| {
+            if &caps[1] != PREVIEW && &caps[1] != namespace {
+                return caps[0].to_string();
+            }
+            found.insert(PackageSpec {
+                name: caps[2].to_string(),
+                version: caps[3].to_string(),
+            });
+            format!("\"@{namespace}/{}:{}\"", &caps[2], &caps[3])
+        })
+        .into_owned()
+}
+
+/// Lists the `.typ` files below a directory.
+fn typ_files(dir: &Path) -> PyResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).into_pyresult()? {
+        let path = entry.into_pyresult()?.path();
+        if path.is_dir() {
+            files.extend(typ_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "typ") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Downloads packages into the package directory of a namespace.
+struct Vendor {
+    client: Client,
+    registry: String,
+    namespace: String,
+    /// `<cache_dir>/<namespace>`, holding one `<name>/<version>` directory per package.
+    root: PathBuf,
+}
+
+impl Vendor {
+    fn new(
+        cache_dir: &Path,
+        namespace: &str,
+        registry: &str,
+        proxy: Option<&str>,
+    ) -> PyResult<Self> {
+        // Without an explicit proxy, the `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+        // environment variables are honored.
+        let mut builder = Client::builder().timeout(Duration::from_secs(60));
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(
+                Proxy::all(proxy)
+                    .map_err(|e| PyValueError::new_err(format!("Invalid proxy `{proxy}`: {e}")))?,
+            );
+        }
+        Ok(Self {
+            client: builder
+                .build()
+                .map_err(|e| PyConnectionError::new_err(e.to_string()))?,
+            registry: registry.trim_end_matches('/').to_string(),
+            namespace: namespace.to_string(),
+            root: cache_dir.join(namespace),
+        })
+    }
+
+    /// Downloads and unpacks a package, replacing any incomplete copy.
+    fn download(&self, spec: &PackageSpec, dir: &Path) -> PyResult<()> {
+        let url = format!(
+            "{}/{PREVIEW}/{}-{}.tar.gz",
+            self.registry, spec.name, spec.version
+        );
+        info!("Downloading {spec} from {url}");
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| PyConnectionError::new_err(format!("Failed to download {spec}: {e}")))?;
+
+        // Unpack next to the final directory so an interrupted download is never used.
+        let partial = dir.with_file_name(format!(".{}.partial", spec.version));
+        if partial.exists() {
+            fs::remove_dir_all(&partial).into_pyresult()?;
+        }
+        fs::create_dir_all(&partial).into_pyresult()?;
+        Archive::new(GzDecoder::new(response))
+            .unpack(&partial)
+            .into_pyresult()?;
+        if dir.exists() {
+            fs::remove_dir_all(dir).into_pyresult()?;
+        }
+        fs::rename(&partial, dir).into_pyresult()
+    }
+
+    /// Vendors a package unless it is present already, returning the packages it imports.
+    fn fetch(&self, spec: &PackageSpec) -> PyResult<BTreeSet<PackageSpec>> {
+        let dir = self.root.join(&spec.name).join(&spec.version);
+        if dir.join("typst.toml").is_file() {
+            debug!("{spec} is vendored already");
+        } else {
+            self.download(spec, &dir)?;
+        }
+
+        let mut dependencies = BTreeSet::new();
+        for file in typ_files(&dir)? {
+            let source = fs::read_to_string(&file).into_pyresult()?;
+            let rewritten = rewrite_imports(&source, &self.namespace, &mut dependencies);
+            if rewritten != source {
+                fs::write(&file, rewritten).into_pyresult()?;
+            }
+        }
+        Ok(dependencies)
+    }
+}
+
+/// Vendors the `@preview` packages imported by a Typst source for offline compilation.
+///
+/// Every package imported as `"@preview/name:version"`, and every package those import in
+/// turn, is downloaded into `<cache_dir>/<namespace>/<name>/<version>` unless it is there
+/// already. The imports of the source and of the vendored packages are rewritten to
+/// `"@<namespace>/name:version"`, which Typst resolves locally when compiling with
+/// `--package-path <cache_dir>` instead of reaching the package registry.
+///
+/// Args:
+///     source: The Typst source to vendor the packages of.
+///     cache_dir: The local package directory to store the packages in.
+///     namespace: The namespace imports are rewritten to. Must not be `preview`.
+///     registry: Base URL of the package registry to download from.
+///     proxy: Proxy URL used for downloads. If omitted, the `HTTPS_PROXY`, `ALL_PROXY` and
+///         `NO_PROXY` environment variables are honored.
+///
+/// Returns:
+///     A tuple of the rewritten source and the sorted specs of the vendored packages, as
+///     they are named in the registry.
+///
+/// Raises:
+///     ValueError: If the namespace or the proxy URL is invalid.
+///     ConnectionError: If a package cannot be downloaded.
+///     OSError: If a package cannot be unpacked or rewritten.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (source, cache_dir, namespace = "vendor", registry = DEFAULT_REGISTRY, proxy = None))]
+fn vendor_packages(
+    python: Python,
+    source: &str,
+    cache_dir: PathBuf,
+    namespace: &str,
+    registry: &str,
+    proxy: Option<&str>,
+) -> PyResult<(String, Vec<String>)> {
+    if namespace == PREVIEW
+        || namespace.is_empty()
+        || !namespace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(PyValueError::new_err(format!(
+            "Invalid namespace `{namespace}`, expected lowercase letters, digits, `-` or `_` other than `{PREVIEW}`"
+        )));
+    }
+
+    let mut pending = BTreeSet::new();
+    let rewritten = rewrite_imports(source, namespace, &mut pending);
+    python.detach(|| {
+        let vendor = Vendor::new(&cache_dir, namespace, registry, proxy)?;
+        let mut vendored = BTreeSet::new();
+        while let Some(spec) = pending.pop_first() {
+            let dependencies = vendor.fetch(&spec)?;
+            vendored.insert(spec);
+            pending.extend(
+                dependencies
+                    .into_iter()
+                    .filter(|dependency| !vendored.contains(dependency)),
+            );
+        }
+        Ok((
+            rewritten,
+            vendored.iter().map(PackageSpec::to_string).collect(),
+        ))
+    })
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(vendor_packages, m)?)?;
+    Ok(())
+}
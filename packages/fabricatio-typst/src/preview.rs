@@ -0,0 +1,366 @@
+//! Sandboxed compilation of Typst snippets into PNG previews.
+//!
+//! Snippets are compiled in a `World` holding nothing but the snippet itself and the fonts
+//! embedded in the library: reading any other file, including packages, is denied, and the
+//! date is unknown, so a snippet cannot reach the filesystem or the network. Compilation
+//! runs in a worker process, `python -m fabricatio_typst.preview_worker`, whose memory and
+//! CPU time are capped with resource limits where the platform has them, and which is killed
+//! once the timeout expires. The size of the snippet and of the rendered page are capped too,
+//! and so is the number of workers running at once.
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use pyo3::exceptions::{PyRuntimeError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+#[cfg(feature = "stubgen")]
+use pyo3_stub_gen::derive::*;
+use typst::diag::{FileError, FileResult};
+use typst::foundations::{Bytes, Datetime};
+use typst::layout::PagedDocument;
+use typst::syntax::{FileId, Source, VirtualPath};
+use typst::text::{Font, FontBook};
+use typst::utils::LazyHash;
+use typst::{Library, World};
+
+/// Maximum length in bytes of a snippet.
+const MAX_SNIPPET_LEN: usize = 64 * 1024;
+
+/// Maximum number of pixels of a rendered page, about 64 MB of RGBA.
+const MAX_PREVIEW_PIXELS: f64 = 16_000_000.0;
+
+/// Resolution bounds of a preview, in pixels per inch.
+const PPI_RANGE: (f32, f32) = (1.0, 1200.0);
+
+/// Stack size of the compilation threads, deep enough for nested markup.
+const COMPILER_STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default number of bytes of address space a worker may use.
+const DEFAULT_MEMORY_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// Maximum number of workers compiling at once, further previews are refused meanwhile.
+const MAX_WORKERS: usize = 4;
+
+/// Number of bytes of the standard error of a worker kept to report a crash.
+const MAX_STDERR_LEN: u64 = 8 * 1024;
+
+/// Number of workers currently running.
+static WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The fonts embedded in the library, loaded once and shared by every preview.
+static FONTS: LazyLock<(LazyHash<FontBook>, Vec<Font>)> = LazyLock::new(|| {
+    let fonts: Vec<Font> = typst_assets::fonts()
+        .flat_map(|data| Font::iter(Bytes::new(data)))
+        .collect();
+    (LazyHash::new(FontBook::from_fonts(&fonts)), fonts)
+});
+
+/// The standard library, shared by every preview.
+static LIBRARY: LazyLock<LazyHash<Library>> =
+    LazyLock::new(|| LazyHash::new(Library::builder().build()));
+
+/// A world made of a single source, denying access to every other file.
+struct SnippetWorld {
+    main: Source,
+}
+
+impl SnippetWorld {
+    fn new(snippet: String) -> Self {
+        Self {
+            main: Source::new(FileId::new(None, VirtualPath::new("main.typ")), snippet),
+        }
+    }
+}
+
+impl World for SnippetWorld {
+    fn library(&self) -> &LazyHash<Library> {
+        &LIBRARY
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &FONTS.0
+    }
+
+    fn main(&self) -> FileId {
+        self.main.id()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.main.id() {
+            Ok(self.main.clone())
+        } else {
+            Err(FileError::AccessDenied)
+        }
+    }
+
+    fn file(&self, _: FileId) -> FileResult<Bytes> {
+        Err(FileError::AccessDenied)
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        FONTS.1.get(index).cloned()
+    }
+
+    fn today(&self, _: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+/// Compiles a snippet and renders one of its pages, 1-based, as PNG.
+fn render_page(snippet: String, ppi: f32, page: usize) -> PyResult<Vec<u8>> {
+    let world = SnippetWorld::new(snippet);
+    let document = typst::compile::<PagedDocument>(&world)
+        .output
+        .map_err(|errors| {
+            PyRuntimeError::new_err(
+                errors
+                    .iter()
+                    .map(|error| error.message.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        })?;
+
+    let count = document.pages.len();
+    let page = page
+        .checked_sub(1)
+        .and_then(|index| document.pages.get(index))
+        .ok_or_else(|| {
+            PyValueError::new_err(format!("The snippet has {count} pages, no page {page}"))
+        })?;
+    let pixel_per_pt = ppi / 72.0;
+    let size = page.frame.size();
+    let pixels = size.x.to_pt() * size.y.to_pt() * (pixel_per_pt as f64).powi(2);
+    if pixels > MAX_PREVIEW_PIXELS {
+        return Err(PyValueError::new_err(format!(
+            "The page would render to {pixels:.0} pixels, over the limit of {MAX_PREVIEW_PIXELS:.0}, lower the ppi or the page size"
+        )));
+    }
+    typst_render::render(page, pixel_per_pt)
+        .encode_png()
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to encode the preview: {e}")))
+}
+
+/// A slot among the `MAX_WORKERS` running workers, released when dropped.
+struct WorkerSlot;
+
+impl WorkerSlot {
+    fn acquire() -> Option<Self> {
+        WORKERS
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < MAX_WORKERS).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for WorkerSlot {
+    fn drop(&mut self) {
+        WORKERS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Reads a stream to its end on a background thread, keeping at most `limit` bytes.
+fn read_in_background(
+    mut stream: impl Read + Send + 'static,
+    limit: u64,
+) -> mpsc::Receiver<io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut kept = Vec::new();
+        let read = (&mut stream)
+            .take(limit)
+            .read_to_end(&mut kept)
+            // Drained past the limit, so the worker never blocks on a full pipe.
+            .and_then(|_| io::copy(&mut stream, &mut io::sink()))
+            .map(|_| kept);
+        let _ = sender.send(read);
+    });
+    receiver
+}
+
+/// Kills a worker and reaps it.
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Compiles a snippet in a worker process started with `python`, killing it after the timeout.
+fn run_worker(
+    python: &Path,
+    snippet: String,
+    ppi: f32,
+    page: usize,
+    timeout: Duration,
+    memory_limit: u64,
+) -> PyResult<Vec<u8>> {
+    let mut child = Command::new(python)
+        .args(["-m", "fabricatio_typst.preview_worker"])
+        .arg(ppi.to_string())
+        .arg(page.to_string())
+        .arg(memory_limit.to_string())
+        // CPU seconds, in case the worker outlives its parent
+        .arg((timeout.as_secs() + 1).to_string())
+        // Every thread and malloc arena reserves address space, which the memory limit counts.
+        .env("RAYON_NUM_THREADS", "1")
+        .env("MALLOC_ARENA_MAX", "2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to start the compiler: {e}")))?;
+
+    let (Some(mut stdin), Some(stdout), Some(stderr)) =
+        (child.stdin.take(), child.stdout.take(), child.stderr.take())
+    else {
+        kill(&mut child);
+        return Err(PyRuntimeError::new_err("Failed to connect to the compiler"));
+    };
+    thread::spawn(move || {
+        // The worker may have died before reading it, its exit status tells why.
+        let _ = stdin.write_all(snippet.as_bytes());
+    });
+    let stdout = read_in_background(stdout, u64::MAX);
+    let stderr = read_in_background(stderr, MAX_STDERR_LEN);
+
+    let output = match stdout.recv_timeout(timeout) {
+        Ok(output) => output,
+        Err(RecvTimeoutError::Timeout) => {
+            kill(&mut child);
+            return Err(PyTimeoutError::new_err(format!(
+                "The snippet did not compile within {}s",
+                timeout.as_secs_f64()
+            )));
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            kill(&mut child);
+            return Err(PyRuntimeError::new_err("Lost the output of the compiler"));
+        }
+    };
+    let status = child.wait()?;
+    let output = output?;
+    if status.success() {
+        return Ok(output);
+    }
+
+    // Errors of the snippet are reported as their type and message on two lines.
+    let reported = String::from_utf8_lossy(&output);
+    if status.code() == Some(1)
+        && let Some((kind, message)) = reported.split_once('\n')
+    {
+        match kind {
+            "ValueError" => return Err(PyValueError::new_err(message.to_string())),
+            "RuntimeError" => return Err(PyRuntimeError::new_err(message.to_string())),
+            _ => {}
+        }
+    }
+    let stderr = stderr.recv().ok().and_then(Result::ok).unwrap_or_default();
+    Err(PyRuntimeError::new_err(format!(
+        "The compiler crashed ({status}), possibly by exceeding the memory limit of {} MiB: {}",
+        memory_limit / (1024 * 1024),
+        String::from_utf8_lossy(&stderr).trim()
+    )))
+}
+
+/// Compiles a small Typst snippet in a sandbox and renders one of its pages as PNG.
+///
+/// The snippet can only use the standard library and the embedded fonts: imports of files
+/// or packages fail, and so does `datetime.today()`. Meant for previews of math or figures,
+/// e.g. in the template playground of the web UI, rather than for whole documents. Each
+/// preview is compiled by a worker process, started with the current Python interpreter,
+/// which is killed if it is still compiling after the timeout. The memory of the worker is
+/// capped on platforms with resource limits, such as Linux and macOS, but not on Windows.
+///
+/// Args:
+///     snippet: The Typst source to compile, at most 64 KiB.
+///     ppi: The resolution of the preview in pixels per inch, between 1 and 1200.
+///     page: The 1-based number of the page to render.
+///     timeout: The number of seconds to wait for the worker, its startup included.
+///     memory_limit: The number of bytes of address space the worker may use, 1 GiB by
+///         default.
+///
+/// Returns:
+///     The PNG encoded image of the page.
+///
+/// Raises:
+///     ValueError: If the snippet is too long, the ppi out of range, the timeout invalid, the
+///         page missing, or the page would render to more than 16 million pixels.
+///     RuntimeError: If the snippet does not compile, with the compiler errors, if the
+///         worker crashes, such as by exceeding its memory limit, or if 4 previews are
+///         already compiling.
+///     TimeoutError: If the compilation takes longer than the timeout.
+#[cfg_attr(feature = "stubgen", gen_stub_pyfunction)]
+#[pyfunction]
+#[pyo3(signature = (snippet, ppi, page = 1, timeout = 5.0, memory_limit = DEFAULT_MEMORY_LIMIT))]
+fn preview<'py>(
+    python: Python<'py>,
+    snippet: String,
+    ppi: f32,
+    page: usize,
+    timeout: f64,
+    memory_limit: u64,
+) -> PyResult<Bound<'py, PyBytes>> {
+    if snippet.len() > MAX_SNIPPET_LEN {
+        return Err(PyValueError::new_err(format!(
+            "The snippet is {} bytes long, over the limit of {MAX_SNIPPET_LEN}",
+            snippet.len()
+        )));
+    }
+    if !(PPI_RANGE.0..=PPI_RANGE.1).contains(&ppi) {
+        return Err(PyValueError::new_err(format!(
+            "The ppi must be between {} and {}, got {ppi}",
+            PPI_RANGE.0, PPI_RANGE.1
+        )));
+    }
+    let timeout = Duration::try_from_secs_f64(timeout)
+        .map_err(|_| PyValueError::new_err(format!("Invalid timeout {timeout}")))?;
+    let executable = python
+        .import("sys")?
+        .getattr("executable")?
+        .extract::<PathBuf>()?;
+
+    let Some(_slot) = WorkerSlot::acquire() else {
+        return Err(PyRuntimeError::new_err(format!(
+            "{MAX_WORKERS} previews are already compiling, try again later"
+        )));
+    };
+    let png =
+        python.detach(|| run_worker(&executable, snippet, ppi, page, timeout, memory_limit))?;
+    Ok(PyBytes::new(python, &png))
+}
+
+/// Compiles a snippet and renders one of its pages in the current process, for the worker
+/// process of `preview`, which validates the arguments beforehand.
+#[pyfunction]
+#[pyo3(name = "_render_preview")]
+fn render_preview<'py>(
+    python: Python<'py>,
+    snippet: String,
+    ppi: f32,
+    page: usize,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let png = python.detach(move || -> PyResult<Vec<u8>> {
+        thread::Builder::new()
+            .name("typst-preview".to_string())
+            .stack_size(COMPILER_STACK_SIZE)
+            .spawn(move || render_page(snippet, ppi, page))
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start the compiler: {e}")))?
+            .join()
+            .map_err(|_| PyRuntimeError::new_err("The compiler panicked"))?
+    })?;
+    Ok(PyBytes::new(python, &png))
+}
+
+pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(preview, m)?)?;
+    m.add_function(wrap_pyfunction!(render_preview, m)?)?;
+    Ok(())
+}
@@ -3,6 +3,11 @@ import type {
   WorkflowJSON,
   ExecutionRequest,
   ExecutionStatus,
+  FileEntry,
+  FileContent,
+  Transcript,
+  TranscriptEntry,
+  TranscriptSummary,
 } from '@/types/api'
 import { useLoadingStore } from '@/stores/loading'
 import { useNotificationsStore } from '@/stores/notifications'
@@ -75,4 +80,52 @@ export const api = {
   getQueue: () => request<unknown[]>('GET', '/queue', undefined, { silent: true }),
   getHistory: () =>
     request<ExecutionStatus[]>('GET', '/history', undefined, { loading: 'Loading history...' }),
+  getFileRoots: () => request<string[]>('GET', '/files/roots', undefined, { silent: true }),
+  listFiles: (path = '', root = 0, maxDepth = 1) =>
+    request<FileEntry[]>(
+      'GET',
+      `/files?${new URLSearchParams({ root: String(root), path, max_depth: String(maxDepth) })}`,
+      undefined,
+      { loading: 'Loading files...' },
+    ),
+  readFile: (path: string, root = 0) =>
+    request<FileContent>(
+      'GET',
+      `/files/content?${new URLSearchParams({ root: String(root), path })}`,
+      undefined,
+      { loading: 'Loading file...' },
+    ),
+  writeFile: (path: string, content: string, root = 0) =>
+    request<{ ok: boolean; checkpoint?: string }>(
+      'PUT',
+      '/files/content',
+      { root, path, content },
+      { loading: 'Saving file...' },
+    ),
+  getTranscripts: () =>
+    request<TranscriptSummary[]>('GET', '/transcripts', undefined, {
+      loading: 'Loading transcripts...',
+    }),
+  getTranscript: (id: string) =>
+    request<Transcript>('GET', `/transcripts/${encodeURIComponent(id)}`, undefined, {
+      loading: 'Loading transcript...',
+    }),
+  createTranscript: (title?: string, executionId?: string) =>
+    request<TranscriptSummary>(
+      'POST',
+      '/transcripts',
+      { title, execution_id: executionId },
+      { silent: true },
+    ),
+  appendTranscriptEntries: (id: string, entries: TranscriptEntry[]) =>
+    request<TranscriptSummary>(
+      'POST',
+      `/transcripts/${encodeURIComponent(id)}/entries`,
+      entries,
+      { silent: true },
+    ),
+  deleteTranscript: (id: string) =>
+    request<{ ok: boolean }>('DELETE', `/transcripts/${encodeURIComponent(id)}`, undefined, {
+      loading: 'Deleting transcript...',
+    }),
 }
@@ -167,3 +167,60 @@ export interface WSSubmit {
   workflow: WorkflowJSON
   task_input?: unknown
 }
+
+export interface FileEntry {
+  path: string
+  is_dir: boolean
+  size: number
+  depth: number
+}
+
+export interface FileContent {
+  root: number
+  path: string
+  content: string
+}
+
+export interface TokenUsage {
+  prompt_tokens: number
+  completion_tokens: number
+  total_tokens: number
+}
+
+export type TranscriptEntry =
+  | {
+      kind: 'message'
+      role: 'system' | 'user' | 'assistant' | 'tool'
+      content: string
+      usage?: TokenUsage
+      timestamp?: string
+    }
+  | {
+      kind: 'tool_call'
+      call_id?: string
+      name: string
+      arguments: unknown
+      timestamp?: string
+    }
+  | {
+      kind: 'tool_result'
+      call_id?: string
+      name: string
+      result: unknown
+      is_error: boolean
+      timestamp?: string
+    }
+
+export interface TranscriptSummary {
+  id: string
+  title?: string | null
+  execution_id?: string | null
+  created_at: string
+  updated_at: string
+  entry_count: number
+  usage: TokenUsage
+}
+
+export interface Transcript extends TranscriptSummary {
+  entries: TranscriptEntry[]
+}
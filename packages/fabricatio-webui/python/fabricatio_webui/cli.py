@@ -6,13 +6,14 @@ cfg(feats=["cli"])
 import json
 from asyncio import run
 from pathlib import Path
-from typing import Optional
+from typing import Awaitable, Callable, List, Optional
 
 from typer import Option, Typer
 
 from fabricatio_webui.config import webui_config
 from fabricatio_webui.registry import build_node_registry
 from fabricatio_webui.rust import start_service
+from fabricatio_webui.stats import collect_stats
 
 app = Typer()
 
@@ -24,11 +25,45 @@ def _default_www() -> Path:
     return CUR_DIR / "www"
 
 
+def _checkpoint_before_write() -> Optional[Callable[[Path, Path], str]]:
+    """Return a callback saving a checkpoint of the workspace before a file is edited, if enabled and installed."""
+    if not webui_config.checkpoint_before_write:
+        return None
+    try:
+        from fabricatio_checkpoint.inited_service import get_checkpoint_service
+    except ImportError:
+        return None
+
+    def _save(workspace: Path, path: Path) -> str:
+        return get_checkpoint_service().get_store(workspace).save(
+            commit_msg=f"webui: before editing {path.relative_to(workspace)}"
+        )
+
+    return _save
+
+
+def _tool_caller() -> Optional[Callable[[str, str, str, Callable[[str], None]], Awaitable[str]]]:
+    """Return the caller of the MCP tools of the tool console, if enabled and installed."""
+    if not webui_config.tool_console:
+        return None
+    try:
+        import fabricatio_tool  # noqa: F401
+    except ImportError:
+        return None
+
+    from fabricatio_webui.tools import call_mcp_tool
+
+    return call_mcp_tool
+
+
 @app.command()
 def main(
     frontend_dir: Optional[Path] = Option(None, "--frontend-dir", "-d", help="front end directory"),
     data_dir: Path = Option(Path("./workflows"), "--data-dir", help="workflow persistence directory"),
     addr: Optional[str] = Option(None, "--addr", "-a", help="address to bind to"),
+    workspace: Optional[List[Path]] = Option(
+        None, "--workspace", "-w", help="directory exposed to the file browser, can be repeated"
+    ),
 ) -> None:
     """Start the webui service."""
     registry = build_node_registry()
@@ -39,6 +74,7 @@ def main(
     # Resolve config — CLI flags win over config file defaults.
     resolved_addr = addr or webui_config.addr
     resolved_frontend = str(frontend_dir or webui_config.frontend_dir or _default_www())
+    resolved_workspaces = [str(p) for p in workspace or webui_config.workspace_roots]
 
     async def _wrapper() -> None:
         await start_service(
@@ -47,6 +83,11 @@ def main(
             resolved_addr,
             registry_json,
             list(webui_config.allowed_origins),
+            resolved_workspaces,
+            _checkpoint_before_write(),
+            collect_stats,
+            webui_config.stats_ttl,
+            _tool_caller(),
         )
 
     run(_wrapper())
@@ -18,6 +18,10 @@ class WebuiConfig:
     queue_max: int = 64
     history_max: int = 256
     persist_workflows: bool = True
+    workspace_roots: tuple[str, ...] = ()  # directories exposed by the /api/files endpoints
+    checkpoint_before_write: bool = True  # save a fabricatio-checkpoint before editing a file
+    stats_ttl: float = 10.0  # seconds the package sections of /api/stats are cached for
+    tool_console: bool = True  # run MCP tool calls from the /ws/tools websocket
 
 
 webui_config = CONFIG.load("webui", WebuiConfig)
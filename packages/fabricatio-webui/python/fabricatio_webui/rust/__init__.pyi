@@ -6,14 +6,45 @@ import pathlib
 import typing
 
 __all__ = [
+    "ToolEventSink",
     "start_service",
 ]
 
+class ToolEventSink:
+    r"""Passed to the tool caller, forwards the events of a call to the browser."""
+    def __call__(self, event: builtins.str) -> None:
+        r"""Sends an event of the call, ignored once the call is over.
+
+        Args:
+            event: The event as a JSON object string, `{"type": "progress", ...}` or
+                `{"type": "log", ...}` as `MCPManager.call_tool` reports them.
+
+        Raises:
+            ValueError: If the event is not one of those.
+        """
+
 def start_service(
     frontend_dir: builtins.str | os.PathLike | pathlib.Path,
     data_dir: builtins.str | os.PathLike | pathlib.Path,
     addr: builtins.str,
     node_registry_json: builtins.str,
     allowed_origins: typing.Sequence[builtins.str],
+    workspace_roots: typing.Sequence[builtins.str | os.PathLike | pathlib.Path] = [],
+    before_write: typing.Optional[typing.Any] = None,
+    stats_provider: typing.Optional[typing.Any] = None,
+    stats_ttl: builtins.float = 10.0,
+    tool_caller: typing.Optional[typing.Any] = None,
 ) -> typing.Awaitable[None]:
-    r"""Starts the web UI service with the given frontend and data directories."""
+    r"""Starts the web UI service with the given frontend and data directories.
+
+    The `/api/files` endpoints give access to the files under `workspace_roots` only.
+    `before_write`, if given, is called with the workspace root and the target path before
+    a file is written, typically to save a checkpoint, and may return the checkpoint ID.
+    `stats_provider`, if given, is called without arguments by `/api/stats` and returns the
+    sections of the document as a JSON object string, or a coroutine resolving to one; its
+    result is cached for `stats_ttl` seconds. `tool_caller`, if given, runs the tool calls of
+    `/ws/tools`: it is called with the server, the tool, the arguments as a JSON object string
+    and a `ToolEventSink` receiving the progress and output of the call, and returns the result
+    as a JSON string, or a coroutine resolving to one. Browsers can only open `/ws/tools` from
+    `allowed_origins`, where `*` matches any part of the origin without a `/`, such as a port.
+    """
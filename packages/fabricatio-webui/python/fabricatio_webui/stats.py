@@ -0,0 +1,98 @@
+"""Statistics of the installed packages, served by the `/api/stats` endpoint.
+
+A section is only collected if its package is installed, and a section that fails to be
+collected reports its error instead of failing the whole document.
+"""
+
+import asyncio
+import inspect
+from typing import Any, Callable, Dict, Iterable, Optional
+
+import orjson
+from fabricatio_core import TEMPLATE_MANAGER
+from fabricatio_core.journal import logger
+
+
+def _memory() -> Dict[str, Any]:
+    """Summarize the memory stores."""
+    from fabricatio_memory.inited_memory_service import get_memory_service
+
+    service = get_memory_service()
+    stores = {}
+    for name in service.list_stores():
+        stats = service.get_store(name).stats()
+        stores[name] = {
+            "total_memories": stats.total_memories,
+            "avg_importance": stats.avg_importance,
+            "avg_access_count": stats.avg_access_count,
+            "avg_age_days": stats.avg_age_days,
+            "top_tags": stats.tag_frequency[:10],
+        }
+    return {"total_memories": sum(store["total_memories"] for store in stores.values()), "stores": stores}
+
+
+def _checkpoints() -> Dict[str, Any]:
+    """Report the disk usage of the checkpoint stores."""
+    from fabricatio_checkpoint.inited_service import get_checkpoint_service
+
+    usage = {str(workspace): size for workspace, size in get_checkpoint_service().disk_usage().items()}
+    return {"total_bytes": sum(usage.values()), "workspaces": usage}
+
+
+def _templates() -> Dict[str, Any]:
+    """Count the registered templates."""
+    return {
+        "count": TEMPLATE_MANAGER.template_count,
+        "stores": [str(store) for store in TEMPLATE_MANAGER.templates_stores],
+    }
+
+
+async def _mcp() -> Dict[str, Any]:
+    """Ping the MCP servers."""
+    from fabricatio_tool.mcp import get_global_mcp_manager
+
+    manager = await get_global_mcp_manager()
+    servers = manager.server_list()
+    pings = await asyncio.gather(*(manager.ping(server) for server in servers), return_exceptions=True)
+    return {
+        "servers": {
+            server: {"healthy": False, "error": str(ping)} if isinstance(ping, BaseException) else {"healthy": ping}
+            for server, ping in zip(servers, pings, strict=True)
+        },
+        "failed": manager.failed_servers(),
+    }
+
+
+SECTIONS: Dict[str, Callable[[], Any]] = {
+    "memory": _memory,
+    "checkpoints": _checkpoints,
+    "templates": _templates,
+    "mcp": _mcp,
+}
+"""The collectors of the sections, by name. Synchronous ones run in a worker thread."""
+
+
+async def collect_stats(sections: Optional[Iterable[str]] = None) -> str:
+    """Collect the statistics sections as a JSON object string.
+
+    Args:
+        sections: Names of the sections to collect, all of `SECTIONS` by default.
+
+    Returns:
+        A JSON object mapping the names of the sections to their statistics. Sections of
+        packages that are not installed are left out, failing ones hold an `error` message.
+    """
+    document = {}
+    for name in SECTIONS if sections is None else sections:
+        collect = SECTIONS[name]
+        try:
+            if inspect.iscoroutinefunction(collect):
+                document[name] = await collect()
+            else:
+                document[name] = await asyncio.to_thread(collect)
+        except ImportError:
+            continue
+        except Exception as e:  # noqa: BLE001
+            logger.warn(f"Failed to collect the {name} statistics: {e}")
+            document[name] = {"error": str(e)}
+    return orjson.dumps(document).decode()
@@ -0,0 +1,29 @@
+"""MCP tool calls of the tool console, served by the `/ws/tools` websocket."""
+
+from typing import Callable
+
+import orjson
+
+
+async def call_mcp_tool(server: str, tool: str, arguments: str, on_event: Callable[[str], None]) -> str:
+    """Call a tool of an MCP server, reporting its progress and output as they arrive.
+
+    Args:
+        server: The ID of the MCP server.
+        tool: The name of the tool.
+        arguments: The arguments of the tool as a JSON object string.
+        on_event: Receives each progress notification and log message of the server as a JSON object string.
+
+    Returns:
+        A JSON object string holding the `content` strings the tool returned.
+    """
+    from fabricatio_tool.mcp import get_global_mcp_manager
+
+    manager = await get_global_mcp_manager()
+    content = await manager.call_tool(
+        server,
+        tool,
+        orjson.loads(arguments),
+        on_event=lambda event: on_event(orjson.dumps(event).decode()),
+    )
+    return orjson.dumps({"content": content}).decode()
@@ -1,7 +1,56 @@
 """Tests for the webui."""
 
+from typing import Callable, List
+
+import orjson
+import pytest
+from fabricatio_core import TEMPLATE_MANAGER
 from fabricatio_mock.models.mock_role import LLMTestRole
+from fabricatio_webui import stats
+from fabricatio_webui.stats import collect_stats
+from fabricatio_webui.tools import call_mcp_tool
 
 
 class WebuiRole(LLMTestRole):
     """Test role that combines LLMTestRole with Webui for testing."""
+
+
+@pytest.mark.asyncio
+async def test_collect_stats(monkeypatch: pytest.MonkeyPatch) -> None:
+    """Sections of missing packages are left out and failing ones report their error."""
+
+    def missing() -> dict:
+        raise ImportError("not installed")
+
+    def broken() -> dict:
+        raise RuntimeError("store is locked")
+
+    monkeypatch.setitem(stats.SECTIONS, "missing", missing)
+    monkeypatch.setitem(stats.SECTIONS, "broken", broken)
+
+    document = orjson.loads(await collect_stats(["templates", "missing", "broken"]))
+    assert document["templates"]["count"] == TEMPLATE_MANAGER.template_count
+    assert "missing" not in document
+    assert document["broken"] == {"error": "store is locked"}
+
+
+@pytest.mark.asyncio
+async def test_call_mcp_tool(monkeypatch: pytest.MonkeyPatch) -> None:
+    """The arguments are decoded, the events and the result encoded as JSON strings."""
+    mcp = pytest.importorskip("fabricatio_tool.mcp")
+
+    class Manager:
+        async def call_tool(self, server: str, tool: str, arguments: dict, on_event: Callable[[dict], None]) -> list:
+            on_event({"type": "progress", "progress": 1.0, "total": 2.0, "message": None})
+            return [f"{server}/{tool}: {arguments['pattern']}"]
+
+    async def manager() -> Manager:
+        return Manager()
+
+    monkeypatch.setattr(mcp, "get_global_mcp_manager", manager)
+    events: List[str] = []
+    result = await call_mcp_tool("fs", "search", '{"pattern": "*.rs"}', events.append)
+    assert orjson.loads(result) == {"content": ["fs/search: *.rs"]}
+    assert [orjson.loads(event) for event in events] == [
+        {"type": "progress", "progress": 1.0, "total": 2.0, "message": None}
+    ]
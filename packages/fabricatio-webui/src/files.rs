@@ -0,0 +1,246 @@
+//! Browsing and editing of the files under the configured workspace roots.
+//!
+//! Paths are always given relative to a root, identified by its index in the configured
+//! list. Absolute paths, `.` and `..` components, and symlinks leading out of the root are
+//! rejected.
+
+use crate::state::AppState;
+use crate::types::*;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use fabricatio_logger::*;
+use pyo3::prelude::*;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use utils::file_walk::{self, WalkRules};
+use utils::{project, workspace_path};
+
+/// Largest file served by the read endpoint, in bytes.
+const MAX_READ_BYTES: u64 = 2 * 1024 * 1024;
+
+type ApiError = (StatusCode, String);
+
+/// The workspace roots exposed to the file endpoints.
+pub struct FileAccess {
+    roots: Vec<PathBuf>,
+    /// Called with the workspace root and the target path before a file is written,
+    /// returns the ID of the checkpoint it saved, if any.
+    before_write: Option<Py<PyAny>>,
+}
+
+impl FileAccess {
+    /// Canonicalizes the roots, which must exist.
+    pub fn new(roots: Vec<PathBuf>, before_write: Option<Py<PyAny>>) -> std::io::Result<Self> {
+        let roots = roots
+            .iter()
+            .map(|root| root.canonicalize())
+            .collect::<std::io::Result<_>>()?;
+        Ok(Self {
+            roots,
+            before_write,
+        })
+    }
+
+    /// Resolves a path relative to a root, returning the root and the joined path.
+    fn resolve(&self, root: usize, path: &str) -> Result<(&Path, PathBuf), ApiError> {
+        let base = self.roots.get(root).ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("workspace root {root} not configured"),
+            )
+        })?;
+
+        // Only plain names are accepted, `confine` then catches the symlinks leading out.
+        let relative = Path::new(path);
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("path '{path}' escapes the workspace root"),
+            ));
+        }
+        let resolved = workspace_path::confine(base, relative).map_err(|e| match e.kind() {
+            ErrorKind::PermissionDenied => (
+                StatusCode::FORBIDDEN,
+                format!("path '{path}' escapes the workspace root"),
+            ),
+            _ => io_error(path, e),
+        })?;
+        Ok((base, resolved))
+    }
+
+    /// Runs the checkpoint callback, returning the ID of the saved checkpoint.
+    fn checkpoint(&self, root: &Path, path: &Path) -> Result<Option<String>, ApiError> {
+        let Some(before_write) = &self.before_write else {
+            return Ok(None);
+        };
+        Python::attach(|python| {
+            before_write
+                .call1(python, (root.to_path_buf(), path.to_path_buf()))?
+                .extract::<Option<String>>(python)
+        })
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("checkpoint before writing failed: {e}"),
+            )
+        })
+    }
+}
+
+fn io_error(path: &str, e: std::io::Error) -> ApiError {
+    match e.kind() {
+        ErrorKind::NotFound => (StatusCode::NOT_FOUND, format!("'{path}' not found")),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to access '{path}': {e}"),
+        ),
+    }
+}
+
+/// Renders a path relative to its root with `/` separators.
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// GET /api/files/roots — list the configured workspace roots, in index order.
+pub async fn get_roots(State(state): State<Arc<AppState>>) -> Json<Vec<String>> {
+    Json(
+        state
+            .files
+            .roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect(),
+    )
+}
+
+/// GET /api/files — list a directory of a workspace root.
+///
+/// The entries are walked under the rules of the inspect tree of the tools, so hidden files
+/// and gitignored paths are skipped, and come in file name order.
+pub async fn list_files(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListFilesQuery>,
+) -> Result<Json<Vec<FileEntry>>, ApiError> {
+    let (root, dir) = state.files.resolve(query.root, &query.path)?;
+    if !dir.is_dir() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("'{}' is not a directory", query.path),
+        ));
+    }
+
+    let rules = WalkRules {
+        max_depth: query.max_depth,
+        ..WalkRules::default()
+    };
+    let entries = file_walk::walk(&dir, &rules)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .map(|entry| {
+            let metadata = entry.metadata().ok();
+            FileEntry {
+                path: relative_path(root, entry.path()),
+                is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+                size: metadata.map_or(0, |m| m.len()),
+                depth: entry.depth(),
+            }
+        })
+        .collect();
+    Ok(Json(entries))
+}
+
+/// GET /api/files/project — detect the project a path of a workspace root belongs to.
+///
+/// The search for a manifest goes up from the path to the root, not beyond, and the response
+/// is `null` if there is none.
+pub async fn get_project(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ProjectQuery>,
+) -> Result<Json<Option<ProjectInfo>>, ApiError> {
+    let (root, path) = state.files.resolve(query.root, &query.path)?;
+    let detected = project::detect(&path, Some(root)).map_err(|e| io_error(&query.path, e))?;
+    Ok(Json(detected.map(|project| ProjectInfo {
+        path: relative_path(root, &project.root),
+        ignore: project.ignore().into_iter().map(str::to_string).collect(),
+        kinds: project.kinds,
+        name: project.name,
+        version: project.version,
+        description: project.description,
+    })))
+}
+
+/// GET /api/files/content — read a text file of a workspace root.
+pub async fn read_file(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FileQuery>,
+) -> Result<Json<FileContent>, ApiError> {
+    let (_, file) = state.files.resolve(query.root, &query.path)?;
+    let metadata = std::fs::metadata(&file).map_err(|e| io_error(&query.path, e))?;
+    if metadata.is_dir() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("'{}' is a directory", query.path),
+        ));
+    }
+    if metadata.len() > MAX_READ_BYTES {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("'{}' is larger than {MAX_READ_BYTES} bytes", query.path),
+        ));
+    }
+
+    let content = std::fs::read_to_string(&file).map_err(|e| match e.kind() {
+        ErrorKind::InvalidData => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("'{}' is not a UTF-8 text file", query.path),
+        ),
+        _ => io_error(&query.path, e),
+    })?;
+    Ok(Json(FileContent {
+        root: query.root,
+        path: query.path,
+        content,
+    }))
+}
+
+/// PUT /api/files/content — write a text file of a workspace root.
+///
+/// A checkpoint of the workspace is saved first if a checkpoint callback is configured,
+/// and the file is left untouched if saving it fails.
+pub async fn write_file(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WriteFileRequest>,
+) -> Result<Json<WriteFileResponse>, ApiError> {
+    tokio::task::spawn_blocking(move || {
+        let (root, file) = state.files.resolve(req.root, &req.path)?;
+        if file.is_dir() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("'{}' is a directory", req.path),
+            ));
+        }
+
+        let checkpoint = state.files.checkpoint(root, &file)?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(&req.path, e))?;
+        }
+        std::fs::write(&file, req.content).map_err(|e| io_error(&req.path, e))?;
+        info!("Wrote {} via the file editor", file.display());
+        Ok(Json(WriteFileResponse {
+            ok: true,
+            checkpoint,
+        }))
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+}
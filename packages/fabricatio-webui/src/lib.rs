@@ -3,7 +3,11 @@
 use pyo3::prelude::*;
 
 mod api;
+mod files;
 mod state;
+mod stats;
+mod tools;
+mod transcripts;
 mod types;
 mod webui;
 mod ws;
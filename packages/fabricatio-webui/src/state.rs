@@ -1,3 +1,7 @@
+use crate::files::FileAccess;
+use crate::stats::StatsSource;
+use crate::tools::ToolCaller;
+use crate::transcripts::TranscriptStore;
 use crate::types::*;
 use fabricatio_logger::*;
 use std::collections::{HashMap, VecDeque};
@@ -18,11 +22,23 @@ pub struct AppState {
     pub active_executions: RwLock<HashMap<String, ExecutionStatus>>,
     pub ws_sessions: RwLock<HashMap<String, mpsc::UnboundedSender<WsMessage>>>,
     pub workflows: RwLock<HashMap<String, WorkflowJson>>,
+    pub files: FileAccess,
+    pub transcripts: TranscriptStore,
+    pub stats: StatsSource,
+    pub tools: ToolCaller,
+    /// Origins allowed to open the tool console, `*` matching any part without a `/`
+    pub allowed_origins: Vec<String>,
     data_dir: PathBuf,
 }
 
 impl AppState {
-    pub fn new(data_dir: PathBuf) -> Self {
+    pub fn new(
+        data_dir: PathBuf,
+        files: FileAccess,
+        stats: StatsSource,
+        tools: ToolCaller,
+        allowed_origins: Vec<String>,
+    ) -> Self {
         let workflows = Self::load_workflows_from_disk(&data_dir);
         Self {
             node_registry: RwLock::new(Vec::new()),
@@ -31,6 +47,11 @@ impl AppState {
             active_executions: RwLock::new(HashMap::new()),
             ws_sessions: RwLock::new(HashMap::new()),
             workflows: RwLock::new(workflows),
+            files,
+            transcripts: TranscriptStore::new(&data_dir),
+            stats,
+            tools,
+            allowed_origins,
             data_dir,
         }
     }
@@ -0,0 +1,138 @@
+//! Aggregate statistics for the dashboard of the home page.
+//!
+//! The sections reported by other packages, such as memory, checkpoint or MCP usage, come
+//! from a Python provider returning a JSON object. They are cached for a configurable time,
+//! so that polling the endpoint does not query every store on each request.
+
+use crate::state::AppState;
+use crate::types::*;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::get_current_locals;
+use pyo3_async_runtimes::{TaskLocals, into_future_with_locals};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+type ApiError = (StatusCode, String);
+
+/// Sections collected by the provider, with the time they were collected at.
+struct Collected {
+    at: Instant,
+    collected_at: String,
+    sections: Map<String, Value>,
+}
+
+/// The provider of the statistics sections and their cache.
+pub struct StatsSource {
+    /// Called without arguments, returns a JSON object as a string or a coroutine resolving
+    /// to one
+    provider: Option<Py<PyAny>>,
+    /// Event loop the coroutines returned by the provider run on
+    locals: Option<TaskLocals>,
+    ttl: Duration,
+    /// Held while collecting, so concurrent requests wait for a single collection
+    cache: Mutex<Option<Collected>>,
+}
+
+fn provider_error(e: impl std::fmt::Display) -> ApiError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("collecting statistics failed: {e}"),
+    )
+}
+
+impl StatsSource {
+    /// Coroutines returned by the provider run on the event loop running when this is called.
+    pub fn new(python: Python, provider: Option<Py<PyAny>>, ttl: Duration) -> PyResult<Self> {
+        let locals = match provider {
+            Some(_) => Some(get_current_locals(python)?),
+            None => None,
+        };
+        Ok(Self {
+            provider,
+            locals,
+            ttl,
+            cache: Mutex::new(None),
+        })
+    }
+
+    /// Calls the provider, awaiting the coroutine it returns if any.
+    async fn collect(&self) -> Result<Map<String, Value>, ApiError> {
+        let (Some(provider), Some(locals)) = (&self.provider, &self.locals) else {
+            return Ok(Map::new());
+        };
+        let pending = Python::attach(
+            |python| -> PyResult<BoxFuture<'static, PyResult<Py<PyAny>>>> {
+                let result = provider.bind(python).call0()?;
+                if result.hasattr("__await__")? {
+                    Ok(into_future_with_locals(locals, result)?.boxed())
+                } else {
+                    let result = result.unbind();
+                    Ok(async move { Ok(result) }.boxed())
+                }
+            },
+        )
+        .map_err(provider_error)?;
+
+        let result = pending.await.map_err(provider_error)?;
+        let json =
+            Python::attach(|python| result.extract::<String>(python)).map_err(provider_error)?;
+        serde_json::from_str(&json).map_err(provider_error)
+    }
+
+    /// Returns the time the sections were collected at, whether they come from the cache,
+    /// and the sections. They are collected anew if the cache is stale or `refresh` is set.
+    async fn sections(
+        &self,
+        refresh: bool,
+    ) -> Result<(String, bool, Map<String, Value>), ApiError> {
+        let mut cache = self.cache.lock().await;
+        if !refresh
+            && let Some(collected) = cache.as_ref()
+            && collected.at.elapsed() < self.ttl
+        {
+            return Ok((
+                collected.collected_at.clone(),
+                true,
+                collected.sections.clone(),
+            ));
+        }
+
+        let sections = self.collect().await?;
+        let collected_at = chrono::Utc::now().to_rfc3339();
+        *cache = Some(Collected {
+            at: Instant::now(),
+            collected_at: collected_at.clone(),
+            sections: sections.clone(),
+        });
+        Ok((collected_at, false, sections))
+    }
+}
+
+/// GET /api/stats — everything the home page shows, in one document.
+///
+/// The server counters are always current, the sections of the provider may come from the
+/// cache unless `refresh=true` is given.
+pub async fn get_stats(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    let (collected_at, cached, sections) = state.stats.sections(query.refresh).await?;
+    Ok(Json(StatsResponse {
+        collected_at,
+        cached,
+        server: ServerStats {
+            queue_length: state.queue_len(),
+            running_count: state.active_count(),
+            history_length: state.history.read().map(|h| h.len()).unwrap_or(0),
+            workflow_count: state.workflows.read().map(|w| w.len()).unwrap_or(0),
+        },
+        sections,
+    }))
+}
@@ -0,0 +1,238 @@
+//! The `/ws/tools` bridge between the tool console of the browser and the MCP servers.
+//!
+//! The MCP servers belong to the Python side, so tool calls go through a Python caller. It
+//! receives the server, the tool, the arguments as a JSON object string and a `ToolEventSink`
+//! it passes each progress notification and output chunk to as they arrive, and returns the
+//! structured result as a JSON string, or a coroutine resolving to one. Each invocation is
+//! answered with a `started` message, `progress` and `output` messages, and a final `result`
+//! or `error` message, all carrying the `call_id` of the invocation.
+
+use crate::state::AppState;
+use crate::types::*;
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{State, WebSocketUpgrade};
+use axum::http::header::ORIGIN;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use futures::{FutureExt, SinkExt, StreamExt};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::get_current_locals;
+use pyo3_async_runtimes::{TaskLocals, into_future_with_locals};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use pyo3_stub_gen::derive::*;
+
+/// Passed to the tool caller, forwards the events of a call to the browser.
+#[cfg_attr(feature = "stubgen", gen_stub_pyclass)]
+#[pyclass]
+pub struct ToolEventSink {
+    events: UnboundedSender<ToolEvent>,
+}
+
+#[cfg_attr(feature = "stubgen", gen_stub_pymethods)]
+#[pymethods]
+impl ToolEventSink {
+    /// Sends an event of the call, ignored once the call is over.
+    ///
+    /// Args:
+    ///     event: The event as a JSON object string, `{"type": "progress", ...}` or
+    ///         `{"type": "log", ...}` as `MCPManager.call_tool` reports them.
+    ///
+    /// Raises:
+    ///     ValueError: If the event is not one of those.
+    fn __call__(&self, event: &str) -> PyResult<()> {
+        let event = serde_json::from_str(event)
+            .map_err(|e| PyValueError::new_err(format!("Invalid tool event: {e}")))?;
+        let _ = self.events.send(event);
+        Ok(())
+    }
+}
+
+/// The Python caller running the tool calls.
+pub struct ToolCaller {
+    caller: Option<Py<PyAny>>,
+    /// Event loop the coroutines returned by the caller run on
+    locals: Option<TaskLocals>,
+}
+
+impl ToolCaller {
+    /// Coroutines returned by the caller run on the event loop running when this is called.
+    pub fn new(python: Python, caller: Option<Py<PyAny>>) -> PyResult<Self> {
+        let locals = match caller {
+            Some(_) => Some(get_current_locals(python)?),
+            None => None,
+        };
+        Ok(Self { caller, locals })
+    }
+
+    /// Calls the tool, sending the events the caller reports to `events`.
+    async fn call(
+        &self,
+        invocation: &ToolInvocation,
+        events: UnboundedSender<ToolEvent>,
+    ) -> Result<Value, String> {
+        let (Some(caller), Some(locals)) = (&self.caller, &self.locals) else {
+            return Err("no tool caller is configured".to_string());
+        };
+        let arguments = serde_json::to_string(&invocation.arguments).map_err(|e| e.to_string())?;
+        let pending = Python::attach(
+            |python| -> PyResult<BoxFuture<'static, PyResult<Py<PyAny>>>> {
+                let sink = Py::new(python, ToolEventSink { events })?;
+                let result = caller.bind(python).call1((
+                    invocation.server.as_str(),
+                    invocation.tool.as_str(),
+                    arguments,
+                    sink,
+                ))?;
+                if result.hasattr("__await__")? {
+                    Ok(into_future_with_locals(locals, result)?.boxed())
+                } else {
+                    let result = result.unbind();
+                    Ok(async move { Ok(result) }.boxed())
+                }
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+        let result = pending.await.map_err(|e| e.to_string())?;
+        let json =
+            Python::attach(|python| result.extract::<String>(python)).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| format!("invalid tool result: {e}"))
+    }
+}
+
+/// Runs an invocation, sending its messages as they come.
+async fn run(state: Arc<AppState>, invocation: ToolInvocation, tx: UnboundedSender<ToolMessage>) {
+    let call_id = invocation
+        .call_id
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let _ = tx.send(ToolMessage::Started {
+        call_id: call_id.clone(),
+        server: invocation.server.clone(),
+        tool: invocation.tool.clone(),
+    });
+
+    let (events_tx, mut events) = unbounded_channel();
+    let call = state.tools.call(&invocation, events_tx);
+    tokio::pin!(call);
+    let result = loop {
+        tokio::select! {
+            biased;
+            Some(event) = events.recv() => {
+                let _ = tx.send(event.into_message(&call_id));
+            }
+            result = &mut call => break result,
+        }
+    };
+    // Events sent right before the result are still queued.
+    while let Ok(event) = events.try_recv() {
+        let _ = tx.send(event.into_message(&call_id));
+    }
+
+    let _ = tx.send(match result {
+        Ok(result) => ToolMessage::Result { call_id, result },
+        Err(error) => {
+            fabricatio_logger::warn!(
+                "Tool call {}/{} failed: {error}",
+                invocation.server,
+                invocation.tool
+            );
+            ToolMessage::Error {
+                call_id: Some(call_id),
+                error,
+            }
+        }
+    });
+}
+
+/// Whether an origin matches a pattern of `allowed_origins`, where a `*` matches any part of
+/// the origin without a `/`, such as the port in `http://localhost:*`.
+fn origin_matches(pattern: &str, origin: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == origin,
+        Some((prefix, suffix)) => origin
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .is_some_and(|wildcard| !wildcard.contains('/')),
+    }
+}
+
+/// GET /ws/tools — run tool calls and stream their progress and output.
+///
+/// CORS does not apply to websockets, so the handshake of a browser is refused unless its
+/// `Origin` is allowed, or any page open in the browser could run tools. Clients outside of
+/// browsers send no `Origin` and are accepted.
+pub async fn tools_ws_handler(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    if let Some(origin) = headers.get(ORIGIN) {
+        let origin = origin.to_str().unwrap_or_default();
+        if !state
+            .allowed_origins
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin))
+        {
+            fabricatio_logger::warn!("Refused a tool console connection from origin '{origin}'");
+            return (
+                StatusCode::FORBIDDEN,
+                format!("origin '{origin}' is not allowed"),
+            )
+                .into_response();
+        }
+    }
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
+    let (mut sender, mut receiver) = socket.split();
+    let (tx, mut rx) = unbounded_channel::<ToolMessage>();
+
+    // Writer task: forward from channel to websocket
+    let mut send_task = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            if let Ok(text) = serde_json::to_string(&msg)
+                && sender.send(Message::Text(text.into())).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    // Reader task: run each invocation concurrently, the calls are aborted on disconnect
+    let mut recv_task = tokio::spawn(async move {
+        let mut calls = JoinSet::new();
+        while let Some(Ok(msg)) = receiver.next().await {
+            while calls.try_join_next().is_some() {}
+            match msg {
+                Message::Text(text) => match serde_json::from_str::<ToolInvocation>(&text) {
+                    Ok(invocation) => {
+                        calls.spawn(run(state.clone(), invocation, tx.clone()));
+                    }
+                    Err(e) => {
+                        let _ = tx.send(ToolMessage::Error {
+                            call_id: None,
+                            error: format!("invalid tool invocation: {e}"),
+                        });
+                    }
+                },
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    // Wait for either task to finish
+    tokio::select! {
+        _ = &mut send_task => recv_task.abort(),
+        _ = &mut recv_task => send_task.abort(),
+    }
+}
@@ -0,0 +1,233 @@
+//! Persistence and replay of agent session transcripts.
+//!
+//! Each session is stored under `<data_dir>/transcripts` as a `<id>.json` summary and a
+//! `<id>.jsonl` file its entries are appended to, so recording a long session never
+//! rewrites what was already recorded. Session IDs are UUIDs minted by the server, any
+//! other ID is reported as not found.
+
+use crate::state::AppState;
+use crate::types::*;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use fabricatio_logger::*;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+type ApiError = (StatusCode, String);
+
+/// The transcripts saved in the data directory.
+pub struct TranscriptStore {
+    dir: PathBuf,
+    /// Serializes the updates of the summaries
+    lock: Mutex<()>,
+}
+
+impl TranscriptStore {
+    pub fn new(data_dir: &std::path::Path) -> Self {
+        Self {
+            dir: data_dir.join("transcripts"),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the summary and entries files of a session, if the ID is well-formed.
+    fn paths(&self, id: &str) -> Result<(PathBuf, PathBuf), ApiError> {
+        let id = Uuid::parse_str(id)
+            .map_err(|_| not_found(id))?
+            .hyphenated()
+            .to_string();
+        Ok((
+            self.dir.join(format!("{id}.json")),
+            self.dir.join(format!("{id}.jsonl")),
+        ))
+    }
+
+    fn read_summary(&self, id: &str) -> Result<TranscriptSummary, ApiError> {
+        let (summary, _) = self.paths(id)?;
+        let content = fs::read_to_string(&summary).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => not_found(id),
+            _ => internal(e),
+        })?;
+        serde_json::from_str(&content).map_err(internal)
+    }
+
+    /// Writes a summary through a temporary file, so readers never see a partial one.
+    fn write_summary(&self, summary: &TranscriptSummary) -> Result<(), ApiError> {
+        let (path, _) = self.paths(&summary.id)?;
+        fs::create_dir_all(&self.dir).map_err(internal)?;
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(summary).map_err(internal)?;
+        fs::write(&tmp, json).map_err(internal)?;
+        fs::rename(&tmp, &path).map_err(internal)
+    }
+
+    pub fn create(&self, req: CreateTranscriptRequest) -> Result<TranscriptSummary, ApiError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let summary = TranscriptSummary {
+            id: Uuid::new_v4().to_string(),
+            title: req.title,
+            execution_id: req.execution_id,
+            created_at: now.clone(),
+            updated_at: now,
+            entry_count: 0,
+            usage: TokenUsage::default(),
+        };
+        self.write_summary(&summary)?;
+        Ok(summary)
+    }
+
+    /// Lists the summaries of all sessions, most recently updated first.
+    pub fn list(&self) -> Vec<TranscriptSummary> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let mut summaries: Vec<TranscriptSummary> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str(&content).ok())
+                    .or_else(|| {
+                        warn!("Skipping unreadable transcript {}", path.display());
+                        None
+                    })
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries
+    }
+
+    pub fn get(&self, id: &str) -> Result<Transcript, ApiError> {
+        let summary = self.read_summary(id)?;
+        let (_, entries) = self.paths(id)?;
+        let entries = match fs::read_to_string(&entries) {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| {
+                    serde_json::from_str(line)
+                        .inspect_err(|e| warn!("Skipping malformed entry of transcript {id}: {e}"))
+                        .ok()
+                })
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => vec![],
+            Err(e) => return Err(internal(e)),
+        };
+        Ok(Transcript { summary, entries })
+    }
+
+    /// Appends entries to a session, stamping those without a timestamp.
+    pub fn append(
+        &self,
+        id: &str,
+        entries: Vec<TranscriptEntry>,
+    ) -> Result<TranscriptSummary, ApiError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut summary = self.read_summary(id)?;
+        let (_, path) = self.paths(id)?;
+
+        let mut lines = String::new();
+        for entry in entries.into_iter().map(TranscriptEntry::with_timestamp) {
+            if let Some(usage) = entry.usage() {
+                summary.usage.add(usage);
+            }
+            lines.push_str(&serde_json::to_string(&entry).map_err(internal)?);
+            lines.push('\n');
+            summary.entry_count += 1;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(internal)?;
+
+        summary.updated_at = chrono::Utc::now().to_rfc3339();
+        self.write_summary(&summary)?;
+        Ok(summary)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), ApiError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (summary, entries) = self.paths(id)?;
+        fs::remove_file(&summary).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => not_found(id),
+            _ => internal(e),
+        })?;
+        if let Err(e) = fs::remove_file(&entries)
+            && e.kind() != ErrorKind::NotFound
+        {
+            warn!("Failed to remove {}: {e}", entries.display());
+        }
+        Ok(())
+    }
+}
+
+fn not_found(id: &str) -> ApiError {
+    (
+        StatusCode::NOT_FOUND,
+        format!("transcript '{id}' not found"),
+    )
+}
+
+fn internal<E: std::fmt::Display>(e: E) -> ApiError {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// Runs a blocking store operation off the async runtime.
+async fn blocking<T, F>(state: Arc<AppState>, f: F) -> Result<Json<T>, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&TranscriptStore) -> Result<T, ApiError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&state.transcripts).map(Json))
+        .await
+        .map_err(internal)?
+}
+
+/// GET /api/transcripts — list the recorded sessions, most recently updated first.
+pub async fn list_transcripts(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TranscriptSummary>>, ApiError> {
+    blocking(state, |store| Ok(store.list())).await
+}
+
+/// POST /api/transcripts — start recording a session.
+pub async fn create_transcript(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateTranscriptRequest>,
+) -> Result<Json<TranscriptSummary>, ApiError> {
+    blocking(state, move |store| store.create(req)).await
+}
+
+/// GET /api/transcripts/:id — a session with all its entries, for replay.
+pub async fn get_transcript(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Transcript>, ApiError> {
+    blocking(state, move |store| store.get(&id)).await
+}
+
+/// POST /api/transcripts/:id/entries — append messages, tool calls and tool results.
+pub async fn append_entries(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(entries): Json<Vec<TranscriptEntry>>,
+) -> Result<Json<TranscriptSummary>, ApiError> {
+    blocking(state, move |store| store.append(&id, entries)).await
+}
+
+/// DELETE /api/transcripts/:id — delete a recorded session.
+pub async fn delete_transcript(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    blocking(state, move |store| store.delete(&id)).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
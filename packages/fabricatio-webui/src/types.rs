@@ -112,6 +112,85 @@ pub enum ExecutionState {
     Cancelled,
 }
 
+// ── Files ────────────────────────────────────────────────────────────────────
+
+fn default_max_depth() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListFilesQuery {
+    #[serde(default)]
+    pub root: usize,
+    /// Directory to list, relative to the root
+    #[serde(default)]
+    pub path: String,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileQuery {
+    #[serde(default)]
+    pub root: usize,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectQuery {
+    #[serde(default)]
+    pub root: usize,
+    /// File or directory whose project is detected, relative to the root
+    #[serde(default)]
+    pub path: String,
+}
+
+/// The project a path belongs to, as `detect_project` reports it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectInfo {
+    /// Directory holding the manifests, relative to the root, `/`-separated
+    pub path: String,
+    pub kinds: Vec<utils::project::ProjectKind>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    /// Patterns of the build outputs and caches of the project, in the `.gitignore` syntax
+    pub ignore: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// Path relative to the root, `/`-separated
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Depth below the listed directory, starting at 1
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileContent {
+    pub root: usize,
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteFileRequest {
+    #[serde(default)]
+    pub root: usize,
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteFileResponse {
+    pub ok: bool,
+    /// ID of the checkpoint saved before writing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
+}
+
 // ── WebSocket Messages ───────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -205,3 +284,242 @@ pub struct WsSubmit {
     #[serde(default)]
     pub task_input: Option<serde_json::Value>,
 }
+
+// ── Tool Console ─────────────────────────────────────────────────────────────
+
+/// A tool call requested over `/ws/tools`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolInvocation {
+    /// Chosen by the client to tell the messages of concurrent calls apart, generated if absent
+    #[serde(default)]
+    pub call_id: Option<String>,
+    /// Name of the MCP server
+    pub server: String,
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What the MCP layer reports while a tool runs, as `MCPManager.call_tool` passes it on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolEvent {
+    Progress {
+        progress: f64,
+        #[serde(default)]
+        total: Option<f64>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// A log message of the server, which servers use to stream partial output
+    Log {
+        level: String,
+        #[serde(default)]
+        logger: Option<String>,
+        data: serde_json::Value,
+    },
+}
+
+/// Messages of `/ws/tools`, each tagged with the call it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolMessage {
+    Started {
+        call_id: String,
+        server: String,
+        tool: String,
+    },
+    Progress {
+        call_id: String,
+        progress: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// A chunk of partial output
+    Output {
+        call_id: String,
+        level: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        logger: Option<String>,
+        data: serde_json::Value,
+    },
+    /// The structured result, ending the call
+    Result {
+        call_id: String,
+        result: serde_json::Value,
+    },
+    /// The failure of the call, or of a message that could not be read as an invocation
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        call_id: Option<String>,
+        error: String,
+    },
+}
+
+impl ToolEvent {
+    pub fn into_message(self, call_id: &str) -> ToolMessage {
+        let call_id = call_id.to_string();
+        match self {
+            Self::Progress {
+                progress,
+                total,
+                message,
+            } => ToolMessage::Progress {
+                call_id,
+                progress,
+                total,
+                message,
+            },
+            Self::Log {
+                level,
+                logger,
+                data,
+            } => ToolMessage::Output {
+                call_id,
+                level,
+                logger,
+                data,
+            },
+        }
+    }
+}
+
+// ── Transcripts ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// One step of an agent session, in the order it happened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Message {
+        /// `system`, `user`, `assistant` or `tool`
+        role: String,
+        content: String,
+        /// Tokens spent producing the message, for LLM replies
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        usage: Option<TokenUsage>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timestamp: Option<String>,
+    },
+    ToolCall {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        call_id: Option<String>,
+        name: String,
+        #[serde(default)]
+        arguments: serde_json::Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timestamp: Option<String>,
+    },
+    ToolResult {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        call_id: Option<String>,
+        name: String,
+        #[serde(default)]
+        result: serde_json::Value,
+        #[serde(default)]
+        is_error: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timestamp: Option<String>,
+    },
+}
+
+impl TranscriptEntry {
+    /// Stamp the entry with the current time unless it already carries one.
+    pub fn with_timestamp(mut self) -> Self {
+        match &mut self {
+            Self::Message { timestamp, .. }
+            | Self::ToolCall { timestamp, .. }
+            | Self::ToolResult { timestamp, .. } => {
+                if timestamp.is_none() {
+                    *timestamp = Some(Utc::now().to_rfc3339());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn usage(&self) -> Option<&TokenUsage> {
+        match self {
+            Self::Message { usage, .. } => usage.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSummary {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Execution the session belongs to, if it was started from a workflow
+    #[serde(default)]
+    pub execution_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub entry_count: usize,
+    /// Tokens spent over the whole session
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    #[serde(flatten)]
+    pub summary: TranscriptSummary,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateTranscriptRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub execution_id: Option<String>,
+}
+
+// ── Stats ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatsQuery {
+    /// Collect the sections anew instead of serving them from the cache
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStats {
+    pub queue_length: usize,
+    pub running_count: usize,
+    pub history_length: usize,
+    pub workflow_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsResponse {
+    /// When the sections were collected, RFC 3339
+    pub collected_at: String,
+    /// Whether the sections were served from the cache
+    pub cached: bool,
+    pub server: ServerStats,
+    /// Sections of the stats provider, such as `memory`, `checkpoints`, `templates` and `mcp`
+    #[serde(flatten)]
+    pub sections: serde_json::Map<String, serde_json::Value>,
+}
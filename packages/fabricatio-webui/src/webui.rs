@@ -1,5 +1,9 @@
 use crate::api;
+use crate::files::{self, FileAccess};
 use crate::state::AppState;
+use crate::stats::{self, StatsSource};
+use crate::tools::{self, ToolCaller, ToolEventSink};
+use crate::transcripts;
 use crate::types::NodeTypeDefinition;
 use crate::ws;
 use axum::Router;
@@ -10,6 +14,7 @@ use pyo3::prelude::*;
 use pyo3_async_runtimes::tokio::future_into_py;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
 
@@ -45,7 +50,28 @@ fn create_router(
         .route("/api/interrupt", post(api::interrupt_execution))
         .route("/api/queue", get(api::get_queue))
         .route("/api/history", get(api::get_history))
+        .route("/api/stats", get(stats::get_stats))
+        .route("/api/files/roots", get(files::get_roots))
+        .route("/api/files", get(files::list_files))
+        .route("/api/files/project", get(files::get_project))
+        .route(
+            "/api/files/content",
+            get(files::read_file).put(files::write_file),
+        )
+        .route(
+            "/api/transcripts",
+            get(transcripts::list_transcripts).post(transcripts::create_transcript),
+        )
+        .route(
+            "/api/transcripts/{id}",
+            get(transcripts::get_transcript).delete(transcripts::delete_transcript),
+        )
+        .route(
+            "/api/transcripts/{id}/entries",
+            post(transcripts::append_entries),
+        )
         .route("/ws", get(ws::ws_handler))
+        .route("/ws/tools", get(tools::tools_ws_handler))
         .fallback_service(static_files)
         .layer(cors)
         .with_state(state)
@@ -57,7 +83,20 @@ fn create_router(
     override_return_type(type_repr = "typing.Awaitable[None]", imports = ("typing",))
 )]
 #[pyfunction]
+#[pyo3(signature = (frontend_dir, data_dir, addr, node_registry_json, allowed_origins, workspace_roots = vec![], before_write = None, stats_provider = None, stats_ttl = 10.0, tool_caller = None))]
 /// Starts the web UI service with the given frontend and data directories.
+///
+/// The `/api/files` endpoints give access to the files under `workspace_roots` only.
+/// `before_write`, if given, is called with the workspace root and the target path before
+/// a file is written, typically to save a checkpoint, and may return the checkpoint ID.
+/// `stats_provider`, if given, is called without arguments by `/api/stats` and returns the
+/// sections of the document as a JSON object string, or a coroutine resolving to one; its
+/// result is cached for `stats_ttl` seconds. `tool_caller`, if given, runs the tool calls of
+/// `/ws/tools`: it is called with the server, the tool, the arguments as a JSON object string
+/// and a `ToolEventSink` receiving the progress and output of the call, and returns the result
+/// as a JSON string, or a coroutine resolving to one. Browsers can only open `/ws/tools` from
+/// `allowed_origins`, where `*` matches any part of the origin without a `/`, such as a port.
+#[allow(clippy::too_many_arguments)]
 fn start_service<'a>(
     py: Python<'a>,
     frontend_dir: PathBuf,
@@ -65,11 +104,27 @@ fn start_service<'a>(
     addr: String,
     node_registry_json: String,
     allowed_origins: Vec<String>,
+    workspace_roots: Vec<PathBuf>,
+    before_write: Option<Py<PyAny>>,
+    stats_provider: Option<Py<PyAny>>,
+    stats_ttl: f64,
+    tool_caller: Option<Py<PyAny>>,
 ) -> PyResult<Bound<'a, PyAny>> {
     let registry: Vec<NodeTypeDefinition> = serde_json::from_str(&node_registry_json)
         .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    let files = FileAccess::new(workspace_roots, before_write).into_pyresult()?;
+    let ttl = Duration::try_from_secs_f64(stats_ttl)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("stats_ttl: {e}")))?;
+    let stats = StatsSource::new(py, stats_provider, ttl)?;
+    let tools = ToolCaller::new(py, tool_caller)?;
 
-    let state = Arc::new(AppState::new(data_dir));
+    let state = Arc::new(AppState::new(
+        data_dir,
+        files,
+        stats,
+        tools,
+        allowed_origins.clone(),
+    ));
     if let Ok(mut reg) = state.node_registry.write() {
         *reg = registry;
     }
@@ -88,5 +143,6 @@ fn start_service<'a>(
 
 pub(crate) fn register(_: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(start_service, m)?)?;
+    m.add_class::<ToolEventSink>()?;
     Ok(())
 }
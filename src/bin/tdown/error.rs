@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::SystemTimeError;
 use thiserror::Error;
 
@@ -20,6 +21,12 @@ pub enum Error {
 
     #[error("Failed to render template: {0}")]
     Render(#[from] indicatif::style::TemplateError),
+
+    #[error("Invalid pack manifest {}: {source}", path.display())]
+    Manifest {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,5 +1,6 @@
 mod download;
 mod error;
+mod pack;
 mod releases;
 mod repo;
 
@@ -61,7 +62,7 @@ enum Commands {
         #[arg(short, long, default_value = TEMPLATES.as_os_str())]
         template_dir: PathBuf,
 
-        /// Show detailed information about each template
+        /// Show detailed information about each template and the installed packs
         #[arg(short, long)]
         detailed: bool,
 
@@ -70,6 +71,16 @@ enum Commands {
         pattern: Option<String>,
     },
 
+    /// Search the installed template packs by name, description or keyword
+    Search {
+        /// The keyword to look for, ignoring case
+        keyword: String,
+
+        /// The directory the packs are installed in
+        #[arg(short, long, default_value = TEMPLATES.as_os_str())]
+        template_dir: PathBuf,
+    },
+
     /// Remove templates from the local directory
     #[command(alias = "rm")]
     Remove {
@@ -188,7 +199,7 @@ fn list_templates(
         sorted_templates.len()
     );
 
-    for (path, relative_path) in sorted_templates {
+    for (path, relative_path) in &sorted_templates {
         if detailed {
             let metadata = path.metadata()?;
             let size = metadata.len();
@@ -211,9 +222,39 @@ fn list_templates(
         }
     }
 
+    if detailed {
+        list_packs(template_dir, &sorted_templates);
+    }
+
     Ok(())
 }
 
+fn list_packs(template_dir: &PathBuf, templates: &[(PathBuf, String)]) {
+    let packs = pack::collect_packs(template_dir);
+    if packs.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} Found {} packs:", "Packs".green().bold(), packs.len());
+    for pack in packs {
+        let count = templates
+            .iter()
+            .filter(|(path, _)| pack.contains(path))
+            .count();
+        let root = match pack.root.strip_prefix(template_dir) {
+            Ok(root) if root.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(root) => root.to_path_buf(),
+            Err(_) => pack.root.clone(),
+        };
+        println!("  {} {}", "→".cyan(), pack);
+        println!(
+            "      {}",
+            format!("{} templates in {}", count, root.display()).dimmed()
+        );
+    }
+}
+
 fn confirm_removal(relative_path: &str) -> error::Result<bool> {
     print!(
         "{} Remove template '{}'? [y/N]: ",
@@ -333,6 +374,11 @@ async fn main() -> Result<(), error::Error> {
             pattern: filter,
         } => list_templates(template_dir, *detailed, filter.as_deref())?,
 
+        Commands::Search {
+            keyword,
+            template_dir,
+        } => pack::search_packs(template_dir, keyword)?,
+
         Commands::Remove {
             templates,
             template_dir,
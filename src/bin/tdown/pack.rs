@@ -0,0 +1,138 @@
+use crate::error::{Error, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// The file describing a template pack, at the root of its directory.
+pub const PACK_MANIFEST: &str = "pack.toml";
+
+/// The metadata of a template pack, read from its `pack.toml`.
+///
+/// ```toml
+/// name = "novel"
+/// version = "1.2.0"
+/// description = "Templates for drafting novels chapter by chapter"
+/// fabricatio = ">=0.20"
+/// keywords = ["writing", "fiction"]
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// The versions of fabricatio the templates are written for, e.g. `>=0.20`.
+    pub fabricatio: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// A template pack installed in a template directory.
+#[derive(Debug)]
+pub struct Pack {
+    pub manifest: PackManifest,
+    /// The directory holding the manifest and the templates of the pack.
+    pub root: PathBuf,
+}
+
+impl Pack {
+    /// Whether the name, description or keywords of the pack contain the keyword, ignoring case.
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+        let manifest = &self.manifest;
+        [&manifest.name, &manifest.description]
+            .into_iter()
+            .chain(&manifest.keywords)
+            .any(|field| field.to_lowercase().contains(&keyword))
+    }
+
+    /// Whether a template belongs to the pack.
+    pub fn contains(&self, template: &Path) -> bool {
+        template.starts_with(&self.root)
+    }
+}
+
+impl Display for Pack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let manifest = &self.manifest;
+        write!(
+            f,
+            "{} {}",
+            manifest.name.bright_green().bold(),
+            manifest.version.bright_blue()
+        )?;
+        if let Some(requirement) = &manifest.fabricatio {
+            write!(f, "  {}", format!("(fabricatio {requirement})").dimmed())?;
+        }
+        if !manifest.description.is_empty() {
+            write!(f, "\n      {}", manifest.description)?;
+        }
+        if !manifest.keywords.is_empty() {
+            write!(f, "\n      {}", manifest.keywords.join(", ").cyan())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the manifest of a pack.
+fn read_manifest(path: &Path) -> Result<PackManifest> {
+    toml::from_str(&fs::read_to_string(path)?).map_err(|source| Error::Manifest {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Finds the packs installed below a directory, sorted by name.
+///
+/// Invalid manifests are reported and skipped, so that one broken pack does not hide the others.
+pub fn collect_packs(dir: &Path) -> Vec<Pack> {
+    let mut packs: Vec<Pack> = WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && entry.file_name() == PACK_MANIFEST)
+        .filter_map(|entry| match read_manifest(entry.path()) {
+            Ok(manifest) => Some(Pack {
+                manifest,
+                root: entry.path().parent().unwrap_or(dir).to_path_buf(),
+            }),
+            Err(e) => {
+                eprintln!("{} {}", "✗".red(), e);
+                None
+            }
+        })
+        .collect();
+    packs.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    packs
+}
+
+/// Prints the installed packs whose metadata contains a keyword.
+pub fn search_packs(template_dir: &Path, keyword: &str) -> Result<()> {
+    if !template_dir.exists() {
+        println!(
+            "{} Template directory does not exist: {}",
+            "✗".red(),
+            template_dir.display()
+        );
+        return Ok(());
+    }
+
+    let found: Vec<Pack> = collect_packs(template_dir)
+        .into_iter()
+        .filter(|pack| pack.matches(keyword))
+        .collect();
+
+    if found.is_empty() {
+        println!("{} No packs matched '{}'", "ℹ".blue(), keyword);
+        return Ok(());
+    }
+
+    println!("{} Found {} packs:", "Packs".green().bold(), found.len());
+    for pack in found {
+        println!("  {} {}", "→".cyan(), pack);
+    }
+    Ok(())
+}